@@ -0,0 +1,92 @@
+//! Benchmarks for commit fetching on large ranges.
+//!
+//! Builds a synthetic repository once (empty-tree commits, so setup stays
+//! fast) and measures the full-history path used by `init --from-history`.
+//!
+//! Run with `cargo bench --bench fetch_commits`.
+
+use criterion::{BenchmarkId, Criterion, Throughput, criterion_group, criterion_main};
+use git2::{Oid, Repository, Signature};
+
+use keryx::git::{fetch_commits, parse_commit_message, walk_commits};
+
+const COMMIT_COUNTS: &[usize] = &[1_000, 10_000];
+
+/// Create a repository with `count` linear commits. Returns (repo, root, head).
+fn build_repo(count: usize) -> (tempfile::TempDir, Repository, Oid, Oid) {
+    let dir = tempfile::tempdir().expect("Failed to create temp directory");
+    let repo = Repository::init(dir.path()).expect("Failed to init git repo");
+    let sig = Signature::now("Bench", "bench@example.com").expect("Failed to create signature");
+    let tree_id = repo
+        .treebuilder(None)
+        .and_then(|b| b.write())
+        .expect("Failed to write empty tree");
+
+    let mut root = None;
+    let mut parent: Option<Oid> = None;
+    for i in 0..count {
+        let message = match i % 4 {
+            0 => format!("feat(core): add feature {i}\n\nDetails about feature {i}."),
+            1 => format!("fix: resolve issue #{i}"),
+            2 => format!("chore: bump dependency {i}"),
+            _ => format!("refactor!: rework module {i}\n\nBREAKING CHANGE: api {i} removed"),
+        };
+        let oid = {
+            let tree = repo.find_tree(tree_id).expect("Failed to find tree");
+            let parents: Vec<_> = parent
+                .map(|p| repo.find_commit(p).expect("Failed to find parent"))
+                .into_iter()
+                .collect();
+            let parent_refs: Vec<_> = parents.iter().collect();
+            repo.commit(None, &sig, &sig, &message, &tree, &parent_refs)
+                .expect("Failed to create commit")
+        };
+        root.get_or_insert(oid);
+        parent = Some(oid);
+    }
+
+    let head = parent.expect("count must be non-zero");
+    (dir, repo, root.unwrap(), head)
+}
+
+fn bench_fetch_commits(c: &mut Criterion) {
+    let mut group = c.benchmark_group("fetch_commits");
+    group.sample_size(10);
+
+    for &count in COMMIT_COUNTS {
+        let (_dir, repo, root, head) = build_repo(count);
+        group.throughput(Throughput::Elements(count as u64));
+
+        group.bench_with_input(BenchmarkId::new("collect", count), &count, |b, _| {
+            b.iter(|| fetch_commits(&repo, root, head, false).unwrap().len())
+        });
+        group.bench_with_input(BenchmarkId::new("stream", count), &count, |b, _| {
+            b.iter(|| {
+                walk_commits(&repo, root, head, false)
+                    .unwrap()
+                    .filter_map(Result::ok)
+                    .filter(|c| c.breaking)
+                    .count()
+            })
+        });
+    }
+
+    group.finish();
+}
+
+fn bench_parse_commit_message(c: &mut Criterion) {
+    let long_body = format!("feat!: big change\n\n{}", "lorem ipsum ".repeat(2_000));
+    let mut group = c.benchmark_group("parse_commit_message");
+
+    group.bench_function("subject_only", |b| {
+        b.iter(|| parse_commit_message("fix(auth): resolve login bug"))
+    });
+    group.bench_function("long_body_breaking_mark", |b| {
+        b.iter(|| parse_commit_message(&long_body))
+    });
+
+    group.finish();
+}
+
+criterion_group!(benches, bench_fetch_commits, bench_parse_commit_message);
+criterion_main!(benches);
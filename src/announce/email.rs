@@ -0,0 +1,582 @@
+//! Release announcements by email.
+//!
+//! A changelog section is rendered as a `multipart/alternative` message with
+//! a plain-text and a simple HTML part, both quoted-printable encoded. The
+//! message is written to a file (`.eml`, openable by any mail client) or sent
+//! through the SMTP server configured in `keryx.toml`:
+//!
+//! ```toml
+//! [announce.email]
+//! from = "Releases <releases@example.com>"
+//! to = ["users@example.com"]
+//! subject = "{project} {version} is out"
+//! smtp_url = "smtps://smtp.example.com:465"
+//! smtp_user = "releases@example.com"
+//! ```
+//!
+//! The password is read from `KERYX_SMTP_PASSWORD`, never from the file.
+//! Sending shells out to `curl`, which speaks SMTP; TLS is always required.
+
+use std::io::Write;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::sync::LazyLock;
+
+use chrono::{DateTime, FixedOffset};
+use semver::Version;
+use tracing::warn;
+
+use crate::changelog::product::CONFIG_FILE;
+use crate::error::AnnounceError;
+
+/// Environment variable holding the SMTP password.
+pub const SMTP_PASSWORD_ENV_VAR: &str = "KERYX_SMTP_PASSWORD";
+
+/// Subject used when `keryx.toml` sets none.
+pub const DEFAULT_SUBJECT: &str = "{project} {version} released";
+
+/// Longest encoded line of a quoted-printable part (RFC 2045).
+const QP_LINE_LENGTH: usize = 76;
+
+/// Regex for Markdown links, on HTML-escaped text.
+static LINK_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"\[([^\]]+)\]\(([^)\s]+)\)")
+        .expect("Invalid link regex - this is a bug")
+});
+
+/// Regex for inline code spans.
+static CODE_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"`([^`]+)`").expect("Invalid code regex - this is a bug")
+});
+
+/// Regex for bold text.
+static BOLD_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"\*\*([^*]+)\*\*").expect("Invalid bold regex - this is a bug")
+});
+
+/// Regex for compare-link definitions at the end of a section.
+static LINK_DEFINITION_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"^\[[^\]]+\]:\s")
+        .expect("Invalid link definition regex - this is a bug")
+});
+
+/// The `[announce.email]` table of `keryx.toml`. The default is empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct EmailConfig {
+    pub from: Option<String>,
+    pub to: Vec<String>,
+    /// Subject template with `{project}` and `{version}`.
+    pub subject: Option<String>,
+    /// `smtps://host:465` or `smtp://host:587` (STARTTLS).
+    pub smtp_url: Option<String>,
+    pub smtp_user: Option<String>,
+}
+
+impl EmailConfig {
+    /// Read the `[announce.email]` table of `keryx.toml` in `root`.
+    ///
+    /// A missing file or table gives the default; an unreadable or invalid
+    /// file is logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid email settings in {}: {}. Ignoring them.",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let Some(email) = doc
+            .get("announce")
+            .and_then(|a| a.as_table_like())
+            .and_then(|a| a.get("email"))
+        else {
+            return Ok(Self::default());
+        };
+        let email = email
+            .as_table_like()
+            .ok_or("[announce.email] must be a table")?;
+
+        let text = |key: &str| -> Result<Option<String>, String> {
+            match email.get(key) {
+                None => Ok(None),
+                Some(item) => item
+                    .as_str()
+                    .map(|s| Some(s.trim().to_string()).filter(|s| !s.is_empty()))
+                    .ok_or_else(|| format!("announce.email.{} must be a string", key)),
+            }
+        };
+        let to = match email.get("to") {
+            None => Vec::new(),
+            Some(item) if item.is_str() => vec![item.as_str().unwrap_or_default().to_string()],
+            Some(item) => item
+                .as_array()
+                .ok_or("announce.email.to must be a string or an array of strings")?
+                .iter()
+                .map(|v| {
+                    v.as_str()
+                        .map(String::from)
+                        .ok_or("announce.email.to must only contain strings")
+                })
+                .collect::<Result<_, _>>()?,
+        };
+
+        Ok(Self {
+            from: text("from")?,
+            to,
+            subject: text("subject")?,
+            smtp_url: text("smtp_url")?,
+            smtp_user: text("smtp_user")?,
+        })
+    }
+}
+
+/// A release announcement, before MIME encoding.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseEmail {
+    pub subject: String,
+    pub text: String,
+    pub html: String,
+}
+
+/// Render the changelog `section` of `version` as an announcement.
+pub fn render_email(
+    section: &str,
+    project: &str,
+    version: &Version,
+    subject_template: Option<&str>,
+) -> ReleaseEmail {
+    let subject = subject_template
+        .unwrap_or(DEFAULT_SUBJECT)
+        .replace("{project}", project)
+        .replace("{version}", &version.to_string());
+    let blocks = parse_blocks(section);
+    ReleaseEmail {
+        text: to_plain_text(project, &blocks),
+        html: to_html(project, &blocks),
+        subject,
+    }
+}
+
+/// Lines of a changelog section, by kind.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Block {
+    Title(String),
+    Heading(String),
+    Bullet(String),
+    Paragraph(String),
+}
+
+fn parse_blocks(section: &str) -> Vec<Block> {
+    let mut blocks: Vec<Block> = Vec::new();
+    for line in section.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || LINK_DEFINITION_REGEX.is_match(trimmed) {
+            continue;
+        }
+        if let Some(title) = trimmed.strip_prefix("## ") {
+            blocks.push(Block::Title(title.replace(['[', ']'], "")));
+        } else if let Some(heading) = trimmed.strip_prefix("### ") {
+            blocks.push(Block::Heading(heading.to_string()));
+        } else if let Some(item) = trimmed.strip_prefix("- ").or(trimmed.strip_prefix("* ")) {
+            blocks.push(Block::Bullet(item.to_string()));
+        } else if line.starts_with([' ', '\t'])
+            && let Some(Block::Bullet(item)) = blocks.last_mut()
+        {
+            // Continuation of a wrapped bullet
+            item.push(' ');
+            item.push_str(trimmed);
+        } else {
+            blocks.push(Block::Paragraph(trimmed.to_string()));
+        }
+    }
+    blocks
+}
+
+/// Markdown inline formatting as plain text: links become `text (url)`.
+fn plain_inline(text: &str) -> String {
+    let text = LINK_REGEX.replace_all(text, "$1 ($2)");
+    let text = BOLD_REGEX.replace_all(&text, "$1");
+    CODE_REGEX.replace_all(&text, "$1").into_owned()
+}
+
+fn to_plain_text(project: &str, blocks: &[Block]) -> String {
+    let mut out = String::new();
+    for block in blocks {
+        match block {
+            Block::Title(title) => {
+                let title = format!("{} {}", project, plain_inline(title));
+                out.push_str(&format!(
+                    "{}\n{}\n\n",
+                    title,
+                    "=".repeat(title.chars().count())
+                ));
+            }
+            Block::Heading(heading) => {
+                let heading = plain_inline(heading);
+                if !out.is_empty() && !out.ends_with("\n\n") {
+                    out.push('\n');
+                }
+                out.push_str(&format!(
+                    "{}\n{}\n\n",
+                    heading,
+                    "-".repeat(heading.chars().count())
+                ));
+            }
+            Block::Bullet(item) => out.push_str(&format!("- {}\n", plain_inline(item))),
+            Block::Paragraph(text) => out.push_str(&format!("{}\n\n", plain_inline(text))),
+        }
+    }
+    format!("{}\n", out.trim_end())
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Markdown inline formatting as HTML, escaping everything else.
+fn html_inline(text: &str) -> String {
+    let text = escape_html(text);
+    let text = CODE_REGEX.replace_all(&text, "<code>$1</code>");
+    let text = LINK_REGEX.replace_all(&text, "<a href=\"$2\">$1</a>");
+    BOLD_REGEX
+        .replace_all(&text, "<strong>$1</strong>")
+        .into_owned()
+}
+
+fn to_html(project: &str, blocks: &[Block]) -> String {
+    let mut body = String::new();
+    let mut in_list = false;
+    for block in blocks {
+        let is_bullet = matches!(block, Block::Bullet(_));
+        if in_list && !is_bullet {
+            body.push_str("</ul>\n");
+        } else if !in_list && is_bullet {
+            body.push_str("<ul>\n");
+        }
+        in_list = is_bullet;
+        match block {
+            Block::Title(title) => body.push_str(&format!(
+                "<h1>{} {}</h1>\n",
+                escape_html(project),
+                html_inline(title)
+            )),
+            Block::Heading(heading) => {
+                body.push_str(&format!("<h2>{}</h2>\n", html_inline(heading)))
+            }
+            Block::Bullet(item) => body.push_str(&format!("<li>{}</li>\n", html_inline(item))),
+            Block::Paragraph(text) => body.push_str(&format!("<p>{}</p>\n", html_inline(text))),
+        }
+    }
+    if in_list {
+        body.push_str("</ul>\n");
+    }
+    format!(
+        "<!DOCTYPE html>\n<html>\n<head><meta charset=\"utf-8\"></head>\n<body style=\"font-family: sans-serif; line-height: 1.5;\">\n{}</body>\n</html>\n",
+        body
+    )
+}
+
+/// The bare address of `Name <address>` (or `address`).
+pub fn mailbox_address(mailbox: &str) -> &str {
+    mailbox
+        .rsplit_once('<')
+        .and_then(|(_, rest)| rest.split_once('>'))
+        .map(|(address, _)| address)
+        .unwrap_or(mailbox)
+        .trim()
+}
+
+/// Encode a header value as an RFC 2047 encoded word if it is not ASCII.
+fn encode_header(value: &str) -> String {
+    if value.is_ascii() {
+        return value.to_string();
+    }
+    let mut encoded = String::from("=?UTF-8?Q?");
+    for byte in value.bytes() {
+        match byte {
+            b' ' => encoded.push('_'),
+            b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9' | b'.' | b'-' | b'!' | b'*' | b'+' | b'/' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("={:02X}", byte)),
+        }
+    }
+    encoded.push_str("?=");
+    encoded
+}
+
+/// Quoted-printable encoding (RFC 2045) with CRLF line breaks.
+fn quoted_printable(text: &str) -> String {
+    let mut out = String::new();
+    for line in text.lines() {
+        let mut current = String::new();
+        let bytes = line.as_bytes();
+        for (index, &byte) in bytes.iter().enumerate() {
+            let last = index + 1 == bytes.len();
+            let encoded = match byte {
+                // Trailing whitespace would be stripped in transit
+                b' ' | b'\t' if last => format!("={:02X}", byte),
+                b'=' => "=3D".to_string(),
+                b' ' | b'\t' | 33..=126 => (byte as char).to_string(),
+                _ => format!("={:02X}", byte),
+            };
+            if current.len() + encoded.len() > QP_LINE_LENGTH - 1 {
+                out.push_str(&current);
+                out.push_str("=\r\n");
+                current.clear();
+            }
+            current.push_str(&encoded);
+        }
+        out.push_str(&current);
+        out.push_str("\r\n");
+    }
+    out
+}
+
+/// The complete MIME message, with CRLF line endings.
+pub fn mime_message(
+    email: &ReleaseEmail,
+    from: &str,
+    to: &[String],
+    date: DateTime<FixedOffset>,
+) -> String {
+    let boundary = format!("=_keryx_{:x}", date.timestamp());
+    let part = |content_type: &str, body: &str| {
+        format!(
+            "--{}\r\nContent-Type: {}; charset=utf-8\r\nContent-Transfer-Encoding: quoted-printable\r\n\r\n{}",
+            boundary,
+            content_type,
+            quoted_printable(body)
+        )
+    };
+    format!(
+        "From: {}\r\nTo: {}\r\nSubject: {}\r\nDate: {}\r\nMIME-Version: 1.0\r\nContent-Type: multipart/alternative; boundary=\"{}\"\r\n\r\n{}{}--{}--\r\n",
+        from,
+        to.join(", "),
+        encode_header(&email.subject),
+        date.to_rfc2822(),
+        boundary,
+        part("text/plain", &email.text),
+        part("text/html", &email.html),
+        boundary
+    )
+}
+
+/// Write `message` to `path` (an `.eml` file).
+pub fn write_message(path: &Path, message: &str) -> Result<(), AnnounceError> {
+    crate::atomic::write_atomic(path, message).map_err(|source| AnnounceError::WriteFailed {
+        path: path.to_path_buf(),
+        source,
+    })
+}
+
+/// Send `message` with `curl` through the configured SMTP server.
+pub fn send_message(config: &EmailConfig, message: &str) -> Result<(), AnnounceError> {
+    let url = config
+        .smtp_url
+        .as_deref()
+        .ok_or(AnnounceError::MissingSetting("smtp_url"))?;
+    let from = config
+        .from
+        .as_deref()
+        .ok_or(AnnounceError::MissingSetting("from"))?;
+    if config.to.is_empty() {
+        return Err(AnnounceError::MissingSetting("to"));
+    }
+    let curl = which::which("curl").map_err(|_| AnnounceError::CurlNotFound)?;
+
+    let mut file = tempfile::NamedTempFile::new().map_err(|source| AnnounceError::WriteFailed {
+        path: std::env::temp_dir(),
+        source,
+    })?;
+    file.write_all(message.as_bytes())
+        .map_err(|source| AnnounceError::WriteFailed {
+            path: file.path().to_path_buf(),
+            source,
+        })?;
+
+    let mut cmd = Command::new(curl);
+    cmd.args(["--silent", "--show-error", "--ssl-reqd", "--url", url])
+        .args(["--mail-from", mailbox_address(from)]);
+    for recipient in &config.to {
+        cmd.args(["--mail-rcpt", mailbox_address(recipient)]);
+    }
+    cmd.arg("--upload-file").arg(file.path());
+
+    // Credentials go through stdin so they never show up in the process list
+    let credentials = config.smtp_user.as_deref().map(|user| {
+        let password = std::env::var(SMTP_PASSWORD_ENV_VAR).unwrap_or_default();
+        format!(
+            "user = \"{}:{}\"\n",
+            curl_escape(user),
+            curl_escape(&password)
+        )
+    });
+    if credentials.is_some() {
+        cmd.args(["--config", "-"]).stdin(Stdio::piped());
+    }
+
+    let mut child = cmd
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .map_err(|e| AnnounceError::SendFailed(format!("failed to start curl: {}", e)))?;
+    if let Some(credentials) = credentials
+        && let Some(mut stdin) = child.stdin.take()
+    {
+        stdin
+            .write_all(credentials.as_bytes())
+            .map_err(|e| AnnounceError::SendFailed(format!("failed to pass credentials: {}", e)))?;
+    }
+    let output = child
+        .wait_with_output()
+        .map_err(|e| AnnounceError::SendFailed(e.to_string()))?;
+    if output.status.success() {
+        Ok(())
+    } else {
+        Err(AnnounceError::SendFailed(
+            String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        ))
+    }
+}
+
+/// Escape a value for a double-quoted curl config string.
+fn curl_escape(value: &str) -> String {
+    value.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECTION: &str = "## [1.2.0] - 2024-03-01\n\nFaster syncing for **large** teams.\n\n### Added\n\n- Dark mode in the [settings](https://example.com/docs?a=1&b=2) (#12)\n- `keryx announce` for\n  email announcements\n\n### Fixed\n\n- Crash on <empty> input\n\n[1.2.0]: https://github.com/o/r/compare/v1.1.0...v1.2.0\n";
+
+    fn email() -> ReleaseEmail {
+        render_email(SECTION, "keryx", &Version::new(1, 2, 0), None)
+    }
+
+    #[test]
+    fn test_plain_text() {
+        let text = email().text;
+        assert!(text.starts_with("keryx 1.2.0 - 2024-03-01\n========================\n\n"));
+        assert!(text.contains("Faster syncing for large teams.\n"));
+        assert!(text.contains(
+            "Added\n-----\n\n- Dark mode in the settings (https://example.com/docs?a=1&b=2) (#12)\n"
+        ));
+        assert!(text.contains("- keryx announce for email announcements\n"));
+        assert!(text.contains("\n\nFixed\n-----\n\n- Crash on <empty> input\n"));
+        assert!(!text.contains("compare"));
+    }
+
+    #[test]
+    fn test_html() {
+        let html = email().html;
+        assert!(html.contains("<h1>keryx 1.2.0 - 2024-03-01</h1>"));
+        assert!(html.contains("<p>Faster syncing for <strong>large</strong> teams.</p>"));
+        assert!(html.contains(
+            "<li>Dark mode in the <a href=\"https://example.com/docs?a=1&amp;b=2\">settings</a> (#12)</li>"
+        ));
+        assert!(html.contains("<li><code>keryx announce</code> for email announcements</li>"));
+        assert!(html.contains("<li>Crash on &lt;empty&gt; input</li>\n</ul>"));
+        assert_eq!(html.matches("<ul>").count(), 2);
+    }
+
+    #[test]
+    fn test_subject_template() {
+        assert_eq!(email().subject, "keryx 1.2.0 released");
+        let custom = render_email(
+            SECTION,
+            "Widget",
+            &Version::new(1, 2, 0),
+            Some("{project} v{version} is out"),
+        );
+        assert_eq!(custom.subject, "Widget v1.2.0 is out");
+    }
+
+    #[test]
+    fn test_mime_message() {
+        let date = DateTime::parse_from_rfc3339("2024-03-01T12:00:00+00:00").unwrap();
+        let message = mime_message(
+            &email(),
+            "Releases <releases@example.com>",
+            &["users@example.com".to_string()],
+            date,
+        );
+        assert!(message.starts_with("From: Releases <releases@example.com>\r\nTo: users@example.com\r\nSubject: keryx 1.2.0 released\r\n"));
+        assert!(message.contains("Content-Type: multipart/alternative; boundary="));
+        assert!(message.contains("Content-Type: text/plain; charset=utf-8\r\n"));
+        assert!(message.contains("Content-Type: text/html; charset=utf-8\r\n"));
+        assert!(message.contains("href=3D\"https://example.com"));
+        assert!(message.trim_end().ends_with("--"));
+        // SMTP limits lines to 998 characters
+        assert!(message.split("\r\n").all(|line| line.len() <= 998));
+    }
+
+    #[test]
+    fn test_quoted_printable() {
+        assert_eq!(quoted_printable("a=b \nCafé"), "a=3Db=20\r\nCaf=C3=A9\r\n");
+        let long = "x".repeat(200);
+        let encoded = quoted_printable(&long);
+        assert!(
+            encoded
+                .split("\r\n")
+                .all(|line| line.len() <= QP_LINE_LENGTH)
+        );
+        assert_eq!(encoded.replace("=\r\n", "").trim_end(), long);
+    }
+
+    #[test]
+    fn test_encode_header() {
+        assert_eq!(encode_header("keryx 1.2.0"), "keryx 1.2.0");
+        assert_eq!(encode_header("Café 1.0"), "=?UTF-8?Q?Caf=C3=A9_1.0?=");
+    }
+
+    #[test]
+    fn test_mailbox_address() {
+        assert_eq!(mailbox_address("Releases <r@example.com>"), "r@example.com");
+        assert_eq!(mailbox_address(" r@example.com "), "r@example.com");
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = EmailConfig::parse(
+            "[announce.email]\nfrom = \"r@example.com\"\nto = [\"a@example.com\", \"b@example.com\"]\nsmtp_url = \"smtps://smtp.example.com\"\n",
+        )
+        .unwrap();
+        assert_eq!(config.to.len(), 2);
+        assert_eq!(config.smtp_url.as_deref(), Some("smtps://smtp.example.com"));
+        assert_eq!(config.subject, None);
+
+        let single = EmailConfig::parse("[announce.email]\nto = \"a@example.com\"\n").unwrap();
+        assert_eq!(single.to, vec!["a@example.com"]);
+        assert!(EmailConfig::parse("[announce.email]\nto = 3\n").is_err());
+        assert_eq!(EmailConfig::parse("").unwrap(), EmailConfig::default());
+    }
+
+    #[test]
+    fn test_send_needs_settings() {
+        assert!(matches!(
+            send_message(&EmailConfig::default(), "message"),
+            Err(AnnounceError::MissingSetting("smtp_url"))
+        ));
+    }
+}
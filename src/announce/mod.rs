@@ -0,0 +1,5 @@
+//! `keryx announce`: render release notes for channels outside the changelog.
+
+pub mod email;
+
+pub use email::{EmailConfig, ReleaseEmail, mime_message, render_email, send_message};
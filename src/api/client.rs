@@ -0,0 +1,264 @@
+//! HTTP requests to the Anthropic Messages and OpenAI Chat Completions APIs.
+
+use std::env;
+use std::time::Duration;
+
+use reqwest::Client;
+use serde_json::{Value, json};
+use tracing::{trace, warn};
+
+use crate::error::ApiError;
+
+use super::ApiProvider;
+
+/// Default timeout for one API request (5 minutes).
+const DEFAULT_TIMEOUT_SECS: u64 = 300;
+
+/// Environment variable to override the default timeout.
+const TIMEOUT_ENV_VAR: &str = "KERYX_API_TIMEOUT";
+
+/// Anthropic API version sent with every request.
+const ANTHROPIC_VERSION: &str = "2023-06-01";
+
+/// Output token limit of an Anthropic request, which the API requires.
+const ANTHROPIC_MAX_TOKENS: u32 = 8192;
+
+/// Get the configured timeout duration.
+///
+/// Reads from KERYX_API_TIMEOUT environment variable if set, otherwise uses
+/// the default of 300 seconds.
+fn get_timeout() -> Duration {
+    match env::var(TIMEOUT_ENV_VAR) {
+        Ok(v) if !v.is_empty() => match v.parse::<u64>() {
+            Ok(secs) => Duration::from_secs(secs),
+            Err(_) => {
+                warn!(
+                    "Invalid {} value '{}', using default {}s",
+                    TIMEOUT_ENV_VAR, v, DEFAULT_TIMEOUT_SECS
+                );
+                Duration::from_secs(DEFAULT_TIMEOUT_SECS)
+            }
+        },
+        _ => Duration::from_secs(DEFAULT_TIMEOUT_SECS),
+    }
+}
+
+/// A non-empty environment variable.
+fn env_value(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Client for one vendor's API, with its key, model and endpoint.
+pub struct ApiClient {
+    api: ApiProvider,
+    client: Client,
+    base_url: String,
+    api_key: String,
+    model: String,
+    timeout_secs: u64,
+}
+
+impl ApiClient {
+    pub fn new(api: ApiProvider, api_key: &str) -> Self {
+        let timeout = get_timeout();
+        Self {
+            api,
+            client: Client::builder()
+                .timeout(timeout)
+                .build()
+                .unwrap_or_default(),
+            base_url: api.default_base_url().to_string(),
+            api_key: api_key.to_string(),
+            model: api.default_model().to_string(),
+            timeout_secs: timeout.as_secs(),
+        }
+    }
+
+    /// A client configured from the environment: the key from
+    /// [`ApiProvider::key_env`], which must be set, and the model and
+    /// endpoint overrides if given.
+    pub fn from_env(api: ApiProvider) -> Result<Self, ApiError> {
+        let api_key = env_value(api.key_env()).ok_or(ApiError::MissingKey(api.key_env()))?;
+        let mut client = Self::new(api, &api_key);
+        if let Some(model) = env_value(api.model_env()) {
+            client = client.with_model(&model);
+        }
+        if let Some(base_url) = env_value(api.base_url_env()) {
+            client = client.with_base_url(&base_url);
+        }
+        Ok(client)
+    }
+
+    /// Use another model.
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    /// Send requests to another server (a proxy, or a mock in tests).
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    /// Send `prompt` as a single user message and return the text of the
+    /// reply.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub async fn complete(&self, prompt: &str) -> Result<String, ApiError> {
+        trace!(
+            "Calling {} ({}) with <{} byte prompt>",
+            self.api,
+            self.model,
+            prompt.len()
+        );
+        let messages = json!([{ "role": "user", "content": prompt }]);
+        let request = match self.api {
+            ApiProvider::Anthropic => self
+                .client
+                .post(format!("{}/v1/messages", self.base_url))
+                .header("x-api-key", &self.api_key)
+                .header("anthropic-version", ANTHROPIC_VERSION)
+                .json(&json!({
+                    "model": self.model,
+                    "max_tokens": ANTHROPIC_MAX_TOKENS,
+                    "messages": messages,
+                })),
+            ApiProvider::OpenAi => self
+                .client
+                .post(format!("{}/chat/completions", self.base_url))
+                .bearer_auth(&self.api_key)
+                .json(&json!({ "model": self.model, "messages": messages })),
+        };
+
+        let response = request.send().await.map_err(|e| self.request_error(e))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| self.request_error(e))?;
+        let json: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = json
+                .pointer("/error/message")
+                .and_then(|m| m.as_str())
+                .map(String::from)
+                .unwrap_or(body);
+            return Err(ApiError::Status {
+                status: status.as_u16(),
+                message,
+            });
+        }
+
+        let text = match self.api {
+            ApiProvider::Anthropic => {
+                json.get("content")
+                    .and_then(|c| c.as_array())
+                    .map(|blocks| {
+                        blocks
+                            .iter()
+                            .filter(|b| b["type"] == "text")
+                            .filter_map(|b| b["text"].as_str())
+                            .collect::<String>()
+                    })
+            }
+            ApiProvider::OpenAi => json
+                .pointer("/choices/0/message/content")
+                .and_then(|c| c.as_str())
+                .map(String::from),
+        };
+        text.filter(|t| !t.trim().is_empty())
+            .ok_or(ApiError::EmptyResponse)
+    }
+
+    fn request_error(&self, error: reqwest::Error) -> ApiError {
+        if error.is_timeout() {
+            ApiError::Timeout(self.timeout_secs)
+        } else {
+            ApiError::RequestFailed(error.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[tokio::test]
+    async fn test_anthropic_messages_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/v1/messages"))
+            .and(header("x-api-key", "sk-ant"))
+            .and(header("anthropic-version", ANTHROPIC_VERSION))
+            .and(body_partial_json(json!({
+                "model": "claude-test",
+                "messages": [{ "role": "user", "content": "Write notes" }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "content": [
+                    { "type": "text", "text": "{\"entries\": " },
+                    { "type": "text", "text": "[]}" }
+                ]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ApiClient::new(ApiProvider::Anthropic, "sk-ant")
+            .with_model("claude-test")
+            .with_base_url(&server.uri());
+        assert_eq!(
+            client.complete("Write notes").await.unwrap(),
+            "{\"entries\": []}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_openai_chat_completions_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("authorization", "Bearer sk-oai"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "minor" } }]
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = ApiClient::new(ApiProvider::OpenAi, "sk-oai").with_base_url(&server.uri());
+        assert_eq!(client.complete("Bump?").await.unwrap(), "minor");
+    }
+
+    #[tokio::test]
+    async fn test_error_status_reports_api_message() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(401).set_body_json(json!({
+                "error": { "type": "authentication_error", "message": "invalid x-api-key" }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = ApiClient::new(ApiProvider::Anthropic, "bad").with_base_url(&server.uri());
+        match client.complete("hi").await {
+            Err(ApiError::Status { status, message }) => {
+                assert_eq!(status, 401);
+                assert_eq!(message, "invalid x-api-key");
+            }
+            other => panic!("expected status error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_from_env_requires_key() {
+        temp_env::with_var_unset("OPENAI_API_KEY", || {
+            assert!(matches!(
+                ApiClient::from_env(ApiProvider::OpenAi),
+                Err(ApiError::MissingKey("OPENAI_API_KEY"))
+            ));
+        });
+    }
+}
@@ -0,0 +1,81 @@
+//! Anthropic and OpenAI HTTP API integration.
+//!
+//! These providers call the vendors' APIs directly instead of a CLI, so
+//! they work where no CLI can be installed or logged in (CI). The key is
+//! read from `ANTHROPIC_API_KEY` or `OPENAI_API_KEY`, the model from
+//! `ANTHROPIC_MODEL` or `OPENAI_MODEL`, and `ANTHROPIC_BASE_URL` or
+//! `OPENAI_BASE_URL` point them at a proxy or compatible server.
+
+pub mod client;
+pub mod retry;
+
+use std::fmt;
+
+use crate::llm::Provider;
+
+pub use client::ApiClient;
+pub use retry::{ApiExecutor, generate_raw_with_retry, generate_with_retry};
+
+/// An LLM vendor keryx can call over HTTP.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ApiProvider {
+    /// The Messages API.
+    Anthropic,
+    /// The Chat Completions API.
+    OpenAi,
+}
+
+impl ApiProvider {
+    /// The routing provider for this API.
+    pub fn provider(&self) -> Provider {
+        match self {
+            ApiProvider::Anthropic => Provider::AnthropicApi,
+            ApiProvider::OpenAi => Provider::OpenAiApi,
+        }
+    }
+
+    /// Environment variable holding the API key.
+    pub fn key_env(&self) -> &'static str {
+        match self {
+            ApiProvider::Anthropic => "ANTHROPIC_API_KEY",
+            ApiProvider::OpenAi => "OPENAI_API_KEY",
+        }
+    }
+
+    /// Environment variable overriding [`Self::default_model`].
+    pub fn model_env(&self) -> &'static str {
+        match self {
+            ApiProvider::Anthropic => "ANTHROPIC_MODEL",
+            ApiProvider::OpenAi => "OPENAI_MODEL",
+        }
+    }
+
+    /// Model used when [`Self::model_env`] is not set.
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            ApiProvider::Anthropic => "claude-sonnet-4-5",
+            ApiProvider::OpenAi => "gpt-5",
+        }
+    }
+
+    /// Environment variable overriding [`Self::default_base_url`].
+    pub fn base_url_env(&self) -> &'static str {
+        match self {
+            ApiProvider::Anthropic => "ANTHROPIC_BASE_URL",
+            ApiProvider::OpenAi => "OPENAI_BASE_URL",
+        }
+    }
+
+    pub fn default_base_url(&self) -> &'static str {
+        match self {
+            ApiProvider::Anthropic => "https://api.anthropic.com",
+            ApiProvider::OpenAi => "https://api.openai.com/v1",
+        }
+    }
+}
+
+impl fmt::Display for ApiProvider {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.provider().as_str())
+    }
+}
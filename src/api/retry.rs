@@ -0,0 +1,129 @@
+//! Exponential backoff retry logic for the HTTP API providers.
+
+use async_trait::async_trait;
+
+use crate::changelog::ChangelogOutput;
+use crate::error::ApiError;
+use crate::llm::extract_json;
+use crate::llm::retry::retry_with_backoff;
+
+use super::{ApiClient, ApiProvider};
+
+/// Trait for sending a prompt to an LLM API.
+///
+/// This abstraction allows mocking the HTTP requests in tests.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ApiExecutor: Send + Sync {
+    /// Send the prompt and return the text of the reply.
+    async fn run(&self, prompt: &str) -> Result<String, ApiError>;
+}
+
+#[async_trait]
+impl ApiExecutor for ApiClient {
+    async fn run(&self, prompt: &str) -> Result<String, ApiError> {
+        self.complete(prompt).await
+    }
+}
+
+/// Generate changelog entries with retry logic.
+///
+/// Fails at once if the API key is not set; otherwise makes up to 3
+/// attempts with exponential backoff on failure.
+pub async fn generate_with_retry(
+    api: ApiProvider,
+    prompt: &str,
+) -> Result<ChangelogOutput, ApiError> {
+    let client = ApiClient::from_env(api)?;
+    generate_with_retry_impl(prompt, &client).await
+}
+
+/// Generate a raw string response with retry logic (no ChangelogOutput parsing).
+pub async fn generate_raw_with_retry(api: ApiProvider, prompt: &str) -> Result<String, ApiError> {
+    let client = ApiClient::from_env(api)?;
+    generate_raw_with_retry_impl(prompt, &client).await
+}
+
+/// Internal raw retry implementation that accepts any executor (for testing).
+pub(crate) async fn generate_raw_with_retry_impl<E: ApiExecutor>(
+    prompt: &str,
+    executor: &E,
+) -> Result<String, ApiError> {
+    retry_with_backoff(
+        || async { executor.run(prompt).await },
+        |e| ApiError::RetriesExhausted(Box::new(e)),
+    )
+    .await
+}
+
+/// Internal implementation that accepts any executor (for testing).
+pub(crate) async fn generate_with_retry_impl<E: ApiExecutor>(
+    prompt: &str,
+    executor: &E,
+) -> Result<ChangelogOutput, ApiError> {
+    retry_with_backoff(
+        || async { try_generate(prompt, executor).await },
+        |e| ApiError::RetriesExhausted(Box::new(e)),
+    )
+    .await
+}
+
+/// Single attempt to generate changelog.
+async fn try_generate<E: ApiExecutor>(
+    prompt: &str,
+    executor: &E,
+) -> Result<ChangelogOutput, ApiError> {
+    let response = executor.run(prompt).await?;
+    parse_api_response(&response)
+}
+
+/// Parse the reply text into ChangelogOutput.
+fn parse_api_response(response: &str) -> Result<ChangelogOutput, ApiError> {
+    if let Ok(output) = serde_json::from_str::<ChangelogOutput>(response) {
+        return Ok(output);
+    }
+
+    let json_str = extract_json(response);
+    serde_json::from_str(&json_str).map_err(|e| {
+        ApiError::InvalidJson(format!("Failed to parse: {}. Content: {}", e, response))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that a reply with JSON in a code fence is parsed.
+    #[tokio::test(start_paused = true)]
+    async fn test_api_structured_parses_fenced_json() {
+        let mut mock = MockApiExecutor::new();
+        mock.expect_run().times(1).returning(|_| {
+            Ok("Here are the notes:\n```json\n{\"entries\": [{\"category\": \"Fixed\", \"description\": \"Crash on start\"}]}\n```".to_string())
+        });
+
+        let output = generate_with_retry_impl("test prompt", &mock)
+            .await
+            .unwrap();
+        assert_eq!(output.entries.len(), 1);
+        assert_eq!(output.entries[0].description, "Crash on start");
+    }
+
+    /// Test that failed requests are retried and the last error kept.
+    #[tokio::test(start_paused = true)]
+    async fn test_api_raw_retry_exhaustion() {
+        let mut mock = MockApiExecutor::new();
+        mock.expect_run().times(3).returning(|_| {
+            Err(ApiError::Status {
+                status: 529,
+                message: "Overloaded".to_string(),
+            })
+        });
+
+        match generate_raw_with_retry_impl("test prompt", &mock).await {
+            Err(ApiError::RetriesExhausted(inner)) => {
+                assert!(matches!(*inner, ApiError::Status { status: 529, .. }));
+            }
+            other => panic!("Expected RetriesExhausted error, got {:?}", other),
+        }
+    }
+}
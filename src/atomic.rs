@@ -0,0 +1,85 @@
+//! Crash-safe file writes.
+//!
+//! Files keryx changes in a repository (changelogs, version files, manifests,
+//! state under `.keryx`) are written to a temp file in the same directory,
+//! synced, and renamed over the target. A crash or a concurrent reader sees
+//! either the old or the new content, never a truncated file. The temp file
+//! takes the target's permissions, so an executable script or a read-only
+//! config stays that way.
+
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+
+use tempfile::NamedTempFile;
+
+/// Permissions of a file keryx creates.
+#[cfg(unix)]
+const NEW_FILE_MODE: u32 = 0o644;
+
+/// A temp file in the directory of `path` (created if missing), with the
+/// permissions of `path` if it exists, ready to be persisted onto it.
+pub fn temp_file_beside(path: &Path) -> io::Result<NamedTempFile> {
+    let parent = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => {
+            fs::create_dir_all(parent)?;
+            parent
+        }
+        _ => Path::new("."),
+    };
+    let temp_file = NamedTempFile::new_in(parent)?;
+    match fs::metadata(path) {
+        Ok(metadata) => temp_file
+            .as_file()
+            .set_permissions(metadata.permissions())?,
+        #[cfg(unix)]
+        Err(_) => {
+            use std::os::unix::fs::PermissionsExt;
+            temp_file
+                .as_file()
+                .set_permissions(fs::Permissions::from_mode(NEW_FILE_MODE))?
+        }
+        #[cfg(not(unix))]
+        Err(_) => {}
+    }
+    Ok(temp_file)
+}
+
+/// Replace `path` with `contents` through a synced temp file and a rename.
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> io::Result<()> {
+    let mut temp_file = temp_file_beside(path)?;
+    temp_file.write_all(contents.as_ref())?;
+    temp_file.as_file().sync_all()?;
+    temp_file.persist(path).map_err(|e| e.error)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_write_atomic_replaces_and_keeps_permissions() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join("nested/release.sh");
+        write_atomic(&path, "echo 1\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "echo 1\n");
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755)).unwrap();
+        }
+        write_atomic(&path, "echo 2\n").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "echo 2\n");
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(&path).unwrap().permissions().mode();
+            assert_eq!(mode & 0o777, 0o755);
+        }
+        // No temp file is left behind
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+}
@@ -0,0 +1,349 @@
+//! Category headings other tools write, mapped onto Keep a Changelog.
+//!
+//! Changelogs started with other tools group entries under headings such as
+//! "Features" or "Bug Fixes". keryx reads those as the standard category
+//! they stand for, writes new sections with the headings the file already
+//! uses, and `keryx fmt` rewrites them to the standard names. Aliases beyond
+//! the built-in ones are set in the `[category_aliases]` table of
+//! `keryx.toml`, heading to category:
+//!
+//! ```toml
+//! [category_aliases]
+//! "Enhancements" = "changed"
+//! "Docs" = "changed"
+//! ```
+
+use std::io::BufRead;
+use std::path::Path;
+
+use git2::Repository;
+use tracing::warn;
+
+use crate::error::ChangelogError;
+use crate::git::read_head_file;
+
+use super::ChangelogCategory;
+use super::parser::{find_version_token, read_changelog, scan_frozen_versions};
+use super::product::CONFIG_FILE;
+use super::writer::atomic_write;
+
+/// Headings of conventional-changelog, release-please and git-cliff
+/// changelogs.
+const BUILT_IN: [(&str, ChangelogCategory); 10] = [
+    ("Features", ChangelogCategory::Added),
+    ("New Features", ChangelogCategory::Added),
+    ("Bug Fixes", ChangelogCategory::Fixed),
+    ("Bugfixes", ChangelogCategory::Fixed),
+    ("Fixes", ChangelogCategory::Fixed),
+    ("Performance", ChangelogCategory::Changed),
+    ("Performance Improvements", ChangelogCategory::Changed),
+    ("Improvements", ChangelogCategory::Changed),
+    ("Deprecations", ChangelogCategory::Deprecated),
+    ("Security Fixes", ChangelogCategory::Security),
+];
+
+/// Heading aliases of the changelog categories.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CategoryAliases {
+    /// Alias and category, configured ones first.
+    aliases: Vec<(String, ChangelogCategory)>,
+}
+
+impl Default for CategoryAliases {
+    /// The built-in aliases.
+    fn default() -> Self {
+        Self {
+            aliases: BUILT_IN
+                .iter()
+                .map(|(alias, category)| (alias.to_string(), category.clone()))
+                .collect(),
+        }
+    }
+}
+
+impl CategoryAliases {
+    /// The built-in aliases plus the `[category_aliases]` table of
+    /// `keryx.toml` in `root`.
+    ///
+    /// A missing file or table gives the built-in aliases; an unreadable or
+    /// invalid file is logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid category aliases in {}: {}. Ignoring them.",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    /// Like [`Self::load`] for the root of `repo`; a bare repository's
+    /// `keryx.toml` is read from the tree of `HEAD`.
+    pub fn for_repository(repo: &Repository) -> Self {
+        if let Some(root) = repo.workdir() {
+            return Self::load(root);
+        }
+        let Some(content) = read_head_file(repo, Path::new(CONFIG_FILE)) else {
+            return Self::default();
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid category aliases in {} in HEAD: {}. Ignoring them.",
+                CONFIG_FILE, e
+            );
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let mut aliases = Self::default();
+        let Some(table) = doc.get("category_aliases") else {
+            return Ok(aliases);
+        };
+        let table = table
+            .as_table_like()
+            .ok_or("[category_aliases] must be a table")?;
+        let mut configured = Vec::new();
+        for (alias, category) in table.iter() {
+            let category = category
+                .as_str()
+                .ok_or_else(|| format!("category_aliases.{} must be a string", alias))?
+                .trim()
+                .parse::<ChangelogCategory>()
+                .map_err(|e| format!("category_aliases.{}: {}", alias, e))?;
+            if !alias.trim().is_empty() {
+                configured.push((alias.trim().to_string(), category));
+            }
+        }
+        configured.append(&mut aliases.aliases);
+        aliases.aliases = configured;
+        Ok(aliases)
+    }
+
+    /// The category of a `### ` heading: a standard name or an alias,
+    /// compared case-insensitively.
+    pub fn resolve(&self, heading: &str) -> Option<ChangelogCategory> {
+        let heading = heading.trim();
+        heading.parse().ok().or_else(|| {
+            self.aliases
+                .iter()
+                .find(|(alias, _)| alias.eq_ignore_ascii_case(heading))
+                .map(|(_, category)| category.clone())
+        })
+    }
+
+    /// The category an alias heading stands for; `None` for standard names
+    /// and unknown headings.
+    fn alias_category(&self, heading: &str) -> Option<ChangelogCategory> {
+        match heading.trim().parse::<ChangelogCategory>() {
+            Ok(_) => None,
+            Err(_) => self.resolve(heading),
+        }
+    }
+
+    /// The alias headings `content` uses, the newest for each category.
+    pub fn headings_in(&self, content: &str) -> FileHeadings {
+        let mut headings = FileHeadings::default();
+        for line in content.lines() {
+            headings.add(self, line);
+        }
+        headings
+    }
+
+    /// Like [`Self::headings_in`] for the file at `path`, read a line at a
+    /// time. A missing or unreadable file uses no aliases.
+    pub fn headings_in_file(&self, path: &Path) -> FileHeadings {
+        let mut headings = FileHeadings::default();
+        let Ok(file) = std::fs::File::open(path) else {
+            return headings;
+        };
+        for line in std::io::BufReader::new(file).lines() {
+            let Ok(line) = line else {
+                break;
+            };
+            headings.add(self, &line);
+        }
+        headings
+    }
+
+    /// `content` with every alias heading replaced by the standard name of
+    /// its category, and how many headings were replaced. Frozen sections
+    /// are left as they are.
+    pub fn normalize(&self, content: &str) -> (String, usize) {
+        let frozen = scan_frozen_versions(content);
+        let mut in_frozen = false;
+        let mut replaced = 0;
+        let mut normalized = String::with_capacity(content.len());
+        for line in content.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\r', '\n']);
+            if let Some(title) = body.strip_prefix("## ") {
+                in_frozen = find_version_token(title).is_some_and(|v| frozen.contains(&v));
+            }
+            match body
+                .strip_prefix("### ")
+                .filter(|_| !in_frozen)
+                .and_then(|heading| self.alias_category(heading))
+            {
+                Some(category) => {
+                    replaced += 1;
+                    normalized.push_str("### ");
+                    normalized.push_str(category.as_str());
+                    normalized.push_str(&line[body.len()..]);
+                }
+                None => normalized.push_str(line),
+            }
+        }
+        (normalized, replaced)
+    }
+}
+
+/// Rewrite the alias headings of the changelog at `path` to the standard
+/// names (`keryx fmt`); with `dry_run` the file is left as is. Returns how
+/// many headings were (or would be) rewritten; a missing file has none.
+pub fn normalize_changelog(
+    path: &Path,
+    aliases: &CategoryAliases,
+    dry_run: bool,
+) -> Result<usize, ChangelogError> {
+    let Some(changelog) = read_changelog(path)? else {
+        return Ok(0);
+    };
+    let (normalized, replaced) = aliases.normalize(&changelog.raw_content);
+    if replaced > 0 && !dry_run {
+        atomic_write(path, &normalized)?;
+    }
+    Ok(replaced)
+}
+
+/// The heading a changelog uses for each category it writes under an alias.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileHeadings(Vec<(ChangelogCategory, String)>);
+
+impl FileHeadings {
+    fn add(&mut self, aliases: &CategoryAliases, line: &str) {
+        if let Some(heading) = line.strip_prefix("### ")
+            && let Some(category) = aliases.alias_category(heading)
+            && !self.0.iter().any(|(c, _)| *c == category)
+        {
+            self.0.push((category, heading.trim().to_string()));
+        }
+    }
+
+    /// Heading for `category`: the file's alias, else the standard name.
+    pub fn heading(&self, category: &ChangelogCategory) -> &str {
+        self.0
+            .iter()
+            .find(|(c, _)| c == category)
+            .map_or(category.as_str(), |(_, heading)| heading)
+    }
+
+    /// `section` (rendered with standard headings) with the file's headings.
+    pub fn relabel(&self, section: &str) -> String {
+        if self.0.is_empty() {
+            return section.to_string();
+        }
+        let mut relabeled = String::with_capacity(section.len());
+        for line in section.split_inclusive('\n') {
+            let body = line.trim_end_matches(['\r', '\n']);
+            match body
+                .strip_prefix("### ")
+                .and_then(|heading| heading.parse::<ChangelogCategory>().ok())
+            {
+                Some(category) => {
+                    relabeled.push_str("### ");
+                    relabeled.push_str(self.heading(&category));
+                    relabeled.push_str(&line[body.len()..]);
+                }
+                None => relabeled.push_str(line),
+            }
+        }
+        relabeled
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONVENTIONAL: &str = "# Changelog\n\n## [1.1.0] - 2024-02-01\n\n### Features\n\n- Dark mode\n\n### Bug Fixes\n\n- Crash\n\n## [1.0.0] - 2024-01-01\n\n### Added\n\n- First\n";
+
+    #[test]
+    fn test_resolve_standard_and_aliases() {
+        let aliases = CategoryAliases::parse(
+            "[category_aliases]\n\"Docs\" = \"changed\"\n\"Fixes\" = \"security\"\n",
+        )
+        .unwrap();
+        assert_eq!(aliases.resolve("added"), Some(ChangelogCategory::Added));
+        assert_eq!(aliases.resolve("bug fixes"), Some(ChangelogCategory::Fixed));
+        assert_eq!(aliases.resolve("Docs"), Some(ChangelogCategory::Changed));
+        // Configured aliases win over the built-in ones
+        assert_eq!(aliases.resolve("Fixes"), Some(ChangelogCategory::Security));
+        assert_eq!(aliases.resolve("Chores"), None);
+        assert!(CategoryAliases::parse("[category_aliases]\nDocs = \"misc\"\n").is_err());
+    }
+
+    #[test]
+    fn test_new_section_uses_file_headings() {
+        let headings = CategoryAliases::default().headings_in(CONVENTIONAL);
+        assert_eq!(headings.heading(&ChangelogCategory::Added), "Features");
+        assert_eq!(
+            headings.relabel(
+                "## [1.2.0]\n\n### Added\n\n- A\n\n### Fixed\n\n- B\n\n### Removed\n\n- C\n"
+            ),
+            "## [1.2.0]\n\n### Features\n\n- A\n\n### Bug Fixes\n\n- B\n\n### Removed\n\n- C\n"
+        );
+    }
+
+    #[test]
+    fn test_normalize_rewrites_alias_headings() {
+        let (normalized, replaced) =
+            CategoryAliases::default().normalize(&CONVENTIONAL.replace('\n', "\r\n"));
+        assert_eq!(replaced, 2);
+        assert!(normalized.contains("### Added\r\n\r\n- Dark mode"));
+        assert!(normalized.contains("### Fixed\r\n\r\n- Crash"));
+        assert!(!normalized.contains("Features"));
+    }
+
+    #[test]
+    fn test_normalize_skips_frozen_sections() {
+        let content = "## [1.1.0]\n\n### Features\n\n- A\n\n\
+                       ## [1.0.0]\n<!-- keryx:frozen -->\n\n### Features\n\n- B\n";
+        let (normalized, replaced) = CategoryAliases::default().normalize(content);
+        assert_eq!(replaced, 1);
+        assert_eq!(
+            normalized,
+            "## [1.1.0]\n\n### Added\n\n- A\n\n\
+             ## [1.0.0]\n<!-- keryx:frozen -->\n\n### Features\n\n- B\n"
+        );
+    }
+
+    #[test]
+    fn test_normalize_changelog_file() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(&path, CONVENTIONAL).unwrap();
+        let aliases = CategoryAliases::default();
+
+        assert_eq!(normalize_changelog(&path, &aliases, true).unwrap(), 2);
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), CONVENTIONAL);
+        assert_eq!(normalize_changelog(&path, &aliases, false).unwrap(), 2);
+        assert_eq!(normalize_changelog(&path, &aliases, false).unwrap(), 0);
+        assert_eq!(
+            normalize_changelog(&dir.path().join("missing.md"), &aliases, false).unwrap(),
+            0
+        );
+    }
+}
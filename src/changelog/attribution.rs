@@ -0,0 +1,358 @@
+//! Co-author and reviewer attribution.
+//!
+//! Entries in the enabled categories get a suffix such as
+//! `(thanks @alice, reviewed by @bob)`. Co-authors come from the
+//! `Co-authored-by` trailers of the commits behind an entry, reviewers from
+//! the reviews on its PRs. Which commits and PRs are behind an entry is taken
+//! from the `sources` the LLM cites for it.
+
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+
+use tracing::warn;
+
+use crate::changelog::{ChangelogCategory, ChangelogEntry, ChangelogOutput};
+use crate::git::ParsedCommit;
+
+/// Environment variable listing the categories that get attribution.
+const ATTRIBUTION_ENV_VAR: &str = "KERYX_ATTRIBUTION";
+
+/// Shortest commit hash accepted as a source.
+const MIN_HASH_LEN: usize = 7;
+
+/// Domain of GitHub's per-user noreply addresses (`ID+login@...`).
+const NOREPLY_DOMAIN: &str = "@users.noreply.github.com";
+
+/// Categories whose entries get attribution. The default enables none.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AttributionConfig {
+    categories: Vec<ChangelogCategory>,
+}
+
+impl AttributionConfig {
+    /// Build from `--attribution`, falling back to `KERYX_ATTRIBUTION`.
+    ///
+    /// The value is a comma-separated list of categories (`added,fixed`) or
+    /// `all`. Unknown categories are logged and ignored.
+    pub fn from_flag_or_env(flag: Option<String>) -> Self {
+        let value = flag.or_else(|| env::var(ATTRIBUTION_ENV_VAR).ok());
+        value.map(|v| Self::parse(&v)).unwrap_or_default()
+    }
+
+    fn parse(value: &str) -> Self {
+        let mut categories = Vec::new();
+        for name in value.split(',').map(str::trim).filter(|n| !n.is_empty()) {
+            if name.eq_ignore_ascii_case("all") {
+                return Self::all();
+            }
+            match name.parse::<ChangelogCategory>() {
+                Ok(category) if !categories.contains(&category) => categories.push(category),
+                Ok(_) => {}
+                Err(e) => warn!("Ignoring attribution category \"{}\": {}", name, e),
+            }
+        }
+        Self { categories }
+    }
+
+    /// Attribution for every category.
+    pub fn all() -> Self {
+        Self {
+            categories: vec![
+                ChangelogCategory::Added,
+                ChangelogCategory::Changed,
+                ChangelogCategory::Deprecated,
+                ChangelogCategory::Removed,
+                ChangelogCategory::Fixed,
+                ChangelogCategory::Security,
+            ],
+        }
+    }
+
+    /// Whether any category gets attribution.
+    pub fn is_enabled(&self) -> bool {
+        !self.categories.is_empty()
+    }
+
+    /// Whether entries in `category` get attribution.
+    pub fn applies_to(&self, category: &ChangelogCategory) -> bool {
+        self.categories.contains(category)
+    }
+}
+
+/// People credited on one entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Credits {
+    /// Co-authors, as `@login` when known, otherwise their name.
+    pub thanks: Vec<String>,
+    /// Reviewer logins, as `@login`.
+    pub reviewers: Vec<String>,
+}
+
+impl Credits {
+    pub fn is_empty(&self) -> bool {
+        self.thanks.is_empty() && self.reviewers.is_empty()
+    }
+
+    /// `(thanks @a, reviewed by @b)`, or the half that applies.
+    pub fn render(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if !self.thanks.is_empty() {
+            parts.push(format!("thanks {}", self.thanks.join(", ")));
+        }
+        if !self.reviewers.is_empty() {
+            parts.push(format!("reviewed by {}", self.reviewers.join(", ")));
+        }
+        (!parts.is_empty()).then(|| format!("({})", parts.join(", ")))
+    }
+}
+
+/// Credits for the entries of one release, keyed by entry description.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Attribution {
+    credits: BTreeMap<String, Credits>,
+}
+
+impl Attribution {
+    pub fn is_empty(&self) -> bool {
+        self.credits.is_empty()
+    }
+
+    /// Credits for `entry`, if it has any.
+    pub fn credits(&self, entry: &ChangelogEntry) -> Option<&Credits> {
+        self.credits.get(&entry.description)
+    }
+
+    /// The rendered suffix for `entry`, if it has any credits.
+    pub fn suffix(&self, entry: &ChangelogEntry) -> Option<String> {
+        self.credits(entry).and_then(Credits::render)
+    }
+}
+
+/// PR numbers cited by entries in the enabled categories, for fetching
+/// reviewers.
+pub fn cited_pr_numbers(output: &ChangelogOutput, config: &AttributionConfig) -> Vec<u64> {
+    let mut numbers: Vec<u64> = output
+        .entries
+        .iter()
+        .filter(|e| config.applies_to(&e.category))
+        .flat_map(|e| e.sources.iter().filter_map(|s| pr_number(s)))
+        .collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    numbers
+}
+
+/// Collect the credits for every entry in the enabled categories.
+///
+/// `reviewers` maps PR numbers to reviewer logins; PRs missing from it
+/// contribute co-authors only.
+pub fn build_attribution(
+    output: &ChangelogOutput,
+    config: &AttributionConfig,
+    commits: &[ParsedCommit],
+    reviewers: &HashMap<u64, Vec<String>>,
+) -> Attribution {
+    let mut credits = BTreeMap::new();
+
+    for entry in output
+        .entries
+        .iter()
+        .filter(|e| config.applies_to(&e.category))
+    {
+        let mut entry_credits = Credits::default();
+        for source in &entry.sources {
+            let matched: Vec<&ParsedCommit> = match pr_number(source) {
+                Some(number) => {
+                    for login in reviewers.get(&number).into_iter().flatten() {
+                        push_unique(&mut entry_credits.reviewers, format!("@{}", login));
+                    }
+                    commits
+                        .iter()
+                        .filter(|c| commit_mentions_pr(c, number))
+                        .collect()
+                }
+                None => commits_by_hash(commits, source),
+            };
+            for commit in matched {
+                for co_author in commit.footers.co_authors() {
+                    if let Some(handle) = co_author_handle(co_author) {
+                        push_unique(&mut entry_credits.thanks, handle);
+                    }
+                }
+            }
+        }
+        if !entry_credits.is_empty() {
+            credits.insert(entry.description.clone(), entry_credits);
+        }
+    }
+
+    Attribution { credits }
+}
+
+/// PR number from a `#12` source.
+fn pr_number(source: &str) -> Option<u64> {
+    source.trim().strip_prefix('#')?.parse().ok()
+}
+
+/// Commits whose hash starts with `source`.
+fn commits_by_hash<'a>(commits: &'a [ParsedCommit], source: &str) -> Vec<&'a ParsedCommit> {
+    let prefix = source.trim().to_lowercase();
+    if prefix.len() < MIN_HASH_LEN || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+        return Vec::new();
+    }
+    commits
+        .iter()
+        .filter(|c| c.hash.starts_with(&prefix))
+        .collect()
+}
+
+/// Whether `commit` is the squash or merge commit of PR `number`, or
+/// references it in a `Refs` footer.
+fn commit_mentions_pr(commit: &ParsedCommit, number: u64) -> bool {
+    let reference = format!("#{}", number);
+    let subject = commit.subject();
+    subject.contains(&format!("({})", reference))
+        || subject.starts_with(&format!("Merge pull request {} ", reference))
+        || commit.footers.refs().contains(&reference.as_str())
+}
+
+/// `@login` for GitHub noreply addresses, otherwise the name part of a
+/// `Name <email>` trailer.
+fn co_author_handle(value: &str) -> Option<String> {
+    let (name, email) = match value.split_once('<') {
+        Some((name, rest)) => (name.trim(), rest.trim_end_matches('>').trim()),
+        None => (value.trim(), ""),
+    };
+    if let Some(local) = email.strip_suffix(NOREPLY_DOMAIN) {
+        let login = local.rsplit_once('+').map_or(local, |(_, login)| login);
+        if !login.is_empty() {
+            return Some(format!("@{}", login));
+        }
+    }
+    (!name.is_empty()).then(|| name.to_string())
+}
+
+fn push_unique(list: &mut Vec<String>, value: String) {
+    if !list.contains(&value) {
+        list.push(value);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::Footers;
+    use chrono::Utc;
+    use serial_test::serial;
+
+    fn commit(hash: &str, message: &str) -> ParsedCommit {
+        ParsedCommit {
+            hash: hash.to_string(),
+            message: message.to_string(),
+            commit_type: None,
+            scope: None,
+            breaking: false,
+            timestamp: Utc::now(),
+            footers: Footers::parse(message),
+        }
+    }
+
+    fn entry(category: ChangelogCategory, description: &str, sources: &[&str]) -> ChangelogEntry {
+        ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_categories() {
+        let config = AttributionConfig::parse("added, Fixed,bogus");
+        assert!(config.applies_to(&ChangelogCategory::Added));
+        assert!(config.applies_to(&ChangelogCategory::Fixed));
+        assert!(!config.applies_to(&ChangelogCategory::Changed));
+        assert_eq!(AttributionConfig::parse("all"), AttributionConfig::all());
+        assert!(!AttributionConfig::parse("").is_enabled());
+    }
+
+    #[test]
+    #[serial]
+    fn test_flag_overrides_env() {
+        temp_env::with_var(ATTRIBUTION_ENV_VAR, Some("all"), || {
+            let config = AttributionConfig::from_flag_or_env(Some("security".to_string()));
+            assert!(config.applies_to(&ChangelogCategory::Security));
+            assert!(!config.applies_to(&ChangelogCategory::Added));
+            assert!(
+                AttributionConfig::from_flag_or_env(None).applies_to(&ChangelogCategory::Added)
+            );
+        });
+    }
+
+    #[test]
+    fn test_co_author_handle() {
+        assert_eq!(
+            co_author_handle("Alice <12345+alice@users.noreply.github.com>"),
+            Some("@alice".to_string())
+        );
+        assert_eq!(
+            co_author_handle("Bob <bob@users.noreply.github.com>"),
+            Some("@bob".to_string())
+        );
+        assert_eq!(
+            co_author_handle("Carol Jones <carol@example.com>"),
+            Some("Carol Jones".to_string())
+        );
+    }
+
+    #[test]
+    fn test_build_attribution_from_prs_and_hashes() {
+        let commits = vec![
+            commit(
+                "aaaaaaa111111111111111111111111111111111",
+                "feat: dark mode (#12)\n\nCo-authored-by: Alice <1+alice@users.noreply.github.com>",
+            ),
+            commit(
+                "bbbbbbb222222222222222222222222222222222",
+                "fix: crash\n\nCo-authored-by: Carol <carol@example.com>",
+            ),
+        ];
+        let output = ChangelogOutput {
+            entries: vec![
+                entry(ChangelogCategory::Added, "Dark mode", &["#12"]),
+                entry(ChangelogCategory::Fixed, "Crash on start", &["bbbbbbb"]),
+                entry(ChangelogCategory::Changed, "Faster sync", &["#12"]),
+            ],
+            highlights: None,
+        };
+        let config = AttributionConfig::parse("added,fixed");
+        let reviewers = HashMap::from([(12, vec!["bob".to_string()])]);
+
+        assert_eq!(cited_pr_numbers(&output, &config), vec![12]);
+        let attribution = build_attribution(&output, &config, &commits, &reviewers);
+
+        assert_eq!(
+            attribution.suffix(&output.entries[0]).as_deref(),
+            Some("(thanks @alice, reviewed by @bob)")
+        );
+        assert_eq!(
+            attribution.suffix(&output.entries[1]).as_deref(),
+            Some("(thanks Carol)")
+        );
+        assert_eq!(attribution.suffix(&output.entries[2]), None);
+    }
+
+    #[test]
+    fn test_reviewers_only() {
+        let credits = Credits {
+            thanks: vec![],
+            reviewers: vec!["@bob".to_string(), "@dana".to_string()],
+        };
+        assert_eq!(
+            credits.render().as_deref(),
+            Some("(reviewed by @bob, @dana)")
+        );
+        assert_eq!(Credits::default().render(), None);
+    }
+}
@@ -0,0 +1,411 @@
+//! Pre-release channels: `2.0.0-rc.1`, `2.0.0-beta.2`, ...
+//!
+//! Sections of pre-release versions can be kept out of the main changelog in
+//! a separate pre-release changelog. When the stable version ships, the
+//! entries of its pre-release sections are consolidated into the stable
+//! section: duplicates are dropped, the LLM rewords the combined list as one
+//! set of changes, and the pre-release sections are removed from the
+//! pre-release changelog.
+
+use std::path::Path;
+
+use semver::Version;
+use tracing::warn;
+
+use super::aliases::CategoryAliases;
+use super::format::{ChangelogCategory, ChangelogEntry, ChangelogOutput};
+use super::line_ending::{LineEnding, normalize};
+use super::merge::split_marker;
+use super::parser::{FROZEN_MARKER, find_version_token, scan_frozen_versions};
+use super::writer::atomic_write;
+use crate::error::ChangelogError;
+use crate::llm::prompt::sanitize_for_prompt;
+
+/// Channel of a pre-release version (`rc` for `2.0.0-rc.1`), `None` for a
+/// stable version.
+pub fn channel(version: &Version) -> Option<&str> {
+    if version.pre.is_empty() {
+        return None;
+    }
+    version.pre.as_str().split(['.', '-']).next()
+}
+
+/// Whether `version` is a pre-release of the stable `stable`.
+pub fn is_prerelease_of(version: &Version, stable: &Version) -> bool {
+    !version.pre.is_empty()
+        && (version.major, version.minor, version.patch)
+            == (stable.major, stable.minor, stable.patch)
+}
+
+/// A pre-release section of a changelog and its entries.
+#[derive(Debug, Clone)]
+pub struct PrereleaseSection {
+    pub version: Version,
+    pub entries: Vec<ChangelogEntry>,
+}
+
+/// Sections of `content` for pre-releases of `stable`, in file order (newest
+/// first in a Keep a Changelog file). Category headings may be `aliases`.
+pub fn prerelease_sections(
+    content: &str,
+    stable: &Version,
+    aliases: &CategoryAliases,
+) -> Vec<PrereleaseSection> {
+    let content = normalize(content);
+    let mut sections: Vec<PrereleaseSection> = Vec::new();
+    let mut in_section = false;
+    let mut category: Option<ChangelogCategory> = None;
+    // Entry text with any marker; continuation lines are joined
+    let mut item: Option<(ChangelogCategory, String)> = None;
+
+    fn flush(item: &mut Option<(ChangelogCategory, String)>, sections: &mut [PrereleaseSection]) {
+        if let (Some((category, text)), Some(section)) = (item.take(), sections.last_mut()) {
+            let (description, sources) = split_marker(&text);
+            if !description.is_empty() {
+                section.entries.push(ChangelogEntry {
+                    category,
+                    description: description.to_string(),
+                    sources: sources.into_iter().map(str::to_string).collect(),
+                    scope: None,
+                    importance: None,
+                });
+            }
+        }
+    }
+
+    for line in content.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            flush(&mut item, &mut sections);
+            category = None;
+            in_section = match find_version_token(title) {
+                Some(version) if is_prerelease_of(&version, stable) => {
+                    sections.push(PrereleaseSection {
+                        version,
+                        entries: Vec::new(),
+                    });
+                    true
+                }
+                _ => false,
+            };
+            continue;
+        }
+        if !in_section {
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix("### ") {
+            flush(&mut item, &mut sections);
+            category = aliases.resolve(heading);
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if let Some(text) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            flush(&mut item, &mut sections);
+            item = category
+                .clone()
+                .map(|category| (category, text.to_string()));
+        } else if line.starts_with([' ', '\t'])
+            && !trimmed.is_empty()
+            && let Some((_, text)) = item.as_mut()
+        {
+            text.push(' ');
+            text.push_str(trimmed.trim_end());
+        } else {
+            flush(&mut item, &mut sections);
+        }
+    }
+    flush(&mut item, &mut sections);
+    sections
+}
+
+/// `content` without the sections of `versions` and their link reference
+/// definitions. Sections marked [`FROZEN_MARKER`] stay in place. Line
+/// endings are kept.
+pub fn remove_sections(content: &str, versions: &[Version]) -> String {
+    let line_ending = LineEnding::detect(content);
+    let normalized = normalize(content);
+    let frozen = scan_frozen_versions(&normalized);
+    let versions: Vec<&Version> = versions.iter().filter(|v| !frozen.contains(v)).collect();
+    let links: Vec<String> = versions.iter().map(|v| format!("[{}]:", v)).collect();
+
+    let mut kept: Vec<&str> = Vec::new();
+    let mut removing = false;
+    for line in normalized.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            removing = find_version_token(title).is_some_and(|v| versions.contains(&&v));
+        } else if removing && is_link_definition(line) {
+            // Link definitions after the last section belong to the file
+            removing = false;
+        }
+        if removing || links.iter().any(|link| line.starts_with(link.as_str())) {
+            continue;
+        }
+        kept.push(line);
+    }
+
+    let mut result = kept.join("\n");
+    if normalized.ends_with('\n') {
+        result.push('\n');
+    }
+    line_ending.apply(&result)
+}
+
+/// Remove the sections of `versions` from the changelog at `path`, leaving
+/// frozen sections in place with a warning. Returns the versions removed;
+/// none when the file did not change.
+pub fn remove_sections_from_file(
+    path: &Path,
+    versions: &[Version],
+) -> Result<Vec<Version>, ChangelogError> {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(ChangelogError::ReadFailed(e)),
+    };
+    let frozen = scan_frozen_versions(&normalize(&content));
+    let (kept, removable): (Vec<Version>, Vec<Version>) =
+        versions.iter().cloned().partition(|v| frozen.contains(v));
+    for version in &kept {
+        warn!(
+            "Keeping section {} in {}: it is marked {}",
+            version,
+            path.display(),
+            FROZEN_MARKER
+        );
+    }
+    let updated = remove_sections(&content, &removable);
+    if updated == content {
+        return Ok(Vec::new());
+    }
+    atomic_write(path, &updated)?;
+    Ok(removable)
+}
+
+/// `output` followed by the entries of `sections` that it does not already
+/// have. An entry is a duplicate when it cites a source of an earlier entry
+/// or has the same text.
+pub fn combine(output: ChangelogOutput, sections: &[PrereleaseSection]) -> ChangelogOutput {
+    let mut combined = ChangelogOutput {
+        entries: Vec::new(),
+        highlights: output.highlights,
+    };
+    let candidates = output
+        .entries
+        .into_iter()
+        .chain(sections.iter().flat_map(|s| s.entries.iter().cloned()));
+    for entry in candidates {
+        let duplicate = combined.entries.iter().any(|kept| {
+            entry
+                .sources
+                .iter()
+                .any(|source| kept.sources.contains(source))
+                || normalize_text(&kept.description) == normalize_text(&entry.description)
+        });
+        if !duplicate {
+            combined.entries.push(entry);
+        }
+    }
+    combined
+}
+
+/// Entry text compared case- and whitespace-insensitively, without the
+/// trailing period.
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
+/// Whether `line` is a Markdown link reference definition (`[1.0.0]: url`).
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[')
+        && line
+            .split_once("]:")
+            .is_some_and(|(label, _)| !label.contains(']'))
+}
+
+/// Build the prompt asking the LLM to turn the combined entries of the
+/// pre-releases and the final changes into the stable release's entries.
+pub fn build_consolidation_prompt(
+    version: &Version,
+    prereleases: &[Version],
+    combined: &ChangelogOutput,
+    repository_name: &str,
+) -> String {
+    let mut entries = String::new();
+    for entry in &combined.entries {
+        entries.push_str(&format!(
+            "- [{}] {}",
+            entry.category.as_str(),
+            sanitize_for_prompt(&entry.description)
+        ));
+        if !entry.sources.is_empty() {
+            entries.push_str(&format!(" (sources: {})", entry.sources.join(", ")));
+        }
+        entries.push('\n');
+    }
+    let prereleases: Vec<String> = prereleases.iter().map(Version::to_string).collect();
+
+    format!(
+        r##"You are writing the changelog entries of {repository} {version}, the stable release that follows the pre-releases {prereleases}.
+These are the entries of those pre-releases and of the changes made since the last one, with their category:
+
+{entries}
+## Instructions
+1. Describe the changes as users upgrading from the previous stable release see them
+2. Merge entries that describe the same change into one, keeping every source they cite
+3. Drop fixes for problems that only existed in a pre-release, and mention a change that was added and later reverted or replaced only in its final form
+4. Keep each entry's category unless the merged change belongs in another one
+5. Do not invent changes that are not listed
+
+Respond with JSON only:
+{{
+  "entries": [
+    {{"category": "Added", "description": "...", "sources": ["#12"]}}
+  ]
+}}"##,
+        repository = sanitize_for_prompt(repository_name),
+        version = version,
+        prereleases = prereleases.join(", "),
+        entries = entries,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog\n\n## [2.0.0-rc.2] - 2024-06-10\n\n### Fixed\n\n- Fix crash in the new exporter <!-- keryx:sources #21 -->\n\n## [2.0.0-rc.1] - 2024-06-01\n\n### Added\n\n- CSV exporter\n  for reports <!-- keryx:sources #12 -->\n\n### Removed\n\n- The `--legacy` flag\n\n## [1.9.0] - 2024-05-01\n\n### Added\n\n- Dark mode\n\n[2.0.0-rc.2]: https://example.com/compare/v2.0.0-rc.1...v2.0.0-rc.2\n[2.0.0-rc.1]: https://example.com/compare/v1.9.0...v2.0.0-rc.1\n[1.9.0]: https://example.com/releases/tag/v1.9.0\n";
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn entry(category: ChangelogCategory, description: &str, sources: &[&str]) -> ChangelogEntry {
+        ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_channel() {
+        assert_eq!(channel(&v("2.0.0-rc.1")), Some("rc"));
+        assert_eq!(channel(&v("2.0.0-beta")), Some("beta"));
+        assert_eq!(channel(&v("2.0.0")), None);
+        assert!(is_prerelease_of(&v("2.0.0-rc.1"), &v("2.0.0")));
+        assert!(!is_prerelease_of(&v("2.0.1-rc.1"), &v("2.0.0")));
+        assert!(!is_prerelease_of(&v("2.0.0"), &v("2.0.0")));
+    }
+
+    #[test]
+    fn test_prerelease_sections() {
+        let sections = prerelease_sections(CHANGELOG, &v("2.0.0"), &CategoryAliases::default());
+        let versions: Vec<&Version> = sections.iter().map(|s| &s.version).collect();
+        assert_eq!(versions, vec![&v("2.0.0-rc.2"), &v("2.0.0-rc.1")]);
+
+        let rc1 = &sections[1].entries;
+        assert_eq!(rc1.len(), 2);
+        assert_eq!(rc1[0].description, "CSV exporter for reports");
+        assert_eq!(rc1[0].sources, vec!["#12"]);
+        assert_eq!(rc1[1].category, ChangelogCategory::Removed);
+
+        assert!(
+            prerelease_sections(CHANGELOG, &v("1.9.0"), &CategoryAliases::default()).is_empty()
+        );
+    }
+
+    #[test]
+    fn test_remove_sections() {
+        let removed = remove_sections(CHANGELOG, &[v("2.0.0-rc.1"), v("2.0.0-rc.2")]);
+        assert_eq!(
+            removed,
+            "# Changelog\n\n## [1.9.0] - 2024-05-01\n\n### Added\n\n- Dark mode\n\n[1.9.0]: https://example.com/releases/tag/v1.9.0\n"
+        );
+
+        let crlf = CHANGELOG.replace('\n', "\r\n");
+        assert_eq!(
+            remove_sections(&crlf, &[v("2.0.0-rc.1"), v("2.0.0-rc.2")]),
+            removed.replace('\n', "\r\n")
+        );
+    }
+
+    #[test]
+    fn test_remove_sections_keeps_frozen_sections() {
+        let frozen = CHANGELOG.replacen(
+            "## [2.0.0-rc.1]",
+            &format!("## [2.0.0-rc.1]\n{}", FROZEN_MARKER),
+            1,
+        );
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(&path, &frozen).unwrap();
+
+        let removed =
+            remove_sections_from_file(&path, &[v("2.0.0-rc.1"), v("2.0.0-rc.2")]).unwrap();
+        assert_eq!(removed, vec![v("2.0.0-rc.2")]);
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## [2.0.0-rc.1]"));
+        assert!(content.contains("[2.0.0-rc.1]: "));
+        assert!(!content.contains("## [2.0.0-rc.2]"));
+
+        // Nothing left to remove
+        assert!(
+            remove_sections_from_file(&path, &[v("2.0.0-rc.1")])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_combine_dedupes_by_source_and_text() {
+        let sections = prerelease_sections(CHANGELOG, &v("2.0.0"), &CategoryAliases::default());
+        let output = ChangelogOutput {
+            entries: vec![
+                entry(ChangelogCategory::Added, "Export reports as CSV", &["#12"]),
+                entry(ChangelogCategory::Removed, "the `--legacy` flag.", &[]),
+                entry(ChangelogCategory::Fixed, "Faster startup", &["#30"]),
+            ],
+            highlights: None,
+        };
+        let combined = combine(output, &sections);
+        let descriptions: Vec<&str> = combined
+            .entries
+            .iter()
+            .map(|e| e.description.as_str())
+            .collect();
+        assert_eq!(
+            descriptions,
+            vec![
+                "Export reports as CSV",
+                "the `--legacy` flag.",
+                "Faster startup",
+                "Fix crash in the new exporter",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_consolidation_prompt_lists_entries() {
+        let combined = ChangelogOutput {
+            entries: vec![entry(ChangelogCategory::Added, "CSV exporter", &["#12"])],
+            highlights: None,
+        };
+        let prompt = build_consolidation_prompt(
+            &v("2.0.0"),
+            &[v("2.0.0-rc.2"), v("2.0.0-rc.1")],
+            &combined,
+            "acme/app",
+        );
+        assert!(prompt.contains("acme/app 2.0.0"));
+        assert!(prompt.contains("pre-releases 2.0.0-rc.2, 2.0.0-rc.1"));
+        assert!(prompt.contains("- [Added] CSV exporter (sources: #12)"));
+    }
+}
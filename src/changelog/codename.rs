@@ -0,0 +1,164 @@
+//! LLM-suggested release codenames.
+//!
+//! Used when a heading template contains `{codename}` but neither `--codename`
+//! nor a configured sequence provides one. A suggestion is optional: any
+//! failure leaves the release without a codename.
+
+use serde::Deserialize;
+use tracing::{debug, warn};
+
+use crate::changelog::ChangelogOutput;
+use crate::llm::extract_json;
+use crate::llm::prompt::sanitize_for_prompt;
+use crate::llm::{LlmRouter, TextRequest};
+
+/// Longest codename accepted from the LLM.
+const MAX_CODENAME_LEN: usize = 40;
+
+/// Response from the LLM for a codename suggestion.
+#[derive(Deserialize)]
+struct CodenameResponse {
+    codename: String,
+}
+
+/// Build the prompt asking for a codename that fits the release content.
+fn build_codename_prompt(output: &ChangelogOutput, used: &[String]) -> String {
+    let entries: Vec<String> = output
+        .entries
+        .iter()
+        .map(|e| {
+            format!(
+                "- {}: {}",
+                e.category.as_str(),
+                sanitize_for_prompt(&e.description)
+            )
+        })
+        .collect();
+    let avoid = if used.is_empty() {
+        "(none)".to_string()
+    } else {
+        used.iter()
+            .map(|name| sanitize_for_prompt(name))
+            .collect::<Vec<_>>()
+            .join(", ")
+    };
+
+    format!(
+        r#"Suggest a short, memorable codename for a software release with these changes:
+
+{entries}
+
+## Rules
+- One or two words, letters only, Title Case
+- Evocative of the release's main theme
+- Must not be one of these already-used codenames: {avoid}
+
+Respond with JSON only (no markdown wrapping):
+{{"codename": "..."}}"#,
+        entries = entries.join("\n"),
+        avoid = avoid,
+    )
+}
+
+/// Parse and validate the codename from an LLM response.
+fn parse_codename_response(response: &str) -> Option<String> {
+    let json_str = extract_json(response);
+    let parsed: CodenameResponse = match serde_json::from_str(&json_str) {
+        Ok(v) => v,
+        Err(e) => {
+            debug!("Failed to parse codename JSON: {}", e);
+            return None;
+        }
+    };
+
+    let codename = parsed.codename.trim();
+    let valid = !codename.is_empty()
+        && codename.chars().count() <= MAX_CODENAME_LEN
+        && codename
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == ' ' || c == '-');
+    if valid {
+        Some(codename.to_string())
+    } else {
+        debug!("Rejected codename suggestion '{}'", codename);
+        None
+    }
+}
+
+/// Ask the LLM for a codename, avoiding names in `used`.
+///
+/// This function **never fails**. On any error it prints a warning and
+/// returns `None`, leaving the release without a codename.
+pub async fn suggest_codename(
+    output: &ChangelogOutput,
+    used: &[String],
+    llm: &mut LlmRouter,
+) -> Option<String> {
+    let prompt = build_codename_prompt(output, used);
+
+    let response = match llm.request(&TextRequest::new(prompt)).await {
+        Ok(completion) => completion.output,
+        Err(e) => {
+            warn!("Codename suggestion failed: {}", e.summary());
+            eprintln!("\x1b[33m⚠ Codename suggestion failed, omitting codename\x1b[0m");
+            eprintln!("  Reason: {}", e.summary());
+            return None;
+        }
+    };
+
+    let codename = parse_codename_response(&response)
+        .filter(|name| !used.iter().any(|u| u.eq_ignore_ascii_case(name)));
+    if codename.is_none() {
+        eprintln!("\x1b[33m⚠ Could not use suggested codename, omitting codename\x1b[0m");
+    }
+    codename
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::{ChangelogCategory, ChangelogEntry};
+
+    #[test]
+    fn test_parse_codename_response() {
+        assert_eq!(
+            parse_codename_response(r#"{"codename": "Aurora"}"#),
+            Some("Aurora".to_string())
+        );
+        assert_eq!(
+            parse_codename_response("```json\n{\"codename\": \" Silver Fox \"}\n```"),
+            Some("Silver Fox".to_string())
+        );
+    }
+
+    #[test]
+    fn test_parse_codename_response_rejects_invalid() {
+        assert_eq!(parse_codename_response("Aurora"), None);
+        assert_eq!(parse_codename_response(r#"{"codename": ""}"#), None);
+        assert_eq!(
+            parse_codename_response("{\"codename\": \"## Injected\\n- heading\"}"),
+            None
+        );
+        assert_eq!(
+            parse_codename_response(&format!(r#"{{"codename": "{}"}}"#, "A".repeat(41))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_prompt_lists_entries_and_used_names() {
+        let output = ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Added,
+                description: "Dark mode".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: None,
+        };
+        let prompt = build_codename_prompt(&output, &["Aurora".to_string()]);
+        assert!(prompt.contains("- Added: Dark mode"));
+        assert!(prompt.contains("already-used codenames: Aurora"));
+    }
+}
@@ -0,0 +1,181 @@
+//! Release notes written in commit trailers.
+//!
+//! A commit with a `Release-Note:` trailer supplies its own entry: the
+//! trailer text becomes the entry, and the commit (with the PR its subject
+//! references) is left out of the LLM input. Unlike PR changelog sections,
+//! these entries still go through verification with the generated ones. The
+//! text may name its category with a prefix (`Fixed: ...`); otherwise the
+//! category follows the commit type. `Release-Note: none` marks a commit
+//! with nothing to announce.
+//!
+//! ```text
+//! fix(cli): honor --date-timezone in headings (#31)
+//!
+//! Release-Note: Dates in the heading now honor `--date-timezone`
+//! ```
+
+use std::collections::HashSet;
+
+use crate::changelog::{ChangelogEntry, ChangelogOutput};
+use crate::git::{FooterKey, ParsedCommit};
+use crate::github::prs::referenced_pr_numbers;
+use crate::llm::ChangelogInput;
+
+use super::pr_notes::{NO_ENTRY_MARKERS, default_category, parse_item};
+
+/// Entries taken from `Release-Note` trailers.
+#[derive(Debug, Clone, Default)]
+pub struct CommitNotes {
+    pub entries: Vec<ChangelogEntry>,
+    /// Commits with a `Release-Note` trailer, including those that declared
+    /// no entry.
+    pub hashes: HashSet<String>,
+    /// PRs referenced by those commits' subjects.
+    pub pr_numbers: HashSet<u64>,
+}
+
+impl CommitNotes {
+    /// Collect the `Release-Note` trailers of `commits`.
+    pub fn from_commits(commits: &[ParsedCommit]) -> Self {
+        let mut notes = Self::default();
+        for commit in commits {
+            let values = commit.footers.get(&FooterKey::ReleaseNote);
+            let texts: Vec<String> = values
+                .iter()
+                .map(|value| value.split_whitespace().collect::<Vec<_>>().join(" "))
+                .filter(|text| !text.is_empty())
+                .collect();
+            if texts.is_empty() {
+                continue;
+            }
+            notes.hashes.insert(commit.hash.clone());
+            let referenced = referenced_pr_numbers([commit.subject()]);
+            let mut sources = vec![commit.hash[..commit.hash.len().min(7)].to_string()];
+            sources.extend(referenced.iter().map(|number| format!("#{}", number)));
+            notes.pr_numbers.extend(referenced);
+
+            for text in texts {
+                if NO_ENTRY_MARKERS
+                    .iter()
+                    .any(|marker| text.trim_end_matches('.').eq_ignore_ascii_case(marker))
+                {
+                    continue;
+                }
+                let item = parse_item(&text, None);
+                notes.entries.push(ChangelogEntry {
+                    category: item
+                        .category
+                        .unwrap_or_else(|| default_category(commit.subject())),
+                    description: item.text,
+                    sources: sources.clone(),
+                    scope: commit.scope.clone(),
+                    importance: None,
+                });
+            }
+        }
+        notes
+    }
+
+    /// Whether no commit had a `Release-Note` trailer.
+    pub fn is_empty(&self) -> bool {
+        self.hashes.is_empty()
+    }
+
+    /// `input` without the noted commits and the PRs they reference: what
+    /// is left for the LLM to describe.
+    pub fn llm_input(&self, input: &ChangelogInput) -> ChangelogInput {
+        let mut llm_input = input.clone();
+        if self.is_empty() {
+            return llm_input;
+        }
+        llm_input
+            .commits
+            .retain(|commit| !self.hashes.contains(&commit.hash));
+        llm_input
+            .pull_requests
+            .retain(|pr| !self.pr_numbers.contains(&pr.number.get()));
+        llm_input
+    }
+
+    /// Add the noted entries to `output`.
+    pub fn merge_into(&self, output: &mut ChangelogOutput) {
+        output.entries.extend(self.entries.iter().cloned());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogCategory;
+    use crate::git::{Footers, parse_commit_message};
+
+    fn commit(hash: &str, message: &str) -> ParsedCommit {
+        let (commit_type, scope, breaking) = parse_commit_message(message);
+        ParsedCommit {
+            hash: hash.repeat(40),
+            message: message.to_string(),
+            commit_type,
+            scope,
+            breaking,
+            timestamp: chrono::Utc::now(),
+            footers: Footers::parse(message),
+        }
+    }
+
+    #[test]
+    fn test_trailers_become_entries() {
+        let commits = vec![
+            commit(
+                "a",
+                "fix(cli): date heading (#31)\n\nRelease-Note: Dates in the heading now\n  honor `--date-timezone`",
+            ),
+            commit(
+                "b",
+                "feat: json output\n\nrelease-note: Security: Tokens are masked in logs",
+            ),
+            commit("c", "chore: bump deps\n\nRelease-Note: NONE"),
+            commit("d", "docs: typo"),
+        ];
+        let notes = CommitNotes::from_commits(&commits);
+        assert_eq!(notes.hashes.len(), 3);
+        assert_eq!(notes.pr_numbers, HashSet::from([31]));
+        assert_eq!(notes.entries.len(), 2);
+
+        let first = &notes.entries[0];
+        assert_eq!(first.category, ChangelogCategory::Fixed);
+        assert_eq!(
+            first.description,
+            "Dates in the heading now honor `--date-timezone`"
+        );
+        assert_eq!(first.sources, vec!["aaaaaaa", "#31"]);
+        assert_eq!(first.scope.as_deref(), Some("cli"));
+        assert_eq!(notes.entries[1].category, ChangelogCategory::Security);
+        assert_eq!(notes.entries[1].description, "Tokens are masked in logs");
+    }
+
+    #[test]
+    fn test_noted_commits_are_removed_from_llm_input() {
+        let commits = vec![
+            commit("a", "fix: date heading (#31)\n\nRelease-Note: Dates fixed"),
+            commit("d", "docs: typo"),
+        ];
+        let notes = CommitNotes::from_commits(&commits);
+        let input = ChangelogInput {
+            commits,
+            pull_requests: Vec::new(),
+            previous_version: None,
+            repository_name: "keryx".to_string(),
+            project_description: None,
+            cli_features: None,
+            product: Default::default(),
+            languages: Default::default(),
+            include_highlights: false,
+            include_sources: false,
+            entry_sort: Default::default(),
+            prs_only: false,
+        };
+        let llm_input = notes.llm_input(&input);
+        let subjects: Vec<&str> = llm_input.commits.iter().map(|c| c.subject()).collect();
+        assert_eq!(subjects, vec!["docs: typo"]);
+    }
+}
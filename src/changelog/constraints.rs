@@ -0,0 +1,462 @@
+//! Size and style limits for generated entries.
+//!
+//! Release notes only get read while they stay short. Limits are stated in the
+//! generation prompt and checked afterwards: violations trigger one
+//! regeneration with a correction note, and whatever still violates them is
+//! fixed up deterministically where that is safe (capping categories,
+//! capitalizing the first letter). Over-long entries are kept with a warning
+//! rather than cut mid-sentence.
+
+use std::collections::BTreeMap;
+use std::env;
+
+use tracing::{debug, warn};
+
+use crate::changelog::sanitize::{SanitizeOptions, generate_sanitized};
+use crate::changelog::{ChangelogCategory, ChangelogOutput};
+use crate::llm::{LlmCompletion, LlmError, LlmRouter};
+
+/// Environment variable for the maximum number of entries per category.
+const MAX_PER_CATEGORY_ENV_VAR: &str = "KERYX_MAX_ENTRIES_PER_CATEGORY";
+
+/// Environment variable for the maximum number of words per entry.
+const MAX_WORDS_ENV_VAR: &str = "KERYX_MAX_ENTRY_WORDS";
+
+/// Environment variable requiring entries to start with a capital letter.
+const SENTENCE_CASE_ENV_VAR: &str = "KERYX_SENTENCE_CASE";
+
+/// Limits applied to generated entries. The default imposes none.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct EntryConstraints {
+    /// Most entries allowed in a single category.
+    pub max_per_category: Option<usize>,
+    /// Most words allowed in a single description.
+    pub max_words: Option<usize>,
+    /// Require every description to start with a capital letter.
+    pub sentence_case: bool,
+}
+
+impl EntryConstraints {
+    /// Build from CLI flags, falling back to `KERYX_MAX_ENTRIES_PER_CATEGORY`,
+    /// `KERYX_MAX_ENTRY_WORDS` and `KERYX_SENTENCE_CASE`.
+    ///
+    /// Logs a warning and ignores a variable that is set but invalid.
+    pub fn from_flags_or_env(
+        max_per_category: Option<usize>,
+        max_words: Option<usize>,
+        sentence_case: bool,
+    ) -> Self {
+        Self {
+            max_per_category: max_per_category.or_else(|| limit_from_env(MAX_PER_CATEGORY_ENV_VAR)),
+            max_words: max_words.or_else(|| limit_from_env(MAX_WORDS_ENV_VAR)),
+            sentence_case: sentence_case || flag_from_env(SENTENCE_CASE_ENV_VAR),
+        }
+    }
+
+    /// Whether no limit is configured.
+    pub fn is_unconstrained(&self) -> bool {
+        self.max_per_category.is_none() && self.max_words.is_none() && !self.sentence_case
+    }
+
+    /// Instructions appended to the generation prompt, if any limit is set.
+    pub fn prompt_note(&self) -> Option<String> {
+        if self.is_unconstrained() {
+            return None;
+        }
+        let mut note = String::from("\n\n## Entry Limits\nThese limits are mandatory:");
+        if let Some(max) = self.max_per_category {
+            note.push_str(&format!(
+                "\n- At most {} entries per category. Merge related changes and leave out minor ones.",
+                max
+            ));
+        }
+        if let Some(max) = self.max_words {
+            note.push_str(&format!("\n- Each description has at most {} words.", max));
+        }
+        if self.sentence_case {
+            note.push_str("\n- Each description starts with a capital letter (sentence case).");
+        }
+        Some(note)
+    }
+
+    /// List every way `output` breaks the limits.
+    pub fn check(&self, output: &ChangelogOutput) -> Vec<Violation> {
+        let mut violations = Vec::new();
+
+        if let Some(max) = self.max_per_category {
+            for (category, entries) in output.entries_by_category() {
+                if entries.len() > max {
+                    violations.push(Violation::TooManyEntries {
+                        category,
+                        count: entries.len(),
+                        max,
+                    });
+                }
+            }
+        }
+
+        for entry in &output.entries {
+            if let Some(max) = self.max_words {
+                let words = word_count(&entry.description);
+                if words > max {
+                    violations.push(Violation::TooManyWords {
+                        description: entry.description.clone(),
+                        words,
+                        max,
+                    });
+                }
+            }
+            if self.sentence_case && starts_lowercase(&entry.description) {
+                violations.push(Violation::NotSentenceCase {
+                    description: entry.description.clone(),
+                });
+            }
+        }
+
+        violations
+    }
+
+    /// Fix what can be fixed without the LLM: drop entries past the
+    /// per-category cap (keeping the first ones) and capitalize descriptions.
+    pub fn enforce(&self, output: &mut ChangelogOutput) -> EnforceReport {
+        let mut report = EnforceReport::default();
+
+        if let Some(max) = self.max_per_category {
+            let mut seen: BTreeMap<u8, usize> = BTreeMap::new();
+            output.entries.retain(|entry| {
+                let count = seen.entry(entry.category.order()).or_default();
+                *count += 1;
+                if *count > max {
+                    debug!("Dropped entry over category cap: {}", entry.description);
+                    report.dropped += 1;
+                    false
+                } else {
+                    true
+                }
+            });
+        }
+
+        for entry in &mut output.entries {
+            if self.sentence_case && starts_lowercase(&entry.description) {
+                entry.description = capitalize_first(&entry.description);
+                report.recased += 1;
+            }
+            if self
+                .max_words
+                .is_some_and(|max| word_count(&entry.description) > max)
+            {
+                report.too_long += 1;
+            }
+        }
+
+        report
+    }
+}
+
+/// A single broken limit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Violation {
+    /// A category has more entries than allowed.
+    TooManyEntries {
+        category: ChangelogCategory,
+        count: usize,
+        max: usize,
+    },
+    /// A description has more words than allowed.
+    TooManyWords {
+        description: String,
+        words: usize,
+        max: usize,
+    },
+    /// A description starts with a lowercase letter.
+    NotSentenceCase { description: String },
+}
+
+impl std::fmt::Display for Violation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Violation::TooManyEntries {
+                category,
+                count,
+                max,
+            } => write!(
+                f,
+                "{} has {} entries (max {})",
+                category.as_str(),
+                count,
+                max
+            ),
+            Violation::TooManyWords {
+                description,
+                words,
+                max,
+            } => write!(f, "\"{}\" has {} words (max {})", description, words, max),
+            Violation::NotSentenceCase { description } => {
+                write!(
+                    f,
+                    "\"{}\" does not start with a capital letter",
+                    description
+                )
+            }
+        }
+    }
+}
+
+/// What [`EntryConstraints::enforce`] changed.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct EnforceReport {
+    /// Entries removed by the per-category cap.
+    pub dropped: usize,
+    /// Descriptions whose first letter was capitalized.
+    pub recased: usize,
+    /// Descriptions still over the word limit (kept as-is).
+    pub too_long: usize,
+}
+
+/// Instruction appended to the prompt when regenerating after violations.
+fn correction_note(violations: &[Violation]) -> String {
+    let mut note = String::from(
+        "\n\n## Limit Correction\nA previous attempt broke the entry limits above. Rewrite the entries so that every limit holds.",
+    );
+    for violation in violations.iter().take(10) {
+        note.push_str(&format!("\n- {}", violation));
+    }
+    note
+}
+
+/// Generate sanitized entries that respect `constraints`.
+///
+/// The limits are added to the prompt; if the result still breaks them, the
+/// entries are regenerated once with the violations listed, the attempt with
+/// fewer violations is kept, and [`EntryConstraints::enforce`] fixes up the
+/// rest. Without any limit this is exactly [`generate_sanitized`].
+pub async fn generate_constrained(
+    llm: &mut LlmRouter,
+    prompt: &str,
+    options: SanitizeOptions,
+    constraints: &EntryConstraints,
+) -> Result<LlmCompletion, LlmError> {
+    let Some(note) = constraints.prompt_note() else {
+        return generate_sanitized(llm, prompt, options).await;
+    };
+
+    let prompt = format!("{}{}", prompt, note);
+    let mut completion = generate_sanitized(llm, &prompt, options).await?;
+
+    let violations = constraints.check(&completion.output);
+    if !violations.is_empty() {
+        eprintln!(
+            "\x1b[33m⚠ {} entry limit violations, regenerating...\x1b[0m",
+            violations.len()
+        );
+        let retry_prompt = format!("{}{}", prompt, correction_note(&violations));
+        match generate_sanitized(llm, &retry_prompt, options).await {
+            Ok(retried) => {
+                if constraints.check(&retried.output).len() <= violations.len() {
+                    completion = retried;
+                }
+            }
+            Err(e) => warn!(
+                "Regeneration failed: {}. Applying limits to the first attempt.",
+                e.summary()
+            ),
+        }
+    }
+
+    let report = constraints.enforce(&mut completion.output);
+    if report.dropped > 0 {
+        eprintln!(
+            "\x1b[33m⚠ Dropped {} entries over the per-category limit\x1b[0m",
+            report.dropped
+        );
+    }
+    if report.too_long > 0 {
+        eprintln!(
+            "\x1b[33m⚠ {} entries are still longer than {} words\x1b[0m",
+            report.too_long,
+            constraints.max_words.unwrap_or_default()
+        );
+    }
+
+    Ok(completion)
+}
+
+fn word_count(description: &str) -> usize {
+    description.split_whitespace().count()
+}
+
+/// Whether the description starts with a lowercase letter. Descriptions that
+/// start with code, digits or punctuation are left alone.
+fn starts_lowercase(description: &str) -> bool {
+    description.chars().next().is_some_and(char::is_lowercase)
+}
+
+fn capitalize_first(description: &str) -> String {
+    let mut chars = description.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+/// Read a positive integer limit; `0` or garbage is ignored with a warning.
+fn limit_from_env(var: &str) -> Option<usize> {
+    match env::var(var) {
+        Ok(v) if !v.is_empty() => match v.trim().parse::<usize>() {
+            Ok(n) if n > 0 => Some(n),
+            _ => {
+                warn!("Invalid {} value '{}', ignoring limit", var, v);
+                None
+            }
+        },
+        _ => None,
+    }
+}
+
+fn flag_from_env(var: &str) -> bool {
+    match env::var(var) {
+        Ok(v) if !v.is_empty() => match v.to_lowercase().as_str() {
+            "1" | "true" | "yes" => true,
+            "0" | "false" | "no" => false,
+            _ => {
+                warn!("Invalid {} value '{}', ignoring", var, v);
+                false
+            }
+        },
+        _ => false,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogEntry;
+    use serial_test::serial;
+
+    fn entry(category: ChangelogCategory, description: &str) -> ChangelogEntry {
+        ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    fn output(entries: Vec<ChangelogEntry>) -> ChangelogOutput {
+        ChangelogOutput {
+            entries,
+            highlights: None,
+        }
+    }
+
+    #[test]
+    fn test_unconstrained_has_no_prompt_note_or_violations() {
+        let constraints = EntryConstraints::default();
+        assert!(constraints.is_unconstrained());
+        assert!(constraints.prompt_note().is_none());
+        let out = output(vec![entry(ChangelogCategory::Added, "lowercase start")]);
+        assert!(constraints.check(&out).is_empty());
+    }
+
+    #[test]
+    fn test_prompt_note_lists_limits() {
+        let constraints = EntryConstraints {
+            max_per_category: Some(5),
+            max_words: Some(20),
+            sentence_case: true,
+        };
+        let note = constraints.prompt_note().unwrap();
+        assert!(note.contains("At most 5 entries per category"));
+        assert!(note.contains("at most 20 words"));
+        assert!(note.contains("capital letter"));
+    }
+
+    #[test]
+    fn test_check_reports_each_violation() {
+        let constraints = EntryConstraints {
+            max_per_category: Some(1),
+            max_words: Some(3),
+            sentence_case: true,
+        };
+        let out = output(vec![
+            entry(ChangelogCategory::Added, "Export to PDF"),
+            entry(ChangelogCategory::Added, "import from a spreadsheet file"),
+            entry(ChangelogCategory::Fixed, "`--dry-run` writes nothing"),
+        ]);
+
+        let violations = constraints.check(&out);
+
+        assert_eq!(
+            violations,
+            vec![
+                Violation::TooManyEntries {
+                    category: ChangelogCategory::Added,
+                    count: 2,
+                    max: 1,
+                },
+                Violation::TooManyWords {
+                    description: "import from a spreadsheet file".to_string(),
+                    words: 5,
+                    max: 3,
+                },
+                Violation::NotSentenceCase {
+                    description: "import from a spreadsheet file".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_enforce_caps_categories_and_recases() {
+        let constraints = EntryConstraints {
+            max_per_category: Some(2),
+            max_words: Some(4),
+            sentence_case: true,
+        };
+        let mut out = output(vec![
+            entry(ChangelogCategory::Added, "First"),
+            entry(ChangelogCategory::Fixed, "über-long fix that keeps going"),
+            entry(ChangelogCategory::Added, "second"),
+            entry(ChangelogCategory::Added, "Third"),
+        ]);
+
+        let report = constraints.enforce(&mut out);
+
+        assert_eq!(
+            report,
+            EnforceReport {
+                dropped: 1,
+                recased: 2,
+                too_long: 1,
+            }
+        );
+        let descriptions: Vec<_> = out.entries.iter().map(|e| e.description.as_str()).collect();
+        assert_eq!(
+            descriptions,
+            vec!["First", "Über-long fix that keeps going", "Second"]
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_flags_or_env() {
+        temp_env::with_vars(
+            [
+                (MAX_PER_CATEGORY_ENV_VAR, Some("8")),
+                (MAX_WORDS_ENV_VAR, Some("0")),
+                (SENTENCE_CASE_ENV_VAR, Some("yes")),
+            ],
+            || {
+                let from_env = EntryConstraints::from_flags_or_env(None, None, false);
+                assert_eq!(from_env.max_per_category, Some(8));
+                assert_eq!(from_env.max_words, None);
+                assert!(from_env.sentence_case);
+
+                let from_flags = EntryConstraints::from_flags_or_env(Some(3), Some(12), false);
+                assert_eq!(from_flags.max_per_category, Some(3));
+                assert_eq!(from_flags.max_words, Some(12));
+            },
+        );
+    }
+}
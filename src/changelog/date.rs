@@ -0,0 +1,270 @@
+//! Release dates for changelog section headers.
+//!
+//! Dates default to the current UTC day, which gives "tomorrow's" date for
+//! releases cut late in the evening west of Greenwich (and vice versa). The
+//! timezone and the instant the date is taken from are configurable so that
+//! preview, writer, and from-history output agree.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use chrono::{DateTime, FixedOffset, Local, Utc};
+use tracing::warn;
+
+/// Environment variable for the timezone used in section dates.
+const TIMEZONE_ENV_VAR: &str = "KERYX_CHANGELOG_TIMEZONE";
+
+/// Environment variable selecting where the section date comes from.
+const DATE_SOURCE_ENV_VAR: &str = "KERYX_CHANGELOG_DATE_SOURCE";
+
+/// Timezone in which release dates are rendered.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateTimezone {
+    /// Coordinated Universal Time (the historical default).
+    #[default]
+    Utc,
+    /// The local timezone of the machine running keryx.
+    Local,
+    /// A fixed UTC offset such as `+02:00` or `-0530`.
+    Fixed(FixedOffset),
+}
+
+impl FromStr for DateTimezone {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        match trimmed.to_lowercase().as_str() {
+            "utc" | "z" => Ok(DateTimezone::Utc),
+            "local" => Ok(DateTimezone::Local),
+            _ => parse_offset(trimmed).map(DateTimezone::Fixed).ok_or_else(|| {
+                format!(
+                    "invalid timezone '{}' (expected 'utc', 'local', or an offset like '+02:00')",
+                    s
+                )
+            }),
+        }
+    }
+}
+
+impl fmt::Display for DateTimezone {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DateTimezone::Utc => f.write_str("utc"),
+            DateTimezone::Local => f.write_str("local"),
+            DateTimezone::Fixed(offset) => write!(f, "{}", offset),
+        }
+    }
+}
+
+/// Where the instant used for a release date comes from.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DateSource {
+    /// The moment the changelog is written (the historical default).
+    #[default]
+    Now,
+    /// The timestamp of the newest commit in the release.
+    Commit,
+}
+
+impl FromStr for DateSource {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "now" => Ok(DateSource::Now),
+            "commit" => Ok(DateSource::Commit),
+            _ => Err(format!(
+                "invalid date source '{}' (expected 'now' or 'commit')",
+                s
+            )),
+        }
+    }
+}
+
+/// How release dates in section headers are computed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ReleaseDateConfig {
+    pub timezone: DateTimezone,
+    pub source: DateSource,
+}
+
+impl ReleaseDateConfig {
+    /// Build from CLI flags, falling back to `KERYX_CHANGELOG_TIMEZONE` and
+    /// `KERYX_CHANGELOG_DATE_SOURCE` for anything not given on the command line.
+    ///
+    /// Logs a warning and uses the default if an environment variable is set
+    /// but invalid.
+    pub fn from_flags_or_env(timezone: Option<DateTimezone>, source: Option<DateSource>) -> Self {
+        Self {
+            timezone: timezone.unwrap_or_else(|| env_or_default(TIMEZONE_ENV_VAR)),
+            source: source.unwrap_or_else(|| env_or_default(DATE_SOURCE_ENV_VAR)),
+        }
+    }
+
+    /// Release date for a section, given the newest commit's timestamp.
+    ///
+    /// With [`DateSource::Commit`] the commit timestamp is used when known;
+    /// otherwise (or with [`DateSource::Now`]) the current time is used.
+    pub fn release_date(&self, newest_commit: Option<DateTime<Utc>>) -> String {
+        let instant = match (self.source, newest_commit) {
+            (DateSource::Commit, Some(ts)) => ts,
+            _ => Utc::now(),
+        };
+        format_date(instant, self.timezone)
+    }
+
+    /// Date of a historical release (e.g. a tag's commit) in the configured timezone.
+    pub fn historical_date(&self, instant: DateTime<Utc>) -> String {
+        format_date(instant, self.timezone)
+    }
+}
+
+/// Format `instant` as `YYYY-MM-DD` in the given timezone.
+pub fn format_date(instant: DateTime<Utc>, timezone: DateTimezone) -> String {
+    const FORMAT: &str = "%Y-%m-%d";
+    match timezone {
+        DateTimezone::Utc => instant.format(FORMAT).to_string(),
+        DateTimezone::Local => instant.with_timezone(&Local).format(FORMAT).to_string(),
+        DateTimezone::Fixed(offset) => instant.with_timezone(&offset).format(FORMAT).to_string(),
+    }
+}
+
+/// Parse `+HH:MM`, `-HHMM`, or `+HH` into a fixed offset.
+fn parse_offset(s: &str) -> Option<FixedOffset> {
+    let (sign, rest) = match s.as_bytes().first()? {
+        b'+' => (1, &s[1..]),
+        b'-' => (-1, &s[1..]),
+        _ => return None,
+    };
+    let digits: String = rest.chars().filter(|c| *c != ':').collect();
+    if !digits.chars().all(|c| c.is_ascii_digit()) {
+        return None;
+    }
+    let (hours, minutes) = match digits.len() {
+        2 => (digits.parse::<i32>().ok()?, 0),
+        4 => (
+            digits[..2].parse::<i32>().ok()?,
+            digits[2..].parse::<i32>().ok()?,
+        ),
+        _ => return None,
+    };
+    if minutes >= 60 {
+        return None;
+    }
+    FixedOffset::east_opt(sign * (hours * 3600 + minutes * 60))
+}
+
+/// Read a setting from the environment, warning on invalid values.
+fn env_or_default<T>(var: &str) -> T
+where
+    T: FromStr<Err = String> + Default,
+{
+    match env::var(var) {
+        Ok(v) if !v.is_empty() => v.parse().unwrap_or_else(|e| {
+            warn!("Invalid {} value: {}, using default", var, e);
+            T::default()
+        }),
+        _ => T::default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn instant(s: &str) -> DateTime<Utc> {
+        DateTime::parse_from_rfc3339(s).unwrap().with_timezone(&Utc)
+    }
+
+    #[test]
+    fn test_parse_timezone() {
+        assert_eq!("UTC".parse::<DateTimezone>().unwrap(), DateTimezone::Utc);
+        assert_eq!(
+            "local".parse::<DateTimezone>().unwrap(),
+            DateTimezone::Local
+        );
+        assert_eq!(
+            "+02:00".parse::<DateTimezone>().unwrap(),
+            DateTimezone::Fixed(FixedOffset::east_opt(7200).unwrap())
+        );
+        assert_eq!(
+            "-0530".parse::<DateTimezone>().unwrap(),
+            DateTimezone::Fixed(FixedOffset::west_opt(5 * 3600 + 30 * 60).unwrap())
+        );
+        assert!("Europe/Berlin".parse::<DateTimezone>().is_err());
+        assert!("+25:00".parse::<DateTimezone>().is_err());
+        assert!("+02:75".parse::<DateTimezone>().is_err());
+    }
+
+    #[test]
+    fn test_format_date_crosses_midnight() {
+        let late_evening_utc = instant("2024-03-01T23:30:00Z");
+        assert_eq!(
+            format_date(late_evening_utc, DateTimezone::Utc),
+            "2024-03-01"
+        );
+        assert_eq!(
+            format_date(late_evening_utc, "+02:00".parse().unwrap()),
+            "2024-03-02"
+        );
+        assert_eq!(
+            format_date(instant("2024-03-02T03:00:00Z"), "-08:00".parse().unwrap()),
+            "2024-03-01"
+        );
+    }
+
+    #[test]
+    fn test_release_date_from_commit() {
+        let config = ReleaseDateConfig {
+            timezone: DateTimezone::Utc,
+            source: DateSource::Commit,
+        };
+        assert_eq!(
+            config.release_date(Some(instant("2023-12-31T12:00:00Z"))),
+            "2023-12-31"
+        );
+    }
+
+    #[test]
+    fn test_release_date_now_ignores_commit() {
+        let config = ReleaseDateConfig::default();
+        let today = Utc::now().format("%Y-%m-%d").to_string();
+        assert_eq!(
+            config.release_date(Some(instant("2001-01-01T00:00:00Z"))),
+            today
+        );
+    }
+
+    #[test]
+    fn test_from_env() {
+        temp_env::with_vars(
+            [
+                (TIMEZONE_ENV_VAR, Some("+01:00")),
+                (DATE_SOURCE_ENV_VAR, Some("commit")),
+            ],
+            || {
+                let config = ReleaseDateConfig::from_flags_or_env(None, None);
+                assert_eq!(config.timezone, "+01:00".parse().unwrap());
+                assert_eq!(config.source, DateSource::Commit);
+            },
+        );
+    }
+
+    #[test]
+    fn test_flags_override_env() {
+        temp_env::with_var(TIMEZONE_ENV_VAR, Some("local"), || {
+            let config = ReleaseDateConfig::from_flags_or_env(Some(DateTimezone::Utc), None);
+            assert_eq!(config.timezone, DateTimezone::Utc);
+        });
+    }
+
+    #[test]
+    fn test_invalid_env_uses_default() {
+        temp_env::with_var(DATE_SOURCE_ENV_VAR, Some("tomorrow"), || {
+            let config = ReleaseDateConfig::from_flags_or_env(None, None);
+            assert_eq!(config.source, DateSource::Now);
+        });
+    }
+}
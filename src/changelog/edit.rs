@@ -0,0 +1,216 @@
+//! Hand edits of a generated section before it is written (`--edit`).
+//!
+//! [`render_for_edit`] turns the entries into the Markdown the changelog
+//! would get, with each entry's sources in a hidden marker so attribution
+//! survives rewording. [`parse_edited`] reads the edited text back and
+//! rejects anything that would not render as a Keep a Changelog section.
+
+use super::format::{ChangelogCategory, ChangelogEntry, ChangelogOutput};
+use super::merge::split_marker;
+use super::sort::EntrySort;
+use super::writer::entry_line;
+
+/// Comment lines explaining the format, ignored when parsing.
+const EDIT_HELP: &str = "\
+# Edit the release notes above. Lines starting with '# ' are ignored.
+# An optional highlights paragraph goes before the first category.
+# Categories: ### Added, Changed, Deprecated, Removed, Fixed or Security.
+# Entries are '- ' lines; keep the <!-- keryx:sources --> markers for attribution.
+# Delete every entry to write nothing.";
+
+/// The section's highlights and entries as editable Markdown.
+pub fn render_for_edit(output: &ChangelogOutput) -> String {
+    let mut text = String::new();
+    if let Some(highlights) = output.highlights_paragraph() {
+        text.push_str(&highlights);
+        text.push_str("\n\n");
+    }
+    for (category, entries) in output.entries_by_category() {
+        text.push_str(&format!("### {}\n\n", category.as_str()));
+        for entry in entries {
+            text.push_str(&entry_line(entry, EntrySort::Source, None, true));
+            text.push('\n');
+        }
+        text.push('\n');
+    }
+    text.push_str(EDIT_HELP);
+    text.push('\n');
+    text
+}
+
+/// Read the edited section back.
+///
+/// Entries keep the scope and importance of the generated entry with the
+/// same sources (or, without sources, the same text). Fails on unknown
+/// categories, entries outside a category, other headings, and text between
+/// entries that is not an indented continuation.
+pub fn parse_edited(text: &str, original: &ChangelogOutput) -> Result<ChangelogOutput, String> {
+    let mut highlights: Vec<&str> = Vec::new();
+    // Category and text (with any marker) of each entry, continuations joined
+    let mut items: Vec<(ChangelogCategory, usize, String)> = Vec::new();
+    let mut category: Option<ChangelogCategory> = None;
+
+    for (index, line) in text.lines().enumerate() {
+        let number = index + 1;
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed == "#" || line.starts_with("# ") {
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix("### ") {
+            category = Some(
+                heading
+                    .trim()
+                    .parse()
+                    .map_err(|e| format!("line {}: {}", number, e))?,
+            );
+            continue;
+        }
+        if line.starts_with('#') {
+            return Err(format!(
+                "line {}: only ### category headings are allowed",
+                number
+            ));
+        }
+
+        let item = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "));
+        match (item, &category) {
+            (Some(item), Some(category)) => {
+                items.push((category.clone(), number, item.to_string()));
+            }
+            (Some(_), None) => {
+                return Err(format!(
+                    "line {}: entry outside a ### category heading",
+                    number
+                ));
+            }
+            (None, None) => highlights.push(trimmed),
+            (None, Some(_)) => match items.last_mut() {
+                Some((_, _, item)) if line.starts_with([' ', '\t']) => {
+                    item.push(' ');
+                    item.push_str(trimmed);
+                }
+                _ => {
+                    return Err(format!(
+                        "line {}: expected a '- ' entry under the category",
+                        number
+                    ));
+                }
+            },
+        }
+    }
+
+    let mut entries = Vec::with_capacity(items.len());
+    for (category, number, item) in items {
+        let (description, sources) = split_marker(&item);
+        if description.is_empty() {
+            return Err(format!("line {}: empty entry", number));
+        }
+        let sources: Vec<String> = sources.into_iter().map(str::to_string).collect();
+        let generated = original.entries.iter().find(|generated| {
+            if sources.is_empty() {
+                generated.description == description
+            } else {
+                generated.sources == sources
+            }
+        });
+        entries.push(ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources,
+            scope: generated.and_then(|g| g.scope.clone()),
+            importance: generated.and_then(|g| g.importance),
+        });
+    }
+
+    Ok(ChangelogOutput {
+        entries,
+        highlights: (!highlights.is_empty()).then(|| highlights.join(" ")),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn output() -> ChangelogOutput {
+        ChangelogOutput {
+            entries: vec![
+                ChangelogEntry {
+                    category: ChangelogCategory::Fixed,
+                    description: "Fix crash on empty tags".to_string(),
+                    sources: vec!["#12".to_string()],
+                    scope: Some("cli".to_string()),
+                    importance: Some(4),
+                },
+                ChangelogEntry {
+                    category: ChangelogCategory::Added,
+                    description: "Add dark mode".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: Some(2),
+                },
+            ],
+            highlights: Some("A small release.".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let output = output();
+        let rendered = render_for_edit(&output);
+        assert!(rendered.starts_with(
+            "A small release.\n\n### Added\n\n- Add dark mode\n\n### Fixed\n\n- Fix crash on empty tags <!-- keryx:sources #12 -->\n"
+        ));
+
+        let parsed = parse_edited(&rendered, &output).unwrap();
+        assert_eq!(parsed.highlights.as_deref(), Some("A small release."));
+        assert_eq!(parsed.entries.len(), 2);
+        assert_eq!(parsed.entries[0].description, "Add dark mode");
+        assert_eq!(parsed.entries[0].importance, Some(2));
+        assert_eq!(parsed.entries[1].sources, vec!["#12"]);
+    }
+
+    #[test]
+    fn test_reworded_entry_keeps_metadata_by_sources() {
+        let edited = "### Security\n\n- Fix a crash when a tag\n  has no message <!-- keryx:sources #12 -->\n- Mask tokens in logs\n";
+        let parsed = parse_edited(edited, &output()).unwrap();
+        assert_eq!(parsed.highlights, None);
+        assert_eq!(parsed.entries.len(), 2);
+        let first = &parsed.entries[0];
+        assert_eq!(first.category, ChangelogCategory::Security);
+        assert_eq!(first.description, "Fix a crash when a tag has no message");
+        assert_eq!(first.sources, vec!["#12"]);
+        assert_eq!(first.scope.as_deref(), Some("cli"));
+        assert_eq!(parsed.entries[1].sources, Vec::<String>::new());
+    }
+
+    #[test]
+    fn test_rejects_invalid_sections() {
+        let original = output();
+        let error = |text| parse_edited(text, &original).unwrap_err();
+        assert_eq!(
+            error("### Improved\n\n- Faster\n"),
+            "line 1: Unknown category: Improved"
+        );
+        assert_eq!(
+            error("- Faster\n"),
+            "line 1: entry outside a ### category heading"
+        );
+        assert_eq!(
+            error("## [2.0.0]\n"),
+            "line 1: only ### category headings are allowed"
+        );
+        assert_eq!(
+            error("### Added\n\n- New\nStray text\n"),
+            "line 4: expected a '- ' entry under the category"
+        );
+        assert!(
+            parse_edited("# only comments\n", &original)
+                .unwrap()
+                .entries
+                .is_empty()
+        );
+    }
+}
@@ -0,0 +1,129 @@
+//! Guard against implausibly empty generations.
+//!
+//! Providers occasionally answer a range full of features and fixes with a
+//! valid but empty entry list. When the input has enough `feat`/`fix` (or
+//! breaking) commits that zero entries cannot be right, the entries are
+//! regenerated once with an explicit instruction; only an empty second
+//! attempt is reported as empty output.
+
+use tracing::{debug, warn};
+
+use crate::changelog::constraints::{EntryConstraints, generate_constrained};
+use crate::changelog::sanitize::SanitizeOptions;
+use crate::git::{CommitType, ParsedCommit};
+use crate::llm::{LlmCompletion, LlmError, LlmRouter};
+
+/// Notable commits needed before an empty result counts as implausible.
+const MIN_NOTABLE_COMMITS: usize = 2;
+
+/// Commits that almost always deserve an entry: features, fixes and
+/// breaking changes.
+pub fn notable_commit_count(commits: &[ParsedCommit]) -> usize {
+    commits
+        .iter()
+        .filter(|c| c.breaking || matches!(c.commit_type, Some(CommitType::Feat | CommitType::Fix)))
+        .count()
+}
+
+/// Whether an empty entry list is implausible for `commits`.
+pub fn expects_entries(commits: &[ParsedCommit]) -> bool {
+    notable_commit_count(commits) >= MIN_NOTABLE_COMMITS
+}
+
+/// Instruction appended to the prompt when regenerating an empty result.
+fn empty_retry_note(notable: usize) -> String {
+    format!(
+        "\n\n## Empty Output Correction\nA previous attempt returned no entries, but this range contains {} feature, fix or breaking commits. \
+        Those are user-facing by definition: write at least one entry for each of them (merging commits that describe the same change). \
+        Return an empty `entries` array only if every one of them is purely internal.",
+        notable
+    )
+}
+
+/// [`generate_constrained`], regenerated once if the result is empty although
+/// `commits` make entries all but certain.
+///
+/// The second attempt is returned whatever it holds, so callers still see an
+/// empty output when the provider insists.
+pub async fn generate_guarded(
+    llm: &mut LlmRouter,
+    prompt: &str,
+    options: SanitizeOptions,
+    constraints: &EntryConstraints,
+    commits: &[ParsedCommit],
+) -> Result<LlmCompletion, LlmError> {
+    let completion = generate_constrained(llm, prompt, options, constraints).await?;
+    if !completion.output.entries.is_empty() || !expects_entries(commits) {
+        return Ok(completion);
+    }
+
+    let notable = notable_commit_count(commits);
+    eprintln!(
+        "\x1b[33m⚠ No entries generated for {} feature/fix commits, regenerating...\x1b[0m",
+        notable
+    );
+    let retry_prompt = format!("{}{}", prompt, empty_retry_note(notable));
+    match generate_constrained(llm, &retry_prompt, options, constraints).await {
+        Ok(retried) => {
+            if retried.output.entries.is_empty() {
+                debug!("Regeneration also produced no entries");
+            }
+            Ok(retried)
+        }
+        Err(e) => {
+            warn!("Regeneration failed: {}", e.summary());
+            Ok(completion)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::Footers;
+    use chrono::Utc;
+
+    fn commit(message: &str, commit_type: Option<CommitType>, breaking: bool) -> ParsedCommit {
+        ParsedCommit {
+            hash: "abc1234".to_string(),
+            message: message.to_string(),
+            commit_type,
+            scope: None,
+            breaking,
+            timestamp: Utc::now(),
+            footers: Footers::default(),
+        }
+    }
+
+    #[test]
+    fn test_notable_commit_count() {
+        let commits = vec![
+            commit("feat: dark mode", Some(CommitType::Feat), false),
+            commit("fix: crash", Some(CommitType::Fix), false),
+            commit("chore: deps", Some(CommitType::Chore), false),
+            commit("refactor!: drop v1 api", Some(CommitType::Refactor), true),
+            commit("update readme", None, false),
+        ];
+        assert_eq!(notable_commit_count(&commits), 3);
+        assert!(expects_entries(&commits));
+    }
+
+    #[test]
+    fn test_single_or_internal_commits_may_be_empty() {
+        let single = vec![commit("fix: typo", Some(CommitType::Fix), false)];
+        assert!(!expects_entries(&single));
+
+        let internal = vec![
+            commit("chore: deps", Some(CommitType::Chore), false),
+            commit("ci: cache", Some(CommitType::Ci), false),
+        ];
+        assert!(!expects_entries(&internal));
+    }
+
+    #[test]
+    fn test_retry_note_names_count() {
+        let note = empty_retry_note(4);
+        assert!(note.contains("## Empty Output Correction"));
+        assert!(note.contains("4 feature, fix or breaking commits"));
+    }
+}
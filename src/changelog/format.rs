@@ -75,12 +75,32 @@ impl std::str::FromStr for ChangelogCategory {
 pub struct ChangelogEntry {
     pub category: ChangelogCategory,
     pub description: String,
+    /// PR refs (`#12`) or short commit hashes the entry is based on, when
+    /// the prompt asked for them. Used for attribution.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sources: Vec<String>,
+    /// Area of the product the entry affects (e.g. `cli`), when the prompt
+    /// asked for it. Used to sort by scope.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<String>,
+    /// Importance to users from 1 (minor) to 5 (major), when the prompt
+    /// asked for it. Used to sort by importance.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        deserialize_with = "crate::changelog::sort::deserialize_importance"
+    )]
+    pub importance: Option<u8>,
 }
 
 /// Output from Claude containing changelog entries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChangelogOutput {
     pub entries: Vec<ChangelogEntry>,
+    /// Optional 2-3 sentence summary of the release for end users
+    /// (requested with `--highlights`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub highlights: Option<String>,
 }
 
 impl ChangelogOutput {
@@ -101,6 +121,17 @@ impl ChangelogOutput {
         grouped.into_values().collect()
     }
 
+    /// Highlights normalized to a single paragraph, if non-empty.
+    ///
+    /// Collapses line breaks and runs of whitespace so the paragraph cannot
+    /// break the section structure (e.g. by starting a new heading).
+    pub fn highlights_paragraph(&self) -> Option<String> {
+        let text = self.highlights.as_deref()?;
+        let paragraph = text.split_whitespace().collect::<Vec<_>>().join(" ");
+        let paragraph = paragraph.trim_start_matches(['#', '-', '*', '>', ' ']);
+        (!paragraph.is_empty()).then(|| paragraph.to_string())
+    }
+
     /// Count entries by type for summary output.
     pub fn count_by_type(&self) -> Vec<(String, usize)> {
         let grouped = self.entries_by_category();
@@ -144,6 +175,24 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_highlights_paragraph_is_single_line() {
+        let output = ChangelogOutput {
+            entries: vec![],
+            highlights: Some("## Big release\n\nFaster   builds.\nNew UI.".to_string()),
+        };
+        assert_eq!(
+            output.highlights_paragraph(),
+            Some("Big release Faster builds. New UI.".to_string())
+        );
+
+        let blank = ChangelogOutput {
+            entries: vec![],
+            highlights: Some("  \n ".to_string()),
+        };
+        assert_eq!(blank.highlights_paragraph(), None);
+    }
+
     #[test]
     fn test_entries_by_category() {
         let output = ChangelogOutput {
@@ -151,12 +200,19 @@ mod tests {
                 ChangelogEntry {
                     category: ChangelogCategory::Fixed,
                     description: "Bug fix".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
                 },
                 ChangelogEntry {
                     category: ChangelogCategory::Added,
                     description: "New feature".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
                 },
             ],
+            highlights: None,
         };
 
         let grouped = output.entries_by_category();
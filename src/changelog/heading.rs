@@ -0,0 +1,383 @@
+//! Version heading templates and release codenames.
+//!
+//! The default heading is Keep a Changelog's `## [{version}] - {date}`. A
+//! custom template may add a `{codename}` placeholder, e.g.
+//! `## [{version}] — {codename} — {date}`. Codenames come from the
+//! `--codename` flag, a configured sequence (`KERYX_CODENAMES`), or an LLM
+//! suggestion.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use tracing::warn;
+
+/// Heading template used when none is configured.
+pub const DEFAULT_HEADING_TEMPLATE: &str = "## [{version}] - {date}";
+
+/// Environment variable holding a custom heading template.
+const HEADING_TEMPLATE_ENV_VAR: &str = "KERYX_HEADING_TEMPLATE";
+
+/// Environment variable holding a comma-separated codename sequence.
+const CODENAMES_ENV_VAR: &str = "KERYX_CODENAMES";
+
+const VERSION_PLACEHOLDER: &str = "{version}";
+const DATE_PLACEHOLDER: &str = "{date}";
+const CODENAME_PLACEHOLDER: &str = "{codename}";
+
+/// Characters treated as separators around an omitted `{codename}`.
+const SEPARATOR_CHARS: &[char] = &[' ', '-', '—', '–', '|', ':', '·', ','];
+
+/// A validated version heading template.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HeadingTemplate(String);
+
+impl Default for HeadingTemplate {
+    fn default() -> Self {
+        Self(DEFAULT_HEADING_TEMPLATE.to_string())
+    }
+}
+
+impl FromStr for HeadingTemplate {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if !s.starts_with("## ") {
+            return Err(format!(
+                "invalid heading template '{}' (must start with '## ')",
+                s
+            ));
+        }
+        if !s.contains(VERSION_PLACEHOLDER) {
+            return Err(format!(
+                "invalid heading template '{}' (must contain {})",
+                s, VERSION_PLACEHOLDER
+            ));
+        }
+        if s.contains('\n') {
+            return Err("invalid heading template (must be a single line)".to_string());
+        }
+        Ok(Self(s.to_string()))
+    }
+}
+
+impl fmt::Display for HeadingTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl HeadingTemplate {
+    /// Use the `--heading-template` flag if given, else `KERYX_HEADING_TEMPLATE`,
+    /// else the default. Invalid environment values are logged and ignored.
+    pub fn from_flag_or_env(flag: Option<HeadingTemplate>) -> Self {
+        if let Some(template) = flag {
+            return template;
+        }
+        match env::var(HEADING_TEMPLATE_ENV_VAR) {
+            Ok(v) if !v.is_empty() => v.parse().unwrap_or_else(|e| {
+                warn!(
+                    "Invalid {} value: {}, using default",
+                    HEADING_TEMPLATE_ENV_VAR, e
+                );
+                Self::default()
+            }),
+            _ => Self::default(),
+        }
+    }
+
+    /// Whether the template renders a codename.
+    pub fn uses_codename(&self) -> bool {
+        self.0.contains(CODENAME_PLACEHOLDER)
+    }
+
+    /// Whether the template renders a release date.
+    pub fn uses_date(&self) -> bool {
+        self.0.contains(DATE_PLACEHOLDER)
+    }
+
+    /// Render the heading line (without trailing newline).
+    ///
+    /// When no codename is available, the `{codename}` placeholder is dropped
+    /// together with the separator preceding it, so
+    /// `## [{version}] — {codename} — {date}` becomes `## [1.0.0] — 2024-01-01`.
+    pub fn render(&self, version: &str, date: &str, codename: Option<&str>) -> String {
+        let template = match codename {
+            Some(_) => self.0.clone(),
+            None => remove_codename_placeholder(&self.0),
+        };
+        template
+            .replace(VERSION_PLACEHOLDER, version)
+            .replace(DATE_PLACEHOLDER, date)
+            .replace(CODENAME_PLACEHOLDER, codename.unwrap_or_default())
+    }
+
+    /// Codename in a heading line rendered from this template, if any.
+    ///
+    /// Matches the literal text around `{codename}`, so
+    /// `## [{version}] — {codename} — {date}` yields `Aurora` from
+    /// `## [1.0.0] — Aurora — 2024-01-01`.
+    pub fn extract_codename<'a>(&self, line: &'a str) -> Option<&'a str> {
+        let pos = self.0.find(CODENAME_PLACEHOLDER)?;
+        let prefix = literal_suffix(&self.0[..pos]);
+        let suffix = literal_prefix(&self.0[pos + CODENAME_PLACEHOLDER.len()..]);
+
+        let start = line.find(prefix)? + prefix.len();
+        let rest = &line[start..];
+        let end = if suffix.is_empty() {
+            rest.len()
+        } else {
+            rest.find(suffix)?
+        };
+        let codename = rest[..end].trim();
+        (!codename.is_empty()).then_some(codename)
+    }
+}
+
+/// Literal text after the last placeholder in `s`.
+fn literal_suffix(s: &str) -> &str {
+    s.rfind('}').map_or(s, |i| &s[i + 1..])
+}
+
+/// Literal text before the first placeholder in `s`.
+fn literal_prefix(s: &str) -> &str {
+    s.find('{').map_or(s, |i| &s[..i])
+}
+
+/// Codenames already used in an existing changelog: those in headings
+/// rendered from `heading`, plus `**Codename:**` attributes.
+pub fn used_codenames(existing_changelog: &str, heading: &HeadingTemplate) -> Vec<String> {
+    const ATTRIBUTE: &str = "**Codename:** ";
+    let mut used: Vec<String> = Vec::new();
+    for line in existing_changelog.lines() {
+        let found = if line.starts_with("## ") {
+            heading.extract_codename(line)
+        } else {
+            line.find(ATTRIBUTE).map(|i| {
+                let value = &line[i + ATTRIBUTE.len()..];
+                value.split(" · ").next().unwrap_or(value).trim()
+            })
+        };
+        if let Some(name) = found
+            && !name.is_empty()
+            && !used.iter().any(|u| u == name)
+        {
+            used.push(name.to_string());
+        }
+    }
+    used
+}
+
+/// Where a release's codename comes from.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CodenameConfig {
+    /// Codename given explicitly via `--codename`.
+    pub explicit: Option<String>,
+    /// Configured sequence; the first unused name is taken.
+    pub sequence: Vec<String>,
+    /// Ask the LLM for a codename when neither of the above yields one.
+    pub suggest: bool,
+}
+
+impl CodenameConfig {
+    /// Build from CLI flags, reading the sequence from `KERYX_CODENAMES`.
+    pub fn from_flags_or_env(explicit: Option<String>, suggest: bool) -> Self {
+        Self {
+            explicit,
+            sequence: codename_sequence_from_env(),
+            suggest,
+        }
+    }
+
+    /// Codename from the flag or the sequence (without consulting the LLM).
+    pub fn resolve(&self, existing_changelog: Option<&str>) -> Option<String> {
+        self.explicit
+            .clone()
+            .or_else(|| next_codename(&self.sequence, existing_changelog))
+    }
+}
+
+/// Drop `{codename}` (with any brackets wrapping it) and the separator run in
+/// front of it. A leading placeholder (`## {codename} - ...`) drops the
+/// separator after it instead.
+fn remove_codename_placeholder(template: &str) -> String {
+    let Some(pos) = template.find(CODENAME_PLACEHOLDER) else {
+        return template.to_string();
+    };
+    let mut head = &template[..pos];
+    let mut tail = &template[pos + CODENAME_PLACEHOLDER.len()..];
+    for (open, close) in [('(', ')'), ('[', ']'), ('"', '"')] {
+        if let (Some(h), Some(t)) = (head.strip_suffix(open), tail.strip_prefix(close)) {
+            head = h;
+            tail = t;
+            break;
+        }
+    }
+
+    let trimmed_head = head.trim_end_matches(SEPARATOR_CHARS);
+    if trimmed_head.ends_with('#') {
+        format!("{}{}", head, tail.trim_start_matches(SEPARATOR_CHARS))
+    } else {
+        format!("{}{}", trimmed_head, tail)
+    }
+}
+
+/// Codename sequence configured via `KERYX_CODENAMES`.
+pub fn codename_sequence_from_env() -> Vec<String> {
+    match env::var(CODENAMES_ENV_VAR) {
+        Ok(v) => v
+            .split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::to_string)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// First codename in `sequence` not already used in the changelog.
+pub fn next_codename(sequence: &[String], existing_changelog: Option<&str>) -> Option<String> {
+    sequence
+        .iter()
+        .find(|name| existing_changelog.is_none_or(|content| !content.contains(name.as_str())))
+        .cloned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_template_renders_keep_a_changelog_heading() {
+        let heading = HeadingTemplate::default().render("1.2.0", "2024-01-01", None);
+        assert_eq!(heading, "## [1.2.0] - 2024-01-01");
+    }
+
+    #[test]
+    fn test_render_with_codename() {
+        let template: HeadingTemplate = "## [{version}] — {codename} — {date}".parse().unwrap();
+        assert_eq!(
+            template.render("2.0.0", "2024-06-01", Some("Aurora")),
+            "## [2.0.0] — Aurora — 2024-06-01"
+        );
+    }
+
+    #[test]
+    fn test_render_without_codename_drops_placeholder() {
+        let template: HeadingTemplate = "## [{version}] — {codename} — {date}".parse().unwrap();
+        assert_eq!(
+            template.render("2.0.0", "2024-06-01", None),
+            "## [2.0.0] — 2024-06-01"
+        );
+
+        let trailing: HeadingTemplate = "## [{version}] - {date} ({codename})".parse().unwrap();
+        assert_eq!(
+            trailing.render("2.0.0", "2024-06-01", None),
+            "## [2.0.0] - 2024-06-01"
+        );
+
+        let leading: HeadingTemplate = "## {codename} - [{version}] - {date}".parse().unwrap();
+        assert_eq!(
+            leading.render("2.0.0", "2024-06-01", None),
+            "## [2.0.0] - 2024-06-01"
+        );
+    }
+
+    #[test]
+    fn test_template_validation() {
+        assert!("[{version}]".parse::<HeadingTemplate>().is_err());
+        assert!("## {date}".parse::<HeadingTemplate>().is_err());
+        assert!("## [{version}]".parse::<HeadingTemplate>().is_ok());
+    }
+
+    #[test]
+    fn test_template_from_env() {
+        temp_env::with_var(
+            HEADING_TEMPLATE_ENV_VAR,
+            Some("## {version} ({date})"),
+            || {
+                let template = HeadingTemplate::from_flag_or_env(None);
+                assert_eq!(
+                    template.render("1.0.0", "2024-01-01", None),
+                    "## 1.0.0 (2024-01-01)"
+                );
+            },
+        );
+        temp_env::with_var(HEADING_TEMPLATE_ENV_VAR, Some("no version here"), || {
+            assert_eq!(
+                HeadingTemplate::from_flag_or_env(None),
+                HeadingTemplate::default()
+            );
+        });
+    }
+
+    #[test]
+    fn test_next_codename_skips_used_names() {
+        let sequence = vec![
+            "Aurora".to_string(),
+            "Borealis".to_string(),
+            "Cirrus".to_string(),
+        ];
+        let existing = "## [1.0.0] — Aurora — 2024-01-01\n";
+        assert_eq!(
+            next_codename(&sequence, Some(existing)),
+            Some("Borealis".to_string())
+        );
+        assert_eq!(next_codename(&sequence, None), Some("Aurora".to_string()));
+        assert_eq!(
+            next_codename(&sequence, Some("Aurora Borealis Cirrus")),
+            None
+        );
+    }
+
+    #[test]
+    fn test_extract_codename() {
+        let template: HeadingTemplate = "## [{version}] — {codename} — {date}".parse().unwrap();
+        assert_eq!(
+            template.extract_codename("## [1.0.0] — Aurora — 2024-01-01"),
+            Some("Aurora")
+        );
+        assert_eq!(template.extract_codename("## [1.0.0] - 2024-01-01"), None);
+        assert_eq!(
+            HeadingTemplate::default().extract_codename("## [1.0.0] - 2024-01-01"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_used_codenames() {
+        let template: HeadingTemplate = "## [{version}] — {codename} — {date}".parse().unwrap();
+        let content = "# Changelog\n\n## [1.1.0] — Borealis — 2024-02-01\n\n## [1.0.0] - 2024-01-01\n\n**Codename:** Aurora · **Release manager:** @alice\n";
+        assert_eq!(
+            used_codenames(content, &template),
+            vec!["Borealis".to_string(), "Aurora".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_codename_config_prefers_explicit() {
+        let config = CodenameConfig {
+            explicit: Some("Zephyr".to_string()),
+            sequence: vec!["Aurora".to_string()],
+            suggest: false,
+        };
+        assert_eq!(config.resolve(None), Some("Zephyr".to_string()));
+
+        let config = CodenameConfig {
+            explicit: None,
+            ..config
+        };
+        assert_eq!(config.resolve(None), Some("Aurora".to_string()));
+        assert_eq!(config.resolve(Some("Aurora")), None);
+    }
+
+    #[test]
+    fn test_codename_sequence_from_env() {
+        temp_env::with_var(CODENAMES_ENV_VAR, Some("Aurora, Borealis,,"), || {
+            assert_eq!(
+                codename_sequence_from_env(),
+                vec!["Aurora".to_string(), "Borealis".to_string()]
+            );
+        });
+    }
+}
@@ -0,0 +1,212 @@
+//! Checkpoints for `init --from-history`.
+//!
+//! Generating a changelog from full history makes one LLM call per tag. Each
+//! generated section is persisted to `.keryx/history-cache/<tag>.json` as soon
+//! as it is produced, so a run that fails at tag 37 of 60 resumes at tag 37
+//! instead of starting over.
+//!
+//! Entries store the LLM output rather than rendered markdown, so heading
+//! templates and date settings still apply on resume. Each entry records the
+//! tagged commit; a tag that was moved since the entry was written is treated
+//! as a cache miss.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+use tracing::debug;
+
+use crate::changelog::ChangelogOutput;
+use crate::error::HistoryCacheError;
+use crate::journal::{JOURNAL_DIR, ensure_state_dir};
+
+/// Directory name of the cache inside [`JOURNAL_DIR`].
+pub const HISTORY_CACHE_DIR: &str = "history-cache";
+
+/// A cached section for one tag.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+struct CachedSection {
+    tag: String,
+    commit: String,
+    output: ChangelogOutput,
+}
+
+/// Handle to the history cache of a single repository.
+#[derive(Debug, Clone)]
+pub struct HistoryCache {
+    dir: PathBuf,
+}
+
+impl HistoryCache {
+    /// Cache stored under `<repo_root>/.keryx/history-cache`.
+    pub fn for_repo(repo_root: &Path) -> Self {
+        Self {
+            dir: repo_root.join(JOURNAL_DIR).join(HISTORY_CACHE_DIR),
+        }
+    }
+
+    /// Cache for the repository at `repo`, if it has a working directory.
+    pub fn for_repository(repo: &git2::Repository) -> Option<Self> {
+        repo.workdir().map(Self::for_repo)
+    }
+
+    /// Directory holding the cache entries.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn entry_path(&self, tag: &str) -> PathBuf {
+        // Tag names may contain '/', which must not create subdirectories
+        let file_name: String = tag
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        self.dir.join(format!("{}.json", file_name))
+    }
+
+    /// Load the cached output for `tag`, if present and still pointing at
+    /// `commit`.
+    ///
+    /// Unreadable or stale entries are treated as misses.
+    pub fn load(&self, tag: &str, commit: git2::Oid) -> Option<ChangelogOutput> {
+        let path = self.entry_path(tag);
+        let content = fs::read_to_string(&path).ok()?;
+        let cached: CachedSection = match serde_json::from_str(&content) {
+            Ok(cached) => cached,
+            Err(e) => {
+                debug!("Ignoring malformed history cache {}: {}", path.display(), e);
+                return None;
+            }
+        };
+
+        if cached.tag != tag || cached.commit != commit.to_string() {
+            debug!("Ignoring stale history cache entry for {}", tag);
+            return None;
+        }
+        Some(cached.output)
+    }
+
+    /// Persist the output generated for `tag`.
+    pub fn store(
+        &self,
+        tag: &str,
+        commit: git2::Oid,
+        output: &ChangelogOutput,
+    ) -> Result<(), HistoryCacheError> {
+        if let Some(state_dir) = self.dir.parent() {
+            ensure_state_dir(state_dir).map_err(|source| HistoryCacheError::CreateDirFailed {
+                path: state_dir.to_path_buf(),
+                source,
+            })?;
+        }
+        fs::create_dir_all(&self.dir).map_err(|source| HistoryCacheError::CreateDirFailed {
+            path: self.dir.clone(),
+            source,
+        })?;
+
+        let entry = CachedSection {
+            tag: tag.to_string(),
+            commit: commit.to_string(),
+            output: output.clone(),
+        };
+        let json =
+            serde_json::to_string_pretty(&entry).map_err(HistoryCacheError::SerializationFailed)?;
+
+        // Write atomically so an interrupted run never leaves a truncated entry
+        let path = self.entry_path(tag);
+        let write_err = |source| HistoryCacheError::WriteFailed {
+            path: path.clone(),
+            source,
+        };
+        crate::atomic::write_atomic(&path, json).map_err(write_err)?;
+
+        Ok(())
+    }
+
+    /// Remove all cached entries.
+    pub fn clear(&self) -> Result<(), HistoryCacheError> {
+        match fs::remove_dir_all(&self.dir) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(source) => Err(HistoryCacheError::ClearFailed {
+                path: self.dir.clone(),
+                source,
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::{ChangelogCategory, ChangelogEntry};
+    use tempfile::TempDir;
+
+    fn output(description: &str) -> ChangelogOutput {
+        ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Added,
+                description: description.to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: None,
+        }
+    }
+
+    fn oid(byte: u8) -> git2::Oid {
+        git2::Oid::from_bytes(&[byte; 20]).unwrap()
+    }
+
+    #[test]
+    fn test_store_and_load_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let cache = HistoryCache::for_repo(dir.path());
+
+        assert!(cache.load("v1.0.0", oid(1)).is_none());
+        cache.store("v1.0.0", oid(1), &output("Dark mode")).unwrap();
+
+        let loaded = cache.load("v1.0.0", oid(1)).unwrap();
+        assert_eq!(loaded.entries[0].description, "Dark mode");
+        assert!(dir.path().join(".keryx/.gitignore").exists());
+    }
+
+    #[test]
+    fn test_moved_tag_is_a_miss() {
+        let dir = TempDir::new().unwrap();
+        let cache = HistoryCache::for_repo(dir.path());
+
+        cache.store("v1.0.0", oid(1), &output("Dark mode")).unwrap();
+        assert!(cache.load("v1.0.0", oid(2)).is_none());
+    }
+
+    #[test]
+    fn test_tag_names_with_slashes_stay_in_cache_dir() {
+        let dir = TempDir::new().unwrap();
+        let cache = HistoryCache::for_repo(dir.path());
+
+        cache
+            .store("release/v1.0.0", oid(1), &output("Dark mode"))
+            .unwrap();
+        assert!(cache.dir().join("release_v1.0.0.json").exists());
+        assert!(cache.load("release/v1.0.0", oid(1)).is_some());
+    }
+
+    #[test]
+    fn test_clear_removes_entries() {
+        let dir = TempDir::new().unwrap();
+        let cache = HistoryCache::for_repo(dir.path());
+
+        cache.clear().unwrap();
+        cache.store("v1.0.0", oid(1), &output("Dark mode")).unwrap();
+        cache.clear().unwrap();
+        assert!(cache.load("v1.0.0", oid(1)).is_none());
+    }
+}
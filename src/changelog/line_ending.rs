@@ -0,0 +1,61 @@
+//! Line ending detection and restoration.
+//!
+//! Changelogs are parsed and edited with LF line endings. A file checked out
+//! with CRLF (Windows, `core.autocrlf`) is written back with CRLF, so an
+//! inserted section never leaves it with mixed line endings.
+
+/// Line ending used by a file.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LineEnding {
+    #[default]
+    Lf,
+    CrLf,
+}
+
+impl LineEnding {
+    /// The line ending used by most lines of `content`; LF for content
+    /// without line breaks.
+    pub fn detect(content: &str) -> Self {
+        let crlf = content.matches("\r\n").count();
+        let lf = content.matches('\n').count() - crlf;
+        if crlf > lf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        }
+    }
+
+    /// Convert `content` to this line ending, whatever it used before.
+    pub fn apply(self, content: &str) -> String {
+        let normalized = normalize(content);
+        match self {
+            LineEnding::Lf => normalized,
+            LineEnding::CrLf => normalized.replace('\n', "\r\n"),
+        }
+    }
+}
+
+/// `content` with every CRLF replaced by LF.
+pub fn normalize(content: &str) -> String {
+    content.replace("\r\n", "\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_detect() {
+        assert_eq!(LineEnding::detect("a\r\nb\r\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("a\nb\n"), LineEnding::Lf);
+        assert_eq!(LineEnding::detect("a\r\nb\r\nc\n"), LineEnding::CrLf);
+        assert_eq!(LineEnding::detect("no newline"), LineEnding::Lf);
+    }
+
+    #[test]
+    fn test_apply_never_mixes() {
+        let mixed = "a\r\nb\nc\r\n";
+        assert_eq!(LineEnding::CrLf.apply(mixed), "a\r\nb\r\nc\r\n");
+        assert_eq!(LineEnding::Lf.apply(mixed), "a\nb\nc\n");
+    }
+}
@@ -0,0 +1,504 @@
+//! Idempotent re-runs: add newly generated entries to an existing release
+//! section without touching the ones already there.
+//!
+//! Entries written with `--merge` end with a hidden `<!-- keryx:sources ... -->`
+//! marker listing the PRs and commits they are based on. A re-run keys the new
+//! entries by those sources and only adds the ones whose sources are not in the
+//! section yet, so edited wording survives. Entries without a marker (written
+//! by hand or before `--merge` was used) are matched by their text instead.
+//! `keryx unreleased` merges into the `[Unreleased]` section the same way.
+
+use semver::Version;
+
+use super::aliases::CategoryAliases;
+use super::attribution::Attribution;
+use super::format::{ChangelogCategory, ChangelogEntry, ChangelogOutput};
+use super::parser::{find_insertion_point, find_version_token};
+use super::sort::EntrySort;
+use super::writer::entry_line;
+
+/// Start of the hidden marker listing an entry's sources.
+pub const SOURCES_MARKER_PREFIX: &str = "<!-- keryx:sources ";
+
+/// End of an HTML comment.
+const MARKER_SUFFIX: &str = "-->";
+
+/// Shortest commit hash prefix that identifies a source.
+const MIN_HASH_LEN: usize = 7;
+
+/// The hidden marker for `entry`'s sources, `None` when it cites none.
+pub fn sources_marker(entry: &ChangelogEntry) -> Option<String> {
+    let keys: Vec<&str> = entry
+        .sources
+        .iter()
+        .map(|s| s.trim())
+        .filter(|s| !s.is_empty() && !s.contains(char::is_whitespace) && !s.contains("--"))
+        .collect();
+    if keys.is_empty() {
+        return None;
+    }
+    Some(format!(
+        "{}{} {}",
+        SOURCES_MARKER_PREFIX,
+        keys.join(" "),
+        MARKER_SUFFIX
+    ))
+}
+
+/// Split an entry line into its visible text and the sources in its marker.
+pub(crate) fn split_marker(item: &str) -> (&str, Vec<&str>) {
+    let Some(start) = item.find(SOURCES_MARKER_PREFIX) else {
+        return (item.trim_end(), Vec::new());
+    };
+    let rest = &item[start + SOURCES_MARKER_PREFIX.len()..];
+    let keys = rest
+        .split_once(MARKER_SUFFIX)
+        .map_or(rest, |(keys, _)| keys)
+        .split_whitespace()
+        .collect();
+    (item[..start].trim_end(), keys)
+}
+
+/// Whether two source keys name the same PR or commit. Short commit hashes
+/// match their longer forms.
+fn same_source(a: &str, b: &str) -> bool {
+    let a = a.trim().to_ascii_lowercase();
+    let b = b.trim().to_ascii_lowercase();
+    if a.starts_with('#') || b.starts_with('#') {
+        return a == b;
+    }
+    let (short, long) = if a.len() <= b.len() { (a, b) } else { (b, a) };
+    short.len() >= MIN_HASH_LEN && long.starts_with(&short)
+}
+
+/// Entry text compared case- and whitespace-insensitively, without the
+/// trailing period.
+fn normalize_text(text: &str) -> String {
+    text.split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .trim_end_matches('.')
+        .to_lowercase()
+}
+
+/// A release section after merging new entries into it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionMerge {
+    /// The whole changelog with the merged section.
+    pub content: String,
+    /// Entries added to the section.
+    pub added: usize,
+    /// Generated entries that were already in the section.
+    pub kept: usize,
+}
+
+/// Add the entries of `output` that are missing from the section of
+/// `version` in `content` (LF line endings).
+///
+/// New entries go at the end of their `### Category` block (a heading
+/// `aliases` maps to the category counts), or in a new block at the end of
+/// the section; existing lines are never rewritten.
+/// Returns `None` when `content` has no section for `version`.
+pub fn merge_section(
+    content: &str,
+    version: &Version,
+    output: &ChangelogOutput,
+    sort: EntrySort,
+    attribution: Option<&Attribution>,
+    aliases: &CategoryAliases,
+) -> Option<SectionMerge> {
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines.iter().position(|line| {
+        line.strip_prefix("## ")
+            .and_then(find_version_token)
+            .is_some_and(|v| &v == version)
+    })?;
+    Some(merge_at(
+        content,
+        &lines,
+        start,
+        output,
+        sort,
+        attribution,
+        aliases,
+    ))
+}
+
+/// Whether `line` is the heading of the `[Unreleased]` section.
+fn is_unreleased_heading(line: &str) -> bool {
+    line.strip_prefix("## ")
+        .is_some_and(|title| title.to_lowercase().contains("unreleased"))
+}
+
+/// Add the entries of `output` that are missing from the `[Unreleased]`
+/// section of `content` (LF line endings), like [`merge_section`] does for
+/// a release. A changelog without the section gets one above its first
+/// release.
+pub fn merge_unreleased(
+    content: &str,
+    output: &ChangelogOutput,
+    sort: EntrySort,
+    attribution: Option<&Attribution>,
+    aliases: &CategoryAliases,
+) -> SectionMerge {
+    let with_section;
+    let content = if content.lines().any(is_unreleased_heading) {
+        content
+    } else {
+        let at = find_insertion_point(content);
+        let mut inserted = content[..at].trim_end().to_string();
+        if !inserted.is_empty() {
+            inserted.push_str("\n\n");
+        }
+        inserted.push_str("## [Unreleased]\n");
+        if at < content.len() {
+            inserted.push('\n');
+            inserted.push_str(&content[at..]);
+        }
+        with_section = inserted;
+        &with_section
+    };
+    let lines: Vec<&str> = content.lines().collect();
+    let start = lines
+        .iter()
+        .position(|line| is_unreleased_heading(line))
+        .expect("the section was inserted");
+    merge_at(content, &lines, start, output, sort, attribution, aliases)
+}
+
+/// The `[Unreleased]` section of `content`, heading included.
+pub fn unreleased_section(content: &str) -> Option<String> {
+    let mut lines = content
+        .lines()
+        .skip_while(|line| !is_unreleased_heading(line));
+    let heading = lines.next()?;
+    let body: Vec<&str> = lines
+        .take_while(|line| !line.starts_with("## ") && !is_link_definition(line))
+        .collect();
+    let mut section = heading.to_string();
+    for line in body {
+        section.push('\n');
+        section.push_str(line);
+    }
+    Some(section.trim_end().to_string())
+}
+
+/// Merge `output` into the section whose heading is `lines[start]`.
+fn merge_at(
+    content: &str,
+    lines: &[&str],
+    start: usize,
+    output: &ChangelogOutput,
+    sort: EntrySort,
+    attribution: Option<&Attribution>,
+    aliases: &CategoryAliases,
+) -> SectionMerge {
+    let mut end = lines[start + 1..]
+        .iter()
+        .position(|line| line.starts_with("## "))
+        .map_or(lines.len(), |i| start + 1 + i);
+    // Blank lines and link reference definitions belong to the file, not the
+    // last section
+    while end > start + 1
+        && (lines[end - 1].trim().is_empty() || is_link_definition(lines[end - 1]))
+    {
+        end -= 1;
+    }
+
+    // Category blocks with the index of their last non-blank line
+    let mut blocks: Vec<(ChangelogCategory, usize)> = Vec::new();
+    let mut in_block = false;
+    let mut keys: Vec<&str> = Vec::new();
+    let mut texts: Vec<String> = Vec::new();
+    for (i, line) in lines.iter().enumerate().take(end).skip(start + 1) {
+        if let Some(heading) = line.strip_prefix("### ") {
+            in_block = match aliases.resolve(heading) {
+                Some(category) => {
+                    blocks.push((category, i));
+                    true
+                }
+                None => false,
+            };
+            continue;
+        }
+        let trimmed = line.trim_start();
+        if let Some(item) = trimmed
+            .strip_prefix("- ")
+            .or_else(|| trimmed.strip_prefix("* "))
+        {
+            let (text, sources) = split_marker(item);
+            keys.extend(sources);
+            texts.push(normalize_text(text));
+        }
+        if in_block
+            && !line.trim().is_empty()
+            && let Some(block) = blocks.last_mut()
+        {
+            block.1 = i;
+        }
+    }
+
+    let headings = aliases.headings_in(content);
+    let mut insertions: Vec<(usize, Vec<String>)> = Vec::new();
+    let mut added = 0;
+    let mut kept = 0;
+    for (category, mut entries) in output.entries_by_category() {
+        sort.sort(&mut entries);
+        let mut new_lines = Vec::new();
+        for entry in entries {
+            let present = if entry.sources.is_empty() {
+                let text = normalize_text(&sort.entry_text(entry));
+                texts.iter().any(|existing| existing.starts_with(&text))
+            } else {
+                entry
+                    .sources
+                    .iter()
+                    .any(|source| keys.iter().any(|key| same_source(source, key)))
+            };
+            if present {
+                kept += 1;
+            } else {
+                new_lines.push(entry_line(entry, sort, attribution, true));
+            }
+        }
+        if new_lines.is_empty() {
+            continue;
+        }
+        added += new_lines.len();
+
+        match blocks.iter().find(|(c, _)| *c == category) {
+            Some((_, last)) => insertions.push((*last, new_lines)),
+            None => {
+                let mut block = vec![
+                    String::new(),
+                    format!("### {}", headings.heading(&category)),
+                    String::new(),
+                ];
+                block.extend(new_lines);
+                insertions.push((end - 1, block));
+            }
+        }
+    }
+
+    let mut merged: Vec<String> = Vec::with_capacity(lines.len() + added);
+    for (i, line) in lines.iter().enumerate() {
+        merged.push((*line).to_string());
+        for (_, new_lines) in insertions.iter().filter(|(after, _)| *after == i) {
+            merged.extend(new_lines.iter().cloned());
+        }
+    }
+    let mut content_out = merged.join("\n");
+    if content.ends_with('\n') {
+        content_out.push('\n');
+    }
+
+    SectionMerge {
+        content: content_out,
+        added,
+        kept,
+    }
+}
+
+/// Whether `line` is a Markdown link reference definition (`[1.0.0]: url`).
+fn is_link_definition(line: &str) -> bool {
+    line.starts_with('[')
+        && line
+            .split_once("]:")
+            .is_some_and(|(label, _)| !label.contains(']'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(category: ChangelogCategory, description: &str, sources: &[&str]) -> ChangelogEntry {
+        ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    fn merge(content: &str, entries: Vec<ChangelogEntry>) -> SectionMerge {
+        let output = ChangelogOutput {
+            entries,
+            highlights: None,
+        };
+        merge_section(
+            content,
+            &Version::new(1, 1, 0),
+            &output,
+            EntrySort::default(),
+            None,
+            &CategoryAliases::default(),
+        )
+        .expect("section exists")
+    }
+
+    const CHANGELOG: &str = "# Changelog\n\n## [Unreleased]\n\n## [1.1.0] - 2024-06-01\n\n### Added\n\n- Dark mode, hand-edited <!-- keryx:sources #12 -->\n\n### Fixed\n\n- Crash on empty tags <!-- keryx:sources 0123456789ab -->\n\n## [1.0.0] - 2024-01-01\n\n### Added\n\n- First release\n";
+
+    #[test]
+    fn test_sources_marker() {
+        let marked = entry(ChangelogCategory::Added, "X", &["#12", " abc1234 ", ""]);
+        assert_eq!(
+            sources_marker(&marked).as_deref(),
+            Some("<!-- keryx:sources #12 abc1234 -->")
+        );
+        assert_eq!(
+            sources_marker(&entry(ChangelogCategory::Added, "X", &[])),
+            None
+        );
+        assert_eq!(
+            split_marker("Dark mode <!-- keryx:sources #12 abc1234 -->"),
+            ("Dark mode", vec!["#12", "abc1234"])
+        );
+    }
+
+    #[test]
+    fn test_same_source() {
+        assert!(same_source("#12", "#12"));
+        assert!(!same_source("#12", "#123"));
+        assert!(same_source("0123456", "0123456789ab"));
+        assert!(same_source("0123456789AB", "0123456"));
+        assert!(!same_source("0123", "0123456789ab"));
+    }
+
+    #[test]
+    fn test_rerun_keeps_edited_entries_and_adds_missing() {
+        let merged = merge(
+            CHANGELOG,
+            vec![
+                entry(ChangelogCategory::Added, "Add dark mode", &["#12"]),
+                entry(ChangelogCategory::Added, "Add CSV export", &["#14"]),
+                entry(ChangelogCategory::Fixed, "Fix crash", &["0123456"]),
+                entry(ChangelogCategory::Security, "Mask tokens", &["#15"]),
+            ],
+        );
+        assert_eq!((merged.added, merged.kept), (2, 2));
+        assert_eq!(
+            merged.content,
+            "# Changelog\n\n## [Unreleased]\n\n## [1.1.0] - 2024-06-01\n\n### Added\n\n- Dark mode, hand-edited <!-- keryx:sources #12 -->\n- Add CSV export <!-- keryx:sources #14 -->\n\n### Fixed\n\n- Crash on empty tags <!-- keryx:sources 0123456789ab -->\n\n### Security\n\n- Mask tokens <!-- keryx:sources #15 -->\n\n## [1.0.0] - 2024-01-01\n\n### Added\n\n- First release\n"
+        );
+    }
+
+    #[test]
+    fn test_rerun_is_idempotent() {
+        let entries = vec![
+            entry(ChangelogCategory::Added, "Add CSV export", &["#14"]),
+            entry(ChangelogCategory::Security, "Mask tokens", &["#15"]),
+        ];
+        let once = merge(CHANGELOG, entries.clone());
+        let twice = merge(&once.content, entries);
+        assert_eq!((twice.added, twice.kept), (0, 2));
+        assert_eq!(twice.content, once.content);
+    }
+
+    #[test]
+    fn test_entries_without_sources_match_by_text() {
+        let content = "## [1.1.0] - 2024-06-01\n\n### Changed\n\n- Faster startup.\n\n[1.1.0]: https://example.com/compare/v1.0.0...v1.1.0\n";
+        let merged = merge(
+            content,
+            vec![
+                entry(ChangelogCategory::Changed, "faster  startup", &[]),
+                entry(ChangelogCategory::Changed, "Smaller binary", &[]),
+            ],
+        );
+        assert_eq!((merged.added, merged.kept), (1, 1));
+        assert_eq!(
+            merged.content,
+            "## [1.1.0] - 2024-06-01\n\n### Changed\n\n- Faster startup.\n- Smaller binary\n\n[1.1.0]: https://example.com/compare/v1.0.0...v1.1.0\n"
+        );
+    }
+
+    #[test]
+    fn test_alias_headings_count_as_their_category() {
+        let content = "## [1.1.0] - 2024-06-01\n\n### Features\n\n- Dark mode <!-- keryx:sources #12 -->\n\n## [1.0.0] - 2024-01-01\n\n### Bug Fixes\n\n- Old fix\n";
+        let merged = merge(
+            content,
+            vec![
+                entry(ChangelogCategory::Added, "Add CSV export", &["#14"]),
+                entry(ChangelogCategory::Fixed, "Fix crash", &["#15"]),
+            ],
+        );
+        assert_eq!(
+            merged.content,
+            "## [1.1.0] - 2024-06-01\n\n### Features\n\n- Dark mode <!-- keryx:sources #12 -->\n- Add CSV export <!-- keryx:sources #14 -->\n\n### Bug Fixes\n\n- Fix crash <!-- keryx:sources #15 -->\n\n## [1.0.0] - 2024-01-01\n\n### Bug Fixes\n\n- Old fix\n"
+        );
+    }
+
+    fn merge_into_unreleased(content: &str, entries: Vec<ChangelogEntry>) -> SectionMerge {
+        let output = ChangelogOutput {
+            entries,
+            highlights: None,
+        };
+        merge_unreleased(
+            content,
+            &output,
+            EntrySort::default(),
+            None,
+            &CategoryAliases::default(),
+        )
+    }
+
+    #[test]
+    fn test_unreleased_dedupes_against_existing_entries() {
+        let content = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Dark mode <!-- keryx:sources #12 -->\n- CSV export\n\n## [1.0.0] - 2024-01-01\n\n### Added\n\n- First release\n";
+        let entries = vec![
+            entry(ChangelogCategory::Added, "Add dark mode", &["#12"]),
+            entry(ChangelogCategory::Added, "CSV export", &[]),
+            entry(ChangelogCategory::Fixed, "Fix crash", &["#15"]),
+        ];
+        let merged = merge_into_unreleased(content, entries.clone());
+        assert_eq!((merged.added, merged.kept), (1, 2));
+        assert_eq!(
+            unreleased_section(&merged.content).as_deref(),
+            Some(
+                "## [Unreleased]\n\n### Added\n\n- Dark mode <!-- keryx:sources #12 -->\n- CSV export\n\n### Fixed\n\n- Fix crash <!-- keryx:sources #15 -->"
+            )
+        );
+
+        let again = merge_into_unreleased(&merged.content, entries);
+        assert_eq!((again.added, again.kept), (0, 3));
+        assert_eq!(again.content, merged.content);
+    }
+
+    #[test]
+    fn test_unreleased_section_is_created_above_first_release() {
+        let entries = vec![entry(ChangelogCategory::Added, "CSV export", &["#14"])];
+        let merged = merge_into_unreleased(
+            CHANGELOG.replace("## [Unreleased]\n\n", "").as_str(),
+            entries.clone(),
+        );
+        assert_eq!(merged.added, 1);
+        assert!(merged.content.starts_with(
+            "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- CSV export <!-- keryx:sources #14 -->\n\n## [1.1.0] - 2024-06-01\n"
+        ));
+
+        let merged = merge_into_unreleased("# Changelog\n", entries);
+        assert_eq!(
+            merged.content,
+            "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- CSV export <!-- keryx:sources #14 -->\n"
+        );
+    }
+
+    #[test]
+    fn test_missing_section() {
+        let output = ChangelogOutput {
+            entries: Vec::new(),
+            highlights: None,
+        };
+        assert!(
+            merge_section(
+                CHANGELOG,
+                &Version::new(2, 0, 0),
+                &output,
+                EntrySort::default(),
+                None,
+                &CategoryAliases::default()
+            )
+            .is_none()
+        );
+    }
+}
@@ -0,0 +1,405 @@
+//! Migration guides for major releases.
+//!
+//! With `--migration-guide`, a major release (a stable `X.0.0` after an
+//! earlier release) also gets upgrade instructions. Every breaking commit
+//! and PR since the last release of the previous major line (so breaking
+//! changes shipped in `2.0.0-beta` tags are covered too) goes to the LLM,
+//! which answers with one step per change in the changelog entry format.
+//! The steps are verified against the codebase like changelog entries and
+//! written, newest release first, to `MIGRATIONS.md`:
+//!
+//! ```markdown
+//! ## Migrating to 2.0.0
+//!
+//! Upgrading from 1.4.2. Follow these steps in order.
+//!
+//! 1. Rename `--out` to `--output` in scripts and CI jobs. (#41)
+//! ```
+
+use std::path::Path;
+
+use semver::Version;
+
+use crate::changelog::line_ending::LineEnding;
+use crate::changelog::translations::{insert_section, sections};
+use crate::changelog::writer::atomic_write;
+use crate::changelog::{ChangelogEntry, ChangelogOutput};
+use crate::error::ChangelogError;
+use crate::git::ParsedCommit;
+use crate::git::parse_commit_message;
+use crate::git::tags::TagInfo;
+use crate::github::PullRequest;
+use crate::llm::prompt::{UNTRUSTED_INPUT_RULE, sanitize_for_prompt, untrusted_block};
+use crate::text::truncate_bytes_with_suffix;
+
+/// File the guides are written to, in the repository root.
+pub const MIGRATIONS_FILE: &str = "MIGRATIONS.md";
+
+/// Header of a new migrations file.
+const FILE_HEADER: &str = "# Migrations\n\nUpgrade steps for each major release, newest first.\n";
+
+/// Longest commit message or PR body passed to the prompt.
+const MAX_CHANGE_TEXT: usize = 2_000;
+
+/// Whether `next` is a major release that calls for a migration guide: a
+/// stable `X.0.0` (X ≥ 1) following an earlier release.
+pub fn is_major_release(previous: Option<&Version>, next: &Version) -> bool {
+    previous.is_some_and(|previous| previous < next)
+        && next.major > 0
+        && next.minor == 0
+        && next.patch == 0
+        && next.pre.is_empty()
+}
+
+/// The newest stable release of an earlier major line than `next`: the
+/// version users migrate from.
+pub fn last_major_release<'a>(tags: &'a [TagInfo], next: &Version) -> Option<&'a TagInfo> {
+    tags.iter()
+        .filter(|t| {
+            t.version
+                .as_ref()
+                .is_some_and(|v| v.pre.is_empty() && v.major < next.major)
+        })
+        .max_by(|a, b| a.version.cmp(&b.version))
+}
+
+/// Whether a PR is marked as breaking: a `!` or `BREAKING CHANGE` in its
+/// conventional title, a label containing "breaking", or a `BREAKING CHANGE`
+/// note in its description.
+pub fn is_breaking_pr(pr: &PullRequest) -> bool {
+    let (_, _, breaking) = parse_commit_message(&pr.title);
+    breaking
+        || pr
+            .labels
+            .iter()
+            .any(|label| label.to_lowercase().contains("breaking"))
+        || pr
+            .body
+            .as_deref()
+            .is_some_and(|body| body.contains("BREAKING CHANGE"))
+}
+
+fn is_breaking_commit(commit: &ParsedCommit) -> bool {
+    commit.breaking || commit.footers.breaking_change().is_some()
+}
+
+/// Breaking changes a migration guide is written from.
+#[derive(Debug, Clone)]
+pub struct MigrationInput {
+    pub version: Version,
+    /// Release users migrate from, if known.
+    pub since: Option<Version>,
+    pub commits: Vec<ParsedCommit>,
+    pub pull_requests: Vec<PullRequest>,
+    pub repository_name: String,
+}
+
+impl MigrationInput {
+    /// Keep the breaking commits and PRs of `commits` and `pull_requests`.
+    pub fn new(
+        version: &Version,
+        since: Option<&Version>,
+        commits: &[ParsedCommit],
+        pull_requests: &[PullRequest],
+        repository_name: &str,
+    ) -> Self {
+        Self {
+            version: version.clone(),
+            since: since.cloned(),
+            commits: commits
+                .iter()
+                .filter(|c| is_breaking_commit(c))
+                .cloned()
+                .collect(),
+            pull_requests: pull_requests
+                .iter()
+                .filter(|pr| is_breaking_pr(pr))
+                .cloned()
+                .collect(),
+            repository_name: repository_name.to_string(),
+        }
+    }
+
+    /// Whether there is no breaking change to migrate.
+    pub fn is_empty(&self) -> bool {
+        self.commits.is_empty() && self.pull_requests.is_empty()
+    }
+}
+
+/// Build the prompt asking for migration steps. The response uses the
+/// changelog entry format, so it is parsed and verified like entries.
+pub fn build_migration_prompt(input: &MigrationInput) -> String {
+    let mut changes = String::new();
+    for commit in &input.commits {
+        changes.push_str(&format!(
+            "- commit {}: {}\n",
+            &commit.hash[..commit.hash.len().min(7)],
+            sanitize_for_prompt(&truncate_bytes_with_suffix(
+                commit.message.trim(),
+                MAX_CHANGE_TEXT,
+                "..."
+            ))
+            .replace('\n', "\n  ")
+        ));
+    }
+    for pr in &input.pull_requests {
+        changes.push_str(&format!(
+            "- PR #{}: {}\n",
+            pr.number,
+            sanitize_for_prompt(&pr.title)
+        ));
+        if let Some(body) = pr.body.as_deref().filter(|b| !b.trim().is_empty()) {
+            changes.push_str(&format!(
+                "  {}\n",
+                sanitize_for_prompt(&truncate_bytes_with_suffix(
+                    body.trim(),
+                    MAX_CHANGE_TEXT,
+                    "..."
+                ))
+                .replace('\n', "\n  ")
+            ));
+        }
+    }
+
+    let since = input.since.as_ref().map_or_else(
+        || "the previous major version".to_string(),
+        |v| v.to_string(),
+    );
+    format!(
+        r##"You are writing the migration guide for users of {repository} upgrading from {since} to {version}.
+These are the breaking changes in between:
+
+{changes}
+
+{UNTRUSTED_INPUT_RULE}
+
+## Instructions
+1. Write one step per change users must act on, in the order they should apply them
+2. Each step says what changed and exactly what to do: the old and new names, the flag, setting or API to replace, the command to run
+3. Use the names from the commits and PRs verbatim in backticks; do not invent replacements that are not described
+4. Skip breaking changes that need no action from users
+5. Use category "Removed" for removals and "Changed" for everything else
+6. List in "sources" the short commit hashes and PR references (#12) each step is based on
+
+Respond with JSON:
+{{
+  "entries": [
+    {{"category": "Changed", "description": "Rename `--out` to `--output` in scripts and CI jobs.", "sources": ["#41"]}}
+  ]
+}}"##,
+        repository = sanitize_for_prompt(&input.repository_name),
+        since = since,
+        version = input.version,
+        changes = untrusted_block(changes.trim_end()),
+    )
+}
+
+/// Verified upgrade steps for a major release.
+#[derive(Debug, Clone)]
+pub struct MigrationGuide {
+    pub version: Version,
+    pub since: Option<Version>,
+    pub steps: Vec<ChangelogEntry>,
+}
+
+impl MigrationGuide {
+    pub fn new(input: &MigrationInput, output: ChangelogOutput) -> Self {
+        Self {
+            version: input.version.clone(),
+            since: input.since.clone(),
+            steps: output.entries,
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.steps.is_empty()
+    }
+
+    /// The guide as a `## Migrating to X.0.0` section.
+    pub fn render(&self) -> String {
+        let mut out = format!("## Migrating to {}\n\n", self.version);
+        match &self.since {
+            Some(since) => out.push_str(&format!(
+                "Upgrading from {}. Follow these steps in order.\n\n",
+                since
+            )),
+            None => out.push_str("Follow these steps in order.\n\n"),
+        }
+        for (i, step) in self.steps.iter().enumerate() {
+            out.push_str(&format!("{}. {}", i + 1, step.description.trim()));
+            if !step.sources.is_empty() {
+                out.push_str(&format!(" ({})", step.sources.join(", ")));
+            }
+            out.push('\n');
+        }
+        out
+    }
+}
+
+/// Add `guide` to the migrations file at `path`, creating it if needed. A
+/// section for the same version is replaced.
+pub fn write_migration_guide(path: &Path, guide: &MigrationGuide) -> Result<(), ChangelogError> {
+    let existing = match std::fs::read_to_string(path) {
+        Ok(content) => Some(content),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(ChangelogError::ReadFailed(e)),
+    };
+    let ending = existing
+        .as_deref()
+        .map(LineEnding::detect)
+        .unwrap_or_default();
+    let content = upsert_guide(existing.as_deref().unwrap_or(FILE_HEADER), guide);
+    atomic_write(path, &ending.apply(&content))
+}
+
+/// `content` with the section of `guide` inserted in version order, or
+/// replacing the section of the same version. Returns LF-normalized content.
+fn upsert_guide(content: &str, guide: &MigrationGuide) -> String {
+    let content = content.replace("\r\n", "\n");
+    let section = guide.render();
+    match sections(&content)
+        .into_iter()
+        .find(|s| s.version == guide.version)
+    {
+        Some(existing) => {
+            let after = &content[existing.end..];
+            format!(
+                "{}{}{}{}",
+                &content[..existing.start],
+                section.trim_end(),
+                if after.is_empty() { "\n" } else { "\n\n" },
+                after
+            )
+        }
+        None => insert_section(&content, &guide.version, &section),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogCategory;
+    use crate::git::Footers;
+    use std::num::NonZeroU64;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    fn commit(hash: &str, message: &str) -> ParsedCommit {
+        let (commit_type, scope, breaking) = parse_commit_message(message);
+        ParsedCommit {
+            hash: hash.to_string(),
+            message: message.to_string(),
+            commit_type,
+            scope,
+            breaking,
+            timestamp: chrono::Utc::now(),
+            footers: Footers::parse(message),
+        }
+    }
+
+    fn pr(number: u64, title: &str, labels: &[&str]) -> PullRequest {
+        PullRequest {
+            number: NonZeroU64::new(number).unwrap(),
+            title: title.to_string(),
+            body: None,
+            merged_at: None,
+            labels: labels.iter().map(|l| l.to_string()).collect(),
+        }
+    }
+
+    fn guide(version: &str, step: &str) -> MigrationGuide {
+        MigrationGuide {
+            version: v(version),
+            since: Some(v("1.4.2")),
+            steps: vec![ChangelogEntry {
+                category: ChangelogCategory::Changed,
+                description: step.to_string(),
+                sources: vec!["#41".to_string()],
+                scope: None,
+                importance: None,
+            }],
+        }
+    }
+
+    #[test]
+    fn test_is_major_release() {
+        assert!(is_major_release(Some(&v("1.4.2")), &v("2.0.0")));
+        assert!(is_major_release(Some(&v("2.0.0-rc.1")), &v("2.0.0")));
+        assert!(!is_major_release(None, &v("1.0.0")));
+        assert!(!is_major_release(Some(&v("1.4.2")), &v("1.5.0")));
+        assert!(!is_major_release(Some(&v("0.9.0")), &v("0.10.0")));
+        assert!(!is_major_release(Some(&v("1.4.2")), &v("2.0.0-beta.1")));
+    }
+
+    #[test]
+    fn test_last_major_release_skips_prereleases() {
+        let tag = |name: &str| TagInfo {
+            name: name.to_string(),
+            oid: git2::Oid::zero(),
+            version: crate::git::get_version_from_tag(name),
+        };
+        let tags = vec![
+            tag("v1.3.0"),
+            tag("v1.4.2"),
+            tag("v2.0.0-beta.1"),
+            tag("nightly"),
+        ];
+        let base = last_major_release(&tags, &v("2.0.0")).unwrap();
+        assert_eq!(base.name, "v1.4.2");
+        assert!(last_major_release(&tags, &v("1.0.0")).is_none());
+    }
+
+    #[test]
+    fn test_input_keeps_breaking_changes() {
+        let commits = vec![
+            commit("aaaaaaa1", "feat!: rename --out to --output"),
+            commit("bbbbbbb2", "fix: typo"),
+            commit(
+                "ccccccc3",
+                "refactor: config loader\n\nBREAKING CHANGE: keryx.toml moved to .keryx/",
+            ),
+        ];
+        let prs = vec![
+            pr(41, "feat(cli)!: drop --legacy", &[]),
+            pr(42, "Rework auth", &["breaking-change"]),
+            pr(43, "docs: readme", &[]),
+        ];
+        let input = MigrationInput::new(&v("2.0.0"), Some(&v("1.4.2")), &commits, &prs, "keryx");
+        let hashes: Vec<&str> = input.commits.iter().map(|c| c.hash.as_str()).collect();
+        assert_eq!(hashes, vec!["aaaaaaa1", "ccccccc3"]);
+        let numbers: Vec<u64> = input.pull_requests.iter().map(|p| p.number.get()).collect();
+        assert_eq!(numbers, vec![41, 42]);
+
+        let prompt = build_migration_prompt(&input);
+        assert!(prompt.contains("upgrading from 1.4.2 to 2.0.0"));
+        assert!(prompt.contains("- commit aaaaaaa: feat!: rename --out to --output"));
+        assert!(prompt.contains("- PR #42: Rework auth"));
+        assert!(!prompt.contains("typo"));
+    }
+
+    #[test]
+    fn test_render() {
+        assert_eq!(
+            guide("2.0.0", "Rename `--out` to `--output`.").render(),
+            "## Migrating to 2.0.0\n\nUpgrading from 1.4.2. Follow these steps in order.\n\n\
+             1. Rename `--out` to `--output`. (#41)\n"
+        );
+    }
+
+    #[test]
+    fn test_upsert_orders_and_replaces_sections() {
+        let content = upsert_guide(FILE_HEADER, &guide("2.0.0", "Old step."));
+        let content = upsert_guide(&content, &guide("3.0.0", "Newer step."));
+        let content = upsert_guide(&content, &guide("2.0.0", "Fixed step."));
+
+        assert!(content.starts_with(FILE_HEADER));
+        let newer = content.find("## Migrating to 3.0.0").unwrap();
+        let older = content.find("## Migrating to 2.0.0").unwrap();
+        assert!(newer < older);
+        assert!(content.contains("1. Fixed step. (#41)\n"));
+        assert!(!content.contains("Old step"));
+        assert!(content.ends_with("(#41)\n"));
+    }
+}
@@ -1,9 +1,51 @@
 //! Changelog parsing and writing.
 
+pub mod aliases;
+pub mod attribution;
+pub mod channel;
+pub mod codename;
+pub mod commit_notes;
+pub mod constraints;
+pub mod date;
+pub mod edit;
+pub mod empty_guard;
 pub mod format;
+pub mod heading;
+pub mod history_cache;
+pub mod line_ending;
+pub mod merge;
+pub mod migration;
 pub mod parser;
+pub mod pr_notes;
+pub mod product;
+pub mod sanitize;
+pub mod sort;
+pub mod splice;
+pub mod style;
+pub mod template;
+pub mod translations;
+pub mod validate;
 pub mod writer;
 
+pub use aliases::CategoryAliases;
+pub use attribution::{Attribution, AttributionConfig};
+pub use commit_notes::CommitNotes;
+pub use constraints::EntryConstraints;
+pub use date::ReleaseDateConfig;
 pub use format::{ChangelogCategory, ChangelogEntry, ChangelogOutput};
-pub use parser::read_changelog;
-pub use writer::write_changelog;
+pub use heading::{CodenameConfig, HeadingTemplate};
+pub use history_cache::HistoryCache;
+pub use migration::{MigrationGuide, MigrationInput};
+pub use parser::{parse_changelog_content, read_changelog};
+pub use pr_notes::CuratedNotes;
+pub use product::ProductContext;
+pub use sort::EntrySort;
+pub use style::StyleGuide;
+pub use template::ReleaseTemplate;
+pub use validate::{Violation, validate_changelog};
+pub use writer::{
+    CompareLinks, MergeCounts, ReleaseAttribute, ReleaseMetadata, format_entries,
+    format_version_section, merge_changelogs_with_metadata, merge_unreleased_changelogs,
+    write_changelog, write_changelog_dated, write_changelog_with_metadata,
+    write_changelogs_with_metadata,
+};
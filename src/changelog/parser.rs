@@ -7,12 +7,18 @@ use tracing::debug;
 
 use crate::error::ChangelogError;
 
+/// Marker that freezes the version section it appears in (on the heading line
+/// or anywhere in the section). keryx never modifies a frozen section.
+pub const FROZEN_MARKER: &str = "<!-- keryx:frozen -->";
+
 /// Parsed changelog information.
 #[derive(Debug)]
 pub struct ParsedChangelog {
     pub has_unreleased: bool,
     pub latest_version: Option<Version>,
     pub versions: Vec<Version>,
+    /// Versions whose section carries [`FROZEN_MARKER`].
+    pub frozen_versions: Vec<Version>,
     pub raw_content: String,
 }
 
@@ -21,6 +27,11 @@ impl ParsedChangelog {
     pub fn has_version(&self, version: &Version) -> bool {
         self.versions.contains(version)
     }
+
+    /// Check if the section of `version` is frozen.
+    pub fn is_frozen(&self, version: &Version) -> bool {
+        self.frozen_versions.contains(version)
+    }
 }
 
 /// Read and parse an existing changelog file.
@@ -30,32 +41,73 @@ pub fn read_changelog(path: &Path) -> Result<Option<ParsedChangelog>, ChangelogE
     }
 
     let content = std::fs::read_to_string(path).map_err(ChangelogError::ReadFailed)?;
+    parse_changelog_content(content).map(Some)
+}
 
-    let changelog =
-        parse_changelog::parse(&content).map_err(|e| ChangelogError::ParseFailed(e.to_string()))?;
-
-    let has_unreleased =
-        changelog.get("Unreleased").is_some() || changelog.get("unreleased").is_some();
-
-    // Collect all versions from the changelog
-    let versions: Vec<Version> = changelog
-        .iter()
-        .filter(|(title, _)| {
-            let t = title.to_lowercase();
-            t != "unreleased" && !t.is_empty()
-        })
-        .filter_map(|(title, _)| extract_version_from_title(title))
-        .collect();
+/// Parse changelog `content` read from elsewhere than the filesystem, e.g. a
+/// blob in a bare repository.
+pub fn parse_changelog_content(content: String) -> Result<ParsedChangelog, ChangelogError> {
+    // Headings rendered from custom templates (e.g. "## [1.2.0] — Aurora — 2024-01-01")
+    // may not be understood by parse-changelog, so versions are also scanned
+    // from the headings directly.
+    let mut versions = scan_heading_versions(&content);
+
+    let has_unreleased = match parse_changelog::parse(&content) {
+        Ok(changelog) => {
+            let parsed = changelog
+                .iter()
+                .filter(|(title, _)| {
+                    let t = title.to_lowercase();
+                    t != "unreleased" && !t.is_empty()
+                })
+                .filter_map(|(title, _)| extract_version_from_title(title));
+            for version in parsed {
+                if !versions.contains(&version) {
+                    versions.push(version);
+                }
+            }
+            changelog.get("Unreleased").is_some() || changelog.get("unreleased").is_some()
+        }
+        Err(e) if !versions.is_empty() => {
+            debug!(
+                "parse-changelog failed ({}), using scanned version headings",
+                e
+            );
+            content
+                .lines()
+                .any(|line| line.starts_with("## ") && line.to_lowercase().contains("unreleased"))
+        }
+        Err(e) => return Err(ChangelogError::ParseFailed(e.to_string())),
+    };
 
     // The latest version is the first one (changelogs are ordered newest first)
     let latest_version = versions.first().cloned();
 
-    Ok(Some(ParsedChangelog {
+    Ok(ParsedChangelog {
         has_unreleased,
         latest_version,
         versions,
+        frozen_versions: scan_frozen_versions(&content),
         raw_content: content,
-    }))
+    })
+}
+
+/// Versions of the `## ` sections that contain [`FROZEN_MARKER`].
+pub(crate) fn scan_frozen_versions(content: &str) -> Vec<Version> {
+    let mut frozen = Vec::new();
+    let mut current: Option<Version> = None;
+    for line in content.lines() {
+        if let Some(title) = line.strip_prefix("## ") {
+            current = find_version_token(title);
+        }
+        if line.contains(FROZEN_MARKER)
+            && let Some(version) = &current
+            && !frozen.contains(version)
+        {
+            frozen.push(version.clone());
+        }
+    }
+    frozen
 }
 
 /// Extract version number from a changelog section title.
@@ -82,14 +134,37 @@ fn extract_version_from_title(title: &str) -> Option<Version> {
 
     match Version::parse(version_str) {
         Ok(v) => Some(v),
-        Err(e) => {
+        Err(e) => find_version_token(title).or_else(|| {
             debug!(
                 "Could not parse '{}' as semver: {}. Skipping this version.",
                 version_str, e
             );
             None
+        }),
+    }
+}
+
+/// Versions of all `## ` headings, in document order.
+fn scan_heading_versions(content: &str) -> Vec<Version> {
+    let mut versions = Vec::new();
+    for line in content.lines() {
+        if let Some(title) = line.strip_prefix("## ")
+            && let Some(version) = find_version_token(title)
+            && !versions.contains(&version)
+        {
+            versions.push(version);
         }
     }
+    versions
+}
+
+/// First semver-looking token in a heading, wherever the template put it.
+/// e.g., "Aurora — [v1.2.3] — 2024-01-01" -> 1.2.3
+pub(crate) fn find_version_token(title: &str) -> Option<Version> {
+    title
+        .split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '(' | ')' | ','))
+        .map(|token| token.trim_start_matches('v'))
+        .find_map(|token| Version::parse(token).ok())
 }
 
 /// Find the position to insert a new version section.
@@ -151,6 +226,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_extract_version_from_template_heading() {
+        assert_eq!(
+            extract_version_from_title("Aurora — [2.0.0] — 2024-06-01"),
+            Some(Version::new(2, 0, 0))
+        );
+    }
+
+    #[test]
+    fn test_scan_heading_versions_with_codenames() {
+        let content = "# Changelog\n\n## [Unreleased]\n\n## [2.0.0] — Aurora — 2024-06-01\n\n- Change\n\n## Borealis (v1.1.0) 2024-01-01\n\n## [1.0.0] - 2023-06-01\n";
+        assert_eq!(
+            scan_heading_versions(content),
+            vec![
+                Version::new(2, 0, 0),
+                Version::new(1, 1, 0),
+                Version::new(1, 0, 0)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scan_frozen_versions() {
+        let content = "# Changelog\n\n## [Unreleased]\n\n<!-- keryx:frozen -->\n\n## [2.0.0] - 2024-06-01 <!-- keryx:frozen -->\n\n- Change\n\n## [1.1.0] - 2024-01-01\n\n- Fix\n\n## [1.0.0] - 2023-06-01\n\n<!-- keryx:frozen -->\n- Reviewed\n";
+        assert_eq!(
+            scan_frozen_versions(content),
+            vec![Version::new(2, 0, 0), Version::new(1, 0, 0)]
+        );
+    }
+
+    #[test]
+    fn test_read_changelog_recognizes_template_headings() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(
+            &path,
+            "# Changelog\n\n## [1.1.0] — Borealis — 2024-02-01\n\n### Added\n\n- Thing\n\n## [1.0.0] — Aurora — 2024-01-01\n\n### Added\n\n- Initial\n",
+        )
+        .unwrap();
+
+        let changelog = read_changelog(&path).unwrap().unwrap();
+        assert!(changelog.has_version(&Version::new(1, 1, 0)));
+        assert!(changelog.has_version(&Version::new(1, 0, 0)));
+        assert_eq!(changelog.latest_version, Some(Version::new(1, 1, 0)));
+    }
+
     #[test]
     fn test_extract_version_invalid() {
         assert_eq!(extract_version_from_title("not-a-version"), None);
@@ -182,6 +303,7 @@ mod tests {
                 Version::new(1, 1, 0),
                 Version::new(1, 0, 0),
             ],
+            frozen_versions: vec![],
             raw_content: String::new(),
         };
 
@@ -0,0 +1,339 @@
+//! Release notes written in PR descriptions.
+//!
+//! A PR whose description has a `## Changelog` section supplies its own
+//! entries: every item of the section becomes an entry verbatim, and the PR
+//! (with the squash or merge commits referencing it) is left out of the LLM
+//! input. Items may name their category with a prefix (`Fixed: ...`) or a
+//! subheading (`### Fixed`); otherwise the category follows the PR title's
+//! conventional-commit type. A section holding only `None` or `N/A` marks a
+//! PR with nothing to announce, while an empty section (a template left
+//! unfilled) is ignored and the PR goes to the LLM as usual.
+//!
+//! ```markdown
+//! ## Changelog
+//! - Added `--json` output to `keryx pending`
+//! - Fixed: Dates in the heading now honor `--date-timezone`
+//! ```
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use crate::changelog::{ChangelogCategory, ChangelogEntry, ChangelogOutput};
+use crate::git::{CommitType, parse_commit_message};
+use crate::github::PullRequest;
+use crate::github::prs::referenced_pr_numbers;
+use crate::llm::ChangelogInput;
+
+/// A Markdown ATX heading: level and text.
+static HEADING_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"^ {0,3}(#{1,6})\s+(.*?)[\s#]*$")
+        .expect("Invalid heading regex - this is a bug")
+});
+
+/// An item starting with its category: `Fixed: ...` or `[Fixed] ...`.
+static CATEGORY_PREFIX_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(
+        r"(?i)^(?:\[(added|changed|deprecated|removed|fixed|security)\]|(added|changed|deprecated|removed|fixed|security):)\s*(.+)$",
+    )
+    .expect("Invalid category prefix regex - this is a bug")
+});
+
+/// Section contents meaning "no changelog entry for this PR".
+pub(crate) const NO_ENTRY_MARKERS: [&str; 4] = ["none", "n/a", "no changelog", "skip"];
+
+/// One item of a PR's changelog section.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SectionItem {
+    /// Category from a prefix or subheading, if the item named one.
+    pub category: Option<ChangelogCategory>,
+    pub text: String,
+}
+
+/// The items of the `## Changelog` section of a PR description.
+///
+/// Returns `None` when there is no such section or it is empty (HTML
+/// comments do not count), and `Some(vec![])` when it says `None`.
+pub fn changelog_section(body: &str) -> Option<Vec<SectionItem>> {
+    let body = strip_html_comments(body);
+    let mut section_level = None;
+    let mut category = None;
+    let mut items: Vec<SectionItem> = Vec::new();
+    let mut no_entry = false;
+
+    for line in body.lines() {
+        if let Some(caps) = HEADING_REGEX.captures(line) {
+            let level = caps[1].len();
+            let text = caps[2].trim();
+            match section_level {
+                None if text.eq_ignore_ascii_case("changelog") => section_level = Some(level),
+                Some(section) if level <= section => break,
+                Some(_) => category = text.parse().ok(),
+                None => {}
+            }
+            continue;
+        }
+        if section_level.is_none() || line.trim().is_empty() {
+            continue;
+        }
+
+        let trimmed = line.trim();
+        let bullet = ["- ", "* ", "+ "]
+            .iter()
+            .find_map(|marker| trimmed.strip_prefix(marker));
+        match bullet {
+            // Indented text under an item continues it
+            None if line.starts_with([' ', '\t']) && !items.is_empty() => {
+                let last = items.last_mut().expect("items is not empty");
+                last.text.push(' ');
+                last.text.push_str(trimmed);
+            }
+            _ => {
+                let text = bullet.unwrap_or(trimmed).trim();
+                if NO_ENTRY_MARKERS
+                    .iter()
+                    .any(|marker| text.trim_end_matches('.').eq_ignore_ascii_case(marker))
+                {
+                    no_entry = true;
+                    continue;
+                }
+                items.push(parse_item(text, category.clone()));
+            }
+        }
+    }
+
+    if section_level.is_none() || (items.is_empty() && !no_entry) {
+        return None;
+    }
+    Some(items)
+}
+
+pub(crate) fn parse_item(text: &str, category: Option<ChangelogCategory>) -> SectionItem {
+    match CATEGORY_PREFIX_REGEX.captures(text) {
+        Some(caps) => SectionItem {
+            category: caps
+                .get(1)
+                .or_else(|| caps.get(2))
+                .and_then(|m| m.as_str().parse().ok()),
+            text: caps[3].trim().to_string(),
+        },
+        None => SectionItem {
+            category,
+            text: text.to_string(),
+        },
+    }
+}
+
+fn strip_html_comments(body: &str) -> String {
+    let mut out = String::with_capacity(body.len());
+    let mut rest = body;
+    while let Some(start) = rest.find("<!--") {
+        out.push_str(&rest[..start]);
+        match rest[start..].find("-->") {
+            Some(end) => rest = &rest[start + end + 3..],
+            None => return out,
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Category for items that do not name one, from the PR title's type.
+pub(crate) fn default_category(title: &str) -> ChangelogCategory {
+    match parse_commit_message(title) {
+        (Some(CommitType::Feat), _, false) => ChangelogCategory::Added,
+        (Some(CommitType::Fix), _, false) => ChangelogCategory::Fixed,
+        _ => ChangelogCategory::Changed,
+    }
+}
+
+/// Entries taken verbatim from PR descriptions.
+#[derive(Debug, Clone, Default)]
+pub struct CuratedNotes {
+    pub entries: Vec<ChangelogEntry>,
+    /// PRs whose description had a changelog section, including those
+    /// that declared no entry.
+    pub pr_numbers: HashSet<u64>,
+}
+
+impl CuratedNotes {
+    /// Collect the changelog sections of `prs`.
+    pub fn from_pull_requests(prs: &[PullRequest]) -> Self {
+        let mut notes = Self::default();
+        for pr in prs {
+            let Some(items) = pr.body.as_deref().and_then(changelog_section) else {
+                continue;
+            };
+            let number = pr.number.get();
+            notes.pr_numbers.insert(number);
+            notes
+                .entries
+                .extend(items.into_iter().map(|item| ChangelogEntry {
+                    category: item.category.unwrap_or_else(|| default_category(&pr.title)),
+                    description: item.text,
+                    sources: vec![format!("#{}", number)],
+                    scope: None,
+                    importance: None,
+                }));
+        }
+        notes
+    }
+
+    /// Whether no PR had a changelog section.
+    pub fn is_empty(&self) -> bool {
+        self.pr_numbers.is_empty()
+    }
+
+    /// `input` without the curated PRs and the commits referencing them:
+    /// what is left for the LLM to describe.
+    pub fn llm_input(&self, input: &ChangelogInput) -> ChangelogInput {
+        let mut llm_input = input.clone();
+        if self.is_empty() {
+            return llm_input;
+        }
+        llm_input
+            .pull_requests
+            .retain(|pr| !self.pr_numbers.contains(&pr.number.get()));
+        llm_input.commits.retain(|commit| {
+            referenced_pr_numbers([commit.subject()]).is_disjoint(&self.pr_numbers)
+        });
+        llm_input
+    }
+
+    /// Add the curated entries to `output`.
+    pub fn merge_into(&self, output: &mut ChangelogOutput) {
+        output.entries.extend(self.entries.iter().cloned());
+    }
+}
+
+/// Whether the LLM has nothing left to describe in `input`.
+pub fn nothing_to_generate(input: &ChangelogInput) -> bool {
+    input.commits.is_empty() && input.pull_requests.is_empty()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::num::NonZeroU64;
+
+    fn pr(number: u64, title: &str, body: &str) -> PullRequest {
+        PullRequest {
+            number: NonZeroU64::new(number).unwrap(),
+            title: title.to_string(),
+            body: Some(body.to_string()),
+            merged_at: None,
+            labels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_section_items_are_verbatim() {
+        let body = "## Summary\nRefactor the writer.\n\n## Changelog\n\
+                    - Added `--json` output to `keryx pending`\n\
+                    * Fixed: Dates honor `--date-timezone`\n\
+                    - [Security] Tokens are masked in logs\n\n## Testing\n- cargo test\n";
+        let items = changelog_section(body).unwrap();
+        assert_eq!(
+            items,
+            vec![
+                SectionItem {
+                    category: None,
+                    text: "Added `--json` output to `keryx pending`".to_string(),
+                },
+                SectionItem {
+                    category: Some(ChangelogCategory::Fixed),
+                    text: "Dates honor `--date-timezone`".to_string(),
+                },
+                SectionItem {
+                    category: Some(ChangelogCategory::Security),
+                    text: "Tokens are masked in logs".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_section_subheadings_set_category() {
+        let body = "# Changelog\n### Fixed\n- Crash on empty ranges\n  when no tag exists\n### Removed\n- The `--legacy` flag\n";
+        let items = changelog_section(body).unwrap();
+        assert_eq!(items.len(), 2);
+        assert_eq!(items[0].category, Some(ChangelogCategory::Fixed));
+        assert_eq!(items[0].text, "Crash on empty ranges when no tag exists");
+        assert_eq!(items[1].category, Some(ChangelogCategory::Removed));
+    }
+
+    #[test]
+    fn test_unfilled_or_missing_section_is_ignored() {
+        assert_eq!(changelog_section("Just a description"), None);
+        assert_eq!(
+            changelog_section(
+                "## Changelog\n<!-- One line per user-facing change -->\n\n## Notes\n"
+            ),
+            None
+        );
+        assert_eq!(changelog_section("## Changelog\nNone.\n"), Some(vec![]));
+    }
+
+    #[test]
+    fn test_curated_prs_are_removed_from_llm_input() {
+        let prs = vec![
+            pr(
+                12,
+                "feat: pending json",
+                "## Changelog\n- JSON output for `keryx pending`\n",
+            ),
+            pr(13, "chore: bump deps", "## Changelog\nN/A\n"),
+            pr(14, "fix: date heading", "Fixes the heading."),
+        ];
+        let notes = CuratedNotes::from_pull_requests(&prs);
+        assert_eq!(notes.pr_numbers, HashSet::from([12, 13]));
+        assert_eq!(notes.entries.len(), 1);
+        assert_eq!(notes.entries[0].category, ChangelogCategory::Added);
+        assert_eq!(notes.entries[0].sources, vec!["#12"]);
+
+        let input = sample_input(prs);
+        let llm_input = notes.llm_input(&input);
+        let numbers: Vec<u64> = llm_input
+            .pull_requests
+            .iter()
+            .map(|pr| pr.number.get())
+            .collect();
+        assert_eq!(numbers, vec![14]);
+        let subjects: Vec<&str> = llm_input.commits.iter().map(|c| c.subject()).collect();
+        assert_eq!(subjects, vec!["fix: date heading (#14)", "docs: typo"]);
+        assert!(!nothing_to_generate(&llm_input));
+    }
+
+    fn sample_input(prs: Vec<PullRequest>) -> ChangelogInput {
+        let commit = |message: &str| {
+            let (commit_type, scope, breaking) = parse_commit_message(message);
+            crate::git::ParsedCommit {
+                hash: "0".repeat(40),
+                message: message.to_string(),
+                commit_type,
+                scope,
+                breaking,
+                timestamp: chrono::Utc::now(),
+                footers: Default::default(),
+            }
+        };
+        ChangelogInput {
+            commits: vec![
+                commit("feat: pending json (#12)"),
+                commit("chore: bump deps (#13)"),
+                commit("fix: date heading (#14)"),
+                commit("docs: typo"),
+            ],
+            pull_requests: prs,
+            previous_version: None,
+            repository_name: "keryx".to_string(),
+            project_description: None,
+            cli_features: None,
+            product: Default::default(),
+            languages: Default::default(),
+            include_highlights: false,
+            include_sources: false,
+            entry_sort: Default::default(),
+            prs_only: false,
+        }
+    }
+}
@@ -0,0 +1,203 @@
+//! Product context from `keryx.toml`.
+//!
+//! A repository name often says little about the product. The `[product]`
+//! table of `keryx.toml` in the repository root describes it for the LLM and
+//! is injected into every changelog prompt, not just the initial release:
+//!
+//! ```toml
+//! [product]
+//! description = "Self-hosted status pages for small teams"
+//! audience = "Operators who run the status page, not their end users"
+//!
+//! [product.glossary]
+//! Lane = "A deployment target (staging, production)"
+//! ```
+
+use std::path::Path;
+
+use git2::Repository;
+use serde::Serialize;
+use tracing::warn;
+
+use crate::git::read_head_file;
+
+/// Name of the config file in the repository root.
+pub const CONFIG_FILE: &str = "keryx.toml";
+
+/// What the product is and who reads its release notes. The default is empty.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+pub struct ProductContext {
+    /// What the product does.
+    pub description: Option<String>,
+    /// Who the release notes are written for.
+    pub audience: Option<String>,
+    /// Project terms and their meaning, in file order.
+    pub glossary: Vec<(String, String)>,
+}
+
+impl ProductContext {
+    /// Read the `[product]` table of `keryx.toml` in `root`.
+    ///
+    /// A missing file or table gives the empty context; an unreadable or
+    /// invalid file is logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!("Invalid {}: {}. Ignoring it.", path.display(), e);
+            Self::default()
+        })
+    }
+
+    /// Like [`Self::load`] for the root of `repo`; a bare repository's
+    /// `keryx.toml` is read from the tree of `HEAD`.
+    pub fn for_repository(repo: &Repository) -> Self {
+        if let Some(root) = repo.workdir() {
+            return Self::load(root);
+        }
+        let Some(content) = read_head_file(repo, Path::new(CONFIG_FILE)) else {
+            return Self::default();
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!("Invalid {} in HEAD: {}. Ignoring it.", CONFIG_FILE, e);
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let Some(product) = doc.get("product") else {
+            return Ok(Self::default());
+        };
+        let product = product.as_table_like().ok_or("[product] must be a table")?;
+
+        let text = |key: &str| -> Result<Option<String>, String> {
+            match product.get(key) {
+                None => Ok(None),
+                Some(item) => item
+                    .as_str()
+                    .map(|s| Some(s.trim().to_string()).filter(|s| !s.is_empty()))
+                    .ok_or_else(|| format!("product.{} must be a string", key)),
+            }
+        };
+
+        let mut glossary = Vec::new();
+        if let Some(item) = product.get("glossary") {
+            let table = item
+                .as_table_like()
+                .ok_or("product.glossary must be a table")?;
+            for (term, meaning) in table.iter() {
+                let meaning = meaning
+                    .as_str()
+                    .ok_or_else(|| format!("product.glossary.{} must be a string", term))?;
+                glossary.push((term.to_string(), meaning.trim().to_string()));
+            }
+        }
+
+        Ok(Self {
+            description: text("description")?,
+            audience: text("audience")?,
+            glossary,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.description.is_none() && self.audience.is_none() && self.glossary.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_parse_product_table() {
+        let context = ProductContext::parse(
+            r#"
+[product]
+description = "Status pages for small teams"
+audience = "Operators"
+
+[product.glossary]
+Lane = "A deployment target"
+"Probe run" = "One round of health checks"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            context.description.as_deref(),
+            Some("Status pages for small teams")
+        );
+        assert_eq!(context.audience.as_deref(), Some("Operators"));
+        assert_eq!(
+            context.glossary,
+            vec![
+                ("Lane".to_string(), "A deployment target".to_string()),
+                (
+                    "Probe run".to_string(),
+                    "One round of health checks".to_string()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_without_product_table_is_empty() {
+        assert!(
+            ProductContext::parse("[other]\nkey = 1\n")
+                .unwrap()
+                .is_empty()
+        );
+        assert!(ProductContext::parse("[product]\ndescription = 3\n").is_err());
+    }
+
+    #[test]
+    fn test_load_missing_or_invalid_file_is_empty() {
+        let dir = TempDir::new().unwrap();
+        assert!(ProductContext::load(dir.path()).is_empty());
+
+        std::fs::write(dir.path().join(CONFIG_FILE), "[product\n").unwrap();
+        assert!(ProductContext::load(dir.path()).is_empty());
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[product]\naudience = \"Developers\"\n",
+        )
+        .unwrap();
+        assert_eq!(
+            ProductContext::load(dir.path()).audience.as_deref(),
+            Some("Developers")
+        );
+    }
+
+    #[test]
+    fn test_for_repository_reads_head_of_bare_repository() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init_bare(dir.path()).unwrap();
+        let blob = repo
+            .blob(b"[product]\ndescription = \"Status pages\"\n")
+            .unwrap();
+        let mut builder = repo.treebuilder(None).unwrap();
+        builder.insert(CONFIG_FILE, blob, 0o100644).unwrap();
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "chore: init", &tree, &[])
+            .unwrap();
+
+        assert_eq!(
+            ProductContext::for_repository(&repo).description.as_deref(),
+            Some("Status pages")
+        );
+    }
+}
@@ -0,0 +1,386 @@
+//! Post-processing of LLM-generated entries into single-line plain markdown.
+//!
+//! LLMs occasionally emit links, images, HTML, code fences, or headings inside
+//! entry descriptions, which breaks the changelog structure. Entries are
+//! normalized to one line of plain text (optionally keeping inline code);
+//! entries that cannot be salvaged are rejected so the caller can regenerate.
+
+use std::env;
+use std::sync::LazyLock;
+
+use regex_lite::Regex;
+use tracing::{debug, warn};
+
+use crate::changelog::ChangelogOutput;
+use crate::llm::{ChangelogRequest, LlmCompletion, LlmError, LlmRouter};
+
+/// Environment variable controlling whether inline code spans are kept.
+const INLINE_CODE_ENV_VAR: &str = "KERYX_ALLOW_INLINE_CODE";
+
+/// Longest description (in characters) accepted after sanitization.
+const MAX_ENTRY_CHARS: usize = 400;
+
+/// Longest highlights paragraph (in characters) accepted after sanitization.
+const MAX_HIGHLIGHTS_CHARS: usize = 800;
+
+/// Most lines an entry may span before it is considered pathological.
+const MAX_ENTRY_LINES: usize = 4;
+
+/// `![alt](url)` images.
+static IMAGE_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"!\[([^\]]*)\]\([^)]*\)").expect("Invalid image regex - this is a bug")
+});
+
+/// `[text](url)` links and `[text][ref]` reference links.
+static LINK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\[([^\]]+)\](?:\([^)]*\)|\[[^\]]*\])").expect("Invalid link regex - this is a bug")
+});
+
+/// Common HTML tags such as `<img src=...>` or `<br/>`.
+///
+/// Limited to known tag names so generics like `Vec<String>` survive.
+static HTML_TAG_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)</?(?:a|b|i|u|em|strong|br|hr|img|p|div|span|pre|code|sup|sub|kbd|details|summary|h[1-6]|ul|ol|li|table|tr|td|th)(?:\s[^<>]*)?/?>",
+    )
+    .expect("Invalid HTML regex - this is a bug")
+});
+
+/// Block markers at the start of a line: headings, quotes, list bullets.
+static BLOCK_MARKER_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:#{1,6}\s+|>\s*|[-*+]\s+|\d+[.)]\s+)")
+        .expect("Invalid block marker regex - this is a bug")
+});
+
+/// What the sanitizer allows in entry descriptions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SanitizeOptions {
+    /// Keep `inline code` spans (backticks are stripped otherwise).
+    pub allow_inline_code: bool,
+}
+
+impl Default for SanitizeOptions {
+    fn default() -> Self {
+        Self {
+            allow_inline_code: true,
+        }
+    }
+}
+
+impl SanitizeOptions {
+    /// Read options from `KERYX_ALLOW_INLINE_CODE` (`true`/`false`).
+    ///
+    /// Logs a warning and uses the default if the variable is set but invalid.
+    pub fn from_env() -> Self {
+        let allow_inline_code = match env::var(INLINE_CODE_ENV_VAR) {
+            Ok(v) if !v.is_empty() => match v.to_lowercase().as_str() {
+                "1" | "true" | "yes" => true,
+                "0" | "false" | "no" => false,
+                _ => {
+                    warn!(
+                        "Invalid {} value '{}', allowing inline code",
+                        INLINE_CODE_ENV_VAR, v
+                    );
+                    true
+                }
+            },
+            _ => true,
+        };
+        Self { allow_inline_code }
+    }
+}
+
+/// Why an entry could not be salvaged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Rejection {
+    /// Nothing but markup (or whitespace) was left.
+    Empty,
+    /// The description spans too many lines (e.g. an embedded code block).
+    TooManyLines(usize),
+    /// The description is too long to be a changelog entry.
+    TooLong(usize),
+}
+
+impl std::fmt::Display for Rejection {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Rejection::Empty => write!(f, "empty after removing markup"),
+            Rejection::TooManyLines(n) => write!(f, "spans {} lines", n),
+            Rejection::TooLong(n) => write!(f, "{} characters long", n),
+        }
+    }
+}
+
+/// Sanitize one entry description into a single line of plain markdown.
+pub fn sanitize_entry(description: &str, options: SanitizeOptions) -> Result<String, Rejection> {
+    let line_count = description.lines().filter(|l| !l.trim().is_empty()).count();
+    if line_count > MAX_ENTRY_LINES {
+        return Err(Rejection::TooManyLines(line_count));
+    }
+    flatten(description, options, MAX_ENTRY_CHARS)
+}
+
+/// Strip block-level markup and join everything into one line.
+fn flatten(text: &str, options: SanitizeOptions, max_chars: usize) -> Result<String, Rejection> {
+    let mut text = text.replace("```", " ");
+    text = IMAGE_REGEX.replace_all(&text, "").into_owned();
+    text = LINK_REGEX.replace_all(&text, "$1").into_owned();
+    text = HTML_TAG_REGEX.replace_all(&text, "").into_owned();
+
+    let joined: Vec<String> = text
+        .lines()
+        .map(|line| BLOCK_MARKER_REGEX.replace(line, "").into_owned())
+        .collect();
+    text = joined.join(" ");
+
+    // Unbalanced backticks would swallow the rest of the section
+    if !options.allow_inline_code || text.matches('`').count() % 2 != 0 {
+        text = text.replace('`', "");
+    }
+
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    let text = text.trim();
+
+    let char_count = text.chars().count();
+    if char_count == 0 {
+        return Err(Rejection::Empty);
+    }
+    if char_count > max_chars {
+        return Err(Rejection::TooLong(char_count));
+    }
+    Ok(text.to_string())
+}
+
+/// Result of sanitizing a whole output.
+#[derive(Debug, Default)]
+pub struct SanitizeReport {
+    /// Number of entries whose description was rewritten.
+    pub modified: usize,
+    /// Removed entries with the reason they were rejected.
+    pub rejected: Vec<(String, Rejection)>,
+}
+
+/// Sanitize all entries (and highlights) in place, removing rejected entries.
+pub fn sanitize_output(output: &mut ChangelogOutput, options: SanitizeOptions) -> SanitizeReport {
+    let mut report = SanitizeReport::default();
+
+    output
+        .entries
+        .retain_mut(|entry| match sanitize_entry(&entry.description, options) {
+            Ok(clean) => {
+                if clean != entry.description {
+                    debug!("Sanitized entry '{}' -> '{}'", entry.description, clean);
+                    report.modified += 1;
+                    entry.description = clean;
+                }
+                true
+            }
+            Err(reason) => {
+                report
+                    .rejected
+                    .push((std::mem::take(&mut entry.description), reason));
+                false
+            }
+        });
+
+    if let Some(highlights) = &output.highlights {
+        output.highlights = sanitize_highlights(highlights, options);
+    }
+
+    report
+}
+
+/// Highlights may span several lines and sentences but are flattened the same
+/// way; unusable highlights are dropped rather than regenerated.
+fn sanitize_highlights(highlights: &str, options: SanitizeOptions) -> Option<String> {
+    flatten(highlights, options, MAX_HIGHLIGHTS_CHARS).ok()
+}
+
+/// Instruction appended to the prompt when regenerating after rejections.
+fn regeneration_note(rejected: &[(String, Rejection)]) -> String {
+    let mut note = String::from(
+        "\n\n## Formatting Correction\nA previous attempt produced malformed entries. Every description must be a single line of plain text: no links, images, HTML, headings, lists, or code blocks.",
+    );
+    for (_, reason) in rejected.iter().take(5) {
+        note.push_str(&format!("\n- An entry was rejected: {}", reason));
+    }
+    note
+}
+
+/// Generate changelog entries and sanitize them, regenerating once if any
+/// entry had to be rejected.
+///
+/// Entries still rejected after the retry are dropped with a warning.
+pub async fn generate_sanitized(
+    llm: &mut LlmRouter,
+    prompt: &str,
+    options: SanitizeOptions,
+) -> Result<LlmCompletion, LlmError> {
+    let mut completion = llm.request(&ChangelogRequest::new(prompt)).await?;
+    let report = sanitize_output(&mut completion.output, options);
+    if report.rejected.is_empty() {
+        return Ok(completion);
+    }
+
+    eprintln!(
+        "\x1b[33m⚠ {} malformed changelog entries, regenerating...\x1b[0m",
+        report.rejected.len()
+    );
+    let retry_prompt = format!("{}{}", prompt, regeneration_note(&report.rejected));
+    let mut retried = match llm.request(&ChangelogRequest::new(retry_prompt)).await {
+        Ok(retried) => retried,
+        Err(e) => {
+            warn!(
+                "Regeneration failed: {}. Keeping sanitized entries.",
+                e.summary()
+            );
+            return Ok(completion);
+        }
+    };
+
+    let retry_report = sanitize_output(&mut retried.output, options);
+    if !retry_report.rejected.is_empty() {
+        eprintln!(
+            "\x1b[33m⚠ Dropped {} malformed changelog entries\x1b[0m",
+            retry_report.rejected.len()
+        );
+        for (description, reason) in &retry_report.rejected {
+            debug!("Dropped entry ({}): {}", reason, description);
+        }
+    }
+    Ok(retried)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::{ChangelogCategory, ChangelogEntry};
+
+    fn allow() -> SanitizeOptions {
+        SanitizeOptions::default()
+    }
+
+    #[test]
+    fn test_plain_entry_unchanged() {
+        assert_eq!(
+            sanitize_entry("Add dark mode toggle", allow()),
+            Ok("Add dark mode toggle".to_string())
+        );
+    }
+
+    #[test]
+    fn test_links_and_images_are_flattened() {
+        assert_eq!(
+            sanitize_entry(
+                "Add [dark mode](https://example.com/pr/1) ![screenshot](img.png)",
+                allow()
+            ),
+            Ok("Add dark mode".to_string())
+        );
+        assert_eq!(
+            sanitize_entry("Fix crash (see [#12][pr12])", allow()),
+            Ok("Fix crash (see #12)".to_string())
+        );
+    }
+
+    #[test]
+    fn test_html_and_headings_are_removed() {
+        assert_eq!(
+            sanitize_entry("## Support <b>YAML</b> configs<br/>", allow()),
+            Ok("Support YAML configs".to_string())
+        );
+    }
+
+    #[test]
+    fn test_generics_are_not_mistaken_for_html() {
+        assert_eq!(
+            sanitize_entry("Return `Vec<String>` from Option<Config> helpers", allow()),
+            Ok("Return `Vec<String>` from Option<Config> helpers".to_string())
+        );
+    }
+
+    #[test]
+    fn test_multiline_is_joined() {
+        assert_eq!(
+            sanitize_entry("Faster startup\n- cached index\n- lazy plugins", allow()),
+            Ok("Faster startup cached index lazy plugins".to_string())
+        );
+    }
+
+    #[test]
+    fn test_inline_code_policy() {
+        assert_eq!(
+            sanitize_entry("Add `--json` flag", allow()),
+            Ok("Add `--json` flag".to_string())
+        );
+        let strip = SanitizeOptions {
+            allow_inline_code: false,
+        };
+        assert_eq!(
+            sanitize_entry("Add `--json` flag", strip),
+            Ok("Add --json flag".to_string())
+        );
+        // Unbalanced backticks are always stripped
+        assert_eq!(
+            sanitize_entry("Add `--json flag", allow()),
+            Ok("Add --json flag".to_string())
+        );
+    }
+
+    #[test]
+    fn test_pathological_entries_are_rejected() {
+        assert_eq!(sanitize_entry("![](a.png)", allow()), Err(Rejection::Empty));
+        assert_eq!(
+            sanitize_entry(
+                "```rust\nfn a() {}\nfn b() {}\nfn c() {}\nfn d() {}\n```",
+                allow()
+            ),
+            Err(Rejection::TooManyLines(6))
+        );
+        assert!(matches!(
+            sanitize_entry(&"word ".repeat(100), allow()),
+            Err(Rejection::TooLong(_))
+        ));
+    }
+
+    #[test]
+    fn test_sanitize_output_removes_rejected_entries() {
+        let mut output = ChangelogOutput {
+            entries: vec![
+                ChangelogEntry {
+                    category: ChangelogCategory::Added,
+                    description: "Add [search](http://x)".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
+                },
+                ChangelogEntry {
+                    category: ChangelogCategory::Fixed,
+                    description: "<img src=x>".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
+                },
+            ],
+            highlights: Some("A [big](http://x) release.".to_string()),
+        };
+
+        let report = sanitize_output(&mut output, allow());
+
+        assert_eq!(report.modified, 1);
+        assert_eq!(report.rejected.len(), 1);
+        assert_eq!(output.entries.len(), 1);
+        assert_eq!(output.entries[0].description, "Add search");
+        assert_eq!(output.highlights.as_deref(), Some("A big release."));
+    }
+
+    #[test]
+    fn test_options_from_env() {
+        temp_env::with_var(INLINE_CODE_ENV_VAR, Some("false"), || {
+            assert!(!SanitizeOptions::from_env().allow_inline_code);
+        });
+        temp_env::with_var(INLINE_CODE_ENV_VAR, Some("maybe"), || {
+            assert!(SanitizeOptions::from_env().allow_inline_code);
+        });
+    }
+}
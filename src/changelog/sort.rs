@@ -0,0 +1,255 @@
+//! Order of entries within a category.
+//!
+//! Entries come out of the LLM in whatever order it wrote them. A sort
+//! policy orders each category when the section is written; sections
+//! already in the changelog are never reordered. Sorting by scope or
+//! importance needs the LLM to tag each entry, which the policy's
+//! [`EntrySort::prompt_instruction`] asks for. Sorts are stable, so entries
+//! with the same key keep their generated order.
+
+use std::env;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer, Serialize};
+use tracing::warn;
+
+use crate::changelog::ChangelogEntry;
+
+/// Environment variable selecting the entry sort policy.
+const SORT_ENV_VAR: &str = "KERYX_SORT_ENTRIES";
+
+/// Highest importance an entry can have.
+pub const MAX_IMPORTANCE: u8 = 5;
+
+/// How entries are ordered within a category.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum EntrySort {
+    /// The order the LLM returned (the historical default).
+    #[default]
+    Source,
+    /// Case-insensitive by description.
+    Alphabetical,
+    /// Grouped by scope (`cli`, `api`, ...), alphabetically; entries
+    /// without a scope last. The scope is shown as a bold prefix.
+    Scope,
+    /// Most important first, by the weight the LLM assigned.
+    Importance,
+}
+
+impl FromStr for EntrySort {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "source" => Ok(EntrySort::Source),
+            "alphabetical" | "alpha" => Ok(EntrySort::Alphabetical),
+            "scope" => Ok(EntrySort::Scope),
+            "importance" => Ok(EntrySort::Importance),
+            _ => Err(format!(
+                "invalid entry sort '{}' (expected 'source', 'alphabetical', 'scope' or 'importance')",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for EntrySort {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            EntrySort::Source => "source",
+            EntrySort::Alphabetical => "alphabetical",
+            EntrySort::Scope => "scope",
+            EntrySort::Importance => "importance",
+        })
+    }
+}
+
+impl EntrySort {
+    /// Use the CLI flag if given, else `KERYX_SORT_ENTRIES`.
+    ///
+    /// Logs a warning and uses the default if the variable is set but invalid.
+    pub fn from_flag_or_env(flag: Option<EntrySort>) -> Self {
+        if let Some(sort) = flag {
+            return sort;
+        }
+        match env::var(SORT_ENV_VAR) {
+            Ok(value) if !value.trim().is_empty() => value.parse().unwrap_or_else(|e| {
+                warn!("Ignoring {}: {}", SORT_ENV_VAR, e);
+                EntrySort::default()
+            }),
+            _ => EntrySort::default(),
+        }
+    }
+
+    /// Generation prompt instruction and example JSON field for the entry
+    /// tag this policy sorts by, if any.
+    pub fn prompt_instruction(&self) -> Option<(&'static str, &'static str)> {
+        match self {
+            EntrySort::Scope => Some((
+                "Give each entry a \"scope\": the area of the product it affects, as one short lowercase word shared by related entries (e.g. \"cli\", \"api\", \"docs\")",
+                r#", "scope": "cli""#,
+            )),
+            EntrySort::Importance => Some((
+                "Rate each entry's \"importance\" to users from 1 (minor) to 5 (major)",
+                r#", "importance": 3"#,
+            )),
+            EntrySort::Source | EntrySort::Alphabetical => None,
+        }
+    }
+
+    /// Order `entries` (all of one category) by this policy.
+    pub fn sort(&self, entries: &mut [&ChangelogEntry]) {
+        match self {
+            EntrySort::Source => {}
+            EntrySort::Alphabetical => {
+                entries.sort_by_cached_key(|e| e.description.to_lowercase());
+            }
+            EntrySort::Scope => entries.sort_by_cached_key(|e| match &e.scope {
+                Some(scope) => (false, scope.to_lowercase()),
+                None => (true, String::new()),
+            }),
+            EntrySort::Importance => {
+                entries.sort_by_key(|e| std::cmp::Reverse(e.importance.unwrap_or(0)));
+            }
+        }
+    }
+
+    /// The entry line's text: the description, prefixed with the scope when
+    /// sorting by scope.
+    pub fn entry_text(&self, entry: &ChangelogEntry) -> String {
+        match (self, entry.scope.as_deref().map(str::trim)) {
+            (EntrySort::Scope, Some(scope)) if !scope.is_empty() => {
+                format!("**{}:** {}", scope, entry.description)
+            }
+            _ => entry.description.clone(),
+        }
+    }
+}
+
+/// Read an LLM-assigned importance leniently: numbers are rounded and
+/// clamped to `1..=5`, anything else is treated as unrated rather than
+/// failing the whole response.
+pub(crate) fn deserialize_importance<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = Option::<serde_json::Value>::deserialize(deserializer)?;
+    let number = match &value {
+        Some(serde_json::Value::Number(n)) => n.as_f64(),
+        Some(serde_json::Value::String(s)) => s.trim().parse::<f64>().ok(),
+        _ => None,
+    };
+    Ok(number
+        .filter(|n| n.is_finite())
+        .map(|n| n.round().clamp(1.0, f64::from(MAX_IMPORTANCE)) as u8))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogCategory;
+    use serial_test::serial;
+
+    fn entry(description: &str, scope: Option<&str>, importance: Option<u8>) -> ChangelogEntry {
+        ChangelogEntry {
+            category: ChangelogCategory::Added,
+            description: description.to_string(),
+            sources: Vec::new(),
+            scope: scope.map(String::from),
+            importance,
+        }
+    }
+
+    fn sorted(sort: EntrySort, entries: &[ChangelogEntry]) -> Vec<&str> {
+        let mut refs: Vec<&ChangelogEntry> = entries.iter().collect();
+        sort.sort(&mut refs);
+        refs.iter().map(|e| e.description.as_str()).collect()
+    }
+
+    #[test]
+    fn test_parse_entry_sort() {
+        assert_eq!("Alpha".parse::<EntrySort>(), Ok(EntrySort::Alphabetical));
+        assert_eq!(" scope ".parse::<EntrySort>(), Ok(EntrySort::Scope));
+        assert!("random".parse::<EntrySort>().is_err());
+    }
+
+    #[test]
+    fn test_sort_policies() {
+        let entries = vec![
+            entry("zoom controls", Some("ui"), Some(2)),
+            entry("Export to CSV", None, Some(5)),
+            entry("api tokens", Some("api"), None),
+            entry("Dark mode", Some("ui"), Some(4)),
+        ];
+        assert_eq!(
+            sorted(EntrySort::Source, &entries),
+            vec!["zoom controls", "Export to CSV", "api tokens", "Dark mode"]
+        );
+        assert_eq!(
+            sorted(EntrySort::Alphabetical, &entries),
+            vec!["api tokens", "Dark mode", "Export to CSV", "zoom controls"]
+        );
+        assert_eq!(
+            sorted(EntrySort::Scope, &entries),
+            vec!["api tokens", "zoom controls", "Dark mode", "Export to CSV"]
+        );
+        assert_eq!(
+            sorted(EntrySort::Importance, &entries),
+            vec!["Export to CSV", "Dark mode", "zoom controls", "api tokens"]
+        );
+    }
+
+    #[test]
+    fn test_entry_text_shows_scope_only_when_sorting_by_scope() {
+        let tagged = entry("Dark mode", Some("ui"), None);
+        assert_eq!(EntrySort::Scope.entry_text(&tagged), "**ui:** Dark mode");
+        assert_eq!(EntrySort::Alphabetical.entry_text(&tagged), "Dark mode");
+        let untagged = entry("Dark mode", None, None);
+        assert_eq!(EntrySort::Scope.entry_text(&untagged), "Dark mode");
+    }
+
+    #[test]
+    fn test_importance_is_read_leniently() {
+        let parsed: Vec<ChangelogEntry> = serde_json::from_str(
+            r#"[
+                {"category": "Added", "description": "a", "importance": 4.6},
+                {"category": "Added", "description": "b", "importance": 9},
+                {"category": "Added", "description": "c", "importance": "2"},
+                {"category": "Added", "description": "d", "importance": "high"},
+                {"category": "Added", "description": "e"}
+            ]"#,
+        )
+        .unwrap();
+        let importance: Vec<Option<u8>> = parsed.iter().map(|e| e.importance).collect();
+        assert_eq!(importance, vec![Some(5), Some(5), Some(2), None, None]);
+    }
+
+    #[test]
+    fn test_tags_survive_serialization() {
+        let original = entry("Dark mode", Some("ui"), Some(4));
+        let json = serde_json::to_string(&original).unwrap();
+        let parsed: ChangelogEntry = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed.scope.as_deref(), Some("ui"));
+        assert_eq!(parsed.importance, Some(4));
+
+        let untagged = serde_json::to_string(&entry("x", None, None)).unwrap();
+        assert!(!untagged.contains("scope") && !untagged.contains("importance"));
+    }
+
+    #[test]
+    #[serial]
+    fn test_from_flag_or_env() {
+        temp_env::with_var(SORT_ENV_VAR, Some("importance"), || {
+            assert_eq!(EntrySort::from_flag_or_env(None), EntrySort::Importance);
+            assert_eq!(
+                EntrySort::from_flag_or_env(Some(EntrySort::Scope)),
+                EntrySort::Scope
+            );
+        });
+        temp_env::with_var(SORT_ENV_VAR, Some("sideways"), || {
+            assert_eq!(EntrySort::from_flag_or_env(None), EntrySort::Source);
+        });
+    }
+}
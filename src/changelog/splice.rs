@@ -0,0 +1,332 @@
+//! Anchored edits of large changelogs.
+//!
+//! Adding a release only touches the top of a changelog (and, with compare
+//! links, its `[Unreleased]:` link definition), but projects with a long
+//! history have changelogs of several megabytes. Instead of loading and
+//! re-rendering the whole file, [`ChangelogSplice::scan`] reads it once line
+//! by line and keeps only the part above the insertion point: the header and
+//! any `[Unreleased]` section. [`ChangelogSplice::write_to`] then writes that
+//! part, the new section, and copies the rest of the file through unchanged.
+
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+
+use semver::Version;
+
+use crate::error::ChangelogError;
+
+use super::line_ending::LineEnding;
+use super::parser::{FROZEN_MARKER, find_version_token};
+use super::writer::{CompareLinks, unreleased_link_line, update_compare_links, version_link_line};
+
+/// Where a new release section goes in an existing changelog.
+#[derive(Debug)]
+pub struct ChangelogSplice {
+    path: PathBuf,
+    /// The file above the insertion point.
+    head: String,
+    /// Byte offset of the insertion point.
+    rest_offset: u64,
+    line_ending: LineEnding,
+    /// The section of the version being written is frozen.
+    frozen: bool,
+    /// The file has an `[Unreleased]:` link definition below the head.
+    unreleased_link_in_rest: bool,
+    /// The file already has a link definition for the version.
+    has_version_link: bool,
+}
+
+impl ChangelogSplice {
+    /// Scan the changelog at `path` for inserting a section for `version`.
+    /// `None` if the file does not exist.
+    pub fn scan(path: &Path, version: &Version) -> Result<Option<Self>, ChangelogError> {
+        let file = match File::open(path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(ChangelogError::ReadFailed(e)),
+        };
+        let mut reader = BufReader::new(file);
+
+        let version_link = format!("[{}]:", version);
+        let mut head = String::new();
+        let mut rest_offset: Option<u64> = None;
+        let mut offset = 0u64;
+        let mut in_unreleased = false;
+        let mut current: Option<Version> = None;
+        let mut seen_unreleased_link = false;
+        let mut splice = Self {
+            path: path.to_path_buf(),
+            head: String::new(),
+            rest_offset: 0,
+            line_ending: LineEnding::Lf,
+            frozen: false,
+            unreleased_link_in_rest: false,
+            has_version_link: false,
+        };
+        let (mut crlf, mut lf) = (0usize, 0usize);
+
+        let mut buf = Vec::new();
+        while let Some(line) = read_line(&mut reader, &mut buf)? {
+            if line.ends_with("\r\n") {
+                crlf += 1;
+            } else if line.ends_with('\n') {
+                lf += 1;
+            }
+
+            if let Some(title) = line.strip_prefix("## ") {
+                current = find_version_token(title);
+                if rest_offset.is_none() {
+                    if in_unreleased || !title.to_lowercase().contains("unreleased") {
+                        rest_offset = Some(offset);
+                    } else {
+                        in_unreleased = true;
+                    }
+                }
+            }
+            if line.contains(FROZEN_MARKER) && current.as_ref() == Some(version) {
+                splice.frozen = true;
+            }
+            if line.starts_with(&version_link) {
+                splice.has_version_link = true;
+            }
+            if !seen_unreleased_link && is_unreleased_link(line) {
+                seen_unreleased_link = true;
+                splice.unreleased_link_in_rest = rest_offset.is_some();
+            }
+
+            if rest_offset.is_none() {
+                head.push_str(line);
+            }
+            offset += line.len() as u64;
+        }
+
+        splice.head = head;
+        splice.rest_offset = rest_offset.unwrap_or(offset);
+        splice.line_ending = if crlf > lf {
+            LineEnding::CrLf
+        } else {
+            LineEnding::Lf
+        };
+        Ok(Some(splice))
+    }
+
+    /// Whether the section of the version being written is frozen.
+    pub fn is_frozen(&self) -> bool {
+        self.frozen
+    }
+
+    /// Write the changelog with `section` inserted at the insertion point to
+    /// `out`, pointing the compare links at the new release if `links` is
+    /// given. The head and the new section use the file's line ending; the
+    /// rest of the file is copied byte for byte.
+    pub fn write_to(
+        &self,
+        section: &str,
+        version: &Version,
+        links: Option<&CompareLinks>,
+        out: &mut impl Write,
+    ) -> Result<(), ChangelogError> {
+        let head = match links {
+            Some(links) => update_compare_links(&self.head, version, links),
+            None => self.head.clone(),
+        };
+        let mut inserted = head;
+        inserted.push_str(section);
+        inserted.push('\n');
+        out.write_all(self.line_ending.apply(&inserted).as_bytes())
+            .map_err(ChangelogError::WriteFailed)?;
+
+        let mut file = File::open(&self.path).map_err(ChangelogError::ReadFailed)?;
+        file.seek(SeekFrom::Start(self.rest_offset))
+            .map_err(ChangelogError::ReadFailed)?;
+        match links {
+            Some(links) if self.unreleased_link_in_rest => {
+                self.copy_relinking(BufReader::new(file), version, links, out)
+            }
+            _ => copy(&mut file, out),
+        }
+    }
+
+    /// Copy the rest of the file, replacing the first `[Unreleased]:` link
+    /// definition and adding the version's link below it.
+    fn copy_relinking(
+        &self,
+        mut reader: impl BufRead,
+        version: &Version,
+        links: &CompareLinks,
+        out: &mut impl Write,
+    ) -> Result<(), ChangelogError> {
+        let mut relinked = false;
+        let mut buf = Vec::new();
+        while let Some(line) = read_line(&mut reader, &mut buf)? {
+            if relinked || !is_unreleased_link(line) {
+                out.write_all(line.as_bytes())
+                    .map_err(ChangelogError::WriteFailed)?;
+                continue;
+            }
+            relinked = true;
+
+            let content = line.trim_end_matches(['\r', '\n']);
+            let terminator = &line[content.len()..];
+            let mut replacement = unreleased_link_line(links);
+            if !self.has_version_link {
+                let separator = match terminator {
+                    "" => self.line_ending.apply("\n"),
+                    terminator => terminator.to_string(),
+                };
+                replacement.push_str(&separator);
+                replacement.push_str(&version_link_line(version, links));
+            }
+            replacement.push_str(terminator);
+            out.write_all(replacement.as_bytes())
+                .map_err(ChangelogError::WriteFailed)?;
+        }
+        Ok(())
+    }
+}
+
+/// Whether `line` is the `[Unreleased]:` link definition.
+fn is_unreleased_link(line: &str) -> bool {
+    line.get(..13)
+        .is_some_and(|prefix| prefix.eq_ignore_ascii_case("[unreleased]:"))
+}
+
+/// Read one line (with its terminator) into `buf`; `None` at the end of the
+/// file.
+fn read_line<'a>(
+    reader: &mut impl BufRead,
+    buf: &'a mut Vec<u8>,
+) -> Result<Option<&'a str>, ChangelogError> {
+    buf.clear();
+    let read = reader
+        .read_until(b'\n', buf)
+        .map_err(ChangelogError::ReadFailed)?;
+    if read == 0 {
+        return Ok(None);
+    }
+    std::str::from_utf8(buf)
+        .map(Some)
+        .map_err(|e| ChangelogError::ReadFailed(io::Error::new(io::ErrorKind::InvalidData, e)))
+}
+
+fn copy(reader: &mut impl Read, out: &mut impl Write) -> Result<(), ChangelogError> {
+    let mut buf = [0u8; 64 * 1024];
+    loop {
+        let read = reader.read(&mut buf).map_err(ChangelogError::ReadFailed)?;
+        if read == 0 {
+            return Ok(());
+        }
+        out.write_all(&buf[..read])
+            .map_err(ChangelogError::WriteFailed)?;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn splice(content: &str, version: &Version, links: Option<&CompareLinks>) -> String {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(&path, content).unwrap();
+        let scanned = ChangelogSplice::scan(&path, version).unwrap().unwrap();
+        let mut out = Vec::new();
+        scanned
+            .write_to(
+                "## [1.1.0] - 2024-06-01\n\n- New\n",
+                version,
+                links,
+                &mut out,
+            )
+            .unwrap();
+        String::from_utf8(out).unwrap()
+    }
+
+    fn links() -> CompareLinks {
+        CompareLinks {
+            repo_url: "https://github.com/o/r".to_string(),
+            tag: "v1.1.0".to_string(),
+            previous_tag: Some("v1.0.0".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_inserts_below_unreleased() {
+        let content =
+            "# Changelog\n\n## [Unreleased]\n\n- WIP\n\n## [1.0.0] - 2024-01-01\n\n- Initial\n";
+        assert_eq!(
+            splice(content, &Version::new(1, 1, 0), None),
+            "# Changelog\n\n## [Unreleased]\n\n- WIP\n\n## [1.1.0] - 2024-06-01\n\n- New\n\n## [1.0.0] - 2024-01-01\n\n- Initial\n"
+        );
+    }
+
+    #[test]
+    fn test_head_only_changelog() {
+        assert_eq!(
+            splice(
+                "# Changelog\n\n## [Unreleased]\n",
+                &Version::new(1, 1, 0),
+                None
+            ),
+            "# Changelog\n\n## [Unreleased]\n## [1.1.0] - 2024-06-01\n\n- New\n\n"
+        );
+    }
+
+    #[test]
+    fn test_matches_in_memory_insertion() {
+        let version = Version::new(1, 1, 0);
+        let content = "# Changelog\n\n## [1.0.0] - 2024-01-01\n\n- Initial\n\n[Unreleased]: https://github.com/o/r/compare/v1.0.0...HEAD\n[1.0.0]: https://github.com/o/r/releases/tag/v1.0.0\n";
+        let section = "## [1.1.0] - 2024-06-01\n\n- New\n";
+        let insertion_point = crate::changelog::parser::find_insertion_point(content);
+        let expected = update_compare_links(
+            &format!(
+                "{}{}\n{}",
+                &content[..insertion_point],
+                section,
+                &content[insertion_point..]
+            ),
+            &version,
+            &links(),
+        );
+
+        assert_eq!(splice(content, &version, Some(&links())), expected);
+        assert!(expected.contains(
+            "[Unreleased]: https://github.com/o/r/compare/v1.1.0...HEAD\n[1.1.0]: https://github.com/o/r/compare/v1.0.0...v1.1.0\n[1.0.0]:"
+        ));
+    }
+
+    #[test]
+    fn test_keeps_crlf_and_copies_rest_verbatim() {
+        let content = "# Changelog\r\n\r\n## [1.0.0] - 2024-01-01\r\n\r\n- Initial\r\n\r\n[Unreleased]: https://github.com/o/r/compare/v1.0.0...HEAD";
+        assert_eq!(
+            splice(content, &Version::new(1, 1, 0), Some(&links())),
+            "# Changelog\r\n\r\n## [1.1.0] - 2024-06-01\r\n\r\n- New\r\n\r\n## [1.0.0] - 2024-01-01\r\n\r\n- Initial\r\n\r\n[Unreleased]: https://github.com/o/r/compare/v1.1.0...HEAD\r\n[1.1.0]: https://github.com/o/r/compare/v1.0.0...v1.1.0"
+        );
+    }
+
+    #[test]
+    fn test_detects_frozen_section_anywhere() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(
+            &path,
+            "# Changelog\n\n## [2.0.0] - 2024-06-01\n\n## [1.0.0] - 2024-01-01\n\n<!-- keryx:frozen -->\n- Reviewed\n",
+        )
+        .unwrap();
+
+        let frozen = |version| {
+            ChangelogSplice::scan(&path, &version)
+                .unwrap()
+                .unwrap()
+                .is_frozen()
+        };
+        assert!(frozen(Version::new(1, 0, 0)));
+        assert!(!frozen(Version::new(2, 0, 0)));
+        assert!(
+            ChangelogSplice::scan(&dir.path().join("missing.md"), &Version::new(1, 0, 0))
+                .unwrap()
+                .is_none()
+        );
+    }
+}
@@ -0,0 +1,517 @@
+//! Spelling and terminology rules for entries.
+//!
+//! Applied to the final entries before they are written. Misspellings from a
+//! built-in list of common ones are fixed when the correction is unambiguous
+//! and flagged otherwise; this is not a full dictionary check. Project terms
+//! from the `[style]` table of `keryx.toml` are enforced as written:
+//!
+//! ```toml
+//! [style]
+//! # Fix common misspellings (default: true)
+//! spell_check = true
+//! # Always written like this, whatever case the LLM used
+//! terms = ["GitHub", "macOS", "Changelog"]
+//!
+//! [style.replace]
+//! # Variant = preferred form
+//! "change log" = "Changelog"
+//! "e-mail" = "email"
+//! ```
+//!
+//! Matching is case-insensitive and on whole words; inline code is left
+//! alone, as are words that are part of paths, URLs or identifiers.
+
+use std::path::Path;
+
+use git2::Repository;
+use tracing::warn;
+
+use crate::changelog::ChangelogOutput;
+use crate::changelog::product::CONFIG_FILE;
+use crate::git::read_head_file;
+
+/// Common misspellings in release notes and their corrections. More than one
+/// correction makes the misspelling ambiguous: it is flagged, not fixed.
+const MISSPELLINGS: &[(&str, &[&str])] = &[
+    ("accomodate", &["accommodate"]),
+    ("accross", &["across"]),
+    ("acheive", &["achieve"]),
+    ("adress", &["address"]),
+    ("alot", &["a lot"]),
+    ("arguement", &["argument"]),
+    ("arguements", &["arguments"]),
+    ("begining", &["beginning"]),
+    ("beleive", &["believe"]),
+    ("calender", &["calendar"]),
+    ("comitted", &["committed"]),
+    ("commited", &["committed"]),
+    ("compatability", &["compatibility"]),
+    ("compatable", &["compatible"]),
+    ("configuraiton", &["configuration"]),
+    ("definately", &["definitely"]),
+    ("dependancies", &["dependencies"]),
+    ("dependancy", &["dependency"]),
+    ("enviroment", &["environment"]),
+    ("existant", &["existent"]),
+    ("explicitely", &["explicitly"]),
+    ("fucntion", &["function"]),
+    ("funtion", &["function"]),
+    ("guarentee", &["guarantee"]),
+    ("immediatly", &["immediately"]),
+    ("independant", &["independent"]),
+    ("intial", &["initial"]),
+    ("lenght", &["length"]),
+    ("loosing", &["losing", "loosening"]),
+    ("neccessary", &["necessary"]),
+    ("necesary", &["necessary"]),
+    ("occassion", &["occasion"]),
+    ("occured", &["occurred"]),
+    ("occurence", &["occurrence"]),
+    ("occuring", &["occurring"]),
+    ("overriden", &["overridden"]),
+    ("paramater", &["parameter"]),
+    ("paramter", &["parameter"]),
+    ("performace", &["performance"]),
+    ("persistant", &["persistent"]),
+    ("posible", &["possible"]),
+    ("prefered", &["preferred"]),
+    ("propogate", &["propagate"]),
+    ("recieve", &["receive"]),
+    ("recieved", &["received"]),
+    ("recomend", &["recommend"]),
+    ("refered", &["referred"]),
+    ("relevent", &["relevant"]),
+    ("reponse", &["response"]),
+    ("repostiory", &["repository"]),
+    ("retreive", &["retrieve"]),
+    ("seperate", &["separate"]),
+    ("seperately", &["separately"]),
+    ("succesful", &["successful"]),
+    ("succesfully", &["successfully"]),
+    ("successfull", &["successful"]),
+    ("sucess", &["success"]),
+    ("supress", &["suppress"]),
+    ("teh", &["the"]),
+    ("thier", &["their"]),
+    ("transfered", &["transferred"]),
+    ("truely", &["truly"]),
+    ("unecessary", &["unnecessary"]),
+    ("untill", &["until"]),
+    ("wether", &["whether", "weather"]),
+    ("wich", &["which"]),
+    ("writen", &["written"]),
+];
+
+/// Spelling and terminology rules. The default only fixes misspellings.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleGuide {
+    pub spell_check: bool,
+    /// Terms enforced exactly as written.
+    pub terms: Vec<String>,
+    /// `(variant, preferred)` replacements, in file order.
+    pub replacements: Vec<(String, String)>,
+}
+
+impl Default for StyleGuide {
+    fn default() -> Self {
+        Self {
+            spell_check: true,
+            terms: Vec::new(),
+            replacements: Vec::new(),
+        }
+    }
+}
+
+/// A word or phrase rewritten by the style guide.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleFix {
+    pub from: String,
+    pub to: String,
+}
+
+/// A likely misspelling that was left as is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StyleFlag {
+    pub word: String,
+    pub suggestions: Vec<String>,
+    /// The description (or highlights) containing the word.
+    pub text: String,
+}
+
+/// What [`StyleGuide::apply`] changed and flagged.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct StyleReport {
+    pub fixes: Vec<StyleFix>,
+    pub flagged: Vec<StyleFlag>,
+}
+
+impl StyleReport {
+    pub fn is_empty(&self) -> bool {
+        self.fixes.is_empty() && self.flagged.is_empty()
+    }
+
+    fn fixed(&mut self, from: &str, to: &str) {
+        let fix = StyleFix {
+            from: from.to_string(),
+            to: to.to_string(),
+        };
+        if !self.fixes.contains(&fix) {
+            self.fixes.push(fix);
+        }
+    }
+}
+
+impl StyleGuide {
+    /// Read the `[style]` table of `keryx.toml` in `root`.
+    ///
+    /// A missing file or table gives the default; an unreadable or invalid
+    /// file is logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid style rules in {}: {}. Ignoring them.",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    /// Like [`Self::load`] for the root of `repo`; a bare repository's
+    /// `keryx.toml` is read from the tree of `HEAD`.
+    pub fn for_repository(repo: &Repository) -> Self {
+        if let Some(root) = repo.workdir() {
+            return Self::load(root);
+        }
+        let Some(content) = read_head_file(repo, Path::new(CONFIG_FILE)) else {
+            return Self::default();
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid style rules in {} in HEAD: {}. Ignoring them.",
+                CONFIG_FILE, e
+            );
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let Some(style) = doc.get("style") else {
+            return Ok(Self::default());
+        };
+        let style = style.as_table_like().ok_or("[style] must be a table")?;
+
+        let spell_check = match style.get("spell_check") {
+            None => true,
+            Some(item) => item
+                .as_bool()
+                .ok_or("style.spell_check must be true or false")?,
+        };
+        let terms = match style.get("terms") {
+            None => Vec::new(),
+            Some(item) => item
+                .as_array()
+                .and_then(|a| {
+                    a.iter()
+                        .map(|v| v.as_str().map(|s| s.trim().to_string()))
+                        .collect::<Option<Vec<_>>>()
+                })
+                .ok_or("style.terms must be a list of strings")?
+                .into_iter()
+                .filter(|t| !t.is_empty())
+                .collect(),
+        };
+        let mut replacements = Vec::new();
+        if let Some(item) = style.get("replace") {
+            let table = item
+                .as_table_like()
+                .ok_or("style.replace must be a table")?;
+            for (variant, preferred) in table.iter() {
+                let preferred = preferred
+                    .as_str()
+                    .ok_or_else(|| format!("style.replace.{} must be a string", variant))?;
+                if !variant.trim().is_empty() {
+                    replacements.push((variant.trim().to_string(), preferred.trim().to_string()));
+                }
+            }
+        }
+
+        Ok(Self {
+            spell_check,
+            terms,
+            replacements,
+        })
+    }
+
+    /// Whether the guide would never change anything.
+    pub fn is_noop(&self) -> bool {
+        !self.spell_check && self.terms.is_empty() && self.replacements.is_empty()
+    }
+
+    /// Fix the descriptions and highlights of `output` in place.
+    pub fn apply(&self, output: &mut ChangelogOutput) -> StyleReport {
+        let mut report = StyleReport::default();
+        if self.is_noop() {
+            return report;
+        }
+        for entry in &mut output.entries {
+            entry.description = self.fix_text(&entry.description, &mut report);
+        }
+        if let Some(highlights) = &output.highlights {
+            output.highlights = Some(self.fix_text(highlights, &mut report));
+        }
+        report
+    }
+
+    /// `text` with the rules applied outside inline code spans.
+    pub fn fix_text(&self, text: &str, report: &mut StyleReport) -> String {
+        // Odd-numbered pieces are inside backticks
+        text.split('`')
+            .enumerate()
+            .map(|(index, piece)| {
+                if index % 2 == 1 {
+                    piece.to_string()
+                } else {
+                    self.fix_prose(piece, text, report)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join("`")
+    }
+
+    fn fix_prose(&self, prose: &str, full_text: &str, report: &mut StyleReport) -> String {
+        let mut prose = prose.to_string();
+        for (variant, preferred) in &self.replacements {
+            prose = replace_phrase(&prose, variant, |matched| {
+                report.fixed(matched, preferred);
+                preferred.clone()
+            });
+        }
+        for term in &self.terms {
+            prose = replace_phrase(&prose, term, |matched| {
+                if matched != term {
+                    report.fixed(matched, term);
+                }
+                term.clone()
+            });
+        }
+        if self.spell_check {
+            for (wrong, corrections) in MISSPELLINGS {
+                if let [correction] = corrections {
+                    prose = replace_phrase(&prose, wrong, |matched| {
+                        let fixed = match_case(matched, correction);
+                        report.fixed(matched, &fixed);
+                        fixed
+                    });
+                } else {
+                    for (start, end) in find_phrase(&prose, wrong) {
+                        report.flagged.push(StyleFlag {
+                            word: prose[start..end].to_string(),
+                            suggestions: corrections.iter().map(|c| c.to_string()).collect(),
+                            text: full_text.to_string(),
+                        });
+                    }
+                }
+            }
+        }
+        prose
+    }
+}
+
+/// Bytes that continue a word, so a match next to them is not a whole word.
+fn is_word_byte(byte: u8) -> bool {
+    byte.is_ascii_alphanumeric() || byte == b'_' || byte >= 0x80
+}
+
+/// Byte ranges of whole-word, ASCII case-insensitive matches of `needle`.
+///
+/// Matches touching `-`, `/`, `@` or `.` followed by a word character are
+/// part of a path, URL or identifier and skipped.
+fn find_phrase(text: &str, needle: &str) -> Vec<(usize, usize)> {
+    let haystack = text.to_ascii_lowercase();
+    let needle = needle.to_ascii_lowercase();
+    let bytes = haystack.as_bytes();
+    let mut found = Vec::new();
+    if needle.is_empty() {
+        return found;
+    }
+
+    let mut from = 0;
+    while let Some(offset) = haystack[from..].find(&needle) {
+        let start = from + offset;
+        let end = start + needle.len();
+        from = start + 1;
+        while !haystack.is_char_boundary(from) {
+            from += 1;
+        }
+
+        let before_ok = start == 0 || {
+            let b = bytes[start - 1];
+            !is_word_byte(b) && !matches!(b, b'-' | b'/' | b'@' | b'.')
+        };
+        let after_ok = end == bytes.len() || {
+            let b = bytes[end];
+            match b {
+                b'.' => bytes.get(end + 1).is_none_or(|next| !is_word_byte(*next)),
+                b'-' | b'/' | b'@' => false,
+                _ => !is_word_byte(b),
+            }
+        };
+        if before_ok && after_ok {
+            found.push((start, end));
+            from = end;
+        }
+    }
+    found
+}
+
+/// Replace every whole-word match of `needle` with `replacement(matched)`.
+fn replace_phrase(text: &str, needle: &str, mut replacement: impl FnMut(&str) -> String) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut last = 0;
+    for (start, end) in find_phrase(text, needle) {
+        out.push_str(&text[last..start]);
+        out.push_str(&replacement(&text[start..end]));
+        last = end;
+    }
+    out.push_str(&text[last..]);
+    out
+}
+
+/// `correction` capitalized like `original` (first letter only).
+fn match_case(original: &str, correction: &str) -> String {
+    if original.chars().next().is_some_and(char::is_uppercase) {
+        let mut chars = correction.chars();
+        match chars.next() {
+            Some(first) => first.to_uppercase().chain(chars).collect(),
+            None => String::new(),
+        }
+    } else {
+        correction.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::{ChangelogCategory, ChangelogEntry};
+
+    fn guide() -> StyleGuide {
+        StyleGuide::parse(
+            r#"
+[style]
+terms = ["GitHub", "macOS"]
+
+[style.replace]
+"change log" = "Changelog"
+"#,
+        )
+        .unwrap()
+    }
+
+    fn fix(guide: &StyleGuide, text: &str) -> (String, StyleReport) {
+        let mut report = StyleReport::default();
+        let fixed = guide.fix_text(text, &mut report);
+        (fixed, report)
+    }
+
+    #[test]
+    fn test_parse_style_table() {
+        let guide = guide();
+        assert!(guide.spell_check);
+        assert_eq!(guide.terms, vec!["GitHub", "macOS"]);
+        assert_eq!(
+            guide.replacements,
+            vec![("change log".to_string(), "Changelog".to_string())]
+        );
+        assert_eq!(
+            StyleGuide::parse("[product]\n").unwrap(),
+            StyleGuide::default()
+        );
+        assert!(StyleGuide::parse("[style]\nspell_check = \"yes\"\n").is_err());
+        assert!(
+            StyleGuide::parse("[style]\nspell_check = false\n")
+                .unwrap()
+                .is_noop()
+        );
+    }
+
+    #[test]
+    fn test_terms_and_replacements() {
+        let (fixed, report) = fix(&guide(), "Sync the change log to github on MacOS");
+        assert_eq!(fixed, "Sync the Changelog to GitHub on macOS");
+        assert_eq!(report.fixes.len(), 3);
+        assert_eq!(
+            report.fixes[1],
+            StyleFix {
+                from: "github".to_string(),
+                to: "GitHub".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn test_leaves_code_paths_and_identifiers_alone() {
+        let (fixed, report) = fix(
+            &guide(),
+            "Read `github_token` from github.com/settings and github-actions",
+        );
+        assert_eq!(
+            fixed,
+            "Read `github_token` from github.com/settings and github-actions"
+        );
+        assert!(report.is_empty());
+
+        let (fixed, _) = fix(&guide(), "Publish to github.");
+        assert_eq!(fixed, "Publish to GitHub.");
+    }
+
+    #[test]
+    fn test_spelling_fixed_or_flagged() {
+        let (fixed, report) = fix(
+            &StyleGuide::default(),
+            "Recieve events seperately, wether or not `recieve` is set",
+        );
+        assert_eq!(
+            fixed,
+            "Receive events separately, wether or not `recieve` is set"
+        );
+        assert_eq!(report.fixes.len(), 2);
+        assert_eq!(report.flagged.len(), 1);
+        assert_eq!(report.flagged[0].word, "wether");
+        assert_eq!(report.flagged[0].suggestions, vec!["whether", "weather"]);
+    }
+
+    #[test]
+    fn test_apply_to_output() {
+        let mut output = ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Added,
+                description: "Teh github integration".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: Some("Better change log handling".to_string()),
+        };
+        let report = guide().apply(&mut output);
+        assert_eq!(output.entries[0].description, "The GitHub integration");
+        assert_eq!(
+            output.highlights.as_deref(),
+            Some("Better Changelog handling")
+        );
+        assert_eq!(report.fixes.len(), 3);
+    }
+}
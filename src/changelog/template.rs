@@ -0,0 +1,370 @@
+//! Release notes templates (`--template release.tera`).
+//!
+//! A new section is written in the Keep a Changelog layout unless a template
+//! is given; the template then renders the whole section, so headings,
+//! grouping, emoji, PR links and credits are up to it. Templates are
+//! [Tera](https://keats.github.io/tera/docs/) templates without autoescaping;
+//! the variables are described in [`release_context`].
+
+use std::env;
+use std::error::Error as _;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use semver::Version;
+use serde_json::{Value, json};
+use tera::{Context, Tera};
+
+use crate::error::TemplateError;
+
+use super::format::{ChangelogEntry, ChangelogOutput};
+use super::writer::ReleaseMetadata;
+
+/// Environment variable naming a template file when `--template` is not given.
+pub const TEMPLATE_ENV_VAR: &str = "KERYX_TEMPLATE";
+
+/// Name of the template inside its [`Tera`] instance.
+const TEMPLATE_NAME: &str = "release";
+
+/// A parsed release notes template.
+#[derive(Clone)]
+pub struct ReleaseTemplate {
+    source: String,
+    tera: Tera,
+}
+
+impl fmt::Debug for ReleaseTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ReleaseTemplate")
+            .field("source", &self.source)
+            .finish_non_exhaustive()
+    }
+}
+
+impl PartialEq for ReleaseTemplate {
+    fn eq(&self, other: &Self) -> bool {
+        self.source == other.source
+    }
+}
+
+impl Eq for ReleaseTemplate {}
+
+impl FromStr for ReleaseTemplate {
+    type Err = TemplateError;
+
+    fn from_str(source: &str) -> Result<Self, Self::Err> {
+        let mut tera = Tera::default();
+        tera.autoescape_on(Vec::new());
+        tera.add_raw_template(TEMPLATE_NAME, source)
+            .map_err(|e| TemplateError::Syntax(describe(&e)))?;
+        Ok(Self {
+            source: source.to_string(),
+            tera,
+        })
+    }
+}
+
+impl ReleaseTemplate {
+    /// Read and parse the template at `path`.
+    pub fn load(path: &Path) -> Result<Self, TemplateError> {
+        let source = std::fs::read_to_string(path).map_err(|source| TemplateError::Read {
+            path: path.to_path_buf(),
+            source,
+        })?;
+        source.parse().map_err(|e| match e {
+            TemplateError::Syntax(message) => TemplateError::Invalid {
+                path: path.to_path_buf(),
+                message,
+            },
+            e => e,
+        })
+    }
+
+    /// The template from `--template`, else the file in `KERYX_TEMPLATE`;
+    /// `None` keeps the Keep a Changelog layout.
+    pub fn from_flag_or_env(flag: Option<PathBuf>) -> Result<Option<Self>, TemplateError> {
+        flag.or_else(|| {
+            env::var_os(TEMPLATE_ENV_VAR)
+                .filter(|v| !v.is_empty())
+                .map(PathBuf::from)
+        })
+        .map(|path| Self::load(&path))
+        .transpose()
+    }
+
+    /// Render with `context`, usually a [`release_context`]. Fails on
+    /// variables the context does not have.
+    pub fn render(&self, context: &Value) -> Result<String, TemplateError> {
+        Context::from_value(context.clone())
+            .and_then(|context| self.tera.render(TEMPLATE_NAME, &context))
+            .map_err(|e| TemplateError::Render(describe(&e)))
+    }
+
+    /// Render the section for `version`, ending with a blank line like the
+    /// default layout.
+    pub fn render_section(
+        &self,
+        version: &Version,
+        metadata: &ReleaseMetadata,
+        output: &ChangelogOutput,
+    ) -> Result<String, TemplateError> {
+        let rendered = self.render(&release_context(version, metadata, output))?;
+        Ok(format!("{}\n\n", rendered.trim()))
+    }
+}
+
+/// The variables a template sees:
+///
+/// - `version`, `date`, `codename`, `highlights`
+/// - `tag`, `previous_tag`, `repo_url` and `compare_url`, when the
+///   repository's web URL is known
+/// - `attributes`: `name` and `value` of each release attribute
+/// - `categories`: `name` and `entries` of each category, in standard order
+/// - `entries`: every entry, in the same order
+/// - `contributors`: everyone credited on an entry, without duplicates
+///
+/// Each entry has `description`, `category`, `scope`, `importance`,
+/// `sources`, `prs` (`number` and `url`), `commits`, `thanks`, `reviewers`
+/// and `credits` (the rendered credit suffix, or empty).
+pub fn release_context(
+    version: &Version,
+    metadata: &ReleaseMetadata,
+    output: &ChangelogOutput,
+) -> Value {
+    let links = metadata.compare_links.as_ref();
+    let repo_url = links.map(|l| l.repo_url.trim_end_matches('/').to_string());
+    let compare_url = links.map(|l| match &l.previous_tag {
+        Some(previous) => format!("{}/compare/{}...{}", l.repo_url, previous, l.tag),
+        None => format!("{}/releases/tag/{}", l.repo_url, l.tag),
+    });
+
+    let mut contributors: Vec<String> = Vec::new();
+    let mut entries = Vec::new();
+    let categories: Vec<Value> = output
+        .entries_by_category()
+        .into_iter()
+        .map(|(category, mut category_entries)| {
+            metadata.sort.sort(&mut category_entries);
+            let category_entries: Vec<Value> = category_entries
+                .into_iter()
+                .map(|entry| {
+                    let value = entry_context(entry, metadata, repo_url.as_deref());
+                    if let Some(credits) =
+                        metadata.attribution.as_ref().and_then(|a| a.credits(entry))
+                    {
+                        for name in credits.thanks.iter().chain(&credits.reviewers) {
+                            if !contributors.contains(name) {
+                                contributors.push(name.clone());
+                            }
+                        }
+                    }
+                    entries.push(value.clone());
+                    value
+                })
+                .collect();
+            json!({ "name": category.as_str(), "entries": category_entries })
+        })
+        .collect();
+
+    json!({
+        "version": version.to_string(),
+        "date": metadata.date,
+        "codename": metadata.codename,
+        "highlights": output.highlights_paragraph(),
+        "tag": links.map(|l| l.tag.clone()),
+        "previous_tag": links.and_then(|l| l.previous_tag.clone()),
+        "repo_url": repo_url,
+        "compare_url": compare_url,
+        "attributes": metadata
+            .attributes
+            .iter()
+            .map(|a| json!({ "name": a.name, "value": a.value }))
+            .collect::<Vec<_>>(),
+        "categories": categories,
+        "entries": entries,
+        "contributors": contributors,
+    })
+}
+
+fn entry_context(
+    entry: &ChangelogEntry,
+    metadata: &ReleaseMetadata,
+    repo_url: Option<&str>,
+) -> Value {
+    let (prs, commits): (Vec<&String>, Vec<&String>) =
+        entry.sources.iter().partition(|s| s.starts_with('#'));
+    let prs: Vec<Value> = prs
+        .into_iter()
+        .filter_map(|s| s[1..].parse::<u64>().ok())
+        .map(|number| {
+            json!({
+                "number": number,
+                "url": repo_url.map(|url| format!("{}/pull/{}", url, number)),
+            })
+        })
+        .collect();
+    let credits = metadata.attribution.as_ref().and_then(|a| a.credits(entry));
+    json!({
+        "description": metadata.sort.entry_text(entry),
+        "category": entry.category.as_str(),
+        "scope": entry.scope,
+        "importance": entry.importance,
+        "sources": entry.sources,
+        "prs": prs,
+        "commits": commits,
+        "thanks": credits.map(|c| c.thanks.clone()).unwrap_or_default(),
+        "reviewers": credits.map(|c| c.reviewers.clone()).unwrap_or_default(),
+        "credits": credits.and_then(|c| c.render()).unwrap_or_default(),
+    })
+}
+
+/// A Tera error with its causes, which hold the line and what went wrong.
+fn describe(error: &tera::Error) -> String {
+    let mut message = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        message.push_str(": ");
+        message.push_str(cause.to_string().trim());
+        source = cause.source();
+    }
+    message
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::attribution::{AttributionConfig, build_attribution};
+    use crate::changelog::format::ChangelogCategory;
+    use crate::changelog::writer::{CompareLinks, format_version_section};
+
+    fn entry(category: ChangelogCategory, description: &str, sources: &[&str]) -> ChangelogEntry {
+        ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_render_release_section() {
+        let template: ReleaseTemplate = r#"
+## {{ version }}{% if codename %} "{{ codename }}"{% endif %} ({{ date }})
+{#- one block per category #}
+{%- for category in categories %}
+
+### {% if category.name == "Added" %}✨ {% elif category.name == "Fixed" %}🐛 {% endif %}{{ category.name | upper }}
+{% for entry in category.entries %}
+{{ loop.index }}. {{ entry.description }}
+{%- for pr in entry.prs %} ([#{{ pr.number }}]({{ pr.url }})){% endfor %}
+{%- if not entry.prs and entry.commits %} ({{ entry.commits | join(sep=", ") }}){% endif %}
+{%- endfor %}
+{%- endfor %}
+
+{{ entries | length }} changes · {{ highlights | default(value="No highlights") }}
+[Full diff]({{ compare_url }})
+"#
+        .parse()
+        .unwrap();
+
+        let output = ChangelogOutput {
+            entries: vec![
+                entry(
+                    ChangelogCategory::Fixed,
+                    "Fix crash on empty config",
+                    &["abc1234"],
+                ),
+                entry(ChangelogCategory::Added, "Add dark mode", &["#12"]),
+                entry(ChangelogCategory::Added, "Add export", &["#14", "def5678"]),
+            ],
+            highlights: None,
+        };
+        let metadata = ReleaseMetadata {
+            date: "2024-03-01".to_string(),
+            codename: Some("Aurora".to_string()),
+            compare_links: Some(CompareLinks {
+                repo_url: "https://github.com/o/r".to_string(),
+                tag: "v1.2.0".to_string(),
+                previous_tag: Some("v1.1.0".to_string()),
+            }),
+            template: Some(template),
+            ..ReleaseMetadata::default()
+        };
+        assert_eq!(
+            format_version_section(&Version::new(1, 2, 0), &metadata, &output),
+            "## 1.2.0 \"Aurora\" (2024-03-01)\n\n\
+             ### ✨ ADDED\n\n\
+             1. Add dark mode ([#12](https://github.com/o/r/pull/12))\n\
+             2. Add export ([#14](https://github.com/o/r/pull/14))\n\n\
+             ### 🐛 FIXED\n\n\
+             1. Fix crash on empty config (abc1234)\n\n\
+             3 changes · No highlights\n\
+             [Full diff](https://github.com/o/r/compare/v1.1.0...v1.2.0)\n\n"
+        );
+    }
+
+    #[test]
+    fn test_contributors_from_attribution() {
+        let template: ReleaseTemplate =
+            "{% for c in contributors %}{{ c }}{% if not loop.last %}, {% endif %}{% endfor %}|{{ entries.0.credits }}"
+                .parse()
+                .unwrap();
+        let output = ChangelogOutput {
+            entries: vec![entry(ChangelogCategory::Added, "Add dark mode", &["#12"])],
+            highlights: None,
+        };
+        let reviewers = [(12, vec!["bob".to_string()])].into_iter().collect();
+        let metadata = ReleaseMetadata {
+            attribution: Some(build_attribution(
+                &output,
+                &AttributionConfig::all(),
+                &[],
+                &reviewers,
+            )),
+            ..ReleaseMetadata::default()
+        };
+        let context = release_context(&Version::new(1, 0, 0), &metadata, &output);
+        assert_eq!(
+            template.render(&context).unwrap(),
+            "@bob|(reviewed by @bob)"
+        );
+    }
+
+    #[test]
+    fn test_syntax_errors_name_the_line() {
+        let error = |source: &str| source.parse::<ReleaseTemplate>().unwrap_err().to_string();
+        assert!(error("{{ version").contains("--> 1:11"));
+        assert!(error("\n{% endif %}").contains("--> 2:1"));
+        assert!(
+            error("## {{ version }}\n{% for e in entries %}\n- {{ e.description }}\n")
+                .contains("--> 4:1")
+        );
+    }
+
+    #[test]
+    fn test_render_errors_fall_back_to_default_layout() {
+        let output = ChangelogOutput {
+            entries: vec![entry(ChangelogCategory::Added, "Add dark mode", &["#12"])],
+            highlights: None,
+        };
+        let version = Version::new(1, 0, 0);
+        for source in ["## {{ verison }}", "## {{ version | shout }}"] {
+            let template: ReleaseTemplate = source.parse().unwrap();
+            let context = release_context(&version, &ReleaseMetadata::default(), &output);
+            assert!(matches!(
+                template.render(&context),
+                Err(TemplateError::Render(_))
+            ));
+            let metadata = ReleaseMetadata {
+                template: Some(template),
+                ..ReleaseMetadata::default()
+            };
+            assert!(
+                format_version_section(&version, &metadata, &output)
+                    .contains("### Added\n\n- Add dark mode")
+            );
+        }
+    }
+}
@@ -0,0 +1,483 @@
+//! Keep translated changelogs in sync with the primary one.
+//!
+//! Translations live next to the primary file and carry a language code in
+//! their name (`CHANGELOG.de.md`, `CHANGELOG_zh-CN.md`, `CHANGELOG-ja.md`). A
+//! version is *missing* from a translation when the primary has a section for
+//! it and the translation does not, and *outdated* when both have the section
+//! but their entry counts differ, which means the primary section was edited
+//! after it was translated. Missing sections can be translated by the LLM and
+//! inserted in version order.
+
+use std::path::{Path, PathBuf};
+use std::sync::LazyLock;
+
+use regex_lite::Regex;
+use semver::Version;
+use serde::Deserialize;
+use tracing::debug;
+
+use crate::changelog::line_ending::LineEnding;
+use crate::changelog::parser::{find_insertion_point, find_version_token};
+use crate::changelog::writer::atomic_write;
+use crate::error::ChangelogError;
+use crate::llm::extract_json;
+use crate::llm::{LlmRouter, TextRequest};
+
+/// Language codes accepted in translation file names (`de`, `pt-BR`, `zh_Hans`).
+static LANGUAGE_CODE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^[a-z]{2,3}([-_][A-Za-z]{2,4})?$")
+        .expect("Invalid language code regex - this is a bug")
+});
+
+/// Link reference definitions (`[1.0.0]: https://...`) at the end of a changelog.
+static LINK_DEFINITION_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\[[^\]]+\]:\s").expect("Invalid link definition regex - this is a bug")
+});
+
+/// A translated changelog file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TranslatedChangelog {
+    pub path: PathBuf,
+    /// Language code taken from the file name (e.g. `de`, `zh-CN`).
+    pub language: String,
+}
+
+impl TranslatedChangelog {
+    /// Translation at `path`, taking the language from its name relative to
+    /// `primary` (falling back to the file stem).
+    pub fn from_path(primary: &Path, path: PathBuf) -> Self {
+        let language = language_from_path(primary, &path).unwrap_or_else(|| {
+            path.file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default()
+        });
+        Self { path, language }
+    }
+}
+
+/// Language code of `candidate` if it is a translation of `primary`.
+///
+/// `CHANGELOG.md` is translated by `CHANGELOG.<lang>.md`,
+/// `CHANGELOG_<lang>.md`, and `CHANGELOG-<lang>.md`.
+pub fn language_from_path(primary: &Path, candidate: &Path) -> Option<String> {
+    let stem = primary.file_stem()?.to_str()?;
+    let extension = primary.extension().and_then(|e| e.to_str()).unwrap_or("md");
+    let name = candidate.file_name()?.to_str()?;
+
+    let rest = name.strip_prefix(stem)?;
+    let rest = rest
+        .strip_prefix('.')
+        .or_else(|| rest.strip_prefix('_'))
+        .or_else(|| rest.strip_prefix('-'))?;
+    let language = rest.strip_suffix(&format!(".{}", extension))?;
+    LANGUAGE_CODE_RE
+        .is_match(language)
+        .then(|| language.to_string())
+}
+
+/// Translations of `primary` found in its directory, sorted by path.
+pub fn detect_translations(primary: &Path) -> Vec<TranslatedChangelog> {
+    let dir = match primary.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let Ok(entries) = std::fs::read_dir(dir) else {
+        return Vec::new();
+    };
+
+    let mut found: Vec<TranslatedChangelog> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.path())
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            language_from_path(primary, &path).map(|language| TranslatedChangelog {
+                path: primary.with_file_name(path.file_name().unwrap_or_default()),
+                language,
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| a.path.cmp(&b.path));
+    found
+}
+
+/// A version section inside a changelog, as byte offsets of the LF-normalized
+/// content.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Section {
+    pub(crate) version: Version,
+    pub(crate) start: usize,
+    pub(crate) end: usize,
+}
+
+/// Version sections of `content` (which must use LF line endings), in
+/// document order. `[Unreleased]` and other non-version sections are skipped.
+pub(crate) fn sections(content: &str) -> Vec<Section> {
+    let mut headings: Vec<(usize, Option<Version>)> = Vec::new();
+    let mut offset = 0;
+    for line in content.split_inclusive('\n') {
+        if let Some(title) = line.strip_prefix("## ") {
+            let version = if title.to_lowercase().contains("unreleased") {
+                None
+            } else {
+                find_version_token(title.trim_end())
+            };
+            headings.push((offset, version));
+        }
+        offset += line.len();
+    }
+
+    let body_end = trailing_definitions_start(content);
+    headings
+        .iter()
+        .enumerate()
+        .filter_map(|(i, (start, version))| {
+            let end = headings
+                .get(i + 1)
+                .map(|(next, _)| *next)
+                .unwrap_or(body_end.max(*start));
+            version.clone().map(|version| Section {
+                version,
+                start: *start,
+                end,
+            })
+        })
+        .collect()
+}
+
+/// Offset where the trailing block of link reference definitions starts
+/// (the content length if there is none).
+fn trailing_definitions_start(content: &str) -> usize {
+    let mut start = content.len();
+    let mut offset = content.len();
+    for line in content.split_inclusive('\n').rev() {
+        offset -= line.len();
+        let trimmed = line.trim();
+        if trimmed.is_empty() {
+            continue;
+        }
+        if LINK_DEFINITION_RE.is_match(trimmed) {
+            start = offset;
+        } else {
+            break;
+        }
+    }
+    start
+}
+
+/// Number of top-level entries (bullets) in a section.
+fn entry_count(section: &str) -> usize {
+    section
+        .lines()
+        .filter(|line| line.starts_with("- ") || line.starts_with("* "))
+        .count()
+}
+
+fn section_text<'a>(content: &'a str, version: &Version) -> Option<&'a str> {
+    sections(content)
+        .into_iter()
+        .find(|s| &s.version == version)
+        .map(|s| &content[s.start..s.end])
+}
+
+/// How a translation compares with the primary changelog.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TranslationStatus {
+    /// Versions in the primary that the translation lacks, newest first.
+    pub missing: Vec<Version>,
+    /// Versions whose entry count differs from the primary, newest first.
+    pub outdated: Vec<Version>,
+}
+
+impl TranslationStatus {
+    /// Whether the translation covers every version of the primary.
+    pub fn is_in_sync(&self) -> bool {
+        self.missing.is_empty() && self.outdated.is_empty()
+    }
+}
+
+/// Compare a translation with the primary changelog.
+pub fn compare_translation(primary: &str, translation: &str) -> TranslationStatus {
+    let primary = primary.replace("\r\n", "\n");
+    let translation = translation.replace("\r\n", "\n");
+    let translated = sections(&translation);
+
+    let mut status = TranslationStatus::default();
+    for section in sections(&primary) {
+        match translated.iter().find(|t| t.version == section.version) {
+            None => status.missing.push(section.version),
+            Some(t) => {
+                let expected = entry_count(&primary[section.start..section.end]);
+                if entry_count(&translation[t.start..t.end]) != expected {
+                    status.outdated.push(section.version);
+                }
+            }
+        }
+    }
+    status
+}
+
+/// Section for `version` in `content`, if present.
+pub fn version_section(content: &str, version: &Version) -> Option<String> {
+    let content = content.replace("\r\n", "\n");
+    section_text(&content, version).map(|s| s.trim_end().to_string())
+}
+
+/// Insert `section` for `version` into `content`, keeping versions ordered
+/// newest first. Returns LF-normalized content.
+pub fn insert_section(content: &str, version: &Version, section: &str) -> String {
+    let content = content.replace("\r\n", "\n");
+    let existing = sections(&content);
+    let position = match existing.iter().find(|s| &s.version < version) {
+        Some(older) => older.start,
+        None => match existing.last() {
+            Some(last) => last.end,
+            None => find_insertion_point(&content),
+        },
+    };
+
+    let (before, after) = content.split_at(position);
+    let mut block = String::new();
+    if !before.is_empty() && !before.ends_with("\n\n") {
+        block.push_str(if before.ends_with('\n') { "\n" } else { "\n\n" });
+    }
+    block.push_str(section.trim_end());
+    block.push_str(if after.is_empty() { "\n" } else { "\n\n" });
+
+    format!("{}{}{}", before, block, after)
+}
+
+/// Atomically replace a translated changelog with `content`, keeping the
+/// line endings of the file being replaced.
+pub fn write_translation(path: &Path, content: &str) -> Result<(), ChangelogError> {
+    let ending = std::fs::read_to_string(path)
+        .map(|existing| LineEnding::detect(&existing))
+        .unwrap_or_default();
+    atomic_write(path, &ending.apply(content))
+}
+
+/// Response from the LLM for a section translation.
+#[derive(Deserialize)]
+struct TranslationResponse {
+    body: String,
+}
+
+/// Build the prompt asking for a translation of one section body.
+///
+/// The body is the repository's own committed changelog text. It is not run
+/// through `sanitize_for_prompt`, which rewrites the markdown headings the
+/// translation has to preserve; the response is validated structurally
+/// instead.
+fn build_translation_prompt(body: &str, language: &str) -> String {
+    format!(
+        r#"Translate the body of this changelog section into the language with code "{language}".
+
+<body>
+{body}
+</body>
+
+## Rules
+- Keep the markdown structure: same ### headings, one bullet per entry, same number of entries, same order
+- Translate category headings and entry text
+- Keep code, identifiers, file paths, PR/issue references and URLs unchanged
+- Do not add, drop, or merge information
+- Do not add a version heading
+
+Respond with JSON only (no markdown wrapping):
+{{"body": "..."}}"#,
+        language = language,
+        body = body,
+    )
+}
+
+/// Parse and validate a translated section body from an LLM response.
+fn parse_translation_response(response: &str, expected_entries: usize) -> Result<String, String> {
+    let json_str = extract_json(response);
+    let parsed: TranslationResponse = serde_json::from_str(&json_str).map_err(|e| {
+        debug!("Failed to parse translation JSON: {}", e);
+        "response was not valid JSON".to_string()
+    })?;
+
+    let body = parsed.body.replace("\r\n", "\n").trim().to_string();
+    if body
+        .lines()
+        .any(|line| line.starts_with("# ") || line.starts_with("## "))
+    {
+        return Err("response contains a version heading".to_string());
+    }
+    let entries = entry_count(&body);
+    if entries != expected_entries {
+        return Err(format!(
+            "expected {} entries, got {}",
+            expected_entries, entries
+        ));
+    }
+    Ok(body)
+}
+
+/// Translate the `version` section of `primary` into `language`.
+///
+/// Only the body is translated; the heading (version, date, links) is copied
+/// from the primary. The result is validated (same entry count, no extra
+/// headings) before it is returned; any problem is reported as a
+/// human-readable reason.
+pub async fn translate_section(
+    primary: &str,
+    version: &Version,
+    language: &str,
+    llm: &mut LlmRouter,
+) -> Result<String, String> {
+    let section = version_section(primary, version)
+        .ok_or_else(|| format!("{} is not in the primary changelog", version))?;
+    let (heading, body) = section.split_once('\n').unwrap_or((section.as_str(), ""));
+    let body = body.trim();
+    if body.is_empty() {
+        return Ok(heading.to_string());
+    }
+
+    let prompt = build_translation_prompt(body, language);
+    let response = llm
+        .request(&TextRequest::new(prompt))
+        .await
+        .map_err(|e| e.summary())?
+        .output;
+    let translated = parse_translation_response(&response, entry_count(body))?;
+    Ok(format!("{}\n\n{}", heading, translated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    const PRIMARY: &str = "# Changelog\n\n## [Unreleased]\n\n## [1.2.0] - 2024-03-01\n\n### Added\n\n- New export\n- Dark mode\n\n## [1.1.0] - 2024-02-01\n\n### Fixed\n\n- Crash on start\n\n## [1.0.0] - 2024-01-01\n\n### Added\n\n- First release\n\n[1.2.0]: https://example.com/compare/v1.1.0...v1.2.0\n";
+
+    const GERMAN: &str = "# Änderungsprotokoll\n\n## [1.2.0] - 2024-03-01\n\n### Hinzugefügt\n\n- Neuer Export\n\n## [1.0.0] - 2024-01-01\n\n### Hinzugefügt\n\n- Erste Version\n\n[1.2.0]: https://example.com/compare/v1.1.0...v1.2.0\n";
+
+    #[test]
+    fn test_language_from_path() {
+        let primary = Path::new("CHANGELOG.md");
+        let lang = |name: &str| language_from_path(primary, Path::new(name));
+        assert_eq!(lang("CHANGELOG.de.md"), Some("de".to_string()));
+        assert_eq!(lang("CHANGELOG_zh-CN.md"), Some("zh-CN".to_string()));
+        assert_eq!(lang("CHANGELOG-ja.md"), Some("ja".to_string()));
+        assert_eq!(lang("CHANGELOG.md"), None);
+        assert_eq!(lang("CHANGELOG.md.bak"), None);
+        assert_eq!(lang("CHANGES.de.md"), None);
+    }
+
+    #[test]
+    fn test_detect_translations() {
+        let dir = TempDir::new().unwrap();
+        for name in [
+            "CHANGELOG.md",
+            "CHANGELOG.fr.md",
+            "CHANGELOG.de.md",
+            "NOTES.md",
+        ] {
+            std::fs::write(dir.path().join(name), "").unwrap();
+        }
+        let found = detect_translations(&dir.path().join("CHANGELOG.md"));
+        let languages: Vec<_> = found.iter().map(|t| t.language.as_str()).collect();
+        assert_eq!(languages, vec!["de", "fr"]);
+        assert_eq!(found[0].path, dir.path().join("CHANGELOG.de.md"));
+    }
+
+    #[test]
+    fn test_compare_translation() {
+        let status = compare_translation(PRIMARY, GERMAN);
+        assert_eq!(status.missing, vec![Version::new(1, 1, 0)]);
+        assert_eq!(status.outdated, vec![Version::new(1, 2, 0)]);
+        assert!(!status.is_in_sync());
+        assert!(compare_translation(PRIMARY, PRIMARY).is_in_sync());
+    }
+
+    #[test]
+    fn test_last_section_excludes_link_definitions() {
+        let section = version_section(PRIMARY, &Version::new(1, 0, 0)).unwrap();
+        assert!(section.ends_with("- First release"));
+    }
+
+    #[test]
+    fn test_insert_section_keeps_version_order() {
+        let section = "## [1.1.0] - 2024-02-01\n\n### Behoben\n\n- Absturz beim Start";
+        let updated = insert_section(GERMAN, &Version::new(1, 1, 0), section);
+
+        let order: Vec<_> = sections(&updated)
+            .into_iter()
+            .map(|s| s.version.to_string())
+            .collect();
+        assert_eq!(order, vec!["1.2.0", "1.1.0", "1.0.0"]);
+        assert!(updated.contains("- Neuer Export\n\n## [1.1.0]"));
+        assert!(updated.contains("- Absturz beim Start\n\n## [1.0.0]"));
+    }
+
+    #[test]
+    fn test_insert_oldest_section_goes_before_link_definitions() {
+        let translation = "# Änderungsprotokoll\n\n## [1.2.0] - 2024-03-01\n\n- Neuer Export\n\n[1.2.0]: https://example.com\n";
+        let updated = insert_section(
+            translation,
+            &Version::new(1, 0, 0),
+            "## [1.0.0] - 2024-01-01\n\n- Erste Version",
+        );
+        assert!(updated.ends_with("- Erste Version\n\n[1.2.0]: https://example.com\n"));
+    }
+
+    #[test]
+    fn test_insert_into_translation_without_sections() {
+        let updated = insert_section(
+            "# Änderungsprotokoll\n",
+            &Version::new(1, 0, 0),
+            "## [1.0.0]\n\n- Erste Version",
+        );
+        assert_eq!(
+            updated,
+            "# Änderungsprotokoll\n\n## [1.0.0]\n\n- Erste Version\n"
+        );
+    }
+
+    #[test]
+    fn test_write_translation_keeps_crlf() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.de.md");
+        std::fs::write(&path, "# Changelog\r\n\r\n## [1.0.0]\r\n").unwrap();
+
+        let updated = insert_section(
+            &std::fs::read_to_string(&path).unwrap(),
+            &Version::new(1, 1, 0),
+            "## [1.1.0]\n\n- Neu",
+        );
+        write_translation(&path, &updated).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## [1.1.0]\r\n\r\n- Neu\r\n"));
+        assert_eq!(
+            content.matches('\n').count(),
+            content.matches("\r\n").count()
+        );
+    }
+
+    #[test]
+    fn test_parse_translation_response() {
+        let ok = "{\"body\": \"### Behoben\\n\\n- Absturz beim Start\"}";
+        assert_eq!(
+            parse_translation_response(ok, 1).unwrap(),
+            "### Behoben\n\n- Absturz beim Start"
+        );
+
+        let err = parse_translation_response(ok, 2).unwrap_err();
+        assert_eq!(err, "expected 2 entries, got 1");
+
+        let heading = "{\"body\": \"## [1.1.0]\\n\\n- Absturz\"}";
+        assert!(parse_translation_response(heading, 1).is_err());
+
+        assert!(parse_translation_response("not json", 1).is_err());
+    }
+
+    #[test]
+    fn test_build_translation_prompt() {
+        let prompt = build_translation_prompt("### Added\n\n- First", "de");
+        assert!(prompt.contains(r#"language with code "de""#));
+        assert!(prompt.contains("<body>\n### Added\n\n- First\n</body>"));
+        assert!(prompt.contains(r#"{"body": "..."}"#));
+    }
+}
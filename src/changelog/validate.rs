@@ -0,0 +1,217 @@
+//! Keep a Changelog structure checks (`keryx check`).
+//!
+//! A changelog passes when it starts with a `# ` title, its version sections
+//! are newest first with no version twice, every release heading carries an
+//! ISO 8601 date (when the heading template has one), and every `### `
+//! heading is a category, under its standard name or an alias. Lines inside
+//! code fences are ignored.
+
+use std::fmt;
+use std::sync::LazyLock;
+
+use regex_lite::Regex;
+use semver::Version;
+
+use super::aliases::CategoryAliases;
+use super::heading::HeadingTemplate;
+use super::parser::find_version_token;
+
+/// Tokens that look like a date in any common notation.
+static DATE_LIKE_REGEX: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"^\d{1,4}[-/.]\d{1,2}[-/.]\d{1,4}$").unwrap());
+
+/// A problem with the structure of a changelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Violation {
+    /// 1-based line number.
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+/// Check `content` against the Keep a Changelog structure. Release headings
+/// must have a date only if `heading` renders one.
+pub fn validate_changelog(
+    content: &str,
+    aliases: &CategoryAliases,
+    heading: &HeadingTemplate,
+) -> Vec<Violation> {
+    let mut violations = Vec::new();
+    let mut violation = |line: usize, message: String| {
+        violations.push(Violation { line, message });
+    };
+
+    let mut in_fence = false;
+    let mut has_title = false;
+    let mut sections = 0;
+    // Version and line of each release section, in order
+    let mut versions: Vec<(Version, usize)> = Vec::new();
+    // Categories seen in the current section
+    let mut categories: Vec<String> = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        let number = index + 1;
+        let line = line.trim_end();
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+            continue;
+        }
+        if in_fence {
+            continue;
+        }
+
+        if line.starts_with("# ") {
+            if has_title || sections > 0 {
+                violation(
+                    number,
+                    "title must be the only `# ` heading, above every section".to_string(),
+                );
+            }
+            has_title = true;
+        } else if let Some(title) = line.strip_prefix("## ") {
+            if !has_title && sections == 0 {
+                violation(
+                    number,
+                    "missing `# Changelog` title above the first section".to_string(),
+                );
+            }
+            sections += 1;
+            categories.clear();
+
+            if title.to_lowercase().contains("unreleased") {
+                if sections > 1 {
+                    violation(number, "[Unreleased] must be the first section".to_string());
+                }
+                continue;
+            }
+            let Some(version) = find_version_token(title) else {
+                violation(
+                    number,
+                    format!("section \"{}\" has no version", title.trim()),
+                );
+                continue;
+            };
+            if let Some((_, first)) = versions.iter().find(|(v, _)| *v == version) {
+                violation(
+                    number,
+                    format!("duplicate version {} (first on line {})", version, first),
+                );
+            } else if let Some((previous, _)) = versions.last()
+                && version > *previous
+            {
+                violation(
+                    number,
+                    format!(
+                        "{} is listed below {}; versions must be newest first",
+                        version, previous
+                    ),
+                );
+            }
+            if let Some(problem) = date_problem(title, &version, heading) {
+                violation(number, problem);
+            }
+            versions.push((version, number));
+        } else if let Some(name) = line.strip_prefix("### ") {
+            let name = name.trim();
+            if sections == 0 {
+                violation(
+                    number,
+                    format!("category \"{}\" is outside a version section", name),
+                );
+            } else if let Some(category) = aliases.resolve(name) {
+                let category = category.as_str().to_string();
+                if categories.contains(&category) {
+                    violation(
+                        number,
+                        format!("category {} appears twice in this section", category),
+                    );
+                } else {
+                    categories.push(category);
+                }
+            } else {
+                violation(number, format!("unknown category \"{}\"", name));
+            }
+        }
+    }
+
+    if !has_title && sections == 0 {
+        violation(1, "missing `# Changelog` title".to_string());
+    }
+    violations
+}
+
+/// What is wrong with the date in the heading of `version`, if anything.
+fn date_problem(title: &str, version: &Version, heading: &HeadingTemplate) -> Option<String> {
+    let dates: Vec<&str> = title
+        .split(|c: char| c.is_whitespace() || matches!(c, '[' | ']' | '(' | ')' | ','))
+        .filter(|token| DATE_LIKE_REGEX.is_match(token))
+        .filter(|token| token.trim_start_matches('v').parse::<Version>().is_err())
+        .collect();
+    if let Some(invalid) = dates.iter().find(|date| {
+        date.len() != 10 || chrono::NaiveDate::parse_from_str(date, "%Y-%m-%d").is_err()
+    }) {
+        return Some(format!(
+            "invalid date \"{}\" for {} (expected YYYY-MM-DD)",
+            invalid, version
+        ));
+    }
+    (dates.is_empty() && heading.uses_date()).then(|| format!("{} has no release date", version))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check(content: &str) -> Vec<String> {
+        validate_changelog(
+            content,
+            &CategoryAliases::default(),
+            &HeadingTemplate::default(),
+        )
+        .iter()
+        .map(|v| v.to_string())
+        .collect()
+    }
+
+    #[test]
+    fn test_valid_changelog() {
+        let content = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Next\n\n## [1.1.0-rc.1] - 2024-02-01\n\n### Bug Fixes\n\n- Crash\n\n## [1.0.0] - 2024-01-01\n\n### Added\n\n- First\n\n```\n## not a heading\n```\n\n[1.0.0]: https://example.com\n";
+        assert!(check(content).is_empty(), "{:?}", check(content));
+    }
+
+    #[test]
+    fn test_reports_structure_violations() {
+        let content = "## [1.0.0] - 2024-01-01\n\n### Added\n\n## [Unreleased]\n\n## [1.2.0] - 01/02/2024\n\n### Misc\n\n### Fixed\n\n### Fixed\n\n## [1.0.0]\n\n## Old stuff\n";
+        assert_eq!(
+            check(content),
+            vec![
+                "line 1: missing `# Changelog` title above the first section",
+                "line 5: [Unreleased] must be the first section",
+                "line 7: 1.2.0 is listed below 1.0.0; versions must be newest first",
+                "line 7: invalid date \"01/02/2024\" for 1.2.0 (expected YYYY-MM-DD)",
+                "line 9: unknown category \"Misc\"",
+                "line 13: category Fixed appears twice in this section",
+                "line 15: duplicate version 1.0.0 (first on line 1)",
+                "line 15: 1.0.0 has no release date",
+                "line 17: section \"Old stuff\" has no version",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_dates_follow_the_heading_template() {
+        let heading: HeadingTemplate = "## [{version}] {codename}".parse().unwrap();
+        let content =
+            "# Changelog\n\n## [1.0.0] Aurora\n\n## [0.9.0] 2024-02-30\n\n## [0.8.0] 2024-1-5\n";
+        let violations = validate_changelog(content, &CategoryAliases::default(), &heading);
+        assert_eq!(
+            violations.iter().map(|v| v.line).collect::<Vec<_>>(),
+            vec![5, 7]
+        );
+    }
+}
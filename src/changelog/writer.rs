@@ -1,60 +1,43 @@
 //! Write new changelog sections.
 
-use std::io::Write;
-use std::path::Path;
+use std::io::{BufWriter, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
 
-use chrono::Utc;
 use semver::Version;
 use tempfile::NamedTempFile;
+use tracing::warn;
 
+use crate::atomic::temp_file_beside;
 use crate::error::ChangelogError;
 
-use super::format::{CHANGELOG_HEADER, ChangelogOutput};
+use super::aliases::CategoryAliases;
+use super::attribution::Attribution;
+use super::date::ReleaseDateConfig;
+use super::format::{CHANGELOG_HEADER, ChangelogEntry, ChangelogOutput};
+use super::heading::HeadingTemplate;
+use super::line_ending::{LineEnding, normalize as normalize_line_endings};
+use super::merge::{merge_section, merge_unreleased, sources_marker};
 use super::parser::{find_insertion_point, read_changelog};
+use super::sort::EntrySort;
+use super::splice::ChangelogSplice;
+use super::template::ReleaseTemplate;
 
-/// Atomically write content to a file using temp file + rename pattern.
-///
-/// This prevents TOCTOU race conditions by:
-/// 1. Creating a temp file in the same directory as the target
-/// 2. Writing all content to the temp file
-/// 3. Atomically renaming the temp file to the target path
-fn atomic_write(path: &Path, content: &str) -> Result<(), ChangelogError> {
-    // Create temp file in same directory (required for atomic rename across filesystems)
-    let parent = path.parent().unwrap_or(Path::new("."));
-
-    let mut temp_file = NamedTempFile::new_in(parent).map_err(ChangelogError::WriteFailed)?;
-
-    // Write content
-    temp_file
-        .write_all(content.as_bytes())
-        .map_err(ChangelogError::WriteFailed)?;
-
-    // Sync to disk for durability
-    temp_file
-        .as_file()
-        .sync_all()
-        .map_err(ChangelogError::WriteFailed)?;
-
-    // Atomically replace the target file
-    temp_file
-        .persist(path)
-        .map_err(|e| ChangelogError::WriteFailed(e.error))?;
-
-    Ok(())
+/// Atomically write content to a file (see [`write_atomic`](crate::atomic::write_atomic)).
+pub(crate) fn atomic_write(path: &Path, content: &str) -> Result<(), ChangelogError> {
+    crate::atomic::write_atomic(path, content).map_err(ChangelogError::WriteFailed)
 }
 
 /// Atomically copy a file (for backups).
 ///
-/// Uses the same temp file + rename pattern to prevent partial backups.
+/// Uses the same temp file + rename pattern to prevent partial backups. The
+/// content is streamed, never held in memory as a whole.
 fn atomic_copy(src: &Path, dst: &Path) -> Result<(), ChangelogError> {
-    let content = std::fs::read(src).map_err(ChangelogError::BackupFailed)?;
+    let mut source = std::fs::File::open(src).map_err(ChangelogError::BackupFailed)?;
 
-    let parent = dst.parent().unwrap_or(Path::new("."));
-    let mut temp_file = NamedTempFile::new_in(parent).map_err(ChangelogError::BackupFailed)?;
+    let mut temp_file = temp_file_beside(dst).map_err(ChangelogError::BackupFailed)?;
 
-    temp_file
-        .write_all(&content)
-        .map_err(ChangelogError::BackupFailed)?;
+    std::io::copy(&mut source, &mut temp_file).map_err(ChangelogError::BackupFailed)?;
 
     temp_file
         .as_file()
@@ -68,71 +51,562 @@ fn atomic_copy(src: &Path, dst: &Path) -> Result<(), ChangelogError> {
     Ok(())
 }
 
-/// Write changelog entries to a file.
+/// Write changelog entries to a file, dated with today's UTC date.
 ///
 /// - Creates the file with header if it doesn't exist
 /// - Backs up existing file to `<filename>.md.bak` (e.g., `CHANGELOG.md.bak`)
 /// - Handles `[Unreleased]` section conversion per spec
+///
+/// Use [`write_changelog_dated`] to control the date in the section header.
 pub fn write_changelog(
     path: &Path,
     output: &ChangelogOutput,
     version: &Version,
 ) -> Result<(), ChangelogError> {
-    let today = Utc::now().format("%Y-%m-%d").to_string();
+    let today = ReleaseDateConfig::default().release_date(None);
+    write_changelog_dated(path, output, version, &today)
+}
+
+/// Write changelog entries to a file with an explicit section date.
+///
+/// Behaves like [`write_changelog`] otherwise.
+pub fn write_changelog_dated(
+    path: &Path,
+    output: &ChangelogOutput,
+    version: &Version,
+    date: &str,
+) -> Result<(), ChangelogError> {
+    let metadata = ReleaseMetadata {
+        date: date.to_string(),
+        ..ReleaseMetadata::default()
+    };
+    write_changelog_with_metadata(path, output, version, &metadata)
+}
+
+/// A Keep a Changelog extension attribute shown under a release heading
+/// (e.g. `Codename: Aurora`, `Release manager: @alice`).
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseAttribute {
+    pub name: String,
+    pub value: String,
+}
+
+impl ReleaseAttribute {
+    pub fn new(name: impl Into<String>, value: impl Into<String>) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into(),
+        }
+    }
+}
+
+impl FromStr for ReleaseAttribute {
+    type Err = String;
+
+    /// Parse `KEY=VALUE`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some((name, value)) if !name.trim().is_empty() && !value.trim().is_empty() => {
+                Ok(Self::new(name.trim(), value.trim()))
+            }
+            _ => Err(format!("invalid attribute '{}' (expected KEY=VALUE)", s)),
+        }
+    }
+}
+
+/// Settings for the link reference definitions at the bottom of a changelog.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompareLinks {
+    /// Web URL of the repository, e.g. `https://github.com/owner/repo`.
+    pub repo_url: String,
+    /// Tag of the release being written, e.g. `v1.2.0`.
+    pub tag: String,
+    /// Tag of the previous release, if any.
+    pub previous_tag: Option<String>,
+}
+
+/// Per-release metadata written alongside the entries.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ReleaseMetadata {
+    /// Section date (`YYYY-MM-DD`).
+    pub date: String,
+    /// Template for the version heading line.
+    pub heading: HeadingTemplate,
+    /// Release codename. Rendered in the heading if the template has a
+    /// `{codename}` placeholder, otherwise as a `Codename` attribute.
+    pub codename: Option<String>,
+    /// Extension attributes rendered under the version heading.
+    pub attributes: Vec<ReleaseAttribute>,
+    /// When set, the `[Unreleased]` compare link is moved to the new tag and a
+    /// link for the new version is added next to it.
+    pub compare_links: Option<CompareLinks>,
+    /// Co-author and reviewer credits appended to entries.
+    pub attribution: Option<Attribution>,
+    /// Order of entries within each category.
+    pub sort: EntrySort,
+    /// End each entry with a hidden marker listing its sources, so a later
+    /// [`merge_changelogs_with_metadata`] can tell which are already there.
+    pub source_markers: bool,
+    /// Category headings recognized besides the standard ones. A file that
+    /// uses an alias gets its new entries under that heading too.
+    pub category_aliases: CategoryAliases,
+    /// Renders the whole section instead of the Keep a Changelog layout.
+    pub template: Option<ReleaseTemplate>,
+}
+
+/// Write changelog entries to a file with full per-release metadata.
+///
+/// Behaves like [`write_changelog`] otherwise.
+pub fn write_changelog_with_metadata(
+    path: &Path,
+    output: &ChangelogOutput,
+    version: &Version,
+    metadata: &ReleaseMetadata,
+) -> Result<(), ChangelogError> {
+    write_changelogs_with_metadata(&[path.to_path_buf()], output, version, metadata)
+}
+
+/// A rendered changelog staged in a temp file next to its target.
+struct StagedChangelog {
+    path: PathBuf,
+    temp_file: NamedTempFile,
+    /// Whether the target existed before the write. Existing targets are
+    /// restored from their backup on rollback.
+    existed: bool,
+}
+
+/// Write the same release section to several changelog files.
+///
+/// Each target may live in a subdirectory (created if missing) and is backed
+/// up to `<filename>.md.bak` like [`write_changelog`]. All files are rendered
+/// and staged before any target is replaced, so a read or render error leaves
+/// every file untouched; if replacing one fails, the ones already replaced are
+/// restored. Duplicate paths are written once.
+///
+/// Existing files are edited in place around the insertion point (see
+/// [`ChangelogSplice`]), so even multi-megabyte changelogs are never loaded
+/// whole.
+pub fn write_changelogs_with_metadata(
+    paths: &[PathBuf],
+    output: &ChangelogOutput,
+    version: &Version,
+    metadata: &ReleaseMetadata,
+) -> Result<(), ChangelogError> {
+    let new_section = format_version_section(version, metadata, output);
+    replace_changelogs(paths, |path| {
+        stage_spliced(path, version, &new_section, metadata)
+    })
+}
+
+/// Entries added to and already present in a release section by
+/// [`merge_changelogs_with_metadata`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct MergeCounts {
+    pub added: usize,
+    pub kept: usize,
+}
+
+/// Merge a release into several changelog files for an idempotent re-run
+/// (`--merge`).
+///
+/// Where a file already has a section for `version`, only the entries whose
+/// sources (or, for entries citing none, text) are missing from it are added;
+/// the entries already there keep their wording. Files without the section
+/// get a new one as with [`write_changelogs_with_metadata`]. Entries are
+/// always written with source markers. The counts are those of the first
+/// file that had the section.
+pub fn merge_changelogs_with_metadata(
+    paths: &[PathBuf],
+    output: &ChangelogOutput,
+    version: &Version,
+    metadata: &ReleaseMetadata,
+) -> Result<MergeCounts, ChangelogError> {
+    let metadata = ReleaseMetadata {
+        source_markers: true,
+        ..metadata.clone()
+    };
+    let new_section = format_version_section(version, &metadata, output);
+    let mut counts: Option<MergeCounts> = None;
+    let mut render = |existing: Option<&str>| {
+        if let Some(existing) = existing {
+            let normalized = normalize_line_endings(existing);
+            if let Some(merged) = merge_section(
+                &normalized,
+                version,
+                output,
+                metadata.sort,
+                metadata.attribution.as_ref(),
+                &metadata.category_aliases,
+            ) {
+                counts.get_or_insert(MergeCounts {
+                    added: merged.added,
+                    kept: merged.kept,
+                });
+                return LineEnding::detect(existing).apply(&merged.content);
+            }
+        }
+        insert_section(existing, &new_section, version, &metadata)
+    };
+    replace_changelogs(paths, |path| {
+        stage_changelog(path, Some(version), &mut render)
+    })?;
+    Ok(counts.unwrap_or(MergeCounts {
+        added: output.entries.len(),
+        kept: 0,
+    }))
+}
+
+/// Add the entries of `output` missing from the `[Unreleased]` section of
+/// each of `paths` (`keryx unreleased`), creating the section, or the file,
+/// where there is none. Entries are written with source markers so a re-run
+/// only adds what is new. The counts are those of the first file.
+pub fn merge_unreleased_changelogs(
+    paths: &[PathBuf],
+    output: &ChangelogOutput,
+    sort: EntrySort,
+    aliases: &CategoryAliases,
+) -> Result<MergeCounts, ChangelogError> {
+    let mut counts: Option<MergeCounts> = None;
+    let mut render = |existing: Option<&str>| {
+        let (content, line_ending) = match existing {
+            Some(existing) => (
+                normalize_line_endings(existing),
+                LineEnding::detect(existing),
+            ),
+            None => (CHANGELOG_HEADER.to_string(), LineEnding::Lf),
+        };
+        let merged = merge_unreleased(&content, output, sort, None, aliases);
+        counts.get_or_insert(MergeCounts {
+            added: merged.added,
+            kept: merged.kept,
+        });
+        line_ending.apply(&merged.content)
+    };
+    replace_changelogs(paths, |path| stage_changelog(path, None, &mut render))?;
+    Ok(counts.unwrap_or(MergeCounts { added: 0, kept: 0 }))
+}
 
+/// Stage the new content of each of `paths` and replace them all, or none.
+fn replace_changelogs(
+    paths: &[PathBuf],
+    mut stage: impl FnMut(&Path) -> Result<StagedChangelog, ChangelogError>,
+) -> Result<(), ChangelogError> {
+    let mut staged: Vec<StagedChangelog> = Vec::with_capacity(paths.len());
+    for path in paths {
+        if staged.iter().any(|s| &s.path == path) {
+            continue;
+        }
+        staged.push(stage(path)?);
+    }
+
+    // Backups are taken only once every file rendered successfully
+    for file in &staged {
+        if file.existed {
+            atomic_copy(&file.path, &file.path.with_extension("md.bak"))?;
+        }
+    }
+
+    let mut replaced: Vec<(PathBuf, bool)> = Vec::with_capacity(staged.len());
+    for file in staged {
+        match file.temp_file.persist(&file.path) {
+            Ok(_) => replaced.push((file.path, file.existed)),
+            Err(e) => {
+                restore_changelogs(&replaced);
+                return Err(ChangelogError::WriteFailed(e.error));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Insert `new_section` into `path` without loading the whole file: only the
+/// part above the insertion point is read into memory, the rest is copied
+/// into the staged file as is.
+fn stage_spliced(
+    path: &Path,
+    version: &Version,
+    new_section: &str,
+    metadata: &ReleaseMetadata,
+) -> Result<StagedChangelog, ChangelogError> {
+    let Some(splice) = ChangelogSplice::scan(path, version)? else {
+        return stage_changelog(path, Some(version), &mut |existing| {
+            insert_section(existing, new_section, version, metadata)
+        });
+    };
+    if splice.is_frozen() {
+        return Err(ChangelogError::SectionFrozen(version.to_string()));
+    }
+    let new_section = &metadata
+        .category_aliases
+        .headings_in_file(path)
+        .relabel(new_section);
+
+    let mut temp_file = create_temp_file(path)?;
+    let mut out = BufWriter::new(temp_file.as_file_mut());
+    splice.write_to(
+        new_section,
+        version,
+        metadata.compare_links.as_ref(),
+        &mut out,
+    )?;
+    out.flush().map_err(ChangelogError::WriteFailed)?;
+    drop(out);
+    temp_file
+        .as_file()
+        .sync_all()
+        .map_err(ChangelogError::WriteFailed)?;
+
+    Ok(StagedChangelog {
+        path: path.to_path_buf(),
+        temp_file,
+        existed: true,
+    })
+}
+
+/// Render the new content of `path` from its whole current content and write
+/// it to a synced temp file. Fails if the section of `version` is frozen.
+fn stage_changelog(
+    path: &Path,
+    version: Option<&Version>,
+    render: &mut impl FnMut(Option<&str>) -> String,
+) -> Result<StagedChangelog, ChangelogError> {
     // Read existing changelog or create new
     let existing = read_changelog(path)?;
+    if let Some(version) = version
+        && existing.as_ref().is_some_and(|e| e.is_frozen(version))
+    {
+        return Err(ChangelogError::SectionFrozen(version.to_string()));
+    }
+    let existed = existing.is_some();
+    let new_content = render(existing.as_ref().map(|e| e.raw_content.as_str()));
 
-    let new_content = if let Some(existing) = existing {
-        // Atomic backup of existing file
-        let backup_path = path.with_extension("md.bak");
-        atomic_copy(path, &backup_path)?;
+    let mut temp_file = create_temp_file(path)?;
+    temp_file
+        .write_all(new_content.as_bytes())
+        .map_err(ChangelogError::WriteFailed)?;
+    temp_file
+        .as_file()
+        .sync_all()
+        .map_err(ChangelogError::WriteFailed)?;
+
+    Ok(StagedChangelog {
+        path: path.to_path_buf(),
+        temp_file,
+        existed,
+    })
+}
 
-        // Generate new version section
-        let new_section = format_version_section(version, &today, output);
+/// A temp file in the directory of `path` (created if missing), as required
+/// for an atomic rename onto it.
+fn create_temp_file(path: &Path) -> Result<NamedTempFile, ChangelogError> {
+    temp_file_beside(path).map_err(ChangelogError::WriteFailed)
+}
 
+/// Insert `new_section` into `original` (or a new changelog) below the header
+/// and any `[Unreleased]` section.
+fn insert_section(
+    original: Option<&str>,
+    new_section: &str,
+    version: &Version,
+    metadata: &ReleaseMetadata,
+) -> String {
+    if let Some(existing) = original {
         // Normalize line endings before insertion (matches find_insertion_point behavior)
         // This ensures byte offsets are calculated consistently across platforms
-        let normalized_content = existing.raw_content.replace("\r\n", "\n");
+        let normalized_content = normalize_line_endings(existing);
+        let new_section = &metadata
+            .category_aliases
+            .headings_in(&normalized_content)
+            .relabel(new_section);
 
         // Insert new section
         let insertion_point = find_insertion_point(&normalized_content);
 
         let mut new_content = String::new();
         new_content.push_str(&normalized_content[..insertion_point]);
-        new_content.push_str(&new_section);
+        new_content.push_str(new_section);
         new_content.push('\n');
         new_content.push_str(&normalized_content[insertion_point..]);
 
-        new_content
+        let new_content = match &metadata.compare_links {
+            Some(links) => update_compare_links(&new_content, version, links),
+            None => new_content,
+        };
+        // Write back with the file's own line endings
+        LineEnding::detect(existing).apply(&new_content)
     } else {
         // Create new changelog
         let mut content = CHANGELOG_HEADER.to_string();
-        content.push_str(&format_version_section(version, &today, output));
+        content.push_str(new_section);
         content
-    };
-
-    // Atomic write: temp file + rename to prevent TOCTOU race
-    atomic_write(path, &new_content)?;
+    }
+}
 
-    Ok(())
+/// Best-effort rollback of changelogs replaced before a later write failed:
+/// files that existed are restored from their backup, new ones are removed.
+fn restore_changelogs(replaced: &[(PathBuf, bool)]) {
+    for (path, existed) in replaced {
+        let result = if *existed {
+            atomic_copy(&path.with_extension("md.bak"), path)
+        } else {
+            std::fs::remove_file(path).map_err(ChangelogError::WriteFailed)
+        };
+        if let Err(e) = result {
+            warn!("Failed to restore {}: {}", path.display(), e);
+        }
+    }
 }
 
 /// Format a version section in Keep a Changelog format.
-fn format_version_section(version: &Version, date: &str, output: &ChangelogOutput) -> String {
-    let mut section = format!("## [{}] - {}\n\n", version, date);
+pub fn format_version_section(
+    version: &Version,
+    metadata: &ReleaseMetadata,
+    output: &ChangelogOutput,
+) -> String {
+    if let Some(template) = &metadata.template {
+        match template.render_section(version, metadata, output) {
+            Ok(section) => return section,
+            Err(e) => warn!("{}; writing the Keep a Changelog layout instead", e),
+        }
+    }
+    let codename = metadata.codename.as_deref();
+    let mut section = metadata
+        .heading
+        .render(&version.to_string(), &metadata.date, codename);
+    section.push_str("\n\n");
 
-    for (category, entries) in output.entries_by_category() {
-        section.push_str(&format!("### {}\n\n", category.as_str()));
+    let mut attributes = Vec::new();
+    if let Some(codename) = codename
+        && !metadata.heading.uses_codename()
+    {
+        attributes.push(ReleaseAttribute::new("Codename", codename));
+    }
+    attributes.extend(metadata.attributes.iter().cloned());
+
+    if !attributes.is_empty() {
+        let rendered: Vec<String> = attributes
+            .iter()
+            .map(|attr| format!("**{}:** {}", attr.name, attr.value))
+            .collect();
+        section.push_str(&rendered.join(" · "));
+        section.push_str("\n\n");
+    }
 
+    if let Some(highlights) = output.highlights_paragraph() {
+        section.push_str(&highlights);
+        section.push_str("\n\n");
+    }
+
+    section.push_str(&render_entries(
+        output,
+        metadata.sort,
+        metadata.attribution.as_ref(),
+        metadata.source_markers,
+    ));
+    section
+}
+
+/// Format the entries of `output` as `### Category` blocks, each ordered by
+/// `sort` and with attribution suffixes when given.
+pub fn format_entries(
+    output: &ChangelogOutput,
+    sort: EntrySort,
+    attribution: Option<&Attribution>,
+) -> String {
+    render_entries(output, sort, attribution, false)
+}
+
+/// [`format_entries`], optionally ending each entry with its source marker.
+fn render_entries(
+    output: &ChangelogOutput,
+    sort: EntrySort,
+    attribution: Option<&Attribution>,
+    source_markers: bool,
+) -> String {
+    let mut blocks = String::new();
+    for (category, mut entries) in output.entries_by_category() {
+        blocks.push_str(&format!("### {}\n\n", category.as_str()));
+
+        sort.sort(&mut entries);
         for entry in entries {
-            section.push_str(&format!("- {}\n", entry.description));
+            blocks.push_str(&entry_line(entry, sort, attribution, source_markers));
+            blocks.push('\n');
         }
 
-        section.push('\n');
+        blocks.push('\n');
     }
+    blocks
+}
 
-    section
+/// One `- ...` entry line, without the newline.
+pub(crate) fn entry_line(
+    entry: &ChangelogEntry,
+    sort: EntrySort,
+    attribution: Option<&Attribution>,
+    source_marker: bool,
+) -> String {
+    let mut line = format!("- {}", sort.entry_text(entry));
+    if let Some(suffix) = attribution.and_then(|attribution| attribution.suffix(entry)) {
+        line.push(' ');
+        line.push_str(&suffix);
+    }
+    if source_marker && let Some(marker) = sources_marker(entry) {
+        line.push(' ');
+        line.push_str(&marker);
+    }
+    line
+}
+
+/// Point the `[Unreleased]` link reference at the new tag and add a link for
+/// the new version right below it.
+///
+/// Changelogs without an `[Unreleased]:` link definition are returned
+/// unchanged, so link references are never introduced where none were used.
+pub fn update_compare_links(content: &str, version: &Version, links: &CompareLinks) -> String {
+    let mut lines: Vec<String> = content.lines().map(str::to_string).collect();
+
+    let Some(unreleased_idx) = lines
+        .iter()
+        .position(|line| line.to_lowercase().starts_with("[unreleased]:"))
+    else {
+        return content.to_string();
+    };
+
+    lines[unreleased_idx] = unreleased_link_line(links);
+
+    let version_prefix = format!("[{}]:", version);
+    if !lines.iter().any(|line| line.starts_with(&version_prefix)) {
+        lines.insert(unreleased_idx + 1, version_link_line(version, links));
+    }
+
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    updated
+}
+
+/// The `[Unreleased]` link definition comparing the new tag with `HEAD`.
+pub(crate) fn unreleased_link_line(links: &CompareLinks) -> String {
+    format!(
+        "[Unreleased]: {}/compare/{}...HEAD",
+        links.repo_url.trim_end_matches('/'),
+        links.tag
+    )
+}
+
+/// The link definition of the new version: a comparison with the previous
+/// tag, or the release page of the first one.
+pub(crate) fn version_link_line(version: &Version, links: &CompareLinks) -> String {
+    let base = links.repo_url.trim_end_matches('/');
+    match &links.previous_tag {
+        Some(previous) => format!(
+            "[{}]: {}/compare/{}...{}",
+            version, base, previous, links.tag
+        ),
+        None => format!("[{}]: {}/releases/tag/{}", version, base, links.tag),
+    }
 }
 
 /// Generate a summary message for the user.
@@ -180,15 +654,26 @@ mod tests {
                 ChangelogEntry {
                     category: ChangelogCategory::Added,
                     description: "New feature".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
                 },
                 ChangelogEntry {
                     category: ChangelogCategory::Fixed,
                     description: "Bug fix".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
                 },
             ],
+            highlights: None,
         };
 
-        let section = format_version_section(&Version::new(1, 2, 0), "2024-01-01", &output);
+        let metadata = ReleaseMetadata {
+            date: "2024-01-01".to_string(),
+            ..ReleaseMetadata::default()
+        };
+        let section = format_version_section(&Version::new(1, 2, 0), &metadata, &output);
 
         assert!(section.contains("## [1.2.0] - 2024-01-01"));
         assert!(section.contains("### Added"));
@@ -204,16 +689,26 @@ mod tests {
                 ChangelogEntry {
                     category: ChangelogCategory::Added,
                     description: "Feature 1".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
                 },
                 ChangelogEntry {
                     category: ChangelogCategory::Added,
                     description: "Feature 2".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
                 },
                 ChangelogEntry {
                     category: ChangelogCategory::Fixed,
                     description: "Bug fix".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
                 },
             ],
+            highlights: None,
         };
 
         let summary = generate_summary(&output);
@@ -294,4 +789,451 @@ mod tests {
         assert_eq!(files_after.len(), files_before.len() + 1);
         assert!(files_after.iter().any(|e| e.file_name() == "test.md"));
     }
+
+    #[test]
+    fn test_format_version_section_with_attributes() {
+        let output = ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Added,
+                description: "New feature".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: None,
+        };
+        let metadata = ReleaseMetadata {
+            date: "2024-05-01".to_string(),
+            codename: Some("Aurora".to_string()),
+            attributes: vec![ReleaseAttribute::new("Release manager", "@alice")],
+            ..ReleaseMetadata::default()
+        };
+
+        let section = format_version_section(&Version::new(2, 0, 0), &metadata, &output);
+
+        assert!(section.starts_with(
+            "## [2.0.0] - 2024-05-01\n\n**Codename:** Aurora · **Release manager:** @alice\n\n### Added"
+        ));
+    }
+
+    #[test]
+    fn test_format_version_section_with_attribution() {
+        use crate::changelog::attribution::{AttributionConfig, build_attribution};
+        use std::collections::HashMap;
+
+        let output = ChangelogOutput {
+            entries: vec![
+                ChangelogEntry {
+                    category: ChangelogCategory::Added,
+                    description: "Dark mode".to_string(),
+                    sources: vec!["#12".to_string()],
+                    scope: None,
+                    importance: None,
+                },
+                ChangelogEntry {
+                    category: ChangelogCategory::Fixed,
+                    description: "Crash on start".to_string(),
+                    sources: vec!["#13".to_string()],
+                    scope: None,
+                    importance: None,
+                },
+            ],
+            highlights: None,
+        };
+        let reviewers = HashMap::from([
+            (12, vec!["bob".to_string()]),
+            (13, vec!["dana".to_string()]),
+        ]);
+        let attribution = build_attribution(
+            &output,
+            &AttributionConfig::from_flag_or_env(Some("added".to_string())),
+            &[],
+            &reviewers,
+        );
+        let metadata = ReleaseMetadata {
+            date: "2024-05-01".to_string(),
+            attribution: Some(attribution),
+            ..ReleaseMetadata::default()
+        };
+
+        let section = format_version_section(&Version::new(2, 0, 0), &metadata, &output);
+
+        assert!(section.contains("- Dark mode (reviewed by @bob)\n"));
+        assert!(section.contains("- Crash on start\n"));
+    }
+
+    #[test]
+    fn test_format_version_section_sorts_within_categories() {
+        let entry = |category, description: &str, scope: Option<&str>| ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources: Vec::new(),
+            scope: scope.map(String::from),
+            importance: None,
+        };
+        let output = ChangelogOutput {
+            entries: vec![
+                entry(ChangelogCategory::Added, "Zoom controls", Some("ui")),
+                entry(ChangelogCategory::Fixed, "Crash on start", None),
+                entry(ChangelogCategory::Added, "API tokens", Some("api")),
+            ],
+            highlights: None,
+        };
+        let metadata = ReleaseMetadata {
+            date: "2024-05-01".to_string(),
+            sort: EntrySort::Scope,
+            ..ReleaseMetadata::default()
+        };
+
+        let section = format_version_section(&Version::new(2, 0, 0), &metadata, &output);
+
+        assert!(section.contains(
+            "### Added\n\n- **api:** API tokens\n- **ui:** Zoom controls\n\n### Fixed\n\n- Crash on start\n"
+        ));
+    }
+
+    #[test]
+    fn test_format_version_section_with_heading_template() {
+        let output = ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Added,
+                description: "New feature".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: None,
+        };
+        let metadata = ReleaseMetadata {
+            date: "2024-05-01".to_string(),
+            heading: "## [{version}] — {codename} — {date}".parse().unwrap(),
+            codename: Some("Aurora".to_string()),
+            ..ReleaseMetadata::default()
+        };
+
+        let section = format_version_section(&Version::new(2, 0, 0), &metadata, &output);
+
+        // The codename is in the heading, so no Codename attribute line
+        assert!(section.starts_with("## [2.0.0] — Aurora — 2024-05-01\n\n### Added"));
+    }
+
+    #[test]
+    fn test_format_version_section_places_highlights_before_entries() {
+        let output = ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Added,
+                description: "New feature".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: Some("This release adds a new feature.".to_string()),
+        };
+        let metadata = ReleaseMetadata {
+            date: "2024-05-01".to_string(),
+            attributes: vec![ReleaseAttribute::new("Release manager", "@alice")],
+            ..ReleaseMetadata::default()
+        };
+
+        let section = format_version_section(&Version::new(2, 0, 0), &metadata, &output);
+
+        assert!(section.starts_with(
+            "## [2.0.0] - 2024-05-01\n\n**Release manager:** @alice\n\nThis release adds a new feature.\n\n### Added\n\n- New feature\n"
+        ));
+    }
+
+    #[test]
+    fn test_parse_release_attribute() {
+        let attr: ReleaseAttribute = "Release manager = @bob".parse().unwrap();
+        assert_eq!(attr, ReleaseAttribute::new("Release manager", "@bob"));
+        assert!("novalue=".parse::<ReleaseAttribute>().is_err());
+        assert!("missing-separator".parse::<ReleaseAttribute>().is_err());
+    }
+
+    #[test]
+    fn test_update_compare_links() {
+        let content = "## [1.1.0] - 2024-01-01\n\n[Unreleased]: https://github.com/o/r/compare/v1.1.0...HEAD\n[1.1.0]: https://github.com/o/r/compare/v1.0.0...v1.1.0\n";
+        let links = CompareLinks {
+            repo_url: "https://github.com/o/r".to_string(),
+            tag: "v1.2.0".to_string(),
+            previous_tag: Some("v1.1.0".to_string()),
+        };
+
+        let updated = update_compare_links(content, &Version::new(1, 2, 0), &links);
+
+        assert_eq!(
+            updated,
+            "## [1.1.0] - 2024-01-01\n\n[Unreleased]: https://github.com/o/r/compare/v1.2.0...HEAD\n[1.2.0]: https://github.com/o/r/compare/v1.1.0...v1.2.0\n[1.1.0]: https://github.com/o/r/compare/v1.0.0...v1.1.0\n"
+        );
+    }
+
+    #[test]
+    fn test_update_compare_links_first_release() {
+        let content = "[unreleased]: https://github.com/o/r/commits/main\n";
+        let links = CompareLinks {
+            repo_url: "https://github.com/o/r/".to_string(),
+            tag: "v0.1.0".to_string(),
+            previous_tag: None,
+        };
+
+        let updated = update_compare_links(content, &Version::new(0, 1, 0), &links);
+
+        assert!(updated.contains("[Unreleased]: https://github.com/o/r/compare/v0.1.0...HEAD"));
+        assert!(updated.contains("[0.1.0]: https://github.com/o/r/releases/tag/v0.1.0"));
+    }
+
+    #[test]
+    fn test_update_compare_links_without_link_section_is_noop() {
+        let content = "## [1.0.0] - 2024-01-01\n\n### Added\n\n- Thing\n";
+        let links = CompareLinks {
+            repo_url: "https://github.com/o/r".to_string(),
+            tag: "v1.1.0".to_string(),
+            previous_tag: Some("v1.0.0".to_string()),
+        };
+
+        assert_eq!(
+            update_compare_links(content, &Version::new(1, 1, 0), &links),
+            content
+        );
+    }
+
+    #[test]
+    fn test_write_changelog_with_metadata_updates_links() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(
+            &path,
+            format!(
+                "{}## [Unreleased]\n\n## [1.0.0] - 2024-01-01\n\n- Initial\n\n[Unreleased]: https://github.com/o/r/compare/v1.0.0...HEAD\n",
+                CHANGELOG_HEADER
+            ),
+        )
+        .unwrap();
+        let output = ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Fixed,
+                description: "Bug fix".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: None,
+        };
+        let metadata = ReleaseMetadata {
+            date: "2024-02-01".to_string(),
+            attributes: vec![ReleaseAttribute::new("Codename", "Borealis")],
+            compare_links: Some(CompareLinks {
+                repo_url: "https://github.com/o/r".to_string(),
+                tag: "v1.0.1".to_string(),
+                previous_tag: Some("v1.0.0".to_string()),
+            }),
+            ..ReleaseMetadata::default()
+        };
+
+        write_changelog_with_metadata(&path, &output, &Version::new(1, 0, 1), &metadata).unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## [1.0.1] - 2024-02-01\n\n**Codename:** Borealis"));
+        assert!(content.contains("[Unreleased]: https://github.com/o/r/compare/v1.0.1...HEAD"));
+        assert!(content.contains("[1.0.1]: https://github.com/o/r/compare/v1.0.0...v1.0.1"));
+    }
+
+    fn fixed_output() -> ChangelogOutput {
+        ChangelogOutput {
+            entries: vec![ChangelogEntry {
+                category: ChangelogCategory::Fixed,
+                description: "Bug fix".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
+            }],
+            highlights: None,
+        }
+    }
+
+    #[test]
+    fn test_write_preserves_crlf_line_endings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        let original = format!(
+            "{}## [Unreleased]\n\n## [1.0.0] - 2024-01-01\n\n- Initial\n",
+            CHANGELOG_HEADER
+        )
+        .replace('\n', "\r\n");
+        std::fs::write(&path, &original).unwrap();
+        let metadata = ReleaseMetadata {
+            date: "2024-02-01".to_string(),
+            ..ReleaseMetadata::default()
+        };
+
+        write_changelog_with_metadata(&path, &fixed_output(), &Version::new(1, 0, 1), &metadata)
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## [1.0.1] - 2024-02-01\r\n"));
+        assert!(content.contains("## [1.0.0] - 2024-01-01\r\n"));
+        assert_eq!(
+            content.matches('\n').count(),
+            content.matches("\r\n").count()
+        );
+    }
+
+    #[test]
+    fn test_write_uses_the_files_alias_headings() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(
+            &path,
+            "# Changelog\n\n## [1.0.0] - 2024-01-01\n\n### Bug Fixes\n\n- Initial\n",
+        )
+        .unwrap();
+        let metadata = ReleaseMetadata {
+            date: "2024-02-01".to_string(),
+            ..ReleaseMetadata::default()
+        };
+
+        write_changelog_with_metadata(&path, &fixed_output(), &Version::new(1, 0, 1), &metadata)
+            .unwrap();
+
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(content.contains("## [1.0.1] - 2024-02-01\n\n### Bug Fixes\n"));
+        assert!(!content.contains("### Fixed"));
+    }
+
+    #[test]
+    fn test_write_refuses_frozen_section() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        let original = format!(
+            "{}## [1.0.0] - 2024-01-01\n\n<!-- keryx:frozen -->\n- Reviewed\n",
+            CHANGELOG_HEADER
+        );
+        std::fs::write(&path, &original).unwrap();
+
+        let result = write_changelog_with_metadata(
+            &path,
+            &fixed_output(),
+            &Version::new(1, 0, 0),
+            &ReleaseMetadata::default(),
+        );
+
+        assert!(matches!(result, Err(ChangelogError::SectionFrozen(v)) if v == "1.0.0"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), original);
+        assert!(!path.with_extension("md.bak").exists());
+    }
+
+    #[test]
+    fn test_write_changelogs_writes_every_path_and_creates_subdirectories() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("CHANGELOG.md");
+        let docs = dir.path().join("docs").join("changelog.md");
+        std::fs::write(
+            &root,
+            format!("{}## [1.0.0] - 2024-01-01\n", CHANGELOG_HEADER),
+        )
+        .unwrap();
+        let metadata = ReleaseMetadata {
+            date: "2024-02-01".to_string(),
+            ..ReleaseMetadata::default()
+        };
+
+        write_changelogs_with_metadata(
+            &[root.clone(), docs.clone(), root.clone()],
+            &fixed_output(),
+            &Version::new(1, 0, 1),
+            &metadata,
+        )
+        .unwrap();
+
+        let root_content = std::fs::read_to_string(&root).unwrap();
+        let docs_content = std::fs::read_to_string(&docs).unwrap();
+        assert_eq!(root_content.matches("## [1.0.1] - 2024-02-01").count(), 1);
+        assert!(root_content.contains("## [1.0.0] - 2024-01-01"));
+        assert!(docs_content.starts_with(CHANGELOG_HEADER));
+        assert!(docs_content.contains("## [1.0.1] - 2024-02-01"));
+        assert!(dir.path().join("CHANGELOG.md.bak").exists());
+        assert!(!dir.path().join("docs").join("changelog.md.bak").exists());
+    }
+
+    #[test]
+    fn test_write_changelogs_leaves_files_untouched_on_staging_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path().join("CHANGELOG.md");
+        let original = format!("{}## [1.0.0] - 2024-01-01\n", CHANGELOG_HEADER);
+        std::fs::write(&root, &original).unwrap();
+        // A directory where a changelog is expected cannot be read
+        let broken = dir.path().join("HISTORY.md");
+        std::fs::create_dir(&broken).unwrap();
+
+        let result = write_changelogs_with_metadata(
+            &[root.clone(), broken],
+            &fixed_output(),
+            &Version::new(1, 0, 1),
+            &ReleaseMetadata::default(),
+        );
+
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&root).unwrap(), original);
+        assert!(!dir.path().join("CHANGELOG.md.bak").exists());
+    }
+
+    #[test]
+    fn test_merge_changelogs_keeps_edits_across_reruns() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("CHANGELOG.md");
+        std::fs::write(
+            &path,
+            format!(
+                "{}## [1.0.0] - 2024-01-01
+
+- Initial
+",
+                CHANGELOG_HEADER
+            ),
+        )
+        .unwrap();
+        let mut output = fixed_output();
+        output.entries[0].sources = vec!["#7".to_string()];
+        let metadata = ReleaseMetadata {
+            date: "2024-02-01".to_string(),
+            ..ReleaseMetadata::default()
+        };
+        let version = Version::new(1, 0, 1);
+
+        let counts = merge_changelogs_with_metadata(
+            std::slice::from_ref(&path),
+            &output,
+            &version,
+            &metadata,
+        )
+        .unwrap();
+        assert_eq!(counts, MergeCounts { added: 1, kept: 0 });
+        let written = std::fs::read_to_string(&path).unwrap();
+        assert!(written.contains("- Bug fix <!-- keryx:sources #7 -->\n"));
+
+        // The maintainer rewords the entry, then the release is regenerated
+        let edited = written.replace("- Bug fix", "- Fixed a crash when saving");
+        std::fs::write(&path, &edited).unwrap();
+        output.entries.push(ChangelogEntry {
+            category: ChangelogCategory::Added,
+            description: "Export to CSV".to_string(),
+            sources: vec!["#8".to_string()],
+            scope: None,
+            importance: None,
+        });
+
+        let counts = merge_changelogs_with_metadata(
+            std::slice::from_ref(&path),
+            &output,
+            &version,
+            &metadata,
+        )
+        .unwrap();
+        assert_eq!(counts, MergeCounts { added: 1, kept: 1 });
+        let merged = std::fs::read_to_string(&path).unwrap();
+        assert_eq!(merged.matches("## [1.0.1]").count(), 1);
+        assert!(merged.contains("- Fixed a crash when saving <!-- keryx:sources #7 -->"));
+        assert!(!merged.contains("- Bug fix"));
+        assert!(merged.contains("### Added\n\n- Export to CSV <!-- keryx:sources #8 -->\n"));
+    }
 }
@@ -6,7 +6,7 @@ use std::time::Duration;
 
 use tokio::process::Command;
 use tokio::time::timeout;
-use tracing::warn;
+use tracing::{trace, warn};
 
 use crate::error::ClaudeError;
 
@@ -76,6 +76,7 @@ pub async fn check_claude_installed() -> Result<(), ClaudeError> {
 /// variable (value in seconds).
 ///
 /// If the timeout is exceeded, returns `ClaudeError::Timeout`.
+#[tracing::instrument(level = "trace", skip_all)]
 pub async fn run_claude(prompt: &str) -> Result<String, ClaudeError> {
     let timeout_duration = get_timeout();
     let timeout_secs = timeout_duration.as_secs();
@@ -95,6 +96,7 @@ pub async fn run_claude(prompt: &str) -> Result<String, ClaudeError> {
         "claude -p \"$(cat {})\" --output-format json --dangerously-skip-permissions 2>/dev/null",
         prompt_file.display()
     );
+    trace!("Running {} ({} byte prompt)", claude_cmd, prompt.len());
 
     // Use `script` to provide a pseudo-TTY for Claude Code
     // This works around Claude Code's TTY requirement bug (GitHub #9026)
@@ -8,7 +8,7 @@ use std::time::Duration;
 use tempfile::NamedTempFile;
 use tokio::process::Command;
 use tokio::time::timeout;
-use tracing::warn;
+use tracing::{trace, warn};
 
 use crate::error::CodexError;
 
@@ -36,9 +36,10 @@ const CHANGELOG_SCHEMA: &str = r#"{
         "required": ["category", "description"],
         "additionalProperties": false
       }
-    }
+    },
+    "highlights": { "type": ["string", "null"] }
   },
-  "required": ["entries"],
+  "required": ["entries", "highlights"],
   "additionalProperties": false
 }"#;
 
@@ -123,6 +124,7 @@ pub async fn run_codex_raw(prompt: &str) -> Result<String, CodexError> {
 }
 
 /// Shared subprocess helper: runs `codex exec [extra_args...] <prompt>`.
+#[tracing::instrument(level = "trace", skip_all)]
 async fn run_codex_command(prompt: &str, extra_args: &[&str]) -> Result<String, CodexError> {
     let timeout_duration = get_timeout();
     let timeout_secs = timeout_duration.as_secs();
@@ -132,6 +134,11 @@ async fn run_codex_command(prompt: &str, extra_args: &[&str]) -> Result<String,
     for arg in extra_args {
         cmd.arg(arg);
     }
+    trace!(
+        "Running codex exec {} <{} byte prompt>",
+        extra_args.join(" "),
+        prompt.len()
+    );
     cmd.arg(prompt)
         .stdout(Stdio::piped())
         .stderr(Stdio::piped());
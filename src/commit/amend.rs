@@ -0,0 +1,354 @@
+//! `keryx commit --amend-trailers`: add changelog trailers to unpushed commits.
+//!
+//! Commits made outside keryx lack the `Changelog` / `Changelog-Description`
+//! trailers `keryx commit` writes. For every unpushed commit without them the
+//! LLM reads the message and diff and proposes the trailers, which are then
+//! appended to the message; internal changes get `Changelog: none`. The commits are recreated with the same trees and
+//! authors, as an interactive rebase rewording each of them would do, so the
+//! working tree and index are untouched.
+
+use std::collections::HashMap;
+
+use git2::{Oid, Repository, Sort};
+use serde::Deserialize;
+
+use crate::changelog::ChangelogCategory;
+use crate::commit::check::NO_CHANGELOG;
+use crate::error::{CommitError, GitError};
+use crate::explain::CommitContext;
+use crate::git::footers::footer_block;
+use crate::git::{FooterKey, Footers};
+use crate::llm::extract_json;
+use crate::llm::prompt::{UNTRUSTED_INPUT_RULE, sanitize_for_prompt, untrusted_block};
+
+/// Reflog message of the rewritten branch.
+const REFLOG_MESSAGE: &str = "keryx: amend changelog trailers";
+
+/// Changelog trailers proposed for one commit. Both are `None` for internal
+/// changes.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct ChangelogTrailers {
+    pub changelog_category: Option<ChangelogCategory>,
+    pub changelog_description: Option<String>,
+}
+
+impl ChangelogTrailers {
+    /// Whether the change is user-facing.
+    pub fn is_user_facing(&self) -> bool {
+        self.changelog_category.is_some() && self.changelog_description.is_some()
+    }
+
+    /// Trailer lines, as `keryx commit` writes them.
+    pub fn lines(&self) -> Vec<String> {
+        match (&self.changelog_category, &self.changelog_description) {
+            (Some(category), Some(description)) => vec![
+                format!("Changelog: {}", category.as_str().to_lowercase()),
+                format!("Changelog-Description: {}", description.trim()),
+            ],
+            _ => vec![format!("Changelog: {}", NO_CHANGELOG)],
+        }
+    }
+}
+
+/// Whether `message` lacks a `Changelog` trailer.
+pub fn needs_trailers(message: &str) -> bool {
+    Footers::parse(message)
+        .first(&FooterKey::Changelog)
+        .is_none()
+}
+
+/// `message` with `trailers` appended to its footer block, or in a new one.
+pub fn with_trailers(message: &str, trailers: &ChangelogTrailers) -> String {
+    let lines = trailers.lines();
+    let message = message.trim_end();
+    let separator = if footer_block(message).is_some() {
+        "\n"
+    } else {
+        "\n\n"
+    };
+    format!("{}{}{}\n", message, separator, lines.join("\n"))
+}
+
+/// Commits on the current branch that its upstream does not have, oldest
+/// first.
+pub fn unpushed_commits(repo: &Repository) -> Result<Vec<Oid>, CommitError> {
+    let head = repo
+        .head()
+        .map_err(|e| GitError::ReferenceNotFound("HEAD".to_string(), e))?;
+    if !head.is_branch() {
+        return Err(CommitError::DetachedHead);
+    }
+    let branch = head.shorthand().unwrap_or("HEAD").to_string();
+    let head_oid = head
+        .target()
+        .ok_or_else(|| GitError::CommandFailed("HEAD has no target".to_string()))?;
+    let upstream = head
+        .name()
+        .and_then(|name| repo.branch_upstream_name(name).ok())
+        .and_then(|name| name.as_str().map(String::from))
+        .and_then(|name| repo.refname_to_id(&name).ok())
+        .ok_or(CommitError::NoUpstream(branch))?;
+
+    let mut revwalk = repo.revwalk().map_err(GitError::RevwalkError)?;
+    revwalk.push(head_oid).map_err(GitError::RevwalkError)?;
+    revwalk.hide(upstream).map_err(GitError::RevwalkError)?;
+    revwalk
+        .set_sorting(Sort::TOPOLOGICAL | Sort::REVERSE)
+        .map_err(GitError::RevwalkError)?;
+
+    let mut commits = Vec::new();
+    for oid in revwalk {
+        let oid = oid.map_err(GitError::RevwalkError)?;
+        let commit = repo.find_commit(oid).map_err(GitError::ParseCommit)?;
+        if commit.parent_count() > 1 {
+            return Err(CommitError::MergeCommit(oid.to_string()));
+        }
+        commits.push(oid);
+    }
+    Ok(commits)
+}
+
+/// Recreate `commits` (oldest first, ending at `HEAD`) with the messages in
+/// `messages`, and point the current branch at the new `HEAD`.
+///
+/// Commits before the first changed one are kept as they are. Authors and
+/// trees are preserved; the committer is the local git identity.
+pub fn rewrite_messages(
+    repo: &Repository,
+    commits: &[Oid],
+    messages: &HashMap<Oid, String>,
+) -> Result<Oid, CommitError> {
+    let committer = repo.signature().map_err(CommitError::ConfigError)?;
+    let Some(first_changed) = commits.iter().position(|oid| messages.contains_key(oid)) else {
+        return repo
+            .head()
+            .ok()
+            .and_then(|h| h.target())
+            .ok_or_else(|| GitError::CommandFailed("HEAD has no target".to_string()).into());
+    };
+
+    let mut parent = repo
+        .find_commit(commits[first_changed])
+        .and_then(|c| c.parent(0))
+        .ok();
+    for oid in &commits[first_changed..] {
+        let commit = repo.find_commit(*oid).map_err(GitError::ParseCommit)?;
+        let message = messages
+            .get(oid)
+            .map(String::as_str)
+            .unwrap_or_else(|| commit.message().unwrap_or_default());
+        let tree = commit.tree().map_err(CommitError::CommitFailed)?;
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        let new_oid = repo
+            .commit(None, &commit.author(), &committer, message, &tree, &parents)
+            .map_err(CommitError::CommitFailed)?;
+        parent = Some(
+            repo.find_commit(new_oid)
+                .map_err(CommitError::CommitFailed)?,
+        );
+    }
+
+    let new_head = parent
+        .map(|c| c.id())
+        .ok_or_else(|| GitError::CommandFailed("No commits rewritten".to_string()))?;
+    let mut head = repo
+        .head()
+        .map_err(|e| GitError::ReferenceNotFound("HEAD".to_string(), e))?;
+    head.set_target(new_head, REFLOG_MESSAGE)
+        .map_err(CommitError::CommitFailed)?;
+    Ok(new_head)
+}
+
+/// Build the prompt asking for the changelog trailers of an existing commit.
+pub fn build_trailers_prompt(commit: &CommitContext) -> String {
+    format!(
+        r#"You are adding changelog metadata to an existing Git commit so it can be assembled into release notes.
+
+## Commit Message
+{message}
+
+{UNTRUSTED_INPUT_RULE}
+
+## Changed Files
+{files}
+
+## Diff
+```
+{diff}
+```{truncation_note}
+
+## Changelog Metadata
+Determine whether this change is user-facing and should appear in release notes.
+
+`changelog_category`: One of "added", "changed", "fixed", "removed", "deprecated", "security", or null.
+- feat → "added", fix → "fixed", perf → "changed"
+- refactor, test, docs, chore, ci, build, style → null (not user-facing)
+- Override if a refactor IS user-facing (e.g., changes CLI output) → set the appropriate category
+
+`changelog_description`: A one-line description written for END USERS who have never seen the code.
+- Imperative mood, no type prefix, no technical jargon
+- Focus on what the user can now do or what problem is solved
+- Set to null if `changelog_category` is null
+
+## Output Format
+Respond with ONLY a JSON object (no markdown, no explanation):
+{{"changelog_category": "added", "changelog_description": "user-facing description"}}"#,
+        message = untrusted_block(&sanitize_for_prompt(commit.message.trim())),
+        files = commit.files.join("\n"),
+        diff = commit.diff,
+        truncation_note = if commit.truncated {
+            "\n\nNote: The diff was truncated due to size. Focus on the visible changes."
+        } else {
+            ""
+        },
+    )
+}
+
+/// Parse the LLM response to [`build_trailers_prompt`].
+pub fn parse_trailers(output: &str) -> Result<ChangelogTrailers, String> {
+    let json = extract_json(output);
+    let trailers: ChangelogTrailers =
+        serde_json::from_str(&json).map_err(|e| format!("invalid trailers JSON: {}", e))?;
+    let description = trailers
+        .changelog_description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty() && !d.contains('\n'));
+    Ok(ChangelogTrailers {
+        changelog_description: description.clone(),
+        changelog_category: trailers
+            .changelog_category
+            .filter(|_| description.is_some()),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    fn trailers(category: ChangelogCategory, description: &str) -> ChangelogTrailers {
+        ChangelogTrailers {
+            changelog_category: Some(category),
+            changelog_description: Some(description.to_string()),
+        }
+    }
+
+    fn commit_file(repo: &Repository, path: &str, content: &str, message: &str) -> Oid {
+        let root = repo.workdir().unwrap();
+        std::fs::write(root.join(path), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_needs_trailers() {
+        assert!(needs_trailers("feat: dark mode"));
+        assert!(needs_trailers("feat: dark mode\n\nRefs: #12"));
+        assert!(!needs_trailers(
+            "feat: dark mode\n\nChangelog: added\nChangelog-Description: Dark mode"
+        ));
+    }
+
+    #[test]
+    fn test_with_trailers() {
+        let t = trailers(ChangelogCategory::Added, "Switch to dark mode");
+        assert_eq!(
+            with_trailers("feat: dark mode\n", &t),
+            "feat: dark mode\n\nChangelog: added\nChangelog-Description: Switch to dark mode\n"
+        );
+        assert_eq!(
+            with_trailers("feat: dark mode\n\nWhy.\n\nRefs: #12\n", &t),
+            "feat: dark mode\n\nWhy.\n\nRefs: #12\nChangelog: added\nChangelog-Description: Switch to dark mode\n"
+        );
+        let internal = ChangelogTrailers {
+            changelog_category: None,
+            changelog_description: None,
+        };
+        assert_eq!(
+            with_trailers("chore: deps", &internal),
+            "chore: deps\n\nChangelog: none\n"
+        );
+    }
+
+    #[test]
+    fn test_parse_trailers() {
+        assert_eq!(
+            parse_trailers(
+                r#"{"changelog_category": "fixed", "changelog_description": " Fix crash "}"#
+            ),
+            Ok(trailers(ChangelogCategory::Fixed, "Fix crash"))
+        );
+        let internal =
+            parse_trailers(r#"{"changelog_category": "added", "changelog_description": null}"#)
+                .unwrap();
+        assert!(!internal.is_user_facing());
+        assert!(parse_trailers("not json").is_err());
+    }
+
+    #[test]
+    fn test_unpushed_and_rewrite() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let pushed = commit_file(&repo, "a.txt", "a\n", "chore: init");
+        let branch = repo.head().unwrap().shorthand().unwrap().to_string();
+        repo.remote("origin", "https://example.com/repo.git")
+            .unwrap();
+        repo.reference(
+            &format!("refs/remotes/origin/{}", branch),
+            pushed,
+            true,
+            "test",
+        )
+        .unwrap();
+        let mut config = repo.config().unwrap();
+        config
+            .set_str(&format!("branch.{}.remote", branch), "origin")
+            .unwrap();
+        config
+            .set_str(
+                &format!("branch.{}.merge", branch),
+                &format!("refs/heads/{}", branch),
+            )
+            .unwrap();
+        config.set_str("user.name", "Test").unwrap();
+        config.set_str("user.email", "test@example.com").unwrap();
+
+        let first = commit_file(&repo, "b.txt", "b\n", "feat: add b");
+        let second = commit_file(&repo, "c.txt", "c\n", "fix: c");
+        assert_eq!(unpushed_commits(&repo).unwrap(), vec![first, second]);
+
+        let messages = HashMap::from([(
+            first,
+            with_trailers("feat: add b", &trailers(ChangelogCategory::Added, "Add b")),
+        )]);
+        let new_head = rewrite_messages(&repo, &[first, second], &messages).unwrap();
+
+        let head = repo.find_commit(new_head).unwrap();
+        assert_eq!(head.message(), Some("fix: c"));
+        assert_eq!(head.tree_id(), repo.find_commit(second).unwrap().tree_id());
+        let parent = head.parent(0).unwrap();
+        assert!(!needs_trailers(parent.message().unwrap()));
+        assert_eq!(parent.parent_id(0).unwrap(), pushed);
+        assert_eq!(repo.head().unwrap().target(), Some(new_head));
+    }
+
+    #[test]
+    fn test_no_upstream() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit_file(&repo, "a.txt", "a\n", "chore: init");
+        assert!(matches!(
+            unpushed_commits(&repo),
+            Err(CommitError::NoUpstream(_))
+        ));
+    }
+}
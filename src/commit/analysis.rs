@@ -10,6 +10,7 @@ use tracing::{debug, warn};
 
 use crate::commit::diff::DiffSummary;
 use crate::llm::extract_json;
+use crate::llm::request::{LlmTask, TextRequest};
 use crate::llm::router::{LlmError, LlmRouter};
 
 /// Minimum number of changed files before split analysis is attempted.
@@ -33,7 +34,7 @@ pub struct SplitAnalysis {
 
 /// Build the prompt for split analysis.
 ///
-/// Uses ONLY file paths and status (no full diff) to keep the analysis
+/// Uses ONLY file paths, status, and per-file stats (no full diff) to keep the analysis
 /// lightweight — typically under 2K tokens even for 50+ files.
 pub fn build_split_analysis_prompt(diff: &DiffSummary, branch_name: &str) -> String {
     let file_count = diff.changed_files.len();
@@ -41,7 +42,7 @@ pub fn build_split_analysis_prompt(diff: &DiffSummary, branch_name: &str) -> Str
     let files_section: String = diff
         .changed_files
         .iter()
-        .map(|f| format!("- {} ({})", f.path, f.status))
+        .map(|f| f.describe())
         .collect::<Vec<_>>()
         .join("\n");
 
@@ -90,7 +91,9 @@ pub async fn analyze_split(
         debug!("Split analysis prompt length: {} chars", prompt.len());
     }
 
-    let completion = llm.generate_raw(&prompt).await?;
+    let completion = llm
+        .request(&TextRequest::for_task(LlmTask::Commit, prompt))
+        .await?;
 
     let json_str = extract_json(&completion.output);
     let analysis: SplitAnalysis = match serde_json::from_str(&json_str) {
@@ -167,7 +170,7 @@ pub fn validate_split(analysis: &SplitAnalysis, changed_files: &[&str]) -> Optio
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commit::diff::{ChangedFile, DiffSummary, FileStatus};
+    use crate::commit::diff::{ChangedFile, DiffSummary, FileKind, FileStatus};
 
     fn make_diff(files: &[(&str, FileStatus)]) -> DiffSummary {
         DiffSummary {
@@ -178,6 +181,10 @@ mod tests {
                     path: path.to_string(),
                     status: status.clone(),
                     old_path: None,
+                    additions: 0,
+                    deletions: 0,
+                    mode_change: None,
+                    kind: FileKind::Regular,
                 })
                 .collect(),
             truncated: false,
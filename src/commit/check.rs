@@ -0,0 +1,207 @@
+//! `keryx check-trailers`: require changelog trailers on every commit.
+//!
+//! Each commit of a pull request must state what it means for the
+//! changelog: `Changelog: <category>` with a `Changelog-Description`, as
+//! `keryx commit` writes them, or `Changelog: none` for a change without an
+//! entry. Run in CI, this keeps the metadata complete so changelogs can be
+//! assembled from the trailers. Merge commits are not checked.
+
+use std::fmt;
+
+use git2::{Oid, Repository};
+
+use crate::changelog::ChangelogCategory;
+use crate::error::GitError;
+use crate::git::{FooterKey, Footers};
+
+/// `Changelog` trailer value of a commit with no changelog entry.
+pub const NO_CHANGELOG: &str = "none";
+
+/// Whether a `Changelog` trailer value is the [`NO_CHANGELOG`] marker.
+pub fn is_no_changelog(value: &str) -> bool {
+    value.trim().eq_ignore_ascii_case(NO_CHANGELOG)
+}
+
+/// Why a commit's changelog trailers are not valid.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrailerProblem {
+    /// No `Changelog` trailer.
+    Missing,
+    /// The `Changelog` value is neither a category nor `none`.
+    UnknownCategory(String),
+    /// A category without a `Changelog-Description`.
+    MissingDescription,
+    /// `Changelog: none` together with a `Changelog-Description`.
+    DescriptionWithoutEntry,
+}
+
+impl fmt::Display for TrailerProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrailerProblem::Missing => write!(
+                f,
+                "no Changelog trailer (add `Changelog: <category>` or `Changelog: {}`)",
+                NO_CHANGELOG
+            ),
+            TrailerProblem::UnknownCategory(value) => write!(
+                f,
+                "unknown Changelog category '{}' (expected added, changed, deprecated, removed, fixed, security or {})",
+                value, NO_CHANGELOG
+            ),
+            TrailerProblem::MissingDescription => {
+                f.write_str("Changelog category without a Changelog-Description")
+            }
+            TrailerProblem::DescriptionWithoutEntry => write!(
+                f,
+                "Changelog-Description on a `Changelog: {}` commit",
+                NO_CHANGELOG
+            ),
+        }
+    }
+}
+
+/// Check the changelog trailers of one commit message.
+pub fn check_message(message: &str) -> Result<(), TrailerProblem> {
+    let footers = Footers::parse(message);
+    let value = footers
+        .first(&FooterKey::Changelog)
+        .map(str::trim)
+        .ok_or(TrailerProblem::Missing)?;
+    let has_description = footers
+        .first(&FooterKey::ChangelogDescription)
+        .is_some_and(|d| !d.trim().is_empty());
+    if is_no_changelog(value) {
+        return if has_description {
+            Err(TrailerProblem::DescriptionWithoutEntry)
+        } else {
+            Ok(())
+        };
+    }
+    value
+        .parse::<ChangelogCategory>()
+        .map_err(|_| TrailerProblem::UnknownCategory(value.to_string()))?;
+    if has_description {
+        Ok(())
+    } else {
+        Err(TrailerProblem::MissingDescription)
+    }
+}
+
+/// A commit that failed the check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrailerViolation {
+    pub hash: String,
+    pub subject: String,
+    pub problem: TrailerProblem,
+}
+
+/// Result of checking a range of commits.
+#[derive(Debug, Clone, Default)]
+pub struct TrailerReport {
+    /// Non-merge commits checked.
+    pub checked: usize,
+    /// Merge commits skipped.
+    pub merges: usize,
+    pub violations: Vec<TrailerViolation>,
+}
+
+impl TrailerReport {
+    pub fn is_ok(&self) -> bool {
+        self.violations.is_empty()
+    }
+}
+
+/// Check every commit reachable from `to` but not from `from`, newest first.
+pub fn check_range(repo: &Repository, from: Oid, to: Oid) -> Result<TrailerReport, GitError> {
+    let mut revwalk = repo.revwalk().map_err(GitError::RevwalkError)?;
+    revwalk.push(to).map_err(GitError::RevwalkError)?;
+    revwalk.hide(from).map_err(GitError::RevwalkError)?;
+
+    let mut report = TrailerReport::default();
+    for oid in revwalk {
+        let oid = oid.map_err(GitError::RevwalkError)?;
+        let commit = repo.find_commit(oid).map_err(GitError::ParseCommit)?;
+        if commit.parent_count() > 1 {
+            report.merges += 1;
+            continue;
+        }
+        report.checked += 1;
+        let message = String::from_utf8_lossy(commit.message_bytes());
+        if let Err(problem) = check_message(&message) {
+            report.violations.push(TrailerViolation {
+                hash: oid.to_string(),
+                subject: commit.summary().unwrap_or_default().to_string(),
+                problem,
+            });
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_check_message() {
+        assert_eq!(
+            check_message("feat: dark mode\n\nChangelog: added\nChangelog-Description: Dark mode"),
+            Ok(())
+        );
+        assert_eq!(check_message("chore: deps\n\nChangelog: None"), Ok(()));
+        assert_eq!(
+            check_message("feat: dark mode\n\nRefs: #12"),
+            Err(TrailerProblem::Missing)
+        );
+        assert_eq!(
+            check_message("fix: x\n\nChangelog: improved\nChangelog-Description: y"),
+            Err(TrailerProblem::UnknownCategory("improved".to_string()))
+        );
+        assert_eq!(
+            check_message("fix: x\n\nChangelog: fixed"),
+            Err(TrailerProblem::MissingDescription)
+        );
+        assert_eq!(
+            check_message("chore: x\n\nChangelog: none\nChangelog-Description: y"),
+            Err(TrailerProblem::DescriptionWithoutEntry)
+        );
+    }
+
+    #[test]
+    fn test_check_range_skips_merges() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let tree = repo
+            .find_tree(repo.index().unwrap().write_tree().unwrap())
+            .unwrap();
+        let commit = |message: &str, parents: &[&git2::Commit]| {
+            let oid = repo
+                .commit(None, &sig, &sig, message, &tree, parents)
+                .unwrap();
+            repo.find_commit(oid).unwrap()
+        };
+
+        let base = commit("chore: init", &[]);
+        let good = commit(
+            "fix: crash\n\nChangelog: fixed\nChangelog-Description: No crash",
+            &[&base],
+        );
+        let bad = commit("feat: side branch", &[&base]);
+        let merge = commit("Merge branch 'side'", &[&good, &bad]);
+
+        let report = check_range(&repo, base.id(), merge.id()).unwrap();
+        assert_eq!(report.checked, 2);
+        assert_eq!(report.merges, 1);
+        assert_eq!(
+            report.violations,
+            vec![TrailerViolation {
+                hash: bad.id().to_string(),
+                subject: "feat: side branch".to_string(),
+                problem: TrailerProblem::Missing,
+            }]
+        );
+    }
+}
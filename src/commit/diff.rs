@@ -3,7 +3,10 @@
 use std::collections::HashMap;
 use std::fmt;
 
-use git2::{Delta, Diff, DiffFormat, DiffOptions, ErrorCode, Repository, Tree};
+use git2::{
+    Delta, Diff, DiffFindOptions, DiffFormat, DiffOptions, ErrorCode, FileMode, Patch, Repository,
+    Tree,
+};
 use tracing::warn;
 
 use crate::error::CommitError;
@@ -18,6 +21,7 @@ pub enum FileStatus {
     Modified,
     Deleted,
     Renamed,
+    Copied,
 }
 
 impl fmt::Display for FileStatus {
@@ -27,17 +31,169 @@ impl fmt::Display for FileStatus {
             FileStatus::Modified => write!(f, "Modified"),
             FileStatus::Deleted => write!(f, "Deleted"),
             FileStatus::Renamed => write!(f, "Renamed"),
+            FileStatus::Copied => write!(f, "Copied"),
         }
     }
 }
 
+/// A file mode change between the old and new version of a file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ModeChange {
+    /// The executable bit was set.
+    MadeExecutable,
+    /// The executable bit was cleared.
+    RemovedExecutable,
+    /// Any other change (e.g. a file replaced by a symlink), as octal modes.
+    Other { old: String, new: String },
+}
+
+impl ModeChange {
+    fn between(old: FileMode, new: FileMode) -> Option<Self> {
+        if old == new || old == FileMode::Unreadable || new == FileMode::Unreadable {
+            return None;
+        }
+        let is_blob = |mode| matches!(mode, FileMode::Blob | FileMode::BlobGroupWritable);
+        Some(match (old, new) {
+            (old, FileMode::BlobExecutable) if is_blob(old) => ModeChange::MadeExecutable,
+            (FileMode::BlobExecutable, new) if is_blob(new) => ModeChange::RemovedExecutable,
+            _ => ModeChange::Other {
+                old: mode_octal(old).to_string(),
+                new: mode_octal(new).to_string(),
+            },
+        })
+    }
+}
+
+impl fmt::Display for ModeChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ModeChange::MadeExecutable => write!(f, "made executable"),
+            ModeChange::RemovedExecutable => write!(f, "no longer executable"),
+            ModeChange::Other { old, new } => write!(f, "mode {} -> {}", old, new),
+        }
+    }
+}
+
+fn mode_octal(mode: FileMode) -> &'static str {
+    match mode {
+        FileMode::Unreadable => "000000",
+        FileMode::Tree => "040000",
+        FileMode::Blob => "100644",
+        FileMode::BlobGroupWritable => "100664",
+        FileMode::BlobExecutable => "100755",
+        FileMode::Link => "120000",
+        FileMode::Commit => "160000",
+    }
+}
+
+/// First line of a Git LFS pointer file.
+const LFS_POINTER_PREFIX: &str = "version https://git-lfs.github.com/spec/";
+
+/// What kind of content a changed path holds.
+///
+/// Submodule bumps and LFS pointer files show up as one- or three-line text
+/// diffs; classifying them lets prompts describe the real change instead.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum FileKind {
+    #[default]
+    Regular,
+    /// A submodule pointer. Commit ids are abbreviated; `None` when the
+    /// submodule was added or removed.
+    Submodule {
+        old: Option<String>,
+        new: Option<String>,
+    },
+    /// A Git LFS pointer file. Sizes are those of the stored objects, in
+    /// bytes, when the pointer states them.
+    Lfs {
+        old_size: Option<u64>,
+        new_size: Option<u64>,
+    },
+}
+
+impl FileKind {
+    /// Short description for prompts, `None` for regular files.
+    pub fn describe(&self) -> Option<String> {
+        match self {
+            FileKind::Regular => None,
+            FileKind::Submodule {
+                old: Some(old),
+                new: Some(new),
+            } => Some(format!("submodule {} -> {}", old, new)),
+            FileKind::Submodule {
+                old: None,
+                new: Some(new),
+            } => Some(format!("submodule added at {}", new)),
+            FileKind::Submodule { old: Some(old), .. } => {
+                Some(format!("submodule removed (was {})", old))
+            }
+            FileKind::Submodule { .. } => Some("submodule".to_string()),
+            FileKind::Lfs { old_size, new_size } => Some(match (old_size, new_size) {
+                (Some(old), Some(new)) => {
+                    format!("LFS object {} -> {}", human_size(*old), human_size(*new))
+                }
+                (None, Some(size)) | (Some(size), None) => {
+                    format!("LFS object, {}", human_size(*size))
+                }
+                (None, None) => "LFS object".to_string(),
+            }),
+        }
+    }
+}
+
+/// Byte count as `512 B`, `10.2 KB`, `3.0 MB`, ...
+fn human_size(bytes: u64) -> String {
+    const UNITS: [&str; 4] = ["KB", "MB", "GB", "TB"];
+    if bytes < 1024 {
+        return format!("{} B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
 /// A file that was changed in the working tree.
 #[derive(Debug, Clone)]
 pub struct ChangedFile {
     pub path: String,
     pub status: FileStatus,
-    /// Old path for renamed files (None for non-rename changes).
+    /// Source path for renamed and copied files (None otherwise).
     pub old_path: Option<String>,
+    /// Lines added in this file.
+    pub additions: usize,
+    /// Lines deleted in this file.
+    pub deletions: usize,
+    /// File mode change, e.g. the executable bit being set.
+    pub mode_change: Option<ModeChange>,
+    /// Submodule pointer, LFS pointer, or regular file.
+    pub kind: FileKind,
+}
+
+impl ChangedFile {
+    /// One-line description for prompts, e.g.
+    /// `- src/new.rs (Renamed from src/old.rs, +3 -1, made executable)`.
+    pub fn describe(&self) -> String {
+        let mut details = vec![match &self.old_path {
+            Some(old) => format!("{} from {}", self.status, old),
+            None => self.status.to_string(),
+        }];
+        // Line counts of pointer files say nothing about the change
+        if let Some(kind) = self.kind.describe() {
+            details.push(kind);
+            return format!("- {} ({})", self.path, details.join(", "));
+        }
+        if self.additions > 0 || self.deletions > 0 {
+            details.push(format!("+{} -{}", self.additions, self.deletions));
+        }
+        if let Some(mode) = &self.mode_change {
+            details.push(mode.to_string());
+        }
+        format!("- {} ({})", self.path, details.join(", "))
+    }
 }
 
 /// Summary of changes in the working tree.
@@ -99,7 +255,7 @@ pub fn collect_diff_for_paths(
         .diff_index_to_workdir(None, Some(&mut unstaged_opts))
         .map_err(CommitError::DiffFailed)?;
 
-    build_summary(&staged_diff, &unstaged_diff)
+    build_summary(staged_diff, unstaged_diff)
 }
 
 /// Collect the working tree diff (staged + unstaged + untracked).
@@ -119,14 +275,31 @@ pub fn collect_diff(repo: &Repository) -> Result<DiffSummary, CommitError> {
         .diff_index_to_workdir(None, Some(&mut opts))
         .map_err(CommitError::DiffFailed)?;
 
-    build_summary(&staged_diff, &unstaged_diff)
+    build_summary(staged_diff, unstaged_diff)
+}
+
+/// Run rename and copy detection on a diff (including untracked files).
+///
+/// Detection failures are logged and leave the diff as-is, reporting plain
+/// additions and deletions instead.
+fn detect_renames_and_copies(diff: &mut Diff<'_>) {
+    let mut opts = DiffFindOptions::new();
+    opts.renames(true).copies(true).for_untracked(true);
+    if let Err(e) = diff.find_similar(Some(&mut opts)) {
+        warn!("Rename detection failed: {e}");
+    }
 }
 
 /// Merge staged and unstaged diffs into a single [`DiffSummary`].
 ///
 /// Collects changed files from both diffs, deduplicates by path (staged takes
-/// precedence), and assembles the unified diff text with addition/deletion counts.
-fn build_summary(staged: &Diff<'_>, unstaged: &Diff<'_>) -> Result<DiffSummary, CommitError> {
+/// precedence for status, line counts are summed), and assembles the unified
+/// diff text with addition/deletion counts.
+fn build_summary(mut staged: Diff<'_>, mut unstaged: Diff<'_>) -> Result<DiffSummary, CommitError> {
+    detect_renames_and_copies(&mut staged);
+    detect_renames_and_copies(&mut unstaged);
+    let (staged, unstaged) = (&staged, &unstaged);
+
     let mut changed_files_map: HashMap<String, ChangedFile> = HashMap::new();
     collect_files_from_diff(staged, &mut changed_files_map);
     collect_files_from_diff(unstaged, &mut changed_files_map);
@@ -138,6 +311,16 @@ fn build_summary(staged: &Diff<'_>, unstaged: &Diff<'_>) -> Result<DiffSummary,
         return Err(CommitError::NoChanges);
     }
 
+    // Submodule and LFS changes are summarized instead of printed as lines
+    let notes: HashMap<String, String> = changed_files
+        .iter()
+        .filter_map(|f| {
+            f.kind
+                .describe()
+                .map(|kind| (f.path.clone(), format!("{}: {}\n", f.path, kind)))
+        })
+        .collect();
+
     let mut diff_text = String::new();
     let mut additions = 0usize;
     let mut deletions = 0usize;
@@ -145,6 +328,7 @@ fn build_summary(staged: &Diff<'_>, unstaged: &Diff<'_>) -> Result<DiffSummary,
 
     append_diff_text(
         staged,
+        &notes,
         &mut diff_text,
         &mut additions,
         &mut deletions,
@@ -153,6 +337,7 @@ fn build_summary(staged: &Diff<'_>, unstaged: &Diff<'_>) -> Result<DiffSummary,
     if !truncated {
         append_diff_text(
             unstaged,
+            &notes,
             &mut diff_text,
             &mut additions,
             &mut deletions,
@@ -169,7 +354,7 @@ fn build_summary(staged: &Diff<'_>, unstaged: &Diff<'_>) -> Result<DiffSummary,
     })
 }
 
-/// Collect changed file entries from a diff.
+/// Collect changed file entries, with per-file line counts, from a diff.
 fn collect_files_from_diff(diff: &Diff<'_>, files: &mut HashMap<String, ChangedFile>) {
     for delta_idx in 0..diff.deltas().len() {
         let delta = diff.get_delta(delta_idx).unwrap();
@@ -178,6 +363,7 @@ fn collect_files_from_diff(diff: &Diff<'_>, files: &mut HashMap<String, ChangedF
             Delta::Modified => FileStatus::Modified,
             Delta::Deleted => FileStatus::Deleted,
             Delta::Renamed => FileStatus::Renamed,
+            Delta::Copied => FileStatus::Copied,
             _ => FileStatus::Modified,
         };
 
@@ -191,7 +377,7 @@ fn collect_files_from_diff(diff: &Diff<'_>, files: &mut HashMap<String, ChangedF
             .map(|p| p.to_string_lossy().to_string());
 
         let (path, old_path) = match status {
-            FileStatus::Renamed => {
+            FileStatus::Renamed | FileStatus::Copied => {
                 let path = new_path
                     .clone()
                     .or_else(|| old_path.clone())
@@ -208,19 +394,104 @@ fn collect_files_from_diff(diff: &Diff<'_>, files: &mut HashMap<String, ChangedF
             continue;
         }
 
-        let next = ChangedFile {
-            path: path.clone(),
-            status,
-            old_path,
+        // Binary files and patch errors count as zero lines
+        let patch = Patch::from_diff(diff, delta_idx).ok().flatten();
+        let (additions, deletions) = patch
+            .as_ref()
+            .and_then(|patch| patch.line_stats().ok())
+            .map(|(_, additions, deletions)| (additions, deletions))
+            .unwrap_or_default();
+
+        let (old_mode, new_mode) = (delta.old_file().mode(), delta.new_file().mode());
+        let (kind, mode_change) = if old_mode == FileMode::Commit || new_mode == FileMode::Commit {
+            let short_id = |id: git2::Oid| (!id.is_zero()).then(|| id.to_string()[..7].to_string());
+            let kind = FileKind::Submodule {
+                old: (old_mode == FileMode::Commit)
+                    .then(|| short_id(delta.old_file().id()))
+                    .flatten(),
+                new: (new_mode == FileMode::Commit)
+                    .then(|| short_id(delta.new_file().id()))
+                    .flatten(),
+            };
+            (kind, None)
+        } else {
+            let kind = patch
+                .as_ref()
+                .and_then(lfs_pointer_kind)
+                .unwrap_or_default();
+            (kind, ModeChange::between(old_mode, new_mode))
         };
 
-        files.entry(path).or_insert(next);
+        files
+            .entry(path.clone())
+            .and_modify(|existing| {
+                existing.additions += additions;
+                existing.deletions += deletions;
+                if existing.mode_change.is_none() {
+                    existing.mode_change = mode_change.clone();
+                }
+            })
+            .or_insert(ChangedFile {
+                path,
+                status,
+                old_path,
+                additions,
+                deletions,
+                mode_change,
+                kind,
+            });
+    }
+}
+
+/// Classify a patch whose old or new side is a Git LFS pointer file, taking
+/// the object sizes from the `size` lines.
+fn lfs_pointer_kind(patch: &Patch<'_>) -> Option<FileKind> {
+    let mut old_lines = Vec::new();
+    let mut new_lines = Vec::new();
+    for hunk in 0..patch.num_hunks() {
+        for idx in 0..patch.num_lines_in_hunk(hunk).unwrap_or(0) {
+            let Ok(line) = patch.line_in_hunk(hunk, idx) else {
+                continue;
+            };
+            let content = String::from_utf8_lossy(line.content())
+                .trim_end()
+                .to_string();
+            match line.origin() {
+                '-' => old_lines.push(content),
+                '+' => new_lines.push(content),
+                ' ' => {
+                    old_lines.push(content.clone());
+                    new_lines.push(content);
+                }
+                _ => {}
+            }
+        }
     }
+
+    let is_pointer = |lines: &[String]| {
+        lines
+            .first()
+            .is_some_and(|first| first.starts_with(LFS_POINTER_PREFIX))
+    };
+    let size = |lines: &[String]| {
+        lines
+            .iter()
+            .find_map(|line| line.strip_prefix("size ")?.trim().parse().ok())
+    };
+    let (old_pointer, new_pointer) = (is_pointer(&old_lines), is_pointer(&new_lines));
+    (old_pointer || new_pointer).then(|| FileKind::Lfs {
+        old_size: old_pointer.then(|| size(&old_lines)).flatten(),
+        new_size: new_pointer.then(|| size(&new_lines)).flatten(),
+    })
 }
 
 /// Append unified diff text from a diff object, respecting the max length.
+///
+/// Files with an entry in `notes` get that note instead of their lines, and
+/// their lines are left out of the totals.
 fn append_diff_text(
     diff: &Diff<'_>,
+    notes: &HashMap<String, String>,
     text: &mut String,
     additions: &mut usize,
     deletions: &mut usize,
@@ -230,18 +501,39 @@ fn append_diff_text(
         return;
     }
 
-    if let Err(e) = diff.print(DiffFormat::Patch, |_delta, _hunk, line| {
+    let mut last_noted: Option<String> = None;
+    if let Err(e) = diff.print(DiffFormat::Patch, |delta, _hunk, line| {
         if *truncated {
             return true;
         }
 
+        let path = delta
+            .new_file()
+            .path()
+            .or_else(|| delta.old_file().path())
+            .map(|p| p.to_string_lossy().to_string());
+        if let Some(note) = path.as_ref().and_then(|p| notes.get(p)) {
+            if last_noted != path {
+                if text.len() + note.len() > MAX_DIFF_LENGTH {
+                    *truncated = true;
+                    return true;
+                }
+                text.push_str(note);
+                last_noted = path;
+            }
+            return true;
+        }
+
         match line.origin() {
             '+' => *additions += 1,
             '-' => *deletions += 1,
             _ => {}
         }
 
-        let content = std::str::from_utf8(line.content()).unwrap_or("");
+        // CRLF files would otherwise leave a stray \r on every prompt line
+        let content = std::str::from_utf8(line.content())
+            .unwrap_or("")
+            .replace("\r\n", "\n");
 
         // Check if adding this line would exceed the limit
         if text.len() + content.len() + 2 > MAX_DIFF_LENGTH {
@@ -254,7 +546,7 @@ fn append_diff_text(
         if origin == '+' || origin == '-' || origin == ' ' {
             text.push(origin);
         }
-        text.push_str(content);
+        text.push_str(&content);
 
         true
     }) {
@@ -266,6 +558,7 @@ fn append_diff_text(
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::path::Path;
 
     #[test]
     fn test_file_status_display() {
@@ -273,6 +566,221 @@ mod tests {
         assert_eq!(FileStatus::Modified.to_string(), "Modified");
         assert_eq!(FileStatus::Deleted.to_string(), "Deleted");
         assert_eq!(FileStatus::Renamed.to_string(), "Renamed");
+        assert_eq!(FileStatus::Copied.to_string(), "Copied");
+    }
+
+    #[test]
+    fn test_changed_file_describe() {
+        let file = ChangedFile {
+            path: "bin/run.sh".to_string(),
+            status: FileStatus::Renamed,
+            old_path: Some("run.sh".to_string()),
+            additions: 3,
+            deletions: 1,
+            mode_change: Some(ModeChange::MadeExecutable),
+            kind: FileKind::Regular,
+        };
+        assert_eq!(
+            file.describe(),
+            "- bin/run.sh (Renamed from run.sh, +3 -1, made executable)"
+        );
+
+        let submodule = ChangedFile {
+            path: "vendor/lib".to_string(),
+            status: FileStatus::Modified,
+            old_path: None,
+            additions: 1,
+            deletions: 1,
+            mode_change: None,
+            kind: FileKind::Submodule {
+                old: Some("abc1234".to_string()),
+                new: Some("def5678".to_string()),
+            },
+        };
+        assert_eq!(
+            submodule.describe(),
+            "- vendor/lib (Modified, submodule abc1234 -> def5678)"
+        );
+    }
+
+    #[test]
+    fn test_file_kind_describe() {
+        assert_eq!(FileKind::Regular.describe(), None);
+        let added = FileKind::Submodule {
+            old: None,
+            new: Some("def5678".to_string()),
+        };
+        assert_eq!(
+            added.describe().as_deref(),
+            Some("submodule added at def5678")
+        );
+        let removed = FileKind::Submodule {
+            old: Some("abc1234".to_string()),
+            new: None,
+        };
+        assert_eq!(
+            removed.describe().as_deref(),
+            Some("submodule removed (was abc1234)")
+        );
+        let lfs = FileKind::Lfs {
+            old_size: Some(1234),
+            new_size: Some(3 * 1024 * 1024),
+        };
+        assert_eq!(
+            lfs.describe().as_deref(),
+            Some("LFS object 1.2 KB -> 3.0 MB")
+        );
+        assert_eq!(human_size(512), "512 B");
+    }
+
+    #[test]
+    fn test_collect_diff_classifies_lfs_pointer() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let pointer = |oid: &str, size: u64| {
+            format!(
+                "version https://git-lfs.github.com/spec/v1\noid sha256:{}\nsize {}\n",
+                oid, size
+            )
+        };
+
+        std::fs::write(dir.path().join("logo.png"), pointer("aaaa", 2048)).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("logo.png")).unwrap();
+        index.write().unwrap();
+        let sig = git2::Signature::now("Test", "test@test.com").unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+
+        std::fs::write(dir.path().join("logo.png"), pointer("bbbb", 4096)).unwrap();
+
+        let summary = collect_diff(&repo).unwrap();
+        assert_eq!(
+            summary.changed_files[0].kind,
+            FileKind::Lfs {
+                old_size: Some(2048),
+                new_size: Some(4096),
+            }
+        );
+        assert!(
+            summary
+                .diff_text
+                .contains("logo.png: LFS object 2.0 KB -> 4.0 KB")
+        );
+        assert!(!summary.diff_text.contains("sha256"));
+        assert_eq!((summary.additions, summary.deletions), (0, 0));
+    }
+
+    #[test]
+    fn test_mode_change_between() {
+        assert_eq!(
+            ModeChange::between(FileMode::Blob, FileMode::BlobExecutable),
+            Some(ModeChange::MadeExecutable)
+        );
+        assert_eq!(
+            ModeChange::between(FileMode::BlobExecutable, FileMode::Blob),
+            Some(ModeChange::RemovedExecutable)
+        );
+        assert_eq!(
+            ModeChange::between(FileMode::Blob, FileMode::Link)
+                .unwrap()
+                .to_string(),
+            "mode 100644 -> 120000"
+        );
+        assert_eq!(
+            ModeChange::between(FileMode::Unreadable, FileMode::Blob),
+            None
+        );
+        assert_eq!(ModeChange::between(FileMode::Blob, FileMode::Blob), None);
+    }
+
+    #[test]
+    fn test_collect_diff_reports_per_file_stats_and_renames() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let content = "line one\nline two\nline three\nline four\nline five\n";
+        std::fs::write(dir.path().join("old.txt"), content).unwrap();
+        std::fs::write(dir.path().join("edit.txt"), "a\nb\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("old.txt")).unwrap();
+        index.add_path(Path::new("edit.txt")).unwrap();
+        index.write().unwrap();
+        let sig = git2::Signature::now("Test", "test@test.com").unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+
+        // Stage a rename, leave an edit unstaged
+        std::fs::rename(dir.path().join("old.txt"), dir.path().join("new.txt")).unwrap();
+        index.remove_path(Path::new("old.txt")).unwrap();
+        index.add_path(Path::new("new.txt")).unwrap();
+        index.write().unwrap();
+        std::fs::write(dir.path().join("edit.txt"), "a\nB\nc\n").unwrap();
+
+        let summary = collect_diff(&repo).unwrap();
+        let renamed = summary
+            .changed_files
+            .iter()
+            .find(|f| f.path == "new.txt")
+            .unwrap();
+        assert_eq!(renamed.status, FileStatus::Renamed);
+        assert_eq!(renamed.old_path.as_deref(), Some("old.txt"));
+        assert!(!summary.changed_files.iter().any(|f| f.path == "old.txt"));
+
+        let edited = summary
+            .changed_files
+            .iter()
+            .find(|f| f.path == "edit.txt")
+            .unwrap();
+        assert_eq!((edited.additions, edited.deletions), (2, 1));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collect_diff_detects_executable_bit() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let script = dir.path().join("run.sh");
+        std::fs::write(&script, "echo hi\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("run.sh")).unwrap();
+        index.write().unwrap();
+        let sig = git2::Signature::now("Test", "test@test.com").unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+
+        std::fs::set_permissions(&script, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let summary = collect_diff(&repo).unwrap();
+        assert_eq!(
+            summary.changed_files[0].mode_change,
+            Some(ModeChange::MadeExecutable)
+        );
+    }
+
+    #[test]
+    fn test_collect_diff_strips_crlf_from_diff_text() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        std::fs::write(dir.path().join("notes.txt"), "one\r\ntwo\r\n").unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("notes.txt")).unwrap();
+        index.write().unwrap();
+        let sig = git2::Signature::now("Test", "test@test.com").unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "init", &tree, &[])
+            .unwrap();
+
+        std::fs::write(dir.path().join("notes.txt"), "one\r\nthree\r\n").unwrap();
+
+        let summary = collect_diff(&repo).unwrap();
+        assert!(summary.diff_text.contains("+three\n"));
+        assert!(!summary.diff_text.contains('\r'));
     }
 
     #[test]
@@ -0,0 +1,194 @@
+//! `keryx lint-commit`: check a commit message before it is committed.
+//!
+//! The subject must be a conventional commit header that
+//! [`parse_commit_message`](crate::git::parse_commit_message) understands:
+//! `type(scope)!: description`, with one of the known types. Run from a
+//! `commit-msg` hook, this keeps the history parseable for version bumps and
+//! changelogs. Comment lines and everything below git's scissors line are
+//! ignored, as git strips them too, and messages git writes itself (merges,
+//! reverts, `fixup!` and `squash!`) are accepted as they are.
+
+use std::fmt;
+
+use crate::git::CommitType;
+use crate::git::commits::COMMIT_REGEX;
+
+/// Default limit for the subject line, in characters.
+pub const DEFAULT_MAX_SUBJECT_LENGTH: usize = 72;
+
+/// Types accepted in the header, as listed in error messages.
+const TYPES: &str = "feat, fix, docs, style, refactor, perf, test, build, ci, chore";
+
+/// Line below which `git commit --verbose` puts the diff.
+const SCISSORS: &str = "# ------------------------ >8 ------------------------";
+
+/// Subject prefixes of messages git writes itself.
+const GIT_GENERATED: [&str; 4] = ["Merge ", "Revert \"", "fixup! ", "squash! "];
+
+/// What is wrong with a commit message.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LintProblem {
+    /// Nothing but comments and blank lines.
+    EmptyMessage,
+    /// No `:` after the type.
+    MissingColon,
+    /// Text before the `:` that is not `type(scope)!`.
+    MalformedHeader(String),
+    /// A type that is not a conventional commit type.
+    UnknownType(String),
+    /// Nothing after the `:`.
+    MissingDescription,
+    SubjectTooLong {
+        length: usize,
+        max: usize,
+    },
+    /// The body starts on the line right below the subject.
+    MissingBlankLine,
+}
+
+impl fmt::Display for LintProblem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LintProblem::EmptyMessage => f.write_str("the commit message is empty"),
+            LintProblem::MissingColon => {
+                f.write_str("missing colon: the subject must look like `type(scope): description`")
+            }
+            LintProblem::MalformedHeader(header) => write!(
+                f,
+                "'{}' is not a header: expected `type`, `type(scope)` or `type(scope)!` before the colon",
+                header
+            ),
+            LintProblem::UnknownType(kind) => {
+                write!(f, "unknown type '{}' (expected one of {})", kind, TYPES)
+            }
+            LintProblem::MissingDescription => f.write_str("no description after the colon"),
+            LintProblem::SubjectTooLong { length, max } => {
+                write!(f, "subject is {} characters long (at most {})", length, max)
+            }
+            LintProblem::MissingBlankLine => {
+                f.write_str("separate the body from the subject with a blank line")
+            }
+        }
+    }
+}
+
+/// The message git would record: comment lines and everything below the
+/// scissors line removed, surrounding blank lines trimmed.
+pub fn strip_comments(message: &str) -> String {
+    message
+        .lines()
+        .take_while(|line| *line != SCISSORS)
+        .filter(|line| !line.starts_with('#'))
+        .collect::<Vec<_>>()
+        .join("\n")
+        .trim()
+        .to_string()
+}
+
+/// Check `message` (as found in `.git/COMMIT_EDITMSG`) and return every
+/// problem, in the order they appear.
+pub fn lint_message(message: &str, max_subject_length: usize) -> Vec<LintProblem> {
+    let message = strip_comments(message);
+    let mut lines = message.lines();
+    let Some(subject) = lines.next().map(str::trim_end) else {
+        return vec![LintProblem::EmptyMessage];
+    };
+    if GIT_GENERATED
+        .iter()
+        .any(|prefix| subject.starts_with(prefix))
+    {
+        return Vec::new();
+    }
+
+    let mut problems = Vec::new();
+    match COMMIT_REGEX.captures(subject) {
+        Some(caps) => {
+            let kind = &caps[1];
+            if kind.parse::<CommitType>().is_err() {
+                problems.push(LintProblem::UnknownType(kind.to_string()));
+            }
+            if subject[caps.get(0).map_or(0, |m| m.end())..]
+                .trim()
+                .is_empty()
+            {
+                problems.push(LintProblem::MissingDescription);
+            }
+        }
+        None => match subject.split_once(':') {
+            Some((header, _)) => problems.push(LintProblem::MalformedHeader(header.to_string())),
+            None => problems.push(LintProblem::MissingColon),
+        },
+    }
+
+    let length = subject.chars().count();
+    if length > max_subject_length {
+        problems.push(LintProblem::SubjectTooLong {
+            length,
+            max: max_subject_length,
+        });
+    }
+    if lines.next().is_some_and(|line| !line.trim().is_empty()) {
+        problems.push(LintProblem::MissingBlankLine);
+    }
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(message: &str) -> Vec<LintProblem> {
+        lint_message(message, DEFAULT_MAX_SUBJECT_LENGTH)
+    }
+
+    #[test]
+    fn test_valid_messages() {
+        assert!(lint("feat(auth): add two-factor login").is_empty());
+        assert!(lint("fix!: drop the legacy parser\n\nIt was broken.\n").is_empty());
+        assert!(
+            lint("# Please enter the commit message\nchore: bump deps\n# On branch main\n")
+                .is_empty()
+        );
+        assert!(lint("Merge branch 'main' into feature").is_empty());
+        assert!(lint("fixup! feat: add login").is_empty());
+        assert!(
+            lint(&format!(
+                "docs: fix typo\n{}\ndiff --git a/README.md b/README.md\n",
+                SCISSORS
+            ))
+            .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_reports_precise_problems() {
+        assert_eq!(
+            lint("# only a comment\n\n"),
+            vec![LintProblem::EmptyMessage]
+        );
+        assert_eq!(lint("add login page"), vec![LintProblem::MissingColon]);
+        assert_eq!(
+            lint("feat (auth): add login"),
+            vec![LintProblem::MalformedHeader("feat (auth)".to_string())]
+        );
+        assert_eq!(
+            lint("feature: add login"),
+            vec![LintProblem::UnknownType("feature".to_string())]
+        );
+        assert_eq!(lint("fix(api):  "), vec![LintProblem::MissingDescription]);
+        assert_eq!(
+            lint(&format!("feat: {}\nbody", "x".repeat(70))),
+            vec![
+                LintProblem::SubjectTooLong {
+                    length: 76,
+                    max: 72
+                },
+                LintProblem::MissingBlankLine
+            ]
+        );
+        assert_eq!(
+            LintProblem::UnknownType("feature".to_string()).to_string(),
+            "unknown type 'feature' (expected one of feat, fix, docs, style, refactor, perf, test, build, ci, chore)"
+        );
+    }
+}
@@ -8,11 +8,14 @@ use serde::Deserialize;
 use tracing::debug;
 
 use crate::changelog::ChangelogCategory;
+use crate::commit::check::{NO_CHANGELOG, is_no_changelog};
 use crate::commit::diff::{ChangedFile, DiffSummary, FileStatus};
-use crate::commit::prompt::build_commit_prompt;
+use crate::commit::prompt::{build_commit_prompt, build_revision_section};
 use crate::error::CommitError;
+use crate::git::footers::{FooterKey, Footers};
 use crate::llm::extract_json;
-use crate::llm::router::{LlmError, LlmRawCompletion, LlmRouter};
+use crate::llm::request::{LlmRequest, LlmResponse, LlmTask};
+use crate::llm::router::{LlmCompletion, LlmError, LlmRouter};
 
 /// A parsed commit message from the LLM.
 #[derive(Debug, Clone, Deserialize)]
@@ -40,6 +43,8 @@ impl CommitMessage {
     /// Changelog: added
     /// Changelog-Description: User-facing description here
     /// ```
+    ///
+    /// Internal changes get `Changelog: none`.
     pub fn format(&self) -> String {
         let mut parts = Vec::new();
 
@@ -54,8 +59,9 @@ impl CommitMessage {
 
         // Trailers
         let mut trailers = Vec::new();
-        if let Some(ref cat) = self.changelog_category {
-            trailers.push(format!("Changelog: {}", cat.as_str().to_lowercase()));
+        match self.changelog_category {
+            Some(ref cat) => trailers.push(format!("Changelog: {}", cat.as_str().to_lowercase())),
+            None => trailers.push(format!("Changelog: {}", NO_CHANGELOG)),
         }
         if let Some(ref desc) = self.changelog_description {
             trailers.push(format!("Changelog-Description: {desc}"));
@@ -73,6 +79,73 @@ impl CommitMessage {
     pub fn is_user_facing(&self) -> bool {
         self.changelog_category.is_some()
     }
+
+    /// Parse a message in the form [`format`](Self::format) produces, e.g.
+    /// after the user edited it. Lines starting with `#` are ignored.
+    ///
+    /// The `Changelog` and `Changelog-Description` trailers must be given
+    /// together; the category must be one of the Keep a Changelog types, or
+    /// `none` (without a description) for an internal change.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let text = text
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let text = text.trim();
+        let (subject, rest) = text.split_once('\n').unwrap_or((text, ""));
+        let subject = subject.trim();
+        if subject.is_empty() {
+            return Err("the subject line is empty".to_string());
+        }
+
+        let footers = Footers::parse(text);
+        let changelog_category = footers
+            .first(&FooterKey::Changelog)
+            .filter(|category| !is_no_changelog(category))
+            .map(|category| category.trim().parse::<ChangelogCategory>())
+            .transpose()?;
+        let changelog_description = footers
+            .first(&FooterKey::ChangelogDescription)
+            .map(|description| description.trim().to_string())
+            .filter(|description| !description.is_empty());
+        match (&changelog_category, &changelog_description) {
+            (Some(_), None) => {
+                return Err("a Changelog trailer needs a Changelog-Description".to_string());
+            }
+            (None, Some(_)) => {
+                return Err(
+                    "a Changelog-Description trailer needs a Changelog category".to_string()
+                );
+            }
+            _ => {}
+        }
+
+        let body = rest
+            .lines()
+            .filter(|line| !is_changelog_trailer(line))
+            .collect::<Vec<_>>()
+            .join("\n");
+        let body = body.trim();
+
+        Ok(Self {
+            breaking: subject.contains("!:") || footers.breaking_change().is_some(),
+            subject: subject.to_string(),
+            body: (!body.is_empty()).then(|| body.to_string()),
+            changelog_category,
+            changelog_description,
+        })
+    }
+}
+
+/// Whether `line` is a `Changelog` or `Changelog-Description` trailer.
+fn is_changelog_trailer(line: &str) -> bool {
+    line.split_once(':').is_some_and(|(key, _)| {
+        matches!(
+            FooterKey::from(key.trim()),
+            FooterKey::Changelog | FooterKey::ChangelogDescription
+        )
+    })
 }
 
 /// Generate a commit message from the diff using the LLM.
@@ -81,9 +154,35 @@ pub async fn generate_commit_message(
     branch_name: &str,
     llm: &mut LlmRouter,
     verbose: bool,
-) -> Result<(CommitMessage, LlmRawCompletion), LlmError> {
+) -> Result<LlmCompletion<CommitMessage>, LlmError> {
     let prompt = build_commit_prompt(diff, branch_name);
+    complete_commit_message(&prompt, diff, llm, verbose).await
+}
+
+/// Generate a different commit message than `previous`, which the user
+/// rejected, optionally following their `instruction`.
+pub async fn regenerate_commit_message(
+    diff: &DiffSummary,
+    branch_name: &str,
+    previous: &CommitMessage,
+    instruction: Option<&str>,
+    llm: &mut LlmRouter,
+    verbose: bool,
+) -> Result<LlmCompletion<CommitMessage>, LlmError> {
+    let prompt = format!(
+        "{}{}",
+        build_commit_prompt(diff, branch_name),
+        build_revision_section(&previous.format(), instruction)
+    );
+    complete_commit_message(&prompt, diff, llm, verbose).await
+}
 
+async fn complete_commit_message(
+    prompt: &str,
+    diff: &DiffSummary,
+    llm: &mut LlmRouter,
+    verbose: bool,
+) -> Result<LlmCompletion<CommitMessage>, LlmError> {
     if verbose {
         debug!("Commit prompt length: {} chars", prompt.len());
         debug!(
@@ -95,21 +194,33 @@ pub async fn generate_commit_message(
         );
     }
 
-    let completion = llm.generate_raw(&prompt).await?;
+    llm.request(&CommitMessageRequest { prompt }).await
+}
+
+/// A commit message, parsed from the JSON the commit prompt asks for.
+struct CommitMessageRequest<'a> {
+    prompt: &'a str,
+}
 
-    let json_str = extract_json(&completion.output);
-    let message: CommitMessage = serde_json::from_str(&json_str).map_err(|e| {
-        debug!("Failed to parse LLM response as CommitMessage: {}", e);
-        debug!("Raw response: {}", &completion.output);
+impl LlmRequest for CommitMessageRequest<'_> {
+    type Output = CommitMessage;
 
-        LlmError::ResponseParseFailed {
-            provider: completion.provider,
-            raw_output: completion.output.clone(),
-            parse_error: format!("Could not parse commit message JSON: {}", e),
-        }
-    })?;
+    fn task(&self) -> LlmTask {
+        LlmTask::Commit
+    }
 
-    Ok((message, completion))
+    fn prompt(&self) -> &str {
+        self.prompt
+    }
+
+    fn parse(&self, response: LlmResponse) -> Result<CommitMessage, String> {
+        let raw = response.into_text();
+        serde_json::from_str(&extract_json(&raw)).map_err(|e| {
+            debug!("Failed to parse LLM response as CommitMessage: {}", e);
+            debug!("Raw response: {}", raw);
+            format!("Could not parse commit message JSON: {}", e)
+        })
+    }
 }
 
 /// Stage all changes and create a commit.
@@ -239,6 +350,7 @@ fn resolve_parent_commit(repo: &Repository) -> Result<Option<git2::Commit<'_>>,
 mod tests {
     use super::*;
     use crate::changelog::ChangelogCategory;
+    use crate::commit::diff::FileKind;
     use git2::Signature;
 
     #[test]
@@ -250,7 +362,51 @@ mod tests {
             changelog_category: None,
             changelog_description: None,
         };
-        assert_eq!(msg.format(), "feat(auth): add login endpoint");
+        assert_eq!(
+            msg.format(),
+            "feat(auth): add login endpoint\n\nChangelog: none"
+        );
+    }
+
+    #[test]
+    fn test_parse_round_trips_format() {
+        let msg = CommitMessage {
+            subject: "fix(api): handle timeout".to_string(),
+            body: Some("Large repos timed out.\n\nCloses KRX-42".to_string()),
+            breaking: false,
+            changelog_category: Some(ChangelogCategory::Fixed),
+            changelog_description: Some("Fix timeouts on large repositories".to_string()),
+        };
+        let parsed = CommitMessage::parse(&msg.format()).unwrap();
+        assert_eq!(parsed.format(), msg.format());
+        assert_eq!(parsed.changelog_category, Some(ChangelogCategory::Fixed));
+        assert!(!parsed.breaking);
+    }
+
+    #[test]
+    fn test_parse_edited_message() {
+        let parsed = CommitMessage::parse(
+            "# Lines starting with '#' are ignored\nfeat(cli)!: drop --legacy\n\nBREAKING CHANGE: --legacy is gone\n",
+        )
+        .unwrap();
+        assert_eq!(parsed.subject, "feat(cli)!: drop --legacy");
+        assert_eq!(
+            parsed.body.as_deref(),
+            Some("BREAKING CHANGE: --legacy is gone")
+        );
+        assert!(parsed.breaking);
+        assert!(!parsed.is_user_facing());
+    }
+
+    #[test]
+    fn test_parse_rejects_invalid_messages() {
+        assert!(CommitMessage::parse("# only a comment\n\n").is_err());
+        assert!(
+            CommitMessage::parse("fix: x\n\nChangelog: improved\nChangelog-Description: y")
+                .is_err()
+        );
+        assert!(CommitMessage::parse("fix: x\n\nChangelog: fixed").is_err());
+        assert!(CommitMessage::parse("fix: x\n\nChangelog-Description: y").is_err());
     }
 
     #[test]
@@ -279,8 +435,8 @@ mod tests {
             changelog_category: None,
             changelog_description: None,
         };
-        // Empty/whitespace body and no trailers → subject only
-        assert_eq!(msg.format(), "chore: bump deps");
+        // Empty/whitespace body is dropped
+        assert_eq!(msg.format(), "chore: bump deps\n\nChangelog: none");
     }
 
     #[test]
@@ -300,7 +456,7 @@ mod tests {
     }
 
     #[test]
-    fn test_commit_message_format_internal_no_trailers() {
+    fn test_commit_message_format_internal_marks_no_changelog() {
         let msg = CommitMessage {
             subject: "refactor(auth): extract middleware".to_string(),
             body: Some("No behavior change.".to_string()),
@@ -311,9 +467,11 @@ mod tests {
         let formatted = msg.format();
         assert_eq!(
             formatted,
-            "refactor(auth): extract middleware\n\nNo behavior change."
+            "refactor(auth): extract middleware\n\nNo behavior change.\n\nChangelog: none"
         );
-        assert!(!formatted.contains("Changelog"));
+        let parsed = CommitMessage::parse(&formatted).unwrap();
+        assert!(!parsed.is_user_facing());
+        assert_eq!(parsed.body.as_deref(), Some("No behavior change."));
     }
 
     #[test]
@@ -415,6 +573,10 @@ mod tests {
                 path: "a.txt".to_string(),
                 status: FileStatus::Added,
                 old_path: None,
+                additions: 0,
+                deletions: 0,
+                mode_change: None,
+                kind: FileKind::Regular,
             },
         );
         statuses.insert(
@@ -423,6 +585,10 @@ mod tests {
                 path: "b.txt".to_string(),
                 status: FileStatus::Added,
                 old_path: None,
+                additions: 0,
+                deletions: 0,
+                mode_change: None,
+                kind: FileKind::Regular,
             },
         );
 
@@ -466,6 +632,10 @@ mod tests {
                 path: "a.txt".to_string(),
                 status: FileStatus::Added,
                 old_path: None,
+                additions: 0,
+                deletions: 0,
+                mode_change: None,
+                kind: FileKind::Regular,
             },
         );
         statuses.insert(
@@ -474,6 +644,10 @@ mod tests {
                 path: "b.txt".to_string(),
                 status: FileStatus::Added,
                 old_path: None,
+                additions: 0,
+                deletions: 0,
+                mode_change: None,
+                kind: FileKind::Regular,
             },
         );
 
@@ -525,6 +699,10 @@ mod tests {
                 path: "to_delete.txt".to_string(),
                 status: FileStatus::Deleted,
                 old_path: None,
+                additions: 0,
+                deletions: 0,
+                mode_change: None,
+                kind: FileKind::Regular,
             },
         );
 
@@ -573,6 +751,10 @@ mod tests {
                 path: "new.txt".to_string(),
                 status: FileStatus::Renamed,
                 old_path: Some("old.txt".to_string()),
+                additions: 0,
+                deletions: 0,
+                mode_change: None,
+                kind: FileKind::Regular,
             },
         );
 
@@ -626,6 +808,10 @@ mod tests {
                 path: "test.txt".to_string(),
                 status: FileStatus::Added,
                 old_path: None,
+                additions: 0,
+                deletions: 0,
+                mode_change: None,
+                kind: FileKind::Regular,
             },
         );
 
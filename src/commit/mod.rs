@@ -1,13 +1,28 @@
 //! AI-generated commit messages using LLM providers.
 
+pub mod amend;
 pub mod analysis;
+pub mod check;
 pub mod diff;
+pub mod lint;
 pub mod message;
 pub mod prompt;
 
+pub use amend::{
+    ChangelogTrailers, build_trailers_prompt, needs_trailers, parse_trailers, rewrite_messages,
+    unpushed_commits, with_trailers,
+};
 pub use analysis::{CommitGroup, SPLIT_ANALYSIS_THRESHOLD, SplitAnalysis, analyze_split};
-pub use diff::{ChangedFile, DiffSummary, FileStatus, collect_diff, collect_diff_for_paths};
+pub use check::{
+    NO_CHANGELOG, TrailerProblem, TrailerReport, TrailerViolation, check_message, check_range,
+};
+pub use diff::{
+    ChangedFile, DiffSummary, FileKind, FileStatus, ModeChange, collect_diff,
+    collect_diff_for_paths,
+};
+pub use lint::{DEFAULT_MAX_SUBJECT_LENGTH, LintProblem, lint_message};
 pub use message::{
-    CommitMessage, generate_commit_message, stage_and_commit, stage_paths_and_commit,
+    CommitMessage, generate_commit_message, regenerate_commit_message, stage_and_commit,
+    stage_paths_and_commit,
 };
 pub use prompt::build_commit_prompt;
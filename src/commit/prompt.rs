@@ -4,6 +4,7 @@ use crate::commit::diff::DiffSummary;
 use crate::llm::prompt::{
     filter_injection_patterns, normalize_whitespace, remove_ansi_escapes, remove_control_chars,
 };
+use crate::text::truncate_bytes;
 
 /// Maximum length for sanitized diff text.
 const MAX_DIFF_SANITIZED_LENGTH: usize = 30_000;
@@ -17,7 +18,7 @@ pub fn build_commit_prompt(diff: &DiffSummary, branch_name: &str) -> String {
     let files_section: String = diff
         .changed_files
         .iter()
-        .map(|f| format!("- {} ({})", f.path, f.status))
+        .map(|f| f.describe())
         .collect::<Vec<_>>()
         .join("\n");
 
@@ -66,6 +67,7 @@ The body should answer: What motivated this change? What problem does it solve?
 - Wrap lines at 72 characters
 - If the branch contains an issue key (e.g., `feat/KRX-42`), add a reference like `Closes KRX-42` on its own line
 - For trivial changes (typos, formatting), body may be null
+- Files marked as a submodule or LFS object are pointer updates: describe them as a dependency or asset bump (e.g. "bump vendor/lib to def5678"), not as line edits
 
 ## Changelog Metadata
 Determine whether this change is user-facing and should appear in release notes.
@@ -104,6 +106,28 @@ Respond with ONLY a JSON object (no markdown, no explanation):
     )
 }
 
+/// Prompt section asking for a different message than `previous`, the one
+/// the user rejected, optionally following the user's `instruction`.
+pub fn build_revision_section(previous: &str, instruction: Option<&str>) -> String {
+    let instruction = instruction
+        .map(|i| normalize_whitespace(&remove_control_chars(i)))
+        .filter(|i| !i.trim().is_empty())
+        .map(|i| format!("\nApply this instruction from the user: {}\n", i.trim()))
+        .unwrap_or_default();
+    format!(
+        r#"
+
+## Revision
+The user rejected this message:
+```
+{previous}
+```
+Write a different message for the same changes, following all rules above.{instruction}
+Respond with ONLY the JSON object described in Output Format."#,
+        previous = previous.trim(),
+    )
+}
+
 /// Sanitize diff text for inclusion in an LLM prompt.
 ///
 /// Similar to `sanitize_for_prompt()` but designed for diffs:
@@ -126,13 +150,8 @@ pub fn sanitize_diff(text: &str, max_len: usize) -> String {
     result = normalize_whitespace(&result);
 
     // 5. Truncate to max length
-    if result.len() > max_len {
-        let mut end = max_len;
-        while end > 0 && !result.is_char_boundary(end) {
-            end -= 1;
-        }
-        result.truncate(end);
-    }
+    let end = truncate_bytes(&result, max_len).len();
+    result.truncate(end);
 
     result
 }
@@ -140,7 +159,7 @@ pub fn sanitize_diff(text: &str, max_len: usize) -> String {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::commit::diff::{ChangedFile, DiffSummary, FileStatus};
+    use crate::commit::diff::{ChangedFile, DiffSummary, FileKind, FileStatus};
 
     fn make_diff_summary(files: Vec<(&str, FileStatus)>, diff_text: &str) -> DiffSummary {
         DiffSummary {
@@ -151,6 +170,10 @@ mod tests {
                     path: path.to_string(),
                     status,
                     old_path: None,
+                    additions: 0,
+                    deletions: 0,
+                    mode_change: None,
+                    kind: FileKind::Regular,
                 })
                 .collect(),
             truncated: false,
@@ -159,6 +182,19 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_build_revision_section() {
+        let section = build_revision_section(
+            "fix(api): handle timeout",
+            Some("  mention the\x07 perf fix "),
+        );
+        assert!(section.contains("```\nfix(api): handle timeout\n```"));
+        assert!(section.contains("Apply this instruction from the user: mention the perf fix\n"));
+
+        let section = build_revision_section("fix(api): handle timeout", Some("   "));
+        assert!(!section.contains("instruction from the user"));
+    }
+
     #[test]
     fn test_build_commit_prompt_includes_files() {
         let diff = make_diff_summary(
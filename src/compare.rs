@@ -0,0 +1,306 @@
+//! `keryx compare`: the release notes between two versions as one list.
+//!
+//! Users upgrading across several releases want everything that changed
+//! since the version they run, not one section per release. The sections
+//! after `from` up to and including `to` are merged by category, oldest
+//! release first, and each entry keeps the version that introduced it.
+//! Breaking entries are listed first whatever their category. Optionally
+//! the LLM condenses the list into a short upgrade summary.
+
+use semver::Version;
+
+use crate::changelog::line_ending::normalize;
+use crate::changelog::translations::sections;
+use crate::llm::prompt::sanitize_for_prompt;
+use crate::version::changelog_bump::is_breaking;
+
+/// Heading of the group collecting breaking entries.
+const BREAKING_CATEGORY: &str = "Breaking Changes";
+
+/// Heading for entries listed outside any `###` category.
+const UNCATEGORIZED: &str = "Other";
+
+/// Keep a Changelog categories, in the order they are listed.
+const CATEGORY_ORDER: [&str; 6] = [
+    "Added",
+    "Changed",
+    "Deprecated",
+    "Removed",
+    "Fixed",
+    "Security",
+];
+
+/// Parse a version argument, accepting a leading `v` (`v1.3.0`).
+pub fn parse_version_arg(value: &str) -> Result<Version, String> {
+    let trimmed = value.trim();
+    Version::parse(trimmed.strip_prefix('v').unwrap_or(trimmed))
+        .map_err(|e| format!("invalid version '{}': {}", value, e))
+}
+
+/// An entry of one of the compared releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombinedEntry {
+    /// Release that introduced the entry.
+    pub version: Version,
+    /// Entry text without the bullet; continuation lines are kept.
+    pub text: String,
+}
+
+/// Entries of one category across the compared releases.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombinedCategory {
+    pub name: String,
+    /// Oldest release first.
+    pub entries: Vec<CombinedEntry>,
+}
+
+/// The changes between two versions.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CombinedNotes {
+    pub from: Version,
+    pub to: Version,
+    /// Releases whose sections were merged, oldest first.
+    pub versions: Vec<Version>,
+    /// Non-empty categories in display order.
+    pub categories: Vec<CombinedCategory>,
+}
+
+impl CombinedNotes {
+    /// Merge the sections of changelog `content` newer than `from` and up to
+    /// `to` (inclusive). `from` does not need a section of its own.
+    pub fn from_changelog(content: &str, from: &Version, to: &Version) -> Self {
+        let content = normalize(content);
+        let mut in_range: Vec<_> = sections(&content)
+            .into_iter()
+            .filter(|s| &s.version > from && &s.version <= to)
+            .collect();
+        in_range.sort_by(|a, b| a.version.cmp(&b.version));
+
+        let mut categories: Vec<CombinedCategory> = Vec::new();
+        let mut versions = Vec::new();
+        for section in in_range {
+            versions.push(section.version.clone());
+            for (category, text) in section_entries(&content[section.start..section.end]) {
+                let name = if is_breaking(&text)
+                    || category
+                        .as_deref()
+                        .is_some_and(|c| c.to_lowercase().contains("breaking"))
+                {
+                    BREAKING_CATEGORY.to_string()
+                } else {
+                    category.unwrap_or_else(|| UNCATEGORIZED.to_string())
+                };
+                let entry = CombinedEntry {
+                    version: section.version.clone(),
+                    text,
+                };
+                match categories
+                    .iter_mut()
+                    .find(|c| c.name.eq_ignore_ascii_case(&name))
+                {
+                    Some(existing) => existing.entries.push(entry),
+                    None => categories.push(CombinedCategory {
+                        name,
+                        entries: vec![entry],
+                    }),
+                }
+            }
+        }
+        // Stable: unknown categories keep the order they were first seen in
+        categories.sort_by_key(|c| category_rank(&c.name));
+
+        Self {
+            from: from.clone(),
+            to: to.clone(),
+            versions,
+            categories,
+        }
+    }
+
+    /// Whether no release in the range has entries.
+    pub fn is_empty(&self) -> bool {
+        self.categories.is_empty()
+    }
+
+    /// Number of entries across all categories.
+    pub fn entry_count(&self) -> usize {
+        self.categories.iter().map(|c| c.entries.len()).sum()
+    }
+
+    /// Render the combined notes as Markdown.
+    pub fn render(&self) -> String {
+        let mut out = format!("## Changes from {} to {}\n\n", self.from, self.to);
+        let releases: Vec<String> = self.versions.iter().map(Version::to_string).collect();
+        out.push_str(&format!(
+            "{} {}: {}\n",
+            releases.len(),
+            if releases.len() == 1 {
+                "release"
+            } else {
+                "releases"
+            },
+            releases.join(", ")
+        ));
+        for category in &self.categories {
+            out.push_str(&format!("\n### {}\n\n", category.name));
+            for entry in &category.entries {
+                let (first, rest) = entry
+                    .text
+                    .split_once('\n')
+                    .map_or((entry.text.as_str(), None), |(first, rest)| {
+                        (first, Some(rest))
+                    });
+                out.push_str(&format!("- {} ({})\n", first, entry.version));
+                if let Some(rest) = rest {
+                    out.push_str(rest);
+                    out.push('\n');
+                }
+            }
+        }
+        out
+    }
+}
+
+/// Display position of a category: breaking changes, the Keep a Changelog
+/// categories, then everything else.
+fn category_rank(name: &str) -> usize {
+    if name == BREAKING_CATEGORY {
+        return 0;
+    }
+    CATEGORY_ORDER
+        .iter()
+        .position(|c| c.eq_ignore_ascii_case(name))
+        .map_or(CATEGORY_ORDER.len() + 1, |i| i + 1)
+}
+
+/// Entries of a section with their `###` category. Indented lines under a
+/// bullet belong to it.
+fn section_entries(section: &str) -> Vec<(Option<String>, String)> {
+    let mut category: Option<String> = None;
+    let mut entries: Vec<(Option<String>, String)> = Vec::new();
+    let mut in_entry = false;
+    for line in section.lines().skip(1) {
+        if let Some(heading) = line.strip_prefix("### ") {
+            category = Some(heading.trim().to_string());
+            in_entry = false;
+            continue;
+        }
+        if let Some(text) = ["- ", "* "].iter().find_map(|b| line.strip_prefix(b)) {
+            entries.push((category.clone(), text.trim().to_string()));
+            in_entry = true;
+            continue;
+        }
+        if in_entry && line.starts_with([' ', '\t']) && !line.trim().is_empty() {
+            let (_, text) = entries.last_mut().expect("in_entry implies an entry");
+            text.push('\n');
+            text.push_str(line.trim_end());
+            continue;
+        }
+        if !line.trim().is_empty() {
+            in_entry = false;
+        }
+    }
+    entries
+}
+
+/// Build the prompt asking the LLM to condense the combined notes into an
+/// upgrade summary.
+pub fn build_compare_prompt(notes: &CombinedNotes, repository_name: &str) -> String {
+    let mut changes = String::new();
+    for category in &notes.categories {
+        changes.push_str(&format!("## {}\n", sanitize_for_prompt(&category.name)));
+        for entry in &category.entries {
+            changes.push_str(&format!(
+                "- {} ({})\n",
+                sanitize_for_prompt(&entry.text),
+                entry.version
+            ));
+        }
+        changes.push('\n');
+    }
+
+    format!(
+        r#"You are writing upgrade notes for users of {repository} moving from version {from} to {to}.
+These are the changelog entries of every release in between, grouped by category. The release that introduced each entry is in parentheses.
+
+{changes}## Instructions
+1. Start with what users must do to upgrade: breaking changes, removals and required migrations, in the order they should be handled
+2. Then summarize the most important new features and fixes in a few bullets; merge entries that describe the same change across releases
+3. Mention a change that was added and later reverted or replaced only in its final form
+4. Do not invent changes that are not listed
+
+Respond in concise Markdown. Do not respond with JSON."#,
+        repository = sanitize_for_prompt(repository_name),
+        from = notes.from,
+        to = notes.to,
+        changes = changes,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog\n\n## [Unreleased]\n\n### Added\n- Not released yet\n\n\
+        ## [1.5.0] - 2024-05-01\n\n### Added\n- Export to CSV\n\n### Removed\n- **BREAKING** The `--legacy` flag\n\n\
+        ## [1.4.1] - 2024-04-10\n\n### Fixed\n- Crash on empty ranges\n  when no tag exists\n\n\
+        ## [1.4.0] - 2024-04-01\n\n### Added\n- Import from JSON\n\n### Security\n- Tokens are masked in logs\n\n\
+        ## [1.3.0] - 2024-03-01\n\n### Added\n- Initial release\n\n\
+        [1.5.0]: https://example.com/compare/v1.4.1...v1.5.0\n";
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_combines_sections_after_from_up_to_to() {
+        let notes = CombinedNotes::from_changelog(CHANGELOG, &v("1.3.0"), &v("1.5.0"));
+        assert_eq!(notes.versions, vec![v("1.4.0"), v("1.4.1"), v("1.5.0")]);
+        let names: Vec<&str> = notes.categories.iter().map(|c| c.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["Breaking Changes", "Added", "Fixed", "Security"]
+        );
+        let added: Vec<&str> = notes.categories[1]
+            .entries
+            .iter()
+            .map(|e| e.text.as_str())
+            .collect();
+        assert_eq!(added, vec!["Import from JSON", "Export to CSV"]);
+        assert_eq!(notes.entry_count(), 5);
+    }
+
+    #[test]
+    fn test_render() {
+        let notes = CombinedNotes::from_changelog(CHANGELOG, &v("1.4.0"), &v("1.5.0"));
+        assert_eq!(
+            notes.render(),
+            "## Changes from 1.4.0 to 1.5.0\n\n2 releases: 1.4.1, 1.5.0\n\n\
+             ### Breaking Changes\n\n- **BREAKING** The `--legacy` flag (1.5.0)\n\n\
+             ### Added\n\n- Export to CSV (1.5.0)\n\n\
+             ### Fixed\n\n- Crash on empty ranges (1.4.1)\n  when no tag exists\n"
+        );
+    }
+
+    #[test]
+    fn test_empty_range() {
+        let notes = CombinedNotes::from_changelog(CHANGELOG, &v("1.5.0"), &v("2.0.0"));
+        assert!(notes.is_empty());
+        assert!(notes.versions.is_empty());
+    }
+
+    #[test]
+    fn test_parse_version_arg() {
+        assert_eq!(parse_version_arg("v1.3.0"), Ok(v("1.3.0")));
+        assert_eq!(parse_version_arg("1.3.0"), Ok(v("1.3.0")));
+        assert!(parse_version_arg("latest").is_err());
+    }
+
+    #[test]
+    fn test_prompt_names_range() {
+        let notes = CombinedNotes::from_changelog(CHANGELOG, &v("1.3.0"), &v("1.5.0"));
+        let prompt = build_compare_prompt(&notes, "keryx");
+        assert!(prompt.contains("moving from version 1.3.0 to 1.5.0"));
+        assert!(prompt.contains("## Added\n- Import from JSON (1.4.0)\n"));
+    }
+}
@@ -31,6 +31,9 @@ pub enum GitError {
 
     #[error("Git command failed: {0}")]
     CommandFailed(String),
+
+    #[error("No release tag is reachable from '{0}'")]
+    NoReleaseTag(String),
 }
 
 /// Errors from GitHub API operations.
@@ -52,6 +55,19 @@ pub enum GitHubError {
 
     #[error("Failed to parse repository URL")]
     InvalidRepositoryUrl,
+
+    #[error("Milestone \"{name}\" not found in {owner}/{repo}")]
+    MilestoneNotFound {
+        name: String,
+        owner: String,
+        repo: String,
+    },
+
+    #[error("Failed to check GitHub token: {0}")]
+    TokenCheckFailed(#[source] Box<octocrab::Error>),
+
+    #[error("Refusing to {0}: this run is read-only")]
+    ReadOnly(String),
 }
 
 /// Errors from Claude CLI operations.
@@ -112,6 +128,59 @@ pub enum CodexError {
     SerializationFailed(String),
 }
 
+/// Errors from the Anthropic and OpenAI HTTP APIs.
+#[derive(Error, Debug)]
+pub enum ApiError {
+    #[error("{0} is not set. Export an API key to use the HTTP API provider")]
+    MissingKey(&'static str),
+
+    #[error("Request failed: {0}")]
+    RequestFailed(String),
+
+    #[error("Request timed out after {0} seconds")]
+    Timeout(u64),
+
+    #[error("API returned HTTP {status}: {message}")]
+    Status { status: u16, message: String },
+
+    #[error("API response contained no text")]
+    EmptyResponse,
+
+    #[error("API returned invalid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("All retry attempts failed: {0}")]
+    RetriesExhausted(#[source] Box<ApiError>),
+}
+
+/// Errors from a local Ollama server.
+#[derive(Error, Debug)]
+pub enum OllamaError {
+    #[error("No Ollama server at {0}. Start it with `ollama serve` or set OLLAMA_HOST")]
+    NotRunning(String),
+
+    #[error("Model '{0}' is not available. Pull it with `ollama pull {0}` or set OLLAMA_MODEL")]
+    ModelNotFound(String),
+
+    #[error("Request failed: {0}")]
+    RequestFailed(String),
+
+    #[error("Request timed out after {0} seconds")]
+    Timeout(u64),
+
+    #[error("Ollama returned HTTP {status}: {message}")]
+    Status { status: u16, message: String },
+
+    #[error("Ollama response contained no text")]
+    EmptyResponse,
+
+    #[error("Ollama returned invalid JSON: {0}")]
+    InvalidJson(String),
+
+    #[error("All retry attempts failed: {0}")]
+    RetriesExhausted(#[source] Box<OllamaError>),
+}
+
 /// Errors from changelog operations.
 #[derive(Error, Debug)]
 pub enum ChangelogError {
@@ -132,6 +201,32 @@ pub enum ChangelogError {
 
     #[error("Version {0} already exists in changelog. Use --force to overwrite.")]
     VersionAlreadyExists(String),
+
+    #[error("Version {0} is frozen ({marker}) and will not be modified, even with --force", marker = crate::changelog::parser::FROZEN_MARKER)]
+    SectionFrozen(String),
+}
+
+/// Errors from release notes templates.
+#[derive(Error, Debug)]
+pub enum TemplateError {
+    #[error("Failed to read template {path}: {source}", path = path.display())]
+    Read {
+        path: std::path::PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("{0}")]
+    Syntax(String),
+
+    #[error("Invalid template {path}: {message}", path = path.display())]
+    Invalid {
+        path: std::path::PathBuf,
+        message: String,
+    },
+
+    #[error("Failed to render release notes template: {0}")]
+    Render(String),
 }
 
 /// Errors from version operations.
@@ -161,6 +256,43 @@ pub enum CommitError {
 
     #[error("Git config error (missing user.name or user.email): {0}")]
     ConfigError(#[source] git2::Error),
+
+    #[error("HEAD is detached; check out a branch to amend its commits")]
+    DetachedHead,
+
+    #[error("Branch '{0}' has no upstream; cannot tell which commits are unpushed")]
+    NoUpstream(String),
+
+    #[error("Unpushed commit {0} is a merge; rewrite history by hand")]
+    MergeCommit(String),
+
+    #[error("Git error: {0}")]
+    Git(#[from] GitError),
+}
+
+/// Errors from `keryx explain`.
+#[derive(Error, Debug)]
+pub enum ExplainError {
+    #[error("No commit or changelog entry matches \"{0}\"")]
+    NotFound(String),
+
+    #[error(
+        "\"{query}\" matches several changelog entries, be more specific:\n  {}",
+        .candidates.join("\n  ")
+    )]
+    AmbiguousEntry {
+        query: String,
+        candidates: Vec<String>,
+    },
+
+    #[error("No commits in the release related to \"{0}\"")]
+    NoRelatedCommits(String),
+
+    #[error("Failed to read commit diff: {0}")]
+    DiffFailed(#[source] git2::Error),
+
+    #[error("Git error: {0}")]
+    Git(#[from] GitError),
 }
 
 /// Errors from verification and scanning operations.
@@ -220,6 +352,14 @@ pub enum ShipError {
     #[error("No version files found (Cargo.toml, package.json, or pyproject.toml)")]
     NoVersionFiles,
 
+    #[error(
+        "Version file {path} was selected but not detected (missing, ignored, or has no version)"
+    )]
+    VersionFileNotDetected { path: PathBuf },
+
+    #[error("Version files disagree on the current version: {0}")]
+    InconsistentVersions(String),
+
     #[error(
         "{path} contains invalid semver version \"{version}\"\n\n\
              Expected format: MAJOR.MINOR.PATCH (e.g., 1.2.3)\n\
@@ -254,6 +394,329 @@ pub enum ShipError {
     #[error("Verification failed: {0}")]
     VerificationFailed(String),
 
+    #[error(
+        "Release {0} is staged and waiting to be published. Run `keryx ship --publish` to push it first."
+    )]
+    ReleaseAlreadyStaged(String),
+
+    #[error("No staged release to publish. Stage one with `keryx ship --schedule <TIME>`.")]
+    NoStagedRelease,
+
+    #[error("Staged release {tag} no longer matches the repository: {reason}")]
+    StagedReleaseMoved { tag: String, reason: String },
+
+    #[error(
+        "Release {0} is prepared and waiting for approval. Another person must run `keryx ship --approve` on it first."
+    )]
+    AwaitingApproval(String),
+
+    #[error(
+        "Commit {0} is not a prepared release. Check out the commit made by `keryx ship --prepare` (branch release/<tag>)."
+    )]
+    NotPrepared(String),
+
+    #[error("{tag} was prepared by {preparer}; a different person must approve it")]
+    SelfApproval { tag: String, preparer: String },
+
+    #[error("Failed to access staged release {path}: {reason}")]
+    StagedReleaseState { path: PathBuf, reason: String },
+
+    #[error("Failed to write release record {path}: {reason}")]
+    ReleaseRecordFailed { path: PathBuf, reason: String },
+
+    #[error("Invalid publish step: {0}")]
+    InvalidPublishStep(String),
+
+    #[error("Publish step '{step}' cannot run: {reason}")]
+    PublishStepNotReady { step: String, reason: String },
+
+    #[error("Publish step '{step}' failed: {reason}. The pushed release was not rolled back.")]
+    PublishStepFailed { step: String, reason: String },
+
+    #[error("Release assets: {0}")]
+    ReleaseAssets(#[from] ReleaseAssetError),
+
+    #[error("Required checks: {0}")]
+    Checks(#[from] ChecksError),
+
+    #[error("Failed to write the release plan as JSON: {0}")]
+    PlanOutput(#[from] serde_json::Error),
+
+    #[error(transparent)]
+    Lock(#[from] LockError),
+
+    #[error("--train cannot be combined with {0}")]
+    TrainConflict(&'static str),
+
+    #[error("--package cannot be combined with {0}")]
+    PackageConflict(&'static str),
+
+    #[error(
+        "--package needs a workspace (Cargo [workspace], pnpm-workspace.yaml, package.json workspaces or lerna.json)"
+    )]
+    NoWorkspace,
+
+    #[error("No workspace package named '{name}' (packages: {available})")]
+    UnknownPackage { name: String, available: String },
+
+    #[error(
+        "Packages {first} and {second} are both named '{name}'; release trains tag packages by name"
+    )]
+    DuplicatePackageName {
+        name: String,
+        first: String,
+        second: String,
+    },
+
+    #[error("Tag {0} not found. Fetch tags with `git fetch --tags` first.")]
+    TagNotFound(String),
+
+    #[error("{0} is not a release tag (expected vX.Y.Z or <package>-vX.Y.Z)")]
+    NotReleaseTag(String),
+
+    #[error("Release {0} failed post-release validation")]
+    ReleaseUnhealthy(String),
+
     #[error("User cancelled")]
     Cancelled,
 }
+
+/// Errors from uploading release assets to a forge.
+#[derive(Error, Debug)]
+pub enum ReleaseAssetError {
+    #[error("Invalid asset pattern '{0}'")]
+    InvalidPattern(String),
+
+    #[error("Asset pattern '{0}' matched no files")]
+    NoMatches(String),
+
+    #[error("Two assets would be uploaded as '{0}'")]
+    DuplicateName(String),
+
+    #[error("Failed to read asset {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error(
+        "Remote '{0}' is not a GitHub repository; release assets can only be uploaded to GitHub"
+    )]
+    UnsupportedRemote(String),
+
+    #[error("No {forge} release exists for {tag}; create it before uploading assets")]
+    ReleaseNotFound { forge: &'static str, tag: String },
+
+    #[error("Forge API error: {0}")]
+    Api(String),
+
+    #[error("Failed to upload {name}: {reason}. The pushed release was not rolled back.")]
+    UploadFailed { name: String, reason: String },
+
+    #[error("All retry attempts failed: {0}")]
+    RetriesExhausted(#[source] Box<ReleaseAssetError>),
+}
+
+/// Errors from waiting for CI checks on the release commit.
+#[derive(Error, Debug)]
+pub enum ChecksError {
+    #[error("Remote '{0}' is not a GitHub repository; checks can only be read from GitHub")]
+    UnsupportedRemote(String),
+
+    #[error("Forge API error: {0}")]
+    Api(String),
+
+    #[error("Checks failed: {0}. The release commit was pushed but not tagged.")]
+    Failed(String),
+
+    #[error(
+        "Checks did not finish within {timeout} (pending: {pending}). The release commit was pushed but not tagged."
+    )]
+    TimedOut { timeout: String, pending: String },
+}
+
+/// Errors from `keryx announce`.
+#[derive(Error, Debug)]
+pub enum AnnounceError {
+    #[error("announce.email.{0} is not set in keryx.toml")]
+    MissingSetting(&'static str),
+
+    #[error("curl not found in PATH; it is needed to send email")]
+    CurlNotFound,
+
+    #[error("Failed to send email: {0}")]
+    SendFailed(String),
+
+    #[error("Failed to write {path}: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Errors from updating issue trackers after a release.
+#[derive(Error, Debug)]
+pub enum IntegrationError {
+    #[error("{issue} not found")]
+    IssueNotFound { issue: String },
+
+    #[error("{issue} has no transition to '{state}' (available: {available})")]
+    UnknownState {
+        issue: String,
+        state: String,
+        available: String,
+    },
+
+    #[error("Request failed: {0}")]
+    Request(#[from] reqwest::Error),
+
+    #[error("API error: {0}")]
+    Api(String),
+}
+
+/// Errors from updating packaging manifests after a release.
+#[derive(Error, Debug)]
+pub enum PackagingError {
+    #[error("{file} has no {field} to update")]
+    FieldNotFound { file: String, field: &'static str },
+
+    #[error("Failed to download {url}: {reason}")]
+    DownloadFailed { url: String, reason: String },
+
+    #[error("Could not compute the sha256 of {url}: {reason}")]
+    ChecksumFailed { url: String, reason: String },
+
+    #[error("git {operation} failed: {reason}")]
+    GitFailed { operation: String, reason: String },
+
+    #[error("Failed to access {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to open a pull request on {repo}: {reason}")]
+    PullRequestFailed { repo: String, reason: String },
+}
+
+/// Errors from writing repository bootstrap files.
+#[derive(Error, Debug)]
+pub enum ScaffoldError {
+    #[error("{0} already exists. Use --force to overwrite it.")]
+    AlreadyExists(PathBuf),
+
+    #[error("Failed to write {path}: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Errors from updating the LLM settings in `keryx.toml`.
+#[derive(Error, Debug)]
+pub enum ProviderConfigError {
+    #[error("Failed to read {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Invalid {path}: {message}")]
+    Invalid { path: PathBuf, message: String },
+
+    #[error("Failed to write {path}: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Errors from the repository lock.
+#[derive(Error, Debug)]
+pub enum LockError {
+    #[error(
+        "Another keryx process is working in this repository (keryx {command}, pid {pid}, started {started}). Wait for it to finish, or delete {path} if it is gone."
+    )]
+    Held {
+        path: PathBuf,
+        pid: u32,
+        command: String,
+        started: chrono::DateTime<chrono::Utc>,
+    },
+
+    #[error("Failed to take the repository lock {path}: {source}")]
+    Io {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+}
+
+/// Errors from the operations journal.
+#[derive(Error, Debug)]
+pub enum JournalError {
+    #[error("Failed to create journal directory {path}: {source}")]
+    CreateDirFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write journal {path}: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to read journal {path}: {source}")]
+    ReadFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Malformed journal entry at line {line}: {source}")]
+    ParseFailed {
+        line: usize,
+        #[source]
+        source: serde_json::Error,
+    },
+
+    #[error("Failed to serialize journal entry: {0}")]
+    SerializationFailed(#[source] serde_json::Error),
+}
+
+/// Errors from the `--from-history` section cache.
+#[derive(Error, Debug)]
+pub enum HistoryCacheError {
+    #[error("Failed to create history cache directory {path}: {source}")]
+    CreateDirFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to write history cache {path}: {source}")]
+    WriteFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to clear history cache {path}: {source}")]
+    ClearFailed {
+        path: PathBuf,
+        #[source]
+        source: std::io::Error,
+    },
+
+    #[error("Failed to serialize history cache entry: {0}")]
+    SerializationFailed(#[source] serde_json::Error),
+}
@@ -0,0 +1,339 @@
+//! Resolve an explain target and gather the change behind it.
+
+use git2::{DiffFormat, Oid, Repository};
+use semver::Version;
+
+use crate::commit::prompt::sanitize_diff;
+use crate::error::{ExplainError, GitError};
+use crate::git::tags::get_all_tags;
+use crate::github::PrDiscussion;
+use crate::verification::scanner::extract_keywords;
+
+/// Longest diff kept per commit.
+const MAX_DIFF_LENGTH: usize = 12_000;
+
+/// Most commits gathered for a changelog entry.
+const MAX_ENTRY_COMMITS: usize = 3;
+
+/// Most commits scanned when looking for an entry's commits.
+const MAX_SCAN_COMMITS: usize = 1000;
+
+/// Most candidates listed for an ambiguous entry query.
+const MAX_CANDIDATES: usize = 5;
+
+/// What `keryx explain` was asked about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplainTarget {
+    Commit(Oid),
+    Entry(EntryLocation),
+}
+
+/// A changelog entry and the section it appears in.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EntryLocation {
+    pub description: String,
+    /// Section label as written in the heading, e.g. `1.2.0` or `Unreleased`.
+    pub section: String,
+}
+
+/// One commit's message and diff.
+#[derive(Debug, Clone)]
+pub struct CommitContext {
+    pub hash: String,
+    pub message: String,
+    /// Sanitized unified diff against the first parent.
+    pub diff: String,
+    pub files: Vec<String>,
+    pub truncated: bool,
+}
+
+/// Everything handed to the LLM about one change.
+#[derive(Debug, Clone)]
+pub struct ChangeContext {
+    /// The changelog entry being explained, if the target was an entry.
+    pub entry: Option<String>,
+    pub commits: Vec<CommitContext>,
+    pub discussions: Vec<PrDiscussion>,
+}
+
+/// Resolve `query` to a commit (any revision without whitespace that git
+/// resolves) or, failing that, to an entry in `changelog`.
+pub fn resolve_target(
+    repo: &Repository,
+    changelog: Option<&str>,
+    query: &str,
+) -> Result<ExplainTarget, ExplainError> {
+    let query = query.trim();
+    if !query.contains(char::is_whitespace)
+        && let Ok(commit) = repo
+            .revparse_single(query)
+            .and_then(|object| object.peel_to_commit())
+    {
+        return Ok(ExplainTarget::Commit(commit.id()));
+    }
+
+    match changelog {
+        Some(content) => find_entry(content, query).map(ExplainTarget::Entry),
+        None => Err(ExplainError::NotFound(query.to_string())),
+    }
+}
+
+/// Find the entry matching `query` in changelog `content`.
+///
+/// An exact (case-insensitive) match wins; otherwise the query must be a
+/// substring of exactly one entry.
+pub fn find_entry(content: &str, query: &str) -> Result<EntryLocation, ExplainError> {
+    let needle = query.trim().to_lowercase();
+    let mut section = None;
+    let mut matches: Vec<EntryLocation> = Vec::new();
+
+    for line in content.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            section = Some(section_label(heading));
+            continue;
+        }
+        let Some(label) = &section else {
+            continue;
+        };
+        let Some(text) = line
+            .strip_prefix("- ")
+            .or_else(|| line.strip_prefix("* "))
+            .map(str::trim)
+        else {
+            continue;
+        };
+        let lower = text.to_lowercase();
+        let location = EntryLocation {
+            description: text.to_string(),
+            section: label.clone(),
+        };
+        if lower == needle {
+            return Ok(location);
+        }
+        if lower.contains(&needle) {
+            matches.push(location);
+        }
+    }
+
+    match matches.len() {
+        0 => Err(ExplainError::NotFound(query.to_string())),
+        1 => Ok(matches.remove(0)),
+        _ => Err(ExplainError::AmbiguousEntry {
+            query: query.to_string(),
+            candidates: matches
+                .into_iter()
+                .take(MAX_CANDIDATES)
+                .map(|m| format!("[{}] {}", m.section, m.description))
+                .collect(),
+        }),
+    }
+}
+
+/// Label of a `## ` heading: the bracketed part if any, else the first word.
+fn section_label(heading: &str) -> String {
+    let heading = heading.trim();
+    if let Some(rest) = heading.strip_prefix('[')
+        && let Some((label, _)) = rest.split_once(']')
+    {
+        return label.to_string();
+    }
+    heading
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// The commits of the entry's release that best match its description,
+/// ranked by how many of its keywords their messages mention.
+///
+/// A versioned section covers the range from the previous version's tag to
+/// its own; `Unreleased` (or a version without a tag) ends at HEAD.
+pub fn entry_commits(repo: &Repository, entry: &EntryLocation) -> Result<Vec<Oid>, ExplainError> {
+    let tags = get_all_tags(repo)?;
+    let version = Version::parse(entry.section.trim_start_matches('v')).ok();
+
+    let to = version
+        .as_ref()
+        .and_then(|v| tags.iter().find(|t| t.version.as_ref() == Some(v)))
+        .map(|t| t.oid);
+    let from = tags
+        .iter()
+        .filter(|t| {
+            t.version
+                .as_ref()
+                .is_some_and(|tv| version.as_ref().is_none_or(|v| tv < v) && Some(t.oid) != to)
+        })
+        .max_by(|a, b| a.version.cmp(&b.version))
+        .map(|t| t.oid);
+
+    let mut revwalk = repo.revwalk().map_err(GitError::RevwalkError)?;
+    let pushed = match to {
+        Some(oid) => revwalk.push(oid),
+        None => revwalk.push_head(),
+    };
+    pushed.map_err(GitError::RevwalkError)?;
+    if let Some(oid) = from {
+        revwalk.hide(oid).map_err(GitError::RevwalkError)?;
+    }
+
+    // Messages are compared lowercased; env var keywords keep their case
+    let keywords: Vec<String> = extract_keywords(&entry.description)
+        .iter()
+        .map(|k| k.to_lowercase())
+        .collect();
+    let mut scored: Vec<(usize, Oid)> = Vec::new();
+    for oid in revwalk.take(MAX_SCAN_COMMITS) {
+        let oid = oid.map_err(GitError::RevwalkError)?;
+        let commit = repo.find_commit(oid).map_err(GitError::ParseCommit)?;
+        let message = commit.message().unwrap_or_default().to_lowercase();
+        let score = keywords
+            .iter()
+            .filter(|k| message.contains(k.as_str()))
+            .count();
+        if score > 0 {
+            scored.push((score, oid));
+        }
+    }
+
+    if scored.is_empty() {
+        return Err(ExplainError::NoRelatedCommits(entry.description.clone()));
+    }
+    // Stable sort keeps newest first among equal scores
+    scored.sort_by_key(|s| std::cmp::Reverse(s.0));
+    Ok(scored
+        .into_iter()
+        .take(MAX_ENTRY_COMMITS)
+        .map(|(_, oid)| oid)
+        .collect())
+}
+
+/// Message and diff of commit `oid` against its first parent.
+pub fn commit_context(repo: &Repository, oid: Oid) -> Result<CommitContext, ExplainError> {
+    let commit = repo.find_commit(oid).map_err(GitError::ParseCommit)?;
+    let tree = commit.tree().map_err(ExplainError::DiffFailed)?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree().map_err(ExplainError::DiffFailed)?),
+        Err(_) => None,
+    };
+    let diff = repo
+        .diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)
+        .map_err(ExplainError::DiffFailed)?;
+
+    let files = diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+        .map(|path| path.display().to_string())
+        .collect();
+
+    let mut text = String::new();
+    diff.print(DiffFormat::Patch, |_, _, line| {
+        if matches!(line.origin(), '+' | '-' | ' ') {
+            text.push(line.origin());
+        }
+        text.push_str(&String::from_utf8_lossy(line.content()));
+        true
+    })
+    .map_err(ExplainError::DiffFailed)?;
+
+    let truncated = text.len() > MAX_DIFF_LENGTH;
+    Ok(CommitContext {
+        hash: oid.to_string(),
+        message: commit.message().unwrap_or_default().to_string(),
+        diff: sanitize_diff(&text, MAX_DIFF_LENGTH),
+        files,
+        truncated,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    const CHANGELOG: &str = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Dark mode toggle in settings\n\n## [1.0.0] - 2024-01-01\n\n### Fixed\n\n- Crash when the config file is empty\n- Crash on startup\n";
+
+    fn commit_file(repo: &Repository, path: &str, content: &str, message: &str) -> Oid {
+        let root = repo.workdir().unwrap();
+        std::fs::write(root.join(path), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_find_entry_exact_and_substring() {
+        let entry = find_entry(CHANGELOG, "dark mode").unwrap();
+        assert_eq!(entry.description, "Dark mode toggle in settings");
+        assert_eq!(entry.section, "Unreleased");
+
+        let entry = find_entry(CHANGELOG, "crash on startup").unwrap();
+        assert_eq!(entry.section, "1.0.0");
+    }
+
+    #[test]
+    fn test_find_entry_ambiguous_and_missing() {
+        let err = find_entry(CHANGELOG, "crash").unwrap_err();
+        assert!(
+            matches!(err, ExplainError::AmbiguousEntry { ref candidates, .. } if candidates.len() == 2)
+        );
+        assert!(matches!(
+            find_entry(CHANGELOG, "telemetry"),
+            Err(ExplainError::NotFound(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_commit_and_entry() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let oid = commit_file(&repo, "a.txt", "one\n", "feat: add a");
+
+        let short = &oid.to_string()[..7];
+        assert_eq!(
+            resolve_target(&repo, Some(CHANGELOG), short).unwrap(),
+            ExplainTarget::Commit(oid)
+        );
+        assert!(matches!(
+            resolve_target(&repo, Some(CHANGELOG), "dark mode").unwrap(),
+            ExplainTarget::Entry(_)
+        ));
+        assert!(resolve_target(&repo, None, "dark mode").is_err());
+    }
+
+    #[test]
+    fn test_entry_commits_ranks_by_keywords() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_file(&repo, "a.txt", "one\n", "chore: init");
+        repo.tag_lightweight("v1.0.0", &repo.find_object(base, None).unwrap(), false)
+            .unwrap();
+        commit_file(&repo, "b.txt", "b\n", "fix: typo in readme");
+        let feat = commit_file(&repo, "c.txt", "c\n", "feat: dark mode toggle");
+
+        let entry = find_entry(CHANGELOG, "dark mode").unwrap();
+        assert_eq!(entry_commits(&repo, &entry).unwrap(), vec![feat]);
+    }
+
+    #[test]
+    fn test_commit_context_includes_diff() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit_file(&repo, "a.txt", "one\n", "chore: init");
+        let oid = commit_file(&repo, "a.txt", "one\ntwo\n", "feat: add two");
+
+        let context = commit_context(&repo, oid).unwrap();
+        assert_eq!(context.files, vec!["a.txt".to_string()]);
+        assert!(context.diff.contains("+two"));
+        assert!(context.message.starts_with("feat: add two"));
+        assert!(!context.truncated);
+    }
+}
@@ -0,0 +1,13 @@
+//! `keryx explain`: explain a changelog entry or commit in detail.
+//!
+//! The target is resolved to one or more commits, whose messages and diffs
+//! are combined with the discussion on their PRs and handed to the LLM.
+
+pub mod context;
+pub mod prompt;
+
+pub use context::{
+    ChangeContext, CommitContext, EntryLocation, ExplainTarget, commit_context, entry_commits,
+    find_entry, resolve_target,
+};
+pub use prompt::{build_explain_prompt, build_followup_prompt};
@@ -0,0 +1,160 @@
+//! Prompts for explaining a change.
+
+use crate::llm::prompt::{UNTRUSTED_INPUT_RULE, sanitize_for_prompt, untrusted_block};
+
+use super::context::ChangeContext;
+
+/// Render the gathered change as prompt sections, delimited as untrusted
+/// input.
+fn context_sections(context: &ChangeContext) -> String {
+    let mut sections = String::new();
+
+    if let Some(entry) = &context.entry {
+        sections.push_str(&format!(
+            "## Changelog Entry\n{}\n\n",
+            sanitize_for_prompt(entry)
+        ));
+    }
+
+    for commit in &context.commits {
+        sections.push_str(&format!(
+            "## Commit {}\n{}\n\nFiles: {}\n\n```diff\n{}\n```{}\n\n",
+            &commit.hash[..commit.hash.len().min(12)],
+            sanitize_for_prompt(&commit.message),
+            commit.files.join(", "),
+            commit.diff,
+            if commit.truncated {
+                "\n(diff truncated)"
+            } else {
+                ""
+            }
+        ));
+    }
+
+    for pr in &context.discussions {
+        sections.push_str(&format!(
+            "## Pull Request #{}: {}\n",
+            pr.number,
+            sanitize_for_prompt(&pr.title)
+        ));
+        if let Some(body) = &pr.body {
+            sections.push_str(&format!("{}\n", sanitize_for_prompt(body)));
+        }
+        for comment in &pr.comments {
+            sections.push_str(&format!(
+                "\n@{}: {}\n",
+                comment.author,
+                sanitize_for_prompt(&comment.body)
+            ));
+        }
+        sections.push('\n');
+    }
+
+    format!(
+        "{}\n\n{}\n\n",
+        UNTRUSTED_INPUT_RULE,
+        untrusted_block(sections.trim_end())
+    )
+}
+
+/// Build the prompt asking the LLM to explain the change and its user impact.
+pub fn build_explain_prompt(context: &ChangeContext) -> String {
+    let subject = if context.entry.is_some() {
+        "the changelog entry below, using the commits and pull request discussion behind it"
+    } else {
+        "the commit below, using its diff and pull request discussion"
+    };
+
+    format!(
+        r#"You are helping a maintainer review release notes. Explain {subject}.
+
+{sections}## Instructions
+1. Explain what changed, in plain language
+2. Explain why it changed, if the messages or discussion say so
+3. Describe the impact on users: new behavior, changed defaults, anything they must do
+4. Point out risks or follow-ups raised in the discussion
+5. If the changelog entry overstates or misdescribes the change, say so
+6. Say when the evidence is thin rather than guessing
+
+Respond in concise Markdown prose with short headings. Do not respond with JSON."#,
+        sections = context_sections(context)
+    )
+}
+
+/// Build the prompt for a follow-up question, repeating the context and the
+/// conversation so far (providers keep no state between calls).
+pub fn build_followup_prompt(
+    context: &ChangeContext,
+    history: &[(String, String)],
+    question: &str,
+) -> String {
+    let mut conversation = String::new();
+    for (asked, answer) in history {
+        conversation.push_str(&format!(
+            "Q: {}\nA: {}\n\n",
+            sanitize_for_prompt(asked),
+            answer
+        ));
+    }
+
+    format!(
+        r#"You are helping a maintainer understand a change.
+
+{sections}## Conversation So Far
+{conversation}## Question
+{question}
+
+Answer the question using the change above. Say when the evidence does not answer it. Respond in concise Markdown prose, not JSON."#,
+        sections = context_sections(context),
+        question = sanitize_for_prompt(question)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::explain::context::CommitContext;
+    use crate::github::{PrComment, PrDiscussion};
+
+    fn context() -> ChangeContext {
+        ChangeContext {
+            entry: Some("Dark mode toggle in settings".to_string()),
+            commits: vec![CommitContext {
+                hash: "0123456789abcdef0123456789abcdef01234567".to_string(),
+                message: "feat: dark mode (#12)".to_string(),
+                diff: "+theme = dark".to_string(),
+                files: vec!["src/theme.rs".to_string()],
+                truncated: true,
+            }],
+            discussions: vec![PrDiscussion {
+                number: 12,
+                title: "Add dark mode".to_string(),
+                body: Some("Closes #3".to_string()),
+                comments: vec![PrComment {
+                    author: "bob".to_string(),
+                    body: "Default stays light".to_string(),
+                }],
+            }],
+        }
+    }
+
+    #[test]
+    fn test_explain_prompt_includes_context() {
+        let prompt = build_explain_prompt(&context());
+        assert!(prompt.contains("## Changelog Entry\nDark mode toggle in settings"));
+        assert!(prompt.contains("## Commit 0123456789ab"));
+        assert!(prompt.contains("+theme = dark"));
+        assert!(prompt.contains("(diff truncated)"));
+        assert!(prompt.contains("## Pull Request #12: Add dark mode"));
+        assert!(prompt.contains("@bob: Default stays light"));
+        assert!(prompt.contains("Do not respond with JSON"));
+    }
+
+    #[test]
+    fn test_followup_prompt_includes_history() {
+        let history = vec![("Is it on by default?".to_string(), "No.".to_string())];
+        let prompt = build_followup_prompt(&context(), &history, "Which file?");
+        assert!(prompt.contains("Q: Is it on by default?\nA: No."));
+        assert!(prompt.contains("## Question\nWhich file?"));
+    }
+}
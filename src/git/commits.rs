@@ -9,9 +9,11 @@ use tracing::{debug, warn};
 
 use crate::error::GitError;
 
+use super::footers::{Footers, footer_block};
+
 /// Regex for parsing conventional commit messages.
 /// Compiled once at first use using LazyLock.
-static COMMIT_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+pub(crate) static COMMIT_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
     regex_lite::Regex::new(r"^(\w+)(?:\(([^)]+)\))?(!)?\s*:\s*")
         .expect("Invalid commit regex pattern - this is a bug")
 });
@@ -61,6 +63,10 @@ pub struct ParsedCommit {
     pub scope: Option<String>,
     pub breaking: bool,
     pub timestamp: DateTime<Utc>,
+    /// Footers (`BREAKING CHANGE`, `Refs`, `Co-authored-by`, ...) parsed from
+    /// the end of `message`.
+    #[serde(default, skip_serializing_if = "Footers::is_empty")]
+    pub footers: Footers,
 }
 
 impl ParsedCommit {
@@ -112,6 +118,7 @@ impl ParsedCommit {
         };
 
         let (commit_type, scope, breaking) = parse_commit_message(&message);
+        let footers = Footers::parse(&message);
 
         Ok(Self {
             hash,
@@ -120,18 +127,39 @@ impl ParsedCommit {
             scope,
             breaking,
             timestamp,
+            footers,
         })
     }
+
+    /// First line of the commit message.
+    pub fn subject(&self) -> &str {
+        self.message.lines().next().unwrap_or("")
+    }
+
+    /// Commit message body after the subject line and before the footers,
+    /// with surrounding blank lines trimmed. Borrowed from `message`.
+    pub fn body(&self) -> &str {
+        let end = footer_block(&self.message).map_or(self.message.len(), |(start, _)| start);
+        match self.message[..end].split_once('\n') {
+            Some((_, rest)) => rest.trim_matches(|c| c == '\n' || c == '\r'),
+            None => "",
+        }
+    }
 }
 
 /// Parse a conventional commit message.
 /// Returns (commit_type, scope, breaking).
+///
+/// Only the subject line is matched against the conventional commit pattern.
+/// The body is scanned for a `BREAKING CHANGE` footer only when the subject
+/// does not already mark the commit as breaking, so long bodies cost nothing
+/// for `type!:` commits.
 pub fn parse_commit_message(message: &str) -> (Option<CommitType>, Option<String>, bool) {
     let first_line = message.lines().next().unwrap_or("");
 
-    // Check for BREAKING CHANGE in footer
+    // Check for BREAKING CHANGE in footer (deferred until needed)
     let breaking_in_footer =
-        message.contains("BREAKING CHANGE:") || message.contains("BREAKING-CHANGE:");
+        || message.contains("BREAKING CHANGE:") || message.contains("BREAKING-CHANGE:");
 
     // Pattern: type(scope)!: description or type!: description or type(scope): description or type: description
     if let Some(caps) = COMMIT_REGEX.captures(first_line) {
@@ -149,12 +177,72 @@ pub fn parse_commit_message(message: &str) -> (Option<CommitType>, Option<String
                 None
             }
         };
-        let breaking = breaking_mark || breaking_in_footer;
+        let breaking = breaking_mark || breaking_in_footer();
 
         return (commit_type, scope, breaking);
     }
 
-    (None, None, breaking_in_footer)
+    (None, None, breaking_in_footer())
+}
+
+/// Streaming iterator over the commits in a range, newest first.
+///
+/// Each commit is looked up and parsed only when the iterator is advanced,
+/// so callers that count, filter, or stop early never materialize the whole
+/// range. Created by [`walk_commits`].
+pub struct CommitWalk<'repo> {
+    repo: &'repo Repository,
+    revwalk: git2::Revwalk<'repo>,
+    strict: bool,
+}
+
+impl Iterator for CommitWalk<'_> {
+    type Item = Result<ParsedCommit, GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let oid = match self.revwalk.next()? {
+            Ok(oid) => oid,
+            Err(e) => return Some(Err(GitError::RevwalkError(e))),
+        };
+        Some(
+            self.repo
+                .find_commit(oid)
+                .map_err(GitError::ParseCommit)
+                .and_then(|commit| ParsedCommit::from_git2_commit(&commit, self.strict)),
+        )
+    }
+}
+
+impl CommitWalk<'_> {
+    /// Also leave out commits reachable from any of `oids`.
+    pub fn hide(mut self, oids: &[git2::Oid]) -> Result<Self, GitError> {
+        for oid in oids {
+            self.revwalk.hide(*oid).map_err(GitError::RevwalkError)?;
+        }
+        Ok(self)
+    }
+}
+
+/// Walk commits reachable from `to_oid` but not from `from_oid`.
+///
+/// If `strict` is true, yields an error for commits with invalid timestamps.
+/// Otherwise, falls back to current time with a warning.
+pub fn walk_commits(
+    repo: &Repository,
+    from_oid: git2::Oid,
+    to_oid: git2::Oid,
+    strict: bool,
+) -> Result<CommitWalk<'_>, GitError> {
+    let mut revwalk = repo.revwalk().map_err(GitError::RevwalkError)?;
+
+    revwalk.push(to_oid).map_err(GitError::RevwalkError)?;
+    revwalk.hide(from_oid).map_err(GitError::RevwalkError)?;
+
+    Ok(CommitWalk {
+        repo,
+        revwalk,
+        strict,
+    })
 }
 
 /// Fetch commits from a repository in a given range.
@@ -167,21 +255,41 @@ pub fn fetch_commits(
     to_oid: git2::Oid,
     strict: bool,
 ) -> Result<Vec<ParsedCommit>, GitError> {
+    walk_commits(repo, from_oid, to_oid, strict)?.collect()
+}
+
+/// Subject line and commit time of a commit, without conventional parsing.
+#[derive(Debug, Clone)]
+pub struct CommitSubject {
+    pub subject: String,
+    pub timestamp: Option<DateTime<Utc>>,
+}
+
+/// Fetch only subjects and times for a range.
+///
+/// Used where commits locate a range but are not themselves release input
+/// (PR-only generation), so messages are neither copied nor parsed.
+pub fn fetch_commit_subjects(
+    repo: &Repository,
+    from_oid: git2::Oid,
+    to_oid: git2::Oid,
+) -> Result<Vec<CommitSubject>, GitError> {
     let mut revwalk = repo.revwalk().map_err(GitError::RevwalkError)?;
 
     revwalk.push(to_oid).map_err(GitError::RevwalkError)?;
     revwalk.hide(from_oid).map_err(GitError::RevwalkError)?;
 
-    let mut commits = Vec::new();
-
+    let mut subjects = Vec::new();
     for oid_result in revwalk {
         let oid = oid_result.map_err(GitError::RevwalkError)?;
         let commit = repo.find_commit(oid).map_err(GitError::ParseCommit)?;
-        let parsed = ParsedCommit::from_git2_commit(&commit, strict)?;
-        commits.push(parsed);
+        subjects.push(CommitSubject {
+            subject: commit.summary().unwrap_or_default().to_string(),
+            timestamp: Utc.timestamp_opt(commit.time().seconds(), 0).single(),
+        });
     }
 
-    Ok(commits)
+    Ok(subjects)
 }
 
 #[cfg(test)]
@@ -235,4 +343,48 @@ mod tests {
         assert_eq!(scope, None);
         assert!(!breaking);
     }
+
+    #[test]
+    fn test_subject_and_body_borrow_message() {
+        let commit = ParsedCommit {
+            hash: "abc".to_string(),
+            message: "feat: add x\n\nLonger explanation\nover two lines\n".to_string(),
+            commit_type: Some(CommitType::Feat),
+            scope: None,
+            breaking: false,
+            timestamp: Utc::now(),
+            footers: Footers::default(),
+        };
+        assert_eq!(commit.subject(), "feat: add x");
+        assert_eq!(commit.body(), "Longer explanation\nover two lines");
+
+        let subject_only = ParsedCommit {
+            message: "fix: y".to_string(),
+            ..commit
+        };
+        assert_eq!(subject_only.subject(), "fix: y");
+        assert_eq!(subject_only.body(), "");
+    }
+
+    #[test]
+    fn test_body_excludes_footers() {
+        let message = "feat: add x\n\nLonger explanation\n\nRefs: #4\nCo-authored-by: Ann <ann@example.com>\n";
+        let commit = ParsedCommit {
+            hash: "abc".to_string(),
+            message: message.to_string(),
+            commit_type: Some(CommitType::Feat),
+            scope: None,
+            breaking: false,
+            timestamp: Utc::now(),
+            footers: Footers::parse(message),
+        };
+        assert_eq!(commit.body(), "Longer explanation");
+        assert_eq!(commit.footers.refs(), vec!["#4"]);
+
+        let footers_only = ParsedCommit {
+            message: "fix: y\n\nBREAKING CHANGE: z".to_string(),
+            ..commit
+        };
+        assert_eq!(footers_only.body(), "");
+    }
 }
@@ -0,0 +1,218 @@
+//! Cherry-pick deduplication.
+//!
+//! A change cherry-picked onto another branch and merged back shows up in
+//! the range under two hashes. Commits are compared by patch-id (a hash of
+//! the diff that ignores line numbers and whitespace), and only the oldest
+//! copy of each change is kept so the LLM sees one logical change.
+
+use std::collections::HashMap;
+
+use git2::{Diff, Oid, Repository};
+use tracing::debug;
+
+use super::ParsedCommit;
+use super::reverts::{cancel_revert_pairs, print_cancelled_pairs};
+
+/// A commit dropped because an older commit in the range has the same patch.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DuplicateChange {
+    /// Hash of the commit that was kept.
+    pub kept: String,
+    /// Hash of the duplicate that was dropped.
+    pub dropped: String,
+    /// Subject of the duplicate.
+    pub subject: String,
+}
+
+/// Patch-id of a commit's change against its first parent, or of the inverse
+/// change when `inverse` is set. `None` for merges, root commits, empty
+/// commits, and errors.
+pub(crate) fn patch_id(repo: &Repository, hash: &str, inverse: bool) -> Option<Oid> {
+    let compute = || -> Result<Option<Oid>, git2::Error> {
+        let commit = repo.find_commit(Oid::from_str(hash)?)?;
+        if commit.parent_count() != 1 {
+            return Ok(None);
+        }
+        let tree = commit.tree()?;
+        let parent_tree = commit.parent(0)?.tree()?;
+        let diff: Diff = if inverse {
+            repo.diff_tree_to_tree(Some(&tree), Some(&parent_tree), None)?
+        } else {
+            repo.diff_tree_to_tree(Some(&parent_tree), Some(&tree), None)?
+        };
+        if diff.deltas().next().is_none() {
+            return Ok(None);
+        }
+        diff.patchid(None).map(Some)
+    };
+    compute().unwrap_or_else(|e| {
+        debug!("Could not compute patch-id for {}: {}", hash, e);
+        None
+    })
+}
+
+/// Drop later copies of the same patch from `commits`.
+///
+/// Commits are expected newest first, as returned by
+/// [`fetch_commits`](super::fetch_commits); the oldest copy is kept and the
+/// order of the remaining commits is preserved.
+pub fn dedupe_cherry_picks(
+    repo: &Repository,
+    commits: Vec<ParsedCommit>,
+) -> (Vec<ParsedCommit>, Vec<DuplicateChange>) {
+    let mut first_seen: HashMap<Oid, usize> = HashMap::new();
+    let mut duplicate = vec![false; commits.len()];
+    let mut duplicates = Vec::new();
+
+    for (i, commit) in commits.iter().enumerate().rev() {
+        let Some(id) = patch_id(repo, &commit.hash, false) else {
+            continue;
+        };
+        match first_seen.get(&id) {
+            Some(&kept) => {
+                duplicate[i] = true;
+                duplicates.push(DuplicateChange {
+                    kept: commits[kept].hash.clone(),
+                    dropped: commit.hash.clone(),
+                    subject: commit.subject().to_string(),
+                });
+            }
+            None => {
+                first_seen.insert(id, i);
+            }
+        }
+    }
+
+    let remaining = commits
+        .into_iter()
+        .zip(duplicate)
+        .filter(|(_, dup)| !dup)
+        .map(|(c, _)| c)
+        .collect();
+    (remaining, duplicates)
+}
+
+/// Print the dropped duplicates (verbose mode).
+pub fn print_duplicates(duplicates: &[DuplicateChange]) {
+    if duplicates.is_empty() {
+        return;
+    }
+    println!(
+        "Skipping {} cherry-picked duplicate commit(s):",
+        duplicates.len()
+    );
+    for dup in duplicates {
+        println!(
+            "  {} {} (same change as {})",
+            &dup.dropped[..dup.dropped.len().min(7)],
+            dup.subject,
+            &dup.kept[..dup.kept.len().min(7)]
+        );
+    }
+}
+
+/// Reduce a range to its net changes before prompt construction: drop
+/// cherry-picked duplicates, then commits reverted within the range. With
+/// `verbose`, lists what was dropped.
+pub fn net_commits(
+    repo: &Repository,
+    commits: Vec<ParsedCommit>,
+    verbose: bool,
+) -> Vec<ParsedCommit> {
+    let (commits, duplicates) = dedupe_cherry_picks(repo, commits);
+    let (commits, cancelled) = cancel_revert_pairs(repo, commits);
+    if verbose {
+        print_duplicates(&duplicates);
+        print_cancelled_pairs(&cancelled);
+    }
+    commits
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    use crate::git::fetch_commits;
+
+    /// Commit a tree holding exactly `files` on top of `parents`, without
+    /// moving HEAD.
+    fn commit_tree(
+        repo: &Repository,
+        parents: &[Oid],
+        files: &[(&str, &str)],
+        message: &str,
+    ) -> Oid {
+        let mut builder = repo.treebuilder(None).unwrap();
+        for (path, content) in files {
+            let blob = repo.blob(content.as_bytes()).unwrap();
+            builder.insert(path, blob, 0o100644).unwrap();
+        }
+        let tree = repo.find_tree(builder.write().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parents: Vec<git2::Commit> = parents
+            .iter()
+            .map(|p| repo.find_commit(*p).unwrap())
+            .collect();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(None, &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_dedupes_cherry_pick_merged_back() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_tree(&repo, &[], &[("a.txt", "one\n")], "chore: init");
+        let fix = commit_tree(&repo, &[base], &[("a.txt", "one\nfixed\n")], "fix: hotfix");
+        let feat = commit_tree(
+            &repo,
+            &[fix],
+            &[("a.txt", "one\nfixed\n"), ("c.txt", "c\n")],
+            "feat: add c",
+        );
+        // The same fix cherry-picked onto a hotfix branch, then merged back
+        let picked = commit_tree(
+            &repo,
+            &[base],
+            &[("a.txt", "one\nfixed\n")],
+            &format!("fix: hotfix\n\n(cherry picked from commit {})", fix),
+        );
+        let merge = commit_tree(
+            &repo,
+            &[feat, picked],
+            &[("a.txt", "one\nfixed\n"), ("c.txt", "c\n")],
+            "Merge branch 'hotfix'",
+        );
+
+        let commits = fetch_commits(&repo, base, merge, false).unwrap();
+        assert_eq!(commits.len(), 4);
+        let (remaining, duplicates) = dedupe_cherry_picks(&repo, commits);
+
+        assert_eq!(remaining.len(), 3);
+        assert_eq!(duplicates.len(), 1);
+        let pair = [duplicates[0].kept.as_str(), duplicates[0].dropped.as_str()];
+        assert!(pair.contains(&fix.to_string().as_str()));
+        assert!(pair.contains(&picked.to_string().as_str()));
+        assert!(remaining.iter().all(|c| c.hash != duplicates[0].dropped));
+    }
+
+    #[test]
+    fn test_net_commits_keeps_distinct_changes() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_tree(&repo, &[], &[("a.txt", "one\n")], "chore: init");
+        let feat = commit_tree(&repo, &[base], &[("a.txt", "one\ntwo\n")], "feat: add two");
+        let head = commit_tree(
+            &repo,
+            &[feat],
+            &[("a.txt", "one\ntwo\n"), ("b.txt", "b\n")],
+            "fix: add b",
+        );
+
+        let commits = fetch_commits(&repo, base, head, false).unwrap();
+        let remaining = net_commits(&repo, commits, false);
+        assert_eq!(remaining.len(), 2);
+    }
+}
@@ -0,0 +1,264 @@
+//! Conventional commit footers (git trailers).
+//!
+//! The footer block is the last paragraph of a commit message when every
+//! line in it is a `Token: value` or `Token #value` footer, or an indented
+//! continuation of the previous one. Tokens use `-` instead of spaces, except
+//! for `BREAKING CHANGE`.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::sync::LazyLock;
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+/// Regex for a single footer line: `Token: value` or `Token #value`.
+/// The `#` of the second form is kept as part of the value.
+static FOOTER_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(
+        r"^(BREAKING CHANGE|BREAKING-CHANGE|[A-Za-z][A-Za-z0-9-]*)(?:: | (#))(.*)$",
+    )
+    .expect("Invalid footer regex pattern - this is a bug")
+});
+
+/// Footer token, with well-known tokens recognized case-insensitively.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum FooterKey {
+    /// `BREAKING CHANGE` / `BREAKING-CHANGE`.
+    BreakingChange,
+    /// `Refs`: related issues or commits.
+    Refs,
+    /// `Co-authored-by`.
+    CoAuthoredBy,
+    /// `Changelog`: changelog category written by `keryx commit`.
+    Changelog,
+    /// `Changelog-Description`: user-facing entry written by `keryx commit`.
+    ChangelogDescription,
+    /// `Release-Note`: the commit's changelog entry, written by its author.
+    ReleaseNote,
+    /// Any other token, as written.
+    Other(String),
+}
+
+impl FooterKey {
+    pub fn as_str(&self) -> &str {
+        match self {
+            FooterKey::BreakingChange => "BREAKING CHANGE",
+            FooterKey::Refs => "Refs",
+            FooterKey::CoAuthoredBy => "Co-authored-by",
+            FooterKey::Changelog => "Changelog",
+            FooterKey::ChangelogDescription => "Changelog-Description",
+            FooterKey::ReleaseNote => "Release-Note",
+            FooterKey::Other(token) => token,
+        }
+    }
+}
+
+impl From<&str> for FooterKey {
+    fn from(token: &str) -> Self {
+        if token == "BREAKING CHANGE" || token == "BREAKING-CHANGE" {
+            return FooterKey::BreakingChange;
+        }
+        match token.to_lowercase().as_str() {
+            "refs" => FooterKey::Refs,
+            "co-authored-by" => FooterKey::CoAuthoredBy,
+            "changelog" => FooterKey::Changelog,
+            "changelog-description" => FooterKey::ChangelogDescription,
+            "release-note" | "release-notes" => FooterKey::ReleaseNote,
+            _ => FooterKey::Other(token.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for FooterKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Serialized as the token so footers form a plain JSON object in prompts.
+impl Serialize for FooterKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for FooterKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let token = String::deserialize(deserializer)?;
+        Ok(FooterKey::from(token.as_str()))
+    }
+}
+
+/// Footers of one commit, keyed by token. Repeated tokens keep every value
+/// in message order.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct Footers(BTreeMap<FooterKey, Vec<String>>);
+
+impl Footers {
+    /// Parse the footers of a full commit message.
+    pub fn parse(message: &str) -> Self {
+        footer_block(message)
+            .map(|(_, block)| parse_block(block))
+            .unwrap_or_default()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// All values for `key`, in message order.
+    pub fn get(&self, key: &FooterKey) -> &[String] {
+        self.0.get(key).map(Vec::as_slice).unwrap_or_default()
+    }
+
+    /// First value for `key`.
+    pub fn first(&self, key: &FooterKey) -> Option<&str> {
+        self.get(key).first().map(String::as_str)
+    }
+
+    /// Description from the `BREAKING CHANGE` footer.
+    pub fn breaking_change(&self) -> Option<&str> {
+        self.first(&FooterKey::BreakingChange)
+    }
+
+    /// References from `Refs` footers, split on commas and whitespace.
+    pub fn refs(&self) -> Vec<&str> {
+        self.get(&FooterKey::Refs)
+            .iter()
+            .flat_map(|value| value.split([',', ' ']))
+            .filter(|r| !r.is_empty())
+            .collect()
+    }
+
+    /// `Co-authored-by` values (`Name <email>`).
+    pub fn co_authors(&self) -> &[String] {
+        self.get(&FooterKey::CoAuthoredBy)
+    }
+
+    /// Iterate over `(key, value)` pairs.
+    pub fn iter(&self) -> impl Iterator<Item = (&FooterKey, &str)> {
+        self.0
+            .iter()
+            .flat_map(|(key, values)| values.iter().map(move |v| (key, v.as_str())))
+    }
+
+    /// Copy with every value passed through `f` (e.g. prompt sanitization).
+    pub fn map_values(&self, f: impl Fn(&str) -> String) -> Self {
+        Footers(
+            self.0
+                .iter()
+                .map(|(key, values)| (key.clone(), values.iter().map(|v| f(v)).collect()))
+                .collect(),
+        )
+    }
+}
+
+/// Byte offset where the footer block starts and the block itself, if the
+/// message's last paragraph (after the subject) consists only of footers.
+pub(crate) fn footer_block(message: &str) -> Option<(usize, &str)> {
+    let trimmed = message.trim_end();
+    let (_, after_subject) = trimmed.split_once('\n')?;
+    let body_start = trimmed.len() - after_subject.len();
+
+    let block_start = match after_subject.rfind("\n\n") {
+        Some(pos) => body_start + pos + 2,
+        // Footers directly below the subject still need a separating blank line
+        None if after_subject.starts_with('\n') || after_subject.starts_with("\r\n") => body_start,
+        None => return None,
+    };
+    let block = trimmed[block_start..].trim_start_matches(['\n', '\r']);
+    let block_start = trimmed.len() - block.len();
+
+    let mut lines = block.lines();
+    let first = lines.next()?;
+    if !FOOTER_REGEX.is_match(first.trim_end()) {
+        return None;
+    }
+    let all_footers =
+        lines.all(|line| line.starts_with([' ', '\t']) || FOOTER_REGEX.is_match(line.trim_end()));
+    all_footers.then_some((block_start, block))
+}
+
+fn parse_block(block: &str) -> Footers {
+    let mut footers: BTreeMap<FooterKey, Vec<String>> = BTreeMap::new();
+    let mut current: Option<(FooterKey, String)> = None;
+
+    for line in block.lines() {
+        let line = line.trim_end();
+        if line.starts_with([' ', '\t']) {
+            if let Some((_, value)) = current.as_mut() {
+                value.push('\n');
+                value.push_str(line.trim_start());
+            }
+            continue;
+        }
+        if let Some(caps) = FOOTER_REGEX.captures(line) {
+            if let Some((key, value)) = current.take() {
+                footers.entry(key).or_default().push(value);
+            }
+            let key = FooterKey::from(&caps[1]);
+            let hash = if caps.get(2).is_some() { "#" } else { "" };
+            current = Some((key, format!("{}{}", hash, caps[3].trim())));
+        }
+    }
+    if let Some((key, value)) = current {
+        footers.entry(key).or_default().push(value);
+    }
+    Footers(footers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_known_and_other_footers() {
+        let footers = Footers::parse(
+            "feat: add x\n\nExplain x.\n\nRefs: #12, #13\nco-authored-by: Ann <ann@example.com>\nReviewed-by: Bo\nChangelog: added",
+        );
+        assert_eq!(footers.refs(), vec!["#12", "#13"]);
+        assert_eq!(footers.co_authors(), ["Ann <ann@example.com>"]);
+        assert_eq!(footers.first(&FooterKey::Changelog), Some("added"));
+        assert_eq!(
+            footers.first(&FooterKey::Other("Reviewed-by".to_string())),
+            Some("Bo")
+        );
+        assert_eq!(footers.iter().count(), 4);
+    }
+
+    #[test]
+    fn test_breaking_change_with_continuation() {
+        let footers = Footers::parse(
+            "feat!: drop v1\n\nBREAKING CHANGE: the v1 API is gone\n  use v2 instead\nRefs #7",
+        );
+        assert_eq!(
+            footers.breaking_change(),
+            Some("the v1 API is gone\nuse v2 instead")
+        );
+        assert_eq!(footers.refs(), vec!["#7"]);
+    }
+
+    #[test]
+    fn test_repeated_tokens_keep_order() {
+        let footers = Footers::parse("fix: y\n\nCo-authored-by: A <a@x>\nCo-authored-by: B <b@x>");
+        assert_eq!(footers.co_authors(), ["A <a@x>", "B <b@x>"]);
+    }
+
+    #[test]
+    fn test_prose_paragraph_is_not_a_footer_block() {
+        assert!(Footers::parse("fix: y\n\nNote: this is prose\nthat wraps").is_empty());
+        assert!(Footers::parse("fix: y").is_empty());
+        assert!(Footers::parse("Refs: #1").is_empty());
+        assert!(Footers::parse("fix: y\nRefs: #1").is_empty());
+    }
+
+    #[test]
+    fn test_footers_serialize_as_object() {
+        let footers = Footers::parse("fix: y\n\nBREAKING-CHANGE: z\nRefs: #1");
+        let json = serde_json::to_string(&footers).unwrap();
+        assert_eq!(json, r##"{"BREAKING CHANGE":["z"],"Refs":["#1"]}"##);
+        let back: Footers = serde_json::from_str(&json).unwrap();
+        assert_eq!(back, footers);
+    }
+}
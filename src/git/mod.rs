@@ -1,9 +1,22 @@
 //! Git operations using git2-rs.
 
 pub mod commits;
+pub mod dedupe;
+pub mod footers;
 pub mod range;
+pub mod reverts;
 pub mod tags;
+pub mod tree;
+pub mod upstream;
 
-pub use commits::{CommitType, ParsedCommit, fetch_commits, parse_commit_message};
-pub use range::{find_root_commit, resolve_range};
-pub use tags::{get_latest_tag, get_version_from_tag};
+pub use commits::{
+    CommitSubject, CommitType, CommitWalk, ParsedCommit, fetch_commit_subjects, fetch_commits,
+    parse_commit_message, walk_commits,
+};
+pub use dedupe::{DuplicateChange, dedupe_cherry_picks, net_commits};
+pub use footers::{FooterKey, Footers};
+pub use range::{UntaggedRelease, find_root_commit, find_untagged_release, resolve_range};
+pub use reverts::{RevertPair, cancel_revert_pairs};
+pub use tags::{TagRange, get_latest_tag, get_version_from_tag};
+pub use tree::{head_tree_files, read_head_file, read_repo_file, read_tree_file};
+pub use upstream::{UpstreamExclusion, resolve_upstreams, upstream_oids};
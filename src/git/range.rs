@@ -1,11 +1,20 @@
 //! Commit range resolution.
 
+use std::path::Path;
+
 use git2::{Oid, Repository};
+use semver::Version;
 use tracing::{debug, warn};
 
+use crate::changelog::parser::parse_changelog_content;
 use crate::error::GitError;
 
-use super::tags::get_latest_tag;
+use super::tags::{TagInfo, get_all_tags, get_latest_tag};
+use super::tree::read_tree_file;
+
+/// Most first-parent commits walked back from `HEAD` looking for the commit
+/// that added a changelog section.
+const SECTION_SEARCH_LIMIT: usize = 1000;
 
 /// Resolved commit range with start and end OIDs.
 #[derive(Debug, Clone)]
@@ -51,8 +60,80 @@ pub fn resolve_range(
     })
 }
 
+/// A changelog whose newest section has no tag: the release was written up
+/// but never tagged, so a range from the latest tag would include commits
+/// already described in the changelog.
+#[derive(Debug, Clone)]
+pub struct UntaggedRelease {
+    /// Newest version in the changelog.
+    pub changelog_version: Version,
+    /// Highest semver tag, older than `changelog_version`.
+    pub latest_tag: Option<TagInfo>,
+    /// First-parent commit that added the section, if found.
+    pub section_commit: Option<Oid>,
+}
+
+/// Cross-check the changelog's newest version against the tags.
+///
+/// Returns `None` when a tag exists for `changelog_version` or a newer
+/// version is tagged. Otherwise the first-parent history of `HEAD` is
+/// searched for the commit that added the version to `changelog_path`
+/// (relative to the repository root).
+pub fn find_untagged_release(
+    repo: &Repository,
+    changelog_path: &Path,
+    changelog_version: &Version,
+) -> Result<Option<UntaggedRelease>, GitError> {
+    let tags = get_all_tags(repo)?;
+    if tags
+        .iter()
+        .filter_map(|t| t.version.as_ref())
+        .any(|v| v >= changelog_version)
+    {
+        return Ok(None);
+    }
+    let latest_tag = get_latest_tag(repo)?;
+    let section_commit = find_section_commit(repo, changelog_path, changelog_version);
+    Ok(Some(UntaggedRelease {
+        changelog_version: changelog_version.clone(),
+        latest_tag,
+        section_commit,
+    }))
+}
+
+/// The oldest commit in the unbroken first-parent run from `HEAD` whose
+/// changelog contains `version`.
+fn find_section_commit(repo: &Repository, changelog_path: &Path, version: &Version) -> Option<Oid> {
+    let has_section = |commit: &git2::Commit| {
+        commit
+            .tree()
+            .ok()
+            .and_then(|tree| read_tree_file(repo, &tree, changelog_path))
+            .and_then(|content| parse_changelog_content(content).ok())
+            .is_some_and(|parsed| parsed.has_version(version))
+    };
+
+    let mut commit = repo.head().ok()?.peel_to_commit().ok()?;
+    if !has_section(&commit) {
+        debug!("{} is not in the changelog at HEAD", version);
+        return None;
+    }
+    for _ in 0..SECTION_SEARCH_LIMIT {
+        match commit.parent(0) {
+            Ok(parent) if has_section(&parent) => commit = parent,
+            // Added here, or here is the root commit
+            _ => return Some(commit.id()),
+        }
+    }
+    debug!(
+        "Section {} is older than {} commits; not basing the range on it",
+        version, SECTION_SEARCH_LIMIT
+    );
+    None
+}
+
 /// Resolve a reference (tag, branch, commit hash) to an OID.
-fn resolve_reference(repo: &Repository, reference: &str) -> Result<Oid, GitError> {
+pub(crate) fn resolve_reference(repo: &Repository, reference: &str) -> Result<Oid, GitError> {
     // Try as a direct OID first
     if let Ok(oid) = Oid::from_str(reference)
         && repo.find_commit(oid).is_ok()
@@ -131,3 +212,94 @@ pub fn find_root_commit(repo: &Repository, strict: bool) -> Result<Oid, GitError
 
     Ok(root_oid)
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    use super::*;
+
+    fn commit_changelog(repo: &Repository, dir: &Path, versions: &[&str]) -> Oid {
+        let mut content = String::from("# Changelog\n\n## [Unreleased]\n");
+        for version in versions {
+            content.push_str(&format!(
+                "\n## [{}] - 2024-01-01\n\n### Added\n\n- Thing\n",
+                version
+            ));
+        }
+        std::fs::write(dir.join("CHANGELOG.md"), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new("CHANGELOG.md")).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test User", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(
+            Some("HEAD"),
+            &sig,
+            &sig,
+            "update changelog",
+            &tree,
+            &parents,
+        )
+        .unwrap()
+    }
+
+    fn tag(repo: &Repository, name: &str, oid: Oid) {
+        let object = repo.find_object(oid, None).unwrap();
+        repo.tag_lightweight(name, &object, false).unwrap();
+    }
+
+    #[test]
+    fn test_untagged_changelog_section_is_found() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let tagged = commit_changelog(&repo, dir.path(), &["1.4.0"]);
+        tag(&repo, "v1.4.0", tagged);
+        let added = commit_changelog(&repo, dir.path(), &["1.6.0", "1.4.0"]);
+        commit_changelog(&repo, dir.path(), &["1.6.0", "1.4.0", "1.3.9"]);
+
+        let version = Version::new(1, 6, 0);
+        let untagged = find_untagged_release(&repo, Path::new("CHANGELOG.md"), &version)
+            .unwrap()
+            .expect("1.6.0 has no tag");
+        assert_eq!(untagged.changelog_version, version);
+        assert_eq!(untagged.latest_tag.unwrap().name, "v1.4.0");
+        assert_eq!(untagged.section_commit, Some(added));
+    }
+
+    #[test]
+    fn test_tagged_changelog_section_is_consistent() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let oid = commit_changelog(&repo, dir.path(), &["1.4.0"]);
+        tag(&repo, "v1.4.0", oid);
+        let newer = commit_changelog(&repo, dir.path(), &["1.4.0"]);
+        tag(&repo, "v1.5.0", newer);
+
+        let path = Path::new("CHANGELOG.md");
+        assert!(
+            find_untagged_release(&repo, path, &Version::new(1, 4, 0))
+                .unwrap()
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_untagged_section_missing_from_head_has_no_commit() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit_changelog(&repo, dir.path(), &["1.0.0"]);
+
+        let untagged =
+            find_untagged_release(&repo, Path::new("CHANGELOG.md"), &Version::new(2, 0, 0))
+                .unwrap()
+                .unwrap();
+        assert!(untagged.latest_tag.is_none());
+        assert_eq!(untagged.section_commit, None);
+    }
+}
@@ -0,0 +1,250 @@
+//! Revert pair cancellation.
+//!
+//! When a commit and its revert both land in the same range, the release
+//! contains neither change, so both are dropped before prompt construction.
+//! Reverts are matched by the `This reverts commit <hash>` line git writes,
+//! and, for reverts whose message lost that line (squash merges, edited
+//! messages), by comparing the patch-id of the revert's inverse diff with the
+//! patch-ids of the other commits in the range.
+
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+use git2::{Oid, Repository};
+
+use super::ParsedCommit;
+use super::dedupe::patch_id;
+
+/// Regex for the line `git revert` adds to the message.
+static REVERTS_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"(?m)^This reverts commit ([0-9a-fA-F]{7,40})")
+        .expect("Invalid revert regex pattern - this is a bug")
+});
+
+/// A commit and the commit that reverts it, both removed from the range.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RevertPair {
+    /// Hash of the reverted commit.
+    pub original: String,
+    /// Subject of the reverted commit.
+    pub original_subject: String,
+    /// Hash of the revert.
+    pub revert: String,
+    /// Subject of the revert.
+    pub revert_subject: String,
+}
+
+/// Hash named in a `This reverts commit <hash>` line, if any.
+pub fn reverted_hash(message: &str) -> Option<&str> {
+    REVERTS_REGEX
+        .captures(message)
+        .and_then(|caps| caps.get(1))
+        .map(|m| m.as_str())
+}
+
+/// Whether the subject looks like a revert even without the hash line.
+fn looks_like_revert(commit: &ParsedCommit) -> bool {
+    let subject = commit.subject().trim_start();
+    subject.starts_with("Revert \"") || subject.to_lowercase().starts_with("revert:")
+}
+
+/// Remove commits that are reverted within `commits`, together with their
+/// reverts. Returns the remaining commits (order preserved) and the pairs
+/// that cancelled out.
+///
+/// Commits are expected newest first, as returned by
+/// [`fetch_commits`](super::fetch_commits), so a revert of a revert pairs
+/// with the first revert and the original change stays in.
+pub fn cancel_revert_pairs(
+    repo: &Repository,
+    commits: Vec<ParsedCommit>,
+) -> (Vec<ParsedCommit>, Vec<RevertPair>) {
+    let mut cancelled: HashSet<usize> = HashSet::new();
+    let mut pairs = Vec::new();
+    // Patch-ids of every commit, computed only if a revert needs them
+    let mut patch_ids: Option<Vec<Option<Oid>>> = None;
+
+    for (i, revert) in commits.iter().enumerate() {
+        if cancelled.contains(&i) {
+            continue;
+        }
+
+        let target = match reverted_hash(&revert.message) {
+            Some(hash) => {
+                let hash = hash.to_lowercase();
+                commits
+                    .iter()
+                    .enumerate()
+                    .skip(i + 1)
+                    .find(|(j, c)| !cancelled.contains(j) && c.hash.starts_with(&hash))
+                    .map(|(j, _)| j)
+            }
+            None if looks_like_revert(revert) => {
+                let ids = patch_ids.get_or_insert_with(|| {
+                    commits
+                        .iter()
+                        .map(|c| patch_id(repo, &c.hash, false))
+                        .collect()
+                });
+                patch_id(repo, &revert.hash, true).and_then(|inverse| {
+                    (i + 1..commits.len())
+                        .find(|j| !cancelled.contains(j) && ids[*j] == Some(inverse))
+                })
+            }
+            None => None,
+        };
+
+        if let Some(j) = target {
+            cancelled.insert(i);
+            cancelled.insert(j);
+            pairs.push(RevertPair {
+                original: commits[j].hash.clone(),
+                original_subject: commits[j].subject().to_string(),
+                revert: revert.hash.clone(),
+                revert_subject: revert.subject().to_string(),
+            });
+        }
+    }
+
+    let remaining = commits
+        .into_iter()
+        .enumerate()
+        .filter(|(i, _)| !cancelled.contains(i))
+        .map(|(_, c)| c)
+        .collect();
+    (remaining, pairs)
+}
+
+/// Print the cancelled pairs (verbose mode).
+pub fn print_cancelled_pairs(pairs: &[RevertPair]) {
+    if pairs.is_empty() {
+        return;
+    }
+    println!(
+        "Skipping {} commit(s) cancelled by reverts in the same range:",
+        pairs.len() * 2
+    );
+    for pair in pairs {
+        println!(
+            "  {} {} <- reverted by {} {}",
+            &pair.original[..pair.original.len().min(7)],
+            pair.original_subject,
+            &pair.revert[..pair.revert.len().min(7)],
+            pair.revert_subject
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::path::Path;
+    use tempfile::TempDir;
+
+    use crate::git::fetch_commits;
+
+    fn commit_file(repo: &Repository, path: &str, content: &str, message: &str) -> Oid {
+        let root = repo.workdir().unwrap();
+        std::fs::write(root.join(path), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn subjects(commits: &[ParsedCommit]) -> Vec<&str> {
+        commits.iter().map(|c| c.subject()).collect()
+    }
+
+    #[test]
+    fn test_reverted_hash() {
+        let msg =
+            "Revert \"feat: x\"\n\nThis reverts commit 0123456789abcdef0123456789abcdef01234567.";
+        assert_eq!(
+            reverted_hash(msg),
+            Some("0123456789abcdef0123456789abcdef01234567")
+        );
+        assert_eq!(reverted_hash("feat: x"), None);
+    }
+
+    #[test]
+    fn test_cancels_pair_by_message_and_patch_id() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_file(&repo, "a.txt", "one\n", "chore: init");
+        let feat = commit_file(&repo, "a.txt", "one\ntwo\n", "feat: add two");
+        commit_file(&repo, "b.txt", "b\n", "fix: keep me");
+        commit_file(
+            &repo,
+            "a.txt",
+            "one\n",
+            &format!("Revert \"feat: add two\"\n\nThis reverts commit {}.", feat),
+        );
+        commit_file(&repo, "b.txt", "b\nfast\n", "perf: speed up");
+        // Squash-merged revert without the hash line
+        let head = commit_file(&repo, "b.txt", "b\n", "revert: speed up (#12)");
+
+        let commits = fetch_commits(&repo, base, head, false).unwrap();
+        let (remaining, pairs) = cancel_revert_pairs(&repo, commits);
+
+        assert_eq!(subjects(&remaining), vec!["fix: keep me"]);
+        assert_eq!(pairs.len(), 2);
+        assert_eq!(pairs[0].original_subject, "perf: speed up");
+        assert_eq!(pairs[1].original, feat.to_string());
+        assert_eq!(pairs[1].revert_subject, "Revert \"feat: add two\"");
+    }
+
+    #[test]
+    fn test_reapplied_change_stays() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit_file(&repo, "a.txt", "one\n", "chore: init");
+        let feat = commit_file(&repo, "a.txt", "one\ntwo\n", "feat: add two");
+        let revert = commit_file(
+            &repo,
+            "a.txt",
+            "one\n",
+            &format!("Revert \"feat: add two\"\n\nThis reverts commit {}.", feat),
+        );
+        let head = commit_file(
+            &repo,
+            "a.txt",
+            "one\ntwo\n",
+            &format!(
+                "Reapply \"feat: add two\"\n\nThis reverts commit {}.",
+                revert
+            ),
+        );
+
+        let commits = fetch_commits(&repo, base, head, false).unwrap();
+        let (remaining, pairs) = cancel_revert_pairs(&repo, commits);
+
+        assert_eq!(subjects(&remaining), vec!["feat: add two"]);
+        assert_eq!(pairs.len(), 1);
+        assert_eq!(pairs[0].original, revert.to_string());
+    }
+
+    #[test]
+    fn test_revert_of_commit_outside_range_is_kept() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let feat = commit_file(&repo, "a.txt", "one\ntwo\n", "feat: add two");
+        let head = commit_file(
+            &repo,
+            "a.txt",
+            "one\n",
+            &format!("Revert \"feat: add two\"\n\nThis reverts commit {}.", feat),
+        );
+
+        let commits = fetch_commits(&repo, feat, head, false).unwrap();
+        let (remaining, pairs) = cancel_revert_pairs(&repo, commits);
+        assert_eq!(remaining.len(), 1);
+        assert!(pairs.is_empty());
+    }
+}
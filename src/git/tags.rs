@@ -37,11 +37,18 @@ fn is_stable_release_tag(name: &str) -> bool {
 /// This avoids glob-based false positives from non-semver tags while still
 /// respecting branch reachability.
 pub fn get_latest_reachable_tag(repo: &Repository) -> Result<Option<TagInfo>, GitError> {
-    let head_oid = match repo.head().ok().and_then(|head| head.target()) {
-        Some(oid) => oid,
-        None => return Ok(None),
-    };
+    match repo.head().ok().and_then(|head| head.target()) {
+        Some(head_oid) => get_latest_tag_reachable_from(repo, head_oid),
+        None => Ok(None),
+    }
+}
 
+/// Get the latest stable semver tag reachable from `oid`, like
+/// [`get_latest_reachable_tag`] does from `HEAD`.
+pub fn get_latest_tag_reachable_from(
+    repo: &Repository,
+    oid: git2::Oid,
+) -> Result<Option<TagInfo>, GitError> {
     let mut tags_by_commit: HashMap<git2::Oid, Vec<TagInfo>> = HashMap::new();
     for tag in get_all_tags(repo)?
         .into_iter()
@@ -56,7 +63,7 @@ pub fn get_latest_reachable_tag(repo: &Repository) -> Result<Option<TagInfo>, Gi
     }
 
     let mut revwalk = repo.revwalk().map_err(GitError::RevwalkError)?;
-    revwalk.push(head_oid).map_err(GitError::RevwalkError)?;
+    revwalk.push(oid).map_err(GitError::RevwalkError)?;
     revwalk
         .set_sorting(git2::Sort::TOPOLOGICAL | git2::Sort::TIME)
         .map_err(GitError::RevwalkError)?;
@@ -145,6 +152,55 @@ pub fn get_version_from_tag(tag_name: &str) -> Option<Version> {
     Version::parse(version_str).ok()
 }
 
+/// Inclusive range of tag versions, parsed from `FROM..TO`.
+///
+/// Either side may be omitted (`v1.0.0..` or `..v2.0.0`). Tags are compared
+/// by semver, so `v` prefixes are optional.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagRange {
+    pub from: Option<Version>,
+    pub to: Option<Version>,
+}
+
+impl TagRange {
+    /// Whether `version` falls inside the range.
+    pub fn contains(&self, version: &Version) -> bool {
+        self.from.as_ref().is_none_or(|from| version >= from)
+            && self.to.as_ref().is_none_or(|to| version <= to)
+    }
+}
+
+impl std::str::FromStr for TagRange {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (from, to) = s
+            .split_once("..")
+            .ok_or_else(|| format!("Expected a tag range like v1.0.0..v2.0.0, got '{}'", s))?;
+
+        let parse_bound = |bound: &str| -> Result<Option<Version>, String> {
+            let bound = bound.trim();
+            if bound.is_empty() {
+                return Ok(None);
+            }
+            get_version_from_tag(bound)
+                .map(Some)
+                .ok_or_else(|| format!("'{}' is not a semver tag", bound))
+        };
+
+        let range = Self {
+            from: parse_bound(from)?,
+            to: parse_bound(to)?,
+        };
+        if let (Some(from), Some(to)) = (&range.from, &range.to)
+            && from > to
+        {
+            return Err(format!("Tag range start {} is after end {}", from, to));
+        }
+        Ok(range)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::path::{Path, PathBuf};
@@ -218,6 +274,31 @@ mod tests {
         assert_eq!(v, None);
     }
 
+    #[test]
+    fn test_tag_range_parse_and_contains() {
+        let range: TagRange = "v1.0.0..v2.0.0".parse().unwrap();
+        assert!(range.contains(&Version::new(1, 0, 0)));
+        assert!(range.contains(&Version::new(1, 5, 2)));
+        assert!(range.contains(&Version::new(2, 0, 0)));
+        assert!(!range.contains(&Version::new(0, 9, 0)));
+        assert!(!range.contains(&Version::new(2, 0, 1)));
+
+        let open_end: TagRange = "1.2.0..".parse().unwrap();
+        assert_eq!(open_end.to, None);
+        assert!(open_end.contains(&Version::new(9, 0, 0)));
+
+        let open_start: TagRange = "..v0.3.0".parse().unwrap();
+        assert_eq!(open_start.from, None);
+        assert!(open_start.contains(&Version::new(0, 1, 0)));
+    }
+
+    #[test]
+    fn test_tag_range_rejects_invalid() {
+        assert!("v1.0.0".parse::<TagRange>().is_err());
+        assert!("v1.0.0..latest".parse::<TagRange>().is_err());
+        assert!("v2.0.0..v1.0.0".parse::<TagRange>().is_err());
+    }
+
     #[test]
     #[serial]
     fn test_get_latest_reachable_tag_ignores_non_semver_tags() {
@@ -0,0 +1,131 @@
+//! Repository files without a working tree.
+//!
+//! Bare repositories (server-side bots, mirrors) have no checkout, so the
+//! files generation reads (`keryx.toml`, the changelog, `Cargo.toml`) are
+//! read from the tree of `HEAD` instead.
+
+use std::path::{Component, Path, PathBuf};
+
+use git2::{ObjectType, Repository, Tree, TreeWalkMode, TreeWalkResult};
+use tracing::warn;
+
+/// Content of the file at `path` (relative to the repository root) in the
+/// tree of `HEAD`. `None` if there is no such UTF-8 file or no `HEAD`.
+pub fn read_head_file(repo: &Repository, path: &Path) -> Option<String> {
+    let tree = repo.head().ok()?.peel_to_tree().ok()?;
+    read_tree_file(repo, &tree, path)
+}
+
+/// Content of the UTF-8 file at `path` (relative to the repository root) in
+/// `tree`.
+pub fn read_tree_file(repo: &Repository, tree: &Tree, path: &Path) -> Option<String> {
+    let relative: PathBuf = path
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect();
+    let entry = tree.get_path(&relative).ok()?;
+    let blob = entry.to_object(repo).ok()?.into_blob().ok()?;
+    match std::str::from_utf8(blob.content()) {
+        Ok(content) => Some(content.to_string()),
+        Err(_) => {
+            warn!("{} in the tree is not UTF-8. Ignoring it.", path.display());
+            None
+        }
+    }
+}
+
+/// Content of the file at `path`, from the working tree if there is one and
+/// from `HEAD` in a bare repository. `None` if the file does not exist.
+pub fn read_repo_file(repo: &Repository, path: &Path) -> Option<String> {
+    match repo.workdir() {
+        Some(root) => std::fs::read_to_string(root.join(path)).ok(),
+        None => read_head_file(repo, path),
+    }
+}
+
+/// Paths of all files in the tree of `HEAD`.
+pub fn head_tree_files(repo: &Repository) -> Vec<String> {
+    let Some(tree) = repo.head().ok().and_then(|h| h.peel_to_tree().ok()) else {
+        return Vec::new();
+    };
+    let mut files = Vec::new();
+    let walked = tree.walk(TreeWalkMode::PreOrder, |dir, entry| {
+        if entry.kind() == Some(ObjectType::Blob)
+            && let Some(name) = entry.name()
+        {
+            files.push(format!("{}{}", dir, name));
+        }
+        TreeWalkResult::Ok
+    });
+    if let Err(e) = walked {
+        warn!("Failed to list the files of HEAD: {}", e);
+    }
+    files
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    /// A bare repository with one commit holding `files`.
+    fn bare_repo(files: &[(&str, &str)]) -> (TempDir, Repository) {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init_bare(dir.path()).unwrap();
+        {
+            let mut root = repo.treebuilder(None).unwrap();
+            let mut docs = repo.treebuilder(None).unwrap();
+            for (path, content) in files {
+                let blob = repo.blob(content.as_bytes()).unwrap();
+                match path.strip_prefix("docs/") {
+                    Some(name) => docs.insert(name, blob, 0o100644).unwrap(),
+                    None => root.insert(path, blob, 0o100644).unwrap(),
+                };
+            }
+            if !docs.is_empty() {
+                root.insert("docs", docs.write().unwrap(), 0o040000)
+                    .unwrap();
+            }
+            let tree = repo.find_tree(root.write().unwrap()).unwrap();
+            let sig = Signature::now("Test", "test@example.com").unwrap();
+            repo.commit(Some("HEAD"), &sig, &sig, "chore: init", &tree, &[])
+                .unwrap();
+        }
+        (dir, repo)
+    }
+
+    #[test]
+    fn test_read_head_file_in_bare_repository() {
+        let (_dir, repo) = bare_repo(&[
+            ("keryx.toml", "[product]\nname = \"Widget\"\n"),
+            ("docs/CHANGELOG.md", "# Changelog\n"),
+        ]);
+        assert!(repo.workdir().is_none());
+        assert_eq!(
+            read_repo_file(&repo, Path::new("keryx.toml")).as_deref(),
+            Some("[product]\nname = \"Widget\"\n")
+        );
+        assert_eq!(
+            read_head_file(&repo, Path::new("./docs/CHANGELOG.md")).as_deref(),
+            Some("# Changelog\n")
+        );
+        assert_eq!(read_head_file(&repo, Path::new("missing.md")), None);
+    }
+
+    #[test]
+    fn test_head_tree_files() {
+        let (_dir, repo) = bare_repo(&[("a.txt", "a"), ("docs/b.md", "b")]);
+        let mut files = head_tree_files(&repo);
+        files.sort();
+        assert_eq!(files, vec!["a.txt", "docs/b.md"]);
+    }
+
+    #[test]
+    fn test_no_head() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init_bare(dir.path()).unwrap();
+        assert_eq!(read_head_file(&repo, Path::new("keryx.toml")), None);
+        assert!(head_tree_files(&repo).is_empty());
+    }
+}
@@ -0,0 +1,172 @@
+//! Upstream commits left out of a fork's release.
+//!
+//! A fork that periodically syncs from its upstream merges commits authored
+//! there, and upstream already announced them in its own changelog.
+//! `--exclude-upstream <REF>` hides every commit reachable from `REF` (for
+//! example `upstream/main`) from the range. `REF@{last-release}` stands for
+//! the newest stable release tag reachable from `REF`, so upstream changes
+//! synced before upstream released them are still announced by the fork.
+
+use std::env;
+use std::fmt;
+
+use git2::{Oid, Repository};
+
+use crate::error::GitError;
+
+use super::range::resolve_reference;
+use super::tags::get_latest_tag_reachable_from;
+
+/// Comma-separated upstream refs used when `--exclude-upstream` is not given.
+pub const EXCLUDE_UPSTREAM_ENV_VAR: &str = "KERYX_EXCLUDE_UPSTREAM";
+
+/// Suffix that resolves a ref to its newest release tag.
+const LAST_RELEASE_SUFFIX: &str = "@{last-release}";
+
+/// An upstream ref and the commit whose history is left out.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UpstreamExclusion {
+    /// The ref as given.
+    pub spec: String,
+    /// Tag a `@{last-release}` ref resolved to.
+    pub tag: Option<String>,
+    pub oid: Oid,
+}
+
+impl fmt::Display for UpstreamExclusion {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.tag {
+            Some(tag) => write!(f, "{} ({})", self.spec, tag),
+            None => f.write_str(&self.spec),
+        }
+    }
+}
+
+/// Use the refs from the CLI, falling back to `KERYX_EXCLUDE_UPSTREAM`.
+pub fn upstreams_from_flags_or_env(refs: Vec<String>) -> Vec<String> {
+    if !refs.is_empty() {
+        return refs;
+    }
+    match env::var(EXCLUDE_UPSTREAM_ENV_VAR) {
+        Ok(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|r| !r.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Resolve `spec`, a commit, branch or tag optionally followed by
+/// `@{last-release}`.
+pub fn resolve_upstream(repo: &Repository, spec: &str) -> Result<UpstreamExclusion, GitError> {
+    let Some(base) = spec.strip_suffix(LAST_RELEASE_SUFFIX) else {
+        return Ok(UpstreamExclusion {
+            spec: spec.to_string(),
+            tag: None,
+            oid: resolve_reference(repo, spec)?,
+        });
+    };
+    let tip = resolve_reference(repo, base)?;
+    let tag = get_latest_tag_reachable_from(repo, tip)?
+        .ok_or_else(|| GitError::NoReleaseTag(base.to_string()))?;
+    Ok(UpstreamExclusion {
+        spec: spec.to_string(),
+        tag: Some(tag.name),
+        oid: tag.oid,
+    })
+}
+
+/// Resolve every ref of `specs`.
+pub fn resolve_upstreams(
+    repo: &Repository,
+    specs: &[String],
+) -> Result<Vec<UpstreamExclusion>, GitError> {
+    specs
+        .iter()
+        .map(|spec| resolve_upstream(repo, spec))
+        .collect()
+}
+
+/// Commits of the exclusions, for [`CommitWalk::hide`](super::CommitWalk::hide).
+pub fn upstream_oids(exclusions: &[UpstreamExclusion]) -> Vec<Oid> {
+    exclusions.iter().map(|e| e.oid).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    use crate::git::walk_commits;
+
+    fn commit(repo: &Repository, parents: &[Oid], message: &str) -> Oid {
+        let tree = repo
+            .find_tree(repo.treebuilder(None).unwrap().write().unwrap())
+            .unwrap();
+        let parents: Vec<_> = parents
+            .iter()
+            .map(|oid| repo.find_commit(*oid).unwrap())
+            .collect();
+        let parents: Vec<_> = parents.iter().collect();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(None, &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    #[test]
+    fn test_fork_sync_leaves_out_upstream_commits() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+
+        let base = commit(&repo, &[], "feat: base");
+        let released = commit(&repo, &[base], "feat: upstream released");
+        let unreleased = commit(&repo, &[released], "fix: upstream unreleased");
+        let fork = commit(&repo, &[base], "feat: fork change");
+        let sync = commit(&repo, &[fork, unreleased], "Merge upstream/main");
+        repo.branch(
+            "upstream-main",
+            &repo.find_commit(unreleased).unwrap(),
+            false,
+        )
+        .unwrap();
+        repo.tag_lightweight("v1.1.0", &repo.find_object(released, None).unwrap(), false)
+            .unwrap();
+
+        let subjects = |upstream: &str| -> Vec<String> {
+            let exclusions = resolve_upstreams(&repo, &[upstream.to_string()]).unwrap();
+            walk_commits(&repo, base, sync, false)
+                .unwrap()
+                .hide(&upstream_oids(&exclusions))
+                .unwrap()
+                .map(|c| c.unwrap().subject().to_string())
+                .collect()
+        };
+        let mut all = subjects("upstream-main");
+        all.sort();
+        assert_eq!(all, vec!["Merge upstream/main", "feat: fork change"]);
+
+        let mut since_release = subjects("upstream-main@{last-release}");
+        since_release.sort();
+        assert_eq!(
+            since_release,
+            vec![
+                "Merge upstream/main",
+                "feat: fork change",
+                "fix: upstream unreleased"
+            ]
+        );
+
+        let exclusion = resolve_upstream(&repo, "upstream-main@{last-release}").unwrap();
+        assert_eq!(
+            exclusion.to_string(),
+            "upstream-main@{last-release} (v1.1.0)"
+        );
+        assert!(matches!(
+            resolve_upstream(&repo, "missing@{last-release}"),
+            Err(GitError::ReferenceNotFound(..))
+        ));
+    }
+}
@@ -0,0 +1,212 @@
+//! Shared GitHub client for a single keryx run.
+//!
+//! Building an octocrab client per request throws away its connection pool,
+//! and several stages (changelog generation, version bump, history
+//! processing) need the same merged-PR list. [`GitHubClient`] keeps one
+//! client and caches PR lists by query, so each list is fetched at most once
+//! per run.
+//!
+//! Runs that promise to leave GitHub untouched (changelog generation and
+//! `--dry-run`) call [`set_read_only`]. Every API call that creates or
+//! changes something checks [`ensure_writable`] first, so a code path that
+//! would open a PR or create a release fails instead of making the request.
+
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use chrono::{DateTime, Utc};
+use octocrab::Octocrab;
+use tokio::sync::OnceCell;
+use tracing::debug;
+
+use crate::error::GitHubError;
+use crate::github::auth::get_github_token;
+use crate::github::discussion::{PrDiscussion, fetch_pr_discussion_with_client};
+use crate::github::milestones::{
+    Milestone, fetch_milestone_prs_with_client, find_milestone_with_client,
+};
+use crate::github::prs::{
+    PrScope, PullRequest, fetch_open_prs_with_client, fetch_scoped_prs_with_client, get_pr_limit,
+};
+use crate::github::reviews::fetch_pr_reviewers_with_client;
+
+/// Parameters identifying a merged-PR list.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct PrQuery {
+    owner: String,
+    repo: String,
+    scope: PrScope,
+    limit: usize,
+}
+
+/// A GitHub client with an in-memory cache of merged-PR lists.
+pub struct GitHubClient {
+    octocrab: Octocrab,
+    prs: Mutex<HashMap<PrQuery, Vec<PullRequest>>>,
+}
+
+impl GitHubClient {
+    /// Create a client authenticated with `token`.
+    pub fn new(token: &str) -> Result<Self, GitHubError> {
+        let octocrab = Octocrab::builder()
+            .personal_token(token.to_string())
+            .build()
+            .map_err(|e| GitHubError::FetchPRs(Box::new(e)))?;
+        Ok(Self::from_octocrab(octocrab))
+    }
+
+    /// Wrap a pre-configured octocrab client (e.g. pointed at a mock server).
+    pub fn from_octocrab(octocrab: Octocrab) -> Self {
+        Self {
+            octocrab,
+            prs: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// The underlying octocrab client.
+    pub fn octocrab(&self) -> &Octocrab {
+        &self.octocrab
+    }
+
+    /// Merged PRs for `owner/repo`, fetched once per distinct query.
+    ///
+    /// `limit` defaults to `KERYX_PR_LIMIT` or 100, as in
+    /// [`fetch_merged_prs_with_client`](crate::github::fetch_merged_prs_with_client).
+    pub async fn merged_prs(
+        &self,
+        owner: &str,
+        repo: &str,
+        since: Option<DateTime<Utc>>,
+        until: Option<DateTime<Utc>>,
+        limit: Option<usize>,
+    ) -> Result<Vec<PullRequest>, GitHubError> {
+        let scope = PrScope {
+            since,
+            until,
+            ..PrScope::default()
+        };
+        self.scoped_prs(owner, repo, &scope, limit).await
+    }
+
+    /// Merged PRs for a commit range of `owner/repo`, fetched once per
+    /// distinct query. See [`fetch_scoped_prs_with_client`].
+    pub async fn scoped_prs(
+        &self,
+        owner: &str,
+        repo: &str,
+        scope: &PrScope,
+        limit: Option<usize>,
+    ) -> Result<Vec<PullRequest>, GitHubError> {
+        let query = PrQuery {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+            scope: scope.clone(),
+            limit: limit.unwrap_or_else(get_pr_limit),
+        };
+
+        if let Some(prs) = self.cached(&query) {
+            debug!("Using cached PR list for {}/{}", owner, repo);
+            return Ok(prs);
+        }
+
+        let prs =
+            fetch_scoped_prs_with_client(&self.octocrab, owner, repo, scope, Some(query.limit))
+                .await?;
+
+        self.prs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .insert(query, prs.clone());
+        Ok(prs)
+    }
+
+    /// Open PRs targeting `base` in `owner/repo` (not cached: the list is
+    /// only read once, right before a release).
+    pub async fn open_prs(
+        &self,
+        owner: &str,
+        repo: &str,
+        base: &str,
+    ) -> Result<Vec<PullRequest>, GitHubError> {
+        fetch_open_prs_with_client(&self.octocrab, owner, repo, base).await
+    }
+
+    /// The milestone titled `name` and its merged PRs (not cached).
+    pub async fn milestone_prs(
+        &self,
+        owner: &str,
+        repo: &str,
+        name: &str,
+        limit: Option<usize>,
+    ) -> Result<(Milestone, Vec<PullRequest>), GitHubError> {
+        let milestone = find_milestone_with_client(&self.octocrab, owner, repo, name).await?;
+        let prs =
+            fetch_milestone_prs_with_client(&self.octocrab, owner, repo, &milestone, limit).await?;
+        Ok((milestone, prs))
+    }
+
+    /// PR `number` with its discussion (not cached).
+    pub async fn pr_discussion(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<PrDiscussion, GitHubError> {
+        fetch_pr_discussion_with_client(&self.octocrab, owner, repo, number).await
+    }
+
+    /// Logins of the reviewers of PR `number` (not cached).
+    pub async fn pr_reviewers(
+        &self,
+        owner: &str,
+        repo: &str,
+        number: u64,
+    ) -> Result<Vec<String>, GitHubError> {
+        fetch_pr_reviewers_with_client(&self.octocrab, owner, repo, number).await
+    }
+
+    fn cached(&self, query: &PrQuery) -> Option<Vec<PullRequest>> {
+        self.prs
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+            .get(query)
+            .cloned()
+    }
+}
+
+static READ_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Refuse every GitHub write for the rest of the process.
+pub fn set_read_only(read_only: bool) {
+    READ_ONLY.store(read_only, Ordering::Relaxed);
+}
+
+/// Whether [`set_read_only`] is in effect.
+pub fn is_read_only() -> bool {
+    READ_ONLY.load(Ordering::Relaxed)
+}
+
+/// Check before a request that writes to GitHub; `action` says what it
+/// would do, e.g. "create a release".
+pub fn ensure_writable(action: &str) -> Result<(), GitHubError> {
+    if is_read_only() {
+        return Err(GitHubError::ReadOnly(action.to_string()));
+    }
+    Ok(())
+}
+
+/// Process-wide client, authenticated on first use.
+static SHARED_CLIENT: OnceCell<GitHubClient> = OnceCell::const_new();
+
+/// The client shared by every stage of this run.
+///
+/// Authentication runs once; if it fails, later calls retry it.
+pub async fn shared_client() -> Result<&'static GitHubClient, GitHubError> {
+    SHARED_CLIENT
+        .get_or_try_init(|| async {
+            let token = get_github_token().await?;
+            GitHubClient::new(&token)
+        })
+        .await
+}
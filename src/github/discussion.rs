@@ -0,0 +1,110 @@
+//! PR discussion (description, conversation, and review comments), used by
+//! `keryx explain`.
+
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+
+use crate::error::GitHubError;
+use crate::github::prs::{MAX_BODY_LENGTH, classify_list_error, truncate_body};
+
+/// Most comments kept per PR, conversation and review comments combined.
+const MAX_COMMENTS: usize = 30;
+
+/// Longest comment body kept.
+const MAX_COMMENT_LENGTH: usize = 2 * 1024;
+
+/// A PR with its discussion.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrDiscussion {
+    pub number: u64,
+    pub title: String,
+    pub body: Option<String>,
+    /// Conversation comments first, then inline review comments.
+    pub comments: Vec<PrComment>,
+}
+
+/// A single comment on a PR.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct PrComment {
+    pub author: String,
+    pub body: String,
+}
+
+#[derive(Serialize)]
+struct PageParams {
+    per_page: usize,
+}
+
+#[derive(Deserialize)]
+struct PullItem {
+    title: Option<String>,
+    body: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CommentItem {
+    user: Option<CommentUser>,
+    body: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CommentUser {
+    login: String,
+}
+
+/// Fetch PR `number` with up to [`MAX_COMMENTS`] of its comments.
+///
+/// Bot comments and empty comments are skipped.
+pub async fn fetch_pr_discussion_with_client(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Result<PrDiscussion, GitHubError> {
+    let pull: PullItem = octocrab
+        .get(
+            format!("/repos/{}/{}/pulls/{}", owner, repo, number),
+            None::<&()>,
+        )
+        .await
+        .map_err(|e| classify_list_error(e, owner, repo))?;
+
+    let params = PageParams {
+        per_page: MAX_COMMENTS,
+    };
+    let mut items: Vec<CommentItem> = octocrab
+        .get(
+            format!("/repos/{}/{}/issues/{}/comments", owner, repo, number),
+            Some(&params),
+        )
+        .await
+        .map_err(|e| classify_list_error(e, owner, repo))?;
+    let review_items: Vec<CommentItem> = octocrab
+        .get(
+            format!("/repos/{}/{}/pulls/{}/comments", owner, repo, number),
+            Some(&params),
+        )
+        .await
+        .map_err(|e| classify_list_error(e, owner, repo))?;
+    items.extend(review_items);
+
+    let comments = items
+        .into_iter()
+        .filter_map(|item| {
+            let author = item.user?.login;
+            let body = item.body.filter(|b| !b.trim().is_empty())?;
+            (!author.ends_with("[bot]")).then(|| PrComment {
+                author,
+                body: truncate_body(&body, MAX_COMMENT_LENGTH),
+            })
+        })
+        .take(MAX_COMMENTS)
+        .collect();
+
+    Ok(PrDiscussion {
+        number,
+        title: pull.title.unwrap_or_default(),
+        body: pull.body.map(|b| truncate_body(&b, MAX_BODY_LENGTH)),
+        comments,
+    })
+}
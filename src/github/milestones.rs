@@ -0,0 +1,170 @@
+//! Milestone lookup and milestone-scoped PR fetching.
+//!
+//! Milestone membership is read from the issues endpoint, which lists PRs
+//! alongside issues and includes `pull_request.merged_at`, so one request per
+//! page is enough to tell merged PRs from closed-unmerged ones.
+
+use std::num::NonZeroU64;
+
+use chrono::{DateTime, Utc};
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::GitHubError;
+use crate::github::prs::{
+    MAX_BODY_LENGTH, PullRequest, classify_list_error, get_pr_limit, truncate_body,
+};
+
+/// Items requested per page.
+const PER_PAGE: usize = 100;
+
+/// Safety limit on pages read per listing.
+const MAX_PAGES: u32 = 50;
+
+/// A GitHub milestone.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+pub struct Milestone {
+    pub number: u64,
+    pub title: String,
+    /// `open` or `closed`.
+    pub state: String,
+}
+
+#[derive(Serialize)]
+struct ListParams<'a> {
+    state: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    milestone: Option<u64>,
+    per_page: usize,
+    page: u32,
+}
+
+#[derive(Deserialize)]
+struct IssueItem {
+    number: u64,
+    title: Option<String>,
+    body: Option<String>,
+    #[serde(default)]
+    labels: Vec<LabelItem>,
+    pull_request: Option<PullRequestLink>,
+}
+
+#[derive(Deserialize)]
+struct LabelItem {
+    name: String,
+}
+
+#[derive(Deserialize)]
+struct PullRequestLink {
+    merged_at: Option<DateTime<Utc>>,
+}
+
+/// Find the milestone titled `name` (open or closed).
+///
+/// An exact title match wins; otherwise a single case-insensitive match is
+/// accepted.
+pub async fn find_milestone_with_client(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    name: &str,
+) -> Result<Milestone, GitHubError> {
+    let route = format!("/repos/{}/{}/milestones", owner, repo);
+    let mut candidates = Vec::new();
+
+    for page in 1..=MAX_PAGES {
+        let params = ListParams {
+            state: "all",
+            milestone: None,
+            per_page: PER_PAGE,
+            page,
+        };
+        let milestones: Vec<Milestone> = octocrab
+            .get(&route, Some(&params))
+            .await
+            .map_err(|e| classify_list_error(e, owner, repo))?;
+        let last_page = milestones.len() < PER_PAGE;
+
+        for milestone in milestones {
+            if milestone.title == name {
+                return Ok(milestone);
+            }
+            if milestone.title.eq_ignore_ascii_case(name) {
+                candidates.push(milestone);
+            }
+        }
+        if last_page {
+            break;
+        }
+    }
+
+    if candidates.len() == 1 {
+        return Ok(candidates.remove(0));
+    }
+    Err(GitHubError::MilestoneNotFound {
+        name: name.to_string(),
+        owner: owner.to_string(),
+        repo: repo.to_string(),
+    })
+}
+
+/// Merged PRs in `milestone`, newest merge first.
+///
+/// `limit` defaults to `KERYX_PR_LIMIT` or 100, as for merged PR listing.
+pub async fn fetch_milestone_prs_with_client(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    milestone: &Milestone,
+    limit: Option<usize>,
+) -> Result<Vec<PullRequest>, GitHubError> {
+    let effective_limit = limit.unwrap_or_else(get_pr_limit);
+    let route = format!("/repos/{}/{}/issues", owner, repo);
+    let mut prs = Vec::new();
+
+    'pages: for page in 1..=MAX_PAGES {
+        let params = ListParams {
+            state: "closed",
+            milestone: Some(milestone.number),
+            per_page: PER_PAGE,
+            page,
+        };
+        let items: Vec<IssueItem> = octocrab
+            .get(&route, Some(&params))
+            .await
+            .map_err(|e| classify_list_error(e, owner, repo))?;
+        let last_page = items.len() < PER_PAGE;
+
+        for item in items {
+            // Plain issues have no pull_request link; unmerged PRs no merged_at
+            let Some(merged_at) = item.pull_request.and_then(|pr| pr.merged_at) else {
+                continue;
+            };
+            let Some(number) = NonZeroU64::new(item.number) else {
+                continue;
+            };
+            prs.push(PullRequest {
+                number,
+                title: item.title.unwrap_or_default(),
+                body: item.body.map(|b| truncate_body(&b, MAX_BODY_LENGTH)),
+                merged_at: Some(merged_at),
+                labels: item.labels.into_iter().map(|l| l.name).collect(),
+            });
+            if prs.len() >= effective_limit {
+                warn!(
+                    "Reached PR limit ({}) while fetching milestone \"{}\" for {}/{}. \
+                    Use KERYX_PR_LIMIT env var or --pr-limit to increase.",
+                    effective_limit, milestone.title, owner, repo
+                );
+                break 'pages;
+            }
+        }
+        if last_page {
+            break;
+        }
+    }
+
+    prs.sort_by_key(|pr| std::cmp::Reverse(pr.merged_at));
+    Ok(prs)
+}
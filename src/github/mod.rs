@@ -1,7 +1,24 @@
 //! GitHub API operations using octocrab.
 
 pub mod auth;
+pub mod client;
+pub mod discussion;
+pub mod milestones;
 pub mod prs;
+pub mod remote;
+pub mod reviews;
 
 pub use auth::get_github_token;
-pub use prs::{PullRequest, fetch_merged_prs, fetch_merged_prs_with_client};
+pub use client::{GitHubClient, ensure_writable, is_read_only, set_read_only, shared_client};
+pub use discussion::{PrComment, PrDiscussion, fetch_pr_discussion_with_client};
+pub use milestones::{Milestone, fetch_milestone_prs_with_client, find_milestone_with_client};
+pub use prs::{
+    PrScope, PullRequest, fetch_merged_prs, fetch_merged_prs_with_client,
+    fetch_open_prs_with_client, fetch_scoped_prs_with_client, referenced_pr_numbers,
+    select_prs_for_range,
+};
+pub use remote::{
+    DEFAULT_REMOTE, REMOTE_ENV_VAR, RemoteKind, RemoteUrl, UPSTREAM_REMOTE, detect_remote,
+    parse_remote_url, select_pr_remote,
+};
+pub use reviews::fetch_pr_reviewers_with_client;
@@ -1,14 +1,26 @@
 //! PR fetching via octocrab.
 
+use std::collections::{BTreeSet, HashSet};
 use std::env;
 use std::num::NonZeroU64;
+use std::sync::LazyLock;
 
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, TimeDelta, Utc};
 use octocrab::Octocrab;
 use serde::{Deserialize, Serialize};
-use tracing::warn;
+use tracing::{debug, warn};
 
 use crate::error::GitHubError;
+use crate::git::{CommitSubject, ParsedCommit};
+use crate::github::remote::parse_remote_url;
+use crate::text::truncate_bytes_with_suffix;
+
+/// Regex for PR references in merge and squash commit subjects:
+/// `Merge pull request #12 from ...` and `Some title (#12)`.
+static PR_REFERENCE_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"^Merge pull request #(\d+)|\(#(\d+)\)\s*$")
+        .expect("Invalid PR reference regex - this is a bug")
+});
 
 /// Default maximum number of PRs to fetch.
 const DEFAULT_PR_LIMIT: usize = 100;
@@ -23,7 +35,7 @@ const PR_LIMIT_ENV_VAR: &str = "KERYX_PR_LIMIT";
 ///
 /// Logs a warning if the environment variable is set but contains
 /// an invalid value (non-numeric, empty, or zero).
-fn get_pr_limit() -> usize {
+pub(crate) fn get_pr_limit() -> usize {
     match env::var(PR_LIMIT_ENV_VAR) {
         Ok(v) if !v.is_empty() => match v.parse::<usize>() {
             Ok(0) => {
@@ -46,6 +58,63 @@ fn get_pr_limit() -> usize {
     }
 }
 
+/// Margin below the range start before PR listing stops, for merge times
+/// that differ slightly from commit times.
+const CUTOFF_SLACK: TimeDelta = TimeDelta::days(1);
+
+/// The commit range a merged-PR list is fetched for.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct PrScope {
+    /// Oldest commit time in the range; PRs merged earlier are not kept
+    /// unless associated with the range.
+    pub since: Option<DateTime<Utc>>,
+    /// Newest merge time of PRs kept without an association.
+    pub until: Option<DateTime<Utc>>,
+    /// Full SHAs of the commits in the range.
+    pub commits: BTreeSet<String>,
+    /// PR numbers referenced by merge or squash commit subjects in the range.
+    pub referenced: BTreeSet<u64>,
+}
+
+impl PrScope {
+    /// Scope of a range of parsed commits.
+    pub fn for_commits(commits: &[ParsedCommit]) -> Self {
+        Self {
+            since: commits.iter().map(|c| c.timestamp).min(),
+            until: None,
+            commits: commits.iter().map(|c| c.hash.clone()).collect(),
+            referenced: referenced_pr_numbers(commits.iter().map(|c| c.subject()))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Scope of a range known only by commit subjects and times.
+    pub fn for_subjects(subjects: &[CommitSubject]) -> Self {
+        Self {
+            since: subjects.iter().filter_map(|c| c.timestamp).min(),
+            until: None,
+            commits: BTreeSet::new(),
+            referenced: referenced_pr_numbers(subjects.iter().map(|c| c.subject.as_str()))
+                .into_iter()
+                .collect(),
+        }
+    }
+
+    /// Whether the PR's merge commit is in the range or the range
+    /// references it.
+    fn is_associated(&self, number: u64, merge_commit_sha: Option<&str>) -> bool {
+        self.referenced.contains(&number)
+            || merge_commit_sha.is_some_and(|sha| self.commits.contains(sha))
+    }
+
+    /// Whether a PR merged at `merged_at` falls within the date range.
+    fn contains_date(&self, merged_at: DateTime<Utc>) -> bool {
+        self.since.is_none_or(|since| merged_at >= since)
+            && self.until.is_none_or(|until| merged_at <= until)
+    }
+}
+
 /// Represents a GitHub PR.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct PullRequest {
@@ -57,23 +126,13 @@ pub struct PullRequest {
 }
 
 /// Maximum PR body length to prevent token exhaustion (per spec: 10KB).
-const MAX_BODY_LENGTH: usize = 10 * 1024;
+pub(crate) const MAX_BODY_LENGTH: usize = 10 * 1024;
 
 /// Truncate a string to max_len characters, ensuring valid UTF-8 at the boundary.
 ///
 /// Unlike byte slicing, this is safe for multi-byte characters (e.g., Japanese, emoji).
-fn truncate_body(body: &str, max_len: usize) -> String {
-    if body.len() <= max_len {
-        return body.to_string();
-    }
-
-    // Find a valid character boundary at or before max_len
-    let mut end = max_len;
-    while end > 0 && !body.is_char_boundary(end) {
-        end -= 1;
-    }
-
-    format!("{}... [truncated]", &body[..end])
+pub(crate) fn truncate_body(body: &str, max_len: usize) -> String {
+    truncate_bytes_with_suffix(body, max_len, "... [truncated]")
 }
 
 /// Fetch merged PRs from a GitHub repository using a token.
@@ -112,9 +171,39 @@ pub async fn fetch_merged_prs_with_client(
     since: Option<DateTime<Utc>>,
     until: Option<DateTime<Utc>>,
     limit: Option<usize>,
+) -> Result<Vec<PullRequest>, GitHubError> {
+    let scope = PrScope {
+        since,
+        until,
+        ..PrScope::default()
+    };
+    fetch_scoped_prs_with_client(octocrab, owner, repo, &scope, limit).await
+}
+
+/// Fetch the merged PRs for a commit range.
+///
+/// PRs are listed by last update, newest first, so listing stops at the
+/// first page reaching PRs last updated before `scope.since`: nothing
+/// merged in the range can follow. PRs associated with the range (merge
+/// commit in it, or referenced by one of its subjects) are always kept;
+/// `limit` only caps the others. Without `scope.since` the listing stops at
+/// the limit instead, and referenced PRs not listed by then are fetched one
+/// by one.
+///
+/// # Arguments
+/// * `limit` - Maximum number of unassociated PRs to keep. If None, uses KERYX_PR_LIMIT env var or default (100).
+pub async fn fetch_scoped_prs_with_client(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    scope: &PrScope,
+    limit: Option<usize>,
 ) -> Result<Vec<PullRequest>, GitHubError> {
     let effective_limit = limit.unwrap_or_else(get_pr_limit);
+    let cutoff = scope.since.map(|since| since - CUTOFF_SLACK);
     let mut all_prs = Vec::new();
+    let mut unassociated = 0usize;
+    let mut found: HashSet<u64> = HashSet::new();
     let mut page = 1u32;
     let mut hit_limit = false;
 
@@ -130,165 +219,254 @@ pub async fn fetch_merged_prs_with_client(
             .send()
             .await;
 
-        let prs_page = match result {
-            Ok(page) => page,
-            Err(e) => {
-                // Check error content using both Display and Debug output
-                // to handle different octocrab error formats
-                let err_display = e.to_string();
-                let err_debug = format!("{:?}", e);
-                let err_lower = err_display.to_lowercase();
-                let debug_lower = err_debug.to_lowercase();
-
-                // Check for rate limiting (GitHub returns 403 with rate limit message)
-                if err_lower.contains("rate limit") || debug_lower.contains("rate limit") {
-                    return Err(GitHubError::RateLimited {
-                        reset_time: "unknown".to_string(),
-                    });
-                }
-                // Check for not found (GitHub returns 404)
-                if err_display.contains("Not Found") || err_debug.contains("Not Found") {
-                    return Err(GitHubError::RepositoryNotFound {
-                        owner: owner.to_string(),
-                        repo: repo.to_string(),
-                    });
-                }
-                return Err(GitHubError::FetchPRs(Box::new(e)));
-            }
-        };
+        let prs_page = result.map_err(|e| classify_list_error(e, owner, repo))?;
 
         let items = prs_page.items;
         if items.is_empty() {
             break;
         }
 
+        let mut reached_cutoff = false;
         for pr in items {
-            // Only include merged PRs
-            let merged_at = match pr.merged_at {
-                Some(merged) => merged,
-                None => continue,
-            };
-
-            // Filter by date range if specified
-            if let Some(since_date) = since
-                && merged_at < since_date
-            {
-                continue;
-            }
-
-            if let Some(until_date) = until
-                && merged_at > until_date
+            if let (Some(cutoff), Some(updated)) = (cutoff, pr.updated_at)
+                && updated < cutoff
             {
-                continue;
+                reached_cutoff = true;
             }
 
-            // Validate PR number (0 is invalid, should never happen from GitHub API)
-            let number = match NonZeroU64::new(pr.number) {
-                Some(n) => n,
-                None => {
-                    warn!(
-                        "Skipping PR with invalid number 0 (title: {:?})",
-                        pr.title.as_deref().unwrap_or("<no title>")
-                    );
+            let associated = scope.is_associated(pr.number, pr.merge_commit_sha.as_deref());
+            if !associated {
+                // Only include merged PRs from the date range, up to the limit
+                let Some(merged_at) = pr.merged_at else {
+                    continue;
+                };
+                if !scope.contains_date(merged_at) {
                     continue;
                 }
-            };
-
-            // Truncate body per spec (10KB max)
-            let body = pr.body.map(|b| truncate_body(&b, MAX_BODY_LENGTH));
-
-            let labels = pr
-                .labels
-                .unwrap_or_default()
-                .into_iter()
-                .map(|l| l.name)
-                .collect();
+                if unassociated >= effective_limit {
+                    hit_limit = true;
+                    continue;
+                }
+            }
 
-            all_prs.push(PullRequest {
-                number,
-                title: pr.title.unwrap_or_default(),
-                body,
-                merged_at: Some(merged_at),
-                labels,
-            });
-
-            // Check PR limit
-            if all_prs.len() >= effective_limit {
-                hit_limit = true;
-                break;
+            if let Some(pr) = convert_merged_pr(pr) {
+                found.insert(pr.number.get());
+                if !associated {
+                    unassociated += 1;
+                }
+                all_prs.push(pr);
             }
         }
 
-        // Exit outer loop if we hit the PR limit
-        if hit_limit {
-            warn!(
-                "Reached PR limit ({}) while fetching PRs for {}/{}. \
-                Use KERYX_PR_LIMIT env var or --pr-limit to increase.",
-                effective_limit, owner, repo
-            );
+        // Without a range start there is nothing to stop at but the limit
+        if scope.since.is_none() && (hit_limit || unassociated >= effective_limit) {
+            hit_limit = true;
             break;
         }
 
         // Check if there are more pages
-        if prs_page.next.is_none() {
+        if reached_cutoff || prs_page.next.is_none() {
             break;
         }
 
         page += 1;
+    }
 
-        // Safety limit to prevent infinite loops
-        if page > 50 {
-            warn!(
-                "Reached 50-page safety limit while fetching PRs for {}/{}. \
-                {} PRs collected. Consider using date filters to narrow the range.",
-                owner,
-                repo,
-                all_prs.len()
-            );
-            break;
+    if hit_limit {
+        warn!(
+            "Reached PR limit ({}) while fetching PRs for {}/{}; PRs from the release range \
+            are kept regardless. Use KERYX_PR_LIMIT env var or --pr-limit to increase.",
+            effective_limit, owner, repo
+        );
+    }
+
+    for &number in scope.referenced.iter().filter(|n| !found.contains(n)) {
+        match octocrab.pulls(owner, repo).get(number).await {
+            Ok(pr) => all_prs.extend(convert_merged_pr(pr)),
+            Err(e) => debug!(
+                "Could not fetch PR #{} referenced in the range: {}",
+                number, e
+            ),
         }
     }
 
     Ok(all_prs)
 }
 
-/// Extract owner and repo from a git remote URL.
-pub fn parse_github_remote(url: &str) -> Result<(String, String), GitHubError> {
-    // Handle SSH format: git@github.com:owner/repo.git
-    if url.starts_with("git@github.com:") {
-        let path = url
-            .strip_prefix("git@github.com:")
-            .ok_or(GitHubError::InvalidRepositoryUrl)?;
-        return parse_owner_repo_path(path);
-    }
+/// Convert a merged octocrab PR; `None` if it is not merged or has an
+/// invalid number.
+fn convert_merged_pr(pr: octocrab::models::pulls::PullRequest) -> Option<PullRequest> {
+    let merged_at = pr.merged_at?;
+
+    // Validate PR number (0 is invalid, should never happen from GitHub API)
+    let Some(number) = NonZeroU64::new(pr.number) else {
+        warn!(
+            "Skipping PR with invalid number 0 (title: {:?})",
+            pr.title.as_deref().unwrap_or("<no title>")
+        );
+        return None;
+    };
+
+    // Truncate body per spec (10KB max)
+    let body = pr.body.map(|b| truncate_body(&b, MAX_BODY_LENGTH));
+
+    let labels = pr
+        .labels
+        .unwrap_or_default()
+        .into_iter()
+        .map(|l| l.name)
+        .collect();
+
+    Some(PullRequest {
+        number,
+        title: pr.title.unwrap_or_default(),
+        body,
+        merged_at: Some(merged_at),
+        labels,
+    })
+}
 
-    // Handle HTTPS format: https://github.com/owner/repo.git
-    if url.contains("github.com/") {
-        let path = url
-            .split("github.com/")
-            .nth(1)
-            .ok_or(GitHubError::InvalidRepositoryUrl)?;
-        return parse_owner_repo_path(path);
+/// Map an octocrab error from a PR list request to a [`GitHubError`].
+pub(crate) fn classify_list_error(e: octocrab::Error, owner: &str, repo: &str) -> GitHubError {
+    // Check error content using both Display and Debug output
+    // to handle different octocrab error formats
+    let err_display = e.to_string();
+    let err_debug = format!("{:?}", e);
+    let err_lower = err_display.to_lowercase();
+    let debug_lower = err_debug.to_lowercase();
+
+    // Check for rate limiting (GitHub returns 403 with rate limit message)
+    if err_lower.contains("rate limit") || debug_lower.contains("rate limit") {
+        return GitHubError::RateLimited {
+            reset_time: "unknown".to_string(),
+        };
+    }
+    // Check for not found (GitHub returns 404)
+    if err_display.contains("Not Found") || err_debug.contains("Not Found") {
+        return GitHubError::RepositoryNotFound {
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+        };
     }
+    GitHubError::FetchPRs(Box::new(e))
+}
+
+/// Fetch open PRs whose base branch is `base` (first 100 only).
+///
+/// Returned PRs have no `merged_at` and no body.
+pub async fn fetch_open_prs_with_client(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    base: &str,
+) -> Result<Vec<PullRequest>, GitHubError> {
+    let page = octocrab
+        .pulls(owner, repo)
+        .list()
+        .state(octocrab::params::State::Open)
+        .base(base)
+        .per_page(100)
+        .send()
+        .await
+        .map_err(|e| classify_list_error(e, owner, repo))?;
+
+    Ok(page
+        .items
+        .into_iter()
+        .filter_map(|pr| {
+            Some(PullRequest {
+                number: NonZeroU64::new(pr.number)?,
+                title: pr.title.unwrap_or_default(),
+                body: None,
+                merged_at: None,
+                labels: pr
+                    .labels
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(|l| l.name)
+                    .collect(),
+            })
+        })
+        .collect())
+}
 
-    Err(GitHubError::InvalidRepositoryUrl)
+/// PR numbers referenced by merge or squash commit subjects.
+pub fn referenced_pr_numbers<'a>(subjects: impl IntoIterator<Item = &'a str>) -> HashSet<u64> {
+    subjects
+        .into_iter()
+        .filter_map(|subject| PR_REFERENCE_REGEX.captures(subject))
+        .filter_map(|caps| caps.get(1).or_else(|| caps.get(2)))
+        .filter_map(|m| m.as_str().parse().ok())
+        .collect()
 }
 
-fn parse_owner_repo_path(path: &str) -> Result<(String, String), GitHubError> {
-    let path = path.strip_suffix(".git").unwrap_or(path);
-    let parts: Vec<&str> = path.split('/').collect();
+/// Select the PRs that belong to a commit range.
+///
+/// PRs referenced by merge/squash commits in the range are authoritative.
+/// Only when the range references no PRs at all (e.g. rebase merges) are PRs
+/// matched by merge date instead, keeping those merged after `since` and up
+/// to `until`.
+pub fn select_prs_for_range(
+    prs: &[PullRequest],
+    referenced: &HashSet<u64>,
+    since: Option<DateTime<Utc>>,
+    until: Option<DateTime<Utc>>,
+) -> Vec<PullRequest> {
+    if !referenced.is_empty() {
+        return prs
+            .iter()
+            .filter(|pr| referenced.contains(&pr.number.get()))
+            .cloned()
+            .collect();
+    }
+
+    prs.iter()
+        .filter(|pr| {
+            pr.merged_at.is_some_and(|merged| {
+                since.is_none_or(|since| merged > since)
+                    && until.is_none_or(|until| merged <= until)
+            })
+        })
+        .cloned()
+        .collect()
+}
 
-    if parts.len() >= 2 {
-        Ok((parts[0].to_string(), parts[1].to_string()))
-    } else {
-        Err(GitHubError::InvalidRepositoryUrl)
+/// Extract owner and repo from a GitHub remote URL.
+///
+/// Accepts any form understood by [`parse_remote_url`] but rejects hosts other
+/// than github.com.
+pub fn parse_github_remote(url: &str) -> Result<(String, String), GitHubError> {
+    let remote = parse_remote_url(url)?;
+    if !remote.is_github() {
+        return Err(GitHubError::InvalidRepositoryUrl);
     }
+    Ok((remote.owner, remote.repo))
+}
+
+/// Web URL of a GitHub repository from its remote URL,
+/// e.g. `https://github.com/owner/repo`.
+pub fn github_web_url(url: &str) -> Result<String, GitHubError> {
+    let (owner, repo) = parse_github_remote(url)?;
+    Ok(format!("https://github.com/{}/{}", owner, repo))
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_github_web_url() {
+        assert_eq!(
+            github_web_url("git@github.com:owner/repo.git").unwrap(),
+            "https://github.com/owner/repo"
+        );
+        assert_eq!(
+            github_web_url("https://github.com/owner/repo").unwrap(),
+            "https://github.com/owner/repo"
+        );
+        assert!(github_web_url("https://gitlab.com/owner/repo").is_err());
+    }
+
     #[test]
     fn test_parse_ssh_url() {
         let (owner, repo) = parse_github_remote("git@github.com:owner/repo.git").unwrap();
@@ -316,6 +494,27 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_parse_nonstandard_github_urls() {
+        for url in [
+            "ssh://git@github.com:2222/owner/repo.git",
+            "ssh://git@github.com/owner/repo",
+            "git+https://github.com/owner/repo.git",
+            "https://github.com/owner/repo/",
+            "https://GitHub.com/owner/repo.git",
+            "git@GITHUB.COM:owner/repo.git/",
+        ] {
+            let (owner, repo) =
+                parse_github_remote(url).unwrap_or_else(|e| panic!("{}: {}", url, e));
+            assert_eq!(
+                (owner.as_str(), repo.as_str()),
+                ("owner", "repo"),
+                "{}",
+                url
+            );
+        }
+    }
+
     #[test]
     fn test_truncate_body_short_string() {
         let body = "Hello, world!";
@@ -383,6 +582,53 @@ mod tests {
         assert!(truncated_part.len() <= 10000);
     }
 
+    fn pr(number: u64, merged_at: Option<&str>) -> PullRequest {
+        PullRequest {
+            number: NonZeroU64::new(number).unwrap(),
+            title: format!("PR {}", number),
+            body: None,
+            merged_at: merged_at.map(|d| d.parse().unwrap()),
+            labels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_referenced_pr_numbers() {
+        let numbers = referenced_pr_numbers([
+            "Merge pull request #12 from owner/feature",
+            "feat: add dark mode (#34)",
+            "fix: mention #56 in passing",
+            "chore: no reference",
+        ]);
+        assert_eq!(numbers, HashSet::from([12, 34]));
+    }
+
+    #[test]
+    fn test_select_prs_prefers_referenced() {
+        let prs = vec![
+            pr(1, Some("2024-01-02T00:00:00Z")),
+            pr(2, Some("2024-01-03T00:00:00Z")),
+        ];
+        let selected = select_prs_for_range(&prs, &HashSet::from([2]), None, None);
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].number.get(), 2);
+    }
+
+    #[test]
+    fn test_select_prs_falls_back_to_merge_dates() {
+        let prs = vec![
+            pr(1, Some("2024-01-01T00:00:00Z")),
+            pr(2, Some("2024-01-05T00:00:00Z")),
+            pr(3, Some("2024-01-10T00:00:00Z")),
+            pr(4, None),
+        ];
+        let since = "2024-01-01T00:00:00Z".parse().ok();
+        let until = "2024-01-05T00:00:00Z".parse().ok();
+        let selected = select_prs_for_range(&prs, &HashSet::new(), since, until);
+        let numbers: Vec<_> = selected.iter().map(|p| p.number.get()).collect();
+        assert_eq!(numbers, vec![2]);
+    }
+
     #[test]
     fn test_pullrequest_serialization_with_nonzero() {
         use std::num::NonZeroU64;
@@ -0,0 +1,73 @@
+//! PR review participants, used for reviewer attribution.
+
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+
+use crate::error::GitHubError;
+use crate::github::prs::classify_list_error;
+
+/// Items requested per page.
+const PER_PAGE: usize = 100;
+
+/// Safety limit on pages read per PR.
+const MAX_PAGES: u32 = 10;
+
+#[derive(Serialize)]
+struct PageParams {
+    per_page: usize,
+    page: u32,
+}
+
+#[derive(Deserialize)]
+struct ReviewItem {
+    user: Option<ReviewUser>,
+    #[serde(default)]
+    state: String,
+}
+
+#[derive(Deserialize)]
+struct ReviewUser {
+    login: String,
+}
+
+/// Logins of everyone who submitted a review on PR `number`, in order of
+/// their first review. Pending reviews and bot accounts are skipped.
+pub async fn fetch_pr_reviewers_with_client(
+    octocrab: &Octocrab,
+    owner: &str,
+    repo: &str,
+    number: u64,
+) -> Result<Vec<String>, GitHubError> {
+    let route = format!("/repos/{}/{}/pulls/{}/reviews", owner, repo, number);
+    let mut reviewers: Vec<String> = Vec::new();
+
+    for page in 1..=MAX_PAGES {
+        let params = PageParams {
+            per_page: PER_PAGE,
+            page,
+        };
+        let reviews: Vec<ReviewItem> = octocrab
+            .get(&route, Some(&params))
+            .await
+            .map_err(|e| classify_list_error(e, owner, repo))?;
+        let last_page = reviews.len() < PER_PAGE;
+
+        for review in reviews {
+            let Some(user) = review.user else {
+                continue;
+            };
+            if review.state.eq_ignore_ascii_case("PENDING")
+                || user.login.ends_with("[bot]")
+                || reviewers.contains(&user.login)
+            {
+                continue;
+            }
+            reviewers.push(user.login);
+        }
+        if last_page {
+            break;
+        }
+    }
+
+    Ok(reviewers)
+}
@@ -0,0 +1,201 @@
+//! Jira Cloud / Data Center client (REST API v2).
+
+use async_trait::async_trait;
+use reqwest::{Client, Response, StatusCode};
+use serde::Deserialize;
+use serde_json::json;
+
+use super::IssueTracker;
+use crate::error::IntegrationError;
+
+/// Jira authenticated with an account email and API token.
+pub struct JiraTracker {
+    client: Client,
+    base_url: String,
+    email: String,
+    token: String,
+}
+
+#[derive(Deserialize)]
+struct Transitions {
+    transitions: Vec<Transition>,
+}
+
+#[derive(Deserialize)]
+struct Transition {
+    id: String,
+    name: String,
+    to: TransitionTarget,
+}
+
+#[derive(Deserialize)]
+struct TransitionTarget {
+    name: String,
+}
+
+impl JiraTracker {
+    /// Client for the Jira site at `base_url`.
+    pub fn new(base_url: &str, email: &str, token: &str) -> Self {
+        Self {
+            client: Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            email: email.to_string(),
+            token: token.to_string(),
+        }
+    }
+
+    fn issue_url(&self, issue: &str, endpoint: &str) -> String {
+        format!("{}/rest/api/2/issue/{}/{}", self.base_url, issue, endpoint)
+    }
+
+    /// Map error statuses to [`IntegrationError`].
+    async fn check(issue: &str, response: Response) -> Result<Response, IntegrationError> {
+        let status = response.status();
+        if status.is_success() {
+            return Ok(response);
+        }
+        if status == StatusCode::NOT_FOUND {
+            return Err(IntegrationError::IssueNotFound {
+                issue: issue.to_string(),
+            });
+        }
+        let body = response.text().await.unwrap_or_default();
+        Err(IntegrationError::Api(format!("{} {}", status, body.trim())))
+    }
+}
+
+#[async_trait]
+impl IssueTracker for JiraTracker {
+    fn name(&self) -> &'static str {
+        "Jira"
+    }
+
+    /// `state` may name either the transition or the status it leads to.
+    async fn transition(&self, issue: &str, state: &str) -> Result<(), IntegrationError> {
+        let url = self.issue_url(issue, "transitions");
+        let response = self
+            .client
+            .get(&url)
+            .basic_auth(&self.email, Some(&self.token))
+            .send()
+            .await?;
+        let transitions: Transitions = Self::check(issue, response).await?.json().await?;
+
+        let Some(transition) = transitions
+            .transitions
+            .iter()
+            .find(|t| t.name.eq_ignore_ascii_case(state) || t.to.name.eq_ignore_ascii_case(state))
+        else {
+            return Err(IntegrationError::UnknownState {
+                issue: issue.to_string(),
+                state: state.to_string(),
+                available: transitions
+                    .transitions
+                    .iter()
+                    .map(|t| t.name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            });
+        };
+
+        let response = self
+            .client
+            .post(&url)
+            .basic_auth(&self.email, Some(&self.token))
+            .json(&json!({ "transition": { "id": transition.id } }))
+            .send()
+            .await?;
+        Self::check(issue, response).await?;
+        Ok(())
+    }
+
+    async fn comment(&self, issue: &str, text: &str) -> Result<(), IntegrationError> {
+        let response = self
+            .client
+            .post(self.issue_url(issue, "comment"))
+            .basic_auth(&self.email, Some(&self.token))
+            .json(&json!({ "body": text }))
+            .send()
+            .await?;
+        Self::check(issue, response).await?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_json, header_exists, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn server_with_transitions() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/rest/api/2/issue/KRX-1/transitions"))
+            .and(header_exists("authorization"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "transitions": [
+                    { "id": "11", "name": "Start", "to": { "name": "In Progress" } },
+                    { "id": "31", "name": "Ship it", "to": { "name": "Released" } }
+                ]
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_transition_by_target_status() {
+        let server = server_with_transitions().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/2/issue/KRX-1/transitions"))
+            .and(body_json(json!({ "transition": { "id": "31" } })))
+            .respond_with(ResponseTemplate::new(204))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let jira = JiraTracker::new(&server.uri(), "bot@acme.com", "token");
+        jira.transition("KRX-1", "released").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_transition_unknown_state_lists_available() {
+        let server = server_with_transitions().await;
+        let jira = JiraTracker::new(&server.uri(), "bot@acme.com", "token");
+        let err = jira.transition("KRX-1", "Done").await.unwrap_err();
+        assert!(matches!(err, IntegrationError::UnknownState { .. }));
+        assert!(err.to_string().contains("Start, Ship it"));
+    }
+
+    #[tokio::test]
+    async fn test_comment() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/2/issue/KRX-1/comment"))
+            .and(body_json(json!({ "body": "Released in v1.0.0" })))
+            .respond_with(ResponseTemplate::new(201).set_body_json(json!({ "id": "1" })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let jira = JiraTracker::new(&server.uri(), "bot@acme.com", "token");
+        jira.comment("KRX-1", "Released in v1.0.0").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_missing_issue() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/rest/api/2/issue/KRX-9/comment"))
+            .respond_with(ResponseTemplate::new(404))
+            .mount(&server)
+            .await;
+
+        let jira = JiraTracker::new(&server.uri(), "bot@acme.com", "token");
+        assert!(matches!(
+            jira.comment("KRX-9", "x").await,
+            Err(IntegrationError::IssueNotFound { .. })
+        ));
+    }
+}
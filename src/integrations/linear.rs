@@ -0,0 +1,223 @@
+//! Linear client (GraphQL API).
+
+use async_trait::async_trait;
+use reqwest::Client;
+use serde_json::{Value, json};
+
+use super::IssueTracker;
+use crate::error::IntegrationError;
+
+/// Linear's GraphQL endpoint.
+pub const LINEAR_API_URL: &str = "https://api.linear.app/graphql";
+
+const ISSUE_QUERY: &str =
+    "query($id: String!) { issue(id: $id) { id team { states { nodes { id name } } } } }";
+
+const UPDATE_MUTATION: &str = "mutation($id: String!, $stateId: String!) { \
+     issueUpdate(id: $id, input: { stateId: $stateId }) { success } }";
+
+const COMMENT_MUTATION: &str = "mutation($issueId: String!, $body: String!) { \
+     commentCreate(input: { issueId: $issueId, body: $body }) { success } }";
+
+/// Linear authenticated with a personal API key.
+pub struct LinearTracker {
+    client: Client,
+    endpoint: String,
+    api_key: String,
+}
+
+impl LinearTracker {
+    pub fn new(api_key: &str) -> Self {
+        Self {
+            client: Client::new(),
+            endpoint: LINEAR_API_URL.to_string(),
+            api_key: api_key.to_string(),
+        }
+    }
+
+    /// Use another GraphQL endpoint (for tests).
+    pub fn with_endpoint(mut self, endpoint: &str) -> Self {
+        self.endpoint = endpoint.to_string();
+        self
+    }
+
+    /// Run a GraphQL request and return its `data`.
+    async fn graphql(&self, query: &str, variables: Value) -> Result<Value, IntegrationError> {
+        let response = self
+            .client
+            .post(&self.endpoint)
+            .header("Authorization", &self.api_key)
+            .json(&json!({ "query": query, "variables": variables }))
+            .send()
+            .await?;
+        let status = response.status();
+        let body: Value = response.json().await?;
+        if let Some(message) = body.pointer("/errors/0/message").and_then(|m| m.as_str()) {
+            return Err(IntegrationError::Api(message.to_string()));
+        }
+        if !status.is_success() {
+            return Err(IntegrationError::Api(status.to_string()));
+        }
+        Ok(body.get("data").cloned().unwrap_or(Value::Null))
+    }
+
+    /// The issue's internal id and its team's workflow states.
+    async fn issue(
+        &self,
+        issue: &str,
+    ) -> Result<(String, Vec<(String, String)>), IntegrationError> {
+        let data = self.graphql(ISSUE_QUERY, json!({ "id": issue })).await?;
+        let not_found = || IntegrationError::IssueNotFound {
+            issue: issue.to_string(),
+        };
+        let node = data
+            .get("issue")
+            .filter(|n| !n.is_null())
+            .ok_or_else(not_found)?;
+        let id = node["id"].as_str().ok_or_else(not_found)?.to_string();
+        let states = node
+            .pointer("/team/states/nodes")
+            .and_then(|n| n.as_array())
+            .map(|nodes| {
+                nodes
+                    .iter()
+                    .filter_map(|s| Some((s["id"].as_str()?.into(), s["name"].as_str()?.into())))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Ok((id, states))
+    }
+}
+
+/// Fail unless the mutation at `field` reported success.
+fn ensure_success(data: &Value, field: &str) -> Result<(), IntegrationError> {
+    if data.pointer(&format!("/{}/success", field)) == Some(&Value::Bool(true)) {
+        Ok(())
+    } else {
+        Err(IntegrationError::Api(format!(
+            "{} was not successful",
+            field
+        )))
+    }
+}
+
+#[async_trait]
+impl IssueTracker for LinearTracker {
+    fn name(&self) -> &'static str {
+        "Linear"
+    }
+
+    async fn transition(&self, issue: &str, state: &str) -> Result<(), IntegrationError> {
+        let (id, states) = self.issue(issue).await?;
+        let Some((state_id, _)) = states
+            .iter()
+            .find(|(_, name)| name.eq_ignore_ascii_case(state))
+        else {
+            return Err(IntegrationError::UnknownState {
+                issue: issue.to_string(),
+                state: state.to_string(),
+                available: states
+                    .iter()
+                    .map(|(_, name)| name.as_str())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            });
+        };
+        let data = self
+            .graphql(UPDATE_MUTATION, json!({ "id": id, "stateId": state_id }))
+            .await?;
+        ensure_success(&data, "issueUpdate")
+    }
+
+    async fn comment(&self, issue: &str, text: &str) -> Result<(), IntegrationError> {
+        let (id, _) = self.issue(issue).await?;
+        let data = self
+            .graphql(COMMENT_MUTATION, json!({ "issueId": id, "body": text }))
+            .await?;
+        ensure_success(&data, "commentCreate")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, header, method};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn server_with_issue() -> MockServer {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(header("authorization", "lin_api_key"))
+            .and(body_partial_json(json!({ "variables": { "id": "ENG-7" } })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": { "issue": {
+                    "id": "uuid-7",
+                    "team": { "states": { "nodes": [
+                        { "id": "s-todo", "name": "Todo" },
+                        { "id": "s-rel", "name": "Released" }
+                    ] } }
+                } }
+            })))
+            .mount(&server)
+            .await;
+        server
+    }
+
+    #[tokio::test]
+    async fn test_transition() {
+        let server = server_with_issue().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                json!({ "variables": { "id": "uuid-7", "stateId": "s-rel" } }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "data": { "issueUpdate": { "success": true } } })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let linear = LinearTracker::new("lin_api_key").with_endpoint(&server.uri());
+        linear.transition("ENG-7", "released").await.unwrap();
+        assert!(matches!(
+            linear.transition("ENG-7", "Done").await,
+            Err(IntegrationError::UnknownState { .. })
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_comment() {
+        let server = server_with_issue().await;
+        Mock::given(method("POST"))
+            .and(body_partial_json(
+                json!({ "variables": { "issueId": "uuid-7", "body": "Released in v2.0.0" } }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(json!({ "data": { "commentCreate": { "success": true } } })),
+            )
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let linear = LinearTracker::new("lin_api_key").with_endpoint(&server.uri());
+        linear.comment("ENG-7", "Released in v2.0.0").await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn test_graphql_errors() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "data": null,
+                "errors": [{ "message": "Entity not found: Issue" }]
+            })))
+            .mount(&server)
+            .await;
+
+        let linear = LinearTracker::new("lin_api_key").with_endpoint(&server.uri());
+        let err = linear.comment("ENG-1", "x").await.unwrap_err();
+        assert_eq!(err.to_string(), "API error: Entity not found: Issue");
+    }
+}
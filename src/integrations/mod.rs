@@ -0,0 +1,561 @@
+//! Issue tracker updates after a successful ship.
+//!
+//! Issue keys (`KRX-42`) in the changelog entries of a release are looked up
+//! in the configured trackers, optionally moved to a "released" state, and
+//! commented with the version. Trackers are configured in `keryx.toml`:
+//!
+//! ```toml
+//! [integrations.jira]
+//! url = "https://acme.atlassian.net"
+//! email = "release-bot@acme.com"   # token: KERYX_JIRA_TOKEN
+//! projects = ["KRX"]
+//! transition = "Released"
+//!
+//! [integrations.linear]             # API key: KERYX_LINEAR_API_KEY
+//! teams = ["ENG"]
+//! state = "Released"
+//! comment = "Shipped in {tag}"
+//! ```
+//!
+//! Only keys of the listed projects/teams are touched, so version-like
+//! tokens such as `UTF-8` are never mistaken for issues. The comment defaults
+//! to [`DEFAULT_COMMENT`]; an empty `comment` disables it. The release is
+//! already pushed when trackers are updated, so failures are reported and
+//! never roll anything back.
+//!
+//! Trackers are abstracted behind [`IssueTracker`]; [`JiraTracker`] and
+//! [`LinearTracker`] are the implementations.
+
+pub mod jira;
+pub mod linear;
+
+use std::collections::BTreeSet;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use async_trait::async_trait;
+use git2::Repository;
+use tracing::warn;
+
+use crate::changelog::product::CONFIG_FILE;
+use crate::error::IntegrationError;
+use crate::journal::{Journal, OperationKind};
+
+pub use jira::JiraTracker;
+pub use linear::LinearTracker;
+
+/// Comment posted on released issues unless configured otherwise.
+pub const DEFAULT_COMMENT: &str = "Released in {tag}";
+
+/// Environment variable holding the Jira API token.
+pub const JIRA_TOKEN_ENV_VAR: &str = "KERYX_JIRA_TOKEN";
+
+/// Environment variable holding the Linear API key.
+pub const LINEAR_API_KEY_ENV_VAR: &str = "KERYX_LINEAR_API_KEY";
+
+/// Regex for issue keys (`PROJ-123`).
+static ISSUE_KEY_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"\b([A-Z][A-Z0-9]{0,9})-([0-9]+)\b")
+        .expect("Invalid issue key regex - this is a bug")
+});
+
+/// An issue tracker keryx can update.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait IssueTracker: Send + Sync {
+    /// Tracker name for messages (e.g. "Jira").
+    fn name(&self) -> &'static str;
+
+    /// Move `issue` to the state or transition named `state`.
+    async fn transition(&self, issue: &str, state: &str) -> Result<(), IntegrationError>;
+
+    /// Post `text` as a comment on `issue`.
+    async fn comment(&self, issue: &str, text: &str) -> Result<(), IntegrationError>;
+}
+
+/// What to do with the released issues of one tracker.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TrackerSettings {
+    /// Project (Jira) or team (Linear) keys whose issues are updated.
+    pub projects: Vec<String>,
+    /// State to move released issues to, if any.
+    pub state: Option<String>,
+    /// Comment template with `{tag}` and `{version}`; `None` posts none.
+    pub comment: Option<String>,
+}
+
+impl TrackerSettings {
+    /// Whether `issue` belongs to one of the configured projects.
+    pub fn owns(&self, issue: &str) -> bool {
+        issue
+            .split_once('-')
+            .is_some_and(|(project, _)| self.projects.iter().any(|p| p == project))
+    }
+}
+
+/// `[integrations.jira]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct JiraConfig {
+    /// Site URL, e.g. `https://acme.atlassian.net`.
+    pub url: String,
+    /// Account the API token belongs to.
+    pub email: String,
+    pub settings: TrackerSettings,
+}
+
+/// `[integrations.linear]`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LinearConfig {
+    pub settings: TrackerSettings,
+}
+
+/// The `[integrations]` table of `keryx.toml`. The default configures no
+/// tracker.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct IntegrationsConfig {
+    pub jira: Option<JiraConfig>,
+    pub linear: Option<LinearConfig>,
+}
+
+impl IntegrationsConfig {
+    /// Read the `[integrations]` table of `keryx.toml` in `root`.
+    ///
+    /// A missing file or table gives the default; an unreadable or invalid
+    /// file is logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid integrations in {}: {}. Ignoring them.",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let Some(integrations) = doc.get("integrations") else {
+            return Ok(Self::default());
+        };
+        let integrations = integrations
+            .as_table_like()
+            .ok_or("[integrations] must be a table")?;
+
+        let jira = match integrations.get("jira") {
+            None => None,
+            Some(item) => {
+                let table = item
+                    .as_table_like()
+                    .ok_or("[integrations.jira] must be a table")?;
+                let url = required_string(table, "jira", "url")?;
+                Some(JiraConfig {
+                    url: url.trim_end_matches('/').to_string(),
+                    email: required_string(table, "jira", "email")?,
+                    settings: parse_settings(table, "jira", "projects", "transition")?,
+                })
+            }
+        };
+        let linear = match integrations.get("linear") {
+            None => None,
+            Some(item) => {
+                let table = item
+                    .as_table_like()
+                    .ok_or("[integrations.linear] must be a table")?;
+                Some(LinearConfig {
+                    settings: parse_settings(table, "linear", "teams", "state")?,
+                })
+            }
+        };
+        Ok(Self { jira, linear })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.jira.is_none() && self.linear.is_none()
+    }
+}
+
+fn optional_string(
+    table: &dyn toml_edit::TableLike,
+    tracker: &str,
+    key: &str,
+) -> Result<Option<String>, String> {
+    match table.get(key) {
+        None => Ok(None),
+        Some(item) => item
+            .as_str()
+            .map(|s| Some(s.trim().to_string()))
+            .ok_or_else(|| format!("integrations.{}.{} must be a string", tracker, key)),
+    }
+}
+
+fn required_string(
+    table: &dyn toml_edit::TableLike,
+    tracker: &str,
+    key: &str,
+) -> Result<String, String> {
+    optional_string(table, tracker, key)?
+        .filter(|s| !s.is_empty())
+        .ok_or_else(|| format!("integrations.{}.{} is required", tracker, key))
+}
+
+fn parse_settings(
+    table: &dyn toml_edit::TableLike,
+    tracker: &str,
+    projects_key: &str,
+    state_key: &str,
+) -> Result<TrackerSettings, String> {
+    let projects: Vec<String> = table
+        .get(projects_key)
+        .and_then(|item| item.as_array())
+        .ok_or_else(|| {
+            format!(
+                "integrations.{}.{} must list the keys to update",
+                tracker, projects_key
+            )
+        })?
+        .iter()
+        .map(|v| {
+            v.as_str().map(|s| s.trim().to_uppercase()).ok_or_else(|| {
+                format!(
+                    "integrations.{}.{} must only contain strings",
+                    tracker, projects_key
+                )
+            })
+        })
+        .collect::<Result<_, _>>()?;
+    if projects.is_empty() {
+        return Err(format!(
+            "integrations.{}.{} must list the keys to update",
+            tracker, projects_key
+        ));
+    }
+    let comment = match optional_string(table, tracker, "comment")? {
+        None => Some(DEFAULT_COMMENT.to_string()),
+        Some(comment) if comment.is_empty() => None,
+        Some(comment) => Some(comment),
+    };
+    Ok(TrackerSettings {
+        projects,
+        state: optional_string(table, tracker, state_key)?.filter(|s| !s.is_empty()),
+        comment,
+    })
+}
+
+/// Issue keys mentioned in `text`, deduplicated, in sorted order.
+pub fn issue_keys(text: &str) -> Vec<String> {
+    ISSUE_KEY_REGEX
+        .find_iter(text)
+        .map(|m| m.as_str().to_string())
+        .collect::<BTreeSet<_>>()
+        .into_iter()
+        .collect()
+}
+
+/// Issue keys in the changelog lines the release commit of `tag` added.
+///
+/// The release commit only touches version files and changelogs, so the
+/// lines added to Markdown files are exactly the new entries.
+pub fn released_issue_keys(repo: &Repository, tag: &str) -> Result<Vec<String>, git2::Error> {
+    let commit = repo.revparse_single(tag)?.peel_to_commit()?;
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+
+    let mut added = String::new();
+    diff.print(git2::DiffFormat::Patch, |delta, _, line| {
+        let is_markdown = delta
+            .new_file()
+            .path()
+            .and_then(|p| p.extension())
+            .is_some_and(|ext| ext.eq_ignore_ascii_case("md"));
+        if is_markdown && line.origin() == '+' {
+            added.push_str(&String::from_utf8_lossy(line.content()));
+        }
+        true
+    })?;
+    Ok(issue_keys(&added))
+}
+
+/// Fill in the `{tag}` and `{version}` of a comment template.
+fn render_comment(template: &str, tag: &str) -> String {
+    template
+        .replace("{tag}", tag)
+        .replace("{version}", tag.strip_prefix('v').unwrap_or(tag))
+}
+
+/// Transition and comment the issues in `keys` that `settings` owns,
+/// printing one line per issue. Returns the number of issues updated.
+pub async fn update_issues<T: IssueTracker + ?Sized>(
+    tracker: &T,
+    settings: &TrackerSettings,
+    keys: &[String],
+    tag: &str,
+    journal: &Journal,
+) -> usize {
+    let mut updated = 0;
+    for key in keys.iter().filter(|key| settings.owns(key)) {
+        let mut done = Vec::new();
+        let mut result = Ok(());
+        if let Some(state) = &settings.state {
+            result = tracker.transition(key, state).await;
+            if result.is_ok() {
+                done.push(format!("moved to {}", state));
+            }
+        }
+        if result.is_ok()
+            && let Some(template) = &settings.comment
+        {
+            result = tracker.comment(key, &render_comment(template, tag)).await;
+            if result.is_ok() {
+                done.push("commented".to_string());
+            }
+        }
+
+        match result {
+            Ok(()) if done.is_empty() => {}
+            Ok(()) => {
+                println!("  [DONE] {} {}: {}", tracker.name(), key, done.join(", "));
+                journal.record(
+                    journal
+                        .entry(OperationKind::IssueUpdated)
+                        .arg(tracker.name())
+                        .arg(key)
+                        .detail(done.join(", ")),
+                );
+                updated += 1;
+            }
+            Err(e) => eprintln!("  [WARN] {} {}: {}", tracker.name(), key, e),
+        }
+    }
+    updated
+}
+
+/// Update the issues referenced by the release `tag` in every tracker
+/// configured in `root`'s `keryx.toml`. Problems are printed as warnings.
+pub async fn update_released_issues(repo: &Repository, root: &Path, tag: &str, journal: &Journal) {
+    let config = IntegrationsConfig::load(root);
+    if config.is_empty() {
+        return;
+    }
+    let keys = match released_issue_keys(repo, tag) {
+        Ok(keys) => keys,
+        Err(e) => {
+            eprintln!("  [WARN] Could not read the entries of {}: {}", tag, e);
+            return;
+        }
+    };
+    if keys.is_empty() {
+        return;
+    }
+
+    println!();
+    println!("Issue trackers:");
+    if let Some(jira) = &config.jira {
+        match std::env::var(JIRA_TOKEN_ENV_VAR) {
+            Ok(token) if !token.is_empty() => {
+                let tracker = JiraTracker::new(&jira.url, &jira.email, &token);
+                update_issues(&tracker, &jira.settings, &keys, tag, journal).await;
+            }
+            _ => eprintln!(
+                "  [WARN] Jira is configured but {} is not set; skipping it",
+                JIRA_TOKEN_ENV_VAR
+            ),
+        }
+    }
+    if let Some(linear) = &config.linear {
+        match std::env::var(LINEAR_API_KEY_ENV_VAR) {
+            Ok(key) if !key.is_empty() => {
+                let tracker = LinearTracker::new(&key);
+                update_issues(&tracker, &linear.settings, &keys, tag, journal).await;
+            }
+            _ => eprintln!(
+                "  [WARN] Linear is configured but {} is not set; skipping it",
+                LINEAR_API_KEY_ENV_VAR
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    fn settings(state: Option<&str>, comment: Option<&str>) -> TrackerSettings {
+        TrackerSettings {
+            projects: vec!["KRX".to_string()],
+            state: state.map(String::from),
+            comment: comment.map(String::from),
+        }
+    }
+
+    #[test]
+    fn test_issue_keys() {
+        assert_eq!(
+            issue_keys("- Fix sync (KRX-42, #12)\n- UTF-8 names ENG-7 and KRX-42"),
+            vec!["ENG-7", "KRX-42", "UTF-8"]
+        );
+        assert!(issue_keys("no keys, krx-1, -12").is_empty());
+    }
+
+    #[test]
+    fn test_owns_only_configured_projects() {
+        let settings = settings(None, None);
+        assert!(settings.owns("KRX-42"));
+        assert!(!settings.owns("UTF-8"));
+        assert!(!settings.owns("KRXA-1"));
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = IntegrationsConfig::parse(
+            r#"
+[integrations.jira]
+url = "https://acme.atlassian.net/"
+email = "bot@acme.com"
+projects = ["krx"]
+transition = "Released"
+
+[integrations.linear]
+teams = ["ENG"]
+comment = ""
+"#,
+        )
+        .unwrap();
+        let jira = config.jira.unwrap();
+        assert_eq!(jira.url, "https://acme.atlassian.net");
+        assert_eq!(jira.settings.projects, vec!["KRX"]);
+        assert_eq!(jira.settings.state.as_deref(), Some("Released"));
+        assert_eq!(jira.settings.comment.as_deref(), Some(DEFAULT_COMMENT));
+        let linear = config.linear.unwrap();
+        assert_eq!(linear.settings.state, None);
+        assert_eq!(linear.settings.comment, None);
+
+        assert!(IntegrationsConfig::parse("").unwrap().is_empty());
+        assert!(IntegrationsConfig::parse("[integrations.jira]\nurl = \"x\"\n").is_err());
+        assert!(IntegrationsConfig::parse("[integrations.linear]\nteams = []\n").is_err());
+    }
+
+    #[test]
+    fn test_render_comment() {
+        assert_eq!(
+            render_comment(DEFAULT_COMMENT, "v1.2.0"),
+            "Released in v1.2.0"
+        );
+        assert_eq!(
+            render_comment("Version {version}", "v1.2.0"),
+            "Version 1.2.0"
+        );
+    }
+
+    #[test]
+    fn test_released_issue_keys_reads_added_changelog_lines() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let commit = |files: &[(&str, &str)], message: &str| {
+            for (path, content) in files {
+                std::fs::write(dir.path().join(path), content).unwrap();
+            }
+            let mut index = repo.index().unwrap();
+            index
+                .add_all(["*"].iter(), git2::IndexAddOption::DEFAULT, None)
+                .unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+            let parents: Vec<&git2::Commit> = parent.iter().collect();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+                .unwrap()
+        };
+        commit(
+            &[("CHANGELOG.md", "# Changelog\n\n- Old (KRX-1)\n")],
+            "init",
+        );
+        let release = commit(
+            &[
+                (
+                    "CHANGELOG.md",
+                    "# Changelog\n\n- New (KRX-2)\n\n- Old (KRX-1)\n",
+                ),
+                ("Cargo.toml", "# KRX-3\n"),
+            ],
+            "chore(release): v1.0.0",
+        );
+        repo.tag_lightweight("v1.0.0", &repo.find_object(release, None).unwrap(), false)
+            .unwrap();
+
+        assert_eq!(released_issue_keys(&repo, "v1.0.0").unwrap(), vec!["KRX-2"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_issues_transitions_and_comments_owned_keys() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        let mut tracker = MockIssueTracker::new();
+        tracker.expect_name().return_const("Jira");
+        tracker
+            .expect_transition()
+            .withf(|issue, state| issue == "KRX-1" && state == "Released")
+            .times(1)
+            .returning(|_, _| Ok(()));
+        tracker
+            .expect_comment()
+            .withf(|issue, text| issue == "KRX-1" && text == "Released in v1.0.0")
+            .times(1)
+            .returning(|_, _| Ok(()));
+
+        let keys = vec!["ENG-5".to_string(), "KRX-1".to_string()];
+        let updated = update_issues(
+            &tracker,
+            &settings(Some("Released"), Some(DEFAULT_COMMENT)),
+            &keys,
+            "v1.0.0",
+            &journal,
+        )
+        .await;
+        assert_eq!(updated, 1);
+        let entries = journal.read_all().unwrap();
+        assert_eq!(entries[0].operation, OperationKind::IssueUpdated);
+        assert_eq!(entries[0].args, vec!["Jira", "KRX-1"]);
+    }
+
+    #[tokio::test]
+    async fn test_update_issues_skips_comment_after_failed_transition() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        let mut tracker = MockIssueTracker::new();
+        tracker.expect_name().return_const("Jira");
+        tracker.expect_transition().returning(|issue, state| {
+            Err(IntegrationError::UnknownState {
+                issue: issue.to_string(),
+                state: state.to_string(),
+                available: "Done".to_string(),
+            })
+        });
+        tracker.expect_comment().never();
+
+        let keys = vec!["KRX-1".to_string()];
+        let settings = settings(Some("Released"), Some(DEFAULT_COMMENT));
+        assert_eq!(
+            update_issues(&tracker, &settings, &keys, "v1.0.0", &journal).await,
+            0
+        );
+    }
+}
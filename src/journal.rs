@@ -0,0 +1,349 @@
+//! Append-only journal of mutating operations.
+//!
+//! Every operation keryx performs that changes state outside the process
+//! (files written, commits and tags created, pushes, LLM calls) is appended as
+//! a single JSON line to `.keryx/journal` in the repository root. The journal
+//! is never rewritten, so it doubles as an audit trail for release automation.
+//!
+//! Journal writes are best-effort from the caller's perspective: a failure to
+//! record an operation is logged but never aborts the operation itself.
+
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::JournalError;
+
+/// Directory (relative to the repository root) holding keryx state.
+pub const JOURNAL_DIR: &str = ".keryx";
+
+/// File name of the journal inside [`JOURNAL_DIR`].
+pub const JOURNAL_FILE: &str = "journal";
+
+/// Contents of the `.gitignore` written into [`JOURNAL_DIR`].
+///
+/// Ignoring everything (including the `.gitignore` itself) keeps the journal
+/// from dirtying the working tree, which would otherwise trip the ship
+/// preflight clean-tree check on the next release.
+const JOURNAL_GITIGNORE: &str = "*\n";
+
+/// Create the keryx state directory `dir` (normally [`JOURNAL_DIR`]) if
+/// missing, writing its ignore-everything `.gitignore`.
+pub fn ensure_state_dir(dir: &Path) -> std::io::Result<()> {
+    if dir.exists() {
+        return Ok(());
+    }
+    fs::create_dir_all(dir)?;
+    fs::write(dir.join(".gitignore"), JOURNAL_GITIGNORE)
+}
+
+/// Kind of mutating operation recorded in the journal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum OperationKind {
+    /// A file was written or modified.
+    FileModified,
+    /// A git commit was created.
+    CommitCreated,
+    /// A git tag was created.
+    TagCreated,
+    /// Commits and/or tags were pushed to a remote.
+    Pushed,
+    /// A release was rolled back (tag deleted, commit reset).
+    RolledBack,
+    /// A post-push publish step (e.g. `cargo publish`) completed.
+    Published,
+    /// A release asset was uploaded to the forge.
+    AssetUploaded,
+    /// A release was created on the forge for a pushed tag.
+    ReleaseCreated,
+    /// An LLM provider was invoked.
+    LlmCall,
+    /// An issue tracker issue was transitioned or commented.
+    IssueUpdated,
+    /// A packaging manifest (Homebrew, Scoop, AUR) was bumped.
+    ManifestUpdated,
+}
+
+impl OperationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            OperationKind::FileModified => "file_modified",
+            OperationKind::CommitCreated => "commit_created",
+            OperationKind::TagCreated => "tag_created",
+            OperationKind::Pushed => "pushed",
+            OperationKind::RolledBack => "rolled_back",
+            OperationKind::Published => "published",
+            OperationKind::AssetUploaded => "asset_uploaded",
+            OperationKind::ReleaseCreated => "release_created",
+            OperationKind::LlmCall => "llm_call",
+            OperationKind::IssueUpdated => "issue_updated",
+            OperationKind::ManifestUpdated => "manifest_updated",
+        }
+    }
+}
+
+impl std::fmt::Display for OperationKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A single journal record.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JournalEntry {
+    /// When the operation completed.
+    pub timestamp: DateTime<Utc>,
+    /// The keryx subcommand that performed the operation (e.g. `ship`).
+    pub command: String,
+    /// What kind of operation was performed.
+    pub operation: OperationKind,
+    /// Arguments of the operation (paths, refs, provider names, ...).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub args: Vec<String>,
+    /// Optional free-form detail (e.g. commit message, failure reason).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub detail: Option<String>,
+}
+
+impl JournalEntry {
+    /// Create an entry timestamped now.
+    pub fn new(command: impl Into<String>, operation: OperationKind) -> Self {
+        Self {
+            timestamp: Utc::now(),
+            command: command.into(),
+            operation,
+            args: Vec::new(),
+            detail: None,
+        }
+    }
+
+    /// Append an argument.
+    pub fn arg(mut self, arg: impl Into<String>) -> Self {
+        self.args.push(arg.into());
+        self
+    }
+
+    /// Attach a free-form detail.
+    pub fn detail(mut self, detail: impl Into<String>) -> Self {
+        self.detail = Some(detail.into());
+        self
+    }
+}
+
+/// Handle to the journal of a single repository.
+#[derive(Debug, Clone)]
+pub struct Journal {
+    path: PathBuf,
+    command: String,
+}
+
+impl Journal {
+    /// Journal stored under `<repo_root>/.keryx/journal`.
+    ///
+    /// `command` is the keryx subcommand recorded on entries created via
+    /// [`Journal::entry`].
+    pub fn for_repo(repo_root: &Path, command: &str) -> Self {
+        Self {
+            path: repo_root.join(JOURNAL_DIR).join(JOURNAL_FILE),
+            command: command.to_string(),
+        }
+    }
+
+    /// Journal for the repository at `repo`, if it has a working directory.
+    pub fn for_repository(repo: &git2::Repository, command: &str) -> Option<Self> {
+        repo.workdir().map(|root| Self::for_repo(root, command))
+    }
+
+    /// Start a new entry attributed to this journal's command.
+    pub fn entry(&self, operation: OperationKind) -> JournalEntry {
+        JournalEntry::new(self.command.clone(), operation)
+    }
+
+    /// Path of the journal file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append an entry to the journal, creating it if needed.
+    pub fn append(&self, entry: &JournalEntry) -> Result<(), JournalError> {
+        if let Some(dir) = self.path.parent() {
+            ensure_state_dir(dir).map_err(|source| JournalError::CreateDirFailed {
+                path: dir.to_path_buf(),
+                source,
+            })?;
+        }
+
+        let mut line = serde_json::to_string(entry).map_err(JournalError::SerializationFailed)?;
+        line.push('\n');
+
+        let write_err = |source| JournalError::WriteFailed {
+            path: self.path.clone(),
+            source,
+        };
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .map_err(write_err)?;
+        file.write_all(line.as_bytes()).map_err(write_err)?;
+
+        Ok(())
+    }
+
+    /// Append an entry, logging (but otherwise ignoring) failures.
+    ///
+    /// Journaling must never abort the operation being journaled.
+    pub fn record(&self, entry: JournalEntry) {
+        if let Err(e) = self.append(&entry) {
+            warn!("Failed to record {} in journal: {}", entry.operation, e);
+        }
+    }
+
+    /// Read all entries in the order they were recorded.
+    ///
+    /// Returns an empty list if the journal does not exist yet.
+    pub fn read_all(&self) -> Result<Vec<JournalEntry>, JournalError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(source) => {
+                return Err(JournalError::ReadFailed {
+                    path: self.path.clone(),
+                    source,
+                });
+            }
+        };
+
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(idx, line)| {
+                serde_json::from_str(line).map_err(|source| JournalError::ParseFailed {
+                    line: idx + 1,
+                    source,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Format an entry as a single human-readable line for `keryx log`.
+pub fn format_entry(entry: &JournalEntry) -> String {
+    let mut line = format!(
+        "{}  {:<6}  {:<14}",
+        entry.timestamp.format("%Y-%m-%d %H:%M:%S UTC"),
+        entry.command,
+        entry.operation.as_str()
+    );
+    if !entry.args.is_empty() {
+        line.push_str("  ");
+        line.push_str(&entry.args.join(" "));
+    }
+    if let Some(detail) = &entry.detail {
+        line.push_str("  (");
+        line.push_str(detail);
+        line.push(')');
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_read_missing_journal_is_empty() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        assert!(journal.read_all().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_append_and_read_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+
+        let first = JournalEntry::new("ship", OperationKind::TagCreated).arg("v1.2.0");
+        let second = JournalEntry::new("ship", OperationKind::Pushed)
+            .arg("origin")
+            .arg("main")
+            .detail("--follow-tags");
+        journal.append(&first).unwrap();
+        journal.append(&second).unwrap();
+
+        let entries = journal.read_all().unwrap();
+        assert_eq!(entries, vec![first, second]);
+    }
+
+    #[test]
+    fn test_append_is_append_only() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+
+        journal
+            .append(&JournalEntry::new("commit", OperationKind::CommitCreated))
+            .unwrap();
+        let before = fs::read_to_string(journal.path()).unwrap();
+
+        journal
+            .append(&JournalEntry::new("commit", OperationKind::CommitCreated))
+            .unwrap();
+        let after = fs::read_to_string(journal.path()).unwrap();
+
+        assert!(after.starts_with(&before));
+        assert_eq!(after.lines().count(), 2);
+    }
+
+    #[test]
+    fn test_journal_dir_ignores_itself() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        journal.record(journal.entry(OperationKind::LlmCall));
+
+        let gitignore =
+            fs::read_to_string(dir.path().join(JOURNAL_DIR).join(".gitignore")).unwrap();
+        assert_eq!(gitignore, JOURNAL_GITIGNORE);
+    }
+
+    #[test]
+    fn test_read_reports_malformed_line() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        journal
+            .append(&JournalEntry::new("ship", OperationKind::Pushed))
+            .unwrap();
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .unwrap();
+        writeln!(file, "not json").unwrap();
+
+        let err = journal.read_all().unwrap_err();
+        assert!(matches!(err, JournalError::ParseFailed { line: 2, .. }));
+    }
+
+    #[test]
+    fn test_format_entry() {
+        let entry = JournalEntry {
+            timestamp: DateTime::parse_from_rfc3339("2024-01-15T10:30:00Z")
+                .unwrap()
+                .with_timezone(&Utc),
+            command: "ship".to_string(),
+            operation: OperationKind::TagCreated,
+            args: vec!["v1.0.0".to_string()],
+            detail: Some("annotated".to_string()),
+        };
+
+        assert_eq!(
+            format_entry(&entry),
+            "2024-01-15 10:30:00 UTC  ship    tag_created     v1.0.0  (annotated)"
+        );
+    }
+}
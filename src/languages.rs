@@ -0,0 +1,336 @@
+//! Programming languages of the repository.
+//!
+//! The prompts and the verification searches were written with Rust and
+//! web projects in mind. Counting the files of `HEAD` by extension gives
+//! the repository's dominant languages, so the generation prompt can use
+//! the vocabulary of those ecosystems (a Kotlin app has Gradle modules and
+//! screens, not crates and flags), and every known source extension is
+//! searched during verification.
+
+use std::collections::HashMap;
+use std::fmt;
+
+use git2::Repository;
+use serde::Serialize;
+
+use crate::git::head_tree_files;
+
+/// Directories holding vendored or generated code, which says nothing about
+/// the project's own language.
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    "vendor",
+    "third_party",
+    "target",
+    "dist",
+    "build",
+    "Pods",
+    ".git",
+];
+
+/// Share of source files a language needs to count as dominant.
+const DOMINANT_SHARE: f64 = 0.1;
+
+/// Most languages reported as dominant.
+const MAX_DOMINANT: usize = 3;
+
+/// A programming language keryx recognizes by file extension.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize)]
+pub enum Language {
+    Rust,
+    TypeScript,
+    JavaScript,
+    Python,
+    Go,
+    Java,
+    Kotlin,
+    Swift,
+    ObjectiveC,
+    Ruby,
+    Php,
+    CSharp,
+    C,
+    Cpp,
+    Scala,
+    Elixir,
+    Dart,
+    Lua,
+    Zig,
+    Haskell,
+    Clojure,
+    Shell,
+}
+
+impl Language {
+    /// Every recognized language.
+    pub const ALL: [Language; 22] = [
+        Language::Rust,
+        Language::TypeScript,
+        Language::JavaScript,
+        Language::Python,
+        Language::Go,
+        Language::Java,
+        Language::Kotlin,
+        Language::Swift,
+        Language::ObjectiveC,
+        Language::Ruby,
+        Language::Php,
+        Language::CSharp,
+        Language::C,
+        Language::Cpp,
+        Language::Scala,
+        Language::Elixir,
+        Language::Dart,
+        Language::Lua,
+        Language::Zig,
+        Language::Haskell,
+        Language::Clojure,
+        Language::Shell,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Language::Rust => "Rust",
+            Language::TypeScript => "TypeScript",
+            Language::JavaScript => "JavaScript",
+            Language::Python => "Python",
+            Language::Go => "Go",
+            Language::Java => "Java",
+            Language::Kotlin => "Kotlin",
+            Language::Swift => "Swift",
+            Language::ObjectiveC => "Objective-C",
+            Language::Ruby => "Ruby",
+            Language::Php => "PHP",
+            Language::CSharp => "C#",
+            Language::C => "C",
+            Language::Cpp => "C++",
+            Language::Scala => "Scala",
+            Language::Elixir => "Elixir",
+            Language::Dart => "Dart",
+            Language::Lua => "Lua",
+            Language::Zig => "Zig",
+            Language::Haskell => "Haskell",
+            Language::Clojure => "Clojure",
+            Language::Shell => "Shell",
+        }
+    }
+
+    /// File extensions of the language, without the dot.
+    pub fn extensions(&self) -> &'static [&'static str] {
+        match self {
+            Language::Rust => &["rs"],
+            Language::TypeScript => &["ts", "tsx", "mts", "cts", "vue", "svelte"],
+            Language::JavaScript => &["js", "jsx", "mjs", "cjs"],
+            Language::Python => &["py", "pyi"],
+            Language::Go => &["go"],
+            Language::Java => &["java"],
+            Language::Kotlin => &["kt", "kts"],
+            Language::Swift => &["swift"],
+            Language::ObjectiveC => &["m", "mm"],
+            Language::Ruby => &["rb", "rake"],
+            Language::Php => &["php"],
+            Language::CSharp => &["cs"],
+            Language::C => &["c", "h"],
+            Language::Cpp => &["cc", "cpp", "cxx", "hh", "hpp", "hxx"],
+            Language::Scala => &["scala", "sc"],
+            Language::Elixir => &["ex", "exs"],
+            Language::Dart => &["dart"],
+            Language::Lua => &["lua"],
+            Language::Zig => &["zig"],
+            Language::Haskell => &["hs"],
+            Language::Clojure => &["clj", "cljs", "cljc"],
+            Language::Shell => &["sh", "bash", "zsh"],
+        }
+    }
+
+    /// The language of a file, from its extension.
+    pub fn from_path(path: &str) -> Option<Self> {
+        let file = path.rsplit('/').next().unwrap_or(path);
+        let (_, extension) = file.rsplit_once('.')?;
+        Self::ALL
+            .into_iter()
+            .find(|language| language.extensions().contains(&extension))
+    }
+
+    /// How users of this ecosystem talk about changes, for the prompt.
+    fn terminology(&self) -> &'static str {
+        match self {
+            Language::Rust => "crates, modules, traits, Cargo features and CLI flags",
+            Language::TypeScript | Language::JavaScript => {
+                "packages, components, hooks, exported functions and npm scripts"
+            }
+            Language::Python => "packages, modules, functions, keyword arguments and CLI options",
+            Language::Go => "packages, exported functions, interfaces and command flags",
+            Language::Java | Language::Scala => {
+                "packages, classes, public methods and Maven/Gradle modules"
+            }
+            Language::Kotlin => "screens, Gradle modules, classes and Android/JVM APIs",
+            Language::Swift | Language::ObjectiveC => {
+                "screens, views, frameworks and Swift packages"
+            }
+            Language::Ruby => "gems, classes, methods, Rake tasks and Rails routes",
+            Language::Php => "packages, classes, routes and Composer dependencies",
+            Language::CSharp => "assemblies, namespaces, classes and NuGet packages",
+            Language::C | Language::Cpp | Language::Zig => {
+                "functions, headers, build options and platform support"
+            }
+            Language::Elixir => "applications, modules, functions and Mix tasks",
+            Language::Dart => "widgets, screens, packages and platform support",
+            Language::Lua => "modules, functions and configuration options",
+            Language::Haskell => "packages, modules, functions and type classes",
+            Language::Clojure => "namespaces, functions and deps",
+            Language::Shell => "commands, options and environment variables",
+        }
+    }
+}
+
+impl fmt::Display for Language {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Every recognized source extension, for ripgrep's `--type-add`.
+pub fn source_extensions() -> Vec<&'static str> {
+    Language::ALL
+        .iter()
+        .flat_map(|language| language.extensions().iter().copied())
+        .collect()
+}
+
+/// The dominant languages of a repository, most files first.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize)]
+#[serde(transparent)]
+pub struct LanguageProfile {
+    languages: Vec<Language>,
+}
+
+impl LanguageProfile {
+    /// Languages of the files of `HEAD` (works in bare repositories).
+    pub fn for_repository(repo: &Repository) -> Self {
+        Self::from_paths(head_tree_files(repo).iter().map(String::as_str))
+    }
+
+    /// Dominant languages among `paths`: each with at least 10% of the
+    /// source files, at most three, and at least the most common one.
+    /// Vendored and build directories are skipped.
+    pub fn from_paths<'a>(paths: impl IntoIterator<Item = &'a str>) -> Self {
+        let mut counts: HashMap<Language, usize> = HashMap::new();
+        for path in paths {
+            if path.split('/').any(|dir| IGNORED_DIRS.contains(&dir)) {
+                continue;
+            }
+            if let Some(language) = Language::from_path(path) {
+                *counts.entry(language).or_default() += 1;
+            }
+        }
+        let total: usize = counts.values().sum();
+        let mut ranked: Vec<(Language, usize)> = counts.into_iter().collect();
+        // Ties in ALL order, so the result does not depend on hashing
+        ranked.sort_by_key(|(language, count)| {
+            let position = Language::ALL.iter().position(|l| l == language);
+            (std::cmp::Reverse(*count), position)
+        });
+
+        let languages = ranked
+            .iter()
+            .enumerate()
+            .filter(|(i, (_, count))| *i == 0 || *count as f64 / total as f64 >= DOMINANT_SHARE)
+            .map(|(_, (language, _))| *language)
+            .take(MAX_DOMINANT)
+            .collect();
+        Self { languages }
+    }
+
+    /// Dominant languages, most files first.
+    pub fn languages(&self) -> &[Language] {
+        &self.languages
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.languages.is_empty()
+    }
+
+    /// Prompt section naming the languages and their vocabulary; empty if
+    /// no language was recognized.
+    pub fn prompt_section(&self) -> String {
+        if self.languages.is_empty() {
+            return String::new();
+        }
+        let names: Vec<&str> = self.languages.iter().map(Language::as_str).collect();
+        let mut section = format!(
+            "\n## Project Languages\nThis project is mostly written in {}. Describe changes in the terms its users know:\n",
+            names.join(", ")
+        );
+        for language in &self.languages {
+            section.push_str(&format!("- {}: {}\n", language, language.terminology()));
+        }
+        section
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_language_from_path() {
+        assert_eq!(
+            Language::from_path("app/src/main/Main.kt"),
+            Some(Language::Kotlin)
+        );
+        assert_eq!(
+            Language::from_path("Sources/App.swift"),
+            Some(Language::Swift)
+        );
+        assert_eq!(
+            Language::from_path("lib/tasks/db.rake"),
+            Some(Language::Ruby)
+        );
+        assert_eq!(Language::from_path("README.md"), None);
+        assert_eq!(Language::from_path("Makefile"), None);
+    }
+
+    #[test]
+    fn test_profile_keeps_dominant_languages() {
+        let mut paths = vec!["build.gradle.kts"];
+        paths.extend(["a.kt"; 12]);
+        paths.extend(["b.swift"; 6]);
+        paths.extend(["c.sh"; 1]);
+        paths.extend(["node_modules/x/index.js"; 40]);
+        let profile = LanguageProfile::from_paths(paths);
+        assert_eq!(profile.languages(), [Language::Kotlin, Language::Swift]);
+    }
+
+    #[test]
+    fn test_profile_keeps_most_common_language() {
+        let mut paths = Vec::new();
+        for language in Language::ALL.iter().take(12) {
+            paths.push(format!("src/file.{}", language.extensions()[0]));
+        }
+        paths.push("src/extra.rs".to_string());
+        let profile = LanguageProfile::from_paths(paths.iter().map(String::as_str));
+        assert_eq!(profile.languages(), [Language::Rust]);
+        assert!(LanguageProfile::from_paths(["README.md"]).is_empty());
+    }
+
+    #[test]
+    fn test_prompt_section() {
+        let profile = LanguageProfile::from_paths(["a.rb", "b.rb", "c.php"]);
+        let section = profile.prompt_section();
+        assert!(section.contains("mostly written in Ruby, PHP"));
+        assert!(section.contains("- Ruby: gems"));
+        assert_eq!(LanguageProfile::default().prompt_section(), "");
+    }
+
+    #[test]
+    fn test_source_extensions_cover_previous_list() {
+        let extensions = source_extensions();
+        for ext in [
+            "rs", "ts", "tsx", "js", "jsx", "py", "go", "java", "c", "cpp", "h", "hpp", "kt",
+            "swift", "rb", "php",
+        ] {
+            assert!(extensions.contains(&ext), "missing {}", ext);
+        }
+    }
+}
@@ -6,23 +6,45 @@
 //! into human-readable changelog entries, and writes them to CHANGELOG.md in
 //! Keep a Changelog format.
 
+pub mod announce;
+pub mod api;
+pub mod atomic;
 pub mod changelog;
 pub mod claude;
 pub mod codex;
 pub mod commit;
+pub mod compare;
 pub mod error;
+pub mod explain;
 pub mod git;
 pub mod github;
+pub mod integrations;
+pub mod journal;
+pub mod languages;
 pub mod llm;
+pub mod lock;
+pub mod logging;
+pub mod ollama;
+pub mod output;
+pub mod packaging;
+pub mod pending;
+pub mod refine;
+pub mod scaffold;
+pub mod shell;
 pub mod ship;
+pub mod summary;
+pub mod text;
 pub mod verification;
 pub mod version;
+pub mod workspace;
 
 // Re-export commonly used types
 pub use changelog::{ChangelogCategory, ChangelogEntry, ChangelogOutput};
 pub use error::{
-    ChangelogError, ClaudeError, CodexError, CommitError, GitError, GitHubError, ShipError,
-    VerificationError, VersionError,
+    AnnounceError, ApiError, ChangelogError, ClaudeError, CodexError, CommitError, ExplainError,
+    GitError, GitHubError, HistoryCacheError, IntegrationError, JournalError, LockError,
+    OllamaError, PackagingError, ProviderConfigError, ScaffoldError, ShipError, VerificationError,
+    VersionError,
 };
 pub use git::{CommitType, ParsedCommit};
 pub use github::PullRequest;
@@ -0,0 +1,331 @@
+//! Estimates of the LLM calls a run is about to make.
+//!
+//! Before the first provider call, `keryx generate` and
+//! `keryx init --from-history` print how many calls they plan, roughly how
+//! many prompt tokens those send and, for the HTTP API providers, about what
+//! they cost. Above a threshold the run asks for confirmation first (or
+//! stops without a terminal, unless `--yes` is given). The thresholds are
+//! set in the `[llm]` table of `keryx.toml`:
+//!
+//! ```toml
+//! [llm]
+//! confirm_above_usd = 5.0
+//! confirm_above_tokens = 2000000
+//! ```
+
+use std::fmt;
+use std::path::Path;
+
+use tracing::warn;
+
+use crate::changelog::product::CONFIG_FILE;
+use crate::git::ParsedCommit;
+use crate::github::PullRequest;
+
+use super::router::Provider;
+
+/// Rough size of a token in English text and code.
+const BYTES_PER_TOKEN: usize = 4;
+
+/// Tokens assumed for each reply; changelog JSON rarely needs more.
+const OUTPUT_TOKENS_PER_CALL: u64 = 2_000;
+
+/// Instructions, rules and examples every prompt carries besides the
+/// commits and PRs.
+pub const PROMPT_OVERHEAD_BYTES: usize = 8 * 1024;
+
+/// Cost of a prompt that asks for confirmation by default.
+pub const DEFAULT_CONFIRM_ABOVE_USD: f64 = 1.0;
+
+/// Prompt tokens that ask for confirmation by default, whatever the
+/// provider: CLI plans have usage limits too.
+pub const DEFAULT_CONFIRM_ABOVE_TOKENS: u64 = 1_000_000;
+
+/// List prices in USD per million input and output tokens, by model name
+/// prefix. The longest matching prefix wins.
+const PRICES: [(&str, f64, f64); 12] = [
+    ("claude-opus-4-5", 5.0, 25.0),
+    ("claude-opus-4", 15.0, 75.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-haiku-4", 1.0, 5.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("gpt-5-nano", 0.05, 0.4),
+    ("gpt-5-mini", 0.25, 2.0),
+    ("gpt-5", 1.25, 10.0),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("gpt-4o", 2.5, 10.0),
+];
+
+/// Input and output price per million tokens of `model`, if known.
+fn price(model: &str) -> Option<(f64, f64)> {
+    PRICES
+        .iter()
+        .filter(|(prefix, _, _)| model.starts_with(prefix))
+        .max_by_key(|(prefix, _, _)| prefix.len())
+        .map(|(_, input, output)| (*input, *output))
+}
+
+/// Approximate prompt size for `commits` and `prs`, including the
+/// instructions around them.
+pub fn prompt_bytes(commits: &[ParsedCommit], prs: &[PullRequest]) -> usize {
+    let commits: usize = commits.iter().map(|c| c.hash.len() + c.message.len()).sum();
+    let prs: usize = prs
+        .iter()
+        .map(|pr| pr.title.len() + pr.body.as_deref().map_or(0, str::len))
+        .sum();
+    PROMPT_OVERHEAD_BYTES + commits + prs
+}
+
+/// The LLM calls a run plans to make with one provider.
+#[derive(Debug, Clone)]
+pub struct CostEstimate {
+    provider: Provider,
+    /// Model of an HTTP API provider.
+    model: Option<String>,
+    /// Stage and prompt size of each call.
+    calls: Vec<(&'static str, usize)>,
+}
+
+impl CostEstimate {
+    /// An empty plan for `provider`; an HTTP API provider's model is read
+    /// from its environment variable like the client does.
+    pub fn new(provider: Provider) -> Self {
+        let model = provider.api().map(|api| {
+            std::env::var(api.model_env())
+                .ok()
+                .map(|m| m.trim().to_string())
+                .filter(|m| !m.is_empty())
+                .unwrap_or_else(|| api.default_model().to_string())
+        });
+        Self {
+            provider,
+            model,
+            calls: Vec::new(),
+        }
+    }
+
+    /// Plan a call for `stage` with a prompt of `prompt_bytes`.
+    pub fn add(&mut self, stage: &'static str, prompt_bytes: usize) {
+        self.calls.push((stage, prompt_bytes));
+    }
+
+    pub fn calls(&self) -> usize {
+        self.calls.len()
+    }
+
+    pub fn prompt_tokens(&self) -> u64 {
+        self.calls
+            .iter()
+            .map(|(_, bytes)| bytes.div_ceil(BYTES_PER_TOKEN) as u64)
+            .sum()
+    }
+
+    pub fn output_tokens(&self) -> u64 {
+        self.calls.len() as u64 * OUTPUT_TOKENS_PER_CALL
+    }
+
+    /// Cost in USD at list prices; `None` for the CLI providers (billed by
+    /// their own plan), Ollama, and API models without a known price.
+    pub fn cost_usd(&self) -> Option<f64> {
+        let (input, output) = price(self.model.as_deref()?)?;
+        Some(
+            (self.prompt_tokens() as f64 * input + self.output_tokens() as f64 * output)
+                / 1_000_000.0,
+        )
+    }
+
+    /// Whether the plan is above either limit of `threshold`.
+    pub fn exceeds(&self, threshold: &CostThreshold) -> bool {
+        self.prompt_tokens() > threshold.tokens
+            || self.cost_usd().is_some_and(|cost| cost > threshold.usd)
+    }
+
+    /// Number of calls per stage, in the order first planned.
+    fn stages(&self) -> Vec<(&'static str, usize)> {
+        let mut stages: Vec<(&'static str, usize)> = Vec::new();
+        for (stage, _) in &self.calls {
+            match stages.iter_mut().find(|(s, _)| s == stage) {
+                Some((_, count)) => *count += 1,
+                None => stages.push((stage, 1)),
+            }
+        }
+        stages
+    }
+}
+
+/// Token counts as "850", "12k" or "1.4M".
+fn format_tokens(tokens: u64) -> String {
+    match tokens {
+        0..1_000 => tokens.to_string(),
+        1_000..1_000_000 => format!("{}k", tokens.div_ceil(1_000)),
+        _ => format!("{:.1}M", tokens as f64 / 1_000_000.0),
+    }
+}
+
+impl fmt::Display for CostEstimate {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let stages = self
+            .stages()
+            .iter()
+            .map(|(stage, count)| format!("{} {}", count, stage))
+            .collect::<Vec<_>>()
+            .join(", ");
+        write!(
+            f,
+            "{} LLM call{} ({}), ~{} prompt tokens with {}",
+            self.calls.len(),
+            if self.calls.len() == 1 { "" } else { "s" },
+            stages,
+            format_tokens(self.prompt_tokens()),
+            self.provider
+        )?;
+        match (&self.model, self.cost_usd()) {
+            (Some(model), Some(cost)) => write!(f, " ({}), ~${:.2}", model, cost),
+            (Some(model), None) => write!(f, " ({}), no known price", model),
+            (None, _) if self.provider == Provider::Ollama => f.write_str(", no API cost"),
+            (None, _) => f.write_str(", billed by its own plan"),
+        }
+    }
+}
+
+/// Limits above which a run asks before calling the LLM.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct CostThreshold {
+    /// Estimated cost in USD, for providers with a known price.
+    pub usd: f64,
+    /// Prompt tokens across all calls.
+    pub tokens: u64,
+}
+
+impl Default for CostThreshold {
+    fn default() -> Self {
+        Self {
+            usd: DEFAULT_CONFIRM_ABOVE_USD,
+            tokens: DEFAULT_CONFIRM_ABOVE_TOKENS,
+        }
+    }
+}
+
+impl CostThreshold {
+    /// The `confirm_above_usd` and `confirm_above_tokens` keys of the `[llm]`
+    /// table of `keryx.toml` in `root`, each falling back to its default.
+    ///
+    /// A missing file gives the defaults; an unreadable or invalid file is
+    /// logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid confirmation threshold in {}: {}. Using the defaults.",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let mut threshold = Self::default();
+        let Some(llm) = doc.get("llm") else {
+            return Ok(threshold);
+        };
+        let llm = llm.as_table_like().ok_or("[llm] must be a table")?;
+        if let Some(item) = llm.get("confirm_above_usd") {
+            threshold.usd = item
+                .as_float()
+                .or_else(|| item.as_integer().map(|n| n as f64))
+                .filter(|usd| *usd >= 0.0)
+                .ok_or("llm.confirm_above_usd must be a non-negative number")?;
+        }
+        if let Some(item) = llm.get("confirm_above_tokens") {
+            threshold.tokens = item
+                .as_integer()
+                .and_then(|n| u64::try_from(n).ok())
+                .ok_or("llm.confirm_above_tokens must be a non-negative integer")?;
+        }
+        Ok(threshold)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn estimate(provider: Provider, model: Option<&str>) -> CostEstimate {
+        CostEstimate {
+            provider,
+            model: model.map(String::from),
+            calls: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_price_uses_longest_prefix() {
+        assert_eq!(price("claude-opus-4-5-20251101"), Some((5.0, 25.0)));
+        assert_eq!(price("claude-opus-4-1"), Some((15.0, 75.0)));
+        assert_eq!(price("gpt-5-mini"), Some((0.25, 2.0)));
+        assert_eq!(price("gpt-5"), Some((1.25, 10.0)));
+        assert_eq!(price("llama3.1"), None);
+    }
+
+    #[test]
+    fn test_estimate_cost_and_summary() {
+        let mut plan = estimate(Provider::AnthropicApi, Some("claude-sonnet-4-5"));
+        plan.add("changelog", 400_000);
+        plan.add("changelog", 400_000);
+        plan.add("verification", 200_000);
+        assert_eq!(plan.calls(), 3);
+        assert_eq!(plan.prompt_tokens(), 250_000);
+        // 250k input at $3/M plus 6k output at $15/M
+        assert!((plan.cost_usd().unwrap() - 0.84).abs() < 1e-9);
+        assert_eq!(
+            plan.to_string(),
+            "3 LLM calls (2 changelog, 1 verification), ~250k prompt tokens with Anthropic API (claude-sonnet-4-5), ~$0.84"
+        );
+
+        let mut plan = estimate(Provider::Claude, None);
+        plan.add("changelog", 4_000);
+        assert_eq!(plan.cost_usd(), None);
+        assert!(
+            plan.to_string()
+                .ends_with("~1k prompt tokens with Claude, billed by its own plan")
+        );
+    }
+
+    #[test]
+    fn test_threshold() {
+        let threshold =
+            CostThreshold::parse("[llm]\nprovider = \"claude\"\nconfirm_above_usd = 0.5\n")
+                .unwrap();
+        assert_eq!(threshold.usd, 0.5);
+        assert_eq!(threshold.tokens, DEFAULT_CONFIRM_ABOVE_TOKENS);
+        assert!(CostThreshold::parse("[llm]\nconfirm_above_tokens = -1\n").is_err());
+
+        let mut plan = estimate(Provider::OpenAiApi, Some("gpt-5"));
+        plan.add("changelog", 2_000_000);
+        assert!(plan.exceeds(&threshold));
+        assert!(!plan.exceeds(&CostThreshold::default()));
+
+        // Without a price only the token limit applies
+        let mut plan = estimate(Provider::Ollama, None);
+        plan.add("changelog", 6_000_000);
+        assert!(plan.exceeds(&CostThreshold::default()));
+        assert!(
+            plan.to_string()
+                .ends_with("~1.5M prompt tokens with Ollama, no API cost")
+        );
+    }
+}
@@ -1,13 +1,25 @@
 //! LLM provider routing and prompt construction.
 
+pub mod estimate;
 pub mod json;
 pub mod prompt;
+pub mod providers;
+pub mod request;
 pub mod retry;
 pub mod router;
 
+pub use estimate::{CostEstimate, CostThreshold};
 pub use json::extract_json;
 pub use prompt::{ChangelogInput, PromptError, build_prompt, build_verification_prompt};
+pub use providers::{
+    AuthStatus, PROVIDERS, ProviderStatus, SelectionSource, configured_provider, provider_status,
+    set_default_provider,
+};
+pub use request::{
+    ChangelogRequest, LlmRequest, LlmResponse, LlmTask, ResponseFormat, TaskPolicies, TaskPolicy,
+    TextRequest, VerificationRequest,
+};
 pub use router::{
-    LlmCompletion, LlmError, LlmProviderError, LlmRawCompletion, LlmRouter, Provider,
+    LlmCompletion, LlmError, LlmProviderError, LlmRawCompletion, LlmRouter, LlmUsage, Provider,
     ProviderSelection,
 };
@@ -0,0 +1,456 @@
+//! Provider discovery and the persistent default provider.
+//!
+//! `keryx providers` shows which provider CLIs are installed, whether they
+//! are logged in (or have an API key, for the HTTP API providers, or which
+//! server they use, for Ollama), which
+//! model they will use, and the routing order with
+//! where it comes from. The default provider is stored in the `[llm]` table
+//! of `keryx.toml`; `--provider` overrides it for a single run:
+//!
+//! ```toml
+//! [llm]
+//! provider = "codex"
+//! ```
+
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use tracing::warn;
+
+use crate::changelog::product::CONFIG_FILE;
+use crate::error::ProviderConfigError;
+use crate::ollama::{self, OllamaClient};
+
+use super::router::{Provider, ProviderSelection};
+
+/// Every supported provider, in default routing order.
+pub const PROVIDERS: [Provider; 5] = [
+    Provider::Claude,
+    Provider::Codex,
+    Provider::AnthropicApi,
+    Provider::OpenAiApi,
+    Provider::Ollama,
+];
+
+/// Where the primary provider of a run came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SelectionSource {
+    /// The `--provider` flag.
+    Flag,
+    /// `[llm] provider` in `keryx.toml`.
+    Config,
+    /// Neither was set.
+    Default,
+}
+
+impl fmt::Display for SelectionSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SelectionSource::Flag => "--provider",
+            SelectionSource::Config => CONFIG_FILE,
+            SelectionSource::Default => "built-in default",
+        })
+    }
+}
+
+impl ProviderSelection {
+    /// The `--provider` flag if given, else the default provider in the
+    /// `keryx.toml` of `root`, else Claude. The other provider is the fallback.
+    pub fn resolve(flag: Option<Provider>, root: &Path) -> Self {
+        Self::resolve_with_source(flag, root).0
+    }
+
+    /// Like [`Self::resolve`], also telling where the primary came from.
+    pub fn resolve_with_source(flag: Option<Provider>, root: &Path) -> (Self, SelectionSource) {
+        if let Some(provider) = flag {
+            return (Self::from_primary(provider), SelectionSource::Flag);
+        }
+        match configured_provider(root) {
+            Some(provider) => (Self::from_primary(provider), SelectionSource::Config),
+            None => (Self::default(), SelectionSource::Default),
+        }
+    }
+}
+
+/// The default provider from the `[llm]` table of `keryx.toml` in `root`.
+///
+/// A missing file or key gives `None`; an unreadable or invalid file or
+/// value is logged and ignored.
+pub fn configured_provider(root: &Path) -> Option<Provider> {
+    let path = root.join(CONFIG_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return None,
+        Err(e) => {
+            warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+            return None;
+        }
+    };
+    parse_provider(&content).unwrap_or_else(|e| {
+        warn!("Invalid {}: {}. Ignoring it.", path.display(), e);
+        None
+    })
+}
+
+fn parse_provider(content: &str) -> Result<Option<Provider>, String> {
+    let doc = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| e.to_string())?;
+    let Some(llm) = doc.get("llm") else {
+        return Ok(None);
+    };
+    let llm = llm.as_table_like().ok_or("[llm] must be a table")?;
+    match llm.get("provider") {
+        None => Ok(None),
+        Some(item) => item
+            .as_str()
+            .ok_or_else(|| "llm.provider must be a string".to_string())?
+            .parse()
+            .map(Some),
+    }
+}
+
+/// Store `provider` as the default in the `keryx.toml` of `root`, creating
+/// the file or table if needed and keeping the rest of the file as it is.
+/// Returns the path written.
+pub fn set_default_provider(
+    root: &Path,
+    provider: Provider,
+) -> Result<PathBuf, ProviderConfigError> {
+    let path = root.join(CONFIG_FILE);
+    let content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(source) => return Err(ProviderConfigError::ReadFailed { path, source }),
+    };
+    let invalid = |message: String| ProviderConfigError::Invalid {
+        path: path.clone(),
+        message,
+    };
+
+    let mut doc = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| invalid(e.to_string()))?;
+    let llm = doc
+        .entry("llm")
+        .or_insert(toml_edit::table())
+        .as_table_like_mut()
+        .ok_or_else(|| invalid("[llm] must be a table".to_string()))?;
+    llm.insert("provider", toml_edit::value(provider.id()));
+
+    crate::atomic::write_atomic(&path, doc.to_string()).map_err(|source| {
+        ProviderConfigError::WriteFailed {
+            path: path.clone(),
+            source,
+        }
+    })?;
+    Ok(path)
+}
+
+/// Whether a provider CLI can run.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AuthStatus {
+    /// An API key in this environment variable is used.
+    ApiKey(&'static str),
+    /// Logged in, with the CLI's description of the account.
+    LoggedIn(String),
+    /// The CLI reports no login.
+    LoggedOut,
+    /// An HTTP API provider whose key variable is not set.
+    MissingKey(&'static str),
+    /// No login: requests go to the Ollama server at this URL.
+    Server(String),
+    /// The login cannot be checked from outside the CLI.
+    Unknown,
+}
+
+impl fmt::Display for AuthStatus {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AuthStatus::ApiKey(var) => write!(f, "API key ({})", var),
+            AuthStatus::LoggedIn(detail) => write!(f, "logged in ({})", detail),
+            AuthStatus::LoggedOut => f.write_str("not logged in"),
+            AuthStatus::MissingKey(var) => write!(f, "no API key ({} not set)", var),
+            AuthStatus::Server(url) => write!(f, "no login needed (server {})", url),
+            AuthStatus::Unknown => f.write_str("unknown"),
+        }
+    }
+}
+
+/// What keryx can find out about a provider without calling the LLM.
+#[derive(Debug, Clone)]
+pub struct ProviderStatus {
+    pub provider: Provider,
+    /// Location of the CLI; `None` if it is not on `PATH` or the provider
+    /// has none.
+    pub path: Option<PathBuf>,
+    /// First line of `--version`.
+    pub version: Option<String>,
+    pub auth: AuthStatus,
+    /// Model the CLI is configured to use and where that is set; `None`
+    /// when it uses its own default.
+    pub model: Option<(String, String)>,
+}
+
+/// Name of the provider's executable; `None` for the HTTP API providers.
+pub fn binary(provider: Provider) -> Option<&'static str> {
+    match provider {
+        Provider::Claude => Some("claude"),
+        Provider::Codex => Some("codex"),
+        Provider::Ollama => Some("ollama"),
+        Provider::AnthropicApi | Provider::OpenAiApi => None,
+    }
+}
+
+/// Where the provider's CLI is installed, if it has one on `PATH`.
+pub fn find_cli(provider: Provider) -> Option<PathBuf> {
+    which::which(binary(provider)?).ok()
+}
+
+/// Inspect `provider`: its CLI, version, login and model. Runs the CLI's
+/// `--version` (and `codex login status`) but never sends a prompt.
+pub fn provider_status(provider: Provider) -> ProviderStatus {
+    let path = find_cli(provider);
+    let version = path.as_ref().and_then(|path| {
+        let output = Command::new(path)
+            .arg("--version")
+            .output()
+            .ok()
+            .filter(|o| o.status.success())?;
+        first_line(&String::from_utf8_lossy(&output.stdout))
+    });
+    let home = home_dir();
+    let (auth, model) = match provider {
+        Provider::Claude => (claude_auth(home.as_deref()), claude_model(home.as_deref())),
+        Provider::Codex => (
+            codex_auth(path.is_some()),
+            codex_model(codex_home(home.as_deref()).as_deref()),
+        ),
+        Provider::AnthropicApi | Provider::OpenAiApi => {
+            let api = provider.api().expect("API provider");
+            let auth = if env_set(api.key_env()) {
+                AuthStatus::ApiKey(api.key_env())
+            } else {
+                AuthStatus::MissingKey(api.key_env())
+            };
+            let model = match env::var(api.model_env()) {
+                Ok(model) if !model.trim().is_empty() => {
+                    (model.trim().to_string(), api.model_env().to_string())
+                }
+                _ => (api.default_model().to_string(), "default".to_string()),
+            };
+            (auth, Some(model))
+        }
+        Provider::Ollama => {
+            let client = OllamaClient::from_env();
+            let source = if env_set(ollama::MODEL_ENV) {
+                ollama::MODEL_ENV
+            } else {
+                "default"
+            };
+            (
+                AuthStatus::Server(client.base_url().to_string()),
+                Some((client.model().to_string(), source.to_string())),
+            )
+        }
+    };
+    ProviderStatus {
+        provider,
+        path,
+        version,
+        auth,
+        model,
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    env::var_os("HOME")
+        .or_else(|| env::var_os("USERPROFILE"))
+        .map(PathBuf::from)
+}
+
+fn env_set(name: &str) -> bool {
+    env::var(name).is_ok_and(|v| !v.trim().is_empty())
+}
+
+fn first_line(text: &str) -> Option<String> {
+    text.lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty())
+        .map(String::from)
+}
+
+/// Claude Code keeps its login in the system keychain on macOS, so a missing
+/// credentials file does not mean logged out.
+fn claude_auth(home: Option<&Path>) -> AuthStatus {
+    if env_set("ANTHROPIC_API_KEY") {
+        return AuthStatus::ApiKey("ANTHROPIC_API_KEY");
+    }
+    if env_set("CLAUDE_CODE_OAUTH_TOKEN") {
+        return AuthStatus::ApiKey("CLAUDE_CODE_OAUTH_TOKEN");
+    }
+    match home.map(|h| h.join(".claude").join(".credentials.json")) {
+        Some(credentials) if credentials.exists() => {
+            AuthStatus::LoggedIn(credentials.display().to_string())
+        }
+        _ => AuthStatus::Unknown,
+    }
+}
+
+fn claude_model(home: Option<&Path>) -> Option<(String, String)> {
+    if let Ok(model) = env::var("ANTHROPIC_MODEL")
+        && !model.trim().is_empty()
+    {
+        return Some((model.trim().to_string(), "ANTHROPIC_MODEL".to_string()));
+    }
+    let settings = home?.join(".claude").join("settings.json");
+    let model = model_from_claude_settings(&std::fs::read_to_string(&settings).ok()?)?;
+    Some((model, settings.display().to_string()))
+}
+
+/// The `model` of a Claude Code `settings.json`.
+fn model_from_claude_settings(content: &str) -> Option<String> {
+    let settings: serde_json::Value = serde_json::from_str(content).ok()?;
+    let model = settings.get("model")?.as_str()?.trim();
+    (!model.is_empty()).then(|| model.to_string())
+}
+
+fn codex_auth(installed: bool) -> AuthStatus {
+    if env_set("OPENAI_API_KEY") {
+        return AuthStatus::ApiKey("OPENAI_API_KEY");
+    }
+    if !installed {
+        return AuthStatus::Unknown;
+    }
+    match Command::new("codex").args(["login", "status"]).output() {
+        Ok(output) if output.status.success() => {
+            // Codex prints the status on stderr in some versions
+            let text = format!(
+                "{}\n{}",
+                String::from_utf8_lossy(&output.stdout),
+                String::from_utf8_lossy(&output.stderr)
+            );
+            AuthStatus::LoggedIn(first_line(&text).unwrap_or_else(|| "codex".to_string()))
+        }
+        Ok(_) => AuthStatus::LoggedOut,
+        Err(_) => AuthStatus::Unknown,
+    }
+}
+
+fn codex_home(home: Option<&Path>) -> Option<PathBuf> {
+    env::var_os("CODEX_HOME")
+        .map(PathBuf::from)
+        .or_else(|| home.map(|h| h.join(".codex")))
+}
+
+fn codex_model(codex_home: Option<&Path>) -> Option<(String, String)> {
+    let config = codex_home?.join("config.toml");
+    let model = model_from_codex_config(&std::fs::read_to_string(&config).ok()?)?;
+    Some((model, config.display().to_string()))
+}
+
+/// The top-level `model` of a Codex `config.toml`.
+fn model_from_codex_config(content: &str) -> Option<String> {
+    let doc = content.parse::<toml_edit::DocumentMut>().ok()?;
+    let model = doc.get("model")?.as_str()?.trim();
+    (!model.is_empty()).then(|| model.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_resolve_prefers_flag_then_config() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(
+            ProviderSelection::resolve_with_source(None, dir.path()),
+            (ProviderSelection::default(), SelectionSource::Default)
+        );
+
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            "[llm]\nprovider = \"codex\"\n",
+        )
+        .unwrap();
+        let (selection, source) = ProviderSelection::resolve_with_source(None, dir.path());
+        assert_eq!(selection.primary, Provider::Codex);
+        assert_eq!(selection.fallback, Provider::Claude);
+        assert_eq!(source, SelectionSource::Config);
+
+        let (selection, source) =
+            ProviderSelection::resolve_with_source(Some(Provider::Claude), dir.path());
+        assert_eq!(selection.primary, Provider::Claude);
+        assert_eq!(source, SelectionSource::Flag);
+    }
+
+    #[test]
+    fn test_invalid_provider_is_ignored() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE), "[llm]\nprovider = \"gpt\"\n").unwrap();
+        assert_eq!(configured_provider(dir.path()), None);
+        assert!(parse_provider("llm = 3").is_err());
+    }
+
+    #[test]
+    fn test_set_default_provider_keeps_other_settings() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        std::fs::write(
+            &path,
+            "# Release notes\n[product]\ndescription = \"Status pages\"\n",
+        )
+        .unwrap();
+
+        set_default_provider(dir.path(), Provider::Codex).unwrap();
+        let content = std::fs::read_to_string(&path).unwrap();
+        assert!(
+            content.starts_with("# Release notes\n[product]\ndescription = \"Status pages\"\n")
+        );
+        assert_eq!(configured_provider(dir.path()), Some(Provider::Codex));
+
+        set_default_provider(dir.path(), Provider::Claude).unwrap();
+        assert_eq!(configured_provider(dir.path()), Some(Provider::Claude));
+    }
+
+    #[test]
+    fn test_set_default_provider_creates_config() {
+        let dir = TempDir::new().unwrap();
+        let path = set_default_provider(dir.path(), Provider::Codex).unwrap();
+        assert_eq!(
+            std::fs::read_to_string(path).unwrap().trim(),
+            "[llm]\nprovider = \"codex\""
+        );
+    }
+
+    #[test]
+    fn test_set_default_provider_rejects_non_table() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join(CONFIG_FILE), "llm = \"codex\"\n").unwrap();
+        assert!(matches!(
+            set_default_provider(dir.path(), Provider::Codex),
+            Err(ProviderConfigError::Invalid { .. })
+        ));
+    }
+
+    #[test]
+    fn test_models_from_cli_config() {
+        assert_eq!(
+            model_from_claude_settings(r#"{"model": "opus", "theme": "dark"}"#).as_deref(),
+            Some("opus")
+        );
+        assert_eq!(model_from_claude_settings(r#"{"theme": "dark"}"#), None);
+        assert_eq!(
+            model_from_codex_config("model = \"o4-mini\"\n[profiles.fast]\nmodel = \"x\"\n")
+                .as_deref(),
+            Some("o4-mini")
+        );
+        assert_eq!(
+            model_from_codex_config("[profiles.fast]\nmodel = \"x\"\n"),
+            None
+        );
+    }
+}
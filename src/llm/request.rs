@@ -0,0 +1,429 @@
+//! Typed LLM requests and per-task policies.
+//!
+//! Every job keryx gives an LLM is a request type: it carries the prompt,
+//! names its [`LlmTask`], and validates the response into its own output
+//! type. [`LlmRouter::request`](super::LlmRouter::request) runs a request
+//! under the [`TaskPolicy`] of its task: which provider to try first, how
+//! many times to ask again when a response fails validation, and how long
+//! one call may take. Policies are set per task in `keryx.toml`:
+//!
+//! ```toml
+//! [llm.tasks.commit]
+//! provider = "ollama"   # try this provider first for commit messages
+//! attempts = 3          # ask again until a response parses
+//! timeout_secs = 60     # give up on a call after a minute
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+use std::time::Duration;
+
+use tracing::warn;
+
+use crate::changelog::ChangelogOutput;
+use crate::changelog::product::CONFIG_FILE;
+
+use super::router::Provider;
+
+/// What an LLM is asked to do.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum LlmTask {
+    /// Draft changelog entries (and migration steps).
+    Changelog,
+    /// Check drafted entries against the evidence.
+    Verification,
+    /// Pick the semantic version bump.
+    Bump,
+    /// Write commit messages, split plans and changelog trailers.
+    Commit,
+    /// Free-form text: summaries, explanations, refinements, translations.
+    Text,
+}
+
+impl LlmTask {
+    /// Every task, in the order `keryx.toml` documents them.
+    pub const ALL: [LlmTask; 5] = [
+        LlmTask::Changelog,
+        LlmTask::Verification,
+        LlmTask::Bump,
+        LlmTask::Commit,
+        LlmTask::Text,
+    ];
+
+    /// Key of the task's table under `[llm.tasks]`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            LlmTask::Changelog => "changelog",
+            LlmTask::Verification => "verification",
+            LlmTask::Bump => "bump",
+            LlmTask::Commit => "commit",
+            LlmTask::Text => "text",
+        }
+    }
+
+    /// Whether providers are asked for the structured changelog format
+    /// (enforced with a JSON schema where the provider supports one).
+    pub fn format(&self) -> ResponseFormat {
+        match self {
+            LlmTask::Changelog | LlmTask::Verification => ResponseFormat::Changelog,
+            LlmTask::Bump | LlmTask::Commit | LlmTask::Text => ResponseFormat::Text,
+        }
+    }
+}
+
+impl fmt::Display for LlmTask {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.id())
+    }
+}
+
+impl FromStr for LlmTask {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        LlmTask::ALL
+            .into_iter()
+            .find(|task| task.id() == s.trim())
+            .ok_or_else(|| {
+                format!(
+                    "unknown task '{}' (expected changelog, verification, bump, commit or text)",
+                    s
+                )
+            })
+    }
+}
+
+/// How a provider is asked to answer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResponseFormat {
+    /// A [`ChangelogOutput`] document.
+    Changelog,
+    /// Free-form text, parsed by the request.
+    Text,
+}
+
+/// A provider's answer, before the request validates it.
+#[derive(Debug, Clone)]
+pub enum LlmResponse {
+    Changelog(ChangelogOutput),
+    Text(String),
+}
+
+impl LlmResponse {
+    /// The response as text, for requests that parse it themselves.
+    pub fn into_text(self) -> String {
+        match self {
+            LlmResponse::Text(text) => text,
+            LlmResponse::Changelog(output) => serde_json::to_string(&output).unwrap_or_default(),
+        }
+    }
+}
+
+/// A request to an LLM with a typed, validated output.
+pub trait LlmRequest: Sync {
+    type Output: Send;
+
+    /// The task whose policy applies.
+    fn task(&self) -> LlmTask;
+
+    fn prompt(&self) -> &str;
+
+    /// Validate a response. An error explains what is wrong; the request is
+    /// then asked again while the task's attempts last.
+    fn parse(&self, response: LlmResponse) -> Result<Self::Output, String>;
+}
+
+/// Changelog entries for a release (also used for migration steps).
+#[derive(Debug, Clone)]
+pub struct ChangelogRequest {
+    prompt: String,
+}
+
+impl ChangelogRequest {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+        }
+    }
+}
+
+impl LlmRequest for ChangelogRequest {
+    type Output = ChangelogOutput;
+
+    fn task(&self) -> LlmTask {
+        LlmTask::Changelog
+    }
+
+    fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    fn parse(&self, response: LlmResponse) -> Result<ChangelogOutput, String> {
+        changelog_output(response)
+    }
+}
+
+/// Drafted entries checked against the verification evidence.
+#[derive(Debug, Clone)]
+pub struct VerificationRequest {
+    prompt: String,
+}
+
+impl VerificationRequest {
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self {
+            prompt: prompt.into(),
+        }
+    }
+}
+
+impl LlmRequest for VerificationRequest {
+    type Output = ChangelogOutput;
+
+    fn task(&self) -> LlmTask {
+        LlmTask::Verification
+    }
+
+    fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    fn parse(&self, response: LlmResponse) -> Result<ChangelogOutput, String> {
+        changelog_output(response)
+    }
+}
+
+fn changelog_output(response: LlmResponse) -> Result<ChangelogOutput, String> {
+    match response {
+        LlmResponse::Changelog(output) => Ok(output),
+        LlmResponse::Text(text) => serde_json::from_str(&super::extract_json(&text))
+            .map_err(|e| format!("not a changelog document: {}", e)),
+    }
+}
+
+/// Free-form text, returned as is.
+#[derive(Debug, Clone)]
+pub struct TextRequest {
+    task: LlmTask,
+    prompt: String,
+}
+
+impl TextRequest {
+    /// A [`LlmTask::Text`] request.
+    pub fn new(prompt: impl Into<String>) -> Self {
+        Self::for_task(LlmTask::Text, prompt)
+    }
+
+    /// Text parsed by the caller, under the policy of `task`.
+    pub fn for_task(task: LlmTask, prompt: impl Into<String>) -> Self {
+        Self {
+            task,
+            prompt: prompt.into(),
+        }
+    }
+}
+
+impl LlmRequest for TextRequest {
+    type Output = String;
+
+    fn task(&self) -> LlmTask {
+        self.task
+    }
+
+    fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    fn parse(&self, response: LlmResponse) -> Result<String, String> {
+        let text = response.into_text();
+        if text.trim().is_empty() {
+            return Err("empty response".to_string());
+        }
+        Ok(text)
+    }
+}
+
+/// How the requests of one task are run.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskPolicy {
+    /// Provider tried first, with its usual fallback, instead of the run's
+    /// current provider.
+    pub provider: Option<Provider>,
+    /// Calls per provider until a response passes validation.
+    pub attempts: u32,
+    /// Limit for one provider call, on top of the provider's own timeout.
+    pub timeout: Option<Duration>,
+}
+
+impl TaskPolicy {
+    /// Built-in policy of `task`. Changelog documents are already validated
+    /// (and retried) by the providers; parsed text answers get one more try.
+    pub fn default_for(task: LlmTask) -> Self {
+        let attempts = match task {
+            LlmTask::Bump | LlmTask::Commit => 2,
+            LlmTask::Changelog | LlmTask::Verification | LlmTask::Text => 1,
+        };
+        Self {
+            provider: None,
+            attempts,
+            timeout: None,
+        }
+    }
+}
+
+/// The policy of every task.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TaskPolicies {
+    /// Tasks configured in `keryx.toml`; the others use their defaults.
+    configured: HashMap<LlmTask, TaskPolicy>,
+}
+
+impl TaskPolicies {
+    pub fn get(&self, task: LlmTask) -> TaskPolicy {
+        self.configured
+            .get(&task)
+            .copied()
+            .unwrap_or_else(|| TaskPolicy::default_for(task))
+    }
+
+    pub fn set(&mut self, task: LlmTask, policy: TaskPolicy) {
+        self.configured.insert(task, policy);
+    }
+
+    /// The `[llm.tasks.<task>]` tables of `keryx.toml` in `root`.
+    ///
+    /// A missing file gives the defaults; an unreadable or invalid file is
+    /// logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid [llm.tasks] in {}: {}. Using the defaults.",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let mut policies = Self::default();
+        let Some(tasks) = doc.get("llm").and_then(|llm| llm.get("tasks")) else {
+            return Ok(policies);
+        };
+        let tasks = tasks.as_table_like().ok_or("llm.tasks must be a table")?;
+        for (name, item) in tasks.iter() {
+            let task: LlmTask = name.parse()?;
+            let table = item
+                .as_table_like()
+                .ok_or_else(|| format!("llm.tasks.{} must be a table", name))?;
+            let mut policy = TaskPolicy::default_for(task);
+            for (key, value) in table.iter() {
+                match key {
+                    "provider" => {
+                        policy.provider = Some(
+                            value
+                                .as_str()
+                                .ok_or_else(|| {
+                                    format!("llm.tasks.{}.provider must be a string", name)
+                                })?
+                                .parse()?,
+                        )
+                    }
+                    "attempts" => {
+                        policy.attempts = value
+                            .as_integer()
+                            .and_then(|n| u32::try_from(n).ok())
+                            .filter(|n| *n > 0)
+                            .ok_or_else(|| {
+                                format!("llm.tasks.{}.attempts must be a positive integer", name)
+                            })?
+                    }
+                    "timeout_secs" => {
+                        policy.timeout = Some(Duration::from_secs(
+                            value
+                                .as_integer()
+                                .and_then(|n| u64::try_from(n).ok())
+                                .filter(|n| *n > 0)
+                                .ok_or_else(|| {
+                                    format!(
+                                        "llm.tasks.{}.timeout_secs must be a positive integer",
+                                        name
+                                    )
+                                })?,
+                        ))
+                    }
+                    other => return Err(format!("unknown key llm.tasks.{}.{}", name, other)),
+                }
+            }
+            policies.set(task, policy);
+        }
+        Ok(policies)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_task_policies() {
+        let policies = TaskPolicies::parse(
+            "[llm]\nprovider = \"codex\"\n\n[llm.tasks.commit]\nprovider = \"ollama\"\nattempts = 3\n\n[llm.tasks.verification]\ntimeout_secs = 90\n",
+        )
+        .unwrap();
+        assert_eq!(
+            policies.get(LlmTask::Commit),
+            TaskPolicy {
+                provider: Some(Provider::Ollama),
+                attempts: 3,
+                timeout: None,
+            }
+        );
+        assert_eq!(
+            policies.get(LlmTask::Verification).timeout,
+            Some(Duration::from_secs(90))
+        );
+        assert_eq!(
+            policies.get(LlmTask::Bump),
+            TaskPolicy::default_for(LlmTask::Bump)
+        );
+
+        assert!(TaskPolicies::parse("[llm.tasks.digest]\nattempts = 1\n").is_err());
+        assert!(TaskPolicies::parse("[llm.tasks.bump]\nattempts = 0\n").is_err());
+        assert!(TaskPolicies::parse("[llm.tasks.bump]\nretries = 2\n").is_err());
+    }
+
+    #[test]
+    fn test_requests_validate_responses() {
+        let request = ChangelogRequest::new("prompt");
+        assert!(
+            request
+                .parse(LlmResponse::Text(
+                    "```json\n{\"entries\": []}\n```".to_string()
+                ))
+                .is_ok()
+        );
+        assert!(
+            request
+                .parse(LlmResponse::Text("no entries".to_string()))
+                .is_err()
+        );
+        let text = TextRequest::new("prompt");
+        assert_eq!(text.task(), LlmTask::Text);
+        assert!(text.parse(LlmResponse::Text("  \n".to_string())).is_err());
+    }
+}
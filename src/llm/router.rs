@@ -1,19 +1,32 @@
 //! Provider selection and fallback orchestration.
 
 use std::fmt;
+use std::time::Duration;
 
 use async_trait::async_trait;
 
+use crate::api::{self, ApiProvider};
 use crate::changelog::ChangelogOutput;
 use crate::claude;
 use crate::codex;
-use crate::error::{ClaudeError, CodexError};
+use crate::error::{ApiError, ClaudeError, CodexError, OllamaError};
+use crate::journal::{Journal, OperationKind};
+use crate::ollama;
+use crate::text::truncate_graphemes;
+
+use super::request::{LlmRequest, LlmResponse, ResponseFormat, TaskPolicies, TaskPolicy};
 
 /// Supported LLM providers.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Provider {
     Claude,
     Codex,
+    /// The Anthropic Messages API, called over HTTP with `ANTHROPIC_API_KEY`.
+    AnthropicApi,
+    /// The OpenAI Chat Completions API, called over HTTP with `OPENAI_API_KEY`.
+    OpenAiApi,
+    /// A model on a local (or self-hosted) Ollama server.
+    Ollama,
 }
 
 impl Provider {
@@ -21,6 +34,29 @@ impl Provider {
         match self {
             Provider::Claude => "Claude",
             Provider::Codex => "Codex",
+            Provider::AnthropicApi => "Anthropic API",
+            Provider::OpenAiApi => "OpenAI API",
+            Provider::Ollama => "Ollama",
+        }
+    }
+
+    /// Name used by `--provider` and `[llm] provider` in `keryx.toml`.
+    pub fn id(&self) -> &'static str {
+        match self {
+            Provider::Claude => "claude",
+            Provider::Codex => "codex",
+            Provider::AnthropicApi => "anthropic-api",
+            Provider::OpenAiApi => "openai-api",
+            Provider::Ollama => "ollama",
+        }
+    }
+
+    /// The HTTP API this provider calls, `None` for the CLI providers.
+    pub fn api(&self) -> Option<ApiProvider> {
+        match self {
+            Provider::Claude | Provider::Codex | Provider::Ollama => None,
+            Provider::AnthropicApi => Some(ApiProvider::Anthropic),
+            Provider::OpenAiApi => Some(ApiProvider::OpenAi),
         }
     }
 }
@@ -31,6 +67,24 @@ impl fmt::Display for Provider {
     }
 }
 
+impl std::str::FromStr for Provider {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "claude" => Ok(Provider::Claude),
+            "codex" => Ok(Provider::Codex),
+            "anthropic-api" | "anthropic" => Ok(Provider::AnthropicApi),
+            "openai-api" | "openai" => Ok(Provider::OpenAiApi),
+            "ollama" => Ok(Provider::Ollama),
+            _ => Err(format!(
+                "unknown provider '{}' (expected 'claude', 'codex', 'anthropic-api', 'openai-api' or 'ollama')",
+                s
+            )),
+        }
+    }
+}
+
 /// Primary + fallback selection.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct ProviderSelection {
@@ -39,13 +93,24 @@ pub struct ProviderSelection {
 }
 
 impl ProviderSelection {
+    /// `primary` with the other provider of its kind as the fallback: the
+    /// other CLI for a CLI, the other HTTP API for an API. Ollama has no
+    /// fallback (it is its own), so prompts never leave the machine.
     pub fn from_primary(primary: Provider) -> Self {
         let fallback = match primary {
             Provider::Claude => Provider::Codex,
             Provider::Codex => Provider::Claude,
+            Provider::AnthropicApi => Provider::OpenAiApi,
+            Provider::OpenAiApi => Provider::AnthropicApi,
+            Provider::Ollama => Provider::Ollama,
         };
         Self { primary, fallback }
     }
+
+    /// Whether a failure of the primary is retried on another provider.
+    pub fn has_fallback(&self) -> bool {
+        self.primary != self.fallback
+    }
 }
 
 impl Default for ProviderSelection {
@@ -59,6 +124,19 @@ impl Default for ProviderSelection {
 pub enum LlmProviderError {
     Claude(ClaudeError),
     Codex(CodexError),
+    Api(ApiProvider, ApiError),
+    Ollama(OllamaError),
+    /// A call ran past the task's `timeout_secs`.
+    Timeout {
+        provider: Provider,
+        secs: u64,
+    },
+    /// The response failed the request's validation.
+    InvalidResponse {
+        provider: Provider,
+        message: String,
+        raw: String,
+    },
 }
 
 impl LlmProviderError {
@@ -66,6 +144,10 @@ impl LlmProviderError {
         match self {
             LlmProviderError::Claude(_) => Provider::Claude,
             LlmProviderError::Codex(_) => Provider::Codex,
+            LlmProviderError::Api(api, _) => api.provider(),
+            LlmProviderError::Ollama(_) => Provider::Ollama,
+            LlmProviderError::Timeout { provider, .. }
+            | LlmProviderError::InvalidResponse { provider, .. } => *provider,
         }
     }
 
@@ -73,6 +155,14 @@ impl LlmProviderError {
         match self {
             LlmProviderError::Claude(err) => summarize_claude_error(err),
             LlmProviderError::Codex(err) => summarize_codex_error(err),
+            LlmProviderError::Api(api, err) => summarize_api_error(*api, err),
+            LlmProviderError::Ollama(err) => summarize_ollama_error(err),
+            LlmProviderError::Timeout { provider, secs } => {
+                format!("{} timed out after {}s", provider, secs)
+            }
+            LlmProviderError::InvalidResponse {
+                provider, message, ..
+            } => format!("{} returned an invalid response: {}", provider, message),
         }
     }
 
@@ -80,6 +170,19 @@ impl LlmProviderError {
         match self {
             LlmProviderError::Claude(err) => err.to_string(),
             LlmProviderError::Codex(err) => err.to_string(),
+            LlmProviderError::Api(api, err) => format!("{}: {}", api, err),
+            LlmProviderError::Ollama(err) => err.to_string(),
+            LlmProviderError::Timeout { .. } => self.summary(),
+            LlmProviderError::InvalidResponse {
+                provider,
+                message,
+                raw,
+            } => format!(
+                "{} returned an invalid response: {}. Response: {}",
+                provider,
+                message,
+                truncate_graphemes(raw, 500)
+            ),
         }
     }
 }
@@ -104,26 +207,34 @@ impl From<CodexError> for LlmProviderError {
     }
 }
 
+impl From<OllamaError> for LlmProviderError {
+    fn from(err: OllamaError) -> Self {
+        LlmProviderError::Ollama(err)
+    }
+}
+
 /// LLM orchestration error.
 #[derive(Debug)]
 pub enum LlmError {
+    /// A provider without a fallback failed.
+    ProviderFailed {
+        provider: Provider,
+        error: LlmProviderError,
+    },
     AllProvidersFailed {
         primary: Provider,
         primary_error: LlmProviderError,
         fallback: Provider,
         fallback_error: LlmProviderError,
     },
-    /// The LLM returned a response that could not be parsed as valid JSON.
-    ResponseParseFailed {
-        provider: Provider,
-        raw_output: String,
-        parse_error: String,
-    },
 }
 
 impl LlmError {
     pub fn summary(&self) -> String {
         match self {
+            LlmError::ProviderFailed { provider, error } => {
+                format!("{} failed: {}.", provider, error.summary())
+            }
             LlmError::AllProvidersFailed {
                 primary,
                 primary_error,
@@ -136,16 +247,14 @@ impl LlmError {
                 fallback,
                 fallback_error.summary()
             ),
-            LlmError::ResponseParseFailed {
-                provider,
-                parse_error,
-                ..
-            } => format!("{} returned unparseable output: {}", provider, parse_error),
         }
     }
 
     pub fn detailed(&self) -> String {
         match self {
+            LlmError::ProviderFailed { provider, error } => {
+                format!("{} failed: {}.", provider, error.detail())
+            }
             LlmError::AllProvidersFailed {
                 primary,
                 primary_error,
@@ -158,31 +267,20 @@ impl LlmError {
                 fallback,
                 fallback_error.detail()
             ),
-            LlmError::ResponseParseFailed {
-                provider,
-                raw_output,
-                parse_error,
-            } => {
-                let truncated: String = raw_output.chars().take(500).collect();
-                format!(
-                    "{} returned unparseable output. Parse error: {}. Response: {}",
-                    provider, parse_error, truncated
-                )
-            }
         }
     }
 
     pub fn primary_error(&self) -> Option<&LlmProviderError> {
         match self {
+            LlmError::ProviderFailed { error, .. } => Some(error),
             LlmError::AllProvidersFailed { primary_error, .. } => Some(primary_error),
-            LlmError::ResponseParseFailed { .. } => None,
         }
     }
 
     pub fn fallback_error(&self) -> Option<&LlmProviderError> {
         match self {
             LlmError::AllProvidersFailed { fallback_error, .. } => Some(fallback_error),
-            LlmError::ResponseParseFailed { .. } => None,
+            LlmError::ProviderFailed { .. } => None,
         }
     }
 }
@@ -206,13 +304,13 @@ pub struct LlmCompletion<T = ChangelogOutput> {
 pub type LlmRawCompletion = LlmCompletion<String>;
 
 #[async_trait]
-trait ProviderRunner {
+trait ProviderRunner: Sync {
     async fn run(
         &self,
         provider: Provider,
+        format: ResponseFormat,
         prompt: &str,
-    ) -> Result<ChangelogOutput, LlmProviderError>;
-    async fn run_raw(&self, provider: Provider, prompt: &str) -> Result<String, LlmProviderError>;
+    ) -> Result<LlmResponse, LlmProviderError>;
 }
 
 struct DefaultRunner;
@@ -222,34 +320,82 @@ impl ProviderRunner for DefaultRunner {
     async fn run(
         &self,
         provider: Provider,
+        format: ResponseFormat,
         prompt: &str,
-    ) -> Result<ChangelogOutput, LlmProviderError> {
-        match provider {
-            Provider::Claude => claude::generate_with_retry(prompt)
-                .await
-                .map_err(LlmProviderError::from),
-            Provider::Codex => codex::generate_with_retry(prompt)
+    ) -> Result<LlmResponse, LlmProviderError> {
+        match format {
+            ResponseFormat::Changelog => run_structured(provider, prompt)
                 .await
-                .map_err(LlmProviderError::from),
+                .map(LlmResponse::Changelog),
+            ResponseFormat::Text => run_text(provider, prompt).await.map(LlmResponse::Text),
         }
     }
+}
 
-    async fn run_raw(&self, provider: Provider, prompt: &str) -> Result<String, LlmProviderError> {
-        match provider {
-            Provider::Claude => claude::generate_raw_with_retry(prompt)
+async fn run_structured(
+    provider: Provider,
+    prompt: &str,
+) -> Result<ChangelogOutput, LlmProviderError> {
+    match provider {
+        Provider::Claude => claude::generate_with_retry(prompt)
+            .await
+            .map_err(LlmProviderError::from),
+        Provider::Codex => codex::generate_with_retry(prompt)
+            .await
+            .map_err(LlmProviderError::from),
+        Provider::AnthropicApi | Provider::OpenAiApi => {
+            let api = provider.api().expect("API provider");
+            api::generate_with_retry(api, prompt)
                 .await
-                .map_err(LlmProviderError::from),
-            Provider::Codex => codex::generate_raw_with_retry(prompt)
+                .map_err(|e| LlmProviderError::Api(api, e))
+        }
+        Provider::Ollama => ollama::generate_with_retry(prompt)
+            .await
+            .map_err(LlmProviderError::from),
+    }
+}
+
+async fn run_text(provider: Provider, prompt: &str) -> Result<String, LlmProviderError> {
+    match provider {
+        Provider::Claude => claude::generate_raw_with_retry(prompt)
+            .await
+            .map_err(LlmProviderError::from),
+        Provider::Codex => codex::generate_raw_with_retry(prompt)
+            .await
+            .map_err(LlmProviderError::from),
+        Provider::AnthropicApi | Provider::OpenAiApi => {
+            let api = provider.api().expect("API provider");
+            api::generate_raw_with_retry(api, prompt)
                 .await
-                .map_err(LlmProviderError::from),
+                .map_err(|e| LlmProviderError::Api(api, e))
         }
+        Provider::Ollama => ollama::generate_raw_with_retry(prompt)
+            .await
+            .map_err(LlmProviderError::from),
     }
 }
 
+/// Provider usage accumulated by an [`LlmRouter`] over its lifetime.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct LlmUsage {
+    /// Provider invocations, including failed ones.
+    pub calls: usize,
+    /// Generations that only succeeded on the fallback provider.
+    pub fallbacks: usize,
+    /// Provider that produced the most recent successful generation.
+    pub provider: Option<Provider>,
+}
+
 /// Provider router with fallback and stickiness.
+///
+/// A task whose policy names a provider runs on that provider (and its
+/// fallback) without changing the router's own selection.
 pub struct LlmRouter {
     primary: Provider,
     fallback: Provider,
+    journal: Option<Journal>,
+    policies: TaskPolicies,
+    usage: LlmUsage,
 }
 
 impl LlmRouter {
@@ -257,6 +403,41 @@ impl LlmRouter {
         Self {
             primary: selection.primary,
             fallback: selection.fallback,
+            journal: None,
+            policies: TaskPolicies::default(),
+            usage: LlmUsage::default(),
+        }
+    }
+
+    /// Record every provider invocation in the given journal.
+    pub fn with_journal(mut self, journal: Option<Journal>) -> Self {
+        self.journal = journal;
+        self
+    }
+
+    /// Run requests under the given per-task policies.
+    pub fn with_policies(mut self, policies: TaskPolicies) -> Self {
+        self.policies = policies;
+        self
+    }
+
+    fn journal_call(
+        &self,
+        task: &str,
+        provider: Provider,
+        prompt: &str,
+        error: Option<&LlmProviderError>,
+    ) {
+        if let Some(journal) = &self.journal {
+            let mut entry = journal
+                .entry(OperationKind::LlmCall)
+                .arg(provider.as_str())
+                .arg(format!("task={}", task))
+                .arg(format!("prompt_bytes={}", prompt.len()));
+            if let Some(err) = error {
+                entry = entry.detail(format!("failed: {}", err.summary()));
+            }
+            journal.record(entry);
         }
     }
 
@@ -268,46 +449,55 @@ impl LlmRouter {
         self.fallback
     }
 
-    pub async fn generate(&mut self, prompt: &str) -> Result<LlmCompletion, LlmError> {
-        let runner = DefaultRunner;
-        self.try_with_fallback(prompt, |r, p, pr| Box::pin(r.run(p, pr)), &runner)
-            .await
+    /// Provider calls and fallbacks made through this router so far.
+    pub fn usage(&self) -> LlmUsage {
+        self.usage
     }
 
-    pub async fn generate_raw(&mut self, prompt: &str) -> Result<LlmRawCompletion, LlmError> {
-        let runner = DefaultRunner;
-        self.try_with_fallback(prompt, |r, p, pr| Box::pin(r.run_raw(p, pr)), &runner)
-            .await
+    /// Run `request` on the task's provider, falling back to the other one,
+    /// and return its validated output.
+    pub async fn request<R: LlmRequest>(
+        &mut self,
+        request: &R,
+    ) -> Result<LlmCompletion<R::Output>, LlmError> {
+        self.request_with_runner(request, &DefaultRunner).await
     }
 
-    async fn try_with_fallback<T, R, F>(
+    async fn request_with_runner<R: LlmRequest, P: ProviderRunner>(
         &mut self,
-        prompt: &str,
-        run_fn: F,
-        runner: &R,
-    ) -> Result<LlmCompletion<T>, LlmError>
-    where
-        F: for<'a> Fn(
-            &'a R,
-            Provider,
-            &'a str,
-        ) -> std::pin::Pin<
-            Box<dyn std::future::Future<Output = Result<T, LlmProviderError>> + Send + 'a>,
-        >,
-    {
-        let primary = self.primary;
-        let fallback = self.fallback;
-
-        match run_fn(runner, primary, prompt).await {
-            Ok(output) => Ok(LlmCompletion {
-                output,
+        request: &R,
+        runner: &P,
+    ) -> Result<LlmCompletion<R::Output>, LlmError> {
+        let policy = self.policies.get(request.task());
+        let (primary, fallback) = match policy.provider {
+            Some(provider) => {
+                let selection = ProviderSelection::from_primary(provider);
+                (selection.primary, selection.fallback)
+            }
+            None => (self.primary, self.fallback),
+        };
+
+        match self.attempt(request, policy, primary, runner).await {
+            Ok(output) => {
+                self.usage.provider = Some(primary);
+                Ok(LlmCompletion {
+                    output,
+                    provider: primary,
+                    primary_error: None,
+                })
+            }
+            Err(error) if primary == fallback => Err(LlmError::ProviderFailed {
                 provider: primary,
-                primary_error: None,
+                error,
             }),
-            Err(primary_error) => match run_fn(runner, fallback, prompt).await {
+            Err(primary_error) => match self.attempt(request, policy, fallback, runner).await {
                 Ok(output) => {
-                    self.primary = fallback;
-                    self.fallback = primary;
+                    if policy.provider.is_none() {
+                        self.primary = fallback;
+                        self.fallback = primary;
+                    }
+                    self.usage.fallbacks += 1;
+                    self.usage.provider = Some(fallback);
                     Ok(LlmCompletion {
                         output,
                         provider: fallback,
@@ -324,25 +514,54 @@ impl LlmRouter {
         }
     }
 
-    // Test-only entry points that accept a custom runner.
-    #[cfg(test)]
-    async fn generate_with_runner<R: ProviderRunner>(
+    /// Call `provider` until a response passes validation or the policy's
+    /// attempts run out. Other failures are returned at once: providers
+    /// already retry their own transient errors.
+    async fn attempt<R: LlmRequest, P: ProviderRunner>(
         &mut self,
-        prompt: &str,
-        runner: &R,
-    ) -> Result<LlmCompletion, LlmError> {
-        self.try_with_fallback(prompt, |r, p, pr| Box::pin(r.run(p, pr)), runner)
-            .await
+        request: &R,
+        policy: TaskPolicy,
+        provider: Provider,
+        runner: &P,
+    ) -> Result<R::Output, LlmProviderError> {
+        let task = request.task();
+        let prompt = request.prompt();
+        let mut last_error = None;
+        for _ in 0..policy.attempts.max(1) {
+            let result = match policy.timeout {
+                Some(limit) => {
+                    tokio::time::timeout(limit, runner.run(provider, task.format(), prompt))
+                        .await
+                        .unwrap_or_else(|_| Err(timeout_error(provider, limit)))
+                }
+                None => runner.run(provider, task.format(), prompt).await,
+            }
+            .and_then(|response| {
+                request.parse(response.clone()).map_err(|message| {
+                    LlmProviderError::InvalidResponse {
+                        provider,
+                        message,
+                        raw: response.into_text(),
+                    }
+                })
+            });
+            self.journal_call(task.id(), provider, prompt, result.as_ref().err());
+            self.usage.calls += 1;
+
+            match result {
+                Ok(output) => return Ok(output),
+                Err(error @ LlmProviderError::InvalidResponse { .. }) => last_error = Some(error),
+                Err(error) => return Err(error),
+            }
+        }
+        Err(last_error.expect("at least one attempt"))
     }
+}
 
-    #[cfg(test)]
-    async fn generate_raw_with_runner<R: ProviderRunner>(
-        &mut self,
-        prompt: &str,
-        runner: &R,
-    ) -> Result<LlmRawCompletion, LlmError> {
-        self.try_with_fallback(prompt, |r, p, pr| Box::pin(r.run_raw(p, pr)), runner)
-            .await
+fn timeout_error(provider: Provider, limit: Duration) -> LlmProviderError {
+    LlmProviderError::Timeout {
+        provider,
+        secs: limit.as_secs(),
     }
 }
 
@@ -372,9 +591,45 @@ fn summarize_codex_error(err: &CodexError) -> String {
     }
 }
 
+fn summarize_api_error(api: ApiProvider, err: &ApiError) -> String {
+    match err {
+        ApiError::MissingKey(var) => format!("{} is not set", var),
+        ApiError::RequestFailed(_) => format!("{} request failed", api),
+        ApiError::Timeout(secs) => format!("{} timed out after {}s", api, secs),
+        ApiError::Status { status, .. } => format!("{} returned HTTP {}", api, status),
+        ApiError::EmptyResponse => format!("{} returned no text", api),
+        ApiError::InvalidJson(_) => format!("{} returned invalid JSON", api),
+        ApiError::RetriesExhausted(_) => format!("{} failed after retries", api),
+    }
+}
+
+fn summarize_ollama_error(err: &OllamaError) -> String {
+    match err {
+        OllamaError::NotRunning(url) => format!("Ollama is not running at {}", url),
+        OllamaError::ModelNotFound(model) => format!("Ollama model '{}' is not pulled", model),
+        OllamaError::RequestFailed(_) => "Ollama request failed".to_string(),
+        OllamaError::Timeout(secs) => format!("Ollama timed out after {}s", secs),
+        OllamaError::Status { status, .. } => format!("Ollama returned HTTP {}", status),
+        OllamaError::EmptyResponse => "Ollama returned no text".to_string(),
+        OllamaError::InvalidJson(_) => "Ollama returned invalid JSON".to_string(),
+        // Retrying does not change a missing server or model
+        OllamaError::RetriesExhausted(inner)
+            if matches!(
+                **inner,
+                OllamaError::NotRunning(_) | OllamaError::ModelNotFound(_)
+            ) =>
+        {
+            summarize_ollama_error(inner)
+        }
+        OllamaError::RetriesExhausted(_) => "Ollama failed after retries".to_string(),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::llm::request::{ChangelogRequest, LlmTask, TextRequest};
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     struct FakeRunner {
         claude_ok: bool,
@@ -386,38 +641,81 @@ mod tests {
         async fn run(
             &self,
             provider: Provider,
+            format: ResponseFormat,
             _prompt: &str,
-        ) -> Result<ChangelogOutput, LlmProviderError> {
-            match provider {
-                Provider::Claude if self.claude_ok => Ok(ChangelogOutput {
-                    entries: Vec::new(),
-                }),
-                Provider::Codex if self.codex_ok => Ok(ChangelogOutput {
+        ) -> Result<LlmResponse, LlmProviderError> {
+            let ok = match format {
+                ResponseFormat::Changelog => LlmResponse::Changelog(ChangelogOutput {
                     entries: Vec::new(),
+                    highlights: None,
                 }),
+                ResponseFormat::Text => {
+                    LlmResponse::Text(r#"{"bump_type": "minor", "reasoning": "test"}"#.to_string())
+                }
+            };
+            match provider {
+                Provider::Claude if self.claude_ok => Ok(ok),
+                Provider::Codex if self.codex_ok => Ok(ok),
                 Provider::Claude => Err(LlmProviderError::Claude(ClaudeError::NotInstalled)),
                 Provider::Codex => Err(LlmProviderError::Codex(CodexError::NotInstalled)),
+                Provider::AnthropicApi | Provider::OpenAiApi => {
+                    let api = provider.api().unwrap();
+                    Err(LlmProviderError::Api(
+                        api,
+                        ApiError::MissingKey(api.key_env()),
+                    ))
+                }
+                Provider::Ollama => Err(LlmProviderError::Ollama(OllamaError::NotRunning(
+                    ollama::DEFAULT_BASE_URL.to_string(),
+                ))),
             }
         }
+    }
+
+    /// Answers with blank text a given number of times, then with text.
+    struct FlakyRunner {
+        blank_answers: usize,
+        calls: AtomicUsize,
+    }
 
-        async fn run_raw(
+    #[async_trait]
+    impl ProviderRunner for FlakyRunner {
+        async fn run(
             &self,
-            provider: Provider,
+            _provider: Provider,
+            _format: ResponseFormat,
             _prompt: &str,
-        ) -> Result<String, LlmProviderError> {
-            match provider {
-                Provider::Claude if self.claude_ok => {
-                    Ok(r#"{"bump_type": "minor", "reasoning": "test"}"#.to_string())
-                }
-                Provider::Codex if self.codex_ok => {
-                    Ok(r#"{"bump_type": "minor", "reasoning": "test"}"#.to_string())
-                }
-                Provider::Claude => Err(LlmProviderError::Claude(ClaudeError::NotInstalled)),
-                Provider::Codex => Err(LlmProviderError::Codex(CodexError::NotInstalled)),
+        ) -> Result<LlmResponse, LlmProviderError> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call < self.blank_answers {
+                Ok(LlmResponse::Text(String::new()))
+            } else {
+                Ok(LlmResponse::Text("fine".to_string()))
             }
         }
     }
 
+    struct SlowRunner;
+
+    #[async_trait]
+    impl ProviderRunner for SlowRunner {
+        async fn run(
+            &self,
+            _provider: Provider,
+            _format: ResponseFormat,
+            _prompt: &str,
+        ) -> Result<LlmResponse, LlmProviderError> {
+            tokio::time::sleep(Duration::from_secs(60)).await;
+            Ok(LlmResponse::Text("late".to_string()))
+        }
+    }
+
+    fn policies(task: LlmTask, policy: TaskPolicy) -> TaskPolicies {
+        let mut policies = TaskPolicies::default();
+        policies.set(task, policy);
+        policies
+    }
+
     #[test]
     fn default_selection_is_claude_then_codex() {
         let selection = ProviderSelection::default();
@@ -432,6 +730,44 @@ mod tests {
         assert_eq!(selection.fallback, Provider::Claude);
     }
 
+    #[test]
+    fn api_selection_falls_back_to_the_other_api() {
+        let selection = ProviderSelection::from_primary(Provider::OpenAiApi);
+        assert_eq!(selection.fallback, Provider::AnthropicApi);
+        assert_eq!(
+            "anthropic-api".parse::<Provider>(),
+            Ok(Provider::AnthropicApi)
+        );
+        assert_eq!("OpenAI".parse::<Provider>(), Ok(Provider::OpenAiApi));
+    }
+
+    #[tokio::test]
+    async fn ollama_has_no_fallback() {
+        let selection = ProviderSelection::from_primary(Provider::Ollama);
+        assert!(!selection.has_fallback());
+        let mut router = LlmRouter::new(selection);
+        let runner = FakeRunner {
+            claude_ok: true,
+            codex_ok: true,
+        };
+
+        match router
+            .request_with_runner(&ChangelogRequest::new("test"), &runner)
+            .await
+        {
+            Err(LlmError::ProviderFailed { provider, error }) => {
+                assert_eq!(provider, Provider::Ollama);
+                assert_eq!(
+                    error.summary(),
+                    "Ollama is not running at http://localhost:11434"
+                );
+            }
+            Err(other) => panic!("expected ProviderFailed, got {}", other),
+            Ok(_) => panic!("expected ProviderFailed"),
+        }
+        assert_eq!(router.usage().calls, 1);
+    }
+
     #[tokio::test]
     async fn router_swaps_primary_after_fallback_success() {
         let mut router = LlmRouter::new(ProviderSelection::default());
@@ -440,21 +776,53 @@ mod tests {
             codex_ok: true,
         };
 
-        let result = router.generate_with_runner("test", &runner).await;
+        let result = router
+            .request_with_runner(&ChangelogRequest::new("test"), &runner)
+            .await;
         assert!(result.is_ok());
         assert_eq!(router.primary(), Provider::Codex);
         assert_eq!(router.fallback(), Provider::Claude);
     }
 
     #[tokio::test]
-    async fn generate_raw_succeeds_on_primary() {
+    async fn router_tracks_usage_across_calls() {
+        let mut router = LlmRouter::new(ProviderSelection::default());
+        let failing_primary = FakeRunner {
+            claude_ok: false,
+            codex_ok: true,
+        };
+
+        router
+            .request_with_runner(&ChangelogRequest::new("test"), &failing_primary)
+            .await
+            .unwrap();
+        // Codex is now primary, so the second call succeeds first try.
+        router
+            .request_with_runner(&ChangelogRequest::new("test"), &failing_primary)
+            .await
+            .unwrap();
+
+        assert_eq!(
+            router.usage(),
+            LlmUsage {
+                calls: 3,
+                fallbacks: 1,
+                provider: Some(Provider::Codex),
+            }
+        );
+    }
+
+    #[tokio::test]
+    async fn text_request_succeeds_on_primary() {
         let mut router = LlmRouter::new(ProviderSelection::default());
         let runner = FakeRunner {
             claude_ok: true,
             codex_ok: true,
         };
 
-        let result = router.generate_raw_with_runner("test", &runner).await;
+        let result = router
+            .request_with_runner(&TextRequest::new("test"), &runner)
+            .await;
         assert!(result.is_ok());
         let completion = result.unwrap();
         assert_eq!(completion.provider, Provider::Claude);
@@ -463,14 +831,16 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn generate_raw_falls_back_on_primary_failure() {
+    async fn text_request_falls_back_on_primary_failure() {
         let mut router = LlmRouter::new(ProviderSelection::default());
         let runner = FakeRunner {
             claude_ok: false,
             codex_ok: true,
         };
 
-        let result = router.generate_raw_with_runner("test", &runner).await;
+        let result = router
+            .request_with_runner(&TextRequest::new("test"), &runner)
+            .await;
         assert!(result.is_ok());
         let completion = result.unwrap();
         assert_eq!(completion.provider, Provider::Codex);
@@ -479,14 +849,113 @@ mod tests {
     }
 
     #[tokio::test]
-    async fn generate_raw_fails_when_both_fail() {
+    async fn text_request_fails_when_both_fail() {
         let mut router = LlmRouter::new(ProviderSelection::default());
         let runner = FakeRunner {
             claude_ok: false,
             codex_ok: false,
         };
 
-        let result = router.generate_raw_with_runner("test", &runner).await;
+        let result = router
+            .request_with_runner(&TextRequest::new("test"), &runner)
+            .await;
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn task_provider_does_not_change_the_selection() {
+        let mut router = LlmRouter::new(ProviderSelection::default()).with_policies(policies(
+            LlmTask::Commit,
+            TaskPolicy {
+                provider: Some(Provider::Codex),
+                ..TaskPolicy::default_for(LlmTask::Commit)
+            },
+        ));
+        let runner = FakeRunner {
+            claude_ok: true,
+            codex_ok: true,
+        };
+
+        let completion = router
+            .request_with_runner(&TextRequest::for_task(LlmTask::Commit, "test"), &runner)
+            .await
+            .unwrap();
+        assert_eq!(completion.provider, Provider::Codex);
+        assert_eq!(router.primary(), Provider::Claude);
+
+        // A fallback inside the task's selection is not sticky either
+        let runner = FakeRunner {
+            claude_ok: true,
+            codex_ok: false,
+        };
+        let completion = router
+            .request_with_runner(&TextRequest::for_task(LlmTask::Commit, "test"), &runner)
+            .await
+            .unwrap();
+        assert_eq!(completion.provider, Provider::Claude);
+        assert_eq!(router.primary(), Provider::Claude);
+    }
+
+    #[tokio::test]
+    async fn invalid_responses_are_retried_up_to_the_attempts() {
+        let mut router = LlmRouter::new(ProviderSelection::from_primary(Provider::Ollama))
+            .with_policies(policies(
+                LlmTask::Text,
+                TaskPolicy {
+                    attempts: 3,
+                    ..TaskPolicy::default_for(LlmTask::Text)
+                },
+            ));
+        let runner = FlakyRunner {
+            blank_answers: 2,
+            calls: AtomicUsize::new(0),
+        };
+        let completion = router
+            .request_with_runner(&TextRequest::new("test"), &runner)
+            .await
+            .unwrap();
+        assert_eq!(completion.output, "fine");
+        assert_eq!(router.usage().calls, 3);
+
+        let runner = FlakyRunner {
+            blank_answers: 3,
+            calls: AtomicUsize::new(0),
+        };
+        match router
+            .request_with_runner(&TextRequest::new("test"), &runner)
+            .await
+        {
+            Err(LlmError::ProviderFailed {
+                error: LlmProviderError::InvalidResponse { message, .. },
+                ..
+            }) => assert_eq!(message, "empty response"),
+            Err(other) => panic!("expected an invalid response, got {}", other),
+            Ok(_) => panic!("expected an invalid response"),
+        }
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn slow_calls_time_out() {
+        let mut router = LlmRouter::new(ProviderSelection::from_primary(Provider::Ollama))
+            .with_policies(policies(
+                LlmTask::Verification,
+                TaskPolicy {
+                    timeout: Some(Duration::from_secs(5)),
+                    ..TaskPolicy::default_for(LlmTask::Verification)
+                },
+            ));
+        let result = router
+            .request_with_runner(
+                &crate::llm::request::VerificationRequest::new("test"),
+                &SlowRunner,
+            )
+            .await;
+        match result {
+            Err(LlmError::ProviderFailed { error, .. }) => {
+                assert_eq!(error.summary(), "Ollama timed out after 5s")
+            }
+            Err(other) => panic!("expected a timeout, got {}", other),
+            Ok(_) => panic!("expected a timeout"),
+        }
+    }
 }
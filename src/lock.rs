@@ -0,0 +1,259 @@
+//! Repository lock against concurrent runs.
+//!
+//! Two keryx processes in one repository (say, a developer and a CI job)
+//! would interleave index changes, commits and changelog writes. Commands
+//! that change the repository first take `.keryx/lock`, which records the
+//! holder's process id. The file is created complete or not at all, so a
+//! crash never leaves a half-written lock, and a lock whose process is gone
+//! is taken over with a warning instead of blocking every later run.
+
+use std::fs;
+use std::io::{ErrorKind, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::error::LockError;
+use crate::journal::{JOURNAL_DIR, ensure_state_dir};
+
+/// File name of the lock inside [`JOURNAL_DIR`].
+pub const LOCK_FILE: &str = "lock";
+
+/// Who holds a lock, as stored in the lock file.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LockOwner {
+    pub pid: u32,
+    /// Command name, as in the journal.
+    pub command: String,
+    pub started: DateTime<Utc>,
+}
+
+impl LockOwner {
+    fn current(command: &str) -> Self {
+        Self {
+            pid: std::process::id(),
+            command: command.to_string(),
+            started: Utc::now(),
+        }
+    }
+
+    /// Whether the owning process still runs. Where that cannot be told, a
+    /// lock counts as held.
+    fn is_alive(&self) -> bool {
+        if self.pid == std::process::id() {
+            return true;
+        }
+        process_exists(self.pid)
+    }
+}
+
+#[cfg(unix)]
+fn process_exists(pid: u32) -> bool {
+    let Ok(pid) = libc::pid_t::try_from(pid) else {
+        return false;
+    };
+    // Signal 0 only checks whether the process exists; EPERM means it does,
+    // but belongs to another user
+    // SAFETY: kill with signal 0 sends nothing and has no side effects
+    if unsafe { libc::kill(pid, 0) } == 0 {
+        return true;
+    }
+    std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+#[cfg(not(unix))]
+fn process_exists(_pid: u32) -> bool {
+    true
+}
+
+/// The repository lock, released when dropped.
+#[derive(Debug)]
+pub struct RepoLock {
+    path: PathBuf,
+}
+
+impl RepoLock {
+    /// Take the lock of the repository at `root` for `command`.
+    ///
+    /// Fails with [`LockError::Held`] while another live process holds it.
+    pub fn acquire(root: &Path, command: &str) -> Result<Self, LockError> {
+        let dir = root.join(JOURNAL_DIR);
+        ensure_state_dir(&dir).map_err(|source| LockError::Io {
+            path: dir.clone(),
+            source,
+        })?;
+        let path = dir.join(LOCK_FILE);
+        let io_error = |source| LockError::Io {
+            path: path.clone(),
+            source,
+        };
+
+        let owner = LockOwner::current(command);
+        let json = serde_json::to_string(&owner).expect("lock owner serializes");
+        // Retries after taking over a stale lock
+        for _ in 0..3 {
+            let mut temp_file = tempfile::NamedTempFile::new_in(&dir).map_err(io_error)?;
+            temp_file.write_all(json.as_bytes()).map_err(io_error)?;
+            match temp_file.persist_noclobber(&path) {
+                Ok(_) => return Ok(Self { path }),
+                Err(e) if e.error.kind() == ErrorKind::AlreadyExists => {}
+                Err(e) => return Err(io_error(e.error)),
+            }
+
+            let stale = read_owner(&path);
+            match &stale {
+                Some(holder) if holder.is_alive() => {
+                    return Err(LockError::Held {
+                        path,
+                        pid: holder.pid,
+                        command: holder.command.clone(),
+                        started: holder.started,
+                    });
+                }
+                Some(holder) => warn!(
+                    "Removing stale lock {} left by keryx {} (pid {}, started {})",
+                    path.display(),
+                    holder.command,
+                    holder.pid,
+                    holder.started
+                ),
+                None => warn!("Removing unreadable lock {}", path.display()),
+            }
+            take_over(&path, stale.as_ref()).map_err(io_error)?;
+        }
+        Err(io_error(std::io::Error::new(
+            ErrorKind::AlreadyExists,
+            "another process took the lock",
+        )))
+    }
+
+    /// Take the lock of `repo`'s working tree; `None` for a bare repository.
+    pub fn for_repository(
+        repo: &git2::Repository,
+        command: &str,
+    ) -> Result<Option<Self>, LockError> {
+        repo.workdir()
+            .map(|root| Self::acquire(root, command))
+            .transpose()
+    }
+
+    /// Path of the lock file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+}
+
+impl Drop for RepoLock {
+    fn drop(&mut self) {
+        // Leave the file alone if a stale-lock takeover replaced it
+        if read_owner(&self.path).is_some_and(|owner| owner.pid == std::process::id())
+            && let Err(e) = fs::remove_file(&self.path)
+        {
+            warn!("Failed to remove lock {}: {}", self.path.display(), e);
+        }
+    }
+}
+
+/// Remove the lock at `path` if it still belongs to `stale`.
+///
+/// Another process may take over the same stale lock and create a fresh
+/// one between our check and the removal, so the lock is first renamed
+/// aside (only one process can do that) and its owner compared again. A
+/// lock that changed hands is put back unless yet another one was created
+/// meanwhile; the caller's next attempt then finds it held.
+fn take_over(path: &Path, stale: Option<&LockOwner>) -> std::io::Result<()> {
+    let aside = path.with_extension(format!("stale-{}", std::process::id()));
+    match fs::rename(path, &aside) {
+        Ok(()) => {}
+        Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+        Err(e) => return Err(e),
+    }
+    if read_owner(&aside).as_ref() != stale {
+        match fs::hard_link(&aside, path) {
+            Ok(()) => {}
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => {}
+            Err(e) => return Err(e),
+        }
+    }
+    fs::remove_file(&aside)
+}
+
+/// The owner recorded in the lock file at `path`, if it can be read.
+fn read_owner(path: &Path) -> Option<LockOwner> {
+    let content = fs::read_to_string(path).ok()?;
+    serde_json::from_str(&content).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_lock_is_exclusive_and_released_on_drop() {
+        let dir = TempDir::new().unwrap();
+        let lock = RepoLock::acquire(dir.path(), "ship").unwrap();
+
+        match RepoLock::acquire(dir.path(), "generate") {
+            Err(LockError::Held { command, pid, .. }) => {
+                assert_eq!(command, "ship");
+                assert_eq!(pid, std::process::id());
+            }
+            other => panic!("expected a held lock, got {:?}", other),
+        }
+
+        let path = lock.path().to_path_buf();
+        drop(lock);
+        assert!(!path.exists());
+        RepoLock::acquire(dir.path(), "generate").unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_stale_lock_is_taken_over() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(JOURNAL_DIR).join(LOCK_FILE);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+
+        // A child that has exited and been reaped leaves no process behind
+        let mut child = std::process::Command::new("true").spawn().unwrap();
+        let pid = child.id();
+        child.wait().unwrap();
+        let stale = LockOwner {
+            pid,
+            ..LockOwner::current("ship")
+        };
+        fs::write(&path, serde_json::to_string(&stale).unwrap()).unwrap();
+
+        let lock = RepoLock::acquire(dir.path(), "generate").unwrap();
+        assert_eq!(read_owner(lock.path()).unwrap().command, "generate");
+
+        fs::write(&path, "{\"pid\":").unwrap();
+        drop(lock);
+        // A lock that is not ours is left alone
+        assert!(path.exists());
+        RepoLock::acquire(dir.path(), "generate").unwrap();
+    }
+
+    #[test]
+    fn test_take_over_keeps_a_lock_that_changed_hands() {
+        let dir = TempDir::new().unwrap();
+        let path = dir.path().join(LOCK_FILE);
+        let stale = LockOwner {
+            pid: u32::MAX,
+            ..LockOwner::current("ship")
+        };
+        // Another process replaced the stale lock after we read it
+        let fresh = LockOwner::current("generate");
+        fs::write(&path, serde_json::to_string(&fresh).unwrap()).unwrap();
+
+        take_over(&path, Some(&stale)).unwrap();
+        assert_eq!(read_owner(&path), Some(fresh));
+
+        take_over(&path, read_owner(&path).as_ref()).unwrap();
+        assert!(!path.exists());
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 0);
+    }
+}
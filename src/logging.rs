@@ -0,0 +1,119 @@
+//! Log filtering for the `-v` flag and the `KERYX_LOG` environment variable.
+//!
+//! Each `-v` raises the level: warnings only by default, `-v` info, `-vv`
+//! debug, `-vvv` trace, which adds subprocess command lines and the time
+//! spent in each timed span. `KERYX_LOG` takes `tracing` filter directives
+//! (`keryx::verification=trace,octocrab=off`) applied on top of that level;
+//! a bare level in it (`KERYX_LOG=debug`) replaces the level from `-v`.
+
+use tracing::level_filters::LevelFilter;
+use tracing_subscriber::EnvFilter;
+use tracing_subscriber::fmt::format::FmtSpan;
+
+/// Environment variable holding per-module filter directives.
+pub const LOG_ENV: &str = "KERYX_LOG";
+
+/// Number of `-v` flags at which span timings are printed.
+const TIMING_VERBOSITY: u8 = 3;
+
+/// Level selected by the number of `-v` flags.
+pub fn verbosity_level(verbosity: u8) -> LevelFilter {
+    match verbosity {
+        0 => LevelFilter::WARN,
+        1 => LevelFilter::INFO,
+        2 => LevelFilter::DEBUG,
+        _ => LevelFilter::TRACE,
+    }
+}
+
+/// Combine the level from `-v` with the directives from `KERYX_LOG`.
+///
+/// The directives are appended to the level, so module directives refine it.
+/// A bare level among them replaces it.
+pub fn filter_directives(verbosity: u8, env: Option<&str>) -> String {
+    let directives: Vec<&str> = env
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|d| !d.is_empty())
+        .collect();
+
+    let has_level = directives.iter().any(|d| d.parse::<LevelFilter>().is_ok());
+
+    let mut parts = Vec::with_capacity(directives.len() + 1);
+    let base = verbosity_level(verbosity).to_string().to_lowercase();
+    if !has_level {
+        parts.push(base.as_str());
+    }
+    parts.extend(directives);
+    parts.join(",")
+}
+
+/// Install the global subscriber, writing to stderr.
+///
+/// An invalid `KERYX_LOG` is reported and ignored rather than aborting.
+pub fn init(verbosity: u8) {
+    let env = std::env::var(LOG_ENV).ok();
+    let filter = match EnvFilter::try_new(filter_directives(verbosity, env.as_deref())) {
+        Ok(filter) => filter,
+        Err(e) => {
+            eprintln!("Warning: ignoring invalid {}: {}", LOG_ENV, e);
+            EnvFilter::new(filter_directives(verbosity, None))
+        }
+    };
+
+    let span_events = if verbosity >= TIMING_VERBOSITY {
+        FmtSpan::CLOSE
+    } else {
+        FmtSpan::NONE
+    };
+
+    let subscriber = tracing_subscriber::fmt()
+        .with_env_filter(filter)
+        .with_span_events(span_events)
+        .with_writer(std::io::stderr)
+        .without_time()
+        .finish();
+    tracing::subscriber::set_global_default(subscriber).expect("Failed to set tracing subscriber");
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verbosity_levels() {
+        assert_eq!(verbosity_level(0), LevelFilter::WARN);
+        assert_eq!(verbosity_level(1), LevelFilter::INFO);
+        assert_eq!(verbosity_level(2), LevelFilter::DEBUG);
+        assert_eq!(verbosity_level(3), LevelFilter::TRACE);
+        assert_eq!(verbosity_level(7), LevelFilter::TRACE);
+    }
+
+    #[test]
+    fn test_filter_directives_without_env() {
+        assert_eq!(filter_directives(0, None), "warn");
+        assert_eq!(filter_directives(2, Some("")), "debug");
+    }
+
+    #[test]
+    fn test_filter_directives_appends_module_directives() {
+        assert_eq!(
+            filter_directives(1, Some("keryx::verification=trace, octocrab=off")),
+            "info,keryx::verification=trace,octocrab=off"
+        );
+    }
+
+    #[test]
+    fn test_filter_directives_bare_level_replaces_verbosity() {
+        assert_eq!(
+            filter_directives(3, Some("debug,keryx::git=trace")),
+            "debug,keryx::git=trace"
+        );
+    }
+
+    #[test]
+    fn test_filter_directives_parse() {
+        assert!(EnvFilter::try_new(filter_directives(2, Some("keryx::ship=trace"))).is_ok());
+    }
+}
@@ -1,6 +1,7 @@
 //! keryx - CLI entry point.
 
 use std::collections::HashMap;
+use std::io::IsTerminal;
 use std::path::PathBuf;
 use std::process::Stdio;
 use std::sync::mpsc::{self, Receiver, TryRecvError};
@@ -10,31 +11,110 @@ use anyhow::{Context, Result, bail};
 use clap::{CommandFactory, Parser, Subcommand, ValueEnum};
 use git2::Repository;
 use semver::Version;
+use serde::Serialize;
 use tokio::process::Command;
-use tracing::{Level, debug, warn};
-use tracing_subscriber::FmtSubscriber;
-
+use tracing::{debug, warn};
+
+use keryx::announce::email::write_message;
+use keryx::announce::{EmailConfig, mime_message, render_email, send_message};
+use keryx::changelog::aliases::normalize_changelog;
+use keryx::changelog::attribution::{build_attribution, cited_pr_numbers};
+use keryx::changelog::codename::suggest_codename;
+use keryx::changelog::date::{DateSource, DateTimezone, ReleaseDateConfig};
+use keryx::changelog::edit::{parse_edited, render_for_edit};
+use keryx::changelog::empty_guard::{expects_entries, generate_guarded};
 use keryx::changelog::format::CHANGELOG_HEADER;
-use keryx::changelog::{parser::read_changelog, write_changelog, writer::generate_summary};
+use keryx::changelog::heading::used_codenames;
+use keryx::changelog::sanitize::{SanitizeOptions, sanitize_output};
+use keryx::changelog::translations::{
+    TranslatedChangelog, compare_translation, detect_translations, insert_section,
+    translate_section, version_section, write_translation,
+};
+use keryx::changelog::{
+    AttributionConfig, CategoryAliases, CodenameConfig, CommitNotes, CompareLinks, CuratedNotes,
+    EntryConstraints, EntrySort, HeadingTemplate, HistoryCache, MigrationGuide, MigrationInput,
+    ProductContext, ReleaseAttribute, ReleaseMetadata, ReleaseTemplate, StyleGuide, format_entries,
+    format_version_section,
+    line_ending::normalize as normalize_line_endings,
+    merge::{merge_unreleased, unreleased_section},
+    merge_changelogs_with_metadata, merge_unreleased_changelogs,
+    migration::{
+        MIGRATIONS_FILE, build_migration_prompt, is_major_release, last_major_release,
+        write_migration_guide,
+    },
+    parser::{FROZEN_MARKER, ParsedChangelog, parse_changelog_content, read_changelog},
+    pr_notes::nothing_to_generate,
+    product::CONFIG_FILE,
+    validate_changelog, write_changelogs_with_metadata,
+    writer::generate_summary,
+};
 use keryx::commit::{
-    ChangedFile, DiffSummary, SPLIT_ANALYSIS_THRESHOLD, analyze_split, collect_diff,
-    collect_diff_for_paths, generate_commit_message, stage_and_commit, stage_paths_and_commit,
+    ChangedFile, CommitMessage, DiffSummary, SPLIT_ANALYSIS_THRESHOLD, analyze_split,
+    build_trailers_prompt, check_range, collect_diff, collect_diff_for_paths,
+    generate_commit_message, needs_trailers, parse_trailers, regenerate_commit_message,
+    rewrite_messages, stage_and_commit, stage_paths_and_commit, unpushed_commits, with_trailers,
+};
+use keryx::compare::{CombinedNotes, build_compare_prompt, parse_version_arg};
+use keryx::explain::{
+    ChangeContext, ExplainTarget, build_explain_prompt, build_followup_prompt, commit_context,
+    entry_commits, resolve_target,
 };
 use keryx::git::{
-    commits::fetch_commits,
-    range::{find_root_commit, resolve_range},
-    tags::{get_all_tags, get_latest_tag},
+    ParsedCommit,
+    commits::{fetch_commit_subjects, fetch_commits, walk_commits},
+    dedupe::net_commits,
+    range::{find_root_commit, find_untagged_release, resolve_range},
+    tags::{TagInfo, TagRange, get_all_tags, get_latest_tag, get_version_from_tag},
+    tree::{read_head_file, read_repo_file},
+    upstream::{resolve_upstreams, upstream_oids, upstreams_from_flags_or_env},
 };
 use keryx::github::{
-    auth::get_github_token,
-    prs::{fetch_merged_prs, parse_github_remote},
+    PrDiscussion,
+    auth::{
+        AUTH_ORDER_ENV_VAR, READ_ONLY_PERMISSIONS, TokenSource, auth_order_from_env,
+        fetch_token_info, mask_token, token_from_source, unneeded_scopes,
+    },
+    detect_remote,
+    milestones::Milestone,
+    prs::{
+        PrScope, github_web_url, parse_github_remote, referenced_pr_numbers, select_prs_for_range,
+    },
+    select_pr_remote, shared_client,
 };
+use keryx::journal::{Journal, OperationKind, format_entry};
+use keryx::languages::LanguageProfile;
+use keryx::llm::estimate;
+use keryx::llm::providers::{binary as provider_binary, find_cli};
 use keryx::llm::{
-    ChangelogInput, LlmCompletion, LlmError, LlmProviderError, LlmRouter, Provider,
-    ProviderSelection, build_prompt, build_verification_prompt,
+    ChangelogInput, ChangelogRequest, CostEstimate, CostThreshold, LlmCompletion, LlmError,
+    LlmProviderError, LlmRouter, LlmTask, PROVIDERS, Provider, ProviderSelection, TaskPolicies,
+    TextRequest, VerificationRequest, build_prompt, build_verification_prompt, provider_status,
+    set_default_provider,
+};
+use keryx::lock::RepoLock;
+use keryx::ollama::OllamaClient;
+use keryx::output::{OutputFormat, print_json};
+use keryx::pending::{PendingReport, build_pending_prompt};
+use keryx::refine::{
+    RefinableEntry, build_refine_prompt, list_entries, parse_refined, replace_entry, write_refined,
+};
+use keryx::scaffold::{
+    ProjectType, WORKFLOW_PATH, api_key_secret, render_release_workflow, write_workflow,
+};
+use keryx::shell::ShellCommand;
+use keryx::ship::version_files::{VersionFileSelection, VersionMismatchStrategy};
+use keryx::status;
+use keryx::summary::{RunSummary, SummaryFormat};
+use keryx::text::{middle_ellipsis, truncate_to_width};
+use keryx::verification::{
+    Confidence, ScopeMode, SearchScope, SourceCorpus, VerificationEvidence, VerifyMode,
+    apply_verification_rules, check_ripgrep_installed, drop_unsupported_entries,
+    find_missed_changes, gather_verification_evidence_in,
+};
+use keryx::version::{
+    VersionBumpInput, calculate_next_version, calculate_next_version_with_llm,
+    determine_unreleased_bump,
 };
-use keryx::verification::{check_ripgrep_installed, gather_verification_evidence};
-use keryx::version::{VersionBumpInput, calculate_next_version, calculate_next_version_with_llm};
 
 /// Result from the background update check.
 struct UpdateResult {
@@ -158,15 +238,49 @@ struct Cli {
     #[arg(long, default_value = "HEAD", global = true)]
     to: String,
 
-    /// Path to changelog file
-    #[arg(short = 'o', long, default_value = "CHANGELOG.md", global = true)]
-    output: PathBuf,
+    /// When the changelog's newest version has no tag, start the range at
+    /// the commit that added its section instead of the latest tag
+    #[arg(long, global = true, conflicts_with = "from")]
+    range_from_changelog: bool,
+
+    /// Leave out commits reachable from this ref, e.g. the upstream a fork
+    /// syncs from (`upstream/main`, or `upstream/main@{last-release}` for its
+    /// newest release tag); repeat or comma-separate (env: KERYX_EXCLUDE_UPSTREAM)
+    #[arg(long, value_name = "REF", value_delimiter = ',', global = true)]
+    exclude_upstream: Vec<String>,
+
+    /// Path to changelog file; repeat or comma-separate to keep several
+    /// changelogs (e.g. CHANGELOG.md,docs/changelog.md) in sync
+    #[arg(
+        short = 'o',
+        long,
+        default_value = "CHANGELOG.md",
+        value_delimiter = ',',
+        global = true
+    )]
+    output: Vec<PathBuf>,
 
     /// Skip GitHub PR fetching
     #[arg(long, global = true)]
     no_prs: bool,
 
-    /// Maximum number of PRs to fetch (default: 100, env: KERYX_PR_LIMIT)
+    /// Generate from merged PRs in the range only, ignoring commit messages
+    #[arg(long, conflicts_with = "no_prs")]
+    prs_only: bool,
+
+    /// Generate from the merged PRs in this GitHub milestone instead of a commit range
+    #[arg(long, value_name = "NAME", conflicts_with_all = ["no_prs", "prs_only", "from"])]
+    milestone: Option<String>,
+
+    /// Remote to read PRs and compare links from (default: upstream if present, else origin; env: KERYX_REMOTE)
+    #[arg(long, value_name = "NAME", global = true)]
+    remote: Option<String>,
+
+    /// Show which GitHub credential source is used, its scopes, and rate limit, then exit
+    #[arg(long)]
+    show_auth: bool,
+
+    /// Maximum number of PRs outside the release range to fetch; PRs merged in the range are always fetched (default: 100, env: KERYX_PR_LIMIT)
     #[arg(short = 'l', long, global = true)]
     pr_limit: Option<usize>,
 
@@ -174,6 +288,10 @@ struct Cli {
     #[arg(long, global = true)]
     dry_run: bool,
 
+    /// Open the generated changelog section (or commit message) in $EDITOR before writing it
+    #[arg(long, global = true)]
+    edit: bool,
+
     /// Strict mode - fail on any errors instead of graceful degradation
     #[arg(long, global = true)]
     strict: bool,
@@ -182,14 +300,36 @@ struct Cli {
     #[arg(long, global = true)]
     force: bool,
 
-    /// Enable verbose/debug logging
-    #[arg(short, long, global = true)]
-    verbose: bool,
+    /// Make the planned LLM calls without asking, even above the [llm] confirm_above_* thresholds in keryx.toml
+    #[arg(short = 'y', long, global = true)]
+    yes: bool,
 
-    /// Skip verification pass (faster but may include inaccuracies)
-    #[arg(long, global = true)]
+    /// If the version already exists, add only entries whose PRs/commits are not in its section yet, keeping the existing wording
+    #[arg(long, global = true, conflicts_with = "force")]
+    merge: bool,
+
+    /// Increase logging: -v info, -vv debug, -vvv trace with subprocess
+    /// command lines and timings (env KERYX_LOG adds per-module filters)
+    #[arg(short, long, global = true, action = clap::ArgAction::Count)]
+    verbose: u8,
+
+    /// Skip verification pass (faster but may include inaccuracies; same as --verify off)
+    #[arg(long, global = true, conflicts_with = "verify")]
     no_verify: bool,
 
+    /// How entries are verified against the codebase: llm (second LLM pass),
+    /// rules (deterministic checks on the gathered evidence), or off
+    #[arg(long, value_enum, global = true)]
+    verify: Option<VerifyFlag>,
+
+    /// Format of the recap printed when generate or ship finishes: text or json
+    #[arg(long, value_enum, global = true, default_value = "text")]
+    summary_format: SummaryFormatFlag,
+
+    /// Print the result as one JSON document on stdout, with progress on stderr (generate, ship --dry-run, commit --message-only)
+    #[arg(long, value_enum, global = true, default_value = "text")]
+    format: OutputFormatFlag,
+
     /// Skip LLM-based version bump (use algorithmic bump from commit types)
     #[arg(long, global = true)]
     no_llm_bump: bool,
@@ -197,12 +337,246 @@ struct Cli {
     /// LLM provider to use (fallback will be attempted on failure)
     #[arg(long, value_enum, global = true)]
     provider: Option<ProviderFlag>,
+
+    /// Timezone for section dates: utc, local, or an offset like +02:00 (env: KERYX_CHANGELOG_TIMEZONE)
+    #[arg(long, global = true, value_name = "TZ")]
+    date_timezone: Option<DateTimezone>,
+
+    /// Take section dates from the current time or the newest commit (env: KERYX_CHANGELOG_DATE_SOURCE)
+    #[arg(long, global = true, value_name = "now|commit")]
+    date_source: Option<DateSource>,
+
+    /// Version heading template with {version}, {date}, and {codename} placeholders (env: KERYX_HEADING_TEMPLATE)
+    #[arg(long, global = true, value_name = "TEMPLATE")]
+    heading_template: Option<HeadingTemplate>,
+
+    /// Render new sections with this Tera template instead of the Keep a Changelog layout (env: KERYX_TEMPLATE)
+    #[arg(long, global = true, value_name = "PATH")]
+    template: Option<PathBuf>,
+
+    /// Release codename, shown in the heading or under it (env: KERYX_CODENAMES for a sequence)
+    #[arg(long, global = true)]
+    codename: Option<String>,
+
+    /// Ask the LLM for a codename when none is given or left in the sequence
+    #[arg(long, global = true)]
+    suggest_codename: bool,
+
+    /// Add a short LLM-written highlights paragraph at the top of the new section
+    #[arg(long, global = true)]
+    highlights: bool,
+
+    /// For a major release, also write upgrade steps for its breaking changes to MIGRATIONS.md
+    #[arg(long, global = true)]
+    migration_guide: bool,
+
+    /// Most entries per category; extra entries trigger a regeneration (env: KERYX_MAX_ENTRIES_PER_CATEGORY)
+    #[arg(long, global = true, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_entries_per_category: Option<usize>,
+
+    /// Most words per entry; longer entries trigger a regeneration (env: KERYX_MAX_ENTRY_WORDS)
+    #[arg(long, global = true, value_name = "N", value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..))]
+    max_entry_words: Option<usize>,
+
+    /// Require entries to start with a capital letter (env: KERYX_SENTENCE_CASE)
+    #[arg(long, global = true)]
+    sentence_case: bool,
+
+    /// Order of entries within a category: source, alphabetical, scope, or importance (env: KERYX_SORT_ENTRIES)
+    #[arg(long, global = true, value_name = "POLICY")]
+    sort_entries: Option<EntrySort>,
+
+    /// Credit co-authors and PR reviewers on entries in these categories, comma-separated or "all" (env: KERYX_ATTRIBUTION)
+    #[arg(long, global = true, value_name = "CATEGORIES")]
+    attribution: Option<String>,
+
+    /// Release manager shown under the version heading
+    #[arg(long, global = true)]
+    release_manager: Option<String>,
+
+    /// Extra release attribute shown under the version heading (repeatable)
+    #[arg(long = "attribute", global = true, value_name = "KEY=VALUE")]
+    attributes: Vec<ReleaseAttribute>,
+}
+
+impl Cli {
+    /// Whether at least one -v was given.
+    fn verbose(&self) -> bool {
+        self.verbose > 0
+    }
+
+    /// Release attributes from --release-manager and --attribute.
+    fn release_attributes(&self) -> Vec<ReleaseAttribute> {
+        let mut attributes = Vec::new();
+        if let Some(manager) = &self.release_manager {
+            attributes.push(ReleaseAttribute::new("Release manager", manager));
+        }
+        attributes.extend(self.attributes.iter().cloned());
+        attributes
+    }
+
+    /// Entry limits from --max-entries-per-category, --max-entry-words,
+    /// --sentence-case, or their environment variables.
+    fn entry_constraints(&self) -> EntryConstraints {
+        EntryConstraints::from_flags_or_env(
+            self.max_entries_per_category,
+            self.max_entry_words,
+            self.sentence_case,
+        )
+    }
+
+    /// Entry order from --sort-entries or KERYX_SORT_ENTRIES.
+    fn entry_sort(&self) -> EntrySort {
+        EntrySort::from_flag_or_env(self.sort_entries)
+    }
+
+    /// Attribution categories from --attribution or KERYX_ATTRIBUTION.
+    fn attribution_config(&self) -> AttributionConfig {
+        AttributionConfig::from_flag_or_env(self.attribution.clone())
+    }
+
+    /// Heading template from --heading-template or KERYX_HEADING_TEMPLATE.
+    fn heading_template(&self) -> HeadingTemplate {
+        HeadingTemplate::from_flag_or_env(self.heading_template.clone())
+    }
+
+    /// Section template from --template or KERYX_TEMPLATE.
+    fn release_template(&self) -> Result<Option<ReleaseTemplate>> {
+        Ok(ReleaseTemplate::from_flag_or_env(self.template.clone())?)
+    }
+
+    /// The first `-o` path: read for existing versions and codenames, and the
+    /// only one `init` writes.
+    fn primary_output(&self) -> &PathBuf {
+        &self.output[0]
+    }
+
+    /// Resolve the verification mode; `--no-verify` is an alias for `--verify off`.
+    fn verify_mode(&self) -> VerifyMode {
+        if self.no_verify {
+            VerifyMode::Off
+        } else {
+            self.verify.map(VerifyMode::from).unwrap_or_default()
+        }
+    }
+
+    /// Whether the command prints a JSON document with `--format json`.
+    fn supports_json(&self) -> bool {
+        match &self.command {
+            None => true,
+            Some(Commands::Commit {
+                message_only,
+                amend_trailers,
+                ..
+            }) => *message_only && !*amend_trailers,
+            Some(Commands::Push { message_only, .. }) => *message_only,
+            Some(Commands::Ship {
+                publish,
+                approve,
+                train,
+                package,
+                ..
+            }) => self.dry_run && !publish && !approve && !train && package.is_none(),
+            _ => false,
+        }
+    }
+
+    /// Whether the run promises no writes to GitHub and no network calls
+    /// besides GitHub reads and the LLM: changelog generation and every
+    /// `--dry-run`.
+    fn read_only(&self) -> bool {
+        self.command.is_none() || self.dry_run
+    }
+
+    /// Journal name of the command when the run changes the repository and
+    /// must hold its lock. The shell takes the lock only while writing.
+    fn repo_lock_command(&self) -> Option<&'static str> {
+        if self.dry_run || self.show_auth {
+            return None;
+        }
+        match &self.command {
+            None => Some("generate"),
+            Some(Commands::Init { .. }) => Some("init"),
+            Some(Commands::Commit {
+                message_only: false,
+                ..
+            }) => Some("commit"),
+            Some(Commands::Push {
+                message_only: false,
+                ..
+            }) => Some("push"),
+            Some(Commands::Ship { .. }) => Some("ship"),
+            Some(Commands::Translations { sync: true, .. }) => Some("translations"),
+            Some(Commands::Unreleased) => Some("unreleased"),
+            Some(Commands::Fmt { check: false }) => Some("fmt"),
+            Some(Commands::Refine { .. }) => Some("refine"),
+            _ => None,
+        }
+    }
+
+    /// Codename sources from --codename, KERYX_CODENAMES, and --suggest-codename.
+    fn codename_config(&self) -> CodenameConfig {
+        CodenameConfig::from_flags_or_env(self.codename.clone(), self.suggest_codename)
+    }
+
+    /// Providers from --provider, else the default in keryx.toml, else Claude first.
+    fn provider_selection(&self) -> ProviderSelection {
+        ProviderSelection::resolve(self.provider.clone().map(Provider::from), &config_root())
+    }
+}
+
+/// Print the LLM calls a run plans and, when they are above the thresholds
+/// in `keryx.toml`, ask before making them. Without a terminal a plan above
+/// the thresholds fails unless `--yes` is given. Returns false if declined.
+fn confirm_llm_plan(plan: &CostEstimate, yes: bool) -> Result<bool> {
+    status!("Plan: {}", plan);
+    if yes || !plan.exceeds(&CostThreshold::load(&config_root())) {
+        return Ok(true);
+    }
+    if !std::io::stdin().is_terminal() {
+        bail!(
+            "The planned LLM calls are above the confirmation threshold (confirm_above_usd / confirm_above_tokens in the [llm] table of {}). Pass --yes to make them anyway.",
+            CONFIG_FILE
+        );
+    }
+    let proceed = dialoguer::Confirm::new()
+        .with_prompt(format!("Make {} LLM call(s)?", plan.calls()))
+        .default(false)
+        .interact()
+        .context("Failed to read confirmation")?;
+    if !proceed {
+        status!("Cancelled; no LLM calls were made.");
+    }
+    Ok(proceed)
+}
+
+/// Directory holding keryx.toml: the root of the current repository, or the
+/// current directory outside one.
+fn config_root() -> PathBuf {
+    Repository::discover(".")
+        .ok()
+        .and_then(|repo| repo.workdir().map(PathBuf::from))
+        .unwrap_or_else(|| PathBuf::from("."))
+}
+
+/// A router for `selection`, with the `[llm.tasks]` policies of `keryx.toml`.
+fn llm_router(selection: ProviderSelection, journal: Option<Journal>) -> LlmRouter {
+    LlmRouter::new(selection)
+        .with_journal(journal)
+        .with_policies(TaskPolicies::load(&config_root()))
 }
 
 #[derive(Debug, Clone, ValueEnum)]
 enum ProviderFlag {
     Claude,
     Codex,
+    /// Anthropic Messages API over HTTP (needs ANTHROPIC_API_KEY, no CLI)
+    AnthropicApi,
+    /// OpenAI Chat Completions API over HTTP (needs OPENAI_API_KEY, no CLI)
+    #[value(name = "openai-api")]
+    OpenAiApi,
+    /// Local model on an Ollama server (OLLAMA_HOST, OLLAMA_MODEL); no fallback
+    Ollama,
 }
 
 impl From<ProviderFlag> for Provider {
@@ -210,6 +584,89 @@ impl From<ProviderFlag> for Provider {
         match value {
             ProviderFlag::Claude => Provider::Claude,
             ProviderFlag::Codex => Provider::Codex,
+            ProviderFlag::AnthropicApi => Provider::AnthropicApi,
+            ProviderFlag::OpenAiApi => Provider::OpenAiApi,
+            ProviderFlag::Ollama => Provider::Ollama,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum VerifyFlag {
+    /// Drop entries contradicted by the evidence, flag low-confidence ones
+    Rules,
+    /// Run a second LLM pass over the draft and the evidence
+    Llm,
+    /// Skip verification
+    Off,
+}
+
+impl From<VerifyFlag> for VerifyMode {
+    fn from(value: VerifyFlag) -> Self {
+        match value {
+            VerifyFlag::Rules => VerifyMode::Rules,
+            VerifyFlag::Llm => VerifyMode::Llm,
+            VerifyFlag::Off => VerifyMode::Off,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum SummaryFormatFlag {
+    /// Human-readable block
+    Text,
+    /// Single-line JSON object
+    Json,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+enum OutputFormatFlag {
+    /// Progress and results for people
+    Text,
+    /// One JSON document with the result
+    Json,
+}
+
+impl From<OutputFormatFlag> for OutputFormat {
+    fn from(value: OutputFormatFlag) -> Self {
+        match value {
+            OutputFormatFlag::Text => OutputFormat::Text,
+            OutputFormatFlag::Json => OutputFormat::Json,
+        }
+    }
+}
+
+impl From<SummaryFormatFlag> for SummaryFormat {
+    fn from(value: SummaryFormatFlag) -> Self {
+        match value {
+            SummaryFormatFlag::Text => SummaryFormat::Text,
+            SummaryFormatFlag::Json => SummaryFormat::Json,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum AnnounceTarget {
+    /// A plain text + HTML email
+    Email,
+}
+
+#[derive(Debug, Clone, Copy, ValueEnum)]
+enum VersionMismatchFlag {
+    /// Bump from the latest release tag's version
+    Tag,
+    /// Bump from the highest version found in any version file
+    Max,
+    /// Abort the release
+    Fail,
+}
+
+impl From<VersionMismatchFlag> for VersionMismatchStrategy {
+    fn from(value: VersionMismatchFlag) -> Self {
+        match value {
+            VersionMismatchFlag::Tag => VersionMismatchStrategy::Tag,
+            VersionMismatchFlag::Max => VersionMismatchStrategy::Max,
+            VersionMismatchFlag::Fail => VersionMismatchStrategy::Fail,
         }
     }
 }
@@ -228,6 +685,18 @@ enum Commands {
         /// Generate entries for each existing git tag (full history)
         #[arg(long, conflicts_with = "unreleased")]
         from_history: bool,
+
+        /// Only generate sections for tags in this range (e.g. v1.0.0..v2.0.0)
+        #[arg(long, value_name = "RANGE", requires = "from_history")]
+        only_tags: Option<TagRange>,
+
+        /// Discard sections cached by an interrupted --from-history run
+        #[arg(long, requires = "from_history")]
+        fresh: bool,
+
+        /// Write a GitHub Actions release workflow instead of a changelog
+        #[arg(long, conflicts_with_all = ["unreleased", "from_history"])]
+        github_workflow: bool,
     },
 
     /// Generate a commit message from staged/unstaged changes using AI
@@ -239,6 +708,10 @@ enum Commands {
         /// Skip split analysis, always create a single commit
         #[arg(long)]
         no_split: bool,
+
+        /// Add changelog trailers to unpushed commits that lack them
+        #[arg(long, conflicts_with_all = ["message_only", "no_split"])]
+        amend_trailers: bool,
     },
 
     /// Generate a commit message and push the commit to the remote
@@ -252,8 +725,264 @@ enum Commands {
         no_split: bool,
     },
 
+    /// Fail unless every commit since the base has a Changelog trailer or `Changelog: none` (for CI on pull requests)
+    CheckTrailers {
+        /// Check the commits after this revision (default: <remote>/$GITHUB_BASE_REF in pull request workflows, else the latest tag)
+        #[arg(long, value_name = "REV")]
+        base: Option<String>,
+    },
+
+    /// Check a commit message against the conventional commit format (for a commit-msg hook)
+    LintCommit {
+        /// File with the message, as passed to the hook (default: stdin)
+        file: Option<PathBuf>,
+
+        /// Longest subject line allowed, in characters
+        #[arg(long, value_name = "N", default_value_t = keryx::commit::DEFAULT_MAX_SUBJECT_LENGTH)]
+        max_subject_length: usize,
+    },
+
     /// Create a release: bump version, update changelog, tag, and push
-    Ship,
+    Ship {
+        /// Only update this version file (repeatable, env: KERYX_VERSION_FILES)
+        #[arg(long = "version-file", value_name = "PATH")]
+        version_files: Vec<PathBuf>,
+
+        /// Never update this version file (repeatable, env: KERYX_EXCLUDE_VERSION_FILES)
+        #[arg(long = "exclude-version-file", value_name = "PATH")]
+        exclude_version_files: Vec<PathBuf>,
+
+        /// How to resolve version files that disagree (prompts if omitted)
+        #[arg(long, value_enum)]
+        on_version_mismatch: Option<VersionMismatchFlag>,
+
+        /// Commit and tag now, push at TIME (HH:MM, YYYY-MM-DD HH:MM, or RFC 3339)
+        #[arg(long, value_name = "TIME", value_parser = parse_schedule)]
+        schedule: Option<chrono::DateTime<chrono::Utc>>,
+
+        /// Push the release staged with --schedule now
+        #[arg(long, conflicts_with = "schedule")]
+        publish: bool,
+
+        /// Run after a successful push: cargo, npm, cargo:dry-run, npm:dry-run, or run:<command> (repeatable, env: KERYX_PUBLISH_STEPS)
+        #[arg(long = "publish-step", value_name = "STEP", conflicts_with = "publish")]
+        publish_steps: Vec<keryx::ship::publish::PublishStep>,
+
+        /// Upload files matching GLOB to the GitHub release after the push (repeatable, env: KERYX_RELEASE_ASSETS)
+        #[arg(
+            long = "release-asset",
+            value_name = "GLOB",
+            conflicts_with = "publish"
+        )]
+        release_assets: Vec<String>,
+
+        /// Do not create a GitHub release for the tag after the push
+        #[arg(long)]
+        no_github_release: bool,
+
+        /// Push the branch first and create/push the tag only once CI checks on the release commit pass
+        #[arg(long, conflicts_with_all = ["publish", "schedule"])]
+        require_checks: bool,
+
+        /// Give up waiting for checks after this long (e.g. 45m, 1h)
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "30m",
+            value_parser = keryx::ship::checks::parse_duration,
+            requires = "require_checks"
+        )]
+        checks_timeout: std::time::Duration,
+
+        /// Time between two polls of the checks (e.g. 15s, 1m)
+        #[arg(
+            long,
+            value_name = "DURATION",
+            default_value = "15s",
+            value_parser = keryx::ship::checks::parse_duration,
+            requires = "require_checks"
+        )]
+        checks_interval: std::time::Duration,
+
+        /// Commit the release and push it to release/<tag> for approval, without tagging
+        #[arg(
+            long,
+            conflicts_with_all = ["publish", "schedule", "require_checks", "publish_steps", "release_assets"]
+        )]
+        prepare: bool,
+
+        /// Tag and push the release prepared by someone else (run on the prepared commit)
+        #[arg(
+            long,
+            conflicts_with_all = ["publish", "schedule", "require_checks", "prepare"]
+        )]
+        approve: bool,
+
+        /// Release every changed package of a monorepo in one commit, tagged <name>-v<version> each
+        #[arg(
+            long,
+            conflicts_with_all = ["publish", "schedule", "require_checks", "prepare", "approve", "publish_steps", "release_assets"]
+        )]
+        train: bool,
+
+        /// Ship with uncommitted changes (e.g. generated docs); they are left out of the release commit
+        #[arg(long)]
+        allow_dirty: bool,
+
+        /// Ship although the branch is behind or has diverged from its upstream
+        #[arg(long)]
+        allow_behind: bool,
+
+        /// Ship a branch without an upstream, pushing it to origin under the same name
+        #[arg(long)]
+        allow_no_upstream: bool,
+
+        /// Do not bump any version file: write the changelog, tag and push (version from the latest tag)
+        #[arg(
+            long,
+            conflicts_with_all = ["train", "version_files", "exclude_version_files", "on_version_mismatch"]
+        )]
+        tag_only: bool,
+
+        /// Write pre-release sections (e.g. 2.0.0-rc.1) to this file; the stable release consolidates and removes them
+        #[arg(long, value_name = "PATH", conflicts_with = "train")]
+        prerelease_changelog: Option<PathBuf>,
+
+        /// Release only this workspace package: its manifest, its commits, a <name>-v<version> tag and its own CHANGELOG.md
+        #[arg(
+            long,
+            value_name = "NAME",
+            conflicts_with_all = ["train", "publish", "schedule", "require_checks", "prepare", "approve", "publish_steps", "release_assets", "tag_only", "prerelease_changelog"]
+        )]
+        package: Option<String>,
+    },
+
+    /// Check that a shipped release is complete: remote tag, GitHub release, version files, changelog
+    VerifyShip {
+        /// Release tag to check (default: the latest release tag reachable from HEAD)
+        tag: Option<String>,
+
+        /// Also require a GitHub release for the tag
+        #[arg(long)]
+        github_release: bool,
+    },
+
+    /// Check translated changelogs (CHANGELOG.<lang>.md) for missing or outdated versions
+    Translations {
+        /// Translated changelog to check (repeatable; default: translations next to the -o file)
+        #[arg(long = "translation", value_name = "PATH")]
+        translations: Vec<PathBuf>,
+
+        /// Translate missing sections with the LLM and insert them
+        #[arg(long)]
+        sync: bool,
+    },
+
+    /// Explain a changelog entry or commit and its user impact, then take follow-up questions
+    Explain {
+        /// Commit SHA (or any revision), or text of a changelog entry
+        target: String,
+
+        /// Print the explanation and exit without asking for follow-up questions
+        #[arg(long)]
+        no_follow_up: bool,
+    },
+
+    /// List the commits since the last tag by type, with the predicted next version
+    Pending {
+        /// Also ask the LLM for a short narrative of the pending changes
+        #[arg(long)]
+        llm: bool,
+    },
+
+    /// Print the next version implied by the [Unreleased] entries of the changelog, without reading git
+    NextVersion,
+
+    /// Add entries for the commits since the latest tag to the [Unreleased] section, skipping ones already there
+    Unreleased,
+
+    /// Rewrite category headings written by other tools ("Features", "Bug Fixes") to the Keep a Changelog names
+    Fmt {
+        /// Only report alias headings and fail if there are any (for CI)
+        #[arg(long)]
+        check: bool,
+    },
+
+    /// Check the changelog's Keep a Changelog structure and fail on any violation (for CI)
+    Check,
+
+    /// Combine the changelog sections between two versions into one list, for upgrading across releases
+    Compare {
+        /// Version being upgraded from (its own section is not included)
+        #[arg(value_parser = parse_version_arg)]
+        from: Version,
+
+        /// Version being upgraded to (default: the latest in the changelog)
+        #[arg(value_parser = parse_version_arg)]
+        to: Option<Version>,
+
+        /// Also ask the LLM for a condensed upgrade summary
+        #[arg(long)]
+        llm: bool,
+    },
+
+    /// Rewrite changelog entries one at a time by giving the LLM an instruction
+    Refine {
+        /// Only offer entries containing this text (case-insensitive)
+        entry: Option<String>,
+    },
+
+    /// Craft a release interactively: collect, generate, edit, verify and write in one session
+    Shell,
+
+    /// Render the release notes of a version as an announcement (email)
+    Announce {
+        /// Where to announce
+        #[arg(value_enum)]
+        target: AnnounceTarget,
+
+        /// Version to announce (default: the latest in the changelog)
+        version: Option<Version>,
+
+        /// Write the message to this file (e.g. release.eml) instead of stdout
+        #[arg(long, value_name = "PATH")]
+        output: Option<PathBuf>,
+
+        /// Send the message through the SMTP server configured in keryx.toml
+        #[arg(long)]
+        send: bool,
+    },
+
+    /// Show the journal of operations keryx has performed in this repository
+    Log {
+        /// Only show the most recent N entries
+        #[arg(short = 'n', long)]
+        limit: Option<usize>,
+    },
+
+    /// List the LLM providers and their routing order, check their status, or set the default
+    Providers {
+        #[command(subcommand)]
+        action: Option<ProvidersAction>,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+enum ProvidersAction {
+    /// Show the routing order, where it comes from, and the installed CLIs (default)
+    List,
+    /// Show each provider's version, login status and configured model
+    Status,
+    /// Store the default provider in keryx.toml
+    SetDefault {
+        #[arg(value_enum)]
+        provider: ProviderFlag,
+    },
+}
+
+/// Parse `ship --schedule` relative to the current local time.
+fn parse_schedule(value: &str) -> Result<chrono::DateTime<chrono::Utc>, String> {
+    keryx::ship::schedule::parse_publish_time(value, chrono::Local::now())
 }
 
 /// Configuration for the commit command.
@@ -262,6 +991,8 @@ struct CommitConfig {
     message_only: bool,
     /// Preview without actually creating commits.
     dry_run: bool,
+    /// Open each generated message in `$EDITOR` first.
+    edit: bool,
     /// Enable verbose/debug logging.
     verbose: bool,
 }
@@ -278,41 +1009,74 @@ enum CommitOutcome {
 struct InitConfig {
     /// Path to output changelog file.
     output: PathBuf,
+    /// How section dates are rendered.
+    release_date: ReleaseDateConfig,
+    /// Template for version headings.
+    heading: HeadingTemplate,
     /// Preview without writing to file.
     dry_run: bool,
     /// Skip GitHub PR fetching.
     no_prs: bool,
+    /// Remote to read PRs from (`None` = auto-select).
+    remote: Option<String>,
     /// Fail on any errors instead of graceful degradation.
     strict: bool,
     /// Maximum number of PRs to fetch.
     pr_limit: Option<usize>,
-    /// Skip verification pass.
-    no_verify: bool,
+    /// How entries are verified against the codebase.
+    verify: VerifyMode,
     /// Enable verbose/debug logging.
     verbose: bool,
     /// LLM provider selection.
     provider_selection: ProviderSelection,
+    /// Limits on the generated entries.
+    constraints: EntryConstraints,
+    /// Order of entries within each category.
+    entry_sort: EntrySort,
+    /// Skip the confirmation of large LLM runs.
+    yes: bool,
+}
+
+/// A version section of `init --from-history` before the LLM is called.
+enum HistoryDraft {
+    /// Generated by an interrupted run.
+    Cached(keryx::ChangelogOutput),
+    /// To be generated from `prompt`, built from `input`.
+    Generate {
+        input: Box<ChangelogInput>,
+        prompt: String,
+    },
+}
+
+/// Options specific to `init --from-history`.
+#[derive(Debug)]
+struct HistoryOptions {
+    /// Only generate sections for tags in this range.
+    only_tags: Option<TagRange>,
+    /// Ignore and clear the section cache before starting.
+    fresh: bool,
 }
 
 impl InitConfig {
     /// Create an `InitConfig` from the CLI arguments.
     fn from_cli(cli: &Cli) -> Self {
-        let provider_selection = cli
-            .provider
-            .clone()
-            .map(Provider::from)
-            .map(ProviderSelection::from_primary)
-            .unwrap_or_default();
+        let provider_selection = cli.provider_selection();
 
         Self {
-            output: cli.output.clone(),
+            output: cli.primary_output().clone(),
+            release_date: ReleaseDateConfig::from_flags_or_env(cli.date_timezone, cli.date_source),
+            heading: cli.heading_template(),
             dry_run: cli.dry_run,
             no_prs: cli.no_prs,
+            remote: cli.remote.clone(),
             strict: cli.strict,
             pr_limit: cli.pr_limit,
-            no_verify: cli.no_verify,
-            verbose: cli.verbose,
+            verify: cli.verify_mode(),
+            verbose: cli.verbose(),
             provider_selection,
+            constraints: cli.entry_constraints(),
+            entry_sort: cli.entry_sort(),
+            yes: cli.yes,
         }
     }
 }
@@ -322,39 +1086,72 @@ async fn main() -> Result<()> {
     let cli = Cli::parse();
 
     // Initialize tracing subscriber for logging
-    let log_level = if cli.verbose {
-        Level::DEBUG
-    } else {
-        Level::WARN
+    keryx::logging::init(cli.verbose);
+
+    if cli.format == OutputFormatFlag::Json && !cli.supports_json() {
+        bail!(
+            "--format json is supported by generate (no subcommand), ship --dry-run and commit --message-only"
+        );
+    }
+    keryx::output::set_format(cli.format.into());
+
+    keryx::github::set_read_only(cli.read_only());
+
+    // Commands that change the repository hold its lock until they finish
+    let mut repo_lock = match cli.repo_lock_command() {
+        Some(command) => match Repository::open(".") {
+            Ok(repo) => RepoLock::for_repository(&repo, command)?,
+            // Not a repository: the command reports that itself
+            Err(_) => None,
+        },
+        None => None,
     };
-    let subscriber = FmtSubscriber::builder()
-        .with_max_level(log_level)
-        .with_writer(std::io::stderr)
-        .without_time()
-        .finish();
-    tracing::subscriber::set_global_default(subscriber).expect("Failed to set tracing subscriber");
 
-    // Start background update check (non-blocking)
-    let update_checker = UpdateChecker::start(cli.verbose);
+    // Start background update check (non-blocking); read-only runs skip it
+    let update_checker = (!cli.read_only()).then(|| UpdateChecker::start(cli.verbose()));
 
     // Run the requested command
     let result = match cli.command {
+        _ if cli.show_auth => run_show_auth().await,
         Some(Commands::Update) => run_update().await,
+        Some(Commands::Init {
+            github_workflow: true,
+            ..
+        }) => run_init_workflow(&cli),
         Some(Commands::Init {
             unreleased,
             from_history,
+            ref only_tags,
+            fresh,
+            ..
         }) => {
+            if cli.output.len() > 1 {
+                eprintln!(
+                    "\x1b[33m⚠ init writes a single changelog; using {}\x1b[0m",
+                    cli.primary_output().display()
+                );
+            }
             let config = InitConfig::from_cli(&cli);
-            run_init(&config, unreleased, from_history).await
+            let history = HistoryOptions {
+                only_tags: only_tags.clone(),
+                fresh,
+            };
+            run_init(&config, unreleased, from_history, &history).await
         }
+        Some(Commands::Commit {
+            amend_trailers: true,
+            ..
+        }) => run_amend_trailers(&cli).await,
         Some(Commands::Commit {
             message_only,
             no_split,
+            ..
         }) => {
             let config = CommitConfig {
                 message_only,
                 dry_run: cli.dry_run,
-                verbose: cli.verbose,
+                edit: cli.edit,
+                verbose: cli.verbose(),
             };
             run_commit(&config, no_split, cli.provider)
                 .await
@@ -367,71 +1164,1599 @@ async fn main() -> Result<()> {
             let config = CommitConfig {
                 message_only,
                 dry_run: cli.dry_run,
-                verbose: cli.verbose,
+                edit: cli.edit,
+                verbose: cli.verbose(),
             };
             run_push(&config, no_split, cli.provider).await
         }
-        Some(Commands::Ship) => {
-            let provider_selection = cli
-                .provider
-                .clone()
-                .map(Provider::from)
-                .map(ProviderSelection::from_primary)
-                .unwrap_or_default();
+        Some(Commands::CheckTrailers { ref base }) => run_check_trailers(&cli, base.as_deref()),
+        Some(Commands::LintCommit {
+            ref file,
+            max_subject_length,
+        }) => run_lint_commit(file.as_deref(), max_subject_length),
+        Some(Commands::Ship { publish: true, .. }) => keryx::ship::run_publish(cli.dry_run)
+            .await
+            .map_err(|e| anyhow::anyhow!("{}", e)),
+        Some(Commands::Ship {
+            approve: true,
+            publish_steps,
+            release_assets,
+            no_github_release,
+            ..
+        }) => keryx::ship::approval::run_approve(
+            cli.dry_run,
+            keryx::ship::publish::steps_from_flags_or_env(publish_steps),
+            keryx::ship::assets::patterns_from_flags_or_env(release_assets),
+            !no_github_release,
+        )
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e)),
+        Some(Commands::Ship {
+            ref version_files,
+            ref exclude_version_files,
+            on_version_mismatch,
+            schedule,
+            publish: false,
+            ref publish_steps,
+            ref release_assets,
+            no_github_release,
+            require_checks,
+            checks_timeout,
+            checks_interval,
+            prepare,
+            approve: false,
+            train,
+            allow_dirty,
+            allow_behind,
+            allow_no_upstream,
+            tag_only,
+            ref prerelease_changelog,
+            ref package,
+        }) => {
+            let provider_selection = cli.provider_selection();
 
             let ship_config = keryx::ship::ShipConfig {
                 set_version: cli.set_version.clone(),
                 dry_run: cli.dry_run,
                 no_llm_bump: cli.no_llm_bump,
                 no_prs: cli.no_prs,
-                verbose: cli.verbose,
-                no_verify: cli.no_verify,
-                output: cli.output.clone(),
+                remote: cli.remote.clone(),
+                verbose: cli.verbose(),
+                verify: cli.verify_mode(),
+                outputs: cli.output.clone(),
                 provider_selection,
+                version_files: VersionFileSelection::from_flags_or_env(
+                    version_files.clone(),
+                    exclude_version_files.clone(),
+                ),
+                on_version_mismatch: on_version_mismatch.map(VersionMismatchStrategy::from),
+                release_date: ReleaseDateConfig::from_flags_or_env(
+                    cli.date_timezone,
+                    cli.date_source,
+                ),
+                attributes: cli.release_attributes(),
+                heading: cli.heading_template(),
+                template: cli.release_template()?,
+                codename: cli.codename_config(),
+                highlights: cli.highlights,
+                migration_guide: cli.migration_guide,
+                summary_format: cli.summary_format.into(),
+                constraints: cli.entry_constraints(),
+                entry_sort: cli.entry_sort(),
+                schedule,
+                publish_steps: keryx::ship::publish::steps_from_flags_or_env(publish_steps.clone()),
+                release_assets: keryx::ship::assets::patterns_from_flags_or_env(
+                    release_assets.clone(),
+                ),
+                github_release: !no_github_release,
+                attribution: cli.attribution_config(),
+                require_checks: require_checks.then_some(keryx::ship::checks::RequiredChecks {
+                    timeout: checks_timeout,
+                    interval: checks_interval,
+                }),
+                prepare,
+                preflight_overrides: keryx::ship::preflight::PreflightOverrides {
+                    allow_dirty,
+                    allow_behind,
+                    allow_no_upstream,
+                },
+                train,
+                tag_only,
+                prerelease_changelog: prerelease_changelog.clone(),
+                package: package.clone(),
+                exclude_upstream: upstreams_from_flags_or_env(cli.exclude_upstream.clone()),
+                lock: repo_lock.take(),
             };
             keryx::ship::run_ship(ship_config)
                 .await
                 .map_err(|e| anyhow::anyhow!("{}", e))
         }
+        Some(Commands::VerifyShip {
+            ref tag,
+            github_release,
+        }) => keryx::ship::health::run_verify_ship(keryx::ship::health::VerifyShipConfig {
+            tag: tag.clone(),
+            remote: cli
+                .remote
+                .clone()
+                .unwrap_or_else(|| keryx::github::DEFAULT_REMOTE.to_string()),
+            github_release,
+            outputs: cli.output.clone(),
+            version_files: VersionFileSelection::from_flags_or_env(Vec::new(), Vec::new()),
+        })
+        .await
+        .map_err(|e| anyhow::anyhow!("{}", e)),
+        Some(Commands::Translations {
+            ref translations,
+            sync,
+        }) => run_translations(&cli, translations.clone(), sync).await,
+        Some(Commands::Log { limit }) => run_log(limit),
+        Some(Commands::Providers { ref action }) => run_providers(&cli, action.as_ref()),
+        Some(Commands::Announce {
+            target: AnnounceTarget::Email,
+            ref version,
+            ref output,
+            send,
+        }) => run_announce_email(&cli, version.as_ref(), output.as_deref(), send),
+        Some(Commands::Pending { llm }) => run_pending(&cli, llm).await,
+        Some(Commands::NextVersion) => run_next_version(&cli),
+        Some(Commands::Unreleased) => run_unreleased(&cli).await,
+        Some(Commands::Fmt { check }) => run_fmt(&cli, check),
+        Some(Commands::Check) => run_check(&cli),
+        Some(Commands::Compare {
+            ref from,
+            ref to,
+            llm,
+        }) => run_compare(&cli, from, to.as_ref(), llm).await,
+        Some(Commands::Refine { ref entry }) => run_refine(&cli, entry.as_deref()).await,
+        Some(Commands::Shell) => run_shell(&cli).await,
+        Some(Commands::Explain {
+            ref target,
+            no_follow_up,
+        }) => run_explain(&cli, target, no_follow_up).await,
         None => run_generate(cli).await,
     };
 
     // Print update notification at the very end (if available)
     // This prevents output interleaving with main program output
-    update_checker.maybe_notify();
+    if let Some(update_checker) = &update_checker {
+        update_checker.maybe_notify();
+    }
 
     result
 }
 
-/// Check if an update is available (without printing).
+/// Compare translated changelogs with the primary one and optionally
+/// translate the missing sections.
 ///
-/// Returns true if a newer version is available, false otherwise.
-fn check_for_update() -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
-    use axoupdater::AxoUpdater;
+/// Fails when a translation is still missing versions or has outdated
+/// sections afterwards, so the check can gate CI.
+async fn run_translations(cli: &Cli, paths: Vec<PathBuf>, sync: bool) -> Result<()> {
+    let primary_path = cli.primary_output();
+    let primary = std::fs::read_to_string(primary_path)
+        .with_context(|| format!("Failed to read {}", primary_path.display()))?;
+
+    let translations: Vec<TranslatedChangelog> = if paths.is_empty() {
+        detect_translations(primary_path)
+    } else {
+        paths
+            .into_iter()
+            .map(|path| TranslatedChangelog::from_path(primary_path, path))
+            .collect()
+    };
+    if translations.is_empty() {
+        println!("No translations of {} found.", primary_path.display());
+        return Ok(());
+    }
 
-    let mut updater = AxoUpdater::new_for("keryx");
+    let journal = Repository::open(".")
+        .ok()
+        .and_then(|repo| Journal::for_repository(&repo, "translations"));
+    let mut llm: Option<LlmRouter> = None;
+    let mut out_of_date = 0;
 
-    // Load the install receipt to get source info (GitHub owner/repo).
-    // This is required for axoupdater to know where to check for updates.
-    // The receipt also contains the installed version.
-    updater.load_receipt()?;
+    for translation in &translations {
+        let content = std::fs::read_to_string(&translation.path)
+            .with_context(|| format!("Failed to read {}", translation.path.display()))?;
+        let status = compare_translation(&primary, &content);
 
-    Ok(updater.is_update_needed_sync()?)
-}
+        println!();
+        println!("{} ({}):", translation.path.display(), translation.language);
+        if status.is_in_sync() {
+            println!("  [PASS] All versions translated");
+            continue;
+        }
+        for version in &status.outdated {
+            println!(
+                "  [WARN] {} has a different number of entries than the primary",
+                version
+            );
+        }
 
-/// Print the update notification banner to stderr.
-fn print_update_notification() {
-    eprintln!();
-    eprintln!("\x1b[33m╭───────────────────────────────────────────────╮\x1b[0m");
-    eprintln!("\x1b[33m│\x1b[0m  A new version of keryx is available!        \x1b[33m│\x1b[0m");
-    eprintln!(
-        "\x1b[33m│\x1b[0m  Run \x1b[36mkeryx update\x1b[0m to upgrade                 \x1b[33m│\x1b[0m"
-    );
-    eprintln!("\x1b[33m╰───────────────────────────────────────────────╯\x1b[0m");
-    eprintln!();
-}
+        let mut unresolved = status.outdated.len();
+        if !sync {
+            for version in &status.missing {
+                println!("  [MISSING] {}", version);
+            }
+            unresolved += status.missing.len();
+        } else if !status.missing.is_empty() {
+            let llm = llm.get_or_insert_with(|| {
+                let provider_selection = cli.provider_selection();
+                llm_router(provider_selection, journal.clone())
+            });
+
+            let mut updated = content.clone();
+            let mut created = Vec::new();
+            for version in &status.missing {
+                match translate_section(&primary, version, &translation.language, llm).await {
+                    Ok(section) => {
+                        updated = insert_section(&updated, version, &section);
+                        println!("  [CREATE] {}", version);
+                        created.push(version.to_string());
+                    }
+                    Err(reason) => {
+                        println!("  [FAIL] {}: {}", version, reason);
+                        unresolved += 1;
+                    }
+                }
+            }
 
-/// Run the self-update command.
+            if !created.is_empty() {
+                if cli.dry_run {
+                    println!("  (dry run, {} not written)", translation.path.display());
+                } else {
+                    write_translation(&translation.path, &updated).with_context(|| {
+                        format!("Failed to write {}", translation.path.display())
+                    })?;
+                    if let Some(journal) = &journal {
+                        journal.record(
+                            journal
+                                .entry(OperationKind::FileModified)
+                                .arg(translation.path.display().to_string())
+                                .detail(format!("translated {}", created.join(", "))),
+                        );
+                    }
+                }
+            }
+        }
+
+        if unresolved > 0 {
+            out_of_date += 1;
+        }
+    }
+
+    if out_of_date > 0 {
+        let hint = if sync {
+            "review the sections listed above"
+        } else {
+            "run `keryx translations --sync` to translate missing sections"
+        };
+        bail!(
+            "{} translation{} out of date; {}",
+            out_of_date,
+            if out_of_date == 1 { " is" } else { "s are" },
+            hint
+        );
+    }
+    Ok(())
+}
+
+/// Display the operations journal (`.keryx/journal`).
+/// `keryx providers`: routing order, provider status, or the persistent default.
+fn run_providers(cli: &Cli, action: Option<&ProvidersAction>) -> Result<()> {
+    let root = config_root();
+    match action {
+        None | Some(ProvidersAction::List) => {
+            let (selection, source) = ProviderSelection::resolve_with_source(
+                cli.provider.clone().map(Provider::from),
+                &root,
+            );
+            let routed = if selection.has_fallback() {
+                println!(
+                    "Routing: {} (primary), then {} (fallback); primary from {}",
+                    selection.primary, selection.fallback, source
+                );
+                vec![selection.primary, selection.fallback]
+            } else {
+                println!(
+                    "Routing: {} only (no fallback); from {}",
+                    selection.primary, source
+                );
+                vec![selection.primary]
+            };
+            for provider in routed {
+                match (provider.api(), find_cli(provider)) {
+                    (Some(api), _) => {
+                        println!("  {:<13} HTTP API, key from {}", provider, api.key_env())
+                    }
+                    _ if provider == Provider::Ollama => println!(
+                        "  {:<13} server {}",
+                        provider,
+                        OllamaClient::from_env().base_url()
+                    ),
+                    (None, Some(path)) => println!("  {:<13} {}", provider, path.display()),
+                    (None, None) => println!(
+                        "  {:<13} \x1b[33mnot installed ({} not on PATH)\x1b[0m",
+                        provider,
+                        provider_binary(provider).unwrap_or_default()
+                    ),
+                }
+            }
+            Ok(())
+        }
+        Some(ProvidersAction::Status) => {
+            for provider in PROVIDERS {
+                let status = provider_status(provider);
+                println!("{}", provider);
+                match (provider.api(), &status.path, &status.version) {
+                    (Some(api), _, _) => println!(
+                        "  API:   {}",
+                        std::env::var(api.base_url_env())
+                            .unwrap_or_else(|_| api.default_base_url().to_string())
+                    ),
+                    (None, Some(path), Some(version)) => {
+                        println!("  CLI:   {} ({})", path.display(), version)
+                    }
+                    (None, Some(path), None) => println!("  CLI:   {}", path.display()),
+                    (None, None, _) => println!(
+                        "  CLI:   \x1b[33mnot installed ({} not on PATH)\x1b[0m",
+                        provider_binary(provider).unwrap_or_default()
+                    ),
+                }
+                println!("  Auth:  {}", status.auth);
+                match &status.model {
+                    Some((model, source)) => println!("  Model: {} ({})", model, source),
+                    None => println!("  Model: CLI default"),
+                }
+            }
+            Ok(())
+        }
+        Some(ProvidersAction::SetDefault { provider }) => {
+            let provider = Provider::from(provider.clone());
+            let path = set_default_provider(&root, provider)?;
+            println!("Default provider set to {} in {}", provider, path.display());
+            Ok(())
+        }
+    }
+}
+
+fn run_log(limit: Option<usize>) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let journal = Journal::for_repository(&repo, "log")
+        .context("Bare repositories have no operations journal")?;
+
+    let entries = journal
+        .read_all()
+        .with_context(|| format!("Failed to read {}", journal.path().display()))?;
+
+    if entries.is_empty() {
+        println!("No operations recorded yet.");
+        return Ok(());
+    }
+
+    let skip = limit.map_or(0, |n| entries.len().saturating_sub(n));
+    for entry in &entries[skip..] {
+        println!("{}", format_entry(entry));
+    }
+
+    Ok(())
+}
+
+/// Summarize what the next release would contain (`keryx pending`).
+///
+/// Needs no LLM unless `--llm` asks for a narrative on top of the list.
+/// Commits reachable from `to` but not from `from` or an `--exclude-upstream`
+/// ref. With `announce`, says which upstream history is left out.
+fn fetch_range_commits(
+    repo: &Repository,
+    from: git2::Oid,
+    to: git2::Oid,
+    cli: &Cli,
+    announce: bool,
+) -> Result<Vec<ParsedCommit>> {
+    let specs = upstreams_from_flags_or_env(cli.exclude_upstream.clone());
+    let upstream =
+        resolve_upstreams(repo, &specs).context("Failed to resolve --exclude-upstream")?;
+    if announce {
+        for exclusion in &upstream {
+            status!("Leaving out commits reachable from {}", exclusion);
+        }
+    }
+    walk_commits(repo, from, to, cli.strict)
+        .and_then(|walk| walk.hide(&upstream_oids(&upstream)))
+        .and_then(|walk| walk.collect())
+        .context("Failed to fetch commits")
+}
+
+async fn run_pending(cli: &Cli, narrative: bool) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let range = resolve_range(&repo, None, Some("HEAD"), cli.strict)
+        .context("Failed to resolve commit range")?;
+    let commits = fetch_range_commits(&repo, range.from, range.to, cli, false)?;
+    let commits = net_commits(&repo, commits, cli.verbose());
+
+    let report = PendingReport::new(
+        &range.from_ref,
+        get_version_from_tag(&range.from_ref),
+        &commits,
+    );
+    if report.is_empty() {
+        println!("Nothing pending since {}.", range.from_ref);
+        return Ok(());
+    }
+    print!("{}", report.render());
+
+    if !narrative {
+        return Ok(());
+    }
+    let provider_selection = cli.provider_selection();
+    let journal = Journal::for_repository(&repo, "pending");
+    let mut llm = llm_router(provider_selection, journal);
+    let remote = select_pr_remote(&repo, cli.remote.as_deref());
+    let repo_name = get_repo_name(&repo, &remote).unwrap_or_else(|| "repository".to_string());
+
+    println!();
+    println!("Summarizing with {}...", llm.primary());
+    let narrative = llm
+        .request(&TextRequest::new(build_pending_prompt(&report, &repo_name)))
+        .await
+        .map_err(|e| handle_llm_error(e, cli.verbose()))?
+        .output;
+    println!();
+    println!("{}", narrative.trim());
+    Ok(())
+}
+
+/// Infer the next version from the `[Unreleased]` section
+/// (`keryx next-version`).
+///
+/// Only the changelog is read: the latest version in it is bumped by what
+/// the entries call for. The version alone goes to stdout so scripts can
+/// capture it.
+fn run_next_version(cli: &Cli) -> Result<()> {
+    let path = cli.primary_output();
+    let changelog = read_changelog(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .with_context(|| format!("{} does not exist", path.display()))?;
+    let bump = determine_unreleased_bump(
+        &changelog.raw_content,
+        &CategoryAliases::load(&config_root()),
+    )
+    .with_context(|| format!("No entries under [Unreleased] in {}", path.display()))?;
+    let next = bump.next_version(changelog.latest_version.as_ref());
+
+    if cli.verbose() {
+        eprintln!(
+            "{:?} bump from {} entr{} after {}; decided by \"{}\"",
+            bump.bump,
+            bump.entries,
+            if bump.entries == 1 { "y" } else { "ies" },
+            changelog
+                .latest_version
+                .as_ref()
+                .map_or_else(|| "no release".to_string(), |v| v.to_string()),
+            truncate_description(&bump.reason, 80)
+        );
+    }
+    println!("{}", next);
+    Ok(())
+}
+
+/// Add entries for the commits since the latest tag to the `[Unreleased]`
+/// section (`keryx unreleased`).
+///
+/// Generated entries cite their PRs and commits, and only those not in the
+/// section yet are added, so it can run after every merge. Entries written by
+/// hand are matched by their text.
+async fn run_unreleased(cli: &Cli) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let journal = Journal::for_repository(&repo, "unreleased");
+    let mut llm = llm_router(cli.provider_selection(), journal.clone());
+    let remote = select_pr_remote(&repo, cli.remote.as_deref());
+
+    let range = resolve_range(&repo, cli.from.as_deref(), Some(&cli.to), cli.strict)
+        .context("Failed to resolve commit range")?;
+    println!(
+        "Analyzing commits from {} to {}...",
+        range.from_ref, range.to_ref
+    );
+    let commits = fetch_range_commits(&repo, range.from, range.to, cli, true)?;
+    let commits = net_commits(&repo, commits, cli.verbose());
+    if commits.is_empty() {
+        println!("No changes found since {}. Nothing to add.", range.from_ref);
+        return Ok(());
+    }
+    println!("Found {} commits", commits.len());
+
+    let pull_requests = if should_fetch_prs(&repo, &remote, cli.no_prs) {
+        let scope = PrScope::for_commits(&commits);
+        match fetch_prs_for_repo(&repo, &remote, &scope, cli.pr_limit).await {
+            Ok(prs) => {
+                println!("Found {} merged PRs", prs.len());
+                prs
+            }
+            Err(e) => handle_pr_fetch_error(e, cli.strict)?,
+        }
+    } else {
+        Vec::new()
+    };
+
+    let prompt_bytes = estimate::prompt_bytes(&commits, &pull_requests);
+    let mut plan = CostEstimate::new(llm.primary());
+    plan.add("changelog", prompt_bytes);
+    if cli.verify_mode() == VerifyMode::Llm {
+        plan.add("verification", prompt_bytes);
+    }
+    if !confirm_llm_plan(&plan, cli.yes)? {
+        return Ok(());
+    }
+
+    let input = ChangelogInput {
+        commits,
+        pull_requests,
+        previous_version: get_version_from_tag(&range.from_ref),
+        repository_name: get_repo_name(&repo, &remote).unwrap_or_else(|| "repository".to_string()),
+        project_description: None,
+        cli_features: None,
+        product: ProductContext::for_repository(&repo),
+        languages: LanguageProfile::for_repository(&repo),
+        include_highlights: false,
+        // Entries already in the section are recognized by the sources they cite
+        include_sources: true,
+        entry_sort: cli.entry_sort(),
+        prs_only: false,
+    };
+    let mut changelog_output = generate_verified_entries(cli, &repo, &input, &mut llm).await?;
+    if changelog_output.entries.is_empty() {
+        println!("No verified changelog entries found. Nothing to add.");
+        return Ok(());
+    }
+    apply_style_guide(&mut changelog_output, &repo);
+
+    let aliases = CategoryAliases::for_repository(&repo);
+    if cli.dry_run {
+        let path = cli.primary_output();
+        let existing = read_changelog(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+            .map_or_else(
+                || CHANGELOG_HEADER.to_string(),
+                |c| normalize_line_endings(&c.raw_content),
+            );
+        let merged = merge_unreleased(
+            &existing,
+            &changelog_output,
+            cli.entry_sort(),
+            None,
+            &aliases,
+        );
+        println!("\n--- Dry Run Output ---\n");
+        println!(
+            "{}",
+            unreleased_section(&merged.content).unwrap_or_default()
+        );
+        println!(
+            "\n{} new entr{}, {} already in [Unreleased]",
+            merged.added,
+            if merged.added == 1 { "y" } else { "ies" },
+            merged.kept
+        );
+        return Ok(());
+    }
+
+    let counts =
+        merge_unreleased_changelogs(&cli.output, &changelog_output, cli.entry_sort(), &aliases)
+            .context("Failed to update the [Unreleased] section")?;
+    if let Some(journal) = &journal {
+        for path in &cli.output {
+            journal.record(
+                journal
+                    .entry(OperationKind::FileModified)
+                    .arg(path.display().to_string())
+                    .detail(format!("merged {} entries into [Unreleased]", counts.added)),
+            );
+        }
+    }
+    println!(
+        "✓ Added {} new entr{} to [Unreleased] ({} already present)",
+        counts.added,
+        if counts.added == 1 { "y" } else { "ies" },
+        counts.kept
+    );
+    Ok(())
+}
+
+/// Entries for `input`: PR changelog sections and Release-Note trailers as
+/// given, the rest drafted by the LLM and verified.
+async fn generate_verified_entries(
+    cli: &Cli,
+    repo: &Repository,
+    input: &ChangelogInput,
+    llm: &mut LlmRouter,
+) -> Result<keryx::ChangelogOutput> {
+    let curated = CuratedNotes::from_pull_requests(&input.pull_requests);
+    let llm_input = curated.llm_input(input);
+    let commit_notes = CommitNotes::from_commits(&llm_input.commits);
+    let prompt_input = commit_notes.llm_input(&llm_input);
+
+    let mut draft_output = if nothing_to_generate(&prompt_input) {
+        keryx::ChangelogOutput {
+            entries: Vec::new(),
+            highlights: None,
+        }
+    } else {
+        let prompt = build_prompt(&prompt_input).context("Failed to build prompt for LLM")?;
+        println!(
+            "Generating release notes with {} (fallback: {})...",
+            llm.primary(),
+            llm.fallback()
+        );
+        let draft_completion = generate_guarded(
+            llm,
+            &prompt,
+            SanitizeOptions::from_env(),
+            &cli.entry_constraints(),
+            &prompt_input.commits,
+        )
+        .await
+        .map_err(|e| handle_llm_error(e, cli.verbose()))?;
+        report_llm_fallback_if_any(&draft_completion, cli.verbose());
+        draft_completion.output
+    };
+    commit_notes.merge_into(&mut draft_output);
+    draft_output.highlights = None;
+
+    let corpus = SourceCorpus::for_repository(repo, &llm_input.commits, &llm_input.pull_requests);
+    let mut changelog_output = verify_draft(
+        draft_output,
+        repo,
+        &llm_input.commits,
+        &corpus,
+        cli.verify_mode(),
+        cli.verbose(),
+        llm,
+        None,
+    )
+    .await?
+    .output;
+    curated.merge_into(&mut changelog_output);
+    Ok(changelog_output)
+}
+
+/// Combine the changelog sections between two versions (`keryx compare`).
+///
+/// Only the changelog is read, unless `summarize` asks the LLM for an
+/// upgrade summary of the combined list.
+async fn run_compare(
+    cli: &Cli,
+    from: &Version,
+    to: Option<&Version>,
+    summarize: bool,
+) -> Result<()> {
+    let path = cli.primary_output();
+    let changelog = read_changelog(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .with_context(|| format!("{} does not exist", path.display()))?;
+    let to = to
+        .or(changelog.latest_version.as_ref())
+        .with_context(|| format!("No released version in {}", path.display()))?;
+    if from >= to {
+        anyhow::bail!("{} is not older than {}", from, to);
+    }
+
+    let notes = CombinedNotes::from_changelog(&changelog.raw_content, from, to);
+    if notes.is_empty() {
+        println!(
+            "No changelog entries between {} and {} in {}.",
+            from,
+            to,
+            path.display()
+        );
+        return Ok(());
+    }
+    print!("{}", notes.render());
+
+    if !summarize {
+        return Ok(());
+    }
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let journal = Journal::for_repository(&repo, "compare");
+    let mut llm = llm_router(cli.provider_selection(), journal);
+    let remote = select_pr_remote(&repo, cli.remote.as_deref());
+    let repo_name = get_repo_name(&repo, &remote).unwrap_or_else(|| "repository".to_string());
+
+    println!();
+    println!(
+        "Summarizing {} entries with {}...",
+        notes.entry_count(),
+        llm.primary()
+    );
+    let summary = llm
+        .request(&TextRequest::new(build_compare_prompt(&notes, &repo_name)))
+        .await
+        .map_err(|e| handle_llm_error(e, cli.verbose()))?
+        .output;
+    println!();
+    println!("{}", summary.trim());
+    Ok(())
+}
+
+/// Render a release as an email (`keryx announce email`).
+///
+/// The message is printed, written to `output`, and/or sent with `send`;
+/// `--dry-run` never sends.
+fn run_announce_email(
+    cli: &Cli,
+    version: Option<&Version>,
+    output: Option<&std::path::Path>,
+    send: bool,
+) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let path = cli.primary_output();
+    let changelog = read_changelog(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?
+        .with_context(|| format!("{} does not exist", path.display()))?;
+    let version = version
+        .or(changelog.latest_version.as_ref())
+        .with_context(|| format!("No released version in {}", path.display()))?;
+    let section = version_section(&changelog.raw_content, version)
+        .with_context(|| format!("Version {} not found in {}", version, path.display()))?;
+
+    let config = repo.workdir().map(EmailConfig::load).unwrap_or_default();
+    let remote = select_pr_remote(&repo, cli.remote.as_deref());
+    let project = get_repo_name(&repo, &remote)
+        .or_else(|| {
+            repo.workdir()
+                .and_then(|root| root.file_name())
+                .map(|name| name.to_string_lossy().to_string())
+        })
+        .unwrap_or_else(|| "repository".to_string());
+    let email = render_email(&section, &project, version, config.subject.as_deref());
+    let from = config
+        .from
+        .clone()
+        .unwrap_or_else(|| "releases@localhost".to_string());
+    let message = mime_message(
+        &email,
+        &from,
+        &config.to,
+        chrono::Local::now().fixed_offset(),
+    );
+
+    match output {
+        Some(output) => {
+            if cli.dry_run {
+                println!("(dry run, {} not written)", output.display());
+            } else {
+                write_message(output, &message)?;
+                println!("✓ Wrote {}", output.display());
+            }
+        }
+        None if !send => print!("{}", message.replace("\r\n", "\n")),
+        None => {}
+    }
+
+    if send {
+        if cli.dry_run {
+            println!(
+                "(dry run, not sent to {})",
+                if config.to.is_empty() {
+                    "anyone".to_string()
+                } else {
+                    config.to.join(", ")
+                }
+            );
+        } else {
+            send_message(&config, &message)?;
+            println!("✓ Sent \"{}\" to {}", email.subject, config.to.join(", "));
+        }
+    }
+    Ok(())
+}
+
+/// Rewrite changelog entries with the LLM (`keryx refine`).
+///
+/// Pick an entry, then type instructions until it reads right; each result
+/// is shown as a diff and written only once accepted. An empty instruction
+/// goes back to the entry list, Esc quits.
+async fn run_refine(cli: &Cli, query: Option<&str>) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!("keryx refine is interactive; run it in a terminal");
+    }
+    let path = cli.primary_output();
+    let mut content = std::fs::read_to_string(path)
+        .with_context(|| format!("Failed to read {}", path.display()))?;
+
+    let provider_selection = cli.provider_selection();
+    let journal = Repository::open(".")
+        .ok()
+        .and_then(|repo| Journal::for_repository(&repo, "refine"));
+    let mut llm = llm_router(provider_selection, journal.clone());
+    let needle = query.map(|q| q.trim().to_lowercase());
+
+    loop {
+        let entries: Vec<RefinableEntry> = list_entries(&content)
+            .into_iter()
+            .filter(|e| {
+                needle
+                    .as_deref()
+                    .is_none_or(|n| e.description.to_lowercase().contains(n))
+            })
+            .collect();
+        let entry = match (entries.len(), query) {
+            (0, Some(query)) => bail!(
+                "No changelog entry outside frozen sections matches \"{}\"",
+                query
+            ),
+            (0, None) => {
+                println!("No entries to refine in {}.", path.display());
+                return Ok(());
+            }
+            (1, Some(_)) => entries[0].clone(),
+            _ => {
+                let labels: Vec<String> = entries
+                    .iter()
+                    .map(|e| truncate_description(&e.label(), 100))
+                    .collect();
+                let selected = dialoguer::Select::new()
+                    .with_prompt("Entry to refine (Esc to quit)")
+                    .items(&labels)
+                    .default(0)
+                    .interact_opt()
+                    .context("Failed to read selection")?;
+                match selected {
+                    Some(index) => entries[index].clone(),
+                    None => return Ok(()),
+                }
+            }
+        };
+
+        content = refine_entry(cli, path, content, entry, &mut llm, journal.as_ref()).await?;
+        if query.is_some() && entries.len() == 1 {
+            return Ok(());
+        }
+    }
+}
+
+/// Refine one entry until an empty instruction; returns the changelog with
+/// the accepted rewrites applied.
+async fn refine_entry(
+    cli: &Cli,
+    path: &std::path::Path,
+    mut content: String,
+    mut entry: RefinableEntry,
+    llm: &mut LlmRouter,
+    journal: Option<&Journal>,
+) -> Result<String> {
+    println!();
+    println!("{}{}", entry.bullet, entry.description);
+    loop {
+        let instruction: String = dialoguer::Input::new()
+            .with_prompt("Instruction (empty for another entry)")
+            .allow_empty(true)
+            .interact_text()
+            .context("Failed to read instruction")?;
+        let instruction = instruction.trim();
+        if instruction.is_empty() {
+            return Ok(content);
+        }
+
+        println!("Refining with {}...", llm.primary());
+        let output = llm
+            .request(&TextRequest::new(build_refine_prompt(&entry, instruction)))
+            .await
+            .map_err(|e| handle_llm_error(e, cli.verbose()))?
+            .output;
+        let Some(refined) = parse_refined(&output) else {
+            println!("  [WARN] The LLM returned no entry, try rephrasing the instruction");
+            continue;
+        };
+        println!("  \x1b[31m- {}\x1b[0m", entry.description);
+        println!("  \x1b[32m+ {}\x1b[0m", refined);
+
+        let apply = dialoguer::Confirm::new()
+            .with_prompt("Apply?")
+            .default(true)
+            .interact()
+            .context("Failed to read confirmation")?;
+        if !apply {
+            continue;
+        }
+
+        content = replace_entry(&content, &entry, &refined)
+            .with_context(|| format!("Entry no longer found in {}", path.display()))?;
+        if cli.dry_run {
+            println!("  (dry run, {} not written)", path.display());
+        } else {
+            write_refined(path, &content)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+            if let Some(journal) = journal {
+                journal.record(
+                    journal
+                        .entry(OperationKind::FileModified)
+                        .arg(path.display().to_string())
+                        .detail(format!("refined entry in {}", entry.section)),
+                );
+            }
+            println!("  [DONE] Updated {}", path.display());
+        }
+        entry.description = refined;
+    }
+}
+
+/// Commits, PRs and version of the range a `keryx shell` session works on.
+struct ShellRange {
+    from_ref: String,
+    to_ref: String,
+    input: ChangelogInput,
+    newest_commit: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+/// State of a `keryx shell` session between commands.
+struct ShellSession {
+    repo: Repository,
+    llm: LlmRouter,
+    journal: Option<Journal>,
+    remote: String,
+    aliases: CategoryAliases,
+    range: Option<ShellRange>,
+    version: Option<Version>,
+    draft: Option<keryx::ChangelogOutput>,
+    /// Whether the draft has been written since its last change.
+    written: bool,
+}
+
+impl ShellSession {
+    fn range(&self) -> Result<&ShellRange> {
+        self.range
+            .as_ref()
+            .context("No commits collected yet; run `range` first")
+    }
+
+    fn draft(&self) -> Result<&keryx::ChangelogOutput> {
+        self.draft
+            .as_ref()
+            .context("No draft yet; run `generate` first")
+    }
+
+    fn version(&self) -> Result<&Version> {
+        self.version
+            .as_ref()
+            .context("No version yet; run `range` or `version VERSION`")
+    }
+
+    /// The draft entry with 1-based `number`.
+    fn entry_mut(&mut self, number: usize) -> Result<&mut keryx::ChangelogEntry> {
+        let draft = self
+            .draft
+            .as_mut()
+            .context("No draft yet; run `generate` first")?;
+        let count = draft.entries.len();
+        draft
+            .entries
+            .get_mut(number - 1)
+            .with_context(|| format!("No entry {} (the draft has {})", number, count))
+    }
+
+    fn print_entries(&self) -> Result<()> {
+        let draft = self.draft()?;
+        if draft.entries.is_empty() {
+            println!("  (no entries)");
+        }
+        for (index, entry) in draft.entries.iter().enumerate() {
+            println!(
+                "  {:>2}. [{}] {}",
+                index + 1,
+                entry.category.as_str(),
+                entry.description
+            );
+        }
+        Ok(())
+    }
+
+    /// Metadata for writing or previewing the draft as `version`.
+    fn metadata(&self, cli: &Cli, version: &Version) -> Result<ReleaseMetadata> {
+        let existing = read_changelog(cli.primary_output())
+            .context("Failed to read existing changelog")?
+            .map(|c| c.raw_content);
+        let latest_tag = get_latest_tag(&self.repo)?;
+        Ok(ReleaseMetadata {
+            date: ReleaseDateConfig::from_flags_or_env(cli.date_timezone, cli.date_source)
+                .release_date(self.range.as_ref().and_then(|r| r.newest_commit)),
+            heading: cli.heading_template(),
+            codename: cli.codename_config().resolve(existing.as_deref()),
+            attributes: cli.release_attributes(),
+            compare_links: get_repo_web_url(&self.repo, &self.remote).map(|repo_url| {
+                CompareLinks {
+                    repo_url,
+                    tag: format!("v{}", version),
+                    previous_tag: latest_tag.map(|t| t.name),
+                }
+            }),
+            attribution: None,
+            sort: cli.entry_sort(),
+            source_markers: false,
+            category_aliases: self.aliases.clone(),
+            template: cli.release_template()?,
+        })
+    }
+
+    async fn run(&mut self, cli: &Cli, command: ShellCommand) -> Result<()> {
+        match command {
+            ShellCommand::Range { from, to } => {
+                let from = from.or_else(|| cli.from.clone());
+                let to = to.unwrap_or_else(|| cli.to.clone());
+                self.collect(cli, from.as_deref(), &to).await
+            }
+            ShellCommand::Status => {
+                let Some(range) = &self.range else {
+                    println!("  No commits collected yet; run `range`");
+                    return Ok(());
+                };
+                println!("  Range: {}..{}", range.from_ref, range.to_ref);
+                println!(
+                    "  Input: {} commits, {} PRs",
+                    range.input.commits.len(),
+                    range.input.pull_requests.len()
+                );
+                if let Some(version) = &self.version {
+                    println!("  Version: {}", version);
+                }
+                match &self.draft {
+                    Some(draft) => println!(
+                        "  Draft: {} entr{}{}",
+                        draft.entries.len(),
+                        if draft.entries.len() == 1 { "y" } else { "ies" },
+                        if self.written {
+                            ", written"
+                        } else {
+                            ", not written"
+                        }
+                    ),
+                    None => println!("  Draft: none; run `generate`"),
+                }
+                Ok(())
+            }
+            ShellCommand::Generate => {
+                let input = &self.range()?.input;
+                let prompt_bytes = estimate::prompt_bytes(&input.commits, &input.pull_requests);
+                let mut plan = CostEstimate::new(self.llm.primary());
+                plan.add("changelog", prompt_bytes);
+                if cli.verify_mode() == VerifyMode::Llm {
+                    plan.add("verification", prompt_bytes);
+                }
+                if !confirm_llm_plan(&plan, cli.yes)? {
+                    return Ok(());
+                }
+                let input = self.range()?.input.clone();
+                let mut output =
+                    generate_verified_entries(cli, &self.repo, &input, &mut self.llm).await?;
+                apply_style_guide(&mut output, &self.repo);
+                self.draft = Some(output);
+                self.written = false;
+                self.print_entries()
+            }
+            ShellCommand::Entries => self.print_entries(),
+            ShellCommand::Edit { entry, description } => {
+                self.entry_mut(entry)?.description = description;
+                self.written = false;
+                Ok(())
+            }
+            ShellCommand::Move { entry, category } => {
+                let category = self
+                    .aliases
+                    .resolve(&category)
+                    .with_context(|| format!("Unknown category \"{}\"", category))?;
+                self.entry_mut(entry)?.category = category;
+                self.written = false;
+                Ok(())
+            }
+            ShellCommand::Drop(entry) => {
+                self.entry_mut(entry)?;
+                let removed = self
+                    .draft
+                    .as_mut()
+                    .map(|draft| draft.entries.remove(entry - 1));
+                if let Some(removed) = removed {
+                    println!("  Dropped: {}", removed.description);
+                }
+                self.written = false;
+                Ok(())
+            }
+            ShellCommand::Refine { entry, instruction } => {
+                let section = self
+                    .version
+                    .as_ref()
+                    .map_or_else(|| "Unreleased".to_string(), Version::to_string);
+                let current = self.entry_mut(entry)?;
+                let refinable = RefinableEntry {
+                    line: 0,
+                    section,
+                    category: Some(current.category.as_str().to_string()),
+                    bullet: "- ".to_string(),
+                    description: current.description.clone(),
+                };
+                println!("Refining with {}...", self.llm.primary());
+                let output = self
+                    .llm
+                    .request(&TextRequest::new(build_refine_prompt(
+                        &refinable,
+                        &instruction,
+                    )))
+                    .await
+                    .map_err(|e| handle_llm_error(e, cli.verbose()))?
+                    .output;
+                let Some(refined) = parse_refined(&output) else {
+                    println!("  [WARN] The LLM returned no entry, try rephrasing the instruction");
+                    return Ok(());
+                };
+                println!("  \x1b[31m- {}\x1b[0m", refinable.description);
+                println!("  \x1b[32m+ {}\x1b[0m", refined);
+                let apply = dialoguer::Confirm::new()
+                    .with_prompt("Apply?")
+                    .default(true)
+                    .interact()
+                    .context("Failed to read confirmation")?;
+                if apply {
+                    self.entry_mut(entry)?.description = refined;
+                    self.written = false;
+                }
+                Ok(())
+            }
+            ShellCommand::Verify => {
+                let draft = self.draft()?.clone();
+                let range = self.range()?;
+                let commits = range.input.commits.clone();
+                let corpus =
+                    SourceCorpus::for_repository(&self.repo, &commits, &range.input.pull_requests);
+                let before = draft.entries.len();
+                let verified = verify_draft(
+                    draft,
+                    &self.repo,
+                    &commits,
+                    &corpus,
+                    cli.verify_mode(),
+                    cli.verbose(),
+                    &mut self.llm,
+                    None,
+                )
+                .await?;
+                let dropped = before - verified.output.entries.len();
+                println!(
+                    "  [{}] {} of {} entries verified",
+                    if dropped == 0 { "PASS" } else { "WARN" },
+                    verified.output.entries.len(),
+                    before
+                );
+                if dropped > 0 {
+                    self.written = false;
+                }
+                self.draft = Some(verified.output);
+                Ok(())
+            }
+            ShellCommand::Version(None) => {
+                println!("  {}", self.version()?);
+                Ok(())
+            }
+            ShellCommand::Version(Some(version)) => {
+                self.version = Some(version);
+                self.written = false;
+                Ok(())
+            }
+            ShellCommand::Preview => {
+                let version = self.version()?;
+                let metadata = self.metadata(cli, version)?;
+                print_changelog_preview(self.draft()?, version, &metadata);
+                Ok(())
+            }
+            ShellCommand::Write => self.write(cli),
+            ShellCommand::Ship(args) => {
+                if !self.written {
+                    self.write(cli)?;
+                }
+                let version = self.version()?.to_string();
+                let exe = std::env::current_exe().context("Failed to locate the keryx binary")?;
+                let mut command = Command::new(exe);
+                command.arg("ship").arg("--set-version").arg(&version);
+                for path in &cli.output {
+                    command.arg("--output").arg(path);
+                }
+                let status = command
+                    .args(&args)
+                    .status()
+                    .await
+                    .context("Failed to run keryx ship")?;
+                if !status.success() {
+                    bail!("keryx ship failed ({})", status);
+                }
+                Ok(())
+            }
+            ShellCommand::Help => {
+                println!("{}", keryx::shell::HELP);
+                Ok(())
+            }
+            ShellCommand::Quit => Ok(()),
+        }
+    }
+
+    /// Collect the commits and PRs of `from..to` and propose a version.
+    async fn collect(&mut self, cli: &Cli, from: Option<&str>, to: &str) -> Result<()> {
+        let range = resolve_range(&self.repo, from, Some(to), cli.strict)
+            .context("Failed to resolve commit range")?;
+        println!(
+            "Analyzing commits from {} to {}...",
+            range.from_ref, range.to_ref
+        );
+        let commits = fetch_range_commits(&self.repo, range.from, range.to, cli, true)?;
+        let commits = net_commits(&self.repo, commits, cli.verbose());
+        println!("Found {} commits", commits.len());
+
+        let pull_requests = if should_fetch_prs(&self.repo, &self.remote, cli.no_prs) {
+            let scope = PrScope::for_commits(&commits);
+            match fetch_prs_for_repo(&self.repo, &self.remote, &scope, cli.pr_limit).await {
+                Ok(prs) => {
+                    println!("Found {} merged PRs", prs.len());
+                    prs
+                }
+                Err(e) => handle_pr_fetch_error(e, cli.strict)?,
+            }
+        } else {
+            Vec::new()
+        };
+
+        let previous_version = get_version_from_tag(&range.from_ref);
+        let version = cli
+            .set_version
+            .clone()
+            .unwrap_or_else(|| calculate_next_version(previous_version.as_ref(), &commits));
+        println!(
+            "Version: {} -> {}",
+            previous_version
+                .as_ref()
+                .map_or_else(|| "none".to_string(), Version::to_string),
+            version
+        );
+
+        let newest_commit = commits.iter().map(|c| c.timestamp).max();
+        let input = ChangelogInput {
+            commits,
+            pull_requests,
+            previous_version,
+            repository_name: get_repo_name(&self.repo, &self.remote)
+                .unwrap_or_else(|| "repository".to_string()),
+            project_description: None,
+            cli_features: None,
+            product: ProductContext::for_repository(&self.repo),
+            languages: LanguageProfile::for_repository(&self.repo),
+            include_highlights: false,
+            include_sources: false,
+            entry_sort: cli.entry_sort(),
+            prs_only: false,
+        };
+        self.range = Some(ShellRange {
+            from_ref: range.from_ref,
+            to_ref: range.to_ref,
+            input,
+            newest_commit,
+        });
+        self.version = Some(version);
+        self.draft = None;
+        self.written = false;
+        Ok(())
+    }
+
+    /// Write the draft as a new section of every changelog.
+    fn write(&mut self, cli: &Cli) -> Result<()> {
+        let draft = self.draft()?;
+        if draft.entries.is_empty() {
+            bail!("The draft has no entries");
+        }
+        let version = self.version()?;
+        for path in &cli.output {
+            if let Some(changelog) = read_changelog(path)
+                .with_context(|| format!("Failed to read {}", path.display()))?
+                && changelog.has_version(version)
+            {
+                bail!(
+                    "Version {} already exists in {}; choose another with `version`",
+                    version,
+                    path.display()
+                );
+            }
+        }
+        let metadata = self.metadata(cli, version)?;
+        if cli.dry_run {
+            print_changelog_preview(draft, version, &metadata);
+            println!("  (dry run, nothing written)");
+            return Ok(());
+        }
+        // The session holds the repository lock only while writing, so a
+        // `ship` started from it can take it
+        let _lock = RepoLock::for_repository(&self.repo, "shell")?;
+        write_changelogs_with_metadata(&cli.output, draft, version, &metadata)
+            .context("Failed to write changelog")?;
+        if let Some(journal) = &self.journal {
+            for path in &cli.output {
+                journal.record(
+                    journal
+                        .entry(OperationKind::FileModified)
+                        .arg(path.display().to_string())
+                        .detail(format!("added section {}", version)),
+                );
+            }
+        }
+        println!("✓ {}", generate_summary(draft));
+        self.written = true;
+        Ok(())
+    }
+}
+
+/// Craft a release in one interactive session (`keryx shell`).
+///
+/// Commits and PRs are collected once; the draft stays in memory while it is
+/// generated, edited, refined and verified, and is written only on `write`
+/// or `ship`. A failing command is reported and the session goes on.
+async fn run_shell(cli: &Cli) -> Result<()> {
+    if !std::io::stdin().is_terminal() {
+        bail!("keryx shell is interactive; run it in a terminal");
+    }
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let journal = Journal::for_repository(&repo, "shell");
+    let remote = select_pr_remote(&repo, cli.remote.as_deref());
+    let mut session = ShellSession {
+        llm: llm_router(cli.provider_selection(), journal.clone()),
+        journal,
+        remote,
+        aliases: CategoryAliases::for_repository(&repo),
+        repo,
+        range: None,
+        version: None,
+        draft: None,
+        written: false,
+    };
+
+    println!("keryx shell: type `help` for commands, `quit` to leave");
+    if let Err(e) = session.collect(cli, cli.from.as_deref(), &cli.to).await {
+        println!("  [FAIL] {:#}", e);
+    }
+    while let Ok(line) = dialoguer::Input::<String>::new()
+        .with_prompt("keryx")
+        .allow_empty(true)
+        .interact_text()
+    {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let command = match line.parse::<ShellCommand>() {
+            Ok(ShellCommand::Quit) => break,
+            Ok(command) => command,
+            Err(e) => {
+                println!("  {}", e);
+                continue;
+            }
+        };
+        if let Err(e) = session.run(cli, command).await {
+            println!("  [FAIL] {:#}", e);
+        }
+    }
+
+    if session.draft.is_some() && !session.written {
+        println!("The draft was not written.");
+    }
+    Ok(())
+}
+
+/// Explain a changelog entry or commit (`keryx explain`).
+///
+/// Gathers the commit diffs and PR discussion behind the target, prints the
+/// LLM's explanation, and then answers follow-up questions until an empty
+/// line when stdin is a terminal.
+async fn run_explain(cli: &Cli, target: &str, no_follow_up: bool) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let changelog = read_changelog(cli.primary_output())
+        .context("Failed to read existing changelog")?
+        .map(|c| c.raw_content);
+
+    let (entry, oids) = match resolve_target(&repo, changelog.as_deref(), target)? {
+        ExplainTarget::Commit(oid) => (None, vec![oid]),
+        ExplainTarget::Entry(location) => {
+            println!("Entry [{}]: {}", location.section, location.description);
+            let oids = entry_commits(&repo, &location)?;
+            (Some(location.description), oids)
+        }
+    };
+    let commits = oids
+        .into_iter()
+        .map(|oid| commit_context(&repo, oid))
+        .collect::<Result<Vec<_>, _>>()?;
+    for commit in &commits {
+        println!(
+            "  {} {}",
+            &commit.hash[..7],
+            commit.message.lines().next().unwrap_or_default()
+        );
+    }
+
+    let remote = select_pr_remote(&repo, cli.remote.as_deref());
+    let mut discussions = Vec::new();
+    let referenced = referenced_pr_numbers(
+        commits
+            .iter()
+            .map(|c| c.message.lines().next().unwrap_or_default()),
+    );
+    if !referenced.is_empty() && should_fetch_prs(&repo, &remote, cli.no_prs) {
+        let mut numbers: Vec<u64> = referenced.into_iter().collect();
+        numbers.sort_unstable();
+        match fetch_pr_discussions_for_repo(&repo, &remote, &numbers).await {
+            Ok(found) => discussions = found,
+            Err(e) if cli.strict => return Err(e),
+            Err(e) => warn!("Could not fetch PR discussion: {:#}", e),
+        }
+    }
+
+    let context = ChangeContext {
+        entry,
+        commits,
+        discussions,
+    };
+
+    let provider_selection = cli.provider_selection();
+    let journal = Journal::for_repository(&repo, "explain");
+    let mut llm = llm_router(provider_selection, journal);
+
+    println!();
+    println!("Explaining with {}...", llm.primary());
+    let explanation = llm
+        .request(&TextRequest::new(build_explain_prompt(&context)))
+        .await
+        .map_err(|e| handle_llm_error(e, cli.verbose()))?
+        .output;
+    println!();
+    println!("{}", explanation.trim());
+
+    if no_follow_up || !std::io::stdin().is_terminal() {
+        return Ok(());
+    }
+
+    let mut history: Vec<(String, String)> = Vec::new();
+    loop {
+        println!();
+        let question: String = dialoguer::Input::new()
+            .with_prompt("Follow-up question (empty to quit)")
+            .allow_empty(true)
+            .interact_text()
+            .context("Failed to read question")?;
+        let question = question.trim();
+        if question.is_empty() {
+            return Ok(());
+        }
+        let answer = llm
+            .request(&TextRequest::new(build_followup_prompt(
+                &context, &history, question,
+            )))
+            .await
+            .map_err(|e| handle_llm_error(e, cli.verbose()))?
+            .output;
+        println!();
+        println!("{}", answer.trim());
+        history.push((question.to_string(), answer.trim().to_string()));
+    }
+}
+
+/// Report how GitHub authentication resolves (`--show-auth`).
+///
+/// Lists every credential source in priority order with a masked token, then
+/// asks GitHub what the winning token can do. Tokens are never printed in full.
+async fn run_show_auth() -> Result<()> {
+    let order = auth_order_from_env();
+    println!("GitHub authentication:");
+    println!(
+        "  Order: {} (set {} to change)",
+        order
+            .iter()
+            .map(|s| s.as_str())
+            .collect::<Vec<_>>()
+            .join(" -> "),
+        AUTH_ORDER_ENV_VAR
+    );
+
+    let mut selected: Option<(TokenSource, String)> = None;
+    for source in order {
+        match token_from_source(source).await {
+            Some(token) => {
+                let status = if selected.is_none() { "USED" } else { "SKIP" };
+                println!("  [{}] {}: {}", status, source, mask_token(&token));
+                if selected.is_none() {
+                    selected = Some((source, token));
+                }
+            }
+            None => println!("  [----] {}: not available", source),
+        }
+    }
+
+    let Some((source, token)) = selected else {
+        println!();
+        println!("No token found. PR fetching is disabled.");
+        println!("  Fix: Set GITHUB_TOKEN or run `gh auth login`");
+        return Ok(());
+    };
+
+    println!();
+    match fetch_token_info(&token).await {
+        Ok(info) => {
+            println!("Token from {}:", source);
+            match &info.login {
+                Some(login) => println!("  Account:    {}", login),
+                None => println!("  Account:    \x1b[33mnot authenticated (token rejected)\x1b[0m"),
+            }
+            match &info.scopes {
+                Some(scopes) if scopes.is_empty() => println!("  Scopes:     (none)"),
+                Some(scopes) => {
+                    println!("  Scopes:     {}", scopes.join(", "));
+                    let unneeded = unneeded_scopes(scopes);
+                    if !unneeded.is_empty() {
+                        println!(
+                            "  \x1b[33m⚠ keryx never uses {}; a token without them is enough\x1b[0m",
+                            unneeded.join(", ")
+                        );
+                    }
+                }
+                None => println!("  Scopes:     not reported (fine-grained token)"),
+            }
+            println!("  Generate needs: {}", READ_ONLY_PERMISSIONS);
+            if let (Some(limit), Some(remaining)) = (info.rate_limit, info.rate_remaining) {
+                println!("  Rate limit: {}/{} requests remaining", remaining, limit);
+                if limit <= 60 {
+                    println!(
+                        "  \x1b[33m⚠ This is the anonymous limit: GitHub did not accept the token\x1b[0m"
+                    );
+                }
+            }
+        }
+        Err(e) => {
+            eprintln!("\x1b[33m⚠ Could not check token with GitHub\x1b[0m");
+            eprintln!("  Reason: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check if an update is available (without printing).
+///
+/// Returns true if a newer version is available, false otherwise.
+fn check_for_update() -> Result<bool, Box<dyn std::error::Error + Send + Sync>> {
+    use axoupdater::AxoUpdater;
+
+    let mut updater = AxoUpdater::new_for("keryx");
+
+    // Load the install receipt to get source info (GitHub owner/repo).
+    // This is required for axoupdater to know where to check for updates.
+    // The receipt also contains the installed version.
+    updater.load_receipt()?;
+
+    Ok(updater.is_update_needed_sync()?)
+}
+
+/// Print the update notification banner to stderr.
+fn print_update_notification() {
+    eprintln!();
+    eprintln!("\x1b[33m╭───────────────────────────────────────────────╮\x1b[0m");
+    eprintln!("\x1b[33m│\x1b[0m  A new version of keryx is available!        \x1b[33m│\x1b[0m");
+    eprintln!(
+        "\x1b[33m│\x1b[0m  Run \x1b[36mkeryx update\x1b[0m to upgrade                 \x1b[33m│\x1b[0m"
+    );
+    eprintln!("\x1b[33m╰───────────────────────────────────────────────╯\x1b[0m");
+    eprintln!();
+}
+
+/// Run the self-update command.
 async fn run_update() -> Result<()> {
     use axoupdater::AxoUpdater;
 
@@ -485,6 +2810,24 @@ async fn run_update() -> Result<()> {
     Ok(())
 }
 
+/// Decide whether to fetch PRs for this run.
+///
+/// When the remote is absent or not hosted on GitHub there is nothing to
+/// authenticate against, so this switches to commits-only generation with a
+/// single note instead of failing on every run.
+fn should_fetch_prs(repo: &Repository, remote: &str, no_prs: bool) -> bool {
+    if no_prs {
+        return false;
+    }
+    match detect_remote(repo, remote).commits_only_reason(remote) {
+        None => true,
+        Some(reason) => {
+            status!("Note: {}; generating from commits only.", reason);
+            false
+        }
+    }
+}
+
 /// Handle PR fetch errors with consistent messaging.
 ///
 /// In strict mode, returns an error. Otherwise, prints a warning and returns an empty Vec.
@@ -506,13 +2849,57 @@ fn handle_pr_fetch_error(e: anyhow::Error, strict: bool) -> Result<Vec<keryx::Pu
     eprintln!("  Hint: Use --strict to fail instead of continuing with partial data");
     eprintln!();
 
-    Ok(Vec::new())
+    Ok(Vec::new())
+}
+
+/// Run the init command to create a new changelog.
+/// Write the release workflow (`keryx init --github-workflow`).
+///
+/// The workflow is parameterized by the project type detected in the
+/// repository root, the `--provider` (Claude by default) and the `-o` path.
+fn run_init_workflow(cli: &Cli) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let root = repo
+        .workdir()
+        .context("Cannot write a workflow in a bare repository")?;
+    let project = ProjectType::detect(root);
+    let provider = cli.provider_selection().primary;
+    let workflow = render_release_workflow(project, provider, cli.primary_output());
+
+    println!(
+        "Release workflow for a {} project using {}",
+        project, provider
+    );
+    if cli.dry_run {
+        println!("\n--- Dry Run Output ({}) ---\n", WORKFLOW_PATH);
+        print!("{}", workflow);
+        return Ok(());
+    }
+
+    let path = write_workflow(root, &workflow, cli.force)?;
+    if let Some(journal) = Journal::for_repository(&repo, "init") {
+        journal.record(
+            journal
+                .entry(OperationKind::FileModified)
+                .arg(WORKFLOW_PATH)
+                .detail("created release workflow"),
+        );
+    }
+    println!("✓ Wrote {}", path.display());
+    println!(
+        "  Add the {} repository secret, then run the workflow from the Actions tab.",
+        api_key_secret(provider)
+    );
+    Ok(())
 }
 
-/// Run the init command to create a new changelog.
-async fn run_init(config: &InitConfig, unreleased: bool, from_history: bool) -> Result<()> {
-    let mut llm = LlmRouter::new(config.provider_selection);
-
+async fn run_init(
+    config: &InitConfig,
+    unreleased: bool,
+    from_history: bool,
+    history: &HistoryOptions,
+) -> Result<()> {
     // Check if changelog already exists
     if config.output.exists() && !config.dry_run {
         bail!(
@@ -524,25 +2911,40 @@ async fn run_init(config: &InitConfig, unreleased: bool, from_history: bool) ->
     // Open git repository
     let repo = Repository::open(".")
         .context("Not a git repository. Run keryx from within a git repository.")?;
+    let journal = Journal::for_repository(&repo, "init");
+    let mut llm = llm_router(config.provider_selection, journal.clone());
 
     if unreleased {
-        run_init_unreleased(&repo, config, &mut llm).await
+        run_init_unreleased(&repo, config, &mut llm).await?;
     } else if from_history {
-        run_init_from_history(&repo, config, &mut llm).await
+        run_init_from_history(&repo, config, history, &mut llm).await?;
     } else {
-        run_init_basic(&config.output, config.dry_run)
+        run_init_basic(&config.output, config.dry_run)?;
+    }
+
+    if !config.dry_run
+        && let Some(journal) = &journal
+    {
+        journal.record(
+            journal
+                .entry(OperationKind::FileModified)
+                .arg(config.output.display().to_string())
+                .detail("created changelog"),
+        );
     }
+
+    Ok(())
 }
 
 /// Create a basic empty changelog with headers.
-fn run_init_basic(output: &PathBuf, dry_run: bool) -> Result<()> {
+fn run_init_basic(output: &std::path::Path, dry_run: bool) -> Result<()> {
     let content = format!("{}## [Unreleased]\n", CHANGELOG_HEADER);
 
     if dry_run {
         println!("--- Dry Run Output ---\n");
         println!("{}", content);
     } else {
-        std::fs::write(output, &content).context("Failed to write changelog")?;
+        keryx::atomic::write_atomic(output, &content).context("Failed to write changelog")?;
         println!("✓ Created {} with [Unreleased] section", output.display());
     }
 
@@ -563,6 +2965,7 @@ async fn run_init_unreleased(
 
     let commits = fetch_commits(repo, root_oid, head_oid, config.strict)
         .context("Failed to fetch commits")?;
+    let commits = net_commits(repo, commits, config.verbose);
 
     if commits.is_empty() {
         // Just create basic changelog if no commits
@@ -571,11 +2974,13 @@ async fn run_init_unreleased(
 
     println!("Found {} commits", commits.len());
 
+    let remote = select_pr_remote(repo, config.remote.as_deref());
+
     // Fetch PRs if not disabled
-    let pull_requests = if config.no_prs {
+    let pull_requests = if !should_fetch_prs(repo, &remote, config.no_prs) {
         Vec::new()
     } else {
-        match fetch_prs_for_repo(repo, config.pr_limit).await {
+        match fetch_prs_for_repo(repo, &remote, &PrScope::default(), config.pr_limit).await {
             Ok(prs) => {
                 println!("Found {} merged PRs", prs.len());
                 prs
@@ -585,14 +2990,20 @@ async fn run_init_unreleased(
     };
 
     // Build prompt and generate entries
-    let repo_name = get_repo_name(repo).unwrap_or_else(|| "repository".to_string());
+    let repo_name = get_repo_name(repo, &remote).unwrap_or_else(|| "repository".to_string());
     let input = ChangelogInput {
         commits,
         pull_requests,
         previous_version: None,
         repository_name: repo_name,
-        project_description: read_cargo_description(),
+        project_description: read_cargo_description(repo),
         cli_features: None,
+        product: ProductContext::for_repository(repo),
+        languages: LanguageProfile::for_repository(repo),
+        include_highlights: false,
+        include_sources: false,
+        entry_sort: config.entry_sort,
+        prs_only: false,
     };
 
     let prompt = build_prompt(&input).context("Failed to build prompt")?;
@@ -602,48 +3013,52 @@ async fn run_init_unreleased(
         llm.primary(),
         llm.fallback()
     );
-    let draft_completion = llm
-        .generate(&prompt)
-        .await
-        .map_err(|e| handle_llm_error(e, config.verbose))?;
+    let draft_completion = generate_guarded(
+        llm,
+        &prompt,
+        SanitizeOptions::from_env(),
+        &config.constraints,
+        &input.commits,
+    )
+    .await
+    .map_err(|e| handle_llm_error(e, config.verbose))?;
     report_llm_fallback_if_any(&draft_completion, config.verbose);
     let draft_output = draft_completion.output;
 
-    // Verify entries against codebase (unless --no-verify)
-    let changelog_output = if config.no_verify {
-        debug!("Skipping verification (--no-verify flag)");
-        draft_output
-    } else {
-        let repo_path = repo
-            .workdir()
-            .context("Cannot verify in a bare repository. Use --no-verify to skip verification.")?;
-        verify_changelog_entries(&draft_output, repo_path, config.verbose, llm).await?
-    };
+    // Verify entries against codebase (unless --verify off)
+    let corpus = SourceCorpus::for_repository(repo, &input.commits, &input.pull_requests);
+    let changelog_output = verify_draft(
+        draft_output,
+        repo,
+        &input.commits,
+        &corpus,
+        config.verify,
+        config.verbose,
+        llm,
+        None,
+    )
+    .await?
+    .output;
 
     if changelog_output.entries.is_empty() {
         println!("No verified changelog entries found. Creating basic changelog template.");
         return run_init_basic(&config.output, config.dry_run);
     }
+    let mut changelog_output = changelog_output;
+    apply_style_guide(&mut changelog_output, repo);
 
     // Build the changelog content
     let mut content = CHANGELOG_HEADER.to_string();
     content.push_str("## [Unreleased]\n\n");
 
-    if !changelog_output.entries.is_empty() {
-        for (category, entries) in changelog_output.entries_by_category() {
-            content.push_str(&format!("### {}\n\n", category.as_str()));
-            for entry in entries {
-                content.push_str(&format!("- {}\n", entry.description));
-            }
-            content.push('\n');
-        }
-    }
+    content.push_str(&format_entries(&changelog_output, config.entry_sort, None));
 
     if config.dry_run {
         println!("\n--- Dry Run Output ---\n");
         println!("{}", content);
     } else {
-        std::fs::write(&config.output, &content).context("Failed to write changelog")?;
+        keryx::atomic::write_atomic(&config.output, &content)
+            .context("Failed to write changelog")?;
         println!(
             "✓ Created {} with {} entries in [Unreleased]",
             config.output.display(),
@@ -654,6 +3069,49 @@ async fn run_init_unreleased(
     Ok(())
 }
 
+/// Fetch the commits belonging to a tag's section for `--from-history`.
+///
+/// Commits are streamed from the revwalk so a failure partway through keeps
+/// what was already parsed instead of discarding the whole section.
+fn fetch_tag_commits(
+    repo: &Repository,
+    from_oid: git2::Oid,
+    tag: &TagInfo,
+    strict: bool,
+) -> Result<Vec<ParsedCommit>> {
+    let walk = match walk_commits(repo, from_oid, tag.oid, strict) {
+        Ok(walk) => walk,
+        Err(e) => {
+            if strict {
+                bail!("Failed to fetch commits for tag {}: {}", tag.name, e);
+            }
+            warn!(
+                "Failed to fetch commits for tag {}: {}. Section may be incomplete.",
+                tag.name, e
+            );
+            return Ok(Vec::new());
+        }
+    };
+
+    let mut commits = Vec::new();
+    for result in walk {
+        match result {
+            Ok(commit) => commits.push(commit),
+            Err(e) => {
+                if strict {
+                    bail!("Failed to fetch commits for tag {}: {}", tag.name, e);
+                }
+                warn!(
+                    "Failed to fetch commits for tag {}: {}. Section may be incomplete.",
+                    tag.name, e
+                );
+                break;
+            }
+        }
+    }
+    Ok(commits)
+}
+
 /// Create changelog with entries for each existing git tag.
 ///
 /// Note: Verification is not yet implemented for this path as it processes
@@ -661,9 +3119,10 @@ async fn run_init_unreleased(
 async fn run_init_from_history(
     repo: &Repository,
     config: &InitConfig,
+    history: &HistoryOptions,
     llm: &mut LlmRouter,
 ) -> Result<()> {
-    if !config.no_verify {
+    if config.verify != VerifyMode::Off {
         eprintln!(
             "\x1b[33m⚠ Note: Verification is not yet supported for --from-history.\n  \
              Entries will be unverified. Use --no-verify to suppress this warning.\x1b[0m"
@@ -694,11 +3153,31 @@ async fn run_init_from_history(
             .join(", ")
     );
 
+    if let Some(range) = &history.only_tags {
+        let selected = tags
+            .iter()
+            .filter(|t| t.version.as_ref().is_some_and(|v| range.contains(v)))
+            .count();
+        if selected == 0 {
+            bail!("No version tags match --only-tags");
+        }
+        println!("Limiting to {} tag(s) selected by --only-tags", selected);
+    }
+
+    let cache = HistoryCache::for_repository(repo);
+    if let Some(cache) = &cache
+        && history.fresh
+    {
+        cache.clear().context("Failed to clear history cache")?;
+    }
+
+    let remote = select_pr_remote(repo, config.remote.as_deref());
+
     // Fetch PRs once if not disabled
-    let all_prs = if config.no_prs {
+    let all_prs = if !should_fetch_prs(repo, &remote, config.no_prs) {
         Vec::new()
     } else {
-        match fetch_prs_for_repo(repo, config.pr_limit).await {
+        match fetch_prs_for_repo(repo, &remote, &PrScope::default(), config.pr_limit).await {
             Ok(prs) => {
                 println!("Found {} merged PRs", prs.len());
                 prs
@@ -707,30 +3186,34 @@ async fn run_init_from_history(
         }
     };
 
-    let repo_name = get_repo_name(repo).unwrap_or_else(|| "repository".to_string());
+    let repo_name = get_repo_name(repo, &remote).unwrap_or_else(|| "repository".to_string());
+    let languages = LanguageProfile::for_repository(repo);
 
-    // Build sections for each version (newest first in output)
-    let mut version_sections: Vec<(Version, String)> = Vec::new();
+    // Collect the input of each version first, so the LLM calls can be
+    // estimated before any is made
+    let mut drafts: Vec<(&TagInfo, HistoryDraft)> = Vec::new();
     let mut prev_oid: Option<git2::Oid> = None;
 
     for tag in &tags {
         let version = tag.version.as_ref().unwrap();
 
+        if let Some(range) = &history.only_tags
+            && !range.contains(version)
+        {
+            prev_oid = Some(tag.oid);
+            continue;
+        }
+
+        // Reuse the section from an interrupted run when available
+        if let Some(output) = cache.as_ref().and_then(|c| c.load(&tag.name, tag.oid)) {
+            drafts.push((tag, HistoryDraft::Cached(output)));
+            prev_oid = Some(tag.oid);
+            continue;
+        }
+
         // Get commits between previous tag and this tag
         let commits = if let Some(from_oid) = prev_oid {
-            match fetch_commits(repo, from_oid, tag.oid, config.strict) {
-                Ok(c) => c,
-                Err(e) => {
-                    if config.strict {
-                        bail!("Failed to fetch commits for tag {}: {}", tag.name, e);
-                    }
-                    warn!(
-                        "Failed to fetch commits for tag {}: {}. Section may be incomplete.",
-                        tag.name, e
-                    );
-                    Vec::new()
-                }
-            }
+            fetch_tag_commits(repo, from_oid, tag, config.strict)?
         } else {
             // First tag - get all commits from root to this tag
             let root_oid = match find_root_commit(repo, config.strict) {
@@ -746,29 +3229,15 @@ async fn run_init_from_history(
                     tag.oid
                 }
             };
-            match fetch_commits(repo, root_oid, tag.oid, config.strict) {
-                Ok(c) => c,
-                Err(e) => {
-                    if config.strict {
-                        bail!("Failed to fetch commits for tag {}: {}", tag.name, e);
-                    }
-                    warn!(
-                        "Failed to fetch commits for tag {}: {}. Section may be incomplete.",
-                        tag.name, e
-                    );
-                    Vec::new()
-                }
-            }
+            fetch_tag_commits(repo, root_oid, tag, config.strict)?
         };
+        let commits = net_commits(repo, commits, config.verbose);
 
         if commits.is_empty() {
             prev_oid = Some(tag.oid);
             continue;
         }
 
-        println!("Processing {} ({} commits)...", tag.name, commits.len());
-
-        // Generate entries for this version
         let input = ChangelogInput {
             commits,
             pull_requests: all_prs.clone(), // TODO: filter by date range
@@ -779,20 +3248,128 @@ async fn run_init_from_history(
             }),
             repository_name: repo_name.clone(),
             project_description: if prev_oid.is_none() {
-                read_cargo_description()
+                read_cargo_description(repo)
             } else {
                 None
             },
             cli_features: None,
+            product: ProductContext::for_repository(repo),
+            languages: languages.clone(),
+            include_highlights: false,
+            include_sources: false,
+            entry_sort: config.entry_sort,
+            prs_only: false,
         };
-
         let prompt = build_prompt(&input).context("Failed to build prompt")?;
-        let draft_completion = llm
-            .generate(&prompt)
-            .await
-            .map_err(|e| handle_llm_error(e, config.verbose))?;
-        report_llm_fallback_if_any(&draft_completion, config.verbose);
-        let changelog_output = draft_completion.output;
+        drafts.push((
+            tag,
+            HistoryDraft::Generate {
+                input: Box::new(input),
+                prompt,
+            },
+        ));
+        prev_oid = Some(tag.oid);
+    }
+
+    // Check for unreleased commits (after latest tag)
+    let latest_tag = tags.last().unwrap();
+    let head = repo.head()?.peel_to_commit()?.id();
+
+    // A range with an upper bound excludes everything after it
+    let include_unreleased = history.only_tags.as_ref().is_none_or(|r| r.to.is_none());
+    let unreleased_commits = if !include_unreleased {
+        Vec::new()
+    } else {
+        match fetch_commits(repo, latest_tag.oid, head, config.strict) {
+            Ok(c) => c,
+            Err(e) => {
+                if config.strict {
+                    bail!("Failed to fetch unreleased commits: {}", e);
+                }
+                warn!(
+                    "Failed to fetch unreleased commits: {}. Unreleased section may be incomplete.",
+                    e
+                );
+                Vec::new()
+            }
+        }
+    };
+
+    let unreleased_commits = net_commits(repo, unreleased_commits, config.verbose);
+
+    let unreleased = if unreleased_commits.is_empty() {
+        None
+    } else {
+        let input = ChangelogInput {
+            commits: unreleased_commits,
+            pull_requests: all_prs,
+            previous_version: latest_tag.version.clone(),
+            repository_name: repo_name,
+            project_description: None,
+            cli_features: None,
+            product: ProductContext::for_repository(repo),
+            languages,
+            include_highlights: false,
+            include_sources: false,
+            entry_sort: config.entry_sort,
+            prs_only: false,
+        };
+        let prompt = build_prompt(&input)?;
+        Some((input, prompt))
+    };
+
+    let mut plan = CostEstimate::new(config.provider_selection.primary);
+    for (_, draft) in &drafts {
+        if let HistoryDraft::Generate { prompt, .. } = draft {
+            plan.add("version", prompt.len());
+        }
+    }
+    if let Some((_, prompt)) = &unreleased {
+        plan.add("unreleased", prompt.len());
+    }
+    if plan.calls() > 0 && !confirm_llm_plan(&plan, config.yes)? {
+        return Ok(());
+    }
+
+    // Build sections for each version (newest first in output)
+    let mut version_sections: Vec<(Version, String)> = Vec::new();
+
+    for (tag, draft) in drafts {
+        let version = tag.version.as_ref().unwrap();
+        let mut changelog_output = match draft {
+            HistoryDraft::Cached(output) => {
+                println!("Using cached section for {}", tag.name);
+                output
+            }
+            HistoryDraft::Generate { input, prompt } => {
+                println!(
+                    "Processing {} ({} commits)...",
+                    tag.name,
+                    input.commits.len()
+                );
+
+                // Generate entries for this version
+                let draft_completion = generate_guarded(
+                    llm,
+                    &prompt,
+                    SanitizeOptions::from_env(),
+                    &config.constraints,
+                    &input.commits,
+                )
+                .await
+                .map_err(|e| handle_llm_error(e, config.verbose))?;
+                report_llm_fallback_if_any(&draft_completion, config.verbose);
+
+                if let Some(cache) = &cache
+                    && let Err(e) = cache.store(&tag.name, tag.oid, &draft_completion.output)
+                {
+                    warn!("Failed to checkpoint section for {}: {}", tag.name, e);
+                }
+                draft_completion.output
+            }
+        };
+
+        apply_style_guide(&mut changelog_output, repo);
 
         // Get tag date from commit
         let tag_date = repo
@@ -800,107 +3377,388 @@ async fn run_init_from_history(
             .map(|c| {
                 let time = c.time();
                 chrono::DateTime::from_timestamp(time.seconds(), 0)
-                    .map(|dt| dt.format("%Y-%m-%d").to_string())
+                    .map(|dt| config.release_date.historical_date(dt))
                     .unwrap_or_else(|| "unknown".to_string())
             })
             .unwrap_or_else(|_| "unknown".to_string());
 
         // Format section
-        let mut section = format!("## [{}] - {}\n\n", version, tag_date);
+        let mut section = config.heading.render(&version.to_string(), &tag_date, None);
+        section.push_str("\n\n");
 
         if changelog_output.entries.is_empty() {
             section.push_str("- Initial release\n\n");
         } else {
-            for (category, entries) in changelog_output.entries_by_category() {
-                section.push_str(&format!("### {}\n\n", category.as_str()));
-                for entry in entries {
-                    section.push_str(&format!("- {}\n", entry.description));
-                }
-                section.push('\n');
-            }
+            section.push_str(&format_entries(&changelog_output, config.entry_sort, None));
         }
 
         version_sections.push((version.clone(), section));
-        prev_oid = Some(tag.oid);
     }
 
-    // Check for unreleased commits (after latest tag)
-    let latest_tag = tags.last().unwrap();
-    let head = repo.head()?.peel_to_commit()?.id();
+    let mut unreleased_section = String::new();
+    if let Some((input, prompt)) = unreleased {
+        println!("Processing {} unreleased commits...", input.commits.len());
+
+        let draft_completion = generate_guarded(
+            llm,
+            &prompt,
+            SanitizeOptions::from_env(),
+            &config.constraints,
+            &input.commits,
+        )
+        .await
+        .map_err(|e| handle_llm_error(e, config.verbose))?;
+        report_llm_fallback_if_any(&draft_completion, config.verbose);
+        let changelog_output = draft_completion.output;
+
+        unreleased_section.push_str("## [Unreleased]\n\n");
+        unreleased_section.push_str(&format_entries(&changelog_output, config.entry_sort, None));
+    } else {
+        unreleased_section.push_str("## [Unreleased]\n\n");
+    }
+
+    // Build final content (newest versions first)
+    let mut content = CHANGELOG_HEADER.to_string();
+    content.push_str(&unreleased_section);
+
+    // Add versions in reverse order (newest first)
+    let version_count = version_sections.len();
+    for (_, section) in version_sections.into_iter().rev() {
+        content.push_str(&section);
+    }
+
+    if config.dry_run {
+        println!("\n--- Dry Run Output ---\n");
+        println!("{}", content);
+    } else {
+        keryx::atomic::write_atomic(&config.output, &content)
+            .context("Failed to write changelog")?;
+        println!(
+            "✓ Created {} with {} version(s)",
+            config.output.display(),
+            version_count
+        );
+
+        // Checkpoints are only needed until the changelog is written
+        if let Some(cache) = &cache
+            && let Err(e) = cache.clear()
+        {
+            warn!("Failed to clear history cache: {}", e);
+        }
+    }
+
+    Ok(())
+}
+
+/// Check the changelog trailers of the commits since `base`
+/// (`keryx check-trailers`).
+///
+/// Without `--base`, a pull request workflow checks the commits that are not
+/// on its base branch (`GITHUB_BASE_REF`); elsewhere the commits since the
+/// latest tag are checked.
+fn run_check_trailers(cli: &Cli, base: Option<&str>) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let base = base.map(String::from).or_else(|| {
+        let branch = std::env::var("GITHUB_BASE_REF").ok()?;
+        let branch = branch.trim();
+        (!branch.is_empty()).then(|| {
+            format!(
+                "{}/{}",
+                cli.remote
+                    .as_deref()
+                    .unwrap_or(keryx::github::DEFAULT_REMOTE),
+                branch
+            )
+        })
+    });
+    let range = resolve_range(&repo, base.as_deref(), Some("HEAD"), cli.strict)
+        .context("Failed to resolve commit range")?;
+    let report = check_range(&repo, range.from, range.to).context("Failed to read commits")?;
+
+    println!(
+        "Changelog trailers of {} commit{} since {}{}:",
+        report.checked,
+        if report.checked == 1 { "" } else { "s" },
+        range.from_ref,
+        if report.merges > 0 {
+            format!(" ({} merge commit(s) skipped)", report.merges)
+        } else {
+            String::new()
+        }
+    );
+    for violation in &report.violations {
+        println!(
+            "  [FAIL] {} {}: {}",
+            &violation.hash[..7.min(violation.hash.len())],
+            truncate_description(&violation.subject, 60),
+            violation.problem
+        );
+    }
+    if report.is_ok() {
+        println!("  [PASS] Every commit has changelog trailers");
+        return Ok(());
+    }
+    bail!(
+        "{} commit{} without valid changelog trailers; reword them (`keryx commit --amend-trailers` does it for unpushed commits)",
+        report.violations.len(),
+        if report.violations.len() == 1 {
+            ""
+        } else {
+            "s"
+        }
+    );
+}
+
+/// Check a commit message file, or stdin, against the conventional commit
+/// format (`keryx lint-commit`). Fails with every problem found.
+fn run_lint_commit(file: Option<&std::path::Path>, max_subject_length: usize) -> Result<()> {
+    let message = match file {
+        Some(path) if path != std::path::Path::new("-") => std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read {}", path.display()))?,
+        _ => {
+            let mut message = String::new();
+            std::io::Read::read_to_string(&mut std::io::stdin(), &mut message)
+                .context("Failed to read the commit message from stdin")?;
+            message
+        }
+    };
+    let problems = keryx::commit::lint_message(&message, max_subject_length);
+    if problems.is_empty() {
+        println!("  [PASS] Conventional commit message");
+        return Ok(());
+    }
+    for problem in &problems {
+        eprintln!("  [FAIL] {}", problem);
+    }
+    bail!(
+        "{} problem{} in the commit message",
+        problems.len(),
+        if problems.len() == 1 { "" } else { "s" }
+    );
+}
+
+/// Normalize alias category headings in the changelogs (`keryx fmt`).
+///
+/// Aliases are the built-in ones plus `[category_aliases]` of keryx.toml.
+/// With `--check` (or `--dry-run`) nothing is written; `--check` fails when
+/// a file would change.
+fn run_fmt(cli: &Cli, check: bool) -> Result<()> {
+    let aliases = CategoryAliases::load(&config_root());
+    let mut unformatted = 0;
+    let mut seen: Vec<&PathBuf> = Vec::new();
+    for path in &cli.output {
+        if seen.contains(&path) {
+            continue;
+        }
+        seen.push(path);
+        let replaced = normalize_changelog(path, &aliases, check || cli.dry_run)
+            .with_context(|| format!("Failed to format {}", path.display()))?;
+        let headings = if replaced == 1 { "heading" } else { "headings" };
+        if replaced == 0 {
+            println!(
+                "  [PASS] {} uses standard category headings",
+                path.display()
+            );
+        } else if check {
+            unformatted += 1;
+            println!(
+                "  [FAIL] {}: {} alias {}",
+                path.display(),
+                replaced,
+                headings
+            );
+        } else if cli.dry_run {
+            println!(
+                "  {} alias {} in {} (dry run, not written)",
+                replaced,
+                headings,
+                path.display()
+            );
+        } else {
+            println!(
+                "  [DONE] Rewrote {} {} in {}",
+                replaced,
+                headings,
+                path.display()
+            );
+        }
+    }
+    if unformatted > 0 {
+        bail!("Run `keryx fmt` to rewrite the alias headings");
+    }
+    Ok(())
+}
+
+/// Validate the structure of the changelogs (`keryx check`).
+///
+/// Category aliases and the heading template are the ones `keryx` writes
+/// with, so a changelog it maintains passes.
+fn run_check(cli: &Cli) -> Result<()> {
+    let aliases = CategoryAliases::load(&config_root());
+    let heading = cli.heading_template();
+    let mut problems = 0;
+    let mut seen: Vec<&PathBuf> = Vec::new();
+    for path in &cli.output {
+        if seen.contains(&path) {
+            continue;
+        }
+        seen.push(path);
+        let changelog = match read_changelog(path) {
+            Ok(Some(changelog)) => changelog,
+            Ok(None) => {
+                problems += 1;
+                println!("  [FAIL] {} not found", path.display());
+                continue;
+            }
+            Err(e) => {
+                problems += 1;
+                println!("  [FAIL] {}: {}", path.display(), e);
+                continue;
+            }
+        };
+        let violations = validate_changelog(&changelog.raw_content, &aliases, &heading);
+        if violations.is_empty() {
+            println!(
+                "  [PASS] {} ({} versions)",
+                path.display(),
+                changelog.versions.len()
+            );
+            continue;
+        }
+        problems += violations.len();
+        println!(
+            "  [FAIL] {}: {} problem{}",
+            path.display(),
+            violations.len(),
+            if violations.len() == 1 { "" } else { "s" }
+        );
+        for violation in &violations {
+            println!("    {}", violation);
+        }
+    }
+    if problems > 0 {
+        bail!(
+            "{} problem{} in the changelog structure",
+            problems,
+            if problems == 1 { "" } else { "s" }
+        );
+    }
+    Ok(())
+}
+
+/// Add changelog trailers to unpushed commits (`keryx commit --amend-trailers`).
+///
+/// Each unpushed commit without a `Changelog` trailer gets a category and
+/// description from the LLM, or `Changelog: none` for an internal change.
+/// After confirmation the commits are reworded in place, like an
+/// interactive rebase would.
+async fn run_amend_trailers(cli: &Cli) -> Result<()> {
+    let repo = Repository::open(".")
+        .context("Not a git repository. Run keryx from within a git repository.")?;
+    let commits = unpushed_commits(&repo)?;
+    let pending: Vec<git2::Oid> = commits
+        .iter()
+        .copied()
+        .filter(|oid| {
+            repo.find_commit(*oid)
+                .ok()
+                .and_then(|c| c.message().map(needs_trailers))
+                .unwrap_or(false)
+        })
+        .collect();
+    if pending.is_empty() {
+        println!("All unpushed commits already have changelog trailers.");
+        return Ok(());
+    }
+
+    let provider_selection = cli.provider_selection();
+    let journal = Journal::for_repository(&repo, "commit");
+    let mut llm = llm_router(provider_selection, journal.clone());
 
-    let unreleased_commits = match fetch_commits(repo, latest_tag.oid, head, config.strict) {
-        Ok(c) => c,
-        Err(e) => {
-            if config.strict {
-                bail!("Failed to fetch unreleased commits: {}", e);
+    println!(
+        "Generating changelog trailers for {} commit{} with {}...",
+        pending.len(),
+        if pending.len() == 1 { "" } else { "s" },
+        llm.primary()
+    );
+    let mut messages = HashMap::new();
+    for oid in &pending {
+        let context = commit_context(&repo, *oid)?;
+        let subject = context
+            .message
+            .lines()
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        let short = &context.hash[..7.min(context.hash.len())];
+        let output = llm
+            .request(&TextRequest::for_task(
+                LlmTask::Commit,
+                build_trailers_prompt(&context),
+            ))
+            .await
+            .map_err(|e| handle_llm_error(e, cli.verbose()))?
+            .output;
+        let trailers = match parse_trailers(&output) {
+            Ok(trailers) => trailers,
+            Err(e) => {
+                eprintln!("\x1b[33m⚠ Skipping {}: {}\x1b[0m", short, e);
+                continue;
             }
-            warn!(
-                "Failed to fetch unreleased commits: {}. Unreleased section may be incomplete.",
-                e
-            );
-            Vec::new()
-        }
-    };
-
-    let mut unreleased_section = String::new();
-    if !unreleased_commits.is_empty() {
-        println!(
-            "Processing {} unreleased commits...",
-            unreleased_commits.len()
-        );
-
-        let input = ChangelogInput {
-            commits: unreleased_commits,
-            pull_requests: all_prs,
-            previous_version: latest_tag.version.clone(),
-            repository_name: repo_name,
-            project_description: None,
-            cli_features: None,
         };
-
-        let prompt = build_prompt(&input)?;
-        let draft_completion = llm
-            .generate(&prompt)
-            .await
-            .map_err(|e| handle_llm_error(e, config.verbose))?;
-        report_llm_fallback_if_any(&draft_completion, config.verbose);
-        let changelog_output = draft_completion.output;
-
-        unreleased_section.push_str("## [Unreleased]\n\n");
-        if !changelog_output.entries.is_empty() {
-            for (category, entries) in changelog_output.entries_by_category() {
-                unreleased_section.push_str(&format!("### {}\n\n", category.as_str()));
-                for entry in entries {
-                    unreleased_section.push_str(&format!("- {}\n", entry.description));
-                }
-                unreleased_section.push('\n');
+        println!(
+            "  {} {}{}",
+            short,
+            truncate_description(&subject, 72),
+            if trailers.is_user_facing() {
+                ""
+            } else {
+                " (internal)"
             }
+        );
+        for line in trailers.lines() {
+            println!("    \x1b[32m+ {}\x1b[0m", line);
         }
-    } else {
-        unreleased_section.push_str("## [Unreleased]\n\n");
+        messages.insert(*oid, with_trailers(&context.message, &trailers));
     }
 
-    // Build final content (newest versions first)
-    let mut content = CHANGELOG_HEADER.to_string();
-    content.push_str(&unreleased_section);
-
-    // Add versions in reverse order (newest first)
-    for (_, section) in version_sections.into_iter().rev() {
-        content.push_str(&section);
+    if messages.is_empty() {
+        println!("No commits to amend.");
+        return Ok(());
+    }
+    if cli.dry_run {
+        println!("(dry run, {} commit(s) not rewritten)", messages.len());
+        return Ok(());
+    }
+    if std::io::stdin().is_terminal() {
+        let apply = dialoguer::Confirm::new()
+            .with_prompt(format!("Rewrite {} unpushed commit(s)?", messages.len()))
+            .default(true)
+            .interact()
+            .context("Failed to read confirmation")?;
+        if !apply {
+            return Ok(());
+        }
     }
 
-    if config.dry_run {
-        println!("\n--- Dry Run Output ---\n");
-        println!("{}", content);
-    } else {
-        std::fs::write(&config.output, &content).context("Failed to write changelog")?;
-        println!(
-            "✓ Created {} with {} version(s)",
-            config.output.display(),
-            tags.len()
+    let new_head = rewrite_messages(&repo, &commits, &messages)?;
+    if let Some(journal) = &journal {
+        journal.record(
+            journal
+                .entry(OperationKind::CommitCreated)
+                .arg(new_head.to_string())
+                .detail(format!(
+                    "amended changelog trailers on {} commit(s)",
+                    messages.len()
+                )),
         );
     }
-
+    println!(
+        "  [DONE] Rewrote {} commit(s), HEAD is now {}",
+        messages.len(),
+        &new_head.to_string()[..7]
+    );
     Ok(())
 }
 
@@ -914,14 +3772,13 @@ async fn run_commit(
     no_split: bool,
     provider_flag: Option<ProviderFlag>,
 ) -> Result<CommitOutcome> {
-    let provider_selection = provider_flag
-        .map(Provider::from)
-        .map(ProviderSelection::from_primary)
-        .unwrap_or_default();
-    let mut llm = LlmRouter::new(provider_selection);
+    let provider_selection =
+        ProviderSelection::resolve(provider_flag.map(Provider::from), &config_root());
 
     let repo = Repository::open(".")
         .context("Not a git repository. Run keryx from within a git repository.")?;
+    let journal = Journal::for_repository(&repo, "commit");
+    let mut llm = llm_router(provider_selection, journal.clone());
 
     let diff = collect_diff(&repo).map_err(|e| match &e {
         keryx::CommitError::NoChanges => anyhow::anyhow!("Nothing to commit (working tree clean)"),
@@ -940,7 +3797,7 @@ async fn run_commit(
         }
     }
 
-    println!(
+    status!(
         "Analyzing {} changed file{}...",
         diff.changed_files.len(),
         if diff.changed_files.len() == 1 {
@@ -966,7 +3823,7 @@ async fn run_commit(
             );
         }
 
-        println!("Checking if changes should be split into multiple commits...");
+        status!("Checking if changes should be split into multiple commits...");
 
         match analyze_split(&diff, &branch_name, &mut llm, config.verbose).await {
             Ok(Some(analysis)) => Some(analysis),
@@ -995,12 +3852,25 @@ async fn run_commit(
         None
     };
 
-    match analysis {
+    let outcome = match analysis {
         Some(analysis) => {
-            run_split_commits(&repo, &diff, &analysis, &branch_name, &mut llm, config).await
+            run_split_commits(&repo, &diff, &analysis, &branch_name, &mut llm, config).await?
+        }
+        None => run_single_commit(&repo, &diff, &branch_name, &mut llm, config).await?,
+    };
+
+    if let (CommitOutcome::Committed(oids), Some(journal)) = (&outcome, &journal) {
+        for oid in oids {
+            journal.record(
+                journal
+                    .entry(OperationKind::CommitCreated)
+                    .arg(oid.to_string())
+                    .arg(&branch_name),
+            );
         }
-        None => run_single_commit(&repo, &diff, &branch_name, &mut llm, config).await,
     }
+
+    Ok(outcome)
 }
 
 /// Run the commit flow and push the resulting commit(s) to the remote.
@@ -1028,6 +3898,17 @@ async fn run_push(
 
     push_to_remote(config.verbose).await?;
 
+    if let Some(journal) = Repository::open(".")
+        .ok()
+        .and_then(|repo| Journal::for_repository(&repo, "push"))
+    {
+        journal.record(
+            journal
+                .entry(OperationKind::Pushed)
+                .detail(format!("{} commit(s)", commit_count)),
+        );
+    }
+
     println!(
         "\x1b[32m\u{2713} Pushed {} commit{} to remote\x1b[0m",
         commit_count,
@@ -1125,31 +4006,42 @@ async fn run_single_commit(
     llm: &mut LlmRouter,
     config: &CommitConfig,
 ) -> Result<CommitOutcome> {
-    println!(
+    status!(
         "Generating commit message with {} (fallback: {})...",
         llm.primary(),
         llm.fallback()
     );
 
-    let (message, completion) = generate_commit_message(diff, branch_name, llm, config.verbose)
+    let completion = generate_commit_message(diff, branch_name, llm, config.verbose)
         .await
         .map_err(|e| handle_llm_error(e, config.verbose))?;
 
     report_llm_fallback_if_any(&completion, config.verbose);
+    let message = completion.output;
 
     display_commit_message(&message, config.verbose);
+    let message = edit_generated_message(message, config)?;
 
     if config.message_only || config.dry_run {
-        if config.message_only {
+        if config.message_only && keryx::output::is_json() {
+            let files = diff.changed_files.iter().map(|f| f.path.clone()).collect();
+            print_commit_report(vec![CommitMessageReport::new(&message, files)])?;
+        } else if config.message_only {
             print!("{}", message.format());
         }
         return Ok(CommitOutcome::NoCommit);
     }
 
+    let Some(message) = review_commit_message(message, diff, branch_name, llm, config).await?
+    else {
+        status!("Aborted, nothing committed.");
+        return Ok(CommitOutcome::NoCommit);
+    };
+
     let formatted = message.format();
     let oid = stage_and_commit(repo, &formatted).map_err(|e| anyhow::anyhow!("{}", e))?;
 
-    println!(
+    status!(
         "\x1b[32m\u{2713} Created commit {}\x1b[0m",
         &oid.to_string()[..7]
     );
@@ -1166,13 +4058,13 @@ async fn run_split_commits(
     llm: &mut LlmRouter,
     config: &CommitConfig,
 ) -> Result<CommitOutcome> {
-    println!();
-    println!(
+    status!();
+    status!(
         "\x1b[1mProposed split into {} commits:\x1b[0m",
         analysis.groups.len()
     );
     for (i, group) in analysis.groups.iter().enumerate() {
-        println!(
+        status!(
             "  {}. {} ({} file{})",
             i + 1,
             group.label,
@@ -1181,11 +4073,11 @@ async fn run_split_commits(
         );
         if config.verbose {
             for file in &group.files {
-                println!("     - {}", file);
+                status!("     - {}", middle_ellipsis(file, 72));
             }
         }
     }
-    println!();
+    status!();
 
     let file_changes: HashMap<String, ChangedFile> = diff
         .changed_files
@@ -1207,38 +4099,56 @@ async fn run_split_commits(
 
     let mut commit_oids: Vec<(String, git2::Oid)> = Vec::new();
     let mut all_messages: Vec<String> = Vec::new();
+    let mut reports: Vec<CommitMessageReport> = Vec::new();
 
     for (i, (group, group_diff)) in analysis.groups.iter().zip(group_diffs.iter()).enumerate() {
-        println!(
+        status!(
             "\x1b[1m[{}/{}] {}\x1b[0m",
             i + 1,
             analysis.groups.len(),
             group.label
         );
 
-        println!(
+        status!(
             "  Generating message with {} (fallback: {})...",
             llm.primary(),
             llm.fallback()
         );
 
-        let (message, completion) =
-            generate_commit_message(group_diff, branch_name, llm, config.verbose)
-                .await
-                .map_err(|e| handle_llm_error(e, config.verbose))?;
+        let completion = generate_commit_message(group_diff, branch_name, llm, config.verbose)
+            .await
+            .map_err(|e| handle_llm_error(e, config.verbose))?;
 
         report_llm_fallback_if_any(&completion, config.verbose);
+        let message = completion.output;
 
         display_commit_message(&message, config.verbose);
+        let message = edit_generated_message(message, config)?;
+
+        let message = if config.message_only || config.dry_run {
+            message
+        } else {
+            match review_commit_message(message, group_diff, branch_name, llm, config).await? {
+                Some(message) => message,
+                None => {
+                    status!(
+                        "Aborted; the remaining {} group(s) are left uncommitted.",
+                        analysis.groups.len() - i
+                    );
+                    break;
+                }
+            }
+        };
 
         let formatted = message.format();
         all_messages.push(formatted.clone());
+        reports.push(CommitMessageReport::new(&message, group.files.clone()));
 
         if !config.message_only && !config.dry_run {
             let oid = stage_paths_and_commit(repo, &group.files, &file_changes, &formatted)
                 .map_err(|e| anyhow::anyhow!("{}", e))?;
 
-            println!(
+            status!(
                 "\x1b[32m  \u{2713} Created commit {}\x1b[0m",
                 &oid.to_string()[..7]
             );
@@ -1246,17 +4156,20 @@ async fn run_split_commits(
         }
     }
 
-    if config.message_only {
+    if config.message_only && keryx::output::is_json() {
+        print_commit_report(reports)?;
+        return Ok(CommitOutcome::NoCommit);
+    } else if config.message_only {
         print!("{}", all_messages.join("\n---\n"));
         return Ok(CommitOutcome::NoCommit);
     } else if !config.dry_run && !commit_oids.is_empty() {
-        println!();
-        println!(
+        status!();
+        status!(
             "\x1b[32m\u{2713} Created {} commits:\x1b[0m",
             commit_oids.len()
         );
         for (label, oid) in &commit_oids {
-            println!("  {} -- {}", &oid.to_string()[..7], label);
+            status!("  {} -- {}", &oid.to_string()[..7], label);
         }
     }
 
@@ -1269,19 +4182,196 @@ async fn run_split_commits(
     }
 }
 
+/// A generated commit message, printed by `--message-only --format json`.
+#[derive(Debug, Serialize)]
+struct CommitMessageReport {
+    subject: String,
+    body: Option<String>,
+    breaking: bool,
+    changelog_category: Option<keryx::ChangelogCategory>,
+    changelog_description: Option<String>,
+    /// The full message with trailers, as it would be committed.
+    message: String,
+    /// Files the commit would contain.
+    files: Vec<String>,
+}
+
+impl CommitMessageReport {
+    fn new(message: &CommitMessage, files: Vec<String>) -> Self {
+        Self {
+            subject: message.subject.clone(),
+            body: message.body.clone(),
+            breaking: message.breaking,
+            changelog_category: message.changelog_category.clone(),
+            changelog_description: message.changelog_description.clone(),
+            message: message.format(),
+            files,
+        }
+    }
+}
+
+/// Print the generated commit messages as `{"commits": [...]}`.
+fn print_commit_report(commits: Vec<CommitMessageReport>) -> Result<()> {
+    #[derive(Serialize)]
+    struct CommitReport {
+        commits: Vec<CommitMessageReport>,
+    }
+    print_json(&CommitReport { commits }).context("Failed to serialize the commit messages")
+}
+
+/// Choices offered for a generated commit message.
+const REVIEW_CHOICES: [&str; 5] = [
+    "Accept",
+    "Regenerate",
+    "Regenerate with an instruction",
+    "Edit in $EDITOR",
+    "Abort",
+];
+
+/// Help appended to a commit message opened in `$EDITOR`.
+const COMMIT_EDIT_HELP: &str = "\
+# Edit the commit message above. Lines starting with '#' are ignored.
+# User-facing changes keep both trailers:
+#   Changelog: added, changed, deprecated, removed, fixed or security
+#   Changelog-Description: the entry as end users should read it
+# Internal changes keep only `Changelog: none`.";
+
+/// Ask what to do with a generated commit message until it is accepted
+/// (`Some`) or the user aborts (`None`). Without a terminal the message is
+/// accepted as is.
+async fn review_commit_message(
+    mut message: CommitMessage,
+    diff: &DiffSummary,
+    branch_name: &str,
+    llm: &mut LlmRouter,
+    config: &CommitConfig,
+) -> Result<Option<CommitMessage>> {
+    if !std::io::stdin().is_terminal() {
+        return Ok(Some(message));
+    }
+
+    loop {
+        println!();
+        let choice = dialoguer::Select::new()
+            .with_prompt("Commit with this message?")
+            .items(REVIEW_CHOICES)
+            .default(0)
+            .interact_opt()
+            .context("Failed to read selection")?;
+        match choice {
+            Some(0) => return Ok(Some(message)),
+            Some(choice @ (1 | 2)) => {
+                let instruction = if choice == 2 {
+                    let instruction: String = dialoguer::Input::new()
+                        .with_prompt("Instruction (e.g. \"mention the perf fix\")")
+                        .allow_empty(true)
+                        .interact_text()
+                        .context("Failed to read instruction")?;
+                    Some(instruction)
+                } else {
+                    None
+                };
+                println!(
+                    "Regenerating commit message with {} (fallback: {})...",
+                    llm.primary(),
+                    llm.fallback()
+                );
+                match regenerate_commit_message(
+                    diff,
+                    branch_name,
+                    &message,
+                    instruction.as_deref(),
+                    llm,
+                    config.verbose,
+                )
+                .await
+                {
+                    Ok(completion) => {
+                        report_llm_fallback_if_any(&completion, config.verbose);
+                        message = completion.output;
+                        display_commit_message(&message, config.verbose);
+                    }
+                    Err(e) => eprintln!(
+                        "\x1b[33m⚠ Regeneration failed, keeping the previous message: {}\x1b[0m",
+                        e.summary()
+                    ),
+                }
+            }
+            Some(3) => match edit_commit_message(&message)? {
+                Some(edited) => {
+                    message = edited;
+                    display_commit_message(&message, config.verbose);
+                }
+                None => println!("Editor closed without saving; keeping the message."),
+            },
+            _ => return Ok(None),
+        }
+    }
+}
+
+/// Let the user edit a generated message before anything else happens
+/// (`--edit`). The generated message is kept when the editor is closed
+/// without saving.
+fn edit_generated_message(message: CommitMessage, config: &CommitConfig) -> Result<CommitMessage> {
+    if !config.edit {
+        return Ok(message);
+    }
+    match edit_commit_message(&message)? {
+        Some(edited) => {
+            display_commit_message(&edited, config.verbose);
+            Ok(edited)
+        }
+        None => {
+            status!("Editor closed without saving; keeping the generated message.");
+            Ok(message)
+        }
+    }
+}
+
+/// Open `message` in `$EDITOR` and parse the result. An edit that does not
+/// parse is reported and can be fixed in the editor again. `None` if the
+/// editor was closed without saving or the user gave up on an invalid edit.
+fn edit_commit_message(message: &CommitMessage) -> Result<Option<CommitMessage>> {
+    let mut text = format!("{}\n\n{}\n", message.format(), COMMIT_EDIT_HELP);
+    loop {
+        let Some(edited) = dialoguer::Editor::new()
+            .extension(".txt")
+            .edit(&text)
+            .context("Failed to open $EDITOR")?
+        else {
+            return Ok(None);
+        };
+        match CommitMessage::parse(&edited) {
+            Ok(parsed) => return Ok(Some(parsed)),
+            Err(e) => {
+                eprintln!("\x1b[33m⚠ Invalid commit message: {}\x1b[0m", e);
+                let retry = dialoguer::Confirm::new()
+                    .with_prompt("Edit again?")
+                    .default(true)
+                    .interact()
+                    .context("Failed to read confirmation")?;
+                if !retry {
+                    return Ok(None);
+                }
+                text = edited;
+            }
+        }
+    }
+}
+
 /// Display a commit message to the user.
 fn display_commit_message(message: &keryx::commit::CommitMessage, verbose: bool) {
-    println!();
-    println!("\x1b[1m{}\x1b[0m", message.subject);
+    status!();
+    status!("\x1b[1m{}\x1b[0m", message.subject);
 
     if let Some(body) = message.body.as_deref().filter(|b| !b.trim().is_empty()) {
-        println!();
-        println!("{}", body.trim());
+        status!();
+        status!("{}", body.trim());
     }
 
     if message.breaking {
-        println!();
-        println!("\x1b[33m⚠ BREAKING CHANGE\x1b[0m");
+        status!();
+        status!("\x1b[33m⚠ BREAKING CHANGE\x1b[0m");
     }
 
     match message
@@ -1290,73 +4380,228 @@ fn display_commit_message(message: &keryx::commit::CommitMessage, verbose: bool)
         .zip(message.changelog_description.as_ref())
     {
         Some((cat, desc)) => {
-            println!();
-            println!(
+            status!();
+            status!(
                 "\x1b[36mChangelog ({}):\x1b[0m {}",
                 cat.as_str().to_lowercase(),
                 desc
             );
         }
         None if verbose => {
-            println!();
-            println!("\x1b[2mInternal change — excluded from changelog\x1b[0m");
+            status!();
+            status!("\x1b[2mInternal change — excluded from changelog\x1b[0m");
         }
         None => {}
     }
 
-    println!();
+    status!();
 }
 
 /// Run the changelog generation command.
+/// Result of a generate run, printed by `--format json`.
+#[derive(Debug, Default, Serialize)]
+struct GenerateReport {
+    /// Version of the new section.
+    version: Option<String>,
+    previous_version: Option<String>,
+    /// Why the LLM chose the bump.
+    bump_reasoning: Option<String>,
+    dry_run: bool,
+    /// Files written; empty on a dry run or in a bare repository.
+    written: Vec<PathBuf>,
+    highlights: Option<String>,
+    /// Entries of the new section; empty when there was nothing to add.
+    entries: Vec<EntryReport>,
+    /// The section as it is (or would be) written.
+    section: Option<String>,
+    migration_guide: Option<String>,
+    summary: Option<RunSummary>,
+}
+
+/// A changelog entry with how well the codebase supports it.
+#[derive(Debug, Serialize)]
+struct EntryReport {
+    #[serde(flatten)]
+    entry: keryx::ChangelogEntry,
+    /// Confidence of the evidence found in the codebase; `None` when the
+    /// entry was not checked or was reworded by verification.
+    confidence: Option<Confidence>,
+}
+
+impl GenerateReport {
+    /// Print the report if stdout is reserved for JSON.
+    fn emit(&self) -> Result<()> {
+        if keryx::output::is_json() {
+            print_json(self).context("Failed to serialize the result")?;
+        }
+        Ok(())
+    }
+}
+
 async fn run_generate(cli: Cli) -> Result<()> {
-    let provider_selection = cli
-        .provider
-        .clone()
-        .map(Provider::from)
-        .map(ProviderSelection::from_primary)
-        .unwrap_or_default();
-    let mut llm = LlmRouter::new(provider_selection);
+    let provider_selection = cli.provider_selection();
+    let template = cli.release_template()?;
 
     // Step 1: Open git repository
     let repo = Repository::open(".")
         .context("Not a git repository. Run keryx from within a git repository.")?;
+    let journal = Journal::for_repository(&repo, "generate");
+    let mut llm = llm_router(provider_selection, journal.clone());
+    let remote = select_pr_remote(&repo, cli.remote.as_deref());
+    let mut summary = RunSummary::new("generate");
+    summary.begin_stage("collect");
+
+    // Version of an untagged changelog section the range starts at
+    let mut changelog_base = None;
+
+    // Step 3-5: Collect input. A milestone replaces the commit range: its
+    // merged PRs are the only input.
+    let (commits, pull_requests, newest_commit) = if let Some(name) = &cli.milestone {
+        if let Some(reason) = detect_remote(&repo, &remote).commits_only_reason(&remote) {
+            bail!("--milestone needs a GitHub remote, but {}", reason);
+        }
+        status!("Collecting merged PRs in milestone \"{}\"...", name);
+        let (milestone, prs) = fetch_milestone_prs_for_repo(&repo, &remote, name, cli.pr_limit)
+            .await
+            .context("--milestone needs merged PRs from GitHub")?;
+        if prs.is_empty() {
+            status!(
+                "No merged PRs in milestone \"{}\". Nothing to add.",
+                milestone.title
+            );
+            return GenerateReport::default().emit();
+        }
+        status!(
+            "Found {} merged PRs in milestone \"{}\" ({})",
+            prs.len(),
+            milestone.title,
+            milestone.state
+        );
+        let newest_merge = prs.iter().filter_map(|pr| pr.merged_at).max();
+        (Vec::new(), prs, newest_merge)
+    } else {
+        // Step 3: Resolve commit range. Without --from, check the latest tag
+        // against the changelog first.
+        let from = match &cli.from {
+            Some(from) => Some(from.clone()),
+            None => match changelog_range_start(&repo, &cli)? {
+                Some((oid, version)) => {
+                    changelog_base = Some(version);
+                    Some(oid.to_string())
+                }
+                None => None,
+            },
+        };
+        let range = resolve_range(&repo, from.as_deref(), Some(&cli.to), cli.strict)
+            .context("Failed to resolve commit range")?;
 
-    // Step 3: Resolve commit range
-    let range = resolve_range(&repo, cli.from.as_deref(), Some(&cli.to), cli.strict)
-        .context("Failed to resolve commit range")?;
-
-    println!(
-        "Analyzing commits from {} to {}...",
-        range.from_ref, range.to_ref
-    );
-
-    // Step 4: Fetch commits
-    let commits = fetch_commits(&repo, range.from, range.to, cli.strict)
-        .context("Failed to fetch commits")?;
+        status!(
+            "Analyzing commits from {} to {}...",
+            range.from_ref,
+            range.to_ref
+        );
 
-    if commits.is_empty() {
-        println!("No changes found since {}. Nothing to add.", range.from_ref);
-        return Ok(());
-    }
+        // Step 4: Fetch commits. With --prs-only, commits only locate the range,
+        // so just their subjects and dates are read.
+        let (commits, subjects) = if cli.prs_only {
+            let subjects = fetch_commit_subjects(&repo, range.from, range.to)
+                .context("Failed to fetch commits")?;
+            (Vec::new(), subjects)
+        } else {
+            let commits = fetch_range_commits(&repo, range.from, range.to, &cli, true)?;
+            let commits = net_commits(&repo, commits, cli.verbose());
+            (commits, Vec::new())
+        };
 
-    println!("Found {} commits", commits.len());
+        if commits.is_empty() && subjects.is_empty() {
+            status!("No changes found since {}. Nothing to add.", range.from_ref);
+            return GenerateReport::default().emit();
+        }
 
-    // Step 5: Fetch PRs (if not disabled)
-    let pull_requests = if cli.no_prs {
-        Vec::new()
-    } else {
-        match fetch_prs_for_repo(&repo, cli.pr_limit).await {
-            Ok(prs) => {
-                println!("Found {} merged PRs", prs.len());
-                prs
-            }
-            Err(e) => handle_pr_fetch_error(e, cli.strict)?,
+        status!("Found {} commits", commits.len().max(subjects.len()));
+        summary.commits = commits.len().max(subjects.len());
+        let newest_commit = commits
+            .iter()
+            .map(|c| c.timestamp)
+            .chain(subjects.iter().filter_map(|c| c.timestamp))
+            .max();
+
+        // Step 5: Fetch PRs (if not disabled)
+        if cli.prs_only
+            && let Some(reason) = detect_remote(&repo, &remote).commits_only_reason(&remote)
+        {
+            bail!("--prs-only needs a GitHub remote, but {}", reason);
         }
+        let pull_requests = if !should_fetch_prs(&repo, &remote, cli.no_prs) {
+            Vec::new()
+        } else if cli.prs_only {
+            let scope = PrScope::for_subjects(&subjects);
+            let prs = fetch_prs_for_repo(&repo, &remote, &scope, cli.pr_limit)
+                .await
+                .context("--prs-only needs merged PRs from GitHub")?;
+            let since = repo
+                .find_commit(range.from)
+                .ok()
+                .and_then(|c| chrono::DateTime::from_timestamp(c.time().seconds(), 0));
+            let referenced = referenced_pr_numbers(subjects.iter().map(|c| c.subject.as_str()));
+            let selected = select_prs_for_range(&prs, &referenced, since, newest_commit);
+
+            if selected.is_empty() {
+                status!(
+                    "No merged PRs found since {}. Nothing to add.",
+                    range.from_ref
+                );
+                return GenerateReport::default().emit();
+            }
+            status!("Found {} merged PRs in range", selected.len());
+            selected
+        } else {
+            let scope = PrScope::for_commits(&commits);
+            match fetch_prs_for_repo(&repo, &remote, &scope, cli.pr_limit).await {
+                Ok(prs) => {
+                    status!("Found {} merged PRs", prs.len());
+                    prs
+                }
+                Err(e) => {
+                    let reason = format!(
+                        "could not be fetched ({}); entries come from commits only",
+                        e
+                    );
+                    let prs = handle_pr_fetch_error(e, cli.strict)?;
+                    summary.degrade("pull requests", reason);
+                    prs
+                }
+            }
+        };
+
+        (commits, pull_requests, newest_commit)
     };
 
+    summary.pull_requests = pull_requests.len();
+
+    let prompt_bytes = estimate::prompt_bytes(&commits, &pull_requests);
+    let mut plan = CostEstimate::new(llm.primary());
+    if cli.set_version.is_none() && !cli.no_llm_bump {
+        plan.add("version bump", prompt_bytes);
+    }
+    plan.add("changelog", prompt_bytes);
+    if cli.verify_mode() == VerifyMode::Llm {
+        plan.add("verification", prompt_bytes);
+    }
+    if cli.migration_guide {
+        plan.add("migration guide", prompt_bytes);
+    }
+    if !confirm_llm_plan(&plan, cli.yes)? {
+        return Ok(());
+    }
+
     // Step 6: Determine version
-    let base_version = get_latest_tag(&repo)?.and_then(|t| t.version);
-    let repo_name_for_bump = get_repo_name(&repo).unwrap_or_else(|| "repository".to_string());
+    summary.begin_stage("version");
+    let latest_tag = get_latest_tag(&repo)?;
+    let base_version =
+        changelog_base.or_else(|| latest_tag.as_ref().and_then(|t| t.version.clone()));
+    let repo_name_for_bump =
+        get_repo_name(&repo, &remote).unwrap_or_else(|| "repository".to_string());
 
     let (next_version, bump_reasoning) = if let Some(explicit) = cli.set_version.clone() {
         (explicit, None)
@@ -1372,10 +4617,10 @@ async fn run_generate(cli: Cli) -> Result<()> {
             previous_version: base_version.as_ref(),
             repository_name: &repo_name_for_bump,
         };
-        calculate_next_version_with_llm(&bump_input, &mut llm, cli.verbose).await
+        calculate_next_version_with_llm(&bump_input, &mut llm, cli.verbose()).await
     };
 
-    println!(
+    status!(
         "Version: {} -> {}",
         base_version
             .as_ref()
@@ -1384,42 +4629,72 @@ async fn run_generate(cli: Cli) -> Result<()> {
         next_version
     );
 
-    if cli.verbose
+    if cli.verbose()
         && let Some(ref reasoning) = bump_reasoning
     {
-        println!("  LLM bump reasoning: {}", reasoning);
+        status!("  LLM bump reasoning: {}", reasoning);
     }
+    let mut report = GenerateReport {
+        version: Some(next_version.to_string()),
+        previous_version: base_version.as_ref().map(|v| v.to_string()),
+        bump_reasoning,
+        dry_run: cli.dry_run,
+        ..GenerateReport::default()
+    };
 
-    // Step 6b: Check if version already exists in changelog
-    if let Some(parsed) =
-        read_changelog(&cli.output).context("Failed to read existing changelog")?
-        && parsed.has_version(&next_version)
-    {
-        if cli.force {
+    // Step 6b: Check if version already exists in any changelog
+    let existing_changelog = read_existing_changelog(&repo, cli.primary_output())
+        .context("Failed to read existing changelog")?;
+    for path in &cli.output {
+        let Some(parsed) = read_existing_changelog(&repo, path)
+            .with_context(|| format!("Failed to read {}", path.display()))?
+        else {
+            continue;
+        };
+        if !parsed.has_version(&next_version) {
+            continue;
+        }
+        if parsed.is_frozen(&next_version) {
+            bail!(
+                "Version {} in {} is frozen ({}) and will not be modified, even with --force.",
+                next_version,
+                path.display(),
+                FROZEN_MARKER
+            );
+        }
+        if cli.merge {
+            status!(
+                "Version {} already exists in {}; adding only new entries (--merge)",
+                next_version,
+                path.display()
+            );
+        } else if cli.force {
             eprintln!(
-                "\x1b[33m⚠ Warning: Version {} already exists in changelog, overwriting due to --force\x1b[0m",
-                next_version
+                "\x1b[33m⚠ Warning: Version {} already exists in {}, overwriting due to --force\x1b[0m",
+                next_version,
+                path.display()
             );
         } else {
             bail!(
                 "Version {} already exists in {}. Use --force to overwrite, or use --set-version to specify a different version.",
                 next_version,
-                cli.output.display()
+                path.display()
             );
         }
     }
 
     // Step 7: Build prompt and call LLM provider
-    let repo_name = get_repo_name(&repo).unwrap_or_else(|| "repository".to_string());
+    let repo_name = get_repo_name(&repo, &remote).unwrap_or_else(|| "repository".to_string());
     let is_initial_release = base_version.is_none();
 
     // For initial releases, gather extra context
     let (project_description, cli_features) = if is_initial_release {
-        (read_cargo_description(), Some(get_cli_features()))
+        (read_cargo_description(&repo), Some(get_cli_features()))
     } else {
         (None, None)
     };
 
+    let attribution = cli.attribution_config();
     let input = ChangelogInput {
         commits,
         pull_requests,
@@ -1427,80 +4702,420 @@ async fn run_generate(cli: Cli) -> Result<()> {
         repository_name: repo_name,
         project_description,
         cli_features,
+        product: ProductContext::for_repository(&repo),
+        languages: LanguageProfile::for_repository(&repo),
+        include_highlights: cli.highlights,
+        // --merge keys entries by the PRs and commits they cite
+        include_sources: attribution.is_enabled() || cli.merge,
+        entry_sort: cli.entry_sort(),
+        prs_only: cli.prs_only || cli.milestone.is_some(),
     };
 
-    let prompt = build_prompt(&input).context("Failed to build prompt for LLM")?;
+    // PRs with a "## Changelog" section in their description bring their own entries
+    let curated = CuratedNotes::from_pull_requests(&input.pull_requests);
+    let llm_input = curated.llm_input(&input);
+    if !curated.is_empty() {
+        status!(
+            "Using the changelog sections of {} PRs ({} entries)",
+            curated.pr_numbers.len(),
+            curated.entries.len()
+        );
+    }
+
+    // Commits with a "Release-Note:" trailer bring their own, still verified, entry
+    let commit_notes = CommitNotes::from_commits(&llm_input.commits);
+    let prompt_input = commit_notes.llm_input(&llm_input);
+    if !commit_notes.is_empty() {
+        status!(
+            "Using the Release-Note trailers of {} commits ({} entries)",
+            commit_notes.hashes.len(),
+            commit_notes.entries.len()
+        );
+    }
 
-    println!(
-        "Generating release notes with {} (fallback: {})...",
-        llm.primary(),
-        llm.fallback()
-    );
+    summary.begin_stage("generate");
+    let mut draft_output = if nothing_to_generate(&prompt_input) {
+        keryx::ChangelogOutput {
+            entries: Vec::new(),
+            highlights: None,
+        }
+    } else {
+        let prompt = build_prompt(&prompt_input).context("Failed to build prompt for LLM")?;
+
+        status!(
+            "Generating release notes with {} (fallback: {})...",
+            llm.primary(),
+            llm.fallback()
+        );
 
-    let draft_completion = llm
-        .generate(&prompt)
+        let draft_completion = generate_guarded(
+            &mut llm,
+            &prompt,
+            SanitizeOptions::from_env(),
+            &cli.entry_constraints(),
+            &prompt_input.commits,
+        )
         .await
-        .map_err(|e| handle_llm_error(e, cli.verbose))?;
-    report_llm_fallback_if_any(&draft_completion, cli.verbose);
-    let draft_output = draft_completion.output;
+        .map_err(|e| handle_llm_error(e, cli.verbose()))?;
+        report_llm_fallback_if_any(&draft_completion, cli.verbose());
+        draft_completion.output
+    };
+    commit_notes.merge_into(&mut draft_output);
+    if !cli.highlights {
+        draft_output.highlights = None;
+    }
 
-    if draft_output.entries.is_empty() {
-        println!("No changelog entries generated. Nothing to add.");
-        return Ok(());
+    if draft_output.entries.is_empty() && curated.entries.is_empty() {
+        // Regeneration already failed; feat/fix commits with no entries is an error
+        if expects_entries(&llm_input.commits) {
+            return Err(keryx::ChangelogError::EmptyOutput.into());
+        }
+        status!("No changelog entries generated. Nothing to add.");
+        return report.emit();
     }
 
-    // Step 8: Verify entries against codebase (unless --no-verify)
-    let changelog_output = if cli.no_verify {
-        debug!("Skipping verification (--no-verify flag)");
-        draft_output
-    } else {
-        let repo_path = repo
-            .workdir()
-            .context("Cannot verify in a bare repository. Use --no-verify to skip verification.")?;
-        verify_changelog_entries(&draft_output, repo_path, cli.verbose, &mut llm).await?
-    };
+    // Step 8: Verify entries against codebase (unless --verify off); curated
+    // entries are the PR author's words and skip verification
+    summary.begin_stage("verify");
+    let entries_generated = draft_output.entries.len() + curated.entries.len();
+    let corpus = SourceCorpus::for_repository(&repo, &llm_input.commits, &llm_input.pull_requests);
+    let VerifiedEntries {
+        output: mut changelog_output,
+        confidence,
+    } = verify_draft(
+        draft_output,
+        &repo,
+        &llm_input.commits,
+        &corpus,
+        cli.verify_mode(),
+        cli.verbose(),
+        &mut llm,
+        Some(&mut summary),
+    )
+    .await?;
+    curated.merge_into(&mut changelog_output);
+    summary.record_entries(entries_generated, changelog_output.entries.len());
 
     if changelog_output.entries.is_empty() {
-        println!("No verified changelog entries found. Nothing to add.");
-        return Ok(());
+        status!("No verified changelog entries found. Nothing to add.");
+        return report.emit();
+    }
+
+    apply_style_guide(&mut changelog_output, &repo);
+
+    if cli.edit {
+        match edit_changelog_section(&changelog_output)? {
+            Some(edited) if edited.entries.is_empty() => {
+                status!("No changelog entries left after editing. Nothing to add.");
+                return report.emit();
+            }
+            Some(edited) => changelog_output = edited,
+            None => status!("Editor closed without saving; keeping the generated entries."),
+        }
     }
 
+    let migration_guide = if cli.migration_guide {
+        summary.begin_stage("migration");
+        generate_migration_guide(&cli, &repo, &mut llm, &input, &next_version, &mut summary).await?
+    } else {
+        None
+    };
+
     // Step 9: Write or display changelog
+    summary.begin_stage("write");
+    let existing_content = existing_changelog.map(|c| c.raw_content);
+    let heading = cli.heading_template();
+    let codenames = cli.codename_config();
+    let mut codename = codenames.resolve(existing_content.as_deref());
+    if codename.is_none() && codenames.suggest {
+        let used = existing_content
+            .as_deref()
+            .map(|content| used_codenames(content, &heading))
+            .unwrap_or_default();
+        codename = suggest_codename(&changelog_output, &used, &mut llm).await;
+    }
+    let attribution = if attribution.is_enabled() {
+        let numbers = cited_pr_numbers(&changelog_output, &attribution);
+        let reviewers = if cli.no_prs || numbers.is_empty() {
+            HashMap::new()
+        } else {
+            match fetch_reviewers_for_repo(&repo, &remote, &numbers).await {
+                Ok(reviewers) => reviewers,
+                Err(e) => {
+                    warn!("Could not fetch PR reviewers for attribution: {:#}", e);
+                    HashMap::new()
+                }
+            }
+        };
+        Some(build_attribution(
+            &changelog_output,
+            &attribution,
+            &input.commits,
+            &reviewers,
+        ))
+    } else {
+        None
+    };
+    let metadata = ReleaseMetadata {
+        date: ReleaseDateConfig::from_flags_or_env(cli.date_timezone, cli.date_source)
+            .release_date(newest_commit),
+        heading,
+        codename,
+        attributes: cli.release_attributes(),
+        compare_links: get_repo_web_url(&repo, &remote).map(|repo_url| CompareLinks {
+            repo_url,
+            tag: format!("v{}", next_version),
+            previous_tag: latest_tag.map(|t| t.name),
+        }),
+        attribution,
+        sort: cli.entry_sort(),
+        source_markers: false,
+        category_aliases: CategoryAliases::for_repository(&repo),
+        template,
+    };
+    let json = keryx::output::is_json();
     if cli.dry_run {
-        println!("\n--- Dry Run Output ---\n");
-        print_changelog_preview(&changelog_output, &next_version);
+        if !json {
+            println!("\n--- Dry Run Output ---\n");
+            print_changelog_preview(&changelog_output, &next_version, &metadata);
+            if let Some(guide) = &migration_guide {
+                println!("\n--- {} ---\n", MIGRATIONS_FILE);
+                print!("{}", guide.render());
+            }
+        }
+    } else if repo.is_bare() {
+        // No checkout to write to: print the section for the caller to use
+        eprintln!(
+            "Bare repository: printing the release notes instead of writing {}",
+            cli.primary_output().display()
+        );
+        if !json {
+            print_changelog_preview(&changelog_output, &next_version, &metadata);
+            if let Some(guide) = &migration_guide {
+                println!();
+                print!("{}", guide.render());
+            }
+        }
+    } else if cli.merge {
+        let counts = merge_changelogs_with_metadata(
+            &cli.output,
+            &changelog_output,
+            &next_version,
+            &metadata,
+        )
+        .context("Failed to merge changelog")?;
+        report.written.extend(cli.output.iter().cloned());
+        if let Some(journal) = &journal {
+            for path in &cli.output {
+                journal.record(
+                    journal
+                        .entry(OperationKind::FileModified)
+                        .arg(path.display().to_string())
+                        .detail(format!(
+                            "merged {} entries into section {}",
+                            counts.added, next_version
+                        )),
+                );
+            }
+        }
+
+        status!(
+            "✓ Added {} new entr{} to {} ({} already present)",
+            counts.added,
+            if counts.added == 1 { "y" } else { "ies" },
+            next_version,
+            counts.kept
+        );
     } else {
-        write_changelog(&cli.output, &changelog_output, &next_version)
+        write_changelogs_with_metadata(&cli.output, &changelog_output, &next_version, &metadata)
             .context("Failed to write changelog")?;
+        report.written.extend(cli.output.iter().cloned());
+        if let Some(journal) = &journal {
+            for path in &cli.output {
+                journal.record(
+                    journal
+                        .entry(OperationKind::FileModified)
+                        .arg(path.display().to_string())
+                        .detail(format!("added section {}", next_version)),
+                );
+            }
+        }
+
+        status!("✓ {}", generate_summary(&changelog_output));
+
+        if let Some(guide) = &migration_guide {
+            write_migration_guide(std::path::Path::new(MIGRATIONS_FILE), guide)
+                .with_context(|| format!("Failed to write {}", MIGRATIONS_FILE))?;
+            report.written.push(PathBuf::from(MIGRATIONS_FILE));
+            if let Some(journal) = &journal {
+                journal.record(
+                    journal
+                        .entry(OperationKind::FileModified)
+                        .arg(MIGRATIONS_FILE)
+                        .detail(format!("added migration guide for {}", next_version)),
+                );
+            }
+            status!(
+                "✓ Wrote {} migration steps to {}",
+                guide.steps.len(),
+                MIGRATIONS_FILE
+            );
+        }
+    }
 
-        let summary = generate_summary(&changelog_output);
-        println!("✓ {}", summary);
+    summary.record_llm(llm.usage());
+    if json {
+        summary.end_stage();
+        report.section = Some(format_version_section(
+            &next_version,
+            &metadata,
+            &changelog_output,
+        ));
+        report.migration_guide = migration_guide.as_ref().map(|guide| guide.render());
+        report.highlights = changelog_output.highlights;
+        report.entries = changelog_output
+            .entries
+            .into_iter()
+            .map(|entry| EntryReport {
+                confidence: confidence.get(&entry.description).copied(),
+                entry,
+            })
+            .collect();
+        report.summary = Some(summary);
+        return report.emit();
     }
+    summary.print(cli.summary_format.into());
 
     Ok(())
 }
 
-/// Verify changelog entries against the codebase using a second LLM pass.
+/// Write and verify upgrade steps for a major release
+/// (`--migration-guide`).
 ///
-/// This function:
-/// 1. Scans the codebase for evidence supporting/refuting each entry
-/// 2. Sends the evidence to Claude for verification
-/// 3. Returns corrected entries with hallucinations removed
-async fn verify_changelog_entries(
-    draft: &keryx::ChangelogOutput,
-    repo_path: &std::path::Path,
-    verbose: bool,
+/// Covers the breaking commits and PRs since the last release of the
+/// previous major line. `None` when the release is not major, nothing in
+/// it is breaking, or no step survived verification.
+async fn generate_migration_guide(
+    cli: &Cli,
+    repo: &Repository,
     llm: &mut LlmRouter,
-) -> Result<keryx::ChangelogOutput> {
-    // Check prerequisites
-    check_ripgrep_installed().context("Verification requires ripgrep")?;
+    input: &ChangelogInput,
+    next_version: &Version,
+    summary: &mut RunSummary,
+) -> Result<Option<MigrationGuide>> {
+    if !is_major_release(input.previous_version.as_ref(), next_version) {
+        debug!(
+            "{} is not a major release; no migration guide",
+            next_version
+        );
+        return Ok(None);
+    }
 
-    println!("Verifying entries against codebase...");
+    // Pre-releases of this major line already shipped some breaking changes
+    let tags = get_all_tags(repo).context("Failed to read tags")?;
+    let base = last_major_release(&tags, next_version);
+    let (commits, since_time) = match base {
+        Some(tag) => {
+            let to = repo
+                .revparse_single(&cli.to)
+                .and_then(|object| object.peel_to_commit())
+                .with_context(|| format!("Failed to resolve {}", cli.to))?;
+            let commits = fetch_commits(repo, tag.oid, to.id(), cli.strict)
+                .with_context(|| format!("Failed to fetch commits since {}", tag.name))?;
+            let since_time = repo
+                .find_commit(tag.oid)
+                .ok()
+                .and_then(|c| chrono::DateTime::from_timestamp(c.time().seconds(), 0));
+            (commits, since_time)
+        }
+        None => (input.commits.clone(), None),
+    };
+    let pull_requests: Vec<keryx::PullRequest> = input
+        .pull_requests
+        .iter()
+        .filter(|pr| match (since_time, pr.merged_at) {
+            (Some(since), Some(merged)) => merged >= since,
+            _ => true,
+        })
+        .cloned()
+        .collect();
 
-    // Gather evidence from the codebase
-    let evidence = gather_verification_evidence(&draft.entries, repo_path);
+    let since = base
+        .and_then(|tag| tag.version.as_ref())
+        .or(input.previous_version.as_ref());
+    let migration = MigrationInput::new(
+        next_version,
+        since,
+        &commits,
+        &pull_requests,
+        &input.repository_name,
+    );
+    if migration.is_empty() {
+        status!("No breaking changes found; skipping the migration guide.");
+        return Ok(None);
+    }
 
-    // Report verification findings
+    status!(
+        "Writing migration guide from {} breaking commits and {} PRs...",
+        migration.commits.len(),
+        migration.pull_requests.len()
+    );
+    let completion = llm
+        .request(&ChangelogRequest::new(build_migration_prompt(&migration)))
+        .await
+        .map_err(|e| handle_llm_error(e, cli.verbose()))?;
+    report_llm_fallback_if_any(&completion, cli.verbose());
+    let mut steps = completion.output;
+    steps.highlights = None;
+    sanitize_output(&mut steps, SanitizeOptions::from_env());
+    if steps.entries.is_empty() {
+        status!("No breaking change needs action from users; skipping the migration guide.");
+        return Ok(None);
+    }
+
+    // Steps are checked against the codebase like changelog entries
+    let mode = cli.verify_mode();
+    let steps = match repo.workdir() {
+        _ if mode == VerifyMode::Off => steps,
+        None => {
+            eprintln!(
+                "\x1b[33m⚠ Bare repository: migration steps were not checked against the codebase\x1b[0m"
+            );
+            steps
+        }
+        Some(repo_path) => {
+            let scope = SearchScope::for_commits(repo, &migration.commits, ScopeMode::from_env());
+            if mode == VerifyMode::Rules {
+                verify_changelog_entries_with_rules(
+                    &steps,
+                    repo_path,
+                    &scope,
+                    cli.verbose(),
+                    Some(summary),
+                )?
+                .output
+            } else {
+                verify_changelog_entries(
+                    &steps,
+                    repo_path,
+                    &scope,
+                    cli.verbose(),
+                    llm,
+                    Some(summary),
+                )
+                .await?
+                .output
+            }
+        }
+    };
+
+    let guide = MigrationGuide::new(&migration, steps);
+    Ok((!guide.is_empty()).then_some(guide))
+}
+
+/// Print low-confidence entries, search failures and (in verbose mode) the
+/// evidence summary for every entry.
+fn report_verification_evidence(evidence: &VerificationEvidence, verbose: bool) {
     let low_confidence: Vec<_> = evidence.low_confidence_entries();
     if !low_confidence.is_empty() {
         eprintln!();
@@ -1562,6 +5177,17 @@ async fn verify_changelog_entries(
         eprintln!();
     }
 
+    if !evidence.unmentioned_api.is_empty() {
+        eprintln!(
+            "\x1b[33m⚠ New public API not mentioned in the changelog ({}):\x1b[0m",
+            evidence.unmentioned_api.len()
+        );
+        for item in &evidence.unmentioned_api {
+            eprintln!("  • {}", item);
+        }
+        eprintln!();
+    }
+
     if verbose {
         // Show all evidence in verbose mode
         for entry_ev in &evidence.entries {
@@ -1574,6 +5200,229 @@ async fn verify_changelog_entries(
             );
         }
     }
+}
+
+/// Note incomplete verification evidence in the run summary.
+fn record_evidence_degradation(evidence: &VerificationEvidence, summary: Option<&mut RunSummary>) {
+    if let Some(summary) = summary
+        && let Some(reason) = evidence.degradation()
+    {
+        summary.degrade("verification", reason);
+    }
+}
+
+/// Verify a draft according to the selected mode.
+///
+/// `Off` returns the draft unchanged, `Llm` runs the verification agent and
+/// `Rules` applies the deterministic rules without another LLM call. Both are
+/// preceded by the sanity pass against `corpus`.
+async fn verify_draft(
+    draft: keryx::ChangelogOutput,
+    repo: &Repository,
+    commits: &[ParsedCommit],
+    corpus: &SourceCorpus,
+    mode: VerifyMode,
+    verbose: bool,
+    llm: &mut LlmRouter,
+    summary: Option<&mut RunSummary>,
+) -> Result<VerifiedEntries> {
+    if mode == VerifyMode::Off {
+        debug!("Skipping verification (--verify off)");
+        return Ok(VerifiedEntries::unchecked(draft));
+    }
+
+    let draft = drop_unsupported_draft_entries(draft, corpus, verbose);
+    if draft.entries.is_empty() {
+        return Ok(VerifiedEntries::unchecked(draft));
+    }
+
+    let Some(repo_path) = repo.workdir() else {
+        // Verification searches the checkout; a bare repository has none
+        eprintln!("\x1b[33m⚠ Bare repository: skipping verification against the codebase\x1b[0m");
+        if let Some(summary) = summary {
+            summary.degrade(
+                "verification",
+                "bare repository; entries were not checked against the codebase",
+            );
+        }
+        report_missed_changes(repo, commits, &draft.entries);
+        return Ok(VerifiedEntries::unchecked(draft));
+    };
+    let scope = SearchScope::for_commits(repo, commits, ScopeMode::from_env());
+    let verified = match mode {
+        VerifyMode::Rules => {
+            verify_changelog_entries_with_rules(&draft, repo_path, &scope, verbose, summary)?
+        }
+        _ => verify_changelog_entries(&draft, repo_path, &scope, verbose, llm, summary).await?,
+    };
+    report_missed_changes(repo, commits, &verified.output.entries);
+    Ok(verified)
+}
+
+/// Entries left after verification.
+struct VerifiedEntries {
+    output: keryx::ChangelogOutput,
+    /// Confidence of the codebase evidence for each draft entry, by
+    /// description.
+    confidence: HashMap<String, Confidence>,
+}
+
+impl VerifiedEntries {
+    /// Entries that were not checked against the codebase.
+    fn unchecked(output: keryx::ChangelogOutput) -> Self {
+        Self {
+            output,
+            confidence: HashMap::new(),
+        }
+    }
+
+    fn new(output: keryx::ChangelogOutput, evidence: &VerificationEvidence) -> Self {
+        Self {
+            output,
+            confidence: evidence
+                .entries
+                .iter()
+                .map(|e| (e.original_description.clone(), e.confidence()))
+                .collect(),
+        }
+    }
+}
+
+/// List significant changes in the range that no verified entry covers.
+fn report_missed_changes(
+    repo: &Repository,
+    commits: &[ParsedCommit],
+    entries: &[keryx::ChangelogEntry],
+) {
+    let missed = find_missed_changes(repo, commits, entries);
+    if missed.is_empty() {
+        return;
+    }
+    eprintln!(
+        "\x1b[33m⚠ Possibly missing from changelog ({}):\x1b[0m",
+        missed.len()
+    );
+    for change in &missed {
+        eprintln!("  • {}", change);
+    }
+    eprintln!();
+}
+
+/// Drop entries naming components that appear in neither the commits, the
+/// PRs, nor the repository's files, before paying for verification.
+fn drop_unsupported_draft_entries(
+    draft: keryx::ChangelogOutput,
+    corpus: &SourceCorpus,
+    verbose: bool,
+) -> keryx::ChangelogOutput {
+    let outcome = drop_unsupported_entries(&draft, corpus);
+    if !outcome.dropped.is_empty() {
+        status!(
+            "\x1b[33m⚠ Removed {} entries naming components not found in the input:\x1b[0m",
+            outcome.dropped.len()
+        );
+        for (entry, components) in &outcome.dropped {
+            if verbose {
+                status!(
+                    "  • {} (unknown: {})",
+                    truncate_description(&entry.description, 60),
+                    components.join(", ")
+                );
+            } else {
+                status!("  • {}", truncate_description(&entry.description, 60));
+            }
+        }
+    }
+    outcome.output
+}
+
+/// Verify changelog entries against the codebase with deterministic rules.
+///
+/// Entries contradicted by the evidence (no keyword found, count mismatch) are
+/// removed; low-confidence entries are kept and listed for review.
+fn verify_changelog_entries_with_rules(
+    draft: &keryx::ChangelogOutput,
+    repo_path: &std::path::Path,
+    scope: &SearchScope,
+    verbose: bool,
+    summary: Option<&mut RunSummary>,
+) -> Result<VerifiedEntries> {
+    check_ripgrep_installed().context("Verification requires ripgrep")?;
+
+    status!("Verifying entries against codebase (rules)...");
+    if verbose && !scope.is_repository() {
+        status!(
+            "  Searching {} paths changed in the range (KERYX_VERIFY_SCOPE=repo for all)",
+            scope.paths().len()
+        );
+    }
+
+    let evidence = gather_verification_evidence_in(&draft.entries, repo_path, scope);
+    report_verification_evidence(&evidence, verbose);
+    record_evidence_degradation(&evidence, summary);
+
+    let outcome = apply_verification_rules(draft, &evidence);
+
+    if !outcome.dropped.is_empty() {
+        status!(
+            "\x1b[33m⚠ Verification removed {} entries contradicted by the codebase:\x1b[0m",
+            outcome.dropped.len()
+        );
+        for (entry, reason) in &outcome.dropped {
+            status!(
+                "  • {} ({})",
+                truncate_description(&entry.description, 60),
+                reason
+            );
+        }
+    }
+    if !outcome.flagged.is_empty() {
+        status!(
+            "\x1b[33m⚠ Kept {} low-confidence entries; review them before releasing\x1b[0m",
+            outcome.flagged.len()
+        );
+    }
+    if outcome.dropped.is_empty() && outcome.flagged.is_empty() {
+        status!(
+            "\x1b[32m✓ All {} entries verified\x1b[0m",
+            outcome.output.entries.len()
+        );
+    }
+
+    Ok(VerifiedEntries::new(outcome.output, &evidence))
+}
+
+/// Verify changelog entries against the codebase using a second LLM pass.
+///
+/// This function:
+/// 1. Scans the codebase for evidence supporting/refuting each entry
+/// 2. Sends the evidence to Claude for verification
+/// 3. Returns corrected entries with hallucinations removed
+async fn verify_changelog_entries(
+    draft: &keryx::ChangelogOutput,
+    repo_path: &std::path::Path,
+    scope: &SearchScope,
+    verbose: bool,
+    llm: &mut LlmRouter,
+    summary: Option<&mut RunSummary>,
+) -> Result<VerifiedEntries> {
+    // Check prerequisites
+    check_ripgrep_installed().context("Verification requires ripgrep")?;
+
+    status!("Verifying entries against codebase...");
+    if verbose && !scope.is_repository() {
+        status!(
+            "  Searching {} paths changed in the range (KERYX_VERIFY_SCOPE=repo for all)",
+            scope.paths().len()
+        );
+    }
+
+    // Gather evidence from the codebase
+    let evidence = gather_verification_evidence_in(&draft.entries, repo_path, scope);
+
+    // Report verification findings
+    report_verification_evidence(&evidence, verbose);
+    record_evidence_degradation(&evidence, summary);
 
     // Serialize draft entries for verification prompt
     let draft_json =
@@ -1583,7 +5432,7 @@ async fn verify_changelog_entries(
     let verification_prompt = build_verification_prompt(&draft_json, &evidence)
         .context("Failed to build verification prompt")?;
 
-    println!(
+    status!(
         "Running verification agent with {} (fallback: {})...",
         llm.primary(),
         llm.fallback()
@@ -1591,26 +5440,32 @@ async fn verify_changelog_entries(
 
     // Run verification pass
     let verified_completion = llm
-        .generate(&verification_prompt)
+        .request(&VerificationRequest::new(verification_prompt))
         .await
         .map_err(|e| handle_llm_error(e, verbose))?;
     report_llm_fallback_if_any(&verified_completion, verbose);
-    let verified_output = verified_completion.output;
+    let mut verified_output = verified_completion.output;
+    sanitize_output(&mut verified_output, SanitizeOptions::from_env());
+
+    // Keep the draft's highlights if the verifier dropped them
+    if verified_output.highlights.is_none() {
+        verified_output.highlights = draft.highlights.clone();
+    }
 
     // Report what changed
     let original_count = draft.entries.len();
     let verified_count = verified_output.entries.len();
 
     if verified_count < original_count {
-        println!(
+        status!(
             "\x1b[33m⚠ Verification removed {} potentially inaccurate entries\x1b[0m",
             original_count - verified_count
         );
     } else if verified_count == original_count {
-        println!("\x1b[32m✓ All {} entries verified\x1b[0m", verified_count);
+        status!("\x1b[32m✓ All {} entries verified\x1b[0m", verified_count);
     }
 
-    Ok(verified_output)
+    Ok(VerifiedEntries::new(verified_output, &evidence))
 }
 
 fn report_llm_fallback_if_any<T>(completion: &LlmCompletion<T>, verbose: bool) {
@@ -1676,76 +5531,282 @@ fn provider_install_hint(err: &LlmProviderError) -> Option<&'static str> {
         LlmProviderError::Codex(keryx::CodexError::NotInstalled) => Some(
             "Install Codex CLI: npm install -g @openai/codex (then run `codex` or set CODEX_API_KEY)",
         ),
+        LlmProviderError::Ollama(keryx::OllamaError::RetriesExhausted(inner))
+            if matches!(**inner, keryx::OllamaError::NotRunning(_)) =>
+        {
+            Some(
+                "Install Ollama from https://ollama.com and run `ollama serve`, or point OLLAMA_HOST at a running server",
+            )
+        }
+        LlmProviderError::Ollama(keryx::OllamaError::RetriesExhausted(inner))
+            if matches!(**inner, keryx::OllamaError::ModelNotFound(_)) =>
+        {
+            Some("Pull the model with `ollama pull <model>`, or set OLLAMA_MODEL to one you have")
+        }
         _ => None,
     }
 }
 
 /// Truncate a description for display.
 fn truncate_description(desc: &str, max_len: usize) -> String {
-    if desc.len() <= max_len {
-        desc.to_string()
-    } else {
-        let mut end = max_len.saturating_sub(3);
-        while end > 0 && !desc.is_char_boundary(end) {
-            end -= 1;
-        }
-        format!("{}...", &desc[..end])
-    }
+    truncate_to_width(desc, max_len)
 }
 
-/// Fetch PRs for the current repository.
+/// Fetch merged PRs of the current repository for the commit range `scope`.
 async fn fetch_prs_for_repo(
     repo: &Repository,
+    remote: &str,
+    scope: &PrScope,
     limit: Option<usize>,
 ) -> Result<Vec<keryx::PullRequest>> {
-    // Get GitHub token
-    let token = get_github_token()
+    // One authenticated client per run; repeated queries hit its cache
+    let client = shared_client()
         .await
         .context("GitHub authentication required for PR fetching")?;
 
     // Get remote URL
     let remote = repo
-        .find_remote("origin")
-        .context("No 'origin' remote found")?;
+        .find_remote(remote)
+        .with_context(|| format!("No '{}' remote found", remote))?;
 
     let url = remote.url().context("Remote has no URL")?;
 
     let (owner, repo_name) =
         parse_github_remote(url).context("Could not parse GitHub remote URL")?;
 
-    // Fetch PRs (no date filter for now, we'll filter by commits later)
-    let prs = fetch_merged_prs(&token, &owner, &repo_name, None, None, limit).await?;
+    // Listing stops at the scope's range start; PRs are matched to commits later
+    let prs = client.scoped_prs(&owner, &repo_name, scope, limit).await?;
 
     Ok(prs)
 }
 
+/// Fetch the milestone titled `name` and its merged PRs from the GitHub remote.
+async fn fetch_milestone_prs_for_repo(
+    repo: &Repository,
+    remote: &str,
+    name: &str,
+    limit: Option<usize>,
+) -> Result<(Milestone, Vec<keryx::PullRequest>)> {
+    let client = shared_client()
+        .await
+        .context("GitHub authentication required for milestone PRs")?;
+    let remote = repo
+        .find_remote(remote)
+        .with_context(|| format!("No '{}' remote found", remote))?;
+    let url = remote.url().context("Remote has no URL")?;
+    let (owner, repo_name) =
+        parse_github_remote(url).context("Could not parse GitHub remote URL")?;
+
+    Ok(client
+        .milestone_prs(&owner, &repo_name, name, limit)
+        .await?)
+}
+
+/// Fetch the reviewers of the given PRs from the GitHub remote.
+async fn fetch_reviewers_for_repo(
+    repo: &Repository,
+    remote: &str,
+    numbers: &[u64],
+) -> Result<HashMap<u64, Vec<String>>> {
+    let client = shared_client()
+        .await
+        .context("GitHub authentication required for PR reviewers")?;
+    let remote = repo
+        .find_remote(remote)
+        .with_context(|| format!("No '{}' remote found", remote))?;
+    let url = remote.url().context("Remote has no URL")?;
+    let (owner, repo_name) =
+        parse_github_remote(url).context("Could not parse GitHub remote URL")?;
+
+    let mut reviewers = HashMap::new();
+    for &number in numbers {
+        reviewers.insert(
+            number,
+            client.pr_reviewers(&owner, &repo_name, number).await?,
+        );
+    }
+    Ok(reviewers)
+}
+
+/// Fetch the discussion of the given PRs from the GitHub remote.
+async fn fetch_pr_discussions_for_repo(
+    repo: &Repository,
+    remote: &str,
+    numbers: &[u64],
+) -> Result<Vec<PrDiscussion>> {
+    let client = shared_client()
+        .await
+        .context("GitHub authentication required for PR discussion")?;
+    let remote = repo
+        .find_remote(remote)
+        .with_context(|| format!("No '{}' remote found", remote))?;
+    let url = remote.url().context("Remote has no URL")?;
+    let (owner, repo_name) =
+        parse_github_remote(url).context("Could not parse GitHub remote URL")?;
+
+    let mut discussions = Vec::new();
+    for &number in numbers {
+        discussions.push(client.pr_discussion(&owner, &repo_name, number).await?);
+    }
+    Ok(discussions)
+}
+
 /// Get the repository name from the remote URL.
-fn get_repo_name(repo: &Repository) -> Option<String> {
-    let remote = repo.find_remote("origin").ok()?;
+fn get_repo_name(repo: &Repository, remote: &str) -> Option<String> {
+    let remote = repo.find_remote(remote).ok()?;
     let url = remote.url()?;
     let (_, name) = parse_github_remote(url).ok()?;
     Some(name)
 }
 
-/// Print a preview of the changelog output.
-fn print_changelog_preview(output: &keryx::ChangelogOutput, version: &Version) {
-    use chrono::Utc;
+/// Get the repository's web URL from a remote (GitHub only).
+fn get_repo_web_url(repo: &Repository, remote: &str) -> Option<String> {
+    let remote = repo.find_remote(remote).ok()?;
+    github_web_url(remote.url()?).ok()
+}
 
-    let today = Utc::now().format("%Y-%m-%d");
-    println!("## [{}] - {}\n", version, today);
+/// Print a preview of the changelog output.
+fn print_changelog_preview(
+    output: &keryx::ChangelogOutput,
+    version: &Version,
+    metadata: &ReleaseMetadata,
+) {
+    print!("{}", format_version_section(version, metadata, output));
+}
 
-    for (category, entries) in output.entries_by_category() {
-        println!("### {}\n", category.as_str());
-        for entry in entries {
-            println!("- {}", entry.description);
+/// Apply the spelling and terminology rules from `keryx.toml` and report
+/// what was fixed or needs a look.
+/// Open the generated section in `$EDITOR` (`--edit`) and read it back. An
+/// edit that does not parse is reported and can be fixed in the editor
+/// again. `None` if the editor was closed without saving or the user gave up
+/// on an invalid edit.
+fn edit_changelog_section(
+    output: &keryx::ChangelogOutput,
+) -> Result<Option<keryx::ChangelogOutput>> {
+    let mut text = render_for_edit(output);
+    loop {
+        let Some(edited) = dialoguer::Editor::new()
+            .extension(".md")
+            .edit(&text)
+            .context("Failed to open $EDITOR")?
+        else {
+            return Ok(None);
+        };
+        match parse_edited(&edited, output) {
+            Ok(parsed) => return Ok(Some(parsed)),
+            Err(e) => {
+                eprintln!("\x1b[33m⚠ Invalid changelog section: {}\x1b[0m", e);
+                let retry = dialoguer::Confirm::new()
+                    .with_prompt("Edit again?")
+                    .default(true)
+                    .interact()
+                    .context("Failed to read confirmation")?;
+                if !retry {
+                    return Ok(None);
+                }
+                text = edited;
+            }
         }
-        println!();
     }
 }
 
+fn apply_style_guide(output: &mut keryx::ChangelogOutput, repo: &Repository) {
+    let guide = StyleGuide::for_repository(repo);
+    let report = guide.apply(output);
+    for fix in &report.fixes {
+        status!("Style: \"{}\" → \"{}\"", fix.from, fix.to);
+    }
+    for flag in &report.flagged {
+        eprintln!(
+            "\x1b[33m⚠ Possible misspelling \"{}\" (did you mean {}?) in: {}\x1b[0m",
+            flag.word,
+            flag.suggestions.join(" or "),
+            truncate_description(&flag.text, 60)
+        );
+    }
+}
+
+/// Check the changelog's newest version against the tags before the range
+/// is based on the latest tag.
+///
+/// When that version was never tagged, warns and, with
+/// `--range-from-changelog` or after confirmation, returns the commit that
+/// added its section together with the version, to use as the range start
+/// and base version.
+fn changelog_range_start(repo: &Repository, cli: &Cli) -> Result<Option<(git2::Oid, Version)>> {
+    let path = cli.primary_output();
+    let Some(changelog_version) = read_existing_changelog(repo, path)
+        .ok()
+        .flatten()
+        .and_then(|c| c.latest_version)
+    else {
+        return Ok(None);
+    };
+    let Some(untagged) = find_untagged_release(repo, path, &changelog_version)? else {
+        return Ok(None);
+    };
+
+    let tag = match &untagged.latest_tag {
+        Some(tag) => format!("the latest tag is {}", tag.name),
+        None => "there are no version tags".to_string(),
+    };
+    eprintln!(
+        "\x1b[33m⚠ {} is at {} but {}; was {} released without a tag?\x1b[0m",
+        path.display(),
+        changelog_version,
+        tag,
+        changelog_version
+    );
+    let Some(oid) = untagged.section_commit else {
+        eprintln!(
+            "  Could not find the commit that added {}; use --from to choose the range",
+            changelog_version
+        );
+        return Ok(None);
+    };
+    let short = &oid.to_string()[..7];
+
+    let use_changelog = cli.range_from_changelog
+        || (std::io::stdin().is_terminal()
+            && dialoguer::Confirm::new()
+                .with_prompt(format!(
+                    "Start the range at {} (where {} was added) instead?",
+                    short, changelog_version
+                ))
+                .default(true)
+                .interact()
+                .context("Failed to read confirmation")?);
+    if !use_changelog {
+        eprintln!(
+            "  Pass --range-from-changelog (or --from {}) to start the range there",
+            short
+        );
+        return Ok(None);
+    }
+    status!(
+        "Basing the range on {} instead of the latest tag",
+        changelog_version
+    );
+    Ok(Some((oid, changelog_version)))
+}
+
+/// The changelog at `path`, read from the tree of `HEAD` in a bare
+/// repository.
+fn read_existing_changelog(
+    repo: &Repository,
+    path: &std::path::Path,
+) -> Result<Option<ParsedChangelog>, keryx::ChangelogError> {
+    if !repo.is_bare() {
+        return read_changelog(path);
+    }
+    read_head_file(repo, path)
+        .map(parse_changelog_content)
+        .transpose()
+}
+
 /// Read project description from Cargo.toml.
-fn read_cargo_description() -> Option<String> {
-    let content = std::fs::read_to_string("Cargo.toml").ok()?;
+fn read_cargo_description(repo: &Repository) -> Option<String> {
+    let content = read_repo_file(repo, std::path::Path::new("Cargo.toml"))?;
 
     // Simple parsing - look for description = "..."
     for line in content.lines() {
@@ -1845,6 +5906,22 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_format_json_supported_commands() {
+        let supports = |args: &[&str]| {
+            Cli::try_parse_from(std::iter::once("keryx").chain(args.iter().copied()))
+                .unwrap()
+                .supports_json()
+        };
+        assert!(supports(&["--dry-run"]));
+        assert!(supports(&["commit", "--message-only"]));
+        assert!(supports(&["ship", "--dry-run"]));
+        assert!(!supports(&["ship"]));
+        assert!(!supports(&["ship", "--dry-run", "--train"]));
+        assert!(!supports(&["commit"]));
+        assert!(!supports(&["check"]));
+    }
+
     #[test]
     fn test_truncate_description_short_string() {
         // Short strings should not be truncated
@@ -1871,7 +5948,7 @@ mod tests {
 
         // Emoji (4 bytes each)
         let emoji_str = "🚀🔥💡✨";
-        let result = truncate_description(emoji_str, 10);
+        let result = truncate_description(emoji_str, 6);
         assert!(result.ends_with("..."));
         // Should not panic and should be valid UTF-8
 
@@ -0,0 +1,257 @@
+//! HTTP requests to the chat endpoint of an Ollama server.
+
+use std::env;
+use std::time::Duration;
+
+use reqwest::{Client, StatusCode};
+use serde_json::{Value, json};
+use tracing::{trace, warn};
+
+use crate::error::OllamaError;
+
+use super::{DEFAULT_BASE_URL, DEFAULT_MODEL, HOST_ENV, MODEL_ENV};
+
+/// Default timeout for one request (10 minutes; local models are slow).
+const DEFAULT_TIMEOUT_SECS: u64 = 600;
+
+/// Environment variable to override the default timeout.
+const TIMEOUT_ENV_VAR: &str = "KERYX_OLLAMA_TIMEOUT";
+
+/// Context window requested for each prompt. Ollama's own default is a few
+/// thousand tokens and silently cuts longer prompts.
+const DEFAULT_NUM_CTX: u64 = 32768;
+
+/// Environment variable to override the context window.
+const NUM_CTX_ENV_VAR: &str = "KERYX_OLLAMA_NUM_CTX";
+
+/// Port of a server address given without one.
+const DEFAULT_PORT: u16 = 11434;
+
+/// A positive number from the environment variable `name`, else `default`.
+fn env_number(name: &str, default: u64) -> u64 {
+    match env::var(name) {
+        Ok(v) if !v.is_empty() => match v.parse::<u64>() {
+            Ok(n) if n > 0 => n,
+            _ => {
+                warn!("Invalid {} value '{}', using default {}", name, v, default);
+                default
+            }
+        },
+        _ => default,
+    }
+}
+
+/// A non-empty environment variable.
+fn env_value(name: &str) -> Option<String> {
+    env::var(name)
+        .ok()
+        .map(|v| v.trim().to_string())
+        .filter(|v| !v.is_empty())
+}
+
+/// Base URL for an `OLLAMA_HOST` value, which may leave out the scheme
+/// (`127.0.0.1:11434`) or the port (`http://gpu-box`).
+fn base_url(host: &str) -> String {
+    let host = host.trim().trim_end_matches('/');
+    let (scheme, address) = host.split_once("://").unwrap_or(("http", host));
+    let has_port = address
+        .rsplit_once(':')
+        .is_some_and(|(_, port)| port.parse::<u16>().is_ok());
+    if has_port {
+        format!("{}://{}", scheme, address)
+    } else {
+        format!("{}://{}:{}", scheme, address, DEFAULT_PORT)
+    }
+}
+
+/// Client for one Ollama server and model.
+pub struct OllamaClient {
+    client: Client,
+    base_url: String,
+    model: String,
+    num_ctx: u64,
+    timeout_secs: u64,
+}
+
+impl Default for OllamaClient {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl OllamaClient {
+    /// A client for [`DEFAULT_MODEL`] on a local server.
+    pub fn new() -> Self {
+        let timeout_secs = env_number(TIMEOUT_ENV_VAR, DEFAULT_TIMEOUT_SECS);
+        Self {
+            client: Client::builder()
+                .timeout(Duration::from_secs(timeout_secs))
+                .build()
+                .unwrap_or_default(),
+            base_url: DEFAULT_BASE_URL.to_string(),
+            model: DEFAULT_MODEL.to_string(),
+            num_ctx: env_number(NUM_CTX_ENV_VAR, DEFAULT_NUM_CTX),
+            timeout_secs,
+        }
+    }
+
+    /// A client for the server in `OLLAMA_HOST` and the model in
+    /// `OLLAMA_MODEL`, each falling back to the default.
+    pub fn from_env() -> Self {
+        let mut client = Self::new();
+        if let Some(model) = env_value(MODEL_ENV) {
+            client = client.with_model(&model);
+        }
+        if let Some(host) = env_value(HOST_ENV) {
+            client = client.with_base_url(&base_url(&host));
+        }
+        client
+    }
+
+    /// Use another model.
+    pub fn with_model(mut self, model: &str) -> Self {
+        self.model = model.to_string();
+        self
+    }
+
+    /// Send requests to another server.
+    pub fn with_base_url(mut self, base_url: &str) -> Self {
+        self.base_url = base_url.trim_end_matches('/').to_string();
+        self
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    pub fn model(&self) -> &str {
+        &self.model
+    }
+
+    /// Send `prompt` as a single user message and return the text of the
+    /// reply.
+    #[tracing::instrument(level = "trace", skip_all)]
+    pub async fn complete(&self, prompt: &str) -> Result<String, OllamaError> {
+        trace!(
+            "Calling Ollama ({}) at {} with <{} byte prompt>",
+            self.model,
+            self.base_url,
+            prompt.len()
+        );
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .json(&json!({
+                "model": self.model,
+                "messages": [{ "role": "user", "content": prompt }],
+                "stream": false,
+                "options": { "num_ctx": self.num_ctx },
+            }))
+            .send()
+            .await
+            .map_err(|e| self.request_error(e))?;
+        let status = response.status();
+        let body = response.text().await.map_err(|e| self.request_error(e))?;
+        let json: Value = serde_json::from_str(&body).unwrap_or(Value::Null);
+        if !status.is_success() {
+            let message = json
+                .get("error")
+                .and_then(|m| m.as_str())
+                .map(String::from)
+                .unwrap_or(body);
+            if status == StatusCode::NOT_FOUND && message.contains("not found") {
+                return Err(OllamaError::ModelNotFound(self.model.clone()));
+            }
+            return Err(OllamaError::Status {
+                status: status.as_u16(),
+                message,
+            });
+        }
+
+        json.pointer("/message/content")
+            .and_then(|c| c.as_str())
+            .filter(|t| !t.trim().is_empty())
+            .map(String::from)
+            .ok_or(OllamaError::EmptyResponse)
+    }
+
+    fn request_error(&self, error: reqwest::Error) -> OllamaError {
+        if error.is_timeout() {
+            OllamaError::Timeout(self.timeout_secs)
+        } else if error.is_connect() {
+            OllamaError::NotRunning(self.base_url.clone())
+        } else {
+            OllamaError::RequestFailed(error.to_string())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    #[test]
+    fn test_base_url_from_host() {
+        assert_eq!(base_url("127.0.0.1:11434"), "http://127.0.0.1:11434");
+        assert_eq!(base_url("http://gpu-box/"), "http://gpu-box:11434");
+        assert_eq!(
+            base_url("https://ollama.lan:8443"),
+            "https://ollama.lan:8443"
+        );
+        assert_eq!(base_url("0.0.0.0"), "http://0.0.0.0:11434");
+    }
+
+    #[tokio::test]
+    async fn test_chat_request() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/api/chat"))
+            .and(body_partial_json(json!({
+                "model": "qwen2.5-coder",
+                "messages": [{ "role": "user", "content": "Write notes" }],
+                "stream": false,
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(json!({
+                "model": "qwen2.5-coder",
+                "message": { "role": "assistant", "content": "{\"entries\": []}" },
+                "done": true
+            })))
+            .expect(1)
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::new()
+            .with_model("qwen2.5-coder")
+            .with_base_url(&server.uri());
+        assert_eq!(
+            client.complete("Write notes").await.unwrap(),
+            "{\"entries\": []}"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_missing_model_and_server() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .respond_with(ResponseTemplate::new(404).set_body_json(json!({
+                "error": "model \"llama3.1\" not found, try pulling it first"
+            })))
+            .mount(&server)
+            .await;
+
+        let client = OllamaClient::new().with_base_url(&server.uri());
+        assert!(matches!(
+            client.complete("hi").await,
+            Err(OllamaError::ModelNotFound(model)) if model == DEFAULT_MODEL
+        ));
+
+        // Nothing listens on port 1
+        let client = OllamaClient::new().with_base_url("http://127.0.0.1:1");
+        assert!(matches!(
+            client.complete("hi").await,
+            Err(OllamaError::NotRunning(url)) if url == "http://127.0.0.1:1"
+        ));
+    }
+}
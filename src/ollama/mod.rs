@@ -0,0 +1,24 @@
+//! Ollama integration for generating changelogs with a local model.
+//!
+//! Prompts go to the chat endpoint of an Ollama server, so no code leaves
+//! the machine. The server is `OLLAMA_HOST` (default
+//! `http://localhost:11434`), the model `OLLAMA_MODEL` (default
+//! `llama3.1`); the model must have been pulled with `ollama pull`.
+
+pub mod client;
+pub mod retry;
+
+pub use client::OllamaClient;
+pub use retry::{OllamaExecutor, generate_raw_with_retry, generate_with_retry};
+
+/// Environment variable with the server address, as read by Ollama itself.
+pub const HOST_ENV: &str = "OLLAMA_HOST";
+
+/// Environment variable overriding [`DEFAULT_MODEL`].
+pub const MODEL_ENV: &str = "OLLAMA_MODEL";
+
+/// Address of a server started with `ollama serve`.
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Model used when [`MODEL_ENV`] is not set.
+pub const DEFAULT_MODEL: &str = "llama3.1";
@@ -0,0 +1,131 @@
+//! Exponential backoff retry logic for Ollama.
+
+use async_trait::async_trait;
+
+use crate::changelog::ChangelogOutput;
+use crate::error::OllamaError;
+use crate::llm::extract_json;
+use crate::llm::retry::retry_with_backoff;
+
+use super::OllamaClient;
+
+/// Trait for sending a prompt to an Ollama server.
+///
+/// This abstraction allows mocking the HTTP requests in tests.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait OllamaExecutor: Send + Sync {
+    /// Send the prompt and return the text of the reply.
+    async fn run(&self, prompt: &str) -> Result<String, OllamaError>;
+}
+
+#[async_trait]
+impl OllamaExecutor for OllamaClient {
+    async fn run(&self, prompt: &str) -> Result<String, OllamaError> {
+        self.complete(prompt).await
+    }
+}
+
+/// Generate changelog entries with retry logic.
+///
+/// Makes up to 3 attempts with exponential backoff on failure.
+pub async fn generate_with_retry(prompt: &str) -> Result<ChangelogOutput, OllamaError> {
+    generate_with_retry_impl(prompt, &OllamaClient::from_env()).await
+}
+
+/// Generate a raw string response with retry logic (no ChangelogOutput parsing).
+pub async fn generate_raw_with_retry(prompt: &str) -> Result<String, OllamaError> {
+    generate_raw_with_retry_impl(prompt, &OllamaClient::from_env()).await
+}
+
+/// Internal raw retry implementation that accepts any executor (for testing).
+pub(crate) async fn generate_raw_with_retry_impl<E: OllamaExecutor>(
+    prompt: &str,
+    executor: &E,
+) -> Result<String, OllamaError> {
+    retry_with_backoff(
+        || async { executor.run(prompt).await },
+        |e| OllamaError::RetriesExhausted(Box::new(e)),
+    )
+    .await
+}
+
+/// Internal implementation that accepts any executor (for testing).
+pub(crate) async fn generate_with_retry_impl<E: OllamaExecutor>(
+    prompt: &str,
+    executor: &E,
+) -> Result<ChangelogOutput, OllamaError> {
+    retry_with_backoff(
+        || async { try_generate(prompt, executor).await },
+        |e| OllamaError::RetriesExhausted(Box::new(e)),
+    )
+    .await
+}
+
+/// Single attempt to generate changelog.
+async fn try_generate<E: OllamaExecutor>(
+    prompt: &str,
+    executor: &E,
+) -> Result<ChangelogOutput, OllamaError> {
+    let response = executor.run(prompt).await?;
+    parse_ollama_response(&response)
+}
+
+/// Parse the reply text into ChangelogOutput. Local models often wrap the
+/// JSON in prose or a code fence.
+fn parse_ollama_response(response: &str) -> Result<ChangelogOutput, OllamaError> {
+    if let Ok(output) = serde_json::from_str::<ChangelogOutput>(response) {
+        return Ok(output);
+    }
+
+    let json_str = extract_json(response);
+    serde_json::from_str(&json_str).map_err(|e| {
+        OllamaError::InvalidJson(format!("Failed to parse: {}. Content: {}", e, response))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Test that an unparseable reply is retried and a later valid one used.
+    #[tokio::test(start_paused = true)]
+    async fn test_ollama_retries_invalid_json() {
+        let mut mock = MockOllamaExecutor::new();
+        let mut seq = mockall::Sequence::new();
+        mock.expect_run()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| Ok("Sure! Here is the changelog you asked for.".to_string()));
+        mock.expect_run()
+            .times(1)
+            .in_sequence(&mut seq)
+            .returning(|_| {
+                Ok("```json\n{\"entries\": [{\"category\": \"Added\", \"description\": \"Offline mode\"}]}\n```".to_string())
+            });
+
+        let output = generate_with_retry_impl("test prompt", &mock)
+            .await
+            .unwrap();
+        assert_eq!(output.entries.len(), 1);
+        assert_eq!(output.entries[0].description, "Offline mode");
+    }
+
+    /// Test that failed requests are retried and the last error kept.
+    #[tokio::test(start_paused = true)]
+    async fn test_ollama_raw_retry_exhaustion() {
+        let mut mock = MockOllamaExecutor::new();
+        mock.expect_run().times(3).returning(|_| {
+            Err(OllamaError::NotRunning(
+                "http://localhost:11434".to_string(),
+            ))
+        });
+
+        match generate_raw_with_retry_impl("test prompt", &mock).await {
+            Err(OllamaError::RetriesExhausted(inner)) => {
+                assert!(matches!(*inner, OllamaError::NotRunning(_)));
+            }
+            other => panic!("Expected RetriesExhausted error, got {:?}", other),
+        }
+    }
+}
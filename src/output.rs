@@ -0,0 +1,66 @@
+//! Human-readable or JSON output on stdout.
+//!
+//! With `--format json`, stdout carries a single JSON document for other
+//! release tooling to read, so the progress and status lines that normally
+//! go to stdout are written to stderr instead. Code on those paths prints
+//! with [`status!`](crate::status) rather than `println!`.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+
+use serde::Serialize;
+
+/// What stdout carries.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Progress and results for people.
+    #[default]
+    Text,
+    /// One JSON document with the result; progress goes to stderr.
+    Json,
+}
+
+static JSON: AtomicBool = AtomicBool::new(false);
+
+/// Set the format for the rest of the process.
+pub fn set_format(format: OutputFormat) {
+    JSON.store(format == OutputFormat::Json, Ordering::Relaxed);
+}
+
+/// The format set with [`set_format`].
+pub fn format() -> OutputFormat {
+    if JSON.load(Ordering::Relaxed) {
+        OutputFormat::Json
+    } else {
+        OutputFormat::Text
+    }
+}
+
+/// Whether stdout is reserved for a JSON document.
+pub fn is_json() -> bool {
+    format() == OutputFormat::Json
+}
+
+/// Print `value` to stdout as the run's JSON document.
+pub fn print_json<T: Serialize>(value: &T) -> serde_json::Result<()> {
+    println!("{}", serde_json::to_string_pretty(value)?);
+    Ok(())
+}
+
+/// Like `println!`, but to stderr when stdout is reserved for JSON.
+#[macro_export]
+macro_rules! status {
+    () => {
+        if $crate::output::is_json() {
+            eprintln!();
+        } else {
+            println!();
+        }
+    };
+    ($($arg:tt)*) => {
+        if $crate::output::is_json() {
+            eprintln!($($arg)*);
+        } else {
+            println!($($arg)*);
+        }
+    };
+}
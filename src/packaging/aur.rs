@@ -0,0 +1,173 @@
+//! AUR packages (`PKGBUILD` and its generated `.SRCINFO`).
+//!
+//! The download URL of a PKGBUILD is usually built from `$pkgver`, so only
+//! the version, release number and checksum change. `.SRCINFO` has the
+//! sources expanded, so the old version is replaced in its `source` lines.
+
+use std::sync::LazyLock;
+
+use super::{ManifestUpdater, PackageRelease, field_not_found, replace_first};
+use crate::error::PackagingError;
+
+/// The `.SRCINFO` file updated alongside a PKGBUILD.
+pub const SRCINFO_FILE: &str = ".SRCINFO";
+
+static PKGVER_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"(?m)^(pkgver=)[^\s#]*()")
+        .expect("Invalid pkgver regex - this is a bug")
+});
+
+static PKGREL_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"(?m)^(pkgrel=)[^\s#]*()")
+        .expect("Invalid pkgrel regex - this is a bug")
+});
+
+/// First checksum of `sha256sums=(...)`: the one of the release source.
+static SHA256SUMS_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r#"(?m)^(sha256sums=\(\s*['"])[^'"]*(['"])"#)
+        .expect("Invalid sha256sums regex - this is a bug")
+});
+
+static SRCINFO_PKGVER_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"(?m)^(\s*pkgver = )(\S*)()")
+        .expect("Invalid .SRCINFO pkgver regex - this is a bug")
+});
+
+static SRCINFO_PKGREL_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"(?m)^(\s*pkgrel = )\S*()")
+        .expect("Invalid .SRCINFO pkgrel regex - this is a bug")
+});
+
+static SRCINFO_SHA256SUMS_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r"(?m)^(\s*sha256sums = )\S*()")
+        .expect("Invalid .SRCINFO sha256sums regex - this is a bug")
+});
+
+/// Updates `pkgver`, resets `pkgrel` to 1 and replaces the first
+/// `sha256sums` entry; `.SRCINFO` next to the PKGBUILD is kept in sync.
+pub struct AurUpdater;
+
+impl ManifestUpdater for AurUpdater {
+    fn name(&self) -> &'static str {
+        "AUR"
+    }
+
+    fn update(
+        &self,
+        file: &str,
+        content: &str,
+        release: &PackageRelease,
+    ) -> Result<String, PackagingError> {
+        let pkgver = pkgver(&release.version);
+        if file.rsplit('/').next() == Some(SRCINFO_FILE) {
+            return update_srcinfo(file, content, &pkgver, &release.sha256);
+        }
+        let updated = replace_first(&PKGVER_REGEX, content, &pkgver)
+            .ok_or_else(|| field_not_found(file, "pkgver"))?;
+        let updated = replace_first(&PKGREL_REGEX, &updated, "1")
+            .ok_or_else(|| field_not_found(file, "pkgrel"))?;
+        replace_first(&SHA256SUMS_REGEX, &updated, &release.sha256)
+            .ok_or_else(|| field_not_found(file, "sha256sums"))
+    }
+
+    fn companions(&self) -> &'static [&'static str] {
+        &[SRCINFO_FILE]
+    }
+}
+
+/// `pkgver` may not contain hyphens; pre-release versions use `_`.
+fn pkgver(version: &str) -> String {
+    version.replace('-', "_")
+}
+
+fn update_srcinfo(
+    file: &str,
+    content: &str,
+    pkgver: &str,
+    sha256: &str,
+) -> Result<String, PackagingError> {
+    let old = SRCINFO_PKGVER_REGEX
+        .captures(content)
+        .map(|caps| caps[2].to_string())
+        .ok_or_else(|| field_not_found(file, "pkgver"))?;
+    let mut updated = String::with_capacity(content.len());
+    for line in content.split_inclusive('\n') {
+        if !old.is_empty() && line.trim_start().starts_with("source") {
+            updated.push_str(&line.replace(&old, pkgver));
+        } else {
+            updated.push_str(line);
+        }
+    }
+    let updated = replace_first(&SRCINFO_PKGVER_REGEX, &updated, pkgver)
+        .ok_or_else(|| field_not_found(file, "pkgver"))?;
+    let updated = replace_first(&SRCINFO_PKGREL_REGEX, &updated, "1").unwrap_or(updated);
+    Ok(replace_first(&SRCINFO_SHA256SUMS_REGEX, &updated, sha256).unwrap_or(updated))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn release(version: &str) -> PackageRelease {
+        PackageRelease {
+            version: version.to_string(),
+            tag: format!("v{}", version),
+            url: format!(
+                "https://github.com/acme/keryx/archive/refs/tags/v{}.tar.gz",
+                version
+            ),
+            sha256: "c".repeat(64),
+        }
+    }
+
+    #[test]
+    fn test_updates_pkgbuild() {
+        let pkgbuild = "pkgname=keryx\npkgver=1.1.0\npkgrel=3\n\
+                        source=(\"$pkgname-$pkgver.tar.gz::https://github.com/acme/keryx/archive/refs/tags/v$pkgver.tar.gz\")\n\
+                        sha256sums=('1111111111111111111111111111111111111111111111111111111111111111'\n            'SKIP')\n";
+        let updated = AurUpdater
+            .update("PKGBUILD", pkgbuild, &release("1.2.0-rc.1"))
+            .unwrap();
+        assert!(updated.contains("pkgver=1.2.0_rc.1\n"));
+        assert!(updated.contains("pkgrel=1\n"));
+        assert!(updated.contains(&format!(
+            "sha256sums=('{}'\n            'SKIP')",
+            "c".repeat(64)
+        )));
+        assert!(updated.contains("v$pkgver.tar.gz"));
+    }
+
+    #[test]
+    fn test_updates_srcinfo() {
+        let srcinfo = "pkgbase = keryx\n\tpkgver = 1.1.0\n\tpkgrel = 3\n\
+                       \tsource = keryx-1.1.0.tar.gz::https://github.com/acme/keryx/archive/refs/tags/v1.1.0.tar.gz\n\
+                       \tsha256sums = 1111111111111111111111111111111111111111111111111111111111111111\n\n\
+                       pkgname = keryx\n";
+        let updated = AurUpdater
+            .update(".SRCINFO", srcinfo, &release("1.2.0"))
+            .unwrap();
+        assert_eq!(
+            updated,
+            format!(
+                "pkgbase = keryx\n\tpkgver = 1.2.0\n\tpkgrel = 1\n\
+                 \tsource = keryx-1.2.0.tar.gz::https://github.com/acme/keryx/archive/refs/tags/v1.2.0.tar.gz\n\
+                 \tsha256sums = {}\n\npkgname = keryx\n",
+                "c".repeat(64)
+            )
+        );
+    }
+
+    #[test]
+    fn test_pkgbuild_without_checksums_is_an_error() {
+        let err = AurUpdater
+            .update("PKGBUILD", "pkgver=1.1.0\npkgrel=1\n", &release("1.2.0"))
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PackagingError::FieldNotFound {
+                field: "sha256sums",
+                ..
+            }
+        ));
+    }
+}
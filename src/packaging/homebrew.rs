@@ -0,0 +1,121 @@
+//! Homebrew formulae (`Formula/<name>.rb` in a tap).
+
+use std::sync::LazyLock;
+
+use super::{ManifestUpdater, PackageRelease, field_not_found, replace_first};
+use crate::error::PackagingError;
+
+/// The formula's `url "..."`. The first one is the stable download;
+/// `head` and `resource` blocks come after it.
+static URL_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r#"(?m)^(\s*url\s+")[^"]*(")"#)
+        .expect("Invalid formula url regex - this is a bug")
+});
+
+/// A plain `sha256 "..."`; bottle checksums (`sha256 cellar: ...`) do not match.
+static SHA256_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r#"(?m)^(\s*sha256\s+")[0-9a-fA-F]*(")"#)
+        .expect("Invalid formula sha256 regex - this is a bug")
+});
+
+/// An explicit `version "..."`, for URLs Homebrew cannot read it from.
+static VERSION_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r#"(?m)^(\s*version\s+")[^"]*(")"#)
+        .expect("Invalid formula version regex - this is a bug")
+});
+
+/// Updates the `url`, `sha256` and, if present, `version` of a formula.
+pub struct HomebrewUpdater;
+
+impl ManifestUpdater for HomebrewUpdater {
+    fn name(&self) -> &'static str {
+        "Homebrew"
+    }
+
+    fn update(
+        &self,
+        file: &str,
+        content: &str,
+        release: &PackageRelease,
+    ) -> Result<String, PackagingError> {
+        let updated = replace_first(&URL_REGEX, content, &release.url)
+            .ok_or_else(|| field_not_found(file, "url"))?;
+        let updated = replace_first(&SHA256_REGEX, &updated, &release.sha256)
+            .ok_or_else(|| field_not_found(file, "sha256"))?;
+        Ok(replace_first(&VERSION_REGEX, &updated, &release.version).unwrap_or(updated))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORMULA: &str = r#"class Keryx < Formula
+  desc "Release notes from merged PRs"
+  homepage "https://github.com/acme/keryx"
+  url "https://github.com/acme/keryx/archive/refs/tags/v1.1.0.tar.gz"
+  sha256 "1111111111111111111111111111111111111111111111111111111111111111"
+  license "MIT"
+
+  bottle do
+    sha256 cellar: :any, arm64_sonoma: "2222222222222222222222222222222222222222222222222222222222222222"
+  end
+
+  head do
+    url "https://github.com/acme/keryx.git", branch: "main"
+  end
+end
+"#;
+
+    fn release() -> PackageRelease {
+        PackageRelease {
+            version: "1.2.0".to_string(),
+            tag: "v1.2.0".to_string(),
+            url: "https://github.com/acme/keryx/archive/refs/tags/v1.2.0.tar.gz".to_string(),
+            sha256: "a".repeat(64),
+        }
+    }
+
+    #[test]
+    fn test_updates_stable_url_and_sha256() {
+        let updated = HomebrewUpdater
+            .update("Formula/keryx.rb", FORMULA, &release())
+            .unwrap();
+        assert!(
+            updated.contains(
+                r#"  url "https://github.com/acme/keryx/archive/refs/tags/v1.2.0.tar.gz""#
+            )
+        );
+        assert!(updated.contains(&format!(r#"  sha256 "{}""#, "a".repeat(64))));
+        // Bottles and head are left alone
+        assert!(updated.contains(r#"arm64_sonoma: "2222"#));
+        assert!(updated.contains(r#"url "https://github.com/acme/keryx.git", branch: "main""#));
+    }
+
+    #[test]
+    fn test_updates_explicit_version() {
+        let formula = FORMULA.replace("  license", "  version \"1.1.0\"\n  license");
+        let updated = HomebrewUpdater
+            .update("Formula/keryx.rb", &formula, &release())
+            .unwrap();
+        assert!(updated.contains(r#"  version "1.2.0""#));
+    }
+
+    #[test]
+    fn test_missing_sha256_is_an_error() {
+        let formula = FORMULA.replace(
+            "  sha256 \"1111111111111111111111111111111111111111111111111111111111111111\"\n",
+            "",
+        );
+        let err = HomebrewUpdater
+            .update("Formula/keryx.rb", &formula, &release())
+            .unwrap_err();
+        assert!(matches!(
+            err,
+            PackagingError::FieldNotFound {
+                field: "sha256",
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,87 @@
+//! Scoop manifests (`bucket/<name>.json`).
+//!
+//! The JSON is edited as text so the bucket's formatting and key order are
+//! kept.
+
+use std::sync::LazyLock;
+
+use super::{ManifestUpdater, PackageRelease, field_not_found, replace_first};
+use crate::error::PackagingError;
+
+static VERSION_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r#"("version"\s*:\s*")[^"]*(")"#)
+        .expect("Invalid scoop version regex - this is a bug")
+});
+
+/// The first `url`: the top-level one, or the first architecture's. The
+/// `autoupdate` block comes last and is left alone.
+static URL_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r#"("url"\s*:\s*")[^"]*(")"#)
+        .expect("Invalid scoop url regex - this is a bug")
+});
+
+static HASH_REGEX: LazyLock<regex_lite::Regex> = LazyLock::new(|| {
+    regex_lite::Regex::new(r#"("hash"\s*:\s*")[^"]*(")"#)
+        .expect("Invalid scoop hash regex - this is a bug")
+});
+
+/// Updates the `version`, and the first `url` and `hash` of a manifest.
+pub struct ScoopUpdater;
+
+impl ManifestUpdater for ScoopUpdater {
+    fn name(&self) -> &'static str {
+        "Scoop"
+    }
+
+    fn update(
+        &self,
+        file: &str,
+        content: &str,
+        release: &PackageRelease,
+    ) -> Result<String, PackagingError> {
+        let updated = replace_first(&VERSION_REGEX, content, &release.version)
+            .ok_or_else(|| field_not_found(file, "version"))?;
+        let updated = replace_first(&URL_REGEX, &updated, &release.url)
+            .ok_or_else(|| field_not_found(file, "url"))?;
+        replace_first(&HASH_REGEX, &updated, &release.sha256)
+            .ok_or_else(|| field_not_found(file, "hash"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MANIFEST: &str = r#"{
+    "version": "1.1.0",
+    "description": "Release notes from merged PRs",
+    "url": "https://github.com/acme/keryx/releases/download/v1.1.0/keryx-x86_64-pc-windows-msvc.zip",
+    "hash": "1111111111111111111111111111111111111111111111111111111111111111",
+    "bin": "keryx.exe",
+    "autoupdate": {
+        "url": "https://github.com/acme/keryx/releases/download/v$version/keryx-x86_64-pc-windows-msvc.zip"
+    }
+}
+"#;
+
+    #[test]
+    fn test_updates_version_url_and_hash() {
+        let release = PackageRelease {
+            version: "1.2.0".to_string(),
+            tag: "v1.2.0".to_string(),
+            url: "https://github.com/acme/keryx/releases/download/v1.2.0/keryx-x86_64-pc-windows-msvc.zip"
+                .to_string(),
+            sha256: "b".repeat(64),
+        };
+        let updated = ScoopUpdater
+            .update("bucket/keryx.json", MANIFEST, &release)
+            .unwrap();
+        assert!(updated.contains(r#""version": "1.2.0","#));
+        assert!(
+            updated.contains(r#""url": "https://github.com/acme/keryx/releases/download/v1.2.0/"#)
+        );
+        assert!(updated.contains(&format!(r#""hash": "{}""#, "b".repeat(64))));
+        assert!(updated.contains("download/v$version/"));
+        serde_json::from_str::<serde_json::Value>(&updated).unwrap();
+    }
+}
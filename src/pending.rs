@@ -0,0 +1,256 @@
+//! `keryx pending`: what would go into the next release.
+//!
+//! Groups the commits since the last tag by conventional commit type and
+//! projects the next version with the same rules as `keryx ship` without
+//! the LLM. Optionally the LLM turns the list into a short narrative to help
+//! decide whether it is time to cut a release.
+
+use semver::Version;
+
+use crate::git::{CommitType, ParsedCommit};
+use crate::llm::prompt::{UNTRUSTED_INPUT_RULE, sanitize_for_prompt, untrusted_block};
+use crate::version::{BumpType, apply_bump_to_version, determine_bump_type};
+
+/// Commits of one kind since the last release.
+#[derive(Debug, Clone)]
+pub struct PendingGroup {
+    /// Heading, e.g. "Features" or "Breaking changes".
+    pub label: &'static str,
+    pub commits: Vec<ParsedCommit>,
+}
+
+/// Everything on the branch since the last release.
+#[derive(Debug, Clone)]
+pub struct PendingReport {
+    /// Tag (or "root") the commits are counted from.
+    pub since: String,
+    /// Version of that tag, if it is one.
+    pub base_version: Option<Version>,
+    /// Non-empty groups in display order.
+    pub groups: Vec<PendingGroup>,
+    pub bump: BumpType,
+    pub next_version: Version,
+}
+
+/// Group order and headings. Breaking changes are listed on their own,
+/// whatever their type; commits without a conventional type come last.
+const GROUPS: [(&str, Option<CommitType>); 11] = [
+    ("Features", Some(CommitType::Feat)),
+    ("Fixes", Some(CommitType::Fix)),
+    ("Performance", Some(CommitType::Perf)),
+    ("Refactoring", Some(CommitType::Refactor)),
+    ("Documentation", Some(CommitType::Docs)),
+    ("Tests", Some(CommitType::Test)),
+    ("Build", Some(CommitType::Build)),
+    ("CI", Some(CommitType::Ci)),
+    ("Style", Some(CommitType::Style)),
+    ("Chores", Some(CommitType::Chore)),
+    ("Other", None),
+];
+
+impl PendingReport {
+    /// Build the report for `commits` (newest first) since `since`.
+    pub fn new(since: &str, base_version: Option<Version>, commits: &[ParsedCommit]) -> Self {
+        let mut groups = Vec::new();
+        let breaking: Vec<ParsedCommit> = commits.iter().filter(|c| c.breaking).cloned().collect();
+        if !breaking.is_empty() {
+            groups.push(PendingGroup {
+                label: "Breaking changes",
+                commits: breaking,
+            });
+        }
+        for (label, commit_type) in GROUPS {
+            let matching: Vec<ParsedCommit> = commits
+                .iter()
+                .filter(|c| !c.breaking && c.commit_type == commit_type)
+                .cloned()
+                .collect();
+            if !matching.is_empty() {
+                groups.push(PendingGroup {
+                    label,
+                    commits: matching,
+                });
+            }
+        }
+
+        let bump = determine_bump_type(commits);
+        Self {
+            since: since.to_string(),
+            next_version: apply_bump_to_version(base_version.as_ref(), bump),
+            base_version,
+            groups,
+            bump,
+        }
+    }
+
+    /// Number of commits in the report.
+    pub fn commit_count(&self) -> usize {
+        self.groups.iter().map(|g| g.commits.len()).sum()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.groups.is_empty()
+    }
+
+    /// Render the report for the terminal.
+    pub fn render(&self) -> String {
+        let mut out = format!("{} commit(s) since {}\n", self.commit_count(), self.since);
+        for group in &self.groups {
+            out.push_str(&format!("\n{} ({})\n", group.label, group.commits.len()));
+            for commit in &group.commits {
+                out.push_str(&format!("  {} {}\n", short_hash(commit), commit.subject()));
+            }
+        }
+        out.push_str(&format!(
+            "\nPredicted bump: {}\nNext version:   {} -> {}\n",
+            bump_name(self.bump),
+            self.base_version
+                .as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+            self.next_version
+        ));
+        out
+    }
+}
+
+fn short_hash(commit: &ParsedCommit) -> &str {
+    &commit.hash[..commit.hash.len().min(7)]
+}
+
+fn bump_name(bump: BumpType) -> &'static str {
+    match bump {
+        BumpType::Major => "major",
+        BumpType::Minor => "minor",
+        BumpType::Patch => "patch",
+    }
+}
+
+/// Build the prompt asking the LLM for a short narrative of the pending
+/// changes and whether they are worth a release.
+pub fn build_pending_prompt(report: &PendingReport, repository_name: &str) -> String {
+    let mut sections = String::new();
+    for group in &report.groups {
+        sections.push_str(&format!("## {}\n", group.label));
+        let subjects: Vec<String> = group
+            .commits
+            .iter()
+            .map(|commit| format!("- {}", sanitize_for_prompt(commit.subject())))
+            .collect();
+        sections.push_str(&format!("{}\n\n", untrusted_block(&subjects.join("\n"))));
+    }
+
+    format!(
+        r#"You are helping the maintainers of {repository} decide when to cut the next release.
+These are the unreleased commits since {since}, grouped by conventional commit type. The projected next version is {next} ({bump} bump).
+
+{UNTRUSTED_INPUT_RULE}
+
+{sections}## Instructions
+1. Summarize in 3-6 sentences what users would get from releasing now
+2. Call out breaking changes and anything that needs migration notes
+3. Say whether the changes look worth a release now or are better batched with more work, and why
+4. Do not invent changes that are not listed
+
+Respond in concise Markdown prose. Do not respond with JSON."#,
+        repository = sanitize_for_prompt(repository_name),
+        since = report.since,
+        next = report.next_version,
+        bump = bump_name(report.bump),
+        sections = sections
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::git::Footers;
+    use chrono::Utc;
+
+    fn commit(
+        hash: &str,
+        message: &str,
+        commit_type: Option<CommitType>,
+        breaking: bool,
+    ) -> ParsedCommit {
+        ParsedCommit {
+            hash: hash.to_string(),
+            message: message.to_string(),
+            commit_type,
+            scope: None,
+            breaking,
+            timestamp: Utc::now(),
+            footers: Footers::default(),
+        }
+    }
+
+    #[test]
+    fn test_groups_in_order_with_breaking_first() {
+        let commits = vec![
+            commit(
+                "aaaaaaa1",
+                "fix: crash on empty tag",
+                Some(CommitType::Fix),
+                false,
+            ),
+            commit(
+                "bbbbbbb2",
+                "feat!: drop --legacy",
+                Some(CommitType::Feat),
+                true,
+            ),
+            commit(
+                "ccccccc3",
+                "feat: add pending",
+                Some(CommitType::Feat),
+                false,
+            ),
+            commit("ddddddd4", "Update readme", None, false),
+        ];
+        let report = PendingReport::new("v1.4.2", Some(Version::new(1, 4, 2)), &commits);
+
+        let labels: Vec<&str> = report.groups.iter().map(|g| g.label).collect();
+        assert_eq!(
+            labels,
+            vec!["Breaking changes", "Features", "Fixes", "Other"]
+        );
+        assert_eq!(report.commit_count(), 4);
+        assert_eq!(report.bump, BumpType::Major);
+        assert_eq!(report.next_version, Version::new(2, 0, 0));
+    }
+
+    #[test]
+    fn test_render_lists_commits_and_projection() {
+        let commits = vec![commit(
+            "0123456789",
+            "feat: add pending",
+            Some(CommitType::Feat),
+            false,
+        )];
+        let report = PendingReport::new("v0.3.0", Some(Version::new(0, 3, 0)), &commits);
+        let rendered = report.render();
+
+        assert!(rendered.contains("1 commit(s) since v0.3.0"));
+        assert!(rendered.contains("Features (1)\n  0123456 feat: add pending"));
+        assert!(rendered.contains("Predicted bump: minor"));
+        assert!(rendered.contains("Next version:   0.3.0 -> 0.4.0"));
+    }
+
+    #[test]
+    fn test_empty_report() {
+        let report = PendingReport::new("v1.0.0", Some(Version::new(1, 0, 0)), &[]);
+        assert!(report.is_empty());
+        assert_eq!(report.next_version, Version::new(1, 0, 1));
+    }
+
+    #[test]
+    fn test_prompt_lists_groups() {
+        let commits = vec![commit("abc", "fix: crash", Some(CommitType::Fix), false)];
+        let report = PendingReport::new("root", None, &commits);
+        let prompt = build_pending_prompt(&report, "keryx");
+
+        assert!(prompt.contains("since root"));
+        assert!(prompt.contains("## Fixes\n<untrusted_input>\n- fix: crash\n</untrusted_input>"));
+        assert!(prompt.contains("0.0.1 (patch bump)"));
+    }
+}
@@ -0,0 +1,243 @@
+//! `keryx refine`: rewrite single changelog entries with the LLM.
+//!
+//! The user picks an entry and types an instruction ("mention it only
+//! affects Windows", "make it shorter"); the entry and the instruction go to
+//! the LLM, and the answer replaces the entry's line in place. Entries in
+//! frozen sections are never offered.
+
+use std::path::Path;
+
+use crate::changelog::line_ending::{LineEnding, normalize};
+use crate::changelog::parser::FROZEN_MARKER;
+use crate::changelog::writer::atomic_write;
+use crate::error::ChangelogError;
+use crate::llm::prompt::sanitize_for_prompt;
+
+/// A bullet entry of the changelog and where it is.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefinableEntry {
+    /// Zero-based line index in the (LF-normalized) changelog.
+    pub line: usize,
+    /// Section label, e.g. `1.2.0` or `Unreleased`.
+    pub section: String,
+    /// `### ` category heading the entry is under, if any.
+    pub category: Option<String>,
+    /// Bullet marker, `- ` or `* `.
+    pub bullet: String,
+    pub description: String,
+}
+
+impl RefinableEntry {
+    /// One-line label for pickers.
+    pub fn label(&self) -> String {
+        match &self.category {
+            Some(category) => format!("[{} / {}] {}", self.section, category, self.description),
+            None => format!("[{}] {}", self.section, self.description),
+        }
+    }
+}
+
+/// Entries of changelog `content` that may be refined, top to bottom.
+pub fn list_entries(content: &str) -> Vec<RefinableEntry> {
+    let content = normalize(content);
+    let lines: Vec<&str> = content.lines().collect();
+    let mut entries = Vec::new();
+    let mut section: Option<(String, bool)> = None;
+    let mut category = None;
+
+    for (index, line) in lines.iter().enumerate() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            let frozen = lines[index..]
+                .iter()
+                .skip(1)
+                .take_while(|l| !l.starts_with("## "))
+                .chain(std::iter::once(line))
+                .any(|l| l.contains(FROZEN_MARKER));
+            section = Some((section_label(heading), frozen));
+            category = None;
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix("### ") {
+            category = Some(heading.trim().to_string());
+            continue;
+        }
+        let Some((label, false)) = &section else {
+            continue;
+        };
+        let Some((bullet, text)) = ["- ", "* "]
+            .iter()
+            .find_map(|b| line.strip_prefix(b).map(|text| (*b, text.trim())))
+        else {
+            continue;
+        };
+        if text.is_empty() {
+            continue;
+        }
+        entries.push(RefinableEntry {
+            line: index,
+            section: label.clone(),
+            category: category.clone(),
+            bullet: bullet.to_string(),
+            description: text.to_string(),
+        });
+    }
+    entries
+}
+
+/// Label of a `## ` heading: the bracketed part if any, else the first word.
+fn section_label(heading: &str) -> String {
+    let heading = heading.trim();
+    if let Some(rest) = heading.strip_prefix('[')
+        && let Some((label, _)) = rest.split_once(']')
+    {
+        return label.to_string();
+    }
+    heading
+        .split_whitespace()
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+/// Build the prompt asking the LLM to rewrite one entry as instructed.
+pub fn build_refine_prompt(entry: &RefinableEntry, instruction: &str) -> String {
+    let category = entry
+        .category
+        .as_deref()
+        .map(|c| format!(" under \"{}\"", sanitize_for_prompt(c)))
+        .unwrap_or_default();
+
+    format!(
+        r#"You are editing one entry of a changelog written in the Keep a Changelog style. The entry is in the {section} section{category}:
+
+{entry}
+
+## Instruction
+{instruction}
+
+## Rules
+1. Apply the instruction and change nothing else about the meaning
+2. Keep the style of a changelog entry: one sentence or phrase, no trailing period unless the original has one
+3. Keep PR references and links such as (#12) exactly as they are
+4. Do not invent details the entry and the instruction do not give
+
+Respond with the rewritten entry text only: a single line, without a leading bullet, quotes or commentary."#,
+        section = sanitize_for_prompt(&entry.section),
+        category = category,
+        entry = sanitize_for_prompt(&entry.description),
+        instruction = sanitize_for_prompt(instruction)
+    )
+}
+
+/// The rewritten entry in an LLM response: the first non-empty line without
+/// code fences, bullet or surrounding quotes. `None` if there is none.
+pub fn parse_refined(output: &str) -> Option<String> {
+    let line = output
+        .lines()
+        .map(str::trim)
+        .find(|l| !l.is_empty() && !l.starts_with("```"))?;
+    let line = line
+        .strip_prefix("- ")
+        .or_else(|| line.strip_prefix("* "))
+        .unwrap_or(line)
+        .trim();
+    let line = ['"', '`', '\'']
+        .iter()
+        .find_map(|q| line.strip_prefix(*q).and_then(|l| l.strip_suffix(*q)))
+        .unwrap_or(line)
+        .trim();
+    (!line.is_empty()).then(|| line.to_string())
+}
+
+/// `content` with `entry` rewritten to `description`.
+///
+/// Returns `None` if the entry's line no longer holds the entry (the file
+/// changed since it was listed).
+pub fn replace_entry(content: &str, entry: &RefinableEntry, description: &str) -> Option<String> {
+    let content = normalize(content);
+    let mut lines: Vec<String> = content.lines().map(String::from).collect();
+    let line = lines.get_mut(entry.line)?;
+    if line.trim() != format!("{}{}", entry.bullet, entry.description).trim() {
+        return None;
+    }
+    *line = format!("{}{}", entry.bullet, description);
+
+    let mut updated = lines.join("\n");
+    if content.ends_with('\n') {
+        updated.push('\n');
+    }
+    Some(updated)
+}
+
+/// Write the refined changelog, keeping the file's line endings.
+pub fn write_refined(path: &Path, content: &str) -> Result<(), ChangelogError> {
+    let ending = std::fs::read_to_string(path)
+        .map(|existing| LineEnding::detect(&existing))
+        .unwrap_or_default();
+    atomic_write(path, &ending.apply(content))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CHANGELOG: &str = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Dark mode (#12)\n\n## [1.1.0] - 2024-03-01\n\n### Fixed\n\n* Crash on startup\n\n## [1.0.0] - 2024-01-01 <!-- keryx:frozen -->\n\n### Added\n\n- Initial release\n";
+
+    #[test]
+    fn test_list_entries_skips_frozen_sections() {
+        let entries = list_entries(CHANGELOG);
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].section, "Unreleased");
+        assert_eq!(entries[0].category.as_deref(), Some("Added"));
+        assert_eq!(entries[0].description, "Dark mode (#12)");
+        assert_eq!(entries[0].line, 6);
+        assert_eq!(entries[1].bullet, "* ");
+        assert_eq!(entries[1].label(), "[1.1.0 / Fixed] Crash on startup");
+    }
+
+    #[test]
+    fn test_replace_entry() {
+        let entries = list_entries(CHANGELOG);
+        let updated = replace_entry(CHANGELOG, &entries[1], "Crash on startup on Windows").unwrap();
+        assert!(updated.contains("\n* Crash on startup on Windows\n"));
+        assert!(updated.ends_with("- Initial release\n"));
+        assert_eq!(
+            updated.lines().count(),
+            CHANGELOG.lines().count(),
+            "only the entry line changes"
+        );
+
+        let changed = CHANGELOG.replace("Crash on startup", "Crash on exit");
+        assert_eq!(replace_entry(&changed, &entries[1], "x"), None);
+    }
+
+    #[test]
+    fn test_replace_entry_crlf() {
+        let crlf = CHANGELOG.replace('\n', "\r\n");
+        let entries = list_entries(&crlf);
+        let updated = replace_entry(&crlf, &entries[0], "Dark mode toggle (#12)").unwrap();
+        assert!(updated.contains("- Dark mode toggle (#12)\n"));
+    }
+
+    #[test]
+    fn test_parse_refined() {
+        assert_eq!(
+            parse_refined("  Dark mode (Windows only)\n").as_deref(),
+            Some("Dark mode (Windows only)")
+        );
+        assert_eq!(
+            parse_refined("```\n- \"Dark mode\"\n```").as_deref(),
+            Some("Dark mode")
+        );
+        assert_eq!(parse_refined("\n  \n"), None);
+    }
+
+    #[test]
+    fn test_prompt_includes_entry_and_instruction() {
+        let entries = list_entries(CHANGELOG);
+        let prompt = build_refine_prompt(&entries[0], "mention it only affects Windows");
+        assert!(prompt.contains("Unreleased section under \"Added\""));
+        assert!(prompt.contains("\nDark mode (#12)\n"));
+        assert!(prompt.contains("## Instruction\nmention it only affects Windows"));
+    }
+}
@@ -0,0 +1,247 @@
+//! Repository bootstrap files (`keryx init --github-workflow`).
+//!
+//! Files are rendered from templates embedded in the binary. Placeholders
+//! are written `@NAME@` so they cannot be confused with GitHub Actions
+//! `${{ ... }}` expressions.
+
+use std::path::{Path, PathBuf};
+
+use crate::error::ScaffoldError;
+use crate::llm::Provider;
+
+/// Where the release workflow is written, relative to the repository root.
+pub const WORKFLOW_PATH: &str = ".github/workflows/keryx-release.yml";
+
+const RELEASE_WORKFLOW: &str = include_str!("templates/github-release.yml");
+const RUST_SETUP: &str = include_str!("templates/rust-setup.yml");
+const RUST_BUMP: &str = include_str!("templates/rust-bump.yml");
+const NODE_BUMP: &str = include_str!("templates/node-bump.yml");
+const PYTHON_BUMP: &str = include_str!("templates/python-bump.yml");
+
+/// Kind of project in the repository root, by its manifest.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProjectType {
+    Rust,
+    Node,
+    Python,
+    /// No known manifest; the workflow only updates the changelog.
+    Generic,
+}
+
+impl ProjectType {
+    /// Detect the project type from the manifests in `root`, in the order
+    /// version files are detected by `ship`.
+    pub fn detect(root: &Path) -> Self {
+        if root.join("Cargo.toml").is_file() {
+            Self::Rust
+        } else if root.join("package.json").is_file() {
+            Self::Node
+        } else if root.join("pyproject.toml").is_file() {
+            Self::Python
+        } else {
+            Self::Generic
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Rust => "Rust",
+            Self::Node => "Node.js",
+            Self::Python => "Python",
+            Self::Generic => "generic",
+        }
+    }
+
+    /// Steps installing the tools the version bump needs.
+    fn setup_steps(&self) -> &'static str {
+        match self {
+            Self::Rust => RUST_SETUP,
+            Self::Node | Self::Python | Self::Generic => "",
+        }
+    }
+
+    /// Steps writing the new version to the project's manifest.
+    fn bump_steps(&self) -> &'static str {
+        match self {
+            Self::Rust => RUST_BUMP,
+            Self::Node => NODE_BUMP,
+            Self::Python => PYTHON_BUMP,
+            Self::Generic => "",
+        }
+    }
+}
+
+impl std::fmt::Display for ProjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// What the workflow needs for `provider`: the npm package of its CLI
+/// (`None` for the HTTP APIs, which need nothing installed) and the API key
+/// secret. Ollama needs the address of a server the runner can reach.
+fn provider_cli(provider: Provider) -> (Option<&'static str>, &'static str) {
+    match provider {
+        Provider::Claude => (Some("@anthropic-ai/claude-code"), "ANTHROPIC_API_KEY"),
+        Provider::Codex => (Some("@openai/codex"), "CODEX_API_KEY"),
+        Provider::AnthropicApi => (None, "ANTHROPIC_API_KEY"),
+        Provider::OpenAiApi => (None, "OPENAI_API_KEY"),
+        Provider::Ollama => (None, "OLLAMA_HOST"),
+    }
+}
+
+/// Repository secret holding the API key of `provider`.
+pub fn api_key_secret(provider: Provider) -> &'static str {
+    provider_cli(provider).1
+}
+
+/// Render the release workflow for `project`, generating notes with
+/// `provider` into `changelog` (relative to the repository root).
+pub fn render_release_workflow(
+    project: ProjectType,
+    provider: Provider,
+    changelog: &Path,
+) -> String {
+    let (package, secret) = provider_cli(provider);
+    let changelog = changelog
+        .to_string_lossy()
+        .trim_start_matches("./")
+        .to_string();
+    let install = package
+        .map(|package| {
+            format!(
+                "      - name: Install {} CLI\n        run: npm install -g {}\n\n",
+                provider, package
+            )
+        })
+        .unwrap_or_default();
+    render(
+        RELEASE_WORKFLOW,
+        &[
+            ("SETUP_STEPS", project.setup_steps()),
+            ("BUMP_STEPS", project.bump_steps()),
+            ("PROVIDER_NAME", provider.as_str()),
+            ("PROVIDER_INSTALL", &install),
+            ("PROVIDER_SECRET", secret),
+            ("PROVIDER", provider.id()),
+            ("CHANGELOG", &changelog),
+        ],
+    )
+}
+
+/// Replace every `@NAME@` in `template`. A placeholder alone on its line
+/// takes the whole line, so empty values leave no blank line behind.
+fn render(template: &str, values: &[(&str, &str)]) -> String {
+    let mut rendered = template.to_string();
+    for (name, value) in values {
+        let placeholder = format!("@{}@", name);
+        rendered = rendered.replace(&format!("{}\n", placeholder), value);
+        rendered = rendered.replace(&placeholder, value);
+    }
+    rendered
+}
+
+/// Write the workflow to [`WORKFLOW_PATH`] under `root`, creating the
+/// directories. An existing file is only replaced with `force`.
+pub fn write_workflow(root: &Path, content: &str, force: bool) -> Result<PathBuf, ScaffoldError> {
+    let path = root.join(WORKFLOW_PATH);
+    if path.exists() && !force {
+        return Err(ScaffoldError::AlreadyExists(path));
+    }
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent).map_err(|source| ScaffoldError::WriteFailed {
+            path: parent.to_path_buf(),
+            source,
+        })?;
+    }
+    crate::atomic::write_atomic(&path, content).map_err(|source| ScaffoldError::WriteFailed {
+        path: path.clone(),
+        source,
+    })?;
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_detect_project_type() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(ProjectType::detect(dir.path()), ProjectType::Generic);
+        std::fs::write(dir.path().join("pyproject.toml"), "").unwrap();
+        assert_eq!(ProjectType::detect(dir.path()), ProjectType::Python);
+        std::fs::write(dir.path().join("package.json"), "{}").unwrap();
+        assert_eq!(ProjectType::detect(dir.path()), ProjectType::Node);
+        std::fs::write(dir.path().join("Cargo.toml"), "").unwrap();
+        assert_eq!(ProjectType::detect(dir.path()), ProjectType::Rust);
+    }
+
+    #[test]
+    fn test_render_leaves_no_placeholders() {
+        let placeholder = regex_lite::Regex::new("@[A-Z_]+@").unwrap();
+        for project in [
+            ProjectType::Rust,
+            ProjectType::Node,
+            ProjectType::Python,
+            ProjectType::Generic,
+        ] {
+            for provider in crate::llm::PROVIDERS {
+                let workflow =
+                    render_release_workflow(project, provider, Path::new("CHANGELOG.md"));
+                assert!(
+                    !placeholder.is_match(&workflow),
+                    "{} / {}",
+                    project,
+                    provider
+                );
+                assert!(!workflow.contains("\n\n\n"));
+            }
+        }
+    }
+
+    #[test]
+    fn test_render_release_workflow() {
+        let workflow = render_release_workflow(
+            ProjectType::Rust,
+            Provider::Codex,
+            Path::new("./docs/CHANGELOG.md"),
+        );
+        assert!(workflow.contains("npm install -g @openai/codex"));
+        assert!(workflow.contains("CODEX_API_KEY: ${{ secrets.CODEX_API_KEY }}"));
+        assert!(workflow.contains("keryx --provider codex -o docs/CHANGELOG.md"));
+        assert!(workflow.contains("cargo set-version"));
+        assert!(workflow.contains("dtolnay/rust-toolchain@stable"));
+
+        let generic = render_release_workflow(
+            ProjectType::Generic,
+            Provider::Claude,
+            Path::new("CHANGELOG.md"),
+        );
+        assert!(generic.contains("ANTHROPIC_API_KEY"));
+        assert!(!generic.contains("Bump version"));
+
+        let api = render_release_workflow(
+            ProjectType::Generic,
+            Provider::OpenAiApi,
+            Path::new("CHANGELOG.md"),
+        );
+        assert!(api.contains("OPENAI_API_KEY: ${{ secrets.OPENAI_API_KEY }}"));
+        assert!(api.contains("keryx --provider openai-api"));
+        assert!(!api.contains("npm install"));
+    }
+
+    #[test]
+    fn test_write_workflow_needs_force_to_overwrite() {
+        let dir = TempDir::new().unwrap();
+        let path = write_workflow(dir.path(), "first", false).unwrap();
+        assert_eq!(path, dir.path().join(WORKFLOW_PATH));
+        assert!(matches!(
+            write_workflow(dir.path(), "second", false),
+            Err(ScaffoldError::AlreadyExists(_))
+        ));
+        write_workflow(dir.path(), "second", true).unwrap();
+        assert_eq!(std::fs::read_to_string(path).unwrap(), "second");
+    }
+}
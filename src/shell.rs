@@ -0,0 +1,211 @@
+//! `keryx shell`: craft a release step by step in one session.
+//!
+//! The commits and PRs of a range are collected once, and the draft entries
+//! stay in memory between commands, so entries can be generated, edited,
+//! re-verified and finally written or shipped without collecting or
+//! generating again. This module parses the session's commands; the session
+//! itself runs in the binary.
+
+use std::str::FromStr;
+
+use semver::Version;
+
+/// Commands of the session, one per line.
+pub const HELP: &str = "\
+range [FROM] [TO]     Collect commits and PRs (default: latest tag to HEAD)
+status                Show the range, version and draft
+generate              Draft entries with the LLM and verify them
+entries               List the draft entries
+edit N TEXT           Replace the description of entry N
+move N CATEGORY       Move entry N to another category
+drop N                Remove entry N
+refine N INSTRUCTION  Rewrite entry N with the LLM
+verify                Check the current entries against the codebase again
+version [VERSION]     Show or set the release version
+preview               Show the section as it would be written
+write                 Write the section to the changelog
+ship [ARGS]           Write, then run `keryx ship` for this version
+help                  Show this list
+quit                  End the session (also `exit` or Ctrl-D)";
+
+/// A parsed session command. Entry numbers are 1-based, as listed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShellCommand {
+    Range {
+        from: Option<String>,
+        to: Option<String>,
+    },
+    Status,
+    Generate,
+    Entries,
+    Edit {
+        entry: usize,
+        description: String,
+    },
+    Move {
+        entry: usize,
+        category: String,
+    },
+    Drop(usize),
+    Refine {
+        entry: usize,
+        instruction: String,
+    },
+    Verify,
+    Version(Option<Version>),
+    Preview,
+    Write,
+    /// Extra arguments for `keryx ship`.
+    Ship(Vec<String>),
+    Help,
+    Quit,
+}
+
+impl FromStr for ShellCommand {
+    type Err = String;
+
+    fn from_str(line: &str) -> Result<Self, Self::Err> {
+        let line = line.trim();
+        let (name, rest) = line
+            .split_once(char::is_whitespace)
+            .map_or((line, ""), |(name, rest)| (name, rest.trim()));
+        let args: Vec<&str> = rest.split_whitespace().collect();
+        let no_args = |command: ShellCommand| {
+            if args.is_empty() {
+                Ok(command)
+            } else {
+                Err(format!("`{}` takes no arguments", name))
+            }
+        };
+
+        match name.to_lowercase().as_str() {
+            "range" => match args.as_slice() {
+                [] => Ok(Self::Range {
+                    from: None,
+                    to: None,
+                }),
+                [from] => Ok(Self::Range {
+                    from: Some(from.to_string()),
+                    to: None,
+                }),
+                [from, to] => Ok(Self::Range {
+                    from: Some(from.to_string()),
+                    to: Some(to.to_string()),
+                }),
+                _ => Err("usage: range [FROM] [TO]".to_string()),
+            },
+            "status" => no_args(Self::Status),
+            "generate" => no_args(Self::Generate),
+            "entries" | "ls" => no_args(Self::Entries),
+            "edit" => {
+                let (entry, text) = entry_and_text(rest, "edit N TEXT")?;
+                Ok(Self::Edit {
+                    entry,
+                    description: text,
+                })
+            }
+            "move" => match args.as_slice() {
+                [entry, category] => Ok(Self::Move {
+                    entry: entry_number(entry)?,
+                    category: category.to_string(),
+                }),
+                _ => Err("usage: move N CATEGORY".to_string()),
+            },
+            "drop" => match args.as_slice() {
+                [entry] => Ok(Self::Drop(entry_number(entry)?)),
+                _ => Err("usage: drop N".to_string()),
+            },
+            "refine" => {
+                let (entry, text) = entry_and_text(rest, "refine N INSTRUCTION")?;
+                Ok(Self::Refine {
+                    entry,
+                    instruction: text,
+                })
+            }
+            "verify" => no_args(Self::Verify),
+            "version" => match args.as_slice() {
+                [] => Ok(Self::Version(None)),
+                [version] => version
+                    .trim_start_matches('v')
+                    .parse()
+                    .map(|v| Self::Version(Some(v)))
+                    .map_err(|e| format!("invalid version \"{}\": {}", version, e)),
+                _ => Err("usage: version [VERSION]".to_string()),
+            },
+            "preview" => no_args(Self::Preview),
+            "write" => no_args(Self::Write),
+            "ship" => Ok(Self::Ship(args.iter().map(|a| a.to_string()).collect())),
+            "help" | "?" => Ok(Self::Help),
+            "quit" | "exit" => no_args(Self::Quit),
+            "" => Err("empty command".to_string()),
+            _ => Err(format!("unknown command `{}`; type `help`", name)),
+        }
+    }
+}
+
+/// A 1-based entry number.
+fn entry_number(arg: &str) -> Result<usize, String> {
+    arg.parse::<usize>()
+        .ok()
+        .filter(|n| *n > 0)
+        .ok_or_else(|| format!("\"{}\" is not an entry number", arg))
+}
+
+/// An entry number followed by free text.
+fn entry_and_text(rest: &str, usage: &str) -> Result<(usize, String), String> {
+    match rest.split_once(char::is_whitespace) {
+        Some((entry, text)) if !text.trim().is_empty() => {
+            Ok((entry_number(entry)?, text.trim().to_string()))
+        }
+        _ => Err(format!("usage: {}", usage)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_commands() {
+        assert_eq!(
+            "range v1.0.0".parse(),
+            Ok(ShellCommand::Range {
+                from: Some("v1.0.0".to_string()),
+                to: None
+            })
+        );
+        assert_eq!(
+            "  edit 2  Add dark mode to the settings  ".parse(),
+            Ok(ShellCommand::Edit {
+                entry: 2,
+                description: "Add dark mode to the settings".to_string()
+            })
+        );
+        assert_eq!(
+            "move 1 fixed".parse(),
+            Ok(ShellCommand::Move {
+                entry: 1,
+                category: "fixed".to_string()
+            })
+        );
+        assert_eq!(
+            "version v2.0.0".parse(),
+            Ok(ShellCommand::Version(Some(Version::new(2, 0, 0))))
+        );
+        assert_eq!(
+            "ship --dry-run".parse(),
+            Ok(ShellCommand::Ship(vec!["--dry-run".to_string()]))
+        );
+        assert_eq!("EXIT".parse(), Ok(ShellCommand::Quit));
+    }
+
+    #[test]
+    fn test_parse_errors() {
+        assert!("drop 0".parse::<ShellCommand>().is_err());
+        assert!("drop two".parse::<ShellCommand>().is_err());
+        assert!("refine 3".parse::<ShellCommand>().is_err());
+        assert!("generate now".parse::<ShellCommand>().is_err());
+        assert!("version next".parse::<ShellCommand>().is_err());
+        assert!("publish".parse::<ShellCommand>().is_err());
+    }
+}
@@ -0,0 +1,381 @@
+//! Two-phase releases with an approval gate (`ship --prepare` / `ship --approve`).
+//!
+//! `keryx ship --prepare` runs every step up to the release commit, but
+//! creates no tag and leaves the target branch alone. The commit is pushed
+//! to `release/<tag>` for review and carries the release in its trailers:
+//!
+//! ```text
+//! chore(release): v1.2.3
+//!
+//! Release-tag: v1.2.3
+//! Release-target: main
+//! Release-remote: origin
+//! Prepared-by: Alice <alice@example.com>
+//! ```
+//!
+//! A second person checks out that commit and runs `keryx ship --approve`,
+//! which tags and pushes it. Approval by the preparer is refused, so every
+//! release has been seen by two people (the four-eyes principle). The
+//! identities are the local git `user.name` / `user.email`.
+
+use std::path::PathBuf;
+
+use chrono::Utc;
+use dialoguer::Confirm;
+use git2::Repository;
+
+use crate::error::ShipError;
+use crate::git::{FooterKey, Footers};
+use crate::github::DEFAULT_REMOTE;
+use crate::integrations;
+use crate::journal::{Journal, OperationKind};
+use crate::summary::RunSummary;
+
+use super::assets;
+use super::executor;
+use super::github_release;
+use super::preflight::check_tag_exists;
+use super::publish::{self, PublishStep};
+use super::record::{Approver, ReleaseRecord, ReleaseRecordStore, ReleaseTimes};
+use super::schedule::PendingReleaseStore;
+
+/// Trailer naming the tag to create on approval.
+pub const RELEASE_TAG_TRAILER: &str = "Release-tag";
+
+/// Trailer naming the branch the approved release is pushed to.
+pub const RELEASE_TARGET_TRAILER: &str = "Release-target";
+
+/// Trailer naming the remote the release was prepared against; commits
+/// prepared without it are pushed to [`DEFAULT_REMOTE`].
+pub const RELEASE_REMOTE_TRAILER: &str = "Release-remote";
+
+/// Trailer naming who prepared the release.
+pub const PREPARED_BY_TRAILER: &str = "Prepared-by";
+
+/// Prefix of the review branch a prepared release is pushed to.
+pub const REVIEW_BRANCH_PREFIX: &str = "release/";
+
+/// A release read from the trailers of a prepared release commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PreparedRelease {
+    pub tag: String,
+    /// Branch the release is pushed to once approved.
+    pub target: String,
+    /// Remote holding the review branch and receiving the release.
+    pub remote: String,
+    /// `Name <email>` of the preparer.
+    pub prepared_by: String,
+}
+
+impl PreparedRelease {
+    /// Read the release from a commit message, if it was prepared.
+    pub fn from_message(message: &str) -> Option<Self> {
+        let footers = Footers::parse(message);
+        let get = |token: &str| {
+            footers
+                .first(&FooterKey::from(token))
+                .map(str::trim)
+                .filter(|v| !v.is_empty())
+                .map(String::from)
+        };
+        Some(Self {
+            tag: get(RELEASE_TAG_TRAILER)?,
+            target: get(RELEASE_TARGET_TRAILER)?,
+            remote: get(RELEASE_REMOTE_TRAILER).unwrap_or_else(|| DEFAULT_REMOTE.to_string()),
+            prepared_by: get(PREPARED_BY_TRAILER)?,
+        })
+    }
+
+    /// Trailer block appended to the release commit message.
+    pub fn trailers(&self) -> String {
+        format!(
+            "{}: {}\n{}: {}\n{}: {}\n{}: {}",
+            RELEASE_TAG_TRAILER,
+            self.tag,
+            RELEASE_TARGET_TRAILER,
+            self.target,
+            RELEASE_REMOTE_TRAILER,
+            self.remote,
+            PREPARED_BY_TRAILER,
+            self.prepared_by
+        )
+    }
+
+    /// Branch the prepared commit is pushed to for review.
+    pub fn review_branch(&self) -> String {
+        review_branch(&self.tag)
+    }
+}
+
+/// Review branch for the release `tag`.
+pub fn review_branch(tag: &str) -> String {
+    format!("{}{}", REVIEW_BRANCH_PREFIX, tag)
+}
+
+/// `Name <email>` of `approver`, with whatever parts are known.
+pub fn identity(approver: &Approver) -> String {
+    match (&approver.name, &approver.email) {
+        (Some(name), Some(email)) => format!("{} <{}>", name, email),
+        (Some(name), None) => name.clone(),
+        (None, Some(email)) => format!("<{}>", email),
+        (None, None) => "unknown".to_string(),
+    }
+}
+
+/// Whether two `Name <email>` identities are the same person: same email
+/// (case-insensitive) if both have one, otherwise the same name.
+pub fn same_person(a: &str, b: &str) -> bool {
+    fn email(identity: &str) -> Option<String> {
+        let start = identity.find('<')?;
+        let end = identity[start..].find('>')? + start;
+        Some(identity[start + 1..end].trim().to_lowercase()).filter(|e| !e.is_empty())
+    }
+    fn name(identity: &str) -> String {
+        identity
+            .split('<')
+            .next()
+            .unwrap_or_default()
+            .trim()
+            .to_lowercase()
+    }
+
+    match (email(a), email(b)) {
+        (Some(a), Some(b)) => a == b,
+        _ => !name(a).is_empty() && name(a) == name(b),
+    }
+}
+
+/// Markdown files changed by the release commit: the changelogs and their
+/// translations (the other changed files are version files).
+fn release_changelogs(repo: &Repository, commit: &git2::Commit) -> Vec<PathBuf> {
+    let Some(workdir) = repo.workdir() else {
+        return Vec::new();
+    };
+    let tree = commit.tree().ok();
+    let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+    let Ok(diff) = repo.diff_tree_to_tree(parent_tree.as_ref(), tree.as_ref(), None) else {
+        return Vec::new();
+    };
+    diff.deltas()
+        .filter_map(|delta| delta.new_file().path().map(|p| workdir.join(p)))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "md") && path.exists())
+        .collect()
+}
+
+/// Approve the prepared release at `HEAD`: tag it, push it to its target
+/// branch, and run the publish steps.
+pub async fn run_approve(
+    dry_run: bool,
+    publish_steps: Vec<PublishStep>,
+    release_assets: Vec<String>,
+    github_release: bool,
+) -> Result<(), ShipError> {
+    let started_at = Utc::now();
+    let repo = Repository::open(".")
+        .map_err(|e| ShipError::GitFailed(format!("Not a git repository: {}", e)))?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
+
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|head| head.peel_to_commit().ok())
+        .ok_or_else(|| ShipError::GitFailed("Cannot resolve HEAD".into()))?;
+    let prepared = PreparedRelease::from_message(head.message().unwrap_or_default())
+        .ok_or_else(|| ShipError::NotPrepared(head.id().to_string()))?;
+    let approver = Approver::local(&repo);
+    let approved_by = identity(&approver);
+
+    println!("Prepared release:");
+    println!("  Tag:         {}", prepared.tag);
+    println!("  Commit:      {}", head.summary().unwrap_or_default());
+    println!("  Prepared by: {}", prepared.prepared_by);
+    println!("  Approver:    {}", approved_by);
+    println!("  Push to:     {}/{}", prepared.remote, prepared.target);
+
+    if same_person(&prepared.prepared_by, &approved_by) {
+        return Err(ShipError::SelfApproval {
+            tag: prepared.tag,
+            preparer: prepared.prepared_by,
+        });
+    }
+    if check_tag_exists(&repo, &prepared.tag)? {
+        return Err(ShipError::TagAlreadyExists(prepared.tag));
+    }
+    publish::check_steps(&publish_steps, workdir)?;
+    assets::check_assets(&release_assets, workdir)?;
+
+    println!();
+    if dry_run {
+        println!("Dry run complete. No changes made.");
+        return Ok(());
+    }
+    let confirmed = Confirm::new()
+        .with_prompt(format!("Approve and ship {}?", prepared.tag))
+        .default(false)
+        .interact()
+        .map_err(|_| ShipError::Cancelled)?;
+    if !confirmed {
+        return Err(ShipError::Cancelled);
+    }
+    let approved_at = Utc::now();
+
+    let journal = Journal::for_repo(workdir, "ship");
+    executor::create_tag(&prepared.tag, Some(&approved_by))?;
+    journal.record(
+        journal
+            .entry(OperationKind::TagCreated)
+            .arg(&prepared.tag)
+            .detail(format!("approved by {}", approved_by)),
+    );
+    println!("  [DONE] Created tag: {}", prepared.tag);
+
+    let records = ReleaseRecordStore::for_repo(workdir);
+    let changelogs = release_changelogs(&repo, &head);
+    let times = ReleaseTimes {
+        started_at,
+        approved_at,
+        tagged_at: Utc::now(),
+    };
+    let saved = ReleaseRecord::new(
+        &repo,
+        prepared.tag.strip_prefix('v').unwrap_or(&prepared.tag),
+        &prepared.tag,
+        &head.id().to_string(),
+        &changelogs,
+        &RunSummary::new("ship"),
+        times,
+    )
+    .map(|record| ReleaseRecord {
+        prepared_by: Some(prepared.prepared_by.clone()),
+        ..record
+    })
+    .and_then(|record| records.save(&record));
+    if let Err(e) = saved {
+        eprintln!("  [WARN] {}", e);
+    }
+
+    if let Err(e) = executor::push_with_tags(&prepared.remote, &prepared.target).await {
+        eprintln!("  [FAIL] {}", e);
+        match executor::rollback(&prepared.tag, false) {
+            Ok(()) => {
+                eprintln!("  [DONE] Deleted tag {}", prepared.tag);
+                if let Err(e) = records.remove(&prepared.tag) {
+                    eprintln!("  [WARN] {}", e);
+                }
+            }
+            Err(rollback_err) => eprintln!("  [FAIL] Rollback failed: {}", rollback_err),
+        }
+        return Err(e);
+    }
+    journal.record(
+        journal
+            .entry(OperationKind::Pushed)
+            .arg(&prepared.remote)
+            .arg(&prepared.target)
+            .arg(&prepared.tag),
+    );
+    println!("  [DONE] Pushed to {}/{}", prepared.remote, prepared.target);
+    super::mark_release_pushed(&records, &prepared.tag);
+
+    if let Err(e) = executor::delete_remote_branch(&prepared.remote, &prepared.review_branch()) {
+        eprintln!("  [WARN] {}", e);
+    }
+
+    // The preparer's clone tracks the release until it is approved
+    let store = PendingReleaseStore::for_repo(workdir);
+    if store
+        .load()?
+        .is_some_and(|pending| pending.tag == prepared.tag)
+    {
+        store.clear()?;
+    }
+
+    println!();
+    println!("Release {} shipped!", prepared.tag);
+    if github_release {
+        github_release::create_for_tag(&repo, &prepared.remote, workdir, &prepared.tag, &journal)
+            .await;
+    }
+    publish::run_steps(&publish_steps, workdir, &prepared.tag, &journal)?;
+    assets::upload_to_remote(
+        &repo,
+        &prepared.remote,
+        workdir,
+        &prepared.tag,
+        &release_assets,
+        &journal,
+    )
+    .await?;
+    integrations::update_released_issues(&repo, workdir, &prepared.tag, &journal).await;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn prepared() -> PreparedRelease {
+        PreparedRelease {
+            tag: "v1.2.3".to_string(),
+            target: "main".to_string(),
+            remote: "upstream".to_string(),
+            prepared_by: "Alice <alice@example.com>".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_trailers_round_trip() {
+        let message = format!("chore(release): v1.2.3\n\n{}\n", prepared().trailers());
+        assert_eq!(PreparedRelease::from_message(&message), Some(prepared()));
+        assert_eq!(prepared().review_branch(), "release/v1.2.3");
+    }
+
+    #[test]
+    fn test_release_without_remote_trailer_uses_origin() {
+        let message = "chore(release): v1.2.3\n\nRelease-tag: v1.2.3\nRelease-target: main\nPrepared-by: Alice <alice@example.com>\n";
+        let prepared = PreparedRelease::from_message(message).unwrap();
+        assert_eq!(prepared.remote, DEFAULT_REMOTE);
+    }
+
+    #[test]
+    fn test_unprepared_commit_is_not_a_release() {
+        assert_eq!(
+            PreparedRelease::from_message("chore(release): v1.2.3"),
+            None
+        );
+        assert_eq!(
+            PreparedRelease::from_message("chore(release): v1.2.3\n\nRelease-tag: v1.2.3\n"),
+            None
+        );
+    }
+
+    #[test]
+    fn test_same_person() {
+        assert!(same_person(
+            "Alice <alice@example.com>",
+            "alice s. <ALICE@example.com>"
+        ));
+        assert!(!same_person(
+            "Alice <alice@example.com>",
+            "Alice <alice@other.org>"
+        ));
+        assert!(same_person("Alice", "alice <alice@example.com>"));
+        assert!(!same_person("unknown", "Bob"));
+    }
+
+    #[test]
+    fn test_identity() {
+        let approver = Approver {
+            name: Some("Bob".to_string()),
+            email: Some("bob@example.com".to_string()),
+        };
+        assert_eq!(identity(&approver), "Bob <bob@example.com>");
+        let approver = Approver {
+            name: None,
+            email: None,
+        };
+        assert_eq!(identity(&approver), "unknown");
+    }
+}
@@ -0,0 +1,620 @@
+//! Release asset upload after a successful ship.
+//!
+//! Assets (built binaries, checksums, ...) are selected with glob patterns
+//! relative to the project root and uploaded to the forge release for the
+//! pushed tag. Patterns are expanded during preflight so a missing artifact
+//! stops the release before anything is committed; the upload itself runs
+//! after the push, one file at a time, with retries.
+//!
+//! Forges are abstracted behind [`ReleaseForge`]; [`GitHubForge`] is the only
+//! implementation today.
+
+use std::path::{Path, PathBuf};
+
+use async_trait::async_trait;
+use git2::Repository;
+use octocrab::Octocrab;
+use tracing::warn;
+
+use crate::error::{ReleaseAssetError, ShipError};
+use crate::github::prs::parse_github_remote;
+use crate::github::{ensure_writable, shared_client};
+use crate::journal::{Journal, OperationKind};
+use crate::llm::retry::retry_with_backoff;
+use crate::status;
+
+/// Environment variable listing asset patterns when no flag is given.
+const RELEASE_ASSETS_ENV_VAR: &str = "KERYX_RELEASE_ASSETS";
+
+/// A forge that hosts releases with downloadable assets.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait ReleaseForge: Send + Sync {
+    /// Forge name for messages (e.g. "GitHub").
+    fn name(&self) -> &'static str;
+
+    /// Id of the release for `tag`, or `None` if there is no release yet.
+    async fn find_release(&self, tag: &str) -> Result<Option<u64>, ReleaseAssetError>;
+
+    /// Publish a release for the pushed `tag`; returns its URL.
+    async fn create_release(
+        &self,
+        tag: &str,
+        name: &str,
+        body: &str,
+        prerelease: bool,
+    ) -> Result<String, ReleaseAssetError>;
+
+    /// Upload one asset to the release `release_id`.
+    async fn upload_asset(
+        &self,
+        release_id: u64,
+        name: &str,
+        content: Vec<u8>,
+    ) -> Result<(), ReleaseAssetError>;
+}
+
+/// GitHub Releases of one repository.
+pub struct GitHubForge<'a> {
+    octocrab: &'a Octocrab,
+    owner: String,
+    repo: String,
+}
+
+impl<'a> GitHubForge<'a> {
+    pub fn new(octocrab: &'a Octocrab, owner: &str, repo: &str) -> Self {
+        Self {
+            octocrab,
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl ReleaseForge for GitHubForge<'_> {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    async fn find_release(&self, tag: &str) -> Result<Option<u64>, ReleaseAssetError> {
+        match self
+            .octocrab
+            .repos(&self.owner, &self.repo)
+            .releases()
+            .get_by_tag(tag)
+            .await
+        {
+            Ok(release) => Ok(Some(release.id.into_inner())),
+            // Same detection as PR listing: octocrab surfaces 404s as "Not Found"
+            Err(e)
+                if e.to_string().contains("Not Found")
+                    || format!("{:?}", e).contains("Not Found") =>
+            {
+                Ok(None)
+            }
+            Err(e) => Err(ReleaseAssetError::Api(e.to_string())),
+        }
+    }
+
+    async fn create_release(
+        &self,
+        tag: &str,
+        name: &str,
+        body: &str,
+        prerelease: bool,
+    ) -> Result<String, ReleaseAssetError> {
+        ensure_writable("create a GitHub release")
+            .map_err(|e| ReleaseAssetError::Api(e.to_string()))?;
+        self.octocrab
+            .repos(&self.owner, &self.repo)
+            .releases()
+            .create(tag)
+            .name(name)
+            .body(body)
+            .prerelease(prerelease)
+            .send()
+            .await
+            .map(|release| release.html_url.to_string())
+            .map_err(|e| ReleaseAssetError::Api(e.to_string()))
+    }
+
+    async fn upload_asset(
+        &self,
+        release_id: u64,
+        name: &str,
+        content: Vec<u8>,
+    ) -> Result<(), ReleaseAssetError> {
+        ensure_writable("upload a release asset").map_err(|e| ReleaseAssetError::UploadFailed {
+            name: name.to_string(),
+            reason: e.to_string(),
+        })?;
+        self.octocrab
+            .repos(&self.owner, &self.repo)
+            .releases()
+            .upload_asset(release_id, name, content.into())
+            .send()
+            .await
+            .map(|_| ())
+            .map_err(|e| ReleaseAssetError::UploadFailed {
+                name: name.to_string(),
+                reason: e.to_string(),
+            })
+    }
+}
+
+/// Use the patterns from the CLI, falling back to `KERYX_RELEASE_ASSETS`.
+pub fn patterns_from_flags_or_env(patterns: Vec<String>) -> Vec<String> {
+    if !patterns.is_empty() {
+        return patterns;
+    }
+    match std::env::var(RELEASE_ASSETS_ENV_VAR) {
+        Ok(value) => value
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(String::from)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Expand every pattern under `root`.
+///
+/// Fails if a pattern matches nothing or if two files would be uploaded under
+/// the same name (assets are named after the file, without directories).
+pub fn expand_assets(root: &Path, patterns: &[String]) -> Result<Vec<PathBuf>, ReleaseAssetError> {
+    let mut files: Vec<PathBuf> = Vec::new();
+    for pattern in patterns {
+        let matched = expand_pattern(root, pattern)?;
+        if matched.is_empty() {
+            return Err(ReleaseAssetError::NoMatches(pattern.clone()));
+        }
+        for path in matched {
+            if !files.contains(&path) {
+                files.push(path);
+            }
+        }
+    }
+
+    let mut names: Vec<&str> = Vec::with_capacity(files.len());
+    for path in &files {
+        let name = asset_name(path);
+        if names.contains(&name) {
+            return Err(ReleaseAssetError::DuplicateName(name.to_string()));
+        }
+        names.push(name);
+    }
+    Ok(files)
+}
+
+/// Files under `root` matching `pattern`, sorted.
+///
+/// `*` and `?` match within one path segment; a `**` segment matches any
+/// number of directories. Hidden entries only match segments that start
+/// with a dot.
+pub fn expand_pattern(root: &Path, pattern: &str) -> Result<Vec<PathBuf>, ReleaseAssetError> {
+    let normalized = pattern.replace('\\', "/");
+    let (base, rest) = match normalized.strip_prefix('/') {
+        Some(rest) => (PathBuf::from("/"), rest.to_string()),
+        None => (root.to_path_buf(), normalized.clone()),
+    };
+    let segments: Vec<&str> = rest
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    if segments.is_empty() || segments.last() == Some(&"**") {
+        return Err(ReleaseAssetError::InvalidPattern(pattern.to_string()));
+    }
+
+    let mut matches = Vec::new();
+    walk(&base, &segments, &mut matches);
+    matches.sort();
+    matches.dedup();
+    Ok(matches)
+}
+
+fn walk(dir: &Path, segments: &[&str], out: &mut Vec<PathBuf>) {
+    let Some((segment, rest)) = segments.split_first() else {
+        return;
+    };
+
+    if *segment == "**" {
+        walk(dir, rest, out);
+        for subdir in read_dir_sorted(dir).into_iter().filter(|p| p.is_dir()) {
+            if !is_hidden(&subdir) {
+                walk(&subdir, segments, out);
+            }
+        }
+        return;
+    }
+
+    let candidates: Vec<PathBuf> = if has_wildcard(segment) {
+        read_dir_sorted(dir)
+            .into_iter()
+            .filter(|path| {
+                let name = path
+                    .file_name()
+                    .and_then(|n| n.to_str())
+                    .unwrap_or_default();
+                (segment.starts_with('.') || !name.starts_with('.'))
+                    && wildcard_match(segment, name)
+            })
+            .collect()
+    } else {
+        vec![dir.join(segment)]
+    };
+
+    for path in candidates {
+        if rest.is_empty() {
+            if path.is_file() {
+                out.push(path);
+            }
+        } else if path.is_dir() {
+            walk(&path, rest, out);
+        }
+    }
+}
+
+fn read_dir_sorted(dir: &Path) -> Vec<PathBuf> {
+    let mut entries: Vec<PathBuf> = match std::fs::read_dir(dir) {
+        Ok(entries) => entries.filter_map(Result::ok).map(|e| e.path()).collect(),
+        Err(_) => Vec::new(),
+    };
+    entries.sort();
+    entries
+}
+
+fn is_hidden(path: &Path) -> bool {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .is_some_and(|n| n.starts_with('.'))
+}
+
+fn has_wildcard(segment: &str) -> bool {
+    segment.contains(['*', '?'])
+}
+
+/// Match `text` against a segment pattern with `*` (any run) and `?` (one char).
+pub(crate) fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        if p < pattern.len() && (pattern[p] == '?' || pattern[p] == text[t]) {
+            p += 1;
+            t += 1;
+        } else if p < pattern.len() && pattern[p] == '*' {
+            backtrack = Some((p, t));
+            p += 1;
+        } else if let Some((star, matched)) = backtrack {
+            p = star + 1;
+            t = matched + 1;
+            backtrack = Some((star, matched + 1));
+        } else {
+            return false;
+        }
+    }
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+/// Asset name for `path` (its file name).
+fn asset_name(path: &Path) -> &str {
+    path.file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or_default()
+}
+
+fn format_size(bytes: usize) -> String {
+    const UNITS: [&str; 4] = ["B", "KB", "MB", "GB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// Preflight: make sure every asset pattern matches before releasing.
+pub fn check_assets(patterns: &[String], root: &Path) -> Result<(), ShipError> {
+    if patterns.is_empty() {
+        return Ok(());
+    }
+    let files = expand_assets(root, patterns)?;
+    status!("  [PASS] {} release asset(s) found", files.len());
+    Ok(())
+}
+
+/// Upload the assets matching `patterns` to the GitHub release for `tag`
+/// on `remote`. Nothing is rolled back if an upload fails.
+pub async fn upload_to_remote(
+    repo: &Repository,
+    remote: &str,
+    root: &Path,
+    tag: &str,
+    patterns: &[String],
+    journal: &Journal,
+) -> Result<(), ShipError> {
+    if patterns.is_empty() {
+        return Ok(());
+    }
+
+    status!();
+    status!("Release assets:");
+    let files = expand_assets(root, patterns)?;
+    let forge = github_forge(repo, remote).await?;
+
+    if let Err(e) = upload_assets(&forge, tag, &files, journal).await {
+        eprintln!("  [FAIL] {}", e);
+        eprintln!();
+        eprintln!(
+            "{} is pushed and stays in place. Upload the remaining assets manually.",
+            tag
+        );
+        return Err(e.into());
+    }
+    Ok(())
+}
+
+/// The GitHub releases of the repository `remote` points to.
+pub async fn github_forge(
+    repo: &Repository,
+    remote: &str,
+) -> Result<GitHubForge<'static>, ReleaseAssetError> {
+    let url = repo
+        .find_remote(remote)
+        .ok()
+        .and_then(|r| r.url().map(String::from))
+        .ok_or_else(|| ReleaseAssetError::UnsupportedRemote(remote.to_string()))?;
+    let (owner, repo_name) = parse_github_remote(&url)
+        .map_err(|_| ReleaseAssetError::UnsupportedRemote(remote.to_string()))?;
+    let client = shared_client()
+        .await
+        .map_err(|e| ReleaseAssetError::Api(e.to_string()))?;
+    Ok(GitHubForge::new(client.octocrab(), &owner, &repo_name))
+}
+
+/// Upload `files` to the release for `tag`, printing one line per file.
+///
+/// Looking up the release and each upload are retried with backoff. Every
+/// uploaded asset is journaled; returns their names.
+pub async fn upload_assets<F: ReleaseForge + ?Sized>(
+    forge: &F,
+    tag: &str,
+    files: &[PathBuf],
+    journal: &Journal,
+) -> Result<Vec<String>, ReleaseAssetError> {
+    let release_id = retry_with_backoff(
+        || forge.find_release(tag),
+        |e| ReleaseAssetError::RetriesExhausted(Box::new(e)),
+    )
+    .await?
+    .ok_or_else(|| ReleaseAssetError::ReleaseNotFound {
+        forge: forge.name(),
+        tag: tag.to_string(),
+    })?;
+
+    let mut uploaded = Vec::with_capacity(files.len());
+    for (i, path) in files.iter().enumerate() {
+        let name = asset_name(path);
+        let content = std::fs::read(path).map_err(|source| ReleaseAssetError::ReadFailed {
+            path: path.clone(),
+            source,
+        })?;
+        status!(
+            "  [UPLOAD] ({}/{}) {} ({})",
+            i + 1,
+            files.len(),
+            name,
+            format_size(content.len())
+        );
+
+        let mut attempt = 0;
+        retry_with_backoff(
+            || {
+                attempt += 1;
+                if attempt > 1 {
+                    warn!("Retrying upload of {} (attempt {})", name, attempt);
+                }
+                forge.upload_asset(release_id, name, content.clone())
+            },
+            |e| ReleaseAssetError::RetriesExhausted(Box::new(e)),
+        )
+        .await?;
+        journal.record(
+            journal
+                .entry(OperationKind::AssetUploaded)
+                .arg(name)
+                .arg(tag),
+        );
+        status!("  [DONE] Uploaded {}", name);
+        uploaded.push(name.to_string());
+    }
+    Ok(uploaded)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    fn tree(files: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for file in files {
+            let path = dir.path().join(file);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, file.as_bytes()).unwrap();
+        }
+        dir
+    }
+
+    fn relative(root: &Path, paths: Vec<PathBuf>) -> Vec<String> {
+        paths
+            .into_iter()
+            .map(|p| p.strip_prefix(root).unwrap().display().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_wildcard_match() {
+        assert!(wildcard_match("*.tar.gz", "keryx-x86_64.tar.gz"));
+        assert!(wildcard_match("keryx-?.zip", "keryx-1.zip"));
+        assert!(wildcard_match("*", "anything"));
+        assert!(wildcard_match("a*b*c", "aXXbYYc"));
+        assert!(!wildcard_match("*.zip", "keryx.tar.gz"));
+        assert!(!wildcard_match("keryx-?.zip", "keryx-10.zip"));
+    }
+
+    #[test]
+    fn test_expand_pattern() {
+        let dir = tree(&[
+            "dist/keryx-linux.tar.gz",
+            "dist/keryx-macos.tar.gz",
+            "dist/SHA256SUMS",
+            "dist/nested/keryx-windows.zip",
+            "dist/.hidden.tar.gz",
+        ]);
+        let root = dir.path();
+
+        let matched = expand_pattern(root, "dist/*.tar.gz").unwrap();
+        assert_eq!(
+            relative(root, matched),
+            vec!["dist/keryx-linux.tar.gz", "dist/keryx-macos.tar.gz"]
+        );
+
+        let matched = expand_pattern(root, "./dist/SHA256SUMS").unwrap();
+        assert_eq!(relative(root, matched), vec!["dist/SHA256SUMS"]);
+
+        let matched = expand_pattern(root, "**/*.zip").unwrap();
+        assert_eq!(
+            relative(root, matched),
+            vec!["dist/nested/keryx-windows.zip"]
+        );
+
+        assert!(expand_pattern(root, "dist/**").is_err());
+        assert!(expand_pattern(root, "build/*").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_expand_assets_rejects_empty_and_duplicate_names() {
+        let dir = tree(&["dist/a/keryx.zip", "dist/b/keryx.zip", "dist/SHA256SUMS"]);
+        let root = dir.path();
+
+        let err = expand_assets(root, &["dist/*.tar.gz".to_string()]).unwrap_err();
+        assert!(matches!(err, ReleaseAssetError::NoMatches(p) if p == "dist/*.tar.gz"));
+
+        let err = expand_assets(root, &["dist/*/keryx.zip".to_string()]).unwrap_err();
+        assert!(matches!(err, ReleaseAssetError::DuplicateName(n) if n == "keryx.zip"));
+
+        let files = expand_assets(
+            root,
+            &["dist/SHA256SUMS".to_string(), "dist/*SUMS".to_string()],
+        )
+        .unwrap();
+        assert_eq!(files.len(), 1);
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MB");
+    }
+
+    #[tokio::test(start_paused = true)]
+    async fn test_upload_assets_retries_failed_upload() {
+        let dir = tree(&["dist/keryx.zip", "dist/SHA256SUMS"]);
+        let files = expand_assets(dir.path(), &["dist/*".to_string()]).unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+
+        let mut forge = MockReleaseForge::new();
+        forge.expect_name().return_const("GitHub");
+        forge
+            .expect_find_release()
+            .withf(|tag| tag == "v1.0.0")
+            .returning(|_| Ok(Some(42)));
+        let mut failures = 1;
+        forge
+            .expect_upload_asset()
+            .withf(|id, name, _| *id == 42 && name == "SHA256SUMS")
+            .times(1)
+            .returning(|_, _, _| Ok(()));
+        forge
+            .expect_upload_asset()
+            .withf(|id, name, content| {
+                *id == 42 && name == "keryx.zip" && content == b"dist/keryx.zip"
+            })
+            .times(2)
+            .returning(move |_, name, _| {
+                if failures > 0 {
+                    failures -= 1;
+                    Err(ReleaseAssetError::UploadFailed {
+                        name: name.to_string(),
+                        reason: "connection reset".to_string(),
+                    })
+                } else {
+                    Ok(())
+                }
+            });
+
+        let uploaded = upload_assets(&forge, "v1.0.0", &files, &journal)
+            .await
+            .unwrap();
+        assert_eq!(uploaded, vec!["SHA256SUMS", "keryx.zip"]);
+
+        let entries = journal.read_all().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[1].operation, OperationKind::AssetUploaded);
+        assert_eq!(entries[1].args, vec!["keryx.zip", "v1.0.0"]);
+    }
+
+    #[tokio::test]
+    async fn test_upload_assets_requires_release() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        let mut forge = MockReleaseForge::new();
+        forge.expect_name().return_const("GitHub");
+        forge.expect_find_release().returning(|_| Ok(None));
+        forge.expect_upload_asset().never();
+
+        let err = upload_assets(
+            &forge,
+            "v1.0.0",
+            &[PathBuf::from("dist/keryx.zip")],
+            &journal,
+        )
+        .await
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "No GitHub release exists for v1.0.0; create it before uploading assets"
+        );
+    }
+
+    #[test]
+    #[serial]
+    fn test_patterns_from_env() {
+        temp_env::with_var(
+            RELEASE_ASSETS_ENV_VAR,
+            Some("dist/*.tar.gz, ,dist/SHA256SUMS"),
+            || {
+                assert_eq!(
+                    patterns_from_flags_or_env(Vec::new()),
+                    vec!["dist/*.tar.gz", "dist/SHA256SUMS"]
+                );
+                assert_eq!(
+                    patterns_from_flags_or_env(vec!["out/*".to_string()]),
+                    vec!["out/*"]
+                );
+            },
+        );
+    }
+}
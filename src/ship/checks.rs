@@ -0,0 +1,545 @@
+//! Waiting for CI before tagging (`keryx ship --require-checks`).
+//!
+//! With required checks the release commit is pushed to the branch first and
+//! the tag is only created and pushed once the checks on that commit pass.
+//! The checks branch protection requires on the release branch are used when
+//! the token may read them; otherwise every check reported for the commit
+//! (check runs and commit statuses) must pass, and at least one must exist.
+//!
+//! Forges are abstracted behind [`CheckSource`]; [`GitHubChecks`] is the only
+//! implementation today.
+
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use git2::Repository;
+use octocrab::Octocrab;
+use serde::{Deserialize, Serialize};
+
+use crate::error::ChecksError;
+use crate::github::prs::parse_github_remote;
+use crate::github::shared_client;
+use crate::status;
+
+/// Default time to wait for checks to finish.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(30 * 60);
+
+/// Default time between two polls.
+pub const DEFAULT_INTERVAL: Duration = Duration::from_secs(15);
+
+/// Items requested per page.
+const PER_PAGE: usize = 100;
+
+/// How long to wait for required checks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RequiredChecks {
+    /// Give up (and leave the release untagged) after this long.
+    pub timeout: Duration,
+    /// Time between two polls.
+    pub interval: Duration,
+}
+
+impl Default for RequiredChecks {
+    fn default() -> Self {
+        Self {
+            timeout: DEFAULT_TIMEOUT,
+            interval: DEFAULT_INTERVAL,
+        }
+    }
+}
+
+/// State of a single check on a commit.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckState {
+    Pending,
+    Success,
+    Failure,
+}
+
+/// A check run or commit status reported for a commit.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckStatus {
+    /// Check run name or status context.
+    pub name: String,
+    pub state: CheckState,
+}
+
+impl CheckStatus {
+    pub fn new(name: &str, state: CheckState) -> Self {
+        Self {
+            name: name.to_string(),
+            state,
+        }
+    }
+}
+
+/// Where check results come from.
+#[cfg_attr(test, mockall::automock)]
+#[async_trait]
+pub trait CheckSource: Send + Sync {
+    /// Forge name for messages (e.g. "GitHub").
+    fn name(&self) -> &'static str;
+
+    /// Checks branch protection requires on `branch`, or `None` if they
+    /// cannot be read (no protection, or not allowed to see it).
+    async fn required_checks(&self, branch: &str) -> Result<Option<Vec<String>>, ChecksError>;
+
+    /// All checks reported for `commit`.
+    async fn commit_checks(&self, commit: &str) -> Result<Vec<CheckStatus>, ChecksError>;
+}
+
+/// Outcome of one poll.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Passed,
+    /// Names of the checks that have not finished (or not started).
+    Pending(Vec<String>),
+    /// Names of the failed checks.
+    Failed(Vec<String>),
+}
+
+/// Judge `checks` against the `required` check names.
+///
+/// Without required names every reported check counts, and a commit with no
+/// checks at all is pending (CI may not have picked it up yet). A check
+/// reported several times (re-runs, status updates) counts with its best
+/// state.
+pub fn evaluate(required: Option<&[String]>, checks: &[CheckStatus]) -> Verdict {
+    let names: Vec<String> = match required {
+        Some(required) if !required.is_empty() => required.to_vec(),
+        _ => {
+            let mut names: Vec<String> = Vec::new();
+            for check in checks {
+                if !names.contains(&check.name) {
+                    names.push(check.name.clone());
+                }
+            }
+            if names.is_empty() {
+                return Verdict::Pending(Vec::new());
+            }
+            names
+        }
+    };
+
+    let mut pending = Vec::new();
+    let mut failed = Vec::new();
+    for name in names {
+        let states: Vec<CheckState> = checks
+            .iter()
+            .filter(|c| c.name == name)
+            .map(|c| c.state)
+            .collect();
+        if states.contains(&CheckState::Success) {
+            continue;
+        }
+        if states.is_empty() || states.contains(&CheckState::Pending) {
+            pending.push(name);
+        } else {
+            failed.push(name);
+        }
+    }
+
+    if !failed.is_empty() {
+        Verdict::Failed(failed)
+    } else if !pending.is_empty() {
+        Verdict::Pending(pending)
+    } else {
+        Verdict::Passed
+    }
+}
+
+/// Poll `source` until the checks on `commit` pass, fail, or time out.
+pub async fn wait_for_checks(
+    source: &dyn CheckSource,
+    branch: &str,
+    commit: &str,
+    config: RequiredChecks,
+) -> Result<(), ChecksError> {
+    let required = source.required_checks(branch).await?;
+    match required.as_deref() {
+        Some(names) if !names.is_empty() => status!(
+            "  [WAIT] Waiting for required checks on {}: {}",
+            branch,
+            names.join(", ")
+        ),
+        _ => status!(
+            "  [WAIT] Waiting for all {} checks on {}",
+            source.name(),
+            short_sha(commit)
+        ),
+    }
+
+    let started = Instant::now();
+    let mut last_pending: Option<Vec<String>> = None;
+    loop {
+        let checks = source.commit_checks(commit).await?;
+        match evaluate(required.as_deref(), &checks) {
+            Verdict::Passed => {
+                status!("  [PASS] Checks passed on {}", short_sha(commit));
+                return Ok(());
+            }
+            Verdict::Failed(names) => return Err(ChecksError::Failed(names.join(", "))),
+            Verdict::Pending(names) => {
+                if started.elapsed() >= config.timeout {
+                    return Err(ChecksError::TimedOut {
+                        timeout: display_duration(config.timeout),
+                        pending: describe_pending(&names),
+                    });
+                }
+                if last_pending.as_ref() != Some(&names) {
+                    status!("         pending: {}", describe_pending(&names));
+                    last_pending = Some(names);
+                }
+                tokio::time::sleep(config.interval).await;
+            }
+        }
+    }
+}
+
+/// Wait for the checks on `commit` in the GitHub repository behind `remote`.
+pub async fn wait_on_remote(
+    repo: &Repository,
+    remote: &str,
+    branch: &str,
+    commit: &str,
+    config: RequiredChecks,
+) -> Result<(), ChecksError> {
+    let url = repo
+        .find_remote(remote)
+        .ok()
+        .and_then(|r| r.url().map(String::from))
+        .ok_or_else(|| ChecksError::UnsupportedRemote(remote.to_string()))?;
+    let (owner, repo_name) = parse_github_remote(&url)
+        .map_err(|_| ChecksError::UnsupportedRemote(remote.to_string()))?;
+    let client = shared_client()
+        .await
+        .map_err(|e| ChecksError::Api(e.to_string()))?;
+    let source = GitHubChecks::new(client.octocrab(), &owner, &repo_name);
+    wait_for_checks(&source, branch, commit, config).await
+}
+
+/// Preflight: checks can only be read from a GitHub remote.
+pub fn check_remote(repo: &Repository, remote: &str) -> Result<(), ChecksError> {
+    let is_github = repo
+        .find_remote(remote)
+        .ok()
+        .and_then(|r| r.url().map(String::from))
+        .is_some_and(|url| parse_github_remote(&url).is_ok());
+    if !is_github {
+        return Err(ChecksError::UnsupportedRemote(remote.to_string()));
+    }
+    status!("  [PASS] Remote '{}' reports CI checks", remote);
+    Ok(())
+}
+
+/// Parse a duration such as `90`, `90s`, `15m` or `1h` (bare numbers are seconds).
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: u64 = number
+        .parse()
+        .map_err(|_| format!("invalid duration '{}' (expected e.g. 90s, 15m, 1h)", value))?;
+    let seconds = match unit.trim() {
+        "s" => number,
+        "m" => number * 60,
+        "h" => number * 60 * 60,
+        _ => {
+            return Err(format!(
+                "invalid duration '{}' (expected e.g. 90s, 15m, 1h)",
+                value
+            ));
+        }
+    };
+    Ok(Duration::from_secs(seconds))
+}
+
+/// Render a duration the way [`parse_duration`] reads it.
+pub fn display_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    if seconds > 0 && seconds % 3600 == 0 {
+        format!("{}h", seconds / 3600)
+    } else if seconds > 0 && seconds % 60 == 0 {
+        format!("{}m", seconds / 60)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+fn describe_pending(names: &[String]) -> String {
+    if names.is_empty() {
+        "no checks reported yet".to_string()
+    } else {
+        names.join(", ")
+    }
+}
+
+fn short_sha(commit: &str) -> &str {
+    commit.get(..7).unwrap_or(commit)
+}
+
+/// Checks and statuses of one GitHub repository.
+pub struct GitHubChecks<'a> {
+    octocrab: &'a Octocrab,
+    owner: String,
+    repo: String,
+}
+
+impl<'a> GitHubChecks<'a> {
+    pub fn new(octocrab: &'a Octocrab, owner: &str, repo: &str) -> Self {
+        Self {
+            octocrab,
+            owner: owner.to_string(),
+            repo: repo.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct PageParams {
+    per_page: usize,
+}
+
+#[derive(Deserialize)]
+struct RequiredStatusChecks {
+    #[serde(default)]
+    contexts: Vec<String>,
+}
+
+#[derive(Deserialize)]
+struct CheckRunList {
+    #[serde(default)]
+    check_runs: Vec<CheckRun>,
+}
+
+#[derive(Deserialize)]
+struct CheckRun {
+    name: String,
+    status: String,
+    conclusion: Option<String>,
+}
+
+#[derive(Deserialize)]
+struct CombinedStatus {
+    #[serde(default)]
+    statuses: Vec<CommitStatus>,
+}
+
+#[derive(Deserialize)]
+struct CommitStatus {
+    context: String,
+    state: String,
+}
+
+/// State of a GitHub check run. Neutral and skipped runs do not block.
+fn check_run_state(run: &CheckRun) -> CheckState {
+    if run.status != "completed" {
+        return CheckState::Pending;
+    }
+    match run.conclusion.as_deref() {
+        Some("success" | "neutral" | "skipped") => CheckState::Success,
+        _ => CheckState::Failure,
+    }
+}
+
+/// State of a GitHub commit status.
+fn commit_status_state(status: &CommitStatus) -> CheckState {
+    match status.state.as_str() {
+        "success" => CheckState::Success,
+        "pending" => CheckState::Pending,
+        _ => CheckState::Failure,
+    }
+}
+
+#[async_trait]
+impl CheckSource for GitHubChecks<'_> {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    async fn required_checks(&self, branch: &str) -> Result<Option<Vec<String>>, ChecksError> {
+        let route = format!(
+            "/repos/{}/{}/branches/{}/protection/required_status_checks",
+            self.owner, self.repo, branch
+        );
+        match self
+            .octocrab
+            .get::<RequiredStatusChecks, _, ()>(&route, None)
+            .await
+        {
+            Ok(checks) => Ok(Some(checks.contexts)),
+            // Unprotected branch (404) or a token without admin rights (403)
+            Err(e) => {
+                let message = format!("{} {:?}", e, e);
+                if message.contains("Not Found")
+                    || message.contains("Forbidden")
+                    || message.contains("Resource not accessible")
+                {
+                    Ok(None)
+                } else {
+                    Err(ChecksError::Api(e.to_string()))
+                }
+            }
+        }
+    }
+
+    async fn commit_checks(&self, commit: &str) -> Result<Vec<CheckStatus>, ChecksError> {
+        let params = PageParams { per_page: PER_PAGE };
+        let runs: CheckRunList = self
+            .octocrab
+            .get(
+                format!(
+                    "/repos/{}/{}/commits/{}/check-runs",
+                    self.owner, self.repo, commit
+                ),
+                Some(&params),
+            )
+            .await
+            .map_err(|e| ChecksError::Api(e.to_string()))?;
+        let statuses: CombinedStatus = self
+            .octocrab
+            .get(
+                format!(
+                    "/repos/{}/{}/commits/{}/status",
+                    self.owner, self.repo, commit
+                ),
+                Some(&params),
+            )
+            .await
+            .map_err(|e| ChecksError::Api(e.to_string()))?;
+
+        let mut checks: Vec<CheckStatus> = runs
+            .check_runs
+            .iter()
+            .map(|run| CheckStatus::new(&run.name, check_run_state(run)))
+            .collect();
+        checks.extend(
+            statuses
+                .statuses
+                .iter()
+                .map(|status| CheckStatus::new(&status.context, commit_status_state(status))),
+        );
+        Ok(checks)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use CheckState::{Failure, Pending, Success};
+
+    fn names(names: &[&str]) -> Vec<String> {
+        names.iter().map(|n| n.to_string()).collect()
+    }
+
+    #[test]
+    fn test_evaluate_required_checks() {
+        let required = names(&["build", "test"]);
+        let checks = vec![
+            CheckStatus::new("build", Success),
+            CheckStatus::new("lint", Failure),
+        ];
+        // Failing checks that are not required do not block
+        assert_eq!(
+            evaluate(Some(&required), &checks),
+            Verdict::Pending(names(&["test"]))
+        );
+
+        let checks = vec![
+            CheckStatus::new("build", Success),
+            CheckStatus::new("test", Failure),
+            CheckStatus::new("test", Success),
+        ];
+        assert_eq!(evaluate(Some(&required), &checks), Verdict::Passed);
+
+        let checks = vec![
+            CheckStatus::new("build", Pending),
+            CheckStatus::new("test", Failure),
+        ];
+        assert_eq!(
+            evaluate(Some(&required), &checks),
+            Verdict::Failed(names(&["test"]))
+        );
+    }
+
+    #[test]
+    fn test_evaluate_without_required_checks_uses_all() {
+        assert_eq!(evaluate(None, &[]), Verdict::Pending(Vec::new()));
+        assert_eq!(
+            evaluate(None, &[CheckStatus::new("ci", Success)]),
+            Verdict::Passed
+        );
+        assert_eq!(
+            evaluate(
+                Some(&[]),
+                &[
+                    CheckStatus::new("ci", Success),
+                    CheckStatus::new("lint", Failure),
+                ]
+            ),
+            Verdict::Failed(names(&["lint"]))
+        );
+    }
+
+    #[test]
+    fn test_parse_and_display_duration() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("15m").unwrap(), Duration::from_secs(900));
+        assert_eq!(parse_duration("1h").unwrap(), Duration::from_secs(3600));
+        assert!(parse_duration("soon").is_err());
+        assert!(parse_duration("5d").is_err());
+        assert_eq!(display_duration(Duration::from_secs(1800)), "30m");
+        assert_eq!(display_duration(Duration::from_secs(45)), "45s");
+    }
+
+    #[test]
+    fn test_check_run_state() {
+        let run = |status: &str, conclusion: Option<&str>| CheckRun {
+            name: "ci".to_string(),
+            status: status.to_string(),
+            conclusion: conclusion.map(String::from),
+        };
+        assert_eq!(check_run_state(&run("in_progress", None)), Pending);
+        assert_eq!(check_run_state(&run("completed", Some("skipped"))), Success);
+        assert_eq!(
+            check_run_state(&run("completed", Some("timed_out"))),
+            Failure
+        );
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_checks_fails_on_failed_check() {
+        let mut source = MockCheckSource::new();
+        source.expect_name().return_const("GitHub");
+        source
+            .expect_required_checks()
+            .returning(|_| Ok(Some(vec!["test".to_string()])));
+        source
+            .expect_commit_checks()
+            .returning(|_| Ok(vec![CheckStatus::new("test", Failure)]));
+
+        let err = wait_for_checks(&source, "main", "abc1234", RequiredChecks::default())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChecksError::Failed(names) if names == "test"));
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_checks_times_out_while_pending() {
+        let mut source = MockCheckSource::new();
+        source.expect_name().return_const("GitHub");
+        source.expect_required_checks().returning(|_| Ok(None));
+        source.expect_commit_checks().returning(|_| Ok(Vec::new()));
+
+        let config = RequiredChecks {
+            timeout: Duration::ZERO,
+            interval: Duration::ZERO,
+        };
+        let err = wait_for_checks(&source, "main", "abc1234", config)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, ChecksError::TimedOut { .. }));
+    }
+}
@@ -1,64 +1,204 @@
 //! Git operations for the ship pipeline: commit, tag, push, and rollback.
 //!
-//! All operations use `std::process::Command` to shell out to the system `git`
-//! binary, inheriting the user's existing git config, SSH agent, and credential store.
+//! Operations shell out to the system `git` binary, inheriting the user's
+//! existing git config, SSH agent, and credential store. Pushes go through
+//! [`super::push`], which tries libgit2 first for branch-only pushes and
+//! pushes releases with `git push --atomic`.
 
+use std::env;
 use std::path::PathBuf;
 use std::process::Command;
+use std::sync::LazyLock;
+use std::time::Duration;
+
+use backoff::ExponentialBackoff;
+use backoff::backoff::Backoff;
+use regex_lite::Regex;
+use tracing::{trace, warn};
 
 use crate::error::ShipError;
 
+use super::push::{PushBackend, PushTarget, push};
+
+/// Environment variable setting how often a transient push failure is retried.
+const PUSH_RETRIES_ENV_VAR: &str = "KERYX_PUSH_RETRIES";
+
+/// Retries of a transient push failure before giving up.
+pub const DEFAULT_PUSH_RETRIES: u32 = 3;
+
+const PUSH_INITIAL_BACKOFF: Duration = Duration::from_secs(2);
+const PUSH_MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Push errors worth retrying: timeouts, dropped connections and server
+/// errors from smart HTTP. Rejections and authentication failures are not.
+static TRANSIENT_PUSH_ERROR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"(?i)timed out|timeout|connection reset|connection closed|connection refused|remote end hung up|early eof|unexpected disconnect|returned error: 5\d\d|HTTP 5\d\d",
+    )
+    .expect("Invalid transient push error regex - this is a bug")
+});
+
+/// Outcome of [`commit_release`].
+pub struct CommitResult {
+    pub commit_created: bool,
+}
+
 /// Stage files, create a release commit (if needed), and tag it.
 ///
 /// Steps:
 /// 1. `git add <files>` - stage only modified version/changelog files
-/// 2. `git commit -m "chore(release): vX.Y.Z"` - create release commit (skipped if no staged changes)
+/// 2. `git commit --only -m "chore(release): vX.Y.Z" -- <files>` - create release commit (skipped if the files are unchanged)
 /// 3. `git tag -a vX.Y.Z -m "Release vX.Y.Z"` - create annotated tag
-pub struct CommitResult {
-    pub commit_created: bool,
-}
-
 pub fn commit_and_tag(
     message: &str,
     tag_name: &str,
     files: &[PathBuf],
+) -> Result<CommitResult, ShipError> {
+    let result = commit_release(message, files, false)?;
+    create_tag(tag_name, None)?;
+    Ok(result)
+}
+
+/// Stage files and create the release commit. Without changes (or files,
+/// as in a tag-only release whose changelog already has the section) the
+/// commit is skipped unless `allow_empty` is set.
+pub fn commit_release(
+    message: &str,
+    files: &[PathBuf],
+    allow_empty: bool,
 ) -> Result<CommitResult, ShipError> {
     // 1. Stage files
     let file_args: Vec<&str> = files.iter().filter_map(|p| p.to_str()).collect();
-    if file_args.is_empty() {
-        return Err(ShipError::GitFailed("No files to stage".into()));
+    if !file_args.is_empty() {
+        let mut add_args = vec!["add"];
+        add_args.extend(&file_args);
+        run_git(&add_args, "stage files")?;
     }
 
-    let mut add_args = vec!["add"];
-    add_args.extend(file_args);
-
-    run_git(&add_args, "stage files")?;
-
-    // 2. Create commit (if there are staged changes)
-    let commit_created = if has_staged_changes()? {
-        run_git(&["commit", "-m", message], "create commit")?;
+    // 2. Create commit (if the release files changed). `--only` leaves out
+    // anything else the user had staged, e.g. with --allow-dirty
+    let commit_created = if !file_args.is_empty() && has_staged_changes(&file_args)? {
+        let mut commit_args = vec!["commit", "--only", "-m", message, "--"];
+        commit_args.extend(&file_args);
+        run_git(&commit_args, "create commit")?;
+        true
+    } else if allow_empty {
+        run_git(
+            &["commit", "--only", "--allow-empty", "-m", message],
+            "create commit",
+        )?;
         true
     } else {
         false
     };
 
-    // 3. Create annotated tag so --follow-tags will push it
-    let tag_message = format!("Release {}", tag_name);
-    run_git(&["tag", "-a", tag_name, "-m", &tag_message], "create tag")?;
-
     Ok(CommitResult { commit_created })
 }
 
-/// Push commits and tags atomically.
-pub fn push_with_tags(remote: &str, remote_branch: &str) -> Result<(), ShipError> {
-    let refspec = format!("HEAD:refs/heads/{}", remote_branch);
-    // Push with tags (atomic to avoid partial updates)
-    match run_git(
-        &["push", remote, &refspec, "--follow-tags", "--atomic"],
-        "push",
-    ) {
-        Ok(()) => Ok(()),
-        Err(e) => Err(ShipError::PushFailed(e.to_string())),
+/// Create the annotated release tag on HEAD, naming the approver of a
+/// two-phase release in the tag message.
+pub fn create_tag(tag_name: &str, approved_by: Option<&str>) -> Result<(), ShipError> {
+    // Annotated so --follow-tags will push it
+    let mut tag_message = format!("Release {}", tag_name);
+    if let Some(approver) = approved_by {
+        tag_message.push_str(&format!("\n\nApproved-by: {}", approver));
+    }
+    run_git(&["tag", "-a", tag_name, "-m", &tag_message], "create tag")
+}
+
+/// Push the release commit without any tags.
+pub async fn push_branch(remote: &str, remote_branch: &str) -> Result<(), ShipError> {
+    push_with_retry(
+        &PushTarget {
+            remote,
+            remote_branch,
+            tags: false,
+        },
+        PushBackend::from_env(),
+    )
+    .await
+}
+
+/// Delete `branch` on `remote`.
+pub fn delete_remote_branch(remote: &str, branch: &str) -> Result<(), ShipError> {
+    run_git(
+        &["push", remote, "--delete", branch],
+        "delete remote branch",
+    )
+}
+
+/// Push commits and every tag on `HEAD` in one atomic request, so the
+/// remote gets the whole release or none of it.
+///
+/// Always uses `git push --atomic`: libgit2 may update some refs and not
+/// others, which would leave the branch pushed without its tag.
+pub async fn push_with_tags(remote: &str, remote_branch: &str) -> Result<(), ShipError> {
+    push_with_retry(
+        &PushTarget {
+            remote,
+            remote_branch,
+            tags: true,
+        },
+        PushBackend::Cli,
+    )
+    .await
+}
+
+/// Push `target`, retrying transient failures with exponential backoff so
+/// a network blip does not trigger the caller's rollback.
+///
+/// A push that reached the remote before the connection dropped is up to
+/// date on the retry, so retrying is safe. The backoff waits without
+/// blocking the runtime.
+async fn push_with_retry(target: &PushTarget<'_>, backend: PushBackend) -> Result<(), ShipError> {
+    let retries = push_retries_from_env();
+    let mut backoff = ExponentialBackoff {
+        initial_interval: PUSH_INITIAL_BACKOFF,
+        max_interval: PUSH_MAX_BACKOFF,
+        max_elapsed_time: None,
+        ..Default::default()
+    };
+    let mut attempt = 0;
+    loop {
+        let error = match push(target, backend) {
+            Ok(()) => return Ok(()),
+            Err(e) => e,
+        };
+        if attempt >= retries || !is_transient_push_error(&error) {
+            return Err(ShipError::PushFailed(error));
+        }
+        attempt += 1;
+        let wait = backoff.next_backoff().unwrap_or(PUSH_MAX_BACKOFF);
+        println!(
+            "  [RETRY] Push failed with a transient error, retrying in {}s ({}/{})",
+            wait.as_secs(),
+            attempt,
+            retries
+        );
+        warn!("Transient push failure: {}", error);
+        tokio::time::sleep(wait).await;
+    }
+}
+
+/// Whether a failed push's error output looks like a transient network or
+/// server problem.
+pub fn is_transient_push_error(stderr: &str) -> bool {
+    TRANSIENT_PUSH_ERROR_RE.is_match(stderr)
+}
+
+/// Read `KERYX_PUSH_RETRIES`; `0` disables retrying.
+///
+/// Logs a warning and uses the default if the variable is set but invalid.
+fn push_retries_from_env() -> u32 {
+    match env::var(PUSH_RETRIES_ENV_VAR) {
+        Ok(value) if !value.trim().is_empty() => value.trim().parse().unwrap_or_else(|_| {
+            warn!(
+                "Ignoring {}: '{}' is not a non-negative number",
+                PUSH_RETRIES_ENV_VAR, value
+            );
+            DEFAULT_PUSH_RETRIES
+        }),
+        _ => DEFAULT_PUSH_RETRIES,
     }
 }
 
@@ -66,12 +206,20 @@ pub fn push_with_tags(remote: &str, remote_branch: &str) -> Result<(), ShipError
 ///
 /// Uses `--soft` reset so the version bump changes stay staged.
 pub fn rollback(tag_name: &str, commit_created: bool) -> Result<(), ShipError> {
-    // 1. Delete local tag
-    if let Err(e) = run_git(&["tag", "-d", tag_name], "delete tag") {
-        return Err(ShipError::RollbackFailed(format!(
-            "Failed to delete tag {}: {}",
-            tag_name, e
-        )));
+    rollback_tags(&[tag_name.to_string()], commit_created)
+}
+
+/// Roll back a failed release with several tags (a release train): delete
+/// every local tag, then undo the release commit.
+pub fn rollback_tags(tag_names: &[String], commit_created: bool) -> Result<(), ShipError> {
+    // 1. Delete local tags
+    for tag_name in tag_names {
+        if let Err(e) = run_git(&["tag", "-d", tag_name], "delete tag") {
+            return Err(ShipError::RollbackFailed(format!(
+                "Failed to delete tag {}: {}",
+                tag_name, e
+            )));
+        }
     }
 
     // 2. Undo the release commit (keep changes staged) if we created one
@@ -87,10 +235,11 @@ pub fn rollback(tag_name: &str, commit_created: bool) -> Result<(), ShipError> {
     Ok(())
 }
 
-/// Return true if there are staged changes (git diff --cached --quiet).
-fn has_staged_changes() -> Result<bool, ShipError> {
+/// Return true if `paths` have staged changes (git diff --cached --quiet).
+fn has_staged_changes(paths: &[&str]) -> Result<bool, ShipError> {
     let output = Command::new("git")
-        .args(["diff", "--cached", "--quiet"])
+        .args(["diff", "--cached", "--quiet", "--"])
+        .args(paths)
         .output()
         .map_err(|e| {
             ShipError::GitFailed(format!("Failed to run git check for staged changes: {}", e))
@@ -113,6 +262,7 @@ fn has_staged_changes() -> Result<bool, ShipError> {
 
 /// Run a git command and return success or a descriptive error.
 fn run_git(args: &[&str], operation: &str) -> Result<(), ShipError> {
+    trace!("Running git {}", args.join(" "));
     let output = Command::new("git")
         .args(args)
         .output()
@@ -133,6 +283,7 @@ fn run_git(args: &[&str], operation: &str) -> Result<(), ShipError> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use serial_test::serial;
 
     #[test]
     fn test_run_git_version_succeeds() {
@@ -146,4 +297,55 @@ mod tests {
         let result = run_git(&["not-a-real-command"], "invalid");
         assert!(result.is_err());
     }
+
+    #[test]
+    fn test_transient_push_errors() {
+        for stderr in [
+            "ssh: connect to host github.com port 22: Connection timed out",
+            "error: RPC failed; HTTP 503 curl 22 The requested URL returned error: 503",
+            "fatal: the remote end hung up unexpectedly",
+            "Connection reset by peer",
+            "fatal: unable to access 'https://github.com/o/r.git/': The requested URL returned error: 502",
+        ] {
+            assert!(is_transient_push_error(stderr), "{}", stderr);
+        }
+        for stderr in [
+            " ! [rejected]        main -> main (non-fast-forward)",
+            "remote: Permission to o/r.git denied to bob. The requested URL returned error: 403",
+            "fatal: Authentication failed for 'https://github.com/o/r.git/'",
+        ] {
+            assert!(!is_transient_push_error(stderr), "{}", stderr);
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_push_retries_from_env() {
+        temp_env::with_var(PUSH_RETRIES_ENV_VAR, Some("0"), || {
+            assert_eq!(push_retries_from_env(), 0);
+        });
+        temp_env::with_var(PUSH_RETRIES_ENV_VAR, Some("many"), || {
+            assert_eq!(push_retries_from_env(), DEFAULT_PUSH_RETRIES);
+        });
+        temp_env::with_var(PUSH_RETRIES_ENV_VAR, None::<&str>, || {
+            assert_eq!(push_retries_from_env(), DEFAULT_PUSH_RETRIES);
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_push_failure_without_retries() {
+        // No such remote, so the push fails permanently on the first try
+        temp_env::with_var(PUSH_RETRIES_ENV_VAR, Some("5"), || {
+            let result = tokio_test::block_on(push_with_retry(
+                &PushTarget {
+                    remote: "keryx-test-missing-remote",
+                    remote_branch: "main",
+                    tags: false,
+                },
+                PushBackend::from_env(),
+            ));
+            assert!(matches!(result, Err(ShipError::PushFailed(_))));
+        });
+    }
 }
@@ -0,0 +1,206 @@
+//! GitHub release creation after a successful ship.
+//!
+//! Once the tag is pushed, a release is published for it with the tag's
+//! changelog section as the body, so the notes show up on the Releases page
+//! and release assets have a release to go to. A remote that is not on
+//! GitHub, a missing token or an API error is reported and skipped; the
+//! pushed release is never undone.
+
+use std::path::{Path, PathBuf};
+
+use git2::Repository;
+
+use crate::changelog::translations::version_section;
+use crate::error::ReleaseAssetError;
+use crate::git::tags::get_version_from_tag;
+use crate::journal::{Journal, OperationKind};
+use crate::llm::retry::retry_with_backoff;
+
+use super::assets::{ReleaseForge, github_forge};
+use super::record::ReleaseRecordStore;
+
+/// Changelog files looked for when the release record names none.
+const CHANGELOG_CANDIDATES: [&str; 3] = ["CHANGELOG.md", "CHANGES.md", "HISTORY.md"];
+
+/// Publish a GitHub release for the pushed `tag` on `remote`, unless one
+/// exists already. Problems are printed as warnings.
+pub async fn create_for_tag(
+    repo: &Repository,
+    remote: &str,
+    root: &Path,
+    tag: &str,
+    journal: &Journal,
+) {
+    println!();
+    println!("GitHub release:");
+    let forge = match github_forge(repo, remote).await {
+        Ok(forge) => forge,
+        Err(ReleaseAssetError::UnsupportedRemote(_)) => {
+            println!(
+                "  [SKIP] Remote '{}' is not on GitHub; no release created",
+                remote
+            );
+            return;
+        }
+        Err(e) => {
+            println!("  [WARN] {}; create the release for {} manually", e, tag);
+            return;
+        }
+    };
+
+    let prerelease = get_version_from_tag(tag).is_some_and(|v| !v.pre.is_empty());
+    let body = release_body(&release_changelogs(root, tag), tag).unwrap_or_else(|| {
+        println!(
+            "  [WARN] No changelog section found for {}; the release has no notes",
+            tag
+        );
+        String::new()
+    });
+    if let Err(e) = ensure_release(&forge, tag, &body, prerelease, journal).await {
+        println!("  [WARN] {}; create the release for {} manually", e, tag);
+    }
+}
+
+/// Publish the release for `tag` if the forge has none yet. Returns the
+/// URL of the new release, `None` if it already existed.
+pub async fn ensure_release<F: ReleaseForge + ?Sized>(
+    forge: &F,
+    tag: &str,
+    body: &str,
+    prerelease: bool,
+    journal: &Journal,
+) -> Result<Option<String>, ReleaseAssetError> {
+    let existing = retry_with_backoff(
+        || forge.find_release(tag),
+        |e| ReleaseAssetError::RetriesExhausted(Box::new(e)),
+    )
+    .await?;
+    if existing.is_some() {
+        println!(
+            "  [SKIP] {} release for {} already exists",
+            forge.name(),
+            tag
+        );
+        return Ok(None);
+    }
+
+    // Not retried: a request that timed out may still have created it
+    let url = forge.create_release(tag, tag, body, prerelease).await?;
+    journal.record(
+        journal
+            .entry(OperationKind::ReleaseCreated)
+            .arg(tag)
+            .detail(url.clone()),
+    );
+    println!(
+        "  [DONE] Created {}{} release {}",
+        if prerelease { "pre-release " } else { "" },
+        forge.name(),
+        url
+    );
+    Ok(Some(url))
+}
+
+/// Changelogs the release was written to: those in its release record, or
+/// the usual changelog names.
+fn release_changelogs(root: &Path, tag: &str) -> Vec<PathBuf> {
+    let recorded = ReleaseRecordStore::for_repo(root)
+        .load(tag)
+        .ok()
+        .flatten()
+        .map(|record| record.changelogs)
+        .unwrap_or_default();
+    if recorded.is_empty() {
+        CHANGELOG_CANDIDATES
+            .iter()
+            .map(|name| root.join(name))
+            .collect()
+    } else {
+        recorded
+            .into_iter()
+            .map(|changelog| root.join(changelog.path))
+            .collect()
+    }
+}
+
+/// The section for `tag` in the first of `changelogs` that has one, without
+/// its heading.
+fn release_body(changelogs: &[PathBuf], tag: &str) -> Option<String> {
+    let version = get_version_from_tag(tag)?;
+    changelogs.iter().find_map(|path| {
+        let content = std::fs::read_to_string(path).ok()?;
+        let section = version_section(&content, &version)?;
+        let body = section.split_once('\n').map_or("", |(_, body)| body);
+        Some(body.trim().to_string())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ship::assets::MockReleaseForge;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_release_body_from_first_changelog_with_section() {
+        let dir = TempDir::new().unwrap();
+        let without = dir.path().join("docs.md");
+        std::fs::write(
+            &without,
+            "# Changelog\n\n## [1.0.0] - 2024-01-01\n\n- Old\n",
+        )
+        .unwrap();
+        let with = dir.path().join("CHANGELOG.md");
+        std::fs::write(
+            &with,
+            "# Changelog\n\n## [1.1.0] - 2024-06-01\n\n### Added\n\n- Dark mode\n\n## [1.0.0] - 2024-01-01\n\n- Old\n",
+        )
+        .unwrap();
+
+        let changelogs = [without, dir.path().join("missing.md"), with];
+        assert_eq!(
+            release_body(&changelogs, "v1.1.0").as_deref(),
+            Some("### Added\n\n- Dark mode")
+        );
+        assert_eq!(release_body(&changelogs, "v2.0.0"), None);
+    }
+
+    #[tokio::test]
+    async fn test_ensure_release_creates_missing_release() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        let mut forge = MockReleaseForge::new();
+        forge.expect_name().return_const("GitHub");
+        forge.expect_find_release().returning(|_| Ok(None));
+        forge
+            .expect_create_release()
+            .withf(|tag, name, body, prerelease| {
+                tag == "v2.0.0-rc.1" && name == tag && body == "- Notes" && *prerelease
+            })
+            .times(1)
+            .returning(|_, _, _, _| Ok("https://github.com/o/r/releases/tag/v2.0.0-rc.1".into()));
+
+        let url = ensure_release(&forge, "v2.0.0-rc.1", "- Notes", true, &journal)
+            .await
+            .unwrap();
+        assert_eq!(
+            url.as_deref(),
+            Some("https://github.com/o/r/releases/tag/v2.0.0-rc.1")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_ensure_release_keeps_existing_release() {
+        let dir = TempDir::new().unwrap();
+        let journal = Journal::for_repo(dir.path(), "ship");
+        let mut forge = MockReleaseForge::new();
+        forge.expect_name().return_const("GitHub");
+        forge.expect_find_release().returning(|_| Ok(Some(7)));
+        forge.expect_create_release().never();
+
+        let url = ensure_release(&forge, "v1.0.0", "", false, &journal)
+            .await
+            .unwrap();
+        assert_eq!(url, None);
+    }
+}
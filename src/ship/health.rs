@@ -0,0 +1,593 @@
+//! Post-release validation (`keryx verify-ship`).
+//!
+//! Checks that a shipped release is complete: the tag is on the remote and
+//! points where the local tag does, the GitHub release exists (when asked
+//! for), the version files at the tag carry the tag's version, and the
+//! changelog at the tag has a section for it. Every check runs even when an
+//! earlier one failed, and the results end in one healthy/unhealthy summary.
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use git2::{Repository, Tree};
+use semver::Version;
+use tracing::trace;
+
+use crate::changelog::parser::parse_changelog_content;
+use crate::error::ShipError;
+use crate::git::tags::{get_latest_reachable_tag, get_version_from_tag};
+use crate::git::tree::read_tree_file;
+
+use super::assets::{ReleaseForge, github_forge};
+use super::train::tag_safe_name;
+use super::version_files::{
+    VersionFile, VersionFileSelection, detect_version_files, manifest_version,
+};
+
+/// Changelog names ship falls back to when the default output is missing.
+const CHANGELOG_CANDIDATES: [&str; 3] = ["CHANGELOG.md", "CHANGES.md", "HISTORY.md"];
+
+// Check names
+const REMOTE_TAG: &str = "Remote tag";
+const GITHUB_RELEASE: &str = "GitHub release";
+const VERSION_FILES: &str = "Version files";
+const CHANGELOG: &str = "Changelog";
+
+/// Configuration for `keryx verify-ship`, derived from CLI flags.
+pub struct VerifyShipConfig {
+    /// Tag to check (default: the latest release tag reachable from `HEAD`).
+    pub tag: Option<String>,
+    /// Remote the release was pushed to.
+    pub remote: String,
+    /// Also require a GitHub release for the tag.
+    pub github_release: bool,
+    /// Changelog files, relative to the project (or package) root.
+    pub outputs: Vec<PathBuf>,
+    /// Version files compared with the tag version.
+    pub version_files: VersionFileSelection,
+}
+
+/// Outcome of one check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CheckStatus {
+    Pass,
+    Fail,
+    /// Not applicable to this release.
+    Skip,
+}
+
+impl CheckStatus {
+    fn label(self) -> &'static str {
+        match self {
+            CheckStatus::Pass => "[PASS]",
+            CheckStatus::Fail => "[FAIL]",
+            CheckStatus::Skip => "[SKIP]",
+        }
+    }
+}
+
+/// Result of one post-release check.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HealthCheck {
+    pub name: &'static str,
+    pub status: CheckStatus,
+    pub detail: String,
+}
+
+impl HealthCheck {
+    fn pass(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Pass,
+            detail: detail.into(),
+        }
+    }
+
+    fn fail(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Fail,
+            detail: detail.into(),
+        }
+    }
+
+    fn skip(name: &'static str, detail: impl Into<String>) -> Self {
+        Self {
+            name,
+            status: CheckStatus::Skip,
+            detail: detail.into(),
+        }
+    }
+}
+
+/// All checks of one release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReleaseHealth {
+    pub tag: String,
+    pub checks: Vec<HealthCheck>,
+}
+
+impl ReleaseHealth {
+    /// Whether no check failed.
+    pub fn is_healthy(&self) -> bool {
+        self.failed() == 0
+    }
+
+    fn failed(&self) -> usize {
+        self.checks
+            .iter()
+            .filter(|c| c.status == CheckStatus::Fail)
+            .count()
+    }
+
+    /// One line per check followed by the summary.
+    pub fn render(&self) -> String {
+        let mut out = format!("Release health of {}:\n", self.tag);
+        for check in &self.checks {
+            out.push_str(&format!(
+                "  {} {}: {}\n",
+                check.status.label(),
+                check.name,
+                check.detail
+            ));
+        }
+        out.push('\n');
+        match self.failed() {
+            0 => out.push_str(&format!("✓ {} is healthy\n", self.tag)),
+            1 => out.push_str(&format!("✗ {} is unhealthy: 1 check failed\n", self.tag)),
+            n => out.push_str(&format!(
+                "✗ {} is unhealthy: {} checks failed\n",
+                self.tag, n
+            )),
+        }
+        out
+    }
+}
+
+/// Package and version named by a release tag: `v1.2.0` (or `1.2.0`) for
+/// the whole project, `<name>-v1.2.0` for one package of a release train.
+pub fn parse_release_tag(tag: &str) -> Option<(Option<&str>, Version)> {
+    if let Some(version) = get_version_from_tag(tag) {
+        return Some((None, version));
+    }
+    let (name, version) = tag.rsplit_once("-v")?;
+    if name.is_empty() {
+        return None;
+    }
+    Version::parse(version).ok().map(|v| (Some(name), v))
+}
+
+/// Run `keryx verify-ship`: check the release and print its health.
+pub async fn run_verify_ship(config: VerifyShipConfig) -> Result<(), ShipError> {
+    let repo = Repository::discover(".").map_err(|e| ShipError::GitFailed(e.to_string()))?;
+
+    let tag = match config.tag.clone() {
+        Some(tag) => tag,
+        None => get_latest_reachable_tag(&repo)
+            .map_err(|e| ShipError::GitFailed(e.to_string()))?
+            .map(|t| t.name)
+            .ok_or_else(|| ShipError::GitFailed("No release tag reachable from HEAD".into()))?,
+    };
+    let (package, version) =
+        parse_release_tag(&tag).ok_or_else(|| ShipError::NotReleaseTag(tag.clone()))?;
+    let tree = repo
+        .revparse_single(&format!("refs/tags/{}", tag))
+        .and_then(|object| object.peel_to_tree())
+        .map_err(|_| ShipError::TagNotFound(tag.clone()))?;
+
+    println!("Verifying {}...", tag);
+    let mut checks = vec![check_remote_tag(&repo, &config.remote, &tag)];
+
+    checks.push(if config.github_release {
+        match github_forge(&repo, &config.remote).await {
+            Ok(forge) => check_github_release(&forge, &tag).await,
+            Err(e) => HealthCheck::fail(GITHUB_RELEASE, e.to_string()),
+        }
+    } else {
+        HealthCheck::skip(GITHUB_RELEASE, "not requested (use --github-release)")
+    });
+
+    let mut files = match repo.workdir() {
+        Some(root) => detect_version_files(root).and_then(|f| config.version_files.apply(root, f)),
+        None => Err(ShipError::NoVersionFiles),
+    };
+    if let (Ok(files), Some(package)) = (&mut files, package) {
+        files.retain(|f| package_tag_name(f).as_deref() == Some(package));
+    }
+    let package_dir = files
+        .as_ref()
+        .ok()
+        .filter(|_| package.is_some())
+        .and_then(|f| f.first())
+        .map(|f| f.package_dir.clone())
+        .unwrap_or_default();
+    checks.push(match files {
+        Ok(files) => check_version_files(&repo, &tree, &files, &version),
+        Err(ShipError::NoVersionFiles) => {
+            HealthCheck::skip(VERSION_FILES, "no version files in this project")
+        }
+        Err(e) => HealthCheck::fail(VERSION_FILES, e.to_string()),
+    });
+
+    let outputs: Vec<PathBuf> = config
+        .outputs
+        .iter()
+        .map(|output| {
+            let relative = repo
+                .workdir()
+                .and_then(|root| output.strip_prefix(root).ok())
+                .unwrap_or(output);
+            package_dir.join(relative)
+        })
+        .collect();
+    checks.push(check_changelog(&repo, &tree, &outputs, &version));
+
+    let health = ReleaseHealth { tag, checks };
+    println!();
+    print!("{}", health.render());
+    if health.is_healthy() {
+        Ok(())
+    } else {
+        Err(ShipError::ReleaseUnhealthy(health.tag))
+    }
+}
+
+/// Name a release train would tag `file`'s package with.
+fn package_tag_name(file: &VersionFile) -> Option<String> {
+    file.package_name
+        .as_deref()
+        .or_else(|| file.package_dir.file_name().and_then(|n| n.to_str()))
+        .map(tag_safe_name)
+}
+
+/// Check that `tag` is on `remote` and points to the same object as locally.
+pub fn check_remote_tag(repo: &Repository, remote: &str, tag: &str) -> HealthCheck {
+    let refname = format!("refs/tags/{}", tag);
+    trace!("Running git ls-remote --tags {} {}", remote, refname);
+    let output = Command::new("git")
+        .arg("-C")
+        .arg(repo.path())
+        .args(["ls-remote", "--tags", remote, &refname])
+        .output();
+    let output = match output {
+        Ok(output) if output.status.success() => output,
+        Ok(output) => {
+            return HealthCheck::fail(
+                REMOTE_TAG,
+                format!(
+                    "git ls-remote {} failed: {}",
+                    remote,
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            );
+        }
+        Err(e) => return HealthCheck::fail(REMOTE_TAG, format!("Failed to run git: {}", e)),
+    };
+
+    let listing = String::from_utf8_lossy(&output.stdout);
+    let local = repo.refname_to_id(&refname).ok().map(|oid| oid.to_string());
+    match remote_ref_oid(&listing, &refname) {
+        None => HealthCheck::fail(REMOTE_TAG, format!("{} is not on {}", tag, remote)),
+        Some(oid) if local.as_deref().is_some_and(|local| local != oid) => HealthCheck::fail(
+            REMOTE_TAG,
+            format!(
+                "{} on {} points to {}, the local tag to {}",
+                tag,
+                remote,
+                short(oid),
+                short(local.as_deref().unwrap_or_default())
+            ),
+        ),
+        Some(_) => HealthCheck::pass(REMOTE_TAG, format!("{} is on {}", tag, remote)),
+    }
+}
+
+/// Object id of `refname` in `git ls-remote` output.
+fn remote_ref_oid<'a>(listing: &'a str, refname: &str) -> Option<&'a str> {
+    listing.lines().find_map(|line| {
+        let (oid, name) = line.split_once('\t')?;
+        (name.trim() == refname).then_some(oid.trim())
+    })
+}
+
+fn short(oid: &str) -> &str {
+    &oid[..oid.len().min(7)]
+}
+
+/// Check that `forge` has a release for `tag`.
+pub async fn check_github_release(forge: &dyn ReleaseForge, tag: &str) -> HealthCheck {
+    match forge.find_release(tag).await {
+        Ok(Some(_)) => HealthCheck::pass(
+            GITHUB_RELEASE,
+            format!("{} release for {} exists", forge.name(), tag),
+        ),
+        Ok(None) => HealthCheck::fail(
+            GITHUB_RELEASE,
+            format!("No {} release exists for {}", forge.name(), tag),
+        ),
+        Err(e) => HealthCheck::fail(GITHUB_RELEASE, e.to_string()),
+    }
+}
+
+/// Check that the version files in `tree` declare `version`. Files that do
+/// not exist at the tag (packages added later) are not checked.
+pub fn check_version_files(
+    repo: &Repository,
+    tree: &Tree,
+    files: &[VersionFile],
+    version: &Version,
+) -> HealthCheck {
+    let mut matching = Vec::new();
+    let mut problems = Vec::new();
+    for file in files {
+        let path = file.relative_path();
+        let Some(content) = read_tree_file(repo, tree, &path) else {
+            continue;
+        };
+        match manifest_version(file.kind, &path, &content) {
+            Ok(Some(found)) if &found == version => matching.push(path.display().to_string()),
+            Ok(Some(found)) => problems.push(format!("{} is at {}", path.display(), found)),
+            Ok(None) => problems.push(format!("{} has no version", path.display())),
+            Err(e) => problems.push(e.to_string()),
+        }
+    }
+
+    if !problems.is_empty() {
+        HealthCheck::fail(
+            VERSION_FILES,
+            format!("expected {}: {}", version, problems.join("; ")),
+        )
+    } else if matching.is_empty() {
+        HealthCheck::fail(VERSION_FILES, "no version files exist at the tag")
+    } else {
+        HealthCheck::pass(
+            VERSION_FILES,
+            format!("{} at {}", matching.join(", "), version),
+        )
+    }
+}
+
+/// Check that every changelog in `outputs` has a section for `version` in
+/// `tree`.
+pub fn check_changelog(
+    repo: &Repository,
+    tree: &Tree,
+    outputs: &[PathBuf],
+    version: &Version,
+) -> HealthCheck {
+    let mut found = Vec::new();
+    let mut problems = Vec::new();
+    for output in outputs {
+        let Some((path, content)) = read_changelog_at(repo, tree, output) else {
+            problems.push(format!("{} does not exist at the tag", output.display()));
+            continue;
+        };
+        match parse_changelog_content(content) {
+            Ok(parsed) if parsed.has_version(version) => found.push(path.display().to_string()),
+            Ok(_) => problems.push(format!("{} has no section for {}", path.display(), version)),
+            Err(e) => problems.push(format!("{}: {}", path.display(), e)),
+        }
+    }
+
+    if !problems.is_empty() {
+        HealthCheck::fail(CHANGELOG, problems.join("; "))
+    } else {
+        HealthCheck::pass(
+            CHANGELOG,
+            format!("{} has a section for {}", found.join(", "), version),
+        )
+    }
+}
+
+/// The changelog at `output` in `tree`, trying the other common names when
+/// the default `CHANGELOG.md` is missing (as ship does).
+fn read_changelog_at(repo: &Repository, tree: &Tree, output: &Path) -> Option<(PathBuf, String)> {
+    if let Some(content) = read_tree_file(repo, tree, output) {
+        return Some((output.to_path_buf(), content));
+    }
+    if output.file_name() != Some(CHANGELOG_CANDIDATES[0].as_ref()) {
+        return None;
+    }
+    let dir = output.parent().unwrap_or(Path::new(""));
+    CHANGELOG_CANDIDATES[1..].iter().find_map(|name| {
+        let path = dir.join(name);
+        read_tree_file(repo, tree, &path).map(|content| (path, content))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ship::assets::MockReleaseForge;
+    use crate::ship::version_files::VersionFileKind;
+    use git2::{Oid, Signature};
+    use tempfile::TempDir;
+
+    fn commit_files(repo: &Repository, files: &[(&str, &str)]) -> Oid {
+        let root = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            let full = root.join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(&full, content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        repo.commit(Some("HEAD"), &sig, &sig, "release", &tree, &[])
+            .unwrap()
+    }
+
+    fn version_file(package_dir: &str, kind: VersionFileKind) -> VersionFile {
+        VersionFile {
+            path: Path::new(package_dir).join(kind.to_string()),
+            kind,
+            current_version: Version::new(1, 2, 0),
+            package_dir: PathBuf::from(package_dir),
+            package_name: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_release_tag() {
+        assert_eq!(
+            parse_release_tag("v1.2.0"),
+            Some((None, Version::new(1, 2, 0)))
+        );
+        assert_eq!(
+            parse_release_tag("1.2.0"),
+            Some((None, Version::new(1, 2, 0)))
+        );
+        assert_eq!(
+            parse_release_tag("acme-core-v0.3.1"),
+            Some((Some("acme-core"), Version::new(0, 3, 1)))
+        );
+        assert_eq!(parse_release_tag("nightly"), None);
+        assert_eq!(parse_release_tag("-v1.0.0"), None);
+    }
+
+    #[test]
+    fn test_remote_ref_oid() {
+        let listing = "1111111111\trefs/tags/v1.2.0\n2222222222\trefs/tags/v1.2.0^{}\n";
+        assert_eq!(
+            remote_ref_oid(listing, "refs/tags/v1.2.0"),
+            Some("1111111111")
+        );
+        assert_eq!(remote_ref_oid(listing, "refs/tags/v1.2"), None);
+    }
+
+    #[test]
+    fn test_version_and_changelog_checks_read_the_tag() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let oid = commit_files(
+            &repo,
+            &[
+                (
+                    "Cargo.toml",
+                    "[package]\nname = \"app\"\nversion = \"1.2.0\"\n",
+                ),
+                ("web/package.json", "{\"version\": \"1.1.0\"}"),
+                (
+                    "CHANGES.md",
+                    "# Changelog\n\n## [1.2.0] - 2024-06-01\n\n### Added\n\n- Export\n",
+                ),
+            ],
+        );
+        let tree = repo.find_commit(oid).unwrap().tree().unwrap();
+        // The working tree has moved on since the release
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nversion = \"1.3.0\"\n",
+        )
+        .unwrap();
+
+        let cargo = version_file("", VersionFileKind::CargoToml);
+        let web = version_file("web", VersionFileKind::PackageJson);
+        let added_later = version_file("cli", VersionFileKind::CargoToml);
+        let version = Version::new(1, 2, 0);
+
+        let check = check_version_files(&repo, &tree, &[cargo.clone(), added_later], &version);
+        assert_eq!(
+            check,
+            HealthCheck::pass(VERSION_FILES, "Cargo.toml at 1.2.0")
+        );
+        let check = check_version_files(&repo, &tree, &[cargo, web], &version);
+        assert_eq!(check.status, CheckStatus::Fail);
+        assert_eq!(check.detail, "expected 1.2.0: web/package.json is at 1.1.0");
+
+        let outputs = [PathBuf::from("CHANGELOG.md")];
+        let check = check_changelog(&repo, &tree, &outputs, &version);
+        assert_eq!(
+            check,
+            HealthCheck::pass(CHANGELOG, "CHANGES.md has a section for 1.2.0")
+        );
+        let check = check_changelog(&repo, &tree, &outputs, &Version::new(1, 3, 0));
+        assert_eq!(
+            check,
+            HealthCheck::fail(CHANGELOG, "CHANGES.md has no section for 1.3.0")
+        );
+        let check = check_changelog(&repo, &tree, &[PathBuf::from("NEWS.md")], &version);
+        assert_eq!(
+            check,
+            HealthCheck::fail(CHANGELOG, "NEWS.md does not exist at the tag")
+        );
+    }
+
+    #[test]
+    fn test_remote_tag_check() {
+        let dir = TempDir::new().unwrap();
+        let remote_dir = TempDir::new().unwrap();
+        Repository::init_bare(remote_dir.path()).unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let oid = commit_files(&repo, &[("README.md", "hello\n")]);
+        let object = repo.find_object(oid, None).unwrap();
+        repo.tag_lightweight("v1.0.0", &object, false).unwrap();
+        repo.tag_lightweight("v1.1.0", &object, false).unwrap();
+        let mut remote = repo
+            .remote("origin", remote_dir.path().to_str().unwrap())
+            .unwrap();
+        remote
+            .push(&["refs/tags/v1.0.0:refs/tags/v1.0.0"], None)
+            .unwrap();
+
+        assert_eq!(
+            check_remote_tag(&repo, "origin", "v1.0.0"),
+            HealthCheck::pass(REMOTE_TAG, "v1.0.0 is on origin")
+        );
+        assert_eq!(
+            check_remote_tag(&repo, "origin", "v1.1.0"),
+            HealthCheck::fail(REMOTE_TAG, "v1.1.0 is not on origin")
+        );
+        assert_eq!(
+            check_remote_tag(&repo, "missing", "v1.0.0").status,
+            CheckStatus::Fail
+        );
+    }
+
+    #[tokio::test]
+    async fn test_github_release_check() {
+        let mut forge = MockReleaseForge::new();
+        forge.expect_name().return_const("GitHub");
+        forge
+            .expect_find_release()
+            .returning(|tag| Ok((tag == "v1.0.0").then_some(7)));
+
+        assert_eq!(
+            check_github_release(&forge, "v1.0.0").await,
+            HealthCheck::pass(GITHUB_RELEASE, "GitHub release for v1.0.0 exists")
+        );
+        assert_eq!(
+            check_github_release(&forge, "v1.1.0").await,
+            HealthCheck::fail(GITHUB_RELEASE, "No GitHub release exists for v1.1.0")
+        );
+    }
+
+    #[test]
+    fn test_render_summary() {
+        let mut health = ReleaseHealth {
+            tag: "v1.0.0".to_string(),
+            checks: vec![
+                HealthCheck::pass(REMOTE_TAG, "v1.0.0 is on origin"),
+                HealthCheck::skip(GITHUB_RELEASE, "not requested (use --github-release)"),
+            ],
+        };
+        assert!(health.is_healthy());
+        assert_eq!(
+            health.render(),
+            "Release health of v1.0.0:\n  [PASS] Remote tag: v1.0.0 is on origin\n  [SKIP] GitHub release: not requested (use --github-release)\n\n✓ v1.0.0 is healthy\n"
+        );
+
+        health.checks.push(HealthCheck::fail(
+            CHANGELOG,
+            "CHANGELOG.md has no section for 1.0.0",
+        ));
+        assert!(!health.is_healthy());
+        assert!(
+            health
+                .render()
+                .ends_with("✗ v1.0.0 is unhealthy: 1 check failed\n")
+        );
+    }
+}
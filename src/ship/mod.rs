@@ -3,28 +3,86 @@
 //! Orchestrates preflight checks, version calculation, version file updates,
 //! changelog generation, and git commit/tag/push.
 
+pub mod approval;
+pub mod assets;
+pub mod checks;
 pub mod executor;
+pub mod github_release;
+pub mod health;
 pub mod preflight;
+pub mod publish;
+pub mod push;
+pub mod record;
+pub mod risk;
+pub mod schedule;
+pub mod train;
 pub mod version_files;
 
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
-use dialoguer::Confirm;
+use chrono::{DateTime, Utc};
+use dialoguer::{Confirm, Select};
 use git2::Repository;
 use semver::Version;
+use serde::Serialize;
 use tracing::debug;
 
+use crate::changelog::attribution::{build_attribution, cited_pr_numbers};
+use crate::changelog::channel::{
+    PrereleaseSection, build_consolidation_prompt, channel, combine, prerelease_sections,
+    remove_sections_from_file,
+};
+use crate::changelog::codename::suggest_codename;
+use crate::changelog::empty_guard::generate_guarded;
+use crate::changelog::heading::used_codenames;
+use crate::changelog::migration::{
+    MIGRATIONS_FILE, build_migration_prompt, is_major_release, last_major_release,
+    write_migration_guide,
+};
 use crate::changelog::parser::read_changelog;
-use crate::changelog::write_changelog;
+use crate::changelog::pr_notes::nothing_to_generate;
+use crate::changelog::sanitize::{SanitizeOptions, sanitize_output};
+use crate::changelog::{
+    AttributionConfig, CategoryAliases, ChangelogOutput, CodenameConfig, CommitNotes, CompareLinks,
+    CuratedNotes, EntryConstraints, EntrySort, HeadingTemplate, MigrationGuide, MigrationInput,
+    ProductContext, ReleaseAttribute, ReleaseDateConfig, ReleaseMetadata, ReleaseTemplate,
+    StyleGuide, write_changelogs_with_metadata,
+};
 use crate::error::ShipError;
+use crate::git::commits::fetch_commits;
+use crate::git::tags::get_all_tags;
+use crate::git::{find_untagged_release, net_commits};
+use crate::github::{DEFAULT_REMOTE, PrScope, detect_remote, select_pr_remote};
+use crate::integrations;
+use crate::journal::{Journal, OperationKind};
+use crate::languages::LanguageProfile;
 use crate::llm::{
-    ChangelogInput, LlmRouter, ProviderSelection, build_prompt, build_verification_prompt,
+    ChangelogInput, ChangelogRequest, LlmRouter, ProviderSelection, TaskPolicies,
+    VerificationRequest, build_prompt, build_verification_prompt,
+};
+use crate::lock::RepoLock;
+use crate::packaging;
+use crate::status;
+use crate::summary::{RunSummary, SummaryFormat};
+use crate::verification::{
+    ScopeMode, SearchScope, SourceCorpus, VerifyMode, apply_verification_rules,
+    check_ripgrep_installed, drop_unsupported_entries, find_missed_changes,
+    gather_verification_evidence_in,
 };
-use crate::verification::{check_ripgrep_installed, gather_verification_evidence};
 use crate::version::{VersionBumpInput, calculate_next_version, calculate_next_version_with_llm};
 
-use self::preflight::{check_tag_exists, run_checks};
-use self::version_files::{detect_version_files, update_version_file};
+use self::approval::PreparedRelease;
+use self::checks::RequiredChecks;
+use self::preflight::{PreflightOverrides, RemoteSync, check_tag_exists, run_checks};
+use self::publish::PublishStep;
+use self::record::{Approver, ReleaseRecord, ReleaseRecordStore, ReleaseTimes};
+use self::risk::{RiskItem, RiskLevel, RiskReport};
+use self::schedule::{PendingRelease, PendingReleaseStore};
+use self::version_files::{
+    VersionFile, VersionFileSelection, VersionMismatchStrategy, check_versions_agree,
+    detect_version_files, group_by_package, resolve_version_mismatch, update_version_file,
+};
 
 /// Configuration for the ship command, derived from CLI flags.
 pub struct ShipConfig {
@@ -32,24 +90,109 @@ pub struct ShipConfig {
     pub dry_run: bool,
     pub no_llm_bump: bool,
     pub no_prs: bool,
+    /// Remote to read PRs and compare links from (`None` = auto-select).
+    /// Pushes always go to `origin`.
+    pub remote: Option<String>,
     pub verbose: bool,
-    pub no_verify: bool,
-    pub output: PathBuf,
+    /// How generated entries are verified against the codebase.
+    pub verify: VerifyMode,
+    /// Changelog files that receive the release section (kept in sync).
+    pub outputs: Vec<PathBuf>,
     pub provider_selection: ProviderSelection,
+    /// Which detected version files to update.
+    pub version_files: VersionFileSelection,
+    /// How to resolve disagreeing version files (`None` = ask interactively).
+    pub on_version_mismatch: Option<VersionMismatchStrategy>,
+    /// How the changelog section date is computed.
+    pub release_date: ReleaseDateConfig,
+    /// Extension attributes (release manager, ...) for the new section.
+    pub attributes: Vec<ReleaseAttribute>,
+    /// Template for the new section's heading.
+    pub heading: HeadingTemplate,
+    /// Template for the whole new section, replacing the default layout.
+    pub template: Option<ReleaseTemplate>,
+    /// Where the release codename comes from.
+    pub codename: CodenameConfig,
+    /// Add an LLM-written highlights paragraph to the new section.
+    pub highlights: bool,
+    /// Write upgrade steps to `MIGRATIONS.md` for a major release.
+    pub migration_guide: bool,
+    /// Format of the recap printed when the run finishes.
+    pub summary_format: SummaryFormat,
+    /// Limits on the generated entries.
+    pub constraints: EntryConstraints,
+    /// Order of entries within each category.
+    pub entry_sort: EntrySort,
+    /// Stage the release locally and defer the push until this time.
+    pub schedule: Option<DateTime<Utc>>,
+    /// Steps run after a successful push (`cargo publish`, ...).
+    pub publish_steps: Vec<PublishStep>,
+    /// Glob patterns of files uploaded to the forge release after the push.
+    pub release_assets: Vec<String>,
+    /// Publish a GitHub release for the tag after the push.
+    pub github_release: bool,
+    /// Categories whose entries credit co-authors and reviewers.
+    pub attribution: AttributionConfig,
+    /// Push the branch first and tag only once CI checks pass.
+    pub require_checks: Option<RequiredChecks>,
+    /// Stop after the release commit and wait for `ship --approve`.
+    pub prepare: bool,
+    /// Preflight failures downgraded to warnings.
+    pub preflight_overrides: PreflightOverrides,
+    /// Release every changed package of a monorepo with its own tag, in one
+    /// release commit.
+    pub train: bool,
+    /// Leave version files alone: take the current version from the latest
+    /// tag and only write the changelog, tag and push.
+    pub tag_only: bool,
+    /// Changelog that receives pre-release sections instead of `outputs`.
+    /// Its sections are consolidated into the stable section and removed
+    /// when the stable version ships.
+    pub prerelease_changelog: Option<PathBuf>,
+    /// Release only this workspace package, with its own tag and changelog.
+    pub package: Option<String>,
+    /// Refs whose history is left out of the release, such as the upstream
+    /// a fork syncs from.
+    pub exclude_upstream: Vec<String>,
+    /// The repository lock taken for the run. `--schedule` gives it up
+    /// while waiting, so `ship --publish` can run from another terminal.
+    pub lock: Option<RepoLock>,
+}
+
+impl ShipConfig {
+    /// Remote to read PRs and compare links from.
+    fn pr_remote(&self) -> &str {
+        self.remote.as_deref().unwrap_or(DEFAULT_REMOTE)
+    }
 }
 
 /// Run the full ship pipeline.
-pub async fn run_ship(config: ShipConfig) -> Result<(), ShipError> {
+pub async fn run_ship(mut config: ShipConfig) -> Result<(), ShipError> {
     let repo = Repository::open(".")
         .map_err(|e| ShipError::GitFailed(format!("Not a git repository: {}", e)))?;
 
+    if let Some(workdir) = repo.workdir()
+        && let Some(pending) = PendingReleaseStore::for_repo(workdir).load()?
+    {
+        return Err(match pending.prepared_by {
+            Some(_) => ShipError::AwaitingApproval(pending.tag),
+            None => ShipError::ReleaseAlreadyStaged(pending.tag),
+        });
+    }
+
+    let mut summary = RunSummary::new("ship");
+    let started_at = Utc::now();
+
     // ── Stage 1: Preflight checks ──
-    println!("Preflight checks:");
+    summary.begin_stage("preflight");
+    status!("Preflight checks:");
 
-    let preflight = run_checks(
+    let mut preflight = run_checks(
         &repo,
         config.no_llm_bump,
         config.provider_selection,
+        config.preflight_overrides,
+        &config.exclude_upstream,
         config.verbose,
     )?;
 
@@ -59,26 +202,120 @@ pub async fn run_ship(config: ShipConfig) -> Result<(), ShipError> {
         .map(|t| t.name.as_str())
         .unwrap_or("(none)");
 
-    println!("  [PASS] Working tree is clean");
-    println!("  [PASS] Local branch is up to date with remote");
-    println!(
+    if preflight.dirty_working_tree {
+        status!(
+            "  [WARN] Working tree has uncommitted changes (--allow-dirty); they are left out of the release commit"
+        );
+    } else {
+        status!("  [PASS] Working tree is clean");
+    }
+    match preflight.remote_sync {
+        RemoteSync::UpToDate => status!("  [PASS] Local branch is up to date with remote"),
+        RemoteSync::Behind => status!(
+            "  [WARN] Local branch is behind {}/{} (--allow-behind); the push may be rejected",
+            preflight.remote_name,
+            preflight.upstream_branch
+        ),
+        RemoteSync::NoUpstream => status!(
+            "  [WARN] Branch has no upstream (--allow-no-upstream); pushing to {}/{} without a sync check",
+            preflight.remote_name,
+            preflight.upstream_branch
+        ),
+    }
+    status!(
         "  [PASS] {} commits since {}",
         preflight.commits_since_tag.len(),
         tag_display
     );
+    for exclusion in &preflight.excluded_upstream {
+        status!("  [SKIP] Upstream commits reachable from {}", exclusion);
+    }
 
     if !config.no_llm_bump {
         if preflight.llm_available {
-            println!("  [PASS] LLM provider available");
+            status!("  [PASS] LLM provider available");
         } else {
-            println!("  [WARN] LLM provider not available, using algorithmic versioning");
+            status!("  [WARN] LLM provider not available, using algorithmic versioning");
         }
     }
 
-    println!();
+    // Without a GitHub remote there are no PRs to fetch; say so once
+    config.remote = Some(select_pr_remote(&repo, config.remote.as_deref()));
+    if !config.no_prs
+        && let Some(reason) =
+            detect_remote(&repo, config.pr_remote()).commits_only_reason(config.pr_remote())
+    {
+        status!("  [SKIP] PR enrichment: {}, using commits only", reason);
+        config.no_prs = true;
+    }
+
+    if config.train || config.package.is_some() {
+        return train::run_train(config, &repo, preflight, &mut summary, started_at).await;
+    }
+
+    // Version files are checked before any LLM call so a disagreement never
+    // feeds an inconsistent base version into the bump.
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
+    let version_files = if config.tag_only {
+        match &preflight.latest_tag {
+            Some(tag) => status!(
+                "  [SKIP] Version files: tag-only release, the current version comes from {}",
+                tag.name
+            ),
+            None => status!(
+                "  [SKIP] Version files: tag-only release with no previous tag (use --set-version to choose the first version)"
+            ),
+        }
+        Vec::new()
+    } else {
+        let version_files = config
+            .version_files
+            .apply(workdir, detect_version_files(workdir)?)?;
+
+        match check_versions_agree(&version_files) {
+            Ok(()) => status!(
+                "  [PASS] Version files agree on {}",
+                version_files[0].current_version
+            ),
+            Err(mismatch) => {
+                status!("  [WARN] {}", mismatch);
+                let strategy = match config.on_version_mismatch {
+                    Some(strategy) => strategy,
+                    None => {
+                        prompt_version_mismatch(&version_files, preflight.base_version.as_ref())?
+                    }
+                };
+                if let Some(base) = resolve_version_mismatch(
+                    &version_files,
+                    preflight.base_version.as_ref(),
+                    strategy,
+                )? {
+                    status!("  [PASS] Using {} as the current version", base);
+                    preflight.base_version = Some(base);
+                }
+            }
+        }
+        version_files
+    };
+
+    warn_untagged_changelog(&repo, workdir, &config.outputs);
+
+    publish::check_steps(&config.publish_steps, workdir)?;
+    assets::check_assets(&config.release_assets, workdir)?;
+    if config.require_checks.is_some() {
+        checks::check_remote(&repo, &preflight.remote_name)?;
+    }
+
+    status!();
+    summary.commits = preflight.commits_since_tag.len();
 
     // ── Stage 2: Version calculation ──
-    let mut llm = LlmRouter::new(config.provider_selection);
+    summary.begin_stage("version");
+    let mut llm = LlmRouter::new(config.provider_selection)
+        .with_journal(Journal::for_repository(&repo, "ship"))
+        .with_policies(TaskPolicies::load(workdir));
 
     let (next_version, bump_reasoning) = if let Some(ref explicit) = config.set_version {
         (explicit.clone(), None)
@@ -95,12 +332,12 @@ pub async fn run_ship(config: ShipConfig) -> Result<(), ShipError> {
             commits: &preflight.commits_since_tag,
             pull_requests: &[], // PRs are optional for version bump
             previous_version: preflight.base_version.as_ref(),
-            repository_name: &get_repo_name(&repo),
+            repository_name: &get_repo_name(&repo, config.pr_remote()),
         };
         calculate_next_version_with_llm(&bump_input, &mut llm, config.verbose).await
     };
 
-    println!(
+    status!(
         "Version: {} -> {}{}",
         preflight
             .base_version
@@ -120,7 +357,7 @@ pub async fn run_ship(config: ShipConfig) -> Result<(), ShipError> {
         let suggested = find_next_available_version(&repo, &next_version)?;
         let suggested_tag = format!("v{}", suggested);
 
-        println!();
+        status!();
         let use_suggested = Confirm::new()
             .with_prompt(format!(
                 "{} already exists. Did you mean {}?",
@@ -140,66 +377,227 @@ pub async fn run_ship(config: ShipConfig) -> Result<(), ShipError> {
             &repo,
             &mut llm,
             &preflight,
+            version_files,
             suggested,
             suggested_tag,
+            bump_reasoning,
+            &mut summary,
+            started_at,
         )
         .await;
     }
 
-    run_ship_with_version(config, &repo, &mut llm, &preflight, next_version, tag_name).await
+    run_ship_with_version(
+        config,
+        &repo,
+        &mut llm,
+        &preflight,
+        version_files,
+        next_version,
+        tag_name,
+        bump_reasoning,
+        &mut summary,
+        started_at,
+    )
+    .await
+}
+
+/// What `ship --dry-run --format json` would do.
+#[derive(Debug, Serialize)]
+struct ShipPlan {
+    previous_version: Option<Version>,
+    version: Version,
+    /// Why the LLM chose the bump.
+    bump_reasoning: Option<String>,
+    tag: String,
+    commit: String,
+    version_files: Vec<VersionFilePlan>,
+    /// Changelogs that get a new section.
+    changelogs: Vec<PathBuf>,
+    /// Changelogs that already have the section and are left alone.
+    existing_changelogs: Vec<PathBuf>,
+    /// Pre-releases whose entries move into this release.
+    consolidates: Vec<Version>,
+    /// `remote/branch` the release is pushed to.
+    push_to: String,
+    scheduled_at: Option<DateTime<Utc>>,
+    publish_steps: Vec<String>,
+    risk: RiskPlan,
+    summary: RunSummary,
+}
+
+#[derive(Debug, Serialize)]
+struct VersionFilePlan {
+    path: String,
+    from: Version,
+    to: Version,
+}
+
+#[derive(Debug, Serialize)]
+struct RiskPlan {
+    level: RiskLevel,
+    items: Vec<RiskItem>,
+}
+
+/// Ask how to resolve version files that disagree on their current version.
+fn prompt_version_mismatch(
+    files: &[VersionFile],
+    tag_version: Option<&Version>,
+) -> Result<VersionMismatchStrategy, ShipError> {
+    let mut options = Vec::new();
+    if let Some(tag_version) = tag_version {
+        options.push((
+            VersionMismatchStrategy::Tag,
+            format!("Use tag version ({})", tag_version),
+        ));
+    }
+    if let Some(max) = files.iter().map(|f| &f.current_version).max() {
+        options.push((
+            VersionMismatchStrategy::Max,
+            format!("Use highest version ({})", max),
+        ));
+    }
+    options.push((VersionMismatchStrategy::Fail, "Abort release".to_string()));
+
+    let labels: Vec<&str> = options.iter().map(|(_, label)| label.as_str()).collect();
+    let choice = Select::new()
+        .with_prompt("Version files disagree. How should the current version be resolved?")
+        .items(&labels)
+        .default(0)
+        .interact()
+        .map_err(|_| ShipError::Cancelled)?;
+
+    Ok(options[choice].0)
 }
 
 /// Continue the ship pipeline with a resolved version.
+#[allow(clippy::too_many_arguments)]
 async fn run_ship_with_version(
-    config: ShipConfig,
+    mut config: ShipConfig,
     repo: &Repository,
     llm: &mut LlmRouter,
     preflight: &preflight::PreflightResult,
+    version_files: Vec<VersionFile>,
     next_version: Version,
     tag_name: String,
+    bump_reasoning: Option<String>,
+    summary: &mut RunSummary,
+    started_at: DateTime<Utc>,
 ) -> Result<(), ShipError> {
     let workdir = repo
         .workdir()
         .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
 
-    // ── Stage 4: Version file detection and update ──
-    let version_files = detect_version_files(workdir)?;
+    // ── Stage 4: Version file update plan ──
+    let packages = group_by_package(&version_files);
 
-    println!();
-    println!("Version files:");
-    for vf in &version_files {
-        println!(
-            "  [UPDATE] {}: {} -> {}",
-            vf.kind, vf.current_version, next_version
+    status!();
+    if packages.is_empty() {
+        status!("Version files:");
+        status!("  [SKIP] Tag-only release, no version file is updated");
+    } else if packages.len() == 1 {
+        status!("Version files:");
+    } else {
+        status!(
+            "Version files ({} files in {} packages):",
+            version_files.len(),
+            packages.len()
         );
     }
+    for package in &packages {
+        if packages.len() > 1 {
+            status!("  {}", package.label);
+        }
+        let indent = if packages.len() > 1 { "    " } else { "  " };
+        for vf in &package.files {
+            status!(
+                "{}[UPDATE] {}: {} -> {}",
+                indent,
+                vf.display_path(),
+                vf.current_version,
+                next_version
+            );
+        }
+    }
 
     // ── Stage 5: Changelog check/generation ──
-    let output_path = resolve_changelog_path(workdir, &config.output);
-    let changelog_path = if is_default_changelog_output(&config.output) {
-        detect_changelog_path(workdir).unwrap_or_else(|| output_path.clone())
-    } else {
-        output_path.clone()
+    // Every output gets the same section; outputs that already have it are
+    // left alone so a partially shipped release can be completed.
+    let prerelease_path = config
+        .prerelease_changelog
+        .as_ref()
+        .map(|path| resolve_changelog_path(workdir, path));
+    let release_channel = channel(&next_version);
+    let changelog_paths: Vec<PathBuf> = match &prerelease_path {
+        Some(path) if release_channel.is_some() => vec![path.clone()],
+        _ => config
+            .outputs
+            .iter()
+            .map(|output| {
+                let output_path = resolve_changelog_path(workdir, output);
+                if is_default_changelog_output(output) {
+                    detect_changelog_path(workdir).unwrap_or(output_path)
+                } else {
+                    output_path
+                }
+            })
+            .collect(),
+    };
+    let section_label = match release_channel {
+        Some(channel) => format!("{} ({} channel)", next_version, channel),
+        None => next_version.to_string(),
     };
 
-    let parsed_changelog = read_changelog(&changelog_path)?;
-    let changelog_exists_for_version = parsed_changelog
+    status!();
+    let mut pending_changelogs: Vec<PathBuf> = Vec::new();
+    let mut existing_changelogs: Vec<PathBuf> = Vec::new();
+    for path in &changelog_paths {
+        if pending_changelogs.contains(path) || existing_changelogs.contains(path) {
+            continue;
+        }
+        let display = path.strip_prefix(workdir).unwrap_or(path).display();
+        if read_changelog(path)?.is_some_and(|parsed| parsed.has_version(&next_version)) {
+            status!(
+                "  [SKIP] Changelog section for {} already exists in {}",
+                section_label,
+                display
+            );
+            existing_changelogs.push(path.clone());
+        } else {
+            status!(
+                "  [CREATE] Changelog section for {} in {}",
+                section_label,
+                display
+            );
+            pending_changelogs.push(path.clone());
+        }
+    }
+    let changelog_generated = !pending_changelogs.is_empty();
+
+    // The stable section takes over the entries of its pre-releases, from the
+    // pre-release changelog or else from the main one
+    let prerelease_source = prerelease_path
+        .clone()
+        .or_else(|| changelog_paths.first().cloned());
+    let category_aliases = CategoryAliases::for_repository(repo);
+    let prereleases = match &prerelease_source {
+        Some(path) if changelog_generated && release_channel.is_none() => read_changelog(path)?
+            .map(|parsed| {
+                prerelease_sections(&parsed.raw_content, &next_version, &category_aliases)
+            })
+            .unwrap_or_default(),
+        _ => Vec::new(),
+    };
+    if let Some(path) = prerelease_source
         .as_ref()
-        .map(|parsed| parsed.has_version(&next_version))
-        .unwrap_or(false);
-
-    let changelog_generated = if changelog_exists_for_version {
-        println!();
-        println!(
-            "  [SKIP] Changelog section for {} already exists",
-            next_version
+        .filter(|_| !prereleases.is_empty())
+    {
+        status!(
+            "  [MERGE] Consolidating {} from {}",
+            prerelease_list(&prereleases),
+            path.strip_prefix(workdir).unwrap_or(path).display()
         );
-        false
-    } else {
-        println!();
-        println!("  [CREATE] Changelog section for {}", next_version);
-        true
-    };
+    }
 
     if changelog_generated && !preflight.llm_available && !config.dry_run {
         let provider = config.provider_selection.primary;
@@ -209,12 +607,19 @@ async fn run_ship_with_version(
         )));
     }
 
-    // ── Stage 6: Confirmation prompt ──
-    let effective_changelog_path = changelog_path.clone();
+    // ── Stage 6: Risk report ──
+    let report = build_risk_report(repo, &config, preflight).await;
+    status!();
+    status!("Risk report:");
+    for item in report.items() {
+        status!("  {} {}", item.label(), item.message);
+    }
+    status!("  Overall risk: {}", report.level());
 
-    println!();
-    println!("Summary:");
-    println!(
+    // ── Stage 7: Confirmation prompt ──
+    status!();
+    status!("Summary:");
+    status!(
         "  Version:   {} -> {}",
         preflight
             .base_version
@@ -223,9 +628,15 @@ async fn run_ship_with_version(
             .unwrap_or_else(|| "none".to_string()),
         next_version
     );
-    println!(
+    status!(
         "  Changelog: {}",
-        if changelog_generated {
+        if changelog_generated && !prereleases.is_empty() {
+            format!(
+                "Auto-generated ({} commits), consolidating {}",
+                preflight.commits_since_tag.len(),
+                prerelease_list(&prereleases)
+            )
+        } else if changelog_generated {
             format!(
                 "Auto-generated ({} commits)",
                 preflight.commits_since_tag.len()
@@ -234,20 +645,93 @@ async fn run_ship_with_version(
             "Existing section (skip)".to_string()
         }
     );
-    println!("  Commit:    chore(release): v{}", next_version);
-    println!("  Tag:       {}", tag_name);
-    println!(
-        "  Push to:   {}/{}",
-        preflight.remote_name, preflight.upstream_branch
-    );
+    status!("  Commit:    chore(release): v{}", next_version);
+    status!("  Tag:       {}", tag_name);
+    match config.schedule {
+        Some(at) => status!(
+            "  Push to:   {}/{} at {}",
+            preflight.remote_name,
+            preflight.upstream_branch,
+            schedule::display_time(at)
+        ),
+        None => status!(
+            "  Push to:   {}/{}",
+            preflight.remote_name,
+            preflight.upstream_branch
+        ),
+    }
+    if !config.publish_steps.is_empty() {
+        let steps: Vec<String> = config
+            .publish_steps
+            .iter()
+            .map(PublishStep::describe)
+            .collect();
+        status!("  Publish:   {}", steps.join(", "));
+    }
+    if config.github_release && !config.prepare {
+        status!("  Release:   GitHub release with the changelog section");
+    }
+    if !config.release_assets.is_empty() {
+        status!("  Assets:    {}", config.release_assets.join(", "));
+    }
+    if config.prepare {
+        status!("  Approval:  required (no tag until `keryx ship --approve`)");
+    }
+    if let Some(required) = config.require_checks {
+        status!(
+            "  Checks:    wait up to {} before tagging",
+            checks::display_duration(required.timeout)
+        );
+    }
 
     if config.dry_run {
-        println!();
-        println!("Dry run complete. No changes made.");
+        status!();
+        status!("Dry run complete. No changes made.");
+        summary.record_llm(llm.usage());
+        if crate::output::is_json() {
+            summary.end_stage();
+            let relative =
+                |path: &PathBuf| path.strip_prefix(workdir).unwrap_or(path).to_path_buf();
+            let plan = ShipPlan {
+                previous_version: preflight.base_version.clone(),
+                version: next_version.clone(),
+                bump_reasoning,
+                tag: tag_name,
+                commit: format!("chore(release): v{}", next_version),
+                version_files: version_files
+                    .iter()
+                    .map(|vf| VersionFilePlan {
+                        path: vf.display_path(),
+                        from: vf.current_version.clone(),
+                        to: next_version.clone(),
+                    })
+                    .collect(),
+                changelogs: pending_changelogs.iter().map(relative).collect(),
+                existing_changelogs: existing_changelogs.iter().map(relative).collect(),
+                consolidates: prereleases.iter().map(|s| s.version.clone()).collect(),
+                push_to: format!("{}/{}", preflight.remote_name, preflight.upstream_branch),
+                scheduled_at: config.schedule,
+                publish_steps: config
+                    .publish_steps
+                    .iter()
+                    .map(PublishStep::describe)
+                    .collect(),
+                risk: RiskPlan {
+                    level: report.level(),
+                    items: report.items(),
+                },
+                summary: summary.clone(),
+            };
+            crate::output::print_json(&plan)?;
+        } else {
+            summary.print(config.summary_format);
+        }
         return Ok(());
     }
 
-    println!();
+    // Time spent waiting on the confirmation prompt is not attributed to a stage
+    summary.end_stage();
+    status!();
     let confirmed = Confirm::new()
         .with_prompt("Proceed?")
         .default(true)
@@ -257,67 +741,334 @@ async fn run_ship_with_version(
     if !confirmed {
         return Err(ShipError::Cancelled);
     }
+    let approved_at = Utc::now();
+
+    // ── Stage 8: Execute ──
+    let journal = Journal::for_repo(workdir, "ship");
 
-    // ── Stage 7: Execute ──
     // 7a. Update version files
     for vf in &version_files {
         update_version_file(vf, &next_version)?;
-        println!("  [DONE] Updated {}", vf.kind);
+        journal.record(
+            journal
+                .entry(OperationKind::FileModified)
+                .arg(vf.path.display().to_string())
+                .detail(format!("{} -> {}", vf.current_version, next_version)),
+        );
+        status!("  [DONE] Updated {}", vf.display_path());
     }
 
     // 7b. Generate and write changelog (if needed)
+    let mut migration_guide = None;
     if changelog_generated {
-        generate_and_write_changelog(
+        let existing_changelog = read_changelog(&pending_changelogs[0])
+            .ok()
+            .flatten()
+            .map(|c| c.raw_content);
+        let metadata = ReleaseMetadata {
+            date: config.release_date.release_date(
+                preflight
+                    .commits_since_tag
+                    .iter()
+                    .map(|c| c.timestamp)
+                    .max(),
+            ),
+            heading: config.heading.clone(),
+            codename: config.codename.resolve(existing_changelog.as_deref()),
+            attributes: config.attributes.clone(),
+            compare_links: get_repo_web_url(repo, config.pr_remote()).map(|repo_url| {
+                CompareLinks {
+                    repo_url,
+                    tag: tag_name.clone(),
+                    // A consolidated release covers everything since the last stable one
+                    previous_tag: if prereleases.is_empty() {
+                        preflight.latest_tag.as_ref().map(|t| t.name.clone())
+                    } else {
+                        previous_stable_tag(repo, &next_version)
+                    },
+                }
+            }),
+            attribution: None,
+            sort: config.entry_sort,
+            source_markers: false,
+            category_aliases,
+            template: config.template.clone(),
+        };
+        migration_guide = generate_and_write_changelog(
             repo,
             llm,
             &preflight.commits_since_tag,
             &next_version,
             preflight.base_version.as_ref(),
-            &effective_changelog_path,
-            config.no_prs,
-            config.no_verify,
-            config.verbose,
+            &pending_changelogs,
+            &prereleases,
+            metadata,
+            &config,
+            summary,
         )
         .await?;
-        println!("  [DONE] Updated CHANGELOG.md");
+        if let Some(path) = &migration_guide {
+            journal.record(
+                journal
+                    .entry(OperationKind::FileModified)
+                    .arg(path.display().to_string())
+                    .detail(format!("added migration guide for {}", next_version)),
+            );
+        }
+        for path in &pending_changelogs {
+            journal.record(
+                journal
+                    .entry(OperationKind::FileModified)
+                    .arg(path.display().to_string())
+                    .detail(format!("added section {}", next_version)),
+            );
+            status!(
+                "  [DONE] Updated {}",
+                path.strip_prefix(workdir).unwrap_or(path).display()
+            );
+        }
+    }
+
+    // Consolidated sections leave the pre-release changelog
+    let mut consolidated_changelog = None;
+    if let Some(path) = prerelease_path.filter(|_| !prereleases.is_empty()) {
+        let versions: Vec<Version> = prereleases.iter().map(|s| s.version.clone()).collect();
+        let removed = remove_sections_from_file(&path, &versions)?;
+        if !removed.is_empty() {
+            journal.record(
+                journal
+                    .entry(OperationKind::FileModified)
+                    .arg(path.display().to_string())
+                    .detail(format!(
+                        "removed sections consolidated into {}",
+                        next_version
+                    )),
+            );
+            status!(
+                "  [DONE] Removed {} from {}",
+                removed
+                    .iter()
+                    .map(Version::to_string)
+                    .collect::<Vec<_>>()
+                    .join(", "),
+                path.strip_prefix(workdir).unwrap_or(&path).display()
+            );
+            consolidated_changelog = Some(path);
+        }
     }
 
     // 7c. Collect files to stage
     let mut files_to_stage: Vec<PathBuf> = version_files.iter().map(|vf| vf.path.clone()).collect();
-    if changelog_generated {
-        files_to_stage.push(effective_changelog_path);
-    }
+    files_to_stage.extend(pending_changelogs);
+    files_to_stage.extend(consolidated_changelog);
+    files_to_stage.extend(migration_guide);
 
     // 7d. Commit, tag, push
+    summary.begin_stage("release");
+    let prepared = config.prepare.then(|| PreparedRelease {
+        tag: tag_name.clone(),
+        target: preflight.upstream_branch.clone(),
+        remote: preflight.remote_name.clone(),
+        prepared_by: approval::identity(&Approver::local(repo)),
+    });
     let commit_message = format!("chore(release): v{}", next_version);
-    let commit_result = executor::commit_and_tag(&commit_message, &tag_name, &files_to_stage)?;
+    let full_message = match &prepared {
+        Some(prepared) => format!("{}\n\n{}", commit_message, prepared.trailers()),
+        None => commit_message.clone(),
+    };
+    // A prepared release always gets its own commit to carry the trailers
+    let commit_result =
+        executor::commit_release(&full_message, &files_to_stage, prepared.is_some())?;
 
     if commit_result.commit_created {
-        println!("  [DONE] Created commit: {}", commit_message);
+        journal.record(
+            journal
+                .entry(OperationKind::CommitCreated)
+                .detail(commit_message.clone()),
+        );
+        status!("  [DONE] Created commit: {}", commit_message);
+    } else {
+        status!("  [SKIP] No changes to commit; using current HEAD");
+    }
+
+    if let Some(prepared) = prepared {
+        summary.record_llm(llm.usage());
+        return stage_prepared_release(repo, preflight, &prepared, &journal, summary, &config)
+            .await;
+    }
+
+    // With required checks the branch is pushed first and the tag waits for CI
+    let branch_pushed = if let Some(required) = config.require_checks {
+        executor::push_branch(&preflight.remote_name, &preflight.upstream_branch).await?;
+        journal.record(
+            journal
+                .entry(OperationKind::Pushed)
+                .arg(&preflight.remote_name)
+                .arg(&preflight.upstream_branch),
+        );
+        status!(
+            "  [DONE] Pushed branch to {}/{}",
+            preflight.remote_name,
+            preflight.upstream_branch
+        );
+        let commit = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .ok_or_else(|| ShipError::GitFailed("Cannot resolve the release commit".into()))?;
+        checks::wait_on_remote(
+            repo,
+            &preflight.remote_name,
+            &preflight.upstream_branch,
+            &commit.to_string(),
+            required,
+        )
+        .await?;
+        true
     } else {
-        println!("  [SKIP] No changes to commit; using current HEAD");
+        false
+    };
+
+    executor::create_tag(&tag_name, None)?;
+    journal.record(journal.entry(OperationKind::TagCreated).arg(&tag_name));
+    status!("  [DONE] Created tag: {}", tag_name);
+
+    summary.record_llm(llm.usage());
+    let records = ReleaseRecordStore::for_repo(workdir);
+    let times = ReleaseTimes {
+        started_at,
+        approved_at,
+        tagged_at: Utc::now(),
+    };
+    save_release_record(
+        repo,
+        &records,
+        &next_version,
+        &tag_name,
+        &changelog_paths,
+        summary,
+        times,
+    );
+
+    if let Some(publish_at) = config.schedule {
+        let commit = repo
+            .head()
+            .ok()
+            .and_then(|head| head.target())
+            .ok_or_else(|| ShipError::GitFailed("Cannot resolve the release commit".into()))?;
+        let pending = PendingRelease {
+            tag: tag_name.clone(),
+            commit: commit.to_string(),
+            remote: preflight.remote_name.clone(),
+            branch: preflight.upstream_branch.clone(),
+            staged_at: Utc::now(),
+            publish_at: Some(publish_at),
+            publish_steps: config
+                .publish_steps
+                .iter()
+                .map(ToString::to_string)
+                .collect(),
+            release_assets: config.release_assets.clone(),
+            no_github_release: !config.github_release,
+            prepared_by: None,
+        };
+        let store = PendingReleaseStore::for_repo(workdir);
+        store.save(&pending)?;
+        status!(
+            "  [DONE] Staged {} for publishing at {}",
+            tag_name,
+            schedule::display_time(publish_at)
+        );
+        summary.record_llm(llm.usage());
+        summary.print(config.summary_format);
+
+        status!();
+        status!(
+            "Waiting until {} to push. Press Ctrl-C to stop waiting; `keryx ship --publish` pushes the staged release at any time.",
+            schedule::display_time(publish_at)
+        );
+        drop(config.lock.take());
+        schedule::wait_until(publish_at).await;
+        let _lock = RepoLock::for_repository(repo, "ship")?;
+
+        // The release may have been published from another terminal meanwhile
+        if store.load()?.is_none_or(|current| current != pending) {
+            status!("  [SKIP] {} was already published", tag_name);
+            return Ok(());
+        }
+        return publish_staged(repo, &store, &pending, &journal).await;
     }
-    println!("  [DONE] Created tag: {}", tag_name);
 
-    match executor::push_with_tags(&preflight.remote_name, &preflight.upstream_branch) {
+    match executor::push_with_tags(&preflight.remote_name, &preflight.upstream_branch).await {
         Ok(()) => {
-            println!(
+            journal.record(
+                journal
+                    .entry(OperationKind::Pushed)
+                    .arg(&preflight.remote_name)
+                    .arg(&preflight.upstream_branch)
+                    .arg(&tag_name),
+            );
+            status!(
                 "  [DONE] Pushed to {}/{}",
-                preflight.remote_name, preflight.upstream_branch
+                preflight.remote_name,
+                preflight.upstream_branch
             );
-            println!();
-            println!("Release {} shipped!", tag_name);
+            mark_release_pushed(&records, &tag_name);
+            status!();
+            status!("Release {} shipped!", tag_name);
+            if config.github_release {
+                github_release::create_for_tag(
+                    repo,
+                    &preflight.remote_name,
+                    workdir,
+                    &tag_name,
+                    &journal,
+                )
+                .await;
+            }
+            publish::run_steps(&config.publish_steps, workdir, &tag_name, &journal)?;
+            assets::upload_to_remote(
+                repo,
+                &preflight.remote_name,
+                workdir,
+                &tag_name,
+                &config.release_assets,
+                &journal,
+            )
+            .await?;
+            integrations::update_released_issues(repo, workdir, &tag_name, &journal).await;
+            packaging::update_released_manifests(
+                repo,
+                &preflight.remote_name,
+                workdir,
+                &tag_name,
+                &journal,
+            )
+            .await;
+            summary.record_llm(llm.usage());
+            summary.print(config.summary_format);
         }
         Err(e) => {
-            // ── Stage 8: Rollback on push failure ──
+            // ── Stage 9: Rollback on push failure ──
             eprintln!("  [FAIL] {}", e);
             eprintln!();
             eprintln!("Rolling back...");
 
-            match executor::rollback(&tag_name, commit_result.commit_created) {
+            // A commit already pushed for the checks stays
+            let reset_commit = commit_result.commit_created && !branch_pushed;
+            match executor::rollback(&tag_name, reset_commit) {
                 Ok(()) => {
+                    journal.record(
+                        journal
+                            .entry(OperationKind::RolledBack)
+                            .arg(&tag_name)
+                            .detail(e.to_string()),
+                    );
                     eprintln!("  [DONE] Deleted tag {}", tag_name);
-                    if commit_result.commit_created {
+                    if let Err(e) = records.remove(&tag_name) {
+                        eprintln!("  [WARN] {}", e);
+                    }
+                    if reset_commit {
                         eprintln!("  [DONE] Reset commit {}", commit_message);
                     }
                     eprintln!();
@@ -326,7 +1077,7 @@ async fn run_ship_with_version(
                 Err(rollback_err) => {
                     eprintln!("  [FAIL] Rollback failed: {}", rollback_err);
                     eprintln!();
-                    if commit_result.commit_created {
+                    if reset_commit {
                         eprintln!(
                             "Manual cleanup may be needed: git tag -d {} && git reset --soft HEAD~1",
                             tag_name
@@ -344,7 +1095,252 @@ async fn run_ship_with_version(
     Ok(())
 }
 
-/// Generate changelog entries and write them to the changelog file.
+/// Push the release staged by `ship --schedule` now (`ship --publish`).
+pub async fn run_publish(dry_run: bool) -> Result<(), ShipError> {
+    let repo = Repository::open(".")
+        .map_err(|e| ShipError::GitFailed(format!("Not a git repository: {}", e)))?;
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
+    let store = PendingReleaseStore::for_repo(workdir);
+    let pending = store.load()?.ok_or(ShipError::NoStagedRelease)?;
+    if pending.prepared_by.is_some() {
+        return Err(ShipError::AwaitingApproval(pending.tag));
+    }
+
+    status!("Staged release:");
+    status!("  Tag:       {}", pending.tag);
+    status!("  Staged:    {}", schedule::display_time(pending.staged_at));
+    if let Some(at) = pending.publish_at {
+        status!("  Scheduled: {}", schedule::display_time(at));
+    }
+    status!("  Push to:   {}/{}", pending.remote, pending.branch);
+
+    check_staged_release(&repo, &pending)?;
+    let steps = staged_publish_steps(&pending)?;
+    if !steps.is_empty() {
+        status!();
+        status!("Publish checks:");
+        publish::check_steps(&steps, workdir)?;
+    }
+    if !pending.release_assets.is_empty() {
+        status!();
+        status!("Release asset checks:");
+        assets::check_assets(&pending.release_assets, workdir)?;
+    }
+    status!();
+    if dry_run {
+        status!("Dry run complete. No changes made.");
+        return Ok(());
+    }
+
+    let journal = Journal::for_repo(workdir, "ship");
+    publish_staged(&repo, &store, &pending, &journal).await
+}
+
+/// Finish `ship --prepare`: push the release commit to its review branch
+/// and record the release as waiting for approval.
+async fn stage_prepared_release(
+    repo: &Repository,
+    preflight: &preflight::PreflightResult,
+    prepared: &PreparedRelease,
+    journal: &Journal,
+    summary: &mut RunSummary,
+    config: &ShipConfig,
+) -> Result<(), ShipError> {
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
+    let review_branch = prepared.review_branch();
+    executor::push_branch(&preflight.remote_name, &review_branch).await?;
+    journal.record(
+        journal
+            .entry(OperationKind::Pushed)
+            .arg(&preflight.remote_name)
+            .arg(&review_branch),
+    );
+    status!(
+        "  [DONE] Pushed release commit to {}/{}",
+        preflight.remote_name,
+        review_branch
+    );
+
+    let commit = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .ok_or_else(|| ShipError::GitFailed("Cannot resolve the release commit".into()))?;
+    PendingReleaseStore::for_repo(workdir).save(&PendingRelease {
+        tag: prepared.tag.clone(),
+        commit: commit.to_string(),
+        remote: preflight.remote_name.clone(),
+        branch: prepared.target.clone(),
+        staged_at: Utc::now(),
+        publish_at: None,
+        publish_steps: Vec::new(),
+        release_assets: Vec::new(),
+        no_github_release: false,
+        prepared_by: Some(prepared.prepared_by.clone()),
+    })?;
+    summary.print(config.summary_format);
+
+    status!();
+    status!(
+        "Release {} is prepared. Another person approves it with:",
+        prepared.tag
+    );
+    status!(
+        "  git fetch {remote} && git checkout {remote}/{branch} && keryx ship --approve",
+        remote = preflight.remote_name,
+        branch = review_branch
+    );
+    Ok(())
+}
+
+/// Make sure `HEAD` and the tag still point at the staged release commit, so
+/// publishing never pushes commits made after the release was staged.
+fn check_staged_release(repo: &Repository, pending: &PendingRelease) -> Result<(), ShipError> {
+    let moved = |reason: String| ShipError::StagedReleaseMoved {
+        tag: pending.tag.clone(),
+        reason,
+    };
+
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .map(|oid| oid.to_string());
+    if head.as_deref() != Some(pending.commit.as_str()) {
+        return Err(moved(format!(
+            "HEAD is no longer the release commit {}",
+            &pending.commit[..pending.commit.len().min(7)]
+        )));
+    }
+
+    let tagged = repo
+        .revparse_single(&format!("refs/tags/{}", pending.tag))
+        .and_then(|object| object.peel_to_commit())
+        .map(|commit| commit.id().to_string());
+    match tagged {
+        Ok(commit) if commit == pending.commit => Ok(()),
+        Ok(_) => Err(moved("the tag points at a different commit".to_string())),
+        Err(_) => Err(moved("the tag no longer exists".to_string())),
+    }
+}
+
+/// Push a staged release and forget it.
+///
+/// A failed push leaves the release staged (commit and tag are kept) so it
+/// can be retried with `ship --publish`.
+async fn publish_staged(
+    repo: &Repository,
+    store: &PendingReleaseStore,
+    pending: &PendingRelease,
+    journal: &Journal,
+) -> Result<(), ShipError> {
+    check_staged_release(repo, pending)?;
+    let steps = staged_publish_steps(pending)?;
+
+    if let Err(e) = executor::push_with_tags(&pending.remote, &pending.branch).await {
+        eprintln!("  [FAIL] {}", e);
+        eprintln!();
+        eprintln!(
+            "{} is still staged. Fix the push issue and run `keryx ship --publish`.",
+            pending.tag
+        );
+        return Err(e);
+    }
+    journal.record(
+        journal
+            .entry(OperationKind::Pushed)
+            .arg(&pending.remote)
+            .arg(&pending.branch)
+            .arg(&pending.tag),
+    );
+    store.clear()?;
+
+    status!("  [DONE] Pushed to {}/{}", pending.remote, pending.branch);
+
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
+    mark_release_pushed(&ReleaseRecordStore::for_repo(workdir), &pending.tag);
+    status!();
+    status!("Release {} shipped!", pending.tag);
+    if !pending.no_github_release {
+        github_release::create_for_tag(repo, &pending.remote, workdir, &pending.tag, journal).await;
+    }
+    publish::run_steps(&steps, workdir, &pending.tag, journal)?;
+    assets::upload_to_remote(
+        repo,
+        &pending.remote,
+        workdir,
+        &pending.tag,
+        &pending.release_assets,
+        journal,
+    )
+    .await?;
+    integrations::update_released_issues(repo, workdir, &pending.tag, journal).await;
+    packaging::update_released_manifests(repo, &pending.remote, workdir, &pending.tag, journal)
+        .await;
+    Ok(())
+}
+
+/// Write the release record for a freshly tagged release. Failures are
+/// reported but do not stop the release; the tag already exists.
+#[allow(clippy::too_many_arguments)]
+fn save_release_record(
+    repo: &Repository,
+    records: &ReleaseRecordStore,
+    version: &Version,
+    tag: &str,
+    changelog_paths: &[PathBuf],
+    summary: &RunSummary,
+    times: ReleaseTimes,
+) {
+    let Some(commit) = repo.head().ok().and_then(|head| head.target()) else {
+        eprintln!("  [WARN] Cannot resolve the release commit; no release record written");
+        return;
+    };
+    let changelogs: Vec<PathBuf> = changelog_paths
+        .iter()
+        .filter(|path| path.exists())
+        .cloned()
+        .collect();
+    let saved = ReleaseRecord::new(
+        repo,
+        &version.to_string(),
+        tag,
+        &commit.to_string(),
+        &changelogs,
+        summary,
+        times,
+    )
+    .and_then(|record| records.save(&record));
+    if let Err(e) = saved {
+        eprintln!("  [WARN] {}", e);
+    }
+}
+
+/// Stamp the release record of `tag` with the push time.
+fn mark_release_pushed(records: &ReleaseRecordStore, tag: &str) {
+    match records.mark_pushed(tag, Utc::now()) {
+        Ok(Some(path)) => status!("  [DONE] Wrote release record {}", path.display()),
+        Ok(None) => {}
+        Err(e) => eprintln!("  [WARN] {}", e),
+    }
+}
+
+/// Publish steps recorded with a staged release.
+fn staged_publish_steps(pending: &PendingRelease) -> Result<Vec<PublishStep>, ShipError> {
+    pending
+        .publish_steps
+        .iter()
+        .map(|step| step.parse().map_err(ShipError::InvalidPublishStep))
+        .collect()
+}
+
+/// Generate changelog entries and write them to every pending changelog file.
 #[allow(clippy::too_many_arguments)]
 async fn generate_and_write_changelog(
     repo: &Repository,
@@ -352,16 +1348,21 @@ async fn generate_and_write_changelog(
     commits: &[crate::git::ParsedCommit],
     version: &Version,
     base_version: Option<&Version>,
-    output_path: &std::path::Path,
-    no_prs: bool,
-    no_verify: bool,
-    verbose: bool,
-) -> Result<(), ShipError> {
+    output_paths: &[PathBuf],
+    prereleases: &[PrereleaseSection],
+    mut metadata: ReleaseMetadata,
+    config: &ShipConfig,
+    summary: &mut RunSummary,
+) -> Result<Option<PathBuf>, ShipError> {
+    let verbose = config.verbose;
+
+    summary.begin_stage("prs");
+
     // Fetch PRs if not disabled
-    let pull_requests = if no_prs {
+    let pull_requests = if config.no_prs {
         Vec::new()
     } else {
-        match fetch_prs(repo).await {
+        match fetch_prs(repo, config.pr_remote(), &PrScope::for_commits(commits)).await {
             Ok(prs) => {
                 if verbose {
                     debug!("Found {} merged PRs for changelog", prs.len());
@@ -372,100 +1373,606 @@ async fn generate_and_write_changelog(
                 if verbose {
                     debug!("Failed to fetch PRs for changelog: {}", e);
                 }
+                summary.degrade(
+                    "pull requests",
+                    format!(
+                        "could not be fetched ({}); entries come from commits only",
+                        e
+                    ),
+                );
                 Vec::new()
             }
         }
     };
 
-    let repo_name = get_repo_name(repo);
+    summary.pull_requests = pull_requests.len();
+
+    let commits = net_commits(repo, commits.to_vec(), verbose);
+
+    let repo_name = get_repo_name(repo, config.pr_remote());
     let input = ChangelogInput {
-        commits: commits.to_vec(),
+        commits,
         pull_requests,
         previous_version: base_version.cloned(),
         repository_name: repo_name,
         project_description: None,
         cli_features: None,
+        product: ProductContext::for_repository(repo),
+        languages: LanguageProfile::for_repository(repo),
+        include_highlights: config.highlights,
+        include_sources: config.attribution.is_enabled(),
+        entry_sort: config.entry_sort,
+        prs_only: false,
     };
 
-    let prompt = build_prompt(&input).map_err(|e| {
-        ShipError::Changelog(crate::error::ChangelogError::ParseFailed(format!(
-            "Failed to build LLM prompt: {}",
-            e
-        )))
-    })?;
+    // PRs with a "## Changelog" section in their description bring their own entries
+    let curated = CuratedNotes::from_pull_requests(&input.pull_requests);
+    let llm_input = curated.llm_input(&input);
+    if !curated.is_empty() {
+        status!(
+            "  [DONE] Using the changelog sections of {} PRs ({} entries)",
+            curated.pr_numbers.len(),
+            curated.entries.len()
+        );
+    }
 
-    println!("  Generating changelog...");
+    // Commits with a "Release-Note:" trailer bring their own, still verified, entry
+    let commit_notes = CommitNotes::from_commits(&llm_input.commits);
+    let prompt_input = commit_notes.llm_input(&llm_input);
+    if !commit_notes.is_empty() {
+        status!(
+            "  [DONE] Using the Release-Note trailers of {} commits ({} entries)",
+            commit_notes.hashes.len(),
+            commit_notes.entries.len()
+        );
+    }
 
-    let completion = llm.generate(&prompt).await.map_err(|e| {
-        ShipError::Changelog(crate::error::ChangelogError::ParseFailed(format!(
-            "LLM generation failed: {}",
-            e.summary()
-        )))
-    })?;
+    summary.begin_stage("generate");
+    let sanitize_options = SanitizeOptions::from_env();
+    let mut changelog_output = if nothing_to_generate(&prompt_input) {
+        ChangelogOutput {
+            entries: Vec::new(),
+            highlights: None,
+        }
+    } else {
+        let prompt = build_prompt(&prompt_input).map_err(|e| {
+            ShipError::Changelog(crate::error::ChangelogError::ParseFailed(format!(
+                "Failed to build LLM prompt: {}",
+                e
+            )))
+        })?;
 
-    let mut changelog_output = completion.output;
+        status!("  Generating changelog...");
+        generate_guarded(
+            llm,
+            &prompt,
+            sanitize_options,
+            &config.constraints,
+            &prompt_input.commits,
+        )
+        .await
+        .map_err(|e| {
+            ShipError::Changelog(crate::error::ChangelogError::ParseFailed(format!(
+                "LLM generation failed: {}",
+                e.summary()
+            )))
+        })?
+        .output
+    };
+    commit_notes.merge_into(&mut changelog_output);
+    if !config.highlights {
+        changelog_output.highlights = None;
+    }
 
-    if changelog_output.entries.is_empty() {
+    // A consolidated release may have nothing new since its last pre-release
+    let carried_over = !prereleases.is_empty();
+    if changelog_output.entries.is_empty() && curated.entries.is_empty() && !carried_over {
         debug!("No changelog entries generated");
         return Err(ShipError::Changelog(
             crate::error::ChangelogError::EmptyOutput,
         ));
     }
 
-    if !no_verify {
+    // Curated entries are the PR author's words and skip verification
+    let entries_generated = changelog_output.entries.len() + curated.entries.len();
+    if config.verify != VerifyMode::Off && !changelog_output.entries.is_empty() {
+        summary.begin_stage("verify");
+        let corpus =
+            SourceCorpus::for_repository(repo, &llm_input.commits, &llm_input.pull_requests);
+        let outcome = drop_unsupported_entries(&changelog_output, &corpus);
+        for (entry, components) in &outcome.dropped {
+            status!(
+                "  [WARN] Removed entry: {} (unknown: {})",
+                entry.description,
+                components.join(", ")
+            );
+        }
+        changelog_output = outcome.output;
+        if changelog_output.entries.is_empty() && curated.entries.is_empty() && !carried_over {
+            return Err(ShipError::Changelog(
+                crate::error::ChangelogError::EmptyOutput,
+            ));
+        }
+
         let repo_path = repo.workdir().ok_or_else(|| {
             ShipError::GitFailed(
-                "Cannot verify in a bare repository. Use --no-verify to skip verification.".into(),
+                "Cannot verify in a bare repository. Use --verify off to skip verification.".into(),
             )
         })?;
 
         check_ripgrep_installed()?;
 
-        println!("  Verifying changelog entries...");
+        status!("  Verifying changelog entries ({})...", config.verify);
+        changelog_output = verify_entries(
+            repo,
+            repo_path,
+            llm,
+            changelog_output,
+            &llm_input.commits,
+            config.verify,
+            sanitize_options,
+            summary,
+        )
+        .await?;
 
-        let evidence = gather_verification_evidence(&changelog_output.entries, repo_path);
-        let draft_json = serde_json::to_string_pretty(&changelog_output).map_err(|e| {
-            ShipError::VerificationFailed(format!("Failed to serialize draft entries: {}", e))
-        })?;
-        let verification_prompt =
-            build_verification_prompt(&draft_json, &evidence).map_err(|e| {
-                ShipError::VerificationFailed(format!("Failed to build verification prompt: {}", e))
-            })?;
+        if changelog_output.entries.is_empty() && curated.entries.is_empty() && !carried_over {
+            debug!("No changelog entries remained after verification");
+            return Err(ShipError::Changelog(
+                crate::error::ChangelogError::EmptyOutput,
+            ));
+        }
+
+        for change in find_missed_changes(repo, &llm_input.commits, &changelog_output.entries) {
+            status!("  [WARN] Possibly missing from changelog: {}", change);
+        }
+    }
+    curated.merge_into(&mut changelog_output);
+
+    if carried_over {
+        changelog_output = consolidate_prereleases(
+            llm,
+            changelog_output,
+            version,
+            prereleases,
+            &input.repository_name,
+            sanitize_options,
+            summary,
+        )
+        .await;
+    }
+
+    let style = StyleGuide::for_repository(repo);
+    let style_report = style.apply(&mut changelog_output);
+    for fix in &style_report.fixes {
+        status!("  [DONE] Style: \"{}\" -> \"{}\"", fix.from, fix.to);
+    }
+    for flag in &style_report.flagged {
+        status!(
+            "  [WARN] Possible misspelling \"{}\" (did you mean {}?): {}",
+            flag.word,
+            flag.suggestions.join(" or "),
+            flag.text
+        );
+    }
+
+    if metadata.codename.is_none() && config.codename.suggest {
+        let used = read_changelog(&output_paths[0])
+            .ok()
+            .flatten()
+            .map(|c| used_codenames(&c.raw_content, &metadata.heading))
+            .unwrap_or_default();
+        metadata.codename = suggest_codename(&changelog_output, &used, llm).await;
+    }
+
+    if config.attribution.is_enabled() {
+        let numbers = cited_pr_numbers(&changelog_output, &config.attribution);
+        let reviewers = if config.no_prs || numbers.is_empty() {
+            HashMap::new()
+        } else {
+            fetch_reviewers(repo, config.pr_remote(), &numbers)
+                .await
+                .unwrap_or_else(|e| {
+                    debug!("Failed to fetch PR reviewers for attribution: {}", e);
+                    HashMap::new()
+                })
+        };
+        metadata.attribution = Some(build_attribution(
+            &changelog_output,
+            &config.attribution,
+            &input.commits,
+            &reviewers,
+        ));
+    }
+
+    summary.record_entries(entries_generated, changelog_output.entries.len());
+
+    summary.begin_stage("write");
+    write_changelogs_with_metadata(output_paths, &changelog_output, version, &metadata)?;
+    summary.end_stage();
+
+    if !config.migration_guide {
+        return Ok(None);
+    }
+    let migration_guide =
+        write_release_migration_guide(repo, llm, &input, version, config, summary).await?;
+    summary.end_stage();
+    Ok(migration_guide)
+}
+
+/// Fold the entries of `version`'s pre-release sections into its stable
+/// section. Duplicates are dropped first; the LLM then rewords the combined
+/// list, which is kept as is when that call fails.
+async fn consolidate_prereleases(
+    llm: &mut LlmRouter,
+    output: ChangelogOutput,
+    version: &Version,
+    prereleases: &[PrereleaseSection],
+    repository_name: &str,
+    sanitize_options: SanitizeOptions,
+    summary: &mut RunSummary,
+) -> ChangelogOutput {
+    let combined = combine(output, prereleases);
+    let versions: Vec<Version> = prereleases.iter().map(|s| s.version.clone()).collect();
+    status!(
+        "  Consolidating {} entries of {}...",
+        combined.entries.len(),
+        prerelease_list(prereleases)
+    );
+    let prompt = build_consolidation_prompt(version, &versions, &combined, repository_name);
+    match llm.request(&ChangelogRequest::new(prompt)).await {
+        Ok(completion) if !completion.output.entries.is_empty() => {
+            let mut consolidated = completion.output;
+            sanitize_output(&mut consolidated, sanitize_options);
+            consolidated.highlights = combined.highlights;
+            consolidated
+        }
+        Ok(_) => combined,
+        Err(e) => {
+            status!(
+                "  [WARN] Consolidation failed ({}); keeping the deduplicated entries",
+                e.summary()
+            );
+            summary.degrade(
+                "pre-release consolidation",
+                format!("failed ({}); entries were only deduplicated", e.summary()),
+            );
+            combined
+        }
+    }
+}
+
+/// Versions of `sections`, comma-separated.
+fn prerelease_list(sections: &[PrereleaseSection]) -> String {
+    sections
+        .iter()
+        .map(|s| s.version.to_string())
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Newest stable release tag below `version`.
+fn previous_stable_tag(repo: &Repository, version: &Version) -> Option<String> {
+    get_all_tags(repo)
+        .ok()?
+        .into_iter()
+        .filter_map(|tag| Some((tag.version?, tag.name)))
+        .filter(|(v, _)| v.pre.is_empty() && v < version)
+        .max_by(|(a, _), (b, _)| a.cmp(b))
+        .map(|(_, name)| name)
+}
 
-        let verified_completion = llm.generate(&verification_prompt).await.map_err(|e| {
+/// Check `output` against the codebase: the rules, or a second LLM pass.
+#[allow(clippy::too_many_arguments)]
+async fn verify_entries(
+    repo: &Repository,
+    repo_path: &Path,
+    llm: &mut LlmRouter,
+    mut output: ChangelogOutput,
+    commits: &[crate::git::ParsedCommit],
+    mode: VerifyMode,
+    sanitize_options: SanitizeOptions,
+    summary: &mut RunSummary,
+) -> Result<ChangelogOutput, ShipError> {
+    let scope = SearchScope::for_commits(repo, commits, ScopeMode::from_env());
+    let evidence = gather_verification_evidence_in(&output.entries, repo_path, &scope);
+    for item in &evidence.unmentioned_api {
+        status!("  [WARN] New public API not in changelog: {}", item);
+    }
+    if let Some(reason) = evidence.degradation() {
+        status!("  [WARN] Verification evidence incomplete: {}", reason);
+        summary.degrade("verification", reason);
+    }
+    if mode == VerifyMode::Rules {
+        let outcome = apply_verification_rules(&output, &evidence);
+        for (entry, reason) in &outcome.dropped {
+            status!("  [WARN] Removed entry: {} ({})", entry.description, reason);
+        }
+        for entry in &outcome.flagged {
+            status!("  [WARN] Low confidence: {}", entry.description);
+        }
+        return Ok(outcome.output);
+    }
+
+    let draft_json = serde_json::to_string_pretty(&output).map_err(|e| {
+        ShipError::VerificationFailed(format!("Failed to serialize draft entries: {}", e))
+    })?;
+    let verification_prompt = build_verification_prompt(&draft_json, &evidence).map_err(|e| {
+        ShipError::VerificationFailed(format!("Failed to build verification prompt: {}", e))
+    })?;
+
+    let verified_completion = llm
+        .request(&VerificationRequest::new(verification_prompt))
+        .await
+        .map_err(|e| {
             ShipError::VerificationFailed(format!("LLM verification failed: {}", e.summary()))
         })?;
 
-        changelog_output = verified_completion.output;
+    let draft_highlights = output.highlights.take();
+    output = verified_completion.output;
+    sanitize_output(&mut output, sanitize_options);
+    if output.highlights.is_none() {
+        output.highlights = draft_highlights;
+    }
+    Ok(output)
+}
 
-        if changelog_output.entries.is_empty() {
-            debug!("No changelog entries remained after verification");
-            return Err(ShipError::Changelog(
-                crate::error::ChangelogError::EmptyOutput,
-            ));
+/// Write upgrade steps for a major release to `MIGRATIONS.md`
+/// (`--migration-guide`), from the breaking commits and PRs since the last
+/// release of the previous major line. Returns the file written, if any.
+async fn write_release_migration_guide(
+    repo: &Repository,
+    llm: &mut LlmRouter,
+    input: &ChangelogInput,
+    version: &Version,
+    config: &ShipConfig,
+    summary: &mut RunSummary,
+) -> Result<Option<PathBuf>, ShipError> {
+    if !is_major_release(input.previous_version.as_ref(), version) {
+        return Ok(None);
+    }
+    let workdir = repo.workdir().ok_or_else(|| {
+        ShipError::GitFailed("Cannot write a migration guide in a bare repository".into())
+    })?;
+
+    // Pre-releases of this major line already shipped some breaking changes
+    let tags =
+        get_all_tags(repo).map_err(|e| ShipError::GitFailed(format!("Cannot read tags: {}", e)))?;
+    let base = last_major_release(&tags, version);
+    let (commits, since_time) = match base {
+        Some(tag) => {
+            let head = repo
+                .head()
+                .and_then(|head| head.peel_to_commit())
+                .map_err(|e| ShipError::GitFailed(format!("Cannot resolve HEAD: {}", e)))?;
+            let commits = fetch_commits(repo, tag.oid, head.id(), false).map_err(|e| {
+                ShipError::GitFailed(format!("Cannot read commits since {}: {}", tag.name, e))
+            })?;
+            let since_time = repo
+                .find_commit(tag.oid)
+                .ok()
+                .and_then(|c| DateTime::from_timestamp(c.time().seconds(), 0));
+            (commits, since_time)
         }
+        None => (input.commits.clone(), None),
+    };
+    let pull_requests: Vec<_> = input
+        .pull_requests
+        .iter()
+        .filter(|pr| match (since_time, pr.merged_at) {
+            (Some(since), Some(merged)) => merged >= since,
+            _ => true,
+        })
+        .cloned()
+        .collect();
+
+    let since = base
+        .and_then(|tag| tag.version.as_ref())
+        .or(input.previous_version.as_ref());
+    let migration = MigrationInput::new(
+        version,
+        since,
+        &commits,
+        &pull_requests,
+        &input.repository_name,
+    );
+    if migration.is_empty() {
+        status!("  [SKIP] No breaking changes; no migration guide");
+        return Ok(None);
     }
 
-    write_changelog(output_path, &changelog_output, version)?;
+    summary.begin_stage("migration");
+    status!(
+        "  Writing migration guide ({} breaking commits, {} PRs)...",
+        migration.commits.len(),
+        migration.pull_requests.len()
+    );
+    let sanitize_options = SanitizeOptions::from_env();
+    let mut steps = llm
+        .request(&ChangelogRequest::new(build_migration_prompt(&migration)))
+        .await
+        .map_err(|e| {
+            ShipError::Changelog(crate::error::ChangelogError::ParseFailed(format!(
+                "Migration guide generation failed: {}",
+                e.summary()
+            )))
+        })?
+        .output;
+    steps.highlights = None;
+    sanitize_output(&mut steps, sanitize_options);
+
+    // Steps are checked against the codebase like changelog entries
+    if config.verify != VerifyMode::Off && !steps.entries.is_empty() {
+        check_ripgrep_installed()?;
+        status!("  Verifying migration steps ({})...", config.verify);
+        steps = verify_entries(
+            repo,
+            workdir,
+            llm,
+            steps,
+            &migration.commits,
+            config.verify,
+            sanitize_options,
+            summary,
+        )
+        .await?;
+    }
 
-    Ok(())
+    let guide = MigrationGuide::new(&migration, steps);
+    if guide.is_empty() {
+        status!("  [SKIP] No breaking change needs action from users; no migration guide");
+        return Ok(None);
+    }
+    let path = workdir.join(MIGRATIONS_FILE);
+    write_migration_guide(&path, &guide)?;
+    status!(
+        "  [DONE] Wrote {} migration steps to {}",
+        guide.steps.len(),
+        MIGRATIONS_FILE
+    );
+    Ok(Some(path))
 }
 
 /// Fetch PRs for changelog generation (best-effort).
-async fn fetch_prs(repo: &Repository) -> Result<Vec<crate::github::PullRequest>, anyhow::Error> {
-    use crate::github::auth::get_github_token;
-    use crate::github::prs::{fetch_merged_prs, parse_github_remote};
+async fn fetch_prs(
+    repo: &Repository,
+    remote: &str,
+    scope: &PrScope,
+) -> Result<Vec<crate::github::PullRequest>, anyhow::Error> {
+    use crate::github::prs::parse_github_remote;
+    use crate::github::shared_client;
+
+    let client = shared_client().await?;
+    let remote = repo.find_remote(remote)?;
+    let url = remote
+        .url()
+        .ok_or_else(|| anyhow::anyhow!("No remote URL"))?;
+    let (owner, repo_name) = parse_github_remote(url)?;
+    let prs = client.scoped_prs(&owner, &repo_name, scope, None).await?;
+    Ok(prs)
+}
+
+/// Fetch the reviewers of the given PRs for attribution (best-effort).
+async fn fetch_reviewers(
+    repo: &Repository,
+    remote: &str,
+    numbers: &[u64],
+) -> Result<HashMap<u64, Vec<String>>, anyhow::Error> {
+    use crate::github::prs::parse_github_remote;
+    use crate::github::shared_client;
+
+    let client = shared_client().await?;
+    let remote = repo.find_remote(remote)?;
+    let url = remote
+        .url()
+        .ok_or_else(|| anyhow::anyhow!("No remote URL"))?;
+    let (owner, repo_name) = parse_github_remote(url)?;
+    let mut reviewers = HashMap::new();
+    for &number in numbers {
+        reviewers.insert(
+            number,
+            client.pr_reviewers(&owner, &repo_name, number).await?,
+        );
+    }
+    Ok(reviewers)
+}
+
+/// Gather the inputs of the pre-confirmation risk report.
+///
+/// Every check is best-effort: a check that cannot run is reported as
+/// skipped instead of failing the release.
+async fn build_risk_report(
+    repo: &Repository,
+    config: &ShipConfig,
+    preflight: &preflight::PreflightResult,
+) -> RiskReport {
+    let commits = &preflight.commits_since_tag;
+    let since_oid = preflight.latest_tag.as_ref().map(|t| t.oid);
+
+    let diff = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .and_then(|head| risk::diff_size(repo, since_oid, head).ok());
+
+    let since_time = since_oid
+        .and_then(|oid| repo.find_commit(oid).ok())
+        .and_then(|commit| DateTime::from_timestamp(commit.time().seconds(), 0));
+    let upstream = format!("{}/{}", preflight.remote_name, preflight.upstream_branch);
+    let force_pushes =
+        risk::count_force_pushes(repo, &format!("refs/remotes/{}", upstream), since_time);
+
+    let open_prs = if config.no_prs {
+        None
+    } else {
+        match fetch_open_prs(repo, config.pr_remote(), &preflight.upstream_branch).await {
+            Ok(prs) => Some(prs),
+            Err(e) => {
+                if config.verbose {
+                    eprintln!("Could not fetch open PRs: {}", e);
+                }
+                None
+            }
+        }
+    };
+
+    RiskReport {
+        since_tag: preflight.latest_tag.as_ref().map(|t| t.name.clone()),
+        commits: commits.len(),
+        breaking_commits: commits.iter().filter(|c| c.breaking).count(),
+        diff,
+        upstream,
+        force_pushes,
+        open_prs,
+    }
+}
 
-    let token = get_github_token().await?;
-    let remote = repo.find_remote("origin")?;
+/// Fetch open PRs targeting `base` (best-effort).
+async fn fetch_open_prs(
+    repo: &Repository,
+    remote: &str,
+    base: &str,
+) -> Result<Vec<crate::github::PullRequest>, anyhow::Error> {
+    use crate::github::prs::parse_github_remote;
+    use crate::github::shared_client;
+
+    let client = shared_client().await?;
+    let remote = repo.find_remote(remote)?;
     let url = remote
         .url()
         .ok_or_else(|| anyhow::anyhow!("No remote URL"))?;
     let (owner, repo_name) = parse_github_remote(url)?;
-    let prs = fetch_merged_prs(&token, &owner, &repo_name, None, None, None).await?;
+    let prs = client.open_prs(&owner, &repo_name, base).await?;
     Ok(prs)
 }
 
+/// Warn when the changelog's newest version was never tagged: the range
+/// from the latest tag then includes commits already written up.
+fn warn_untagged_changelog(repo: &Repository, workdir: &Path, outputs: &[PathBuf]) {
+    let Some(output) = outputs.first() else {
+        return;
+    };
+    let Some(version) = read_changelog(&resolve_changelog_path(workdir, output))
+        .ok()
+        .flatten()
+        .and_then(|c| c.latest_version)
+    else {
+        return;
+    };
+    let relative = output.strip_prefix(workdir).unwrap_or(output);
+    if let Ok(Some(untagged)) = find_untagged_release(repo, relative, &version) {
+        let tag = untagged
+            .latest_tag
+            .map(|t| format!("the latest tag is {}", t.name))
+            .unwrap_or_else(|| "there are no version tags".to_string());
+        status!(
+            "  [WARN] {} is at {} but {}; tag that release or pass --set-version",
+            output.display(),
+            version,
+            tag
+        );
+    }
+}
+
 /// Detect the changelog file path from common names.
 fn detect_changelog_path(root: &std::path::Path) -> Option<PathBuf> {
     let candidates = ["CHANGELOG.md", "CHANGES.md", "HISTORY.md"];
@@ -491,10 +1998,10 @@ fn is_default_changelog_output(output: &Path) -> bool {
 }
 
 /// Get the repository name from the remote URL.
-fn get_repo_name(repo: &Repository) -> String {
+fn get_repo_name(repo: &Repository, remote: &str) -> String {
     use crate::github::prs::parse_github_remote;
 
-    repo.find_remote("origin")
+    repo.find_remote(remote)
         .ok()
         .and_then(|r| r.url().map(String::from))
         .and_then(|url| parse_github_remote(&url).ok())
@@ -502,6 +2009,14 @@ fn get_repo_name(repo: &Repository) -> String {
         .unwrap_or_else(|| "repository".to_string())
 }
 
+/// Get the repository's web URL from a remote (GitHub only).
+fn get_repo_web_url(repo: &Repository, remote: &str) -> Option<String> {
+    use crate::github::prs::github_web_url;
+
+    let remote = repo.find_remote(remote).ok()?;
+    github_web_url(remote.url()?).ok()
+}
+
 /// Suggest the next patch version when a tag collision is detected.
 fn suggest_next_version(version: &Version) -> Version {
     Version::new(version.major, version.minor, version.patch + 1)
@@ -10,18 +10,51 @@ use semver::Version;
 
 use crate::error::ShipError;
 use crate::git::ParsedCommit;
-use crate::git::commits::fetch_commits;
+use crate::git::commits::walk_commits;
 use crate::git::range::find_root_commit;
 use crate::git::tags::{TagInfo, get_all_tags, get_latest_reachable_tag};
+use crate::git::upstream::{UpstreamExclusion, resolve_upstreams, upstream_oids};
 use crate::llm::{Provider, ProviderSelection};
+use crate::ollama::HOST_ENV;
+
+/// Remote pushed to when the branch has no upstream and
+/// `--allow-no-upstream` is set.
+const FALLBACK_PUSH_REMOTE: &str = "origin";
+
+/// Preflight failures the user chose to downgrade to warnings.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct PreflightOverrides {
+    /// Ship with uncommitted changes; they stay out of the release commit.
+    pub allow_dirty: bool,
+    /// Ship although the branch is behind or has diverged from its upstream.
+    pub allow_behind: bool,
+    /// Ship a branch without an upstream, pushing it to `origin`.
+    pub allow_no_upstream: bool,
+}
+
+/// How the local branch relates to its upstream.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteSync {
+    /// Equal to or ahead of the upstream.
+    UpToDate,
+    /// Behind or diverged, allowed by `--allow-behind`.
+    Behind,
+    /// No upstream configured, allowed by `--allow-no-upstream`; not checked.
+    NoUpstream,
+}
 
 /// Result of all preflight checks.
 pub struct PreflightResult {
     pub current_branch: String,
     pub remote_name: String,
     pub upstream_branch: String,
+    /// Uncommitted changes were allowed by `--allow-dirty`.
+    pub dirty_working_tree: bool,
+    pub remote_sync: RemoteSync,
     pub latest_tag: Option<TagInfo>,
     pub commits_since_tag: Vec<ParsedCommit>,
+    /// Upstream refs whose commits were left out of `commits_since_tag`.
+    pub excluded_upstream: Vec<UpstreamExclusion>,
     pub llm_available: bool,
     pub base_version: Option<Version>,
 }
@@ -38,23 +71,49 @@ struct TrackingBranch {
 /// 2. Up to date with remote
 /// 3. Commits exist since last tag
 /// 4. LLM available (if needed)
+///
+/// The first two, and a missing upstream, are downgraded to warnings by the
+/// matching `overrides`. Commits reachable from a ref of `exclude_upstream`
+/// are left out of the release.
 pub fn run_checks(
     repo: &Repository,
     _no_llm_bump: bool,
     provider_selection: ProviderSelection,
+    overrides: PreflightOverrides,
+    exclude_upstream: &[String],
     verbose: bool,
 ) -> Result<PreflightResult, ShipError> {
     // 1. Clean working tree
-    check_clean_working_tree(verbose)?;
+    let dirty_working_tree = match check_clean_working_tree(verbose) {
+        Ok(()) => false,
+        Err(ShipError::DirtyWorkingTree) if overrides.allow_dirty => true,
+        Err(e) => return Err(e),
+    };
 
     // Get branch info
     let current_branch = get_current_branch(repo)?;
-    let tracking = get_tracking_branch(repo, &current_branch)?;
-    let remote_name = tracking.remote;
-    let upstream_branch = tracking.branch;
+    let tracking = match get_tracking_branch(repo, &current_branch) {
+        Ok(tracking) => Some(tracking),
+        Err(ShipError::MissingUpstreamTracking { .. }) if overrides.allow_no_upstream => None,
+        Err(e) => return Err(e),
+    };
 
     // 2. Up to date with remote
-    check_remote_sync(&remote_name, &upstream_branch, verbose)?;
+    let (remote_name, upstream_branch, remote_sync) = match tracking {
+        Some(tracking) => {
+            let sync = match check_remote_sync(&tracking.remote, &tracking.branch, verbose) {
+                Ok(()) => RemoteSync::UpToDate,
+                Err(ShipError::BehindRemote) if overrides.allow_behind => RemoteSync::Behind,
+                Err(e) => return Err(e),
+            };
+            (tracking.remote, tracking.branch, sync)
+        }
+        None => (
+            FALLBACK_PUSH_REMOTE.to_string(),
+            current_branch.clone(),
+            RemoteSync::NoUpstream,
+        ),
+    };
 
     // 3. Commits exist since last reachable stable semver tag
     // Uses commit-graph reachability from HEAD so multi-branch workflows
@@ -78,7 +137,11 @@ pub fn run_checks(
         .map(|c| c.id())
         .map_err(|e| ShipError::GitFailed(format!("Could not determine HEAD commit: {}", e)))?;
 
-    let mut commits = fetch_commits(repo, from_oid, to_oid, false)
+    let excluded_upstream = resolve_upstreams(repo, exclude_upstream)
+        .map_err(|e| ShipError::GitFailed(e.to_string()))?;
+    let mut commits: Vec<ParsedCommit> = walk_commits(repo, from_oid, to_oid, false)
+        .and_then(|walk| walk.hide(&upstream_oids(&excluded_upstream)))
+        .and_then(|walk| walk.collect())
         .map_err(|e| ShipError::GitFailed(e.to_string()))?;
 
     // Include the root commit for initial releases (no tags).
@@ -111,8 +174,11 @@ pub fn run_checks(
         current_branch,
         remote_name,
         upstream_branch,
+        dirty_working_tree,
+        remote_sync,
         latest_tag,
         commits_since_tag: commits,
+        excluded_upstream,
         llm_available,
         base_version,
     })
@@ -280,6 +346,30 @@ fn check_provider_available(provider: Provider, verbose: bool) -> bool {
     let tool_name = match provider {
         Provider::Claude => "claude",
         Provider::Codex => "codex",
+        // A remote server needs no local install
+        Provider::Ollama if std::env::var(HOST_ENV).is_ok_and(|v| !v.trim().is_empty()) => {
+            if verbose {
+                eprintln!("  LLM provider {} server set in {}", provider, HOST_ENV);
+            }
+            return true;
+        }
+        Provider::Ollama => "ollama",
+        Provider::AnthropicApi | Provider::OpenAiApi => {
+            let key = provider.api().expect("API provider").key_env();
+            let set = std::env::var(key).is_ok_and(|v| !v.trim().is_empty());
+            if verbose {
+                eprintln!(
+                    "  LLM provider {} {}",
+                    provider,
+                    if set {
+                        format!("key found in {}", key)
+                    } else {
+                        format!("needs {}", key)
+                    }
+                );
+            }
+            return set;
+        }
     };
 
     match which::which(tool_name) {
@@ -0,0 +1,481 @@
+//! Publish steps that run after a successful release push.
+//!
+//! Steps are given as `--publish-step` values (or the comma-separated
+//! `KERYX_PUBLISH_STEPS` environment variable):
+//!
+//! - `cargo` / `npm`: run `cargo publish` / `npm publish` in the project root
+//! - `cargo:dry-run` / `npm:dry-run`: the same with `--dry-run`
+//! - `run:<command>`: a custom shell command, with `KERYX_RELEASE_TAG` set
+//!
+//! Each step's environment (tool, manifest, registry credentials) is checked
+//! during preflight, before anything is committed. Steps run in order once the
+//! tag is pushed; the first failure stops the remaining steps. The pushed tag
+//! is never rolled back, since others may already have fetched it.
+
+use std::env;
+use std::fmt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::str::FromStr;
+
+use tracing::warn;
+
+use crate::error::ShipError;
+use crate::journal::{Journal, OperationKind};
+use crate::status;
+
+/// Environment variable listing publish steps when no flag is given.
+const PUBLISH_STEPS_ENV_VAR: &str = "KERYX_PUBLISH_STEPS";
+
+/// Environment variable carrying the release tag into custom commands.
+pub const RELEASE_TAG_ENV_VAR: &str = "KERYX_RELEASE_TAG";
+
+/// What a publish step runs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PublishTarget {
+    /// `cargo publish`
+    Cargo,
+    /// `npm publish`
+    Npm,
+    /// A custom shell command.
+    Command(String),
+}
+
+/// One post-push publish step.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PublishStep {
+    pub target: PublishTarget,
+    /// Pass `--dry-run` to the registry tool (built-in targets only).
+    pub dry_run: bool,
+}
+
+impl FromStr for PublishStep {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let trimmed = s.trim();
+        if let Some(command) = trimmed.strip_prefix("run:") {
+            let command = command.trim();
+            if command.is_empty() {
+                return Err("'run:' needs a command (e.g. 'run:./scripts/publish.sh')".into());
+            }
+            return Ok(PublishStep {
+                target: PublishTarget::Command(command.to_string()),
+                dry_run: false,
+            });
+        }
+
+        let (name, dry_run) = match trimmed.split_once(':') {
+            Some((name, "dry-run")) => (name, true),
+            Some((_, option)) => {
+                return Err(format!(
+                    "unknown publish step option '{}' (expected 'dry-run')",
+                    option
+                ));
+            }
+            None => (trimmed, false),
+        };
+        let target = match name.to_lowercase().as_str() {
+            "cargo" => PublishTarget::Cargo,
+            "npm" => PublishTarget::Npm,
+            _ => {
+                return Err(format!(
+                    "invalid publish step '{}' (expected 'cargo', 'npm', their ':dry-run' forms, or 'run:<command>')",
+                    s
+                ));
+            }
+        };
+        Ok(PublishStep { target, dry_run })
+    }
+}
+
+impl fmt::Display for PublishStep {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match &self.target {
+            PublishTarget::Cargo => "cargo",
+            PublishTarget::Npm => "npm",
+            PublishTarget::Command(command) => return write!(f, "run:{}", command),
+        };
+        if self.dry_run {
+            write!(f, "{}:dry-run", name)
+        } else {
+            f.write_str(name)
+        }
+    }
+}
+
+impl PublishStep {
+    /// Human-readable command line of the step.
+    pub fn describe(&self) -> String {
+        let base = match &self.target {
+            PublishTarget::Cargo => "cargo publish",
+            PublishTarget::Npm => "npm publish",
+            PublishTarget::Command(command) => return command.clone(),
+        };
+        if self.dry_run {
+            format!("{} --dry-run", base)
+        } else {
+            base.to_string()
+        }
+    }
+
+    /// Check that the step can run in `root` with the current environment.
+    pub fn check_environment(&self, root: &Path) -> Result<(), String> {
+        self.check_environment_with(
+            root,
+            |var| env::var(var).ok(),
+            |tool| which::which(tool).is_ok(),
+        )
+    }
+
+    fn check_environment_with(
+        &self,
+        root: &Path,
+        var: impl Fn(&str) -> Option<String>,
+        has_tool: impl Fn(&str) -> bool,
+    ) -> Result<(), String> {
+        let var = |name: &str| var(name).filter(|value| !value.trim().is_empty());
+        let (tool, manifest) = match &self.target {
+            PublishTarget::Command(_) => return Ok(()),
+            PublishTarget::Cargo => ("cargo", "Cargo.toml"),
+            PublishTarget::Npm => ("npm", "package.json"),
+        };
+
+        if !has_tool(tool) {
+            return Err(format!("{} is not installed or not on PATH", tool));
+        }
+        if !root.join(manifest).is_file() {
+            return Err(format!("no {} in the project root", manifest));
+        }
+        // Dry runs never reach the registry, so they need no credentials
+        if self.dry_run {
+            return Ok(());
+        }
+
+        let home = var("HOME").map(PathBuf::from);
+        match self.target {
+            PublishTarget::Cargo => {
+                let cargo_home = var("CARGO_HOME")
+                    .map(PathBuf::from)
+                    .or_else(|| home.map(|h| h.join(".cargo")));
+                let has_credentials = cargo_home.is_some_and(|dir| {
+                    dir.join("credentials.toml").is_file() || dir.join("credentials").is_file()
+                });
+                if var("CARGO_REGISTRY_TOKEN").is_none() && !has_credentials {
+                    return Err(
+                        "CARGO_REGISTRY_TOKEN is not set and `cargo login` has not been run"
+                            .to_string(),
+                    );
+                }
+            }
+            PublishTarget::Npm => {
+                let npmrc_has_token = [Some(root.to_path_buf()), home]
+                    .into_iter()
+                    .flatten()
+                    .filter_map(|dir| std::fs::read_to_string(dir.join(".npmrc")).ok())
+                    .any(|content| content.contains("_authToken"));
+                if var("NODE_AUTH_TOKEN").is_none()
+                    && var("NPM_TOKEN").is_none()
+                    && !npmrc_has_token
+                {
+                    return Err(
+                        "NODE_AUTH_TOKEN/NPM_TOKEN is not set and no .npmrc has an auth token"
+                            .to_string(),
+                    );
+                }
+            }
+            PublishTarget::Command(_) => {}
+        }
+        Ok(())
+    }
+
+    fn command(&self, root: &Path, tag: &str) -> Command {
+        let mut cmd = match &self.target {
+            PublishTarget::Cargo | PublishTarget::Npm => {
+                let mut cmd = Command::new(match self.target {
+                    PublishTarget::Cargo => "cargo",
+                    _ => "npm",
+                });
+                cmd.arg("publish");
+                if self.dry_run {
+                    cmd.arg("--dry-run");
+                }
+                cmd
+            }
+            PublishTarget::Command(command) => {
+                let mut cmd = if cfg!(windows) {
+                    let mut cmd = Command::new("cmd");
+                    cmd.arg("/C");
+                    cmd
+                } else {
+                    let mut cmd = Command::new("sh");
+                    cmd.arg("-c");
+                    cmd
+                };
+                cmd.arg(command);
+                cmd
+            }
+        };
+        cmd.current_dir(root).env(RELEASE_TAG_ENV_VAR, tag);
+        cmd
+    }
+
+    /// Run the step in `root`, streaming its output.
+    pub fn run(&self, root: &Path, tag: &str) -> Result<(), String> {
+        let status = self
+            .command(root, tag)
+            .status()
+            .map_err(|e| format!("failed to start: {}", e))?;
+        if status.success() {
+            Ok(())
+        } else {
+            Err(match status.code() {
+                Some(code) => format!("exited with status {}", code),
+                None => "terminated by a signal".to_string(),
+            })
+        }
+    }
+}
+
+/// Use the steps from the CLI, falling back to `KERYX_PUBLISH_STEPS`.
+///
+/// Invalid entries in the environment variable are skipped with a warning.
+pub fn steps_from_flags_or_env(steps: Vec<PublishStep>) -> Vec<PublishStep> {
+    if !steps.is_empty() {
+        return steps;
+    }
+    let Ok(value) = env::var(PUBLISH_STEPS_ENV_VAR) else {
+        return Vec::new();
+    };
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|s| !s.is_empty())
+        .filter_map(|s| match s.parse() {
+            Ok(step) => Some(step),
+            Err(e) => {
+                warn!("Ignoring {} entry: {}", PUBLISH_STEPS_ENV_VAR, e);
+                None
+            }
+        })
+        .collect()
+}
+
+/// Check every step's environment, printing one preflight line per step.
+pub fn check_steps(steps: &[PublishStep], root: &Path) -> Result<(), ShipError> {
+    for step in steps {
+        step.check_environment(root)
+            .map_err(|reason| ShipError::PublishStepNotReady {
+                step: step.describe(),
+                reason,
+            })?;
+        status!("  [PASS] Ready to run {}", step.describe());
+    }
+    Ok(())
+}
+
+/// Run `steps` in order after `tag` was pushed.
+///
+/// Stops at the first failure; the remaining steps are reported as skipped
+/// and nothing is rolled back.
+pub fn run_steps(
+    steps: &[PublishStep],
+    root: &Path,
+    tag: &str,
+    journal: &Journal,
+) -> Result<(), ShipError> {
+    if steps.is_empty() {
+        return Ok(());
+    }
+
+    status!();
+    status!("Publish steps:");
+    for (i, step) in steps.iter().enumerate() {
+        match step.run(root, tag) {
+            Ok(()) => {
+                journal.record(
+                    journal
+                        .entry(OperationKind::Published)
+                        .arg(step.to_string())
+                        .arg(tag),
+                );
+                status!("  [DONE] {}", step.describe());
+            }
+            Err(reason) => {
+                eprintln!("  [FAIL] {}: {}", step.describe(), reason);
+                for skipped in &steps[i + 1..] {
+                    eprintln!("  [SKIP] {}", skipped.describe());
+                }
+                eprintln!();
+                eprintln!(
+                    "{} is pushed and stays in place. Fix the problem and run the remaining steps manually.",
+                    tag
+                );
+                return Err(ShipError::PublishStepFailed {
+                    step: step.describe(),
+                    reason,
+                });
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use tempfile::TempDir;
+
+    fn step(s: &str) -> PublishStep {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse_and_display_round_trip() {
+        for input in [
+            "cargo",
+            "npm",
+            "cargo:dry-run",
+            "npm:dry-run",
+            "run:make publish",
+        ] {
+            assert_eq!(step(input).to_string(), input);
+        }
+        assert_eq!(step("Cargo").target, PublishTarget::Cargo);
+        assert_eq!(step("npm:dry-run").describe(), "npm publish --dry-run");
+        assert_eq!(step("run: ./publish.sh ").describe(), "./publish.sh");
+    }
+
+    #[test]
+    fn test_parse_rejects_unknown_steps() {
+        assert!("pypi".parse::<PublishStep>().is_err());
+        assert!("cargo:force".parse::<PublishStep>().is_err());
+        assert!("run:".parse::<PublishStep>().is_err());
+    }
+
+    #[test]
+    fn test_cargo_requires_token_or_credentials() {
+        let root = TempDir::new().unwrap();
+        std::fs::write(root.path().join("Cargo.toml"), "[package]\n").unwrap();
+        let home = TempDir::new().unwrap();
+        let home_path = home.path().to_string_lossy().to_string();
+        let cargo = step("cargo");
+
+        let no_env = |name: &str| (name == "HOME").then(|| home_path.clone());
+        let err = cargo
+            .check_environment_with(root.path(), no_env, |_| true)
+            .unwrap_err();
+        assert!(err.contains("CARGO_REGISTRY_TOKEN"));
+
+        let with_token = |name: &str| match name {
+            "CARGO_REGISTRY_TOKEN" => Some("secret".to_string()),
+            _ => None,
+        };
+        assert!(
+            cargo
+                .check_environment_with(root.path(), with_token, |_| true)
+                .is_ok()
+        );
+
+        std::fs::create_dir(home.path().join(".cargo")).unwrap();
+        std::fs::write(home.path().join(".cargo/credentials.toml"), "").unwrap();
+        assert!(
+            cargo
+                .check_environment_with(root.path(), no_env, |_| true)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    fn test_dry_run_skips_credentials_but_not_manifest() {
+        let root = TempDir::new().unwrap();
+        let npm = step("npm:dry-run");
+
+        let err = npm
+            .check_environment_with(root.path(), |_| None, |_| true)
+            .unwrap_err();
+        assert_eq!(err, "no package.json in the project root");
+
+        std::fs::write(root.path().join("package.json"), "{}").unwrap();
+        assert!(
+            npm.check_environment_with(root.path(), |_| None, |_| true)
+                .is_ok()
+        );
+        let err = npm
+            .check_environment_with(root.path(), |_| None, |_| false)
+            .unwrap_err();
+        assert!(err.contains("npm is not installed"));
+    }
+
+    #[test]
+    fn test_npm_accepts_npmrc_token() {
+        let root = TempDir::new().unwrap();
+        std::fs::write(root.path().join("package.json"), "{}").unwrap();
+        let npm = step("npm");
+
+        assert!(
+            npm.check_environment_with(root.path(), |_| None, |_| true)
+                .is_err()
+        );
+        std::fs::write(
+            root.path().join(".npmrc"),
+            "//registry.npmjs.org/:_authToken=${NPM_TOKEN}\n",
+        )
+        .unwrap();
+        assert!(
+            npm.check_environment_with(root.path(), |_| None, |_| true)
+                .is_ok()
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_custom_command_gets_release_tag() {
+        let root = TempDir::new().unwrap();
+        let custom = step("run:test \"$KERYX_RELEASE_TAG\" = v1.2.0");
+        assert!(custom.run(root.path(), "v1.2.0").is_ok());
+        assert_eq!(
+            custom.run(root.path(), "v9.9.9").unwrap_err(),
+            "exited with status 1"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_run_steps_stops_at_first_failure() {
+        let root = TempDir::new().unwrap();
+        let journal = Journal::for_repo(root.path(), "ship");
+        let steps = vec![
+            step("run:touch first"),
+            step("run:exit 3"),
+            step("run:touch third"),
+        ];
+
+        let err = run_steps(&steps, root.path(), "v1.0.0", &journal).unwrap_err();
+        assert!(matches!(err, ShipError::PublishStepFailed { .. }));
+        assert!(root.path().join("first").exists());
+        assert!(!root.path().join("third").exists());
+
+        let entries = journal.read_all().unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].operation, OperationKind::Published);
+        assert_eq!(entries[0].args, vec!["run:touch first", "v1.0.0"]);
+    }
+
+    #[test]
+    #[serial]
+    fn test_steps_from_env() {
+        temp_env::with_var(
+            PUBLISH_STEPS_ENV_VAR,
+            Some("cargo, bogus, npm:dry-run"),
+            || {
+                let steps = steps_from_flags_or_env(Vec::new());
+                assert_eq!(steps, vec![step("cargo"), step("npm:dry-run")]);
+
+                let flagged = steps_from_flags_or_env(vec![step("npm")]);
+                assert_eq!(flagged, vec![step("npm")]);
+            },
+        );
+    }
+}
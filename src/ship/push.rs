@@ -0,0 +1,370 @@
+//! Pushing the release to the remote.
+//!
+//! Pushes go through libgit2 first, so shipping works without a `git`
+//! binary and with credentials only libgit2 sees (ssh-agent, the configured
+//! credential helper). When libgit2 cannot connect or authenticate, the
+//! push is retried with `git push`, which knows the user's full transport
+//! and credential setup. A push the remote rejected is never retried with
+//! the other backend.
+//!
+//! Release pushes, which carry the tag with the branch, always use
+//! `git push --atomic`: libgit2 updates refs one by one, so the remote could
+//! accept the branch and reject the tag, leaving a release half pushed.
+
+use std::cell::RefCell;
+use std::env;
+use std::fmt;
+use std::process::Command;
+use std::str::FromStr;
+
+use git2::{Cred, CredentialType, PushOptions, RemoteCallbacks, Repository};
+use tracing::{debug, warn};
+
+/// Environment variable selecting how pushes are made.
+const PUSH_BACKEND_ENV_VAR: &str = "KERYX_PUSH_BACKEND";
+
+/// How pushes reach the remote.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PushBackend {
+    /// libgit2, falling back to `git push` when it cannot connect.
+    #[default]
+    Auto,
+    /// libgit2 only.
+    Git2,
+    /// `git push` only.
+    Cli,
+}
+
+impl FromStr for PushBackend {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "auto" => Ok(PushBackend::Auto),
+            "git2" | "libgit2" => Ok(PushBackend::Git2),
+            "cli" | "git" => Ok(PushBackend::Cli),
+            _ => Err(format!(
+                "invalid push backend '{}' (expected 'auto', 'git2' or 'cli')",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for PushBackend {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            PushBackend::Auto => "auto",
+            PushBackend::Git2 => "git2",
+            PushBackend::Cli => "cli",
+        })
+    }
+}
+
+impl PushBackend {
+    /// Read `KERYX_PUSH_BACKEND`.
+    ///
+    /// Logs a warning and uses the default if the variable is set but invalid.
+    pub fn from_env() -> Self {
+        match env::var(PUSH_BACKEND_ENV_VAR) {
+            Ok(value) if !value.trim().is_empty() => value.parse().unwrap_or_else(|e| {
+                warn!("Ignoring {}: {}", PUSH_BACKEND_ENV_VAR, e);
+                PushBackend::default()
+            }),
+            _ => PushBackend::default(),
+        }
+    }
+}
+
+/// The current branch pushed to `remote_branch` on `remote`.
+#[derive(Debug, Clone, Copy)]
+pub struct PushTarget<'a> {
+    pub remote: &'a str,
+    pub remote_branch: &'a str,
+    /// Also push the annotated tags on `HEAD` (`--follow-tags`), in the same
+    /// atomic request.
+    pub tags: bool,
+}
+
+/// Why a libgit2 push failed.
+enum Git2Failure {
+    /// The remote refused one or more refs; the other backend would too.
+    Rejected(String),
+    /// No push happened: no connection, no credentials, no such remote.
+    Failed(String),
+}
+
+/// Push `target` once with `backend`; pushes with tags ignore it and use
+/// `git push --atomic`. Errors carry the message of the backend that failed
+/// last.
+pub fn push(target: &PushTarget<'_>, backend: PushBackend) -> Result<(), String> {
+    if target.tags {
+        return push_cli(target);
+    }
+    match backend {
+        PushBackend::Cli => push_cli(target),
+        PushBackend::Git2 => push_git2(target).map_err(|e| match e {
+            Git2Failure::Rejected(msg) | Git2Failure::Failed(msg) => msg,
+        }),
+        PushBackend::Auto => match push_git2(target) {
+            Ok(()) => Ok(()),
+            Err(Git2Failure::Rejected(msg)) => Err(msg),
+            Err(Git2Failure::Failed(msg)) => {
+                debug!("libgit2 push failed ({}); falling back to git push", msg);
+                push_cli(target).map_err(|cli| format!("{} (libgit2: {})", cli, msg))
+            }
+        },
+    }
+}
+
+fn push_cli(target: &PushTarget<'_>) -> Result<(), String> {
+    let refspec = format!("HEAD:refs/heads/{}", target.remote_branch);
+    let mut args = vec!["push", target.remote, &refspec];
+    if target.tags {
+        args.extend(["--follow-tags", "--atomic"]);
+    }
+    let output = Command::new("git")
+        .args(&args)
+        .output()
+        .map_err(|e| format!("Failed to run git push: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(format!("git push failed: {}", stderr.trim()));
+    }
+    Ok(())
+}
+
+fn push_git2(target: &PushTarget<'_>) -> Result<(), Git2Failure> {
+    let failed = |e: git2::Error| Git2Failure::Failed(e.message().to_string());
+
+    let repo = Repository::discover(".").map_err(failed)?;
+    let head = repo.head().map_err(failed)?;
+    let branch = match head.name() {
+        Some(name) if head.is_branch() => name.to_string(),
+        _ => return Err(Git2Failure::Failed("HEAD is not a branch".to_string())),
+    };
+    let refspecs = [format!("{}:refs/heads/{}", branch, target.remote_branch)];
+
+    let mut remote = repo.find_remote(target.remote).map_err(failed)?;
+    let config = repo.config().map_err(failed)?;
+    let rejected = RefCell::new(Vec::new());
+    {
+        let mut callbacks = RemoteCallbacks::new();
+        callbacks.credentials(credentials(config));
+        callbacks.push_update_reference(|refname, status| {
+            if let Some(message) = status {
+                rejected
+                    .borrow_mut()
+                    .push(format!("{} ({})", refname, message));
+            }
+            Ok(())
+        });
+        let mut options = PushOptions::new();
+        options.remote_callbacks(callbacks);
+        remote.push(&refspecs, Some(&mut options)).map_err(failed)?;
+    }
+
+    let rejected = rejected.into_inner();
+    if !rejected.is_empty() {
+        return Err(Git2Failure::Rejected(format!(
+            "remote rejected {}",
+            rejected.join(", ")
+        )));
+    }
+    Ok(())
+}
+
+/// Credential callback trying, once each, the username from the URL, the
+/// ssh-agent, the configured credential helper and default (Negotiate/NTLM)
+/// credentials. libgit2 calls it again after every rejected credential, so
+/// each kind is offered only once to end the loop.
+fn credentials(
+    config: git2::Config,
+) -> impl FnMut(&str, Option<&str>, CredentialType) -> Result<Cred, git2::Error> {
+    let mut tried = CredentialType::empty();
+    move |url, username, allowed| {
+        let username = username.unwrap_or("git");
+        let mut offer = |kind: CredentialType| {
+            if !allowed.contains(kind) || tried.contains(kind) {
+                return false;
+            }
+            tried |= kind;
+            true
+        };
+        if offer(CredentialType::USERNAME) {
+            return Cred::username(username);
+        }
+        if offer(CredentialType::SSH_KEY) {
+            return Cred::ssh_key_from_agent(username);
+        }
+        if offer(CredentialType::USER_PASS_PLAINTEXT) {
+            return Cred::credential_helper(&config, url, Some(username));
+        }
+        if offer(CredentialType::DEFAULT) {
+            return Cred::default();
+        }
+        Err(git2::Error::from_str(
+            "no usable credentials (tried ssh-agent and the credential helper)",
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::path::Path;
+
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    struct CwdGuard(std::path::PathBuf);
+
+    impl CwdGuard {
+        fn set(path: &Path) -> Self {
+            let original = env::current_dir().unwrap();
+            env::set_current_dir(path).unwrap();
+            Self(original)
+        }
+    }
+
+    impl Drop for CwdGuard {
+        fn drop(&mut self) {
+            let _ = env::set_current_dir(&self.0);
+        }
+    }
+
+    /// A clone with one commit tagged `v1.0.0`, and its bare remote.
+    fn repo_with_remote() -> (TempDir, TempDir, Repository) {
+        let remote_dir = TempDir::new().unwrap();
+        Repository::init_bare(remote_dir.path()).unwrap();
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        repo.remote("origin", remote_dir.path().to_str().unwrap())
+            .unwrap();
+        {
+            let sig = Signature::now("Test User", "test@example.com").unwrap();
+            let tree_id = repo.index().unwrap().write_tree().unwrap();
+            let tree = repo.find_tree(tree_id).unwrap();
+            let oid = repo
+                .commit(
+                    Some("HEAD"),
+                    &sig,
+                    &sig,
+                    "chore(release): v1.0.0",
+                    &tree,
+                    &[],
+                )
+                .unwrap();
+            let commit = repo.find_object(oid, None).unwrap();
+            repo.tag("v1.0.0", &commit, &sig, "Release v1.0.0", false)
+                .unwrap();
+            repo.tag_lightweight("scratch", &commit, false).unwrap();
+        }
+        (dir, remote_dir, repo)
+    }
+
+    #[test]
+    fn test_parse_push_backend() {
+        assert_eq!("libgit2".parse::<PushBackend>(), Ok(PushBackend::Git2));
+        assert_eq!(" CLI ".parse::<PushBackend>(), Ok(PushBackend::Cli));
+        assert!("rsync".parse::<PushBackend>().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_push_backend_from_env() {
+        temp_env::with_var(PUSH_BACKEND_ENV_VAR, Some("cli"), || {
+            assert_eq!(PushBackend::from_env(), PushBackend::Cli);
+        });
+        temp_env::with_var(PUSH_BACKEND_ENV_VAR, Some("carrier-pigeon"), || {
+            assert_eq!(PushBackend::from_env(), PushBackend::Auto);
+        });
+    }
+
+    #[test]
+    #[serial]
+    fn test_git2_push_sends_branch() {
+        let (dir, remote_dir, _repo) = repo_with_remote();
+        let _cwd = CwdGuard::set(dir.path());
+
+        let target = PushTarget {
+            remote: "origin",
+            remote_branch: "main",
+            tags: false,
+        };
+        push(&target, PushBackend::Git2).unwrap();
+
+        let remote = Repository::open_bare(remote_dir.path()).unwrap();
+        assert!(remote.find_reference("refs/heads/main").is_ok());
+        assert!(remote.find_reference("refs/tags/v1.0.0").is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_tag_push_sends_branch_and_annotated_tags() {
+        let (dir, remote_dir, _repo) = repo_with_remote();
+        let _cwd = CwdGuard::set(dir.path());
+
+        let target = PushTarget {
+            remote: "origin",
+            remote_branch: "main",
+            tags: true,
+        };
+        push(&target, PushBackend::Git2).unwrap();
+
+        let remote = Repository::open_bare(remote_dir.path()).unwrap();
+        assert!(remote.find_reference("refs/heads/main").is_ok());
+        assert!(remote.find_reference("refs/tags/v1.0.0").is_ok());
+        assert!(remote.find_reference("refs/tags/scratch").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    #[serial]
+    fn test_rejected_tag_leaves_remote_branch_alone() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let (dir, remote_dir, _repo) = repo_with_remote();
+        let _cwd = CwdGuard::set(dir.path());
+
+        // The remote refuses tags but would take the branch
+        let hook = remote_dir.path().join("hooks").join("update");
+        std::fs::write(
+            &hook,
+            "#!/bin/sh\ncase \"$1\" in refs/tags/*) echo 'no tags' >&2; exit 1;; esac\n",
+        )
+        .unwrap();
+        std::fs::set_permissions(&hook, std::fs::Permissions::from_mode(0o755)).unwrap();
+
+        let remote = Repository::open_bare(remote_dir.path()).unwrap();
+        for backend in [PushBackend::Auto, PushBackend::Git2, PushBackend::Cli] {
+            let target = PushTarget {
+                remote: "origin",
+                remote_branch: "main",
+                tags: true,
+            };
+            assert!(push(&target, backend).is_err(), "{}", backend);
+            assert!(
+                remote.find_reference("refs/heads/main").is_err(),
+                "{}",
+                backend
+            );
+        }
+    }
+
+    #[test]
+    #[serial]
+    fn test_git2_push_reports_missing_remote() {
+        let (dir, _remote_dir, _repo) = repo_with_remote();
+        let _cwd = CwdGuard::set(dir.path());
+
+        let target = PushTarget {
+            remote: "upstream",
+            remote_branch: "main",
+            tags: false,
+        };
+        let error = push(&target, PushBackend::Git2).unwrap_err();
+        assert!(error.contains("upstream"), "{}", error);
+    }
+}
@@ -0,0 +1,300 @@
+//! Release records for compliance (`.keryx/releases/<tag>.json`).
+//!
+//! Every shipped release leaves a machine-readable provenance record: what
+//! was released (version, tag, commit), the changelog as released (git blob
+//! ids, checkable with `git hash-object`), who approved it, when each step
+//! happened, and which LLM provider wrote the notes. The record is written
+//! when the tag is created and completed with `pushed_at` once the push
+//! succeeds, so a scheduled release waiting for its push has a record with
+//! `pushed_at: null`. A rolled-back release has its record removed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use git2::{ObjectType, Oid, Repository};
+use serde::{Deserialize, Serialize};
+
+use crate::atomic::write_atomic;
+use crate::error::ShipError;
+use crate::journal::{JOURNAL_DIR, ensure_state_dir};
+use crate::summary::RunSummary;
+
+/// Directory of release records inside [`JOURNAL_DIR`].
+pub const RELEASES_DIR: &str = "releases";
+
+/// Provenance of one shipped release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ReleaseRecord {
+    pub version: String,
+    pub tag: String,
+    /// Release commit the tag points at.
+    pub commit: String,
+    /// Changelog files as committed in the release.
+    pub changelogs: Vec<ChangelogDigest>,
+    /// Local git identity that confirmed the release.
+    pub approver: Approver,
+    /// `Name <email>` of whoever ran `ship --prepare`, for two-phase releases.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prepared_by: Option<String>,
+    /// Version of keryx that shipped the release.
+    pub keryx_version: String,
+    /// LLM provider that wrote the changelog, if one was used.
+    pub provider: Option<String>,
+    pub llm_calls: usize,
+    pub fallbacks: usize,
+    pub started_at: DateTime<Utc>,
+    pub approved_at: DateTime<Utc>,
+    pub tagged_at: DateTime<Utc>,
+    /// `None` until the release has been pushed.
+    pub pushed_at: Option<DateTime<Utc>>,
+}
+
+/// Hash of a changelog file as released.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ChangelogDigest {
+    /// Path relative to the repository root, with `/` separators.
+    pub path: String,
+    /// Git blob id of the file content (`git hash-object <path>`).
+    pub blob: String,
+}
+
+/// Who approved a release.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Approver {
+    pub name: Option<String>,
+    pub email: Option<String>,
+}
+
+impl Approver {
+    /// `user.name` and `user.email` from the repository's git config, with
+    /// the OS user name as a fallback name.
+    pub fn local(repo: &Repository) -> Self {
+        let config = repo.config().ok();
+        let get = |key: &str| {
+            config
+                .as_ref()
+                .and_then(|c| c.get_string(key).ok())
+                .filter(|v| !v.trim().is_empty())
+        };
+        Self {
+            name: get("user.name").or_else(|| {
+                std::env::var("USER")
+                    .or_else(|_| std::env::var("USERNAME"))
+                    .ok()
+            }),
+            email: get("user.email"),
+        }
+    }
+}
+
+/// Timestamps of a ship run, collected as the steps happen.
+#[derive(Debug, Clone, Copy)]
+pub struct ReleaseTimes {
+    pub started_at: DateTime<Utc>,
+    pub approved_at: DateTime<Utc>,
+    pub tagged_at: DateTime<Utc>,
+}
+
+impl ReleaseRecord {
+    /// Build the record for a release that was just tagged.
+    pub fn new(
+        repo: &Repository,
+        version: &str,
+        tag: &str,
+        commit: &str,
+        changelogs: &[PathBuf],
+        summary: &RunSummary,
+        times: ReleaseTimes,
+    ) -> Result<Self, ShipError> {
+        let workdir = repo
+            .workdir()
+            .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
+        let changelogs = changelogs
+            .iter()
+            .map(|path| changelog_digest(workdir, path))
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            version: version.to_string(),
+            tag: tag.to_string(),
+            commit: commit.to_string(),
+            changelogs,
+            approver: Approver::local(repo),
+            prepared_by: None,
+            keryx_version: env!("CARGO_PKG_VERSION").to_string(),
+            provider: summary.provider.clone(),
+            llm_calls: summary.llm_calls,
+            fallbacks: summary.fallbacks,
+            started_at: times.started_at,
+            approved_at: times.approved_at,
+            tagged_at: times.tagged_at,
+            pushed_at: None,
+        })
+    }
+}
+
+fn changelog_digest(workdir: &Path, path: &Path) -> Result<ChangelogDigest, ShipError> {
+    let absolute = workdir.join(path);
+    let content = fs::read(&absolute).map_err(|e| {
+        ShipError::GitFailed(format!("Failed to read {}: {}", absolute.display(), e))
+    })?;
+    let blob = Oid::hash_object(ObjectType::Blob, &content)
+        .map_err(|e| ShipError::GitFailed(format!("Failed to hash {}: {}", path.display(), e)))?;
+    let relative = path.strip_prefix(workdir).unwrap_or(path);
+    Ok(ChangelogDigest {
+        path: relative.to_string_lossy().replace('\\', "/"),
+        blob: blob.to_string(),
+    })
+}
+
+/// Handle to the release records of a single repository.
+#[derive(Debug, Clone)]
+pub struct ReleaseRecordStore {
+    dir: PathBuf,
+}
+
+impl ReleaseRecordStore {
+    /// Store at `<repo_root>/.keryx/releases/`.
+    pub fn for_repo(repo_root: &Path) -> Self {
+        Self {
+            dir: repo_root.join(JOURNAL_DIR).join(RELEASES_DIR),
+        }
+    }
+
+    /// Path of the record for `tag`.
+    pub fn path(&self, tag: &str) -> PathBuf {
+        self.dir.join(format!("{}.json", tag))
+    }
+
+    /// Load the record for `tag`, if any.
+    pub fn load(&self, tag: &str) -> Result<Option<ReleaseRecord>, ShipError> {
+        let path = self.path(tag);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(record_error(&path, e)),
+        };
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| record_error(&path, e))
+    }
+
+    /// Write `record`, replacing an earlier record for the same tag.
+    pub fn save(&self, record: &ReleaseRecord) -> Result<PathBuf, ShipError> {
+        let path = self.path(&record.tag);
+        if let Some(state_dir) = self.dir.parent() {
+            ensure_state_dir(state_dir).map_err(|e| record_error(&path, e))?;
+        }
+        fs::create_dir_all(&self.dir).map_err(|e| record_error(&path, e))?;
+        let json = serde_json::to_string_pretty(record).map_err(|e| record_error(&path, e))?;
+        write_atomic(&path, json).map_err(|e| record_error(&path, e))?;
+        Ok(path)
+    }
+
+    /// Set `pushed_at` on the record for `tag`. A missing record (e.g. from a
+    /// release staged by an older keryx) is left missing.
+    pub fn mark_pushed(&self, tag: &str, at: DateTime<Utc>) -> Result<Option<PathBuf>, ShipError> {
+        match self.load(tag)? {
+            Some(mut record) => {
+                record.pushed_at = Some(at);
+                self.save(&record).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    /// Remove the record for `tag`, e.g. after a rollback.
+    pub fn remove(&self, tag: &str) -> Result<(), ShipError> {
+        let path = self.path(tag);
+        match fs::remove_file(&path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(record_error(&path, e)),
+        }
+    }
+}
+
+fn record_error(path: &Path, reason: impl std::fmt::Display) -> ShipError {
+    ShipError::ReleaseRecordFailed {
+        path: path.to_path_buf(),
+        reason: reason.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    fn record(tag: &str) -> ReleaseRecord {
+        let now = Utc::now();
+        ReleaseRecord {
+            version: "1.2.3".to_string(),
+            tag: tag.to_string(),
+            commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            changelogs: vec![ChangelogDigest {
+                path: "CHANGELOG.md".to_string(),
+                blob: "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391".to_string(),
+            }],
+            approver: Approver {
+                name: Some("Alice".to_string()),
+                email: Some("alice@example.com".to_string()),
+            },
+            prepared_by: None,
+            keryx_version: "0.0.0".to_string(),
+            provider: Some("claude".to_string()),
+            llm_calls: 2,
+            fallbacks: 0,
+            started_at: now,
+            approved_at: now,
+            tagged_at: now,
+            pushed_at: None,
+        }
+    }
+
+    #[test]
+    fn test_save_mark_pushed_and_remove() {
+        let dir = TempDir::new().unwrap();
+        let store = ReleaseRecordStore::for_repo(dir.path());
+
+        let path = store.save(&record("v1.2.3")).unwrap();
+        assert_eq!(path, dir.path().join(".keryx/releases/v1.2.3.json"));
+        assert!(dir.path().join(".keryx/.gitignore").exists());
+
+        let pushed_at = Utc::now();
+        store.mark_pushed("v1.2.3", pushed_at).unwrap();
+        assert_eq!(
+            store.load("v1.2.3").unwrap().unwrap().pushed_at,
+            Some(pushed_at)
+        );
+
+        store.remove("v1.2.3").unwrap();
+        assert!(store.load("v1.2.3").unwrap().is_none());
+        assert_eq!(store.mark_pushed("v1.2.3", pushed_at).unwrap(), None);
+    }
+
+    #[test]
+    fn test_changelog_digest_matches_git_hash_object() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(dir.path().join("CHANGELOG.md"), "").unwrap();
+
+        let digest = changelog_digest(dir.path(), Path::new("CHANGELOG.md")).unwrap();
+        assert_eq!(digest.path, "CHANGELOG.md");
+        // Blob id of the empty file
+        assert_eq!(digest.blob, "e69de29bb2d1d6434b8b29ae775ad8c2e48c5391");
+    }
+
+    #[test]
+    fn test_approver_from_git_config() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let mut config = repo.config().unwrap();
+        config.set_str("user.name", "Alice").unwrap();
+        config.set_str("user.email", "alice@example.com").unwrap();
+
+        let approver = Approver::local(&repo);
+        assert_eq!(approver.name.as_deref(), Some("Alice"));
+        assert_eq!(approver.email.as_deref(), Some("alice@example.com"));
+    }
+}
@@ -0,0 +1,390 @@
+//! Release risk report for the ship preflight.
+//!
+//! None of these checks block a release. They are summarized before the
+//! confirmation prompt so the releaser can make an informed call: breaking
+//! changes, an unusually large release, a release branch that was rewritten
+//! since the last tag, and open PRs that were meant to make it in.
+
+use chrono::{DateTime, Utc};
+use git2::{Oid, Repository};
+use serde::Serialize;
+
+use crate::github::PullRequest;
+
+/// Commits above which a release counts as large.
+pub const LARGE_RELEASE_COMMITS: usize = 50;
+
+/// Changed lines (insertions + deletions) above which a release counts as large.
+pub const LARGE_RELEASE_LINES: usize = 2000;
+
+/// Most open PR numbers listed in the report.
+const MAX_LISTED_PRS: usize = 5;
+
+/// Severity of a single check or of the whole report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RiskLevel {
+    Low,
+    Medium,
+    High,
+}
+
+impl std::fmt::Display for RiskLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RiskLevel::Low => write!(f, "low"),
+            RiskLevel::Medium => write!(f, "medium"),
+            RiskLevel::High => write!(f, "high"),
+        }
+    }
+}
+
+/// Size of the diff between the last tag and `HEAD`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DiffSize {
+    pub files_changed: usize,
+    pub insertions: usize,
+    pub deletions: usize,
+}
+
+impl DiffSize {
+    /// Changed lines in total.
+    pub fn lines(&self) -> usize {
+        self.insertions + self.deletions
+    }
+}
+
+/// Inputs of the risk report. `None` marks a check that could not run.
+#[derive(Debug, Clone)]
+pub struct RiskReport {
+    /// Tag the release is measured from (`None` for an initial release).
+    pub since_tag: Option<String>,
+    /// Commits in the release.
+    pub commits: usize,
+    /// Commits marked as breaking changes.
+    pub breaking_commits: usize,
+    /// Diff size since the last tag.
+    pub diff: Option<DiffSize>,
+    /// Remote-tracking ref inspected for force-pushes (e.g. `origin/main`).
+    pub upstream: String,
+    /// Non-fast-forward updates of the upstream ref since the last tag.
+    pub force_pushes: Option<usize>,
+    /// Open PRs targeting the release branch.
+    pub open_prs: Option<Vec<PullRequest>>,
+}
+
+/// One line of the report.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct RiskItem {
+    /// `None` if the check was skipped.
+    pub level: Option<RiskLevel>,
+    pub message: String,
+}
+
+impl RiskItem {
+    fn new(level: RiskLevel, message: String) -> Self {
+        Self {
+            level: Some(level),
+            message,
+        }
+    }
+
+    fn skipped(message: String) -> Self {
+        Self {
+            level: None,
+            message,
+        }
+    }
+
+    /// Status label in the style of the other preflight lines.
+    pub fn label(&self) -> &'static str {
+        match self.level {
+            None => "[SKIP]",
+            Some(RiskLevel::Low) => "[PASS]",
+            Some(RiskLevel::Medium) => "[WARN]",
+            Some(RiskLevel::High) => "[RISK]",
+        }
+    }
+}
+
+impl RiskReport {
+    /// Evaluate every check.
+    pub fn items(&self) -> Vec<RiskItem> {
+        let since = self.since_tag.as_deref().unwrap_or("the first commit");
+        let mut items = Vec::new();
+
+        items.push(match self.breaking_commits {
+            0 => RiskItem::new(RiskLevel::Low, "No breaking-change commits".to_string()),
+            n => RiskItem::new(
+                RiskLevel::Medium,
+                format!("{} breaking-change commit{}", n, plural(n)),
+            ),
+        });
+
+        let mut size = format!("{} commit{}", self.commits, plural(self.commits));
+        if let Some(diff) = &self.diff {
+            size.push_str(&format!(
+                ", +{}/-{} lines in {} file{}",
+                diff.insertions,
+                diff.deletions,
+                diff.files_changed,
+                plural(diff.files_changed)
+            ));
+        }
+        let large = self.commits > LARGE_RELEASE_COMMITS
+            || self
+                .diff
+                .is_some_and(|diff| diff.lines() > LARGE_RELEASE_LINES);
+        items.push(if large {
+            RiskItem::new(RiskLevel::Medium, format!("Large release: {}", size))
+        } else {
+            RiskItem::new(RiskLevel::Low, format!("Release size: {}", size))
+        });
+
+        items.push(match self.force_pushes {
+            None => RiskItem::skipped(format!("Force-push check: no reflog for {}", self.upstream)),
+            Some(0) => RiskItem::new(
+                RiskLevel::Low,
+                format!("No force-pushes to {} since {}", self.upstream, since),
+            ),
+            Some(n) => RiskItem::new(
+                RiskLevel::High,
+                format!(
+                    "{} was force-pushed {} time{} since {}",
+                    self.upstream,
+                    n,
+                    plural(n),
+                    since
+                ),
+            ),
+        });
+
+        items.push(match &self.open_prs {
+            None => RiskItem::skipped("Open PRs: not checked".to_string()),
+            Some(prs) if prs.is_empty() => {
+                RiskItem::new(RiskLevel::Low, "No open PRs target this branch".to_string())
+            }
+            Some(prs) => {
+                let mut numbers: Vec<String> = prs
+                    .iter()
+                    .take(MAX_LISTED_PRS)
+                    .map(|pr| format!("#{}", pr.number))
+                    .collect();
+                if prs.len() > MAX_LISTED_PRS {
+                    numbers.push("...".to_string());
+                }
+                RiskItem::new(
+                    RiskLevel::Medium,
+                    format!(
+                        "{} open PR{} target this branch ({})",
+                        prs.len(),
+                        plural(prs.len()),
+                        numbers.join(", ")
+                    ),
+                )
+            }
+        });
+
+        items
+    }
+
+    /// Highest level among the checks that ran.
+    pub fn level(&self) -> RiskLevel {
+        self.items()
+            .iter()
+            .filter_map(|item| item.level)
+            .max()
+            .unwrap_or(RiskLevel::Low)
+    }
+}
+
+/// Diff size between `from` (or the empty tree) and `to`.
+pub fn diff_size(repo: &Repository, from: Option<Oid>, to: Oid) -> Result<DiffSize, git2::Error> {
+    let old_tree = match from {
+        Some(oid) => Some(repo.find_commit(oid)?.tree()?),
+        None => None,
+    };
+    let new_tree = repo.find_commit(to)?.tree()?;
+    let stats = repo
+        .diff_tree_to_tree(old_tree.as_ref(), Some(&new_tree), None)?
+        .stats()?;
+
+    Ok(DiffSize {
+        files_changed: stats.files_changed(),
+        insertions: stats.insertions(),
+        deletions: stats.deletions(),
+    })
+}
+
+/// Count non-fast-forward updates recorded in the reflog of `reference`
+/// (e.g. `refs/remotes/origin/main`) at or after `since`.
+///
+/// An update is forced when the new commit does not descend from the old
+/// one. Returns `None` when the ref has no reflog to inspect.
+pub fn count_force_pushes(
+    repo: &Repository,
+    reference: &str,
+    since: Option<DateTime<Utc>>,
+) -> Option<usize> {
+    let reflog = repo.reflog(reference).ok()?;
+    if reflog.is_empty() {
+        return None;
+    }
+
+    let since = since.map(|t| t.timestamp()).unwrap_or(i64::MIN);
+    let forced = reflog
+        .iter()
+        .filter(|entry| entry.committer().when().seconds() >= since)
+        .filter(|entry| {
+            let (old, new) = (entry.id_old(), entry.id_new());
+            !old.is_zero() && old != new && !repo.graph_descendant_of(new, old).unwrap_or(true)
+        })
+        .count();
+    Some(forced)
+}
+
+fn plural(n: usize) -> &'static str {
+    if n == 1 { "" } else { "s" }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use std::num::NonZeroU64;
+
+    fn commit(repo: &Repository, parent: Option<Oid>, file: &str, content: &str) -> Oid {
+        let workdir = repo.workdir().unwrap();
+        std::fs::write(workdir.join(file), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(std::path::Path::new(file)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parents: Vec<_> = parent
+            .map(|p| repo.find_commit(p).unwrap())
+            .into_iter()
+            .collect();
+        let parent_refs: Vec<_> = parents.iter().collect();
+        repo.commit(None, &sig, &sig, "commit", &tree, &parent_refs)
+            .unwrap()
+    }
+
+    fn report() -> RiskReport {
+        RiskReport {
+            since_tag: Some("v1.0.0".to_string()),
+            commits: 3,
+            breaking_commits: 0,
+            diff: Some(DiffSize {
+                files_changed: 2,
+                insertions: 10,
+                deletions: 4,
+            }),
+            upstream: "origin/main".to_string(),
+            force_pushes: Some(0),
+            open_prs: Some(Vec::new()),
+        }
+    }
+
+    fn open_pr(number: u64) -> PullRequest {
+        PullRequest {
+            number: NonZeroU64::new(number).unwrap(),
+            title: format!("PR {}", number),
+            body: None,
+            merged_at: None,
+            labels: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_quiet_release_is_low_risk() {
+        let report = report();
+        let items = report.items();
+        assert!(items.iter().all(|i| i.level == Some(RiskLevel::Low)));
+        assert_eq!(
+            items[1].message,
+            "Release size: 3 commits, +10/-4 lines in 2 files"
+        );
+        assert_eq!(report.level(), RiskLevel::Low);
+    }
+
+    #[test]
+    fn test_breaking_large_and_open_prs_are_medium() {
+        let mut report = report();
+        report.breaking_commits = 2;
+        report.commits = LARGE_RELEASE_COMMITS + 1;
+        report.open_prs = Some((1..=7).map(open_pr).collect());
+
+        let items = report.items();
+        assert_eq!(items[0].message, "2 breaking-change commits");
+        assert_eq!(items[0].label(), "[WARN]");
+        assert!(items[1].message.starts_with("Large release: 51 commits"));
+        assert_eq!(
+            items[3].message,
+            "7 open PRs target this branch (#1, #2, #3, #4, #5, ...)"
+        );
+        assert_eq!(report.level(), RiskLevel::Medium);
+    }
+
+    #[test]
+    fn test_force_push_is_high_risk() {
+        let mut report = report();
+        report.force_pushes = Some(1);
+        report.open_prs = None;
+
+        let items = report.items();
+        assert_eq!(
+            items[2].message,
+            "origin/main was force-pushed 1 time since v1.0.0"
+        );
+        assert_eq!(items[2].label(), "[RISK]");
+        assert_eq!(items[3].label(), "[SKIP]");
+        assert_eq!(report.level(), RiskLevel::High);
+    }
+
+    #[test]
+    fn test_diff_size() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let first = commit(&repo, None, "a.txt", "one\ntwo\n");
+        let second = commit(&repo, Some(first), "a.txt", "one\nthree\nfour\n");
+
+        let since_first = diff_size(&repo, Some(first), second).unwrap();
+        assert_eq!(
+            since_first,
+            DiffSize {
+                files_changed: 1,
+                insertions: 2,
+                deletions: 1,
+            }
+        );
+        assert_eq!(diff_size(&repo, None, first).unwrap().insertions, 2);
+    }
+
+    #[test]
+    fn test_count_force_pushes() {
+        let dir = tempfile::tempdir().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let base = commit(&repo, None, "a.txt", "base\n");
+        let ahead = commit(&repo, Some(base), "a.txt", "ahead\n");
+        let rewritten = commit(&repo, Some(base), "b.txt", "rewritten\n");
+
+        let name = "refs/remotes/origin/main";
+        assert_eq!(count_force_pushes(&repo, name, None), None);
+
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let mut reflog = repo.reflog(name).unwrap();
+        reflog.append(base, &sig, Some("fetch")).unwrap();
+        reflog
+            .append(ahead, &sig, Some("fetch: fast-forward"))
+            .unwrap();
+        reflog
+            .append(rewritten, &sig, Some("fetch: forced-update"))
+            .unwrap();
+        reflog.write().unwrap();
+
+        assert_eq!(count_force_pushes(&repo, name, None), Some(1));
+        let later = Utc::now() + chrono::Duration::hours(1);
+        assert_eq!(count_force_pushes(&repo, name, Some(later)), Some(0));
+    }
+}
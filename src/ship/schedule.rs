@@ -0,0 +1,248 @@
+//! Staged releases with a deferred push (`ship --schedule` / `ship --publish`).
+//!
+//! A scheduled ship runs every local step (version bump, changelog, commit,
+//! tag) right away and records the release in `.keryx/pending-release.json`.
+//! The push happens when the scheduled time arrives, or earlier when
+//! `keryx ship --publish` is run. While a release is staged, a new ship is
+//! refused so two releases never pile up locally.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local, NaiveDateTime, NaiveTime, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+
+use crate::atomic::write_atomic;
+use crate::error::ShipError;
+use crate::journal::{JOURNAL_DIR, ensure_state_dir};
+
+/// File name of the staged release inside [`JOURNAL_DIR`].
+pub const PENDING_RELEASE_FILE: &str = "pending-release.json";
+
+/// Longest single sleep while waiting for the publish time, so a machine
+/// that was suspended catches up soon after it wakes.
+const WAIT_INTERVAL: Duration = Duration::from_secs(60);
+
+/// A release that was committed and tagged locally but not pushed yet.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PendingRelease {
+    /// Tag created for the release.
+    pub tag: String,
+    /// Release commit the tag points at.
+    pub commit: String,
+    /// Remote the release is pushed to.
+    pub remote: String,
+    /// Remote branch the release is pushed to.
+    pub branch: String,
+    /// When the release was staged.
+    pub staged_at: DateTime<Utc>,
+    /// When the push is due (`None` = only on `ship --publish`).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publish_at: Option<DateTime<Utc>>,
+    /// Publish steps to run after the push (see [`crate::ship::publish`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub publish_steps: Vec<String>,
+    /// Asset patterns uploaded after the push (see [`crate::ship::assets`]).
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub release_assets: Vec<String>,
+    /// Skip creating the GitHub release after the push
+    /// (see [`crate::ship::github_release`]).
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub no_github_release: bool,
+    /// Set for a release made with `ship --prepare`: committed but not
+    /// tagged, waiting for `ship --approve` by someone else.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prepared_by: Option<String>,
+}
+
+/// Handle to the staged-release file of a single repository.
+#[derive(Debug, Clone)]
+pub struct PendingReleaseStore {
+    path: PathBuf,
+}
+
+impl PendingReleaseStore {
+    /// Store at `<repo_root>/.keryx/pending-release.json`.
+    pub fn for_repo(repo_root: &Path) -> Self {
+        Self {
+            path: repo_root.join(JOURNAL_DIR).join(PENDING_RELEASE_FILE),
+        }
+    }
+
+    /// Path of the staged-release file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Load the staged release, if any.
+    pub fn load(&self) -> Result<Option<PendingRelease>, ShipError> {
+        let content = match fs::read_to_string(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+            Err(e) => return Err(self.state_error(e)),
+        };
+        serde_json::from_str(&content)
+            .map(Some)
+            .map_err(|e| self.state_error(e))
+    }
+
+    /// Record `release` as staged, replacing any previous record.
+    pub fn save(&self, release: &PendingRelease) -> Result<(), ShipError> {
+        if let Some(dir) = self.path.parent() {
+            ensure_state_dir(dir).map_err(|e| self.state_error(e))?;
+        }
+        let json = serde_json::to_string_pretty(release).map_err(|e| self.state_error(e))?;
+        write_atomic(&self.path, json).map_err(|e| self.state_error(e))
+    }
+
+    /// Forget the staged release.
+    pub fn clear(&self) -> Result<(), ShipError> {
+        match fs::remove_file(&self.path) {
+            Ok(()) => Ok(()),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(self.state_error(e)),
+        }
+    }
+
+    fn state_error(&self, reason: impl std::fmt::Display) -> ShipError {
+        ShipError::StagedReleaseState {
+            path: self.path.clone(),
+            reason: reason.to_string(),
+        }
+    }
+}
+
+/// Parse a `--schedule` value relative to `now`.
+///
+/// Accepts an RFC 3339 timestamp (`2025-03-01T22:00:00+01:00`), a date and
+/// time in `now`'s timezone (`2025-03-01 22:00`), or a bare time (`22:00`),
+/// which means its next occurrence. Times that are not in the future are
+/// rejected.
+pub fn parse_publish_time<Tz: TimeZone>(
+    input: &str,
+    now: DateTime<Tz>,
+) -> Result<DateTime<Utc>, String> {
+    let input = input.trim();
+    let tz = now.timezone();
+    let local = |naive: NaiveDateTime| {
+        tz.from_local_datetime(&naive)
+            .earliest()
+            .map(|t| t.with_timezone(&Utc))
+    };
+
+    let parsed = if let Ok(at) = DateTime::parse_from_rfc3339(input) {
+        Some(at.with_timezone(&Utc))
+    } else if let Some(naive) = ["%Y-%m-%d %H:%M", "%Y-%m-%dT%H:%M", "%Y-%m-%d %H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(input, format).ok())
+    {
+        local(naive)
+    } else if let Some(time) = ["%H:%M", "%H:%M:%S"]
+        .iter()
+        .find_map(|format| NaiveTime::parse_from_str(input, format).ok())
+    {
+        let today = now.date_naive().and_time(time);
+        match local(today) {
+            Some(at) if at > now.with_timezone(&Utc) => Some(at),
+            _ => local(today + chrono::Duration::days(1)),
+        }
+    } else {
+        return Err(format!(
+            "invalid time '{}' (expected HH:MM, YYYY-MM-DD HH:MM, or an RFC 3339 timestamp)",
+            input
+        ));
+    };
+
+    let at = parsed.ok_or_else(|| format!("'{}' does not exist in the local timezone", input))?;
+    if at <= now.with_timezone(&Utc) {
+        return Err(format!("'{}' is in the past", input));
+    }
+    Ok(at)
+}
+
+/// Format a publish time in the local timezone for display.
+pub fn display_time(at: DateTime<Utc>) -> String {
+    at.with_timezone(&Local)
+        .format("%Y-%m-%d %H:%M %:z")
+        .to_string()
+}
+
+/// Sleep until `at`.
+pub async fn wait_until(at: DateTime<Utc>) {
+    while let Ok(remaining) = (at - Utc::now()).to_std() {
+        if remaining.is_zero() {
+            break;
+        }
+        tokio::time::sleep(remaining.min(WAIT_INTERVAL)).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::FixedOffset;
+
+    fn now() -> DateTime<FixedOffset> {
+        DateTime::parse_from_rfc3339("2025-03-01T15:30:00+02:00").unwrap()
+    }
+
+    #[test]
+    fn test_parse_bare_time_later_today() {
+        let at = parse_publish_time("22:00", now()).unwrap();
+        assert_eq!(at.to_rfc3339(), "2025-03-01T20:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_bare_time_rolls_to_tomorrow() {
+        let at = parse_publish_time("09:15", now()).unwrap();
+        assert_eq!(at.to_rfc3339(), "2025-03-02T07:15:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_date_and_time_uses_local_offset() {
+        let at = parse_publish_time("2025-03-04 01:00", now()).unwrap();
+        assert_eq!(at.to_rfc3339(), "2025-03-03T23:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_rfc3339() {
+        let at = parse_publish_time("2025-03-02T06:00:00Z", now()).unwrap();
+        assert_eq!(at.to_rfc3339(), "2025-03-02T06:00:00+00:00");
+    }
+
+    #[test]
+    fn test_parse_rejects_past_and_garbage() {
+        let err = parse_publish_time("2025-03-01 10:00", now()).unwrap_err();
+        assert!(err.contains("in the past"));
+        let err = parse_publish_time("tonight", now()).unwrap_err();
+        assert!(err.contains("invalid time"));
+    }
+
+    #[test]
+    fn test_store_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let store = PendingReleaseStore::for_repo(dir.path());
+        assert_eq!(store.load().unwrap(), None);
+
+        let release = PendingRelease {
+            tag: "v1.2.0".to_string(),
+            commit: "0123456789abcdef0123456789abcdef01234567".to_string(),
+            remote: "origin".to_string(),
+            branch: "main".to_string(),
+            staged_at: Utc::now(),
+            publish_at: None,
+            publish_steps: vec!["cargo".to_string()],
+            release_assets: vec!["dist/*.tar.gz".to_string()],
+            no_github_release: true,
+            prepared_by: None,
+        };
+        store.save(&release).unwrap();
+        assert_eq!(store.load().unwrap(), Some(release));
+        assert!(dir.path().join(JOURNAL_DIR).join(".gitignore").exists());
+
+        store.clear().unwrap();
+        assert_eq!(store.load().unwrap(), None);
+        store.clear().unwrap();
+    }
+}
@@ -0,0 +1,783 @@
+//! Release trains: several packages of a monorepo in one release commit.
+//!
+//! `keryx ship --train` treats every detected package as its own release
+//! line, tagged `<name>-v<version>`. A package's commits are those since its
+//! last tag that changed a file inside its directory; files of a nested
+//! package belong to the nested package, and the root package owns whatever
+//! no other package does. Packages without such commits stay out of the
+//! train. Every other package gets its next version (from its commits, like
+//! `--no-llm-bump`), its version files and its own changelog updated. All of
+//! them go into one release commit carrying one tag per package, and the
+//! branch and tags are pushed in a single atomic `git push`. If the push
+//! fails, every tag is deleted and the commit undone.
+//!
+//! `keryx ship --package <name>` runs the same pipeline for one member of a
+//! detected workspace (see [`crate::workspace`]): only its version files,
+//! its commits, its `<name>-v<version>` tag and its changelog.
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+use chrono::{DateTime, Utc};
+use dialoguer::Confirm;
+use git2::{Oid, Repository};
+use semver::Version;
+
+use crate::changelog::parser::read_changelog;
+use crate::changelog::{CategoryAliases, CompareLinks, ReleaseMetadata};
+use crate::error::ShipError;
+use crate::git::ParsedCommit;
+use crate::git::commits::fetch_commits;
+use crate::git::range::find_root_commit;
+use crate::git::tags::{TagInfo, get_all_tags};
+use crate::journal::{Journal, OperationKind};
+use crate::llm::{LlmRouter, TaskPolicies};
+use crate::summary::RunSummary;
+use crate::version::calculate_next_version;
+use crate::workspace::{Workspace, detect_workspaces};
+
+use super::preflight::{PreflightResult, check_tag_exists};
+use super::record::{ReleaseRecordStore, ReleaseTimes};
+use super::version_files::{VersionFile, detect_version_files, group_by_package};
+use super::{
+    ShipConfig, executor, generate_and_write_changelog, get_repo_web_url, mark_release_pushed,
+    save_release_record,
+};
+
+/// One package of the monorepo and its changes since its last release.
+#[derive(Debug, Clone)]
+pub struct TrainPackage {
+    /// Name used in the tag.
+    pub name: String,
+    /// Label for display (see [`VersionFile::package_label`]).
+    pub label: String,
+    /// Directory relative to the project root (empty for the root package).
+    pub dir: PathBuf,
+    pub files: Vec<VersionFile>,
+    /// The package's last tag reachable from `HEAD`.
+    pub previous_tag: Option<String>,
+    /// Version of the last tag, or the manifest version if never tagged.
+    pub base_version: Option<Version>,
+    /// Commits since the last tag that changed the package, newest first.
+    pub commits: Vec<ParsedCommit>,
+}
+
+impl TrainPackage {
+    pub fn next_version(&self) -> Version {
+        calculate_next_version(self.base_version.as_ref(), &self.commits)
+    }
+}
+
+/// Tag of `version` of the package `name`: `core-v1.2.0`.
+pub fn train_tag(name: &str, version: &Version) -> String {
+    format!("{}-v{}", name, version)
+}
+
+/// Package name usable in a tag: npm scopes lose their `@` and `/`
+/// (`@acme/core` becomes `acme-core`).
+pub fn tag_safe_name(name: &str) -> String {
+    name.trim()
+        .trim_start_matches('@')
+        .replace('/', "-")
+        .replace(char::is_whitespace, "-")
+}
+
+/// The newest tag `<name>-v<version>` among `tags` accepted by `reachable`.
+pub fn latest_package_tag<'a>(
+    tags: &'a [TagInfo],
+    name: &str,
+    reachable: impl Fn(Oid) -> bool,
+) -> Option<(&'a TagInfo, Version)> {
+    let prefix = format!("{}-v", name);
+    tags.iter()
+        .filter_map(|tag| {
+            let version = Version::parse(tag.name.strip_prefix(&prefix)?).ok()?;
+            Some((tag, version))
+        })
+        .filter(|(tag, _)| reachable(tag.oid))
+        .max_by(|a, b| a.1.cmp(&b.1))
+}
+
+/// Index of the package in `dirs` owning `path`: the deepest directory
+/// containing it. The root package (empty directory) contains everything.
+pub fn owning_package(path: &Path, dirs: &[PathBuf]) -> Option<usize> {
+    dirs.iter()
+        .enumerate()
+        .filter(|(_, dir)| path.starts_with(dir))
+        .max_by_key(|(_, dir)| dir.components().count())
+        .map(|(i, _)| i)
+}
+
+/// Files changed by the commit `hash` relative to its first parent.
+fn changed_paths(repo: &Repository, hash: &str) -> Result<Vec<PathBuf>, git2::Error> {
+    let commit = repo.find_commit(Oid::from_str(hash)?)?;
+    let tree = commit.tree()?;
+    let parent_tree = match commit.parent(0) {
+        Ok(parent) => Some(parent.tree()?),
+        Err(_) => None,
+    };
+    let diff = repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), None)?;
+    Ok(diff
+        .deltas()
+        .filter_map(|delta| delta.new_file().path().or_else(|| delta.old_file().path()))
+        .map(Path::to_path_buf)
+        .collect())
+}
+
+/// Commits reachable from `head` and not from `from`; all of `head`'s
+/// history, root commit included, without `from`.
+fn commits_since(
+    repo: &Repository,
+    from: Option<Oid>,
+    head: Oid,
+) -> Result<Vec<ParsedCommit>, ShipError> {
+    let git_failed = |e: crate::error::GitError| ShipError::GitFailed(e.to_string());
+    let from_oid = match from {
+        Some(oid) => oid,
+        None => find_root_commit(repo, false).map_err(git_failed)?,
+    };
+    let mut commits = fetch_commits(repo, from_oid, head, false).map_err(git_failed)?;
+    // The revwalk hides the root commit itself
+    if from.is_none() {
+        let root = repo
+            .find_commit(from_oid)
+            .map_err(|e| ShipError::GitFailed(e.to_string()))?;
+        if !commits.iter().any(|c| c.hash == root.id().to_string()) {
+            commits.push(ParsedCommit::from_git2_commit(&root, false).map_err(git_failed)?);
+        }
+    }
+    Ok(commits)
+}
+
+/// Every package of `version_files` with its commits since its last tag.
+/// Packages without commits are included with none.
+pub fn plan_train(
+    repo: &Repository,
+    root: &Path,
+    version_files: &[VersionFile],
+    tags: &[TagInfo],
+    head: Oid,
+) -> Result<Vec<TrainPackage>, ShipError> {
+    let reachable = |oid: Oid| oid == head || repo.graph_descendant_of(head, oid).unwrap_or(false);
+    let root_name = root
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "root".to_string());
+
+    let mut packages: Vec<TrainPackage> = Vec::new();
+    for group in group_by_package(version_files) {
+        let first = group.files[0];
+        let name = tag_safe_name(
+            first
+                .package_name
+                .as_deref()
+                .or_else(|| first.package_dir.file_name().and_then(|n| n.to_str()))
+                .unwrap_or(&root_name),
+        );
+        if let Some(other) = packages.iter().find(|p| p.name == name) {
+            return Err(ShipError::DuplicatePackageName {
+                name,
+                first: other.label.clone(),
+                second: group.label,
+            });
+        }
+        let latest = latest_package_tag(tags, &name, reachable);
+        packages.push(TrainPackage {
+            previous_tag: latest.as_ref().map(|(tag, _)| tag.name.clone()),
+            base_version: latest
+                .map(|(_, version)| version)
+                .or_else(|| group.files.iter().map(|f| f.current_version.clone()).max()),
+            name,
+            label: group.label,
+            dir: first.package_dir.clone(),
+            files: group.files.into_iter().cloned().collect(),
+            commits: Vec::new(),
+        });
+    }
+
+    let dirs: Vec<PathBuf> = packages.iter().map(|p| p.dir.clone()).collect();
+    let mut owners: HashMap<String, Vec<usize>> = HashMap::new();
+    let tag_oids: Vec<Option<Oid>> = packages
+        .iter()
+        .map(|p| {
+            p.previous_tag
+                .as_ref()
+                .and_then(|name| tags.iter().find(|t| &t.name == name))
+                .map(|t| t.oid)
+        })
+        .collect();
+    for (i, from) in tag_oids.into_iter().enumerate() {
+        for commit in commits_since(repo, from, head)? {
+            if !owners.contains_key(&commit.hash) {
+                let paths = changed_paths(repo, &commit.hash)
+                    .map_err(|e| ShipError::GitFailed(e.to_string()))?;
+                let mut owning: Vec<usize> = paths
+                    .iter()
+                    .filter_map(|path| owning_package(path, &dirs))
+                    .collect();
+                owning.sort_unstable();
+                owning.dedup();
+                owners.insert(commit.hash.clone(), owning);
+            }
+            if owners[&commit.hash].contains(&i) {
+                packages[i].commits.push(commit);
+            }
+        }
+    }
+    Ok(packages)
+}
+
+/// The workspace package `name` among `packages`, matched by manifest
+/// name, tag name or directory. Only the root package and members of
+/// `workspaces` can be selected.
+pub fn select_package(
+    packages: Vec<TrainPackage>,
+    workspaces: &[Workspace],
+    name: &str,
+) -> Result<TrainPackage, ShipError> {
+    let mut eligible: Vec<TrainPackage> = packages
+        .into_iter()
+        .filter(|p| p.dir.as_os_str().is_empty() || workspaces.iter().any(|w| w.contains(&p.dir)))
+        .collect();
+    let tag_name = tag_safe_name(name);
+    let found = eligible.iter().position(|p| {
+        p.name == tag_name
+            || p.dir == Path::new(name)
+            || p.files
+                .iter()
+                .any(|f| f.package_name.as_deref() == Some(name))
+    });
+    match found {
+        Some(index) => Ok(eligible.swap_remove(index)),
+        None => Err(ShipError::UnknownPackage {
+            name: name.to_string(),
+            available: eligible
+                .iter()
+                .map(|p| p.name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        }),
+    }
+}
+
+/// Ship every changed package in one release commit with one tag each, or
+/// with `config.package` only that package.
+pub(super) async fn run_train(
+    mut config: ShipConfig,
+    repo: &Repository,
+    preflight: PreflightResult,
+    summary: &mut RunSummary,
+    started_at: DateTime<Utc>,
+) -> Result<(), ShipError> {
+    let package_name = config.package.clone();
+    if package_name.is_some() {
+        if config.migration_guide {
+            return Err(ShipError::PackageConflict("--migration-guide"));
+        }
+    } else if config.set_version.is_some() {
+        return Err(ShipError::TrainConflict("--set-version"));
+    } else if config.migration_guide {
+        return Err(ShipError::TrainConflict("--migration-guide"));
+    }
+    let workdir = repo
+        .workdir()
+        .ok_or_else(|| ShipError::GitFailed("Bare repository not supported".into()))?;
+    if !config.no_prs {
+        match &package_name {
+            Some(_) => println!("  [SKIP] PR enrichment: package entries come from its commits"),
+            None => {
+                println!("  [SKIP] PR enrichment: train entries come from each package's commits")
+            }
+        }
+        config.no_prs = true;
+    }
+
+    let version_files = config
+        .version_files
+        .apply(workdir, detect_version_files(workdir)?)?;
+    let tags = get_all_tags(repo).map_err(|e| ShipError::GitFailed(e.to_string()))?;
+    let head = repo
+        .head()
+        .ok()
+        .and_then(|head| head.target())
+        .ok_or_else(|| ShipError::GitFailed("Could not determine HEAD commit".into()))?;
+    let mut planned = plan_train(repo, workdir, &version_files, &tags, head)?;
+    let package_count = planned.len();
+    if let Some(name) = &package_name {
+        let workspaces = detect_workspaces(workdir);
+        if workspaces.is_empty() {
+            return Err(ShipError::NoWorkspace);
+        }
+        for workspace in &workspaces {
+            println!("  [PASS] Detected {}", workspace.kind);
+        }
+        let package = select_package(planned, &workspaces, name)?;
+        if package.commits.is_empty() {
+            return Err(ShipError::NoCommitsSinceTag(
+                package
+                    .previous_tag
+                    .unwrap_or_else(|| "the first commit".to_string()),
+            ));
+        }
+        planned = vec![package];
+    }
+
+    println!();
+    let mut train: Vec<(TrainPackage, Version, String, Vec<PathBuf>)> = Vec::new();
+    for package in planned {
+        if package.commits.is_empty() {
+            println!(
+                "  [SKIP] {}: no changes since {}",
+                package.label,
+                package
+                    .previous_tag
+                    .as_deref()
+                    .unwrap_or("the first commit")
+            );
+            continue;
+        }
+        let next = config
+            .set_version
+            .clone()
+            .unwrap_or_else(|| package.next_version());
+        let tag = train_tag(&package.name, &next);
+        if check_tag_exists(repo, &tag)? {
+            return Err(ShipError::TagAlreadyExists(tag));
+        }
+        let changelogs: Vec<PathBuf> = config
+            .outputs
+            .iter()
+            .map(|output| workdir.join(&package.dir).join(output))
+            .collect();
+        train.push((package, next, tag, changelogs));
+    }
+    if train.is_empty() {
+        return Err(ShipError::NoCommitsSinceTag(
+            "the last tag of any package".to_string(),
+        ));
+    }
+
+    // ── Plan ──
+    println!();
+    match &package_name {
+        Some(_) => println!("Package release:"),
+        None => println!(
+            "Release train ({} of {} packages):",
+            train.len(),
+            package_count
+        ),
+    }
+    let mut pending: Vec<Vec<PathBuf>> = Vec::new();
+    for (package, next, tag, changelogs) in &train {
+        println!(
+            "  {}: {} -> {} ({} commits), tag {}",
+            package.label,
+            package
+                .base_version
+                .as_ref()
+                .map(|v| v.to_string())
+                .unwrap_or_else(|| "none".to_string()),
+            next,
+            package.commits.len(),
+            tag
+        );
+        for vf in &package.files {
+            println!("    [UPDATE] {}", vf.display_path());
+        }
+        let mut package_pending = Vec::new();
+        for path in changelogs {
+            let display = path.strip_prefix(workdir).unwrap_or(path).display();
+            if read_changelog(path)?.is_some_and(|parsed| parsed.has_version(next)) {
+                println!(
+                    "    [SKIP] Changelog section for {} already exists in {}",
+                    next, display
+                );
+            } else {
+                println!("    [CREATE] Changelog section for {} in {}", next, display);
+                package_pending.push(path.clone());
+            }
+        }
+        pending.push(package_pending);
+    }
+    let tag_names: Vec<String> = train.iter().map(|(_, _, tag, _)| tag.clone()).collect();
+    let commit_message = format!("chore(release): {}", tag_names.join(", "));
+
+    if pending.iter().any(|p| !p.is_empty()) && !preflight.llm_available && !config.dry_run {
+        let provider = config.provider_selection.primary;
+        return Err(ShipError::LlmUnavailable(format!(
+            "{} CLI not available. Install/configure the provider or add the changelog sections manually.",
+            provider
+        )));
+    }
+
+    println!();
+    println!("Summary:");
+    println!("  Commit:    {}", commit_message);
+    println!("  Tags:      {}", tag_names.join(", "));
+    println!(
+        "  Push to:   {}/{} (atomic)",
+        preflight.remote_name, preflight.upstream_branch
+    );
+    summary.commits = preflight.commits_since_tag.len();
+
+    if config.dry_run {
+        println!();
+        println!("Dry run complete. No changes made.");
+        summary.print(config.summary_format);
+        return Ok(());
+    }
+
+    summary.end_stage();
+    println!();
+    let confirmed = Confirm::new()
+        .with_prompt("Proceed?")
+        .default(true)
+        .interact()
+        .map_err(|_| ShipError::Cancelled)?;
+    if !confirmed {
+        return Err(ShipError::Cancelled);
+    }
+    let approved_at = Utc::now();
+
+    // ── Execute ──
+    let journal = Journal::for_repo(workdir, "ship");
+    let mut llm = LlmRouter::new(config.provider_selection)
+        .with_journal(Journal::for_repository(repo, "ship"))
+        .with_policies(TaskPolicies::load(workdir));
+    let repo_url = get_repo_web_url(repo, config.pr_remote());
+    let mut files_to_stage: Vec<PathBuf> = Vec::new();
+    for ((package, next, tag, _), package_pending) in train.iter().zip(&pending) {
+        for vf in &package.files {
+            super::version_files::update_version_file(vf, next)?;
+            journal.record(
+                journal
+                    .entry(OperationKind::FileModified)
+                    .arg(vf.path.display().to_string())
+                    .detail(format!("{} -> {}", vf.current_version, next)),
+            );
+            println!("  [DONE] Updated {}", vf.display_path());
+            files_to_stage.push(vf.path.clone());
+        }
+        if package_pending.is_empty() {
+            continue;
+        }
+
+        let existing_changelog = read_changelog(&package_pending[0])
+            .ok()
+            .flatten()
+            .map(|c| c.raw_content);
+        let metadata = ReleaseMetadata {
+            date: config
+                .release_date
+                .release_date(package.commits.iter().map(|c| c.timestamp).max()),
+            heading: config.heading.clone(),
+            codename: config.codename.resolve(existing_changelog.as_deref()),
+            attributes: config.attributes.clone(),
+            compare_links: repo_url.clone().map(|repo_url| CompareLinks {
+                repo_url,
+                tag: tag.clone(),
+                previous_tag: package.previous_tag.clone(),
+            }),
+            attribution: None,
+            sort: config.entry_sort,
+            source_markers: false,
+            category_aliases: CategoryAliases::for_repository(repo),
+            template: config.template.clone(),
+        };
+        println!("  {}:", package.label);
+        generate_and_write_changelog(
+            repo,
+            &mut llm,
+            &package.commits,
+            next,
+            package.base_version.as_ref(),
+            package_pending,
+            &[],
+            metadata,
+            &config,
+            summary,
+        )
+        .await?;
+        for path in package_pending {
+            journal.record(
+                journal
+                    .entry(OperationKind::FileModified)
+                    .arg(path.display().to_string())
+                    .detail(format!("added section {}", next)),
+            );
+            println!(
+                "  [DONE] Updated {}",
+                path.strip_prefix(workdir).unwrap_or(path).display()
+            );
+            files_to_stage.push(path.clone());
+        }
+    }
+
+    summary.begin_stage("release");
+    let commit_result = executor::commit_release(&commit_message, &files_to_stage, false)?;
+    if commit_result.commit_created {
+        journal.record(
+            journal
+                .entry(OperationKind::CommitCreated)
+                .detail(commit_message.clone()),
+        );
+        println!("  [DONE] Created commit: {}", commit_message);
+    } else {
+        println!("  [SKIP] No changes to commit; using current HEAD");
+    }
+
+    let mut created: Vec<String> = Vec::new();
+    for tag in &tag_names {
+        if let Err(e) = executor::create_tag(tag, None) {
+            eprintln!("  [FAIL] {}", e);
+            roll_back(&created, commit_result.commit_created, &journal, &e);
+            return Err(e);
+        }
+        journal.record(journal.entry(OperationKind::TagCreated).arg(tag));
+        println!("  [DONE] Created tag: {}", tag);
+        created.push(tag.clone());
+    }
+
+    summary.record_llm(llm.usage());
+    let records = ReleaseRecordStore::for_repo(workdir);
+    let times = ReleaseTimes {
+        started_at,
+        approved_at,
+        tagged_at: Utc::now(),
+    };
+    for (_, next, tag, changelogs) in &train {
+        save_release_record(repo, &records, next, tag, changelogs, summary, times);
+    }
+
+    if let Err(e) =
+        executor::push_with_tags(&preflight.remote_name, &preflight.upstream_branch).await
+    {
+        eprintln!("  [FAIL] {}", e);
+        roll_back(&created, commit_result.commit_created, &journal, &e);
+        for tag in &created {
+            if let Err(e) = records.remove(tag) {
+                eprintln!("  [WARN] {}", e);
+            }
+        }
+        return Err(e);
+    }
+
+    let mut pushed = journal
+        .entry(OperationKind::Pushed)
+        .arg(&preflight.remote_name)
+        .arg(&preflight.upstream_branch);
+    for tag in &tag_names {
+        pushed = pushed.arg(tag);
+    }
+    journal.record(pushed);
+    println!(
+        "  [DONE] Pushed to {}/{}",
+        preflight.remote_name, preflight.upstream_branch
+    );
+    for tag in &tag_names {
+        mark_release_pushed(&records, tag);
+    }
+    println!();
+    match &package_name {
+        Some(_) => println!("Release shipped: {}", tag_names.join(", ")),
+        None => println!("Release train shipped: {}", tag_names.join(", ")),
+    }
+    summary.print(config.summary_format);
+    Ok(())
+}
+
+/// Delete the train's tags and undo its commit after a failure.
+fn roll_back(tags: &[String], commit_created: bool, journal: &Journal, cause: &ShipError) {
+    eprintln!();
+    eprintln!("Rolling back...");
+    match executor::rollback_tags(tags, commit_created) {
+        Ok(()) => {
+            let mut entry = journal.entry(OperationKind::RolledBack);
+            for tag in tags {
+                entry = entry.arg(tag);
+            }
+            journal.record(entry.detail(cause.to_string()));
+            for tag in tags {
+                eprintln!("  [DONE] Deleted tag {}", tag);
+            }
+            if commit_created {
+                eprintln!("  [DONE] Reset release commit");
+            }
+            eprintln!();
+            eprintln!("Release aborted. Fix the issue and try again.");
+        }
+        Err(rollback_err) => {
+            eprintln!("  [FAIL] Rollback failed: {}", rollback_err);
+            eprintln!();
+            let mut cleanup: Vec<String> =
+                tags.iter().map(|t| format!("git tag -d {}", t)).collect();
+            if commit_created {
+                cleanup.push("git reset --soft HEAD~1".to_string());
+            }
+            eprintln!("Manual cleanup may be needed: {}", cleanup.join(" && "));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use git2::Signature;
+    use tempfile::TempDir;
+
+    fn commit_files(repo: &Repository, files: &[(&str, &str)], message: &str) -> Oid {
+        let root = repo.workdir().unwrap();
+        let mut index = repo.index().unwrap();
+        for (path, content) in files {
+            let full = root.join(path);
+            std::fs::create_dir_all(full.parent().unwrap()).unwrap();
+            std::fs::write(&full, content).unwrap();
+            index.add_path(Path::new(path)).unwrap();
+        }
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parents: Vec<git2::Commit> = repo
+            .head()
+            .ok()
+            .and_then(|h| h.peel_to_commit().ok())
+            .into_iter()
+            .collect();
+        let parents: Vec<&git2::Commit> = parents.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn tag(repo: &Repository, name: &str, oid: Oid) {
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let object = repo.find_object(oid, None).unwrap();
+        repo.tag(name, &object, &sig, name, false).unwrap();
+    }
+
+    #[test]
+    fn test_tag_names() {
+        assert_eq!(train_tag("core", &Version::new(1, 2, 0)), "core-v1.2.0");
+        assert_eq!(tag_safe_name("@acme/core"), "acme-core");
+        assert_eq!(tag_safe_name("keryx"), "keryx");
+    }
+
+    #[test]
+    fn test_owning_package_is_deepest_directory() {
+        let dirs = vec![
+            PathBuf::new(),
+            PathBuf::from("crates/core"),
+            PathBuf::from("crates/core/macros"),
+        ];
+        assert_eq!(owning_package(Path::new("README.md"), &dirs), Some(0));
+        assert_eq!(
+            owning_package(Path::new("crates/core/src/lib.rs"), &dirs),
+            Some(1)
+        );
+        assert_eq!(
+            owning_package(Path::new("crates/core/macros/src/lib.rs"), &dirs),
+            Some(2)
+        );
+        // Component-wise: crates/core-extra is not inside crates/core
+        assert_eq!(
+            owning_package(Path::new("crates/core-extra/lib.rs"), &dirs[1..]),
+            None
+        );
+    }
+
+    #[test]
+    fn test_latest_package_tag() {
+        let oid = Oid::zero();
+        let tags: Vec<TagInfo> = ["core-v1.0.0", "core-v1.2.0", "cli-v3.0.0", "v9.0.0"]
+            .iter()
+            .map(|name| TagInfo {
+                name: name.to_string(),
+                oid,
+                version: None,
+            })
+            .collect();
+        let (tag, version) = latest_package_tag(&tags, "core", |_| true).unwrap();
+        assert_eq!(tag.name, "core-v1.2.0");
+        assert_eq!(version, Version::new(1, 2, 0));
+        assert!(latest_package_tag(&tags, "core", |_| false).is_none());
+        assert!(latest_package_tag(&tags, "web", |_| true).is_none());
+    }
+
+    #[test]
+    fn test_plan_train_assigns_commits_to_packages() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit_files(
+            &repo,
+            &[
+                (
+                    "packages/core/package.json",
+                    r#"{"name": "@acme/core", "version": "1.0.0"}"#,
+                ),
+                (
+                    "packages/cli/package.json",
+                    r#"{"name": "cli", "version": "0.4.0"}"#,
+                ),
+            ],
+            "chore: initial",
+        );
+        let released = commit_files(&repo, &[("packages/cli/index.js", "1")], "feat: cli");
+        tag(&repo, "acme-core-v1.0.0", released);
+        tag(&repo, "cli-v0.4.0", released);
+        commit_files(
+            &repo,
+            &[("packages/core/index.js", "2")],
+            "feat: core export",
+        );
+        let head = commit_files(&repo, &[("packages/core/util.js", "3")], "fix: core util");
+
+        let files = detect_version_files(dir.path()).unwrap();
+        let tags = get_all_tags(&repo).unwrap();
+        let packages = plan_train(&repo, dir.path(), &files, &tags, head).unwrap();
+
+        let core = packages.iter().find(|p| p.name == "acme-core").unwrap();
+        assert_eq!(core.previous_tag.as_deref(), Some("acme-core-v1.0.0"));
+        assert_eq!(core.commits.len(), 2);
+        assert_eq!(core.next_version(), Version::new(1, 1, 0));
+        let cli = packages.iter().find(|p| p.name == "cli").unwrap();
+        assert!(cli.commits.is_empty());
+    }
+
+    #[test]
+    fn test_select_package_among_workspace_members() {
+        let package = |name: &str, dir: &str| TrainPackage {
+            name: tag_safe_name(name),
+            label: dir.to_string(),
+            dir: PathBuf::from(dir),
+            files: vec![VersionFile {
+                path: PathBuf::from(dir).join("package.json"),
+                kind: super::super::version_files::VersionFileKind::PackageJson,
+                current_version: Version::new(1, 0, 0),
+                package_dir: PathBuf::from(dir),
+                package_name: Some(name.to_string()),
+            }],
+            previous_tag: None,
+            base_version: None,
+            commits: Vec::new(),
+        };
+        let packages = || {
+            vec![
+                package("@acme/core", "packages/core"),
+                package("scratch", "tools/scratch"),
+            ]
+        };
+        let workspaces = [Workspace {
+            kind: crate::workspace::WorkspaceKind::Npm,
+            members: vec!["packages/*".to_string()],
+            exclude: Vec::new(),
+        }];
+
+        for name in ["@acme/core", "acme-core", "packages/core"] {
+            let selected = select_package(packages(), &workspaces, name).unwrap();
+            assert_eq!(selected.name, "acme-core");
+        }
+        match select_package(packages(), &workspaces, "scratch") {
+            Err(ShipError::UnknownPackage { available, .. }) => assert_eq!(available, "acme-core"),
+            other => panic!("expected UnknownPackage, got {:?}", other.map(|p| p.name)),
+        }
+    }
+}
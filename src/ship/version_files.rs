@@ -1,15 +1,58 @@
 //! Version file detection and update across ecosystems.
 //!
 //! Supports Cargo.toml, package.json, and pyproject.toml (PEP 621 + Poetry).
-
-use std::path::{Path, PathBuf};
-
+//!
+//! Detection walks the whole project tree so that monorepos with many packages
+//! are handled. Directory traversal and manifest parsing are spread across
+//! worker threads, ignored directories (build output, dependency caches, and
+//! anything matched by the repository's `.gitignore`) are skipped, and results
+//! are returned in a stable order: the root package first, then nested packages
+//! sorted by path.
+
+use std::env;
+use std::num::NonZeroUsize;
+use std::path::{Component, Path, PathBuf};
+use std::sync::Mutex;
+use std::thread;
+
+use ignore::{WalkBuilder, WalkState};
 use semver::Version;
+use tracing::debug;
 
+use crate::atomic::write_atomic;
 use crate::error::ShipError;
 
+/// Environment variable pinning which version files are updated
+/// (comma-separated paths relative to the project root).
+const INCLUDE_ENV_VAR: &str = "KERYX_VERSION_FILES";
+
+/// Environment variable listing version files that must never be updated
+/// (comma-separated paths relative to the project root).
+const EXCLUDE_ENV_VAR: &str = "KERYX_EXCLUDE_VERSION_FILES";
+
+/// Manifest file names recognized as version files, in detection order.
+const MANIFESTS: [(&str, VersionFileKind); 3] = [
+    ("Cargo.toml", VersionFileKind::CargoToml),
+    ("package.json", VersionFileKind::PackageJson),
+    ("pyproject.toml", VersionFileKind::PyprojectToml),
+];
+
+/// Directory names that are never descended into during discovery.
+///
+/// These hold build output or third-party dependencies whose manifests must
+/// never be bumped. Hidden directories (`.git`, `.venv`, ...) are skipped too.
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    "target",
+    "dist",
+    "build",
+    "vendor",
+    "venv",
+    "__pycache__",
+];
+
 /// The kind of version file detected.
-#[derive(Debug, Clone, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum VersionFileKind {
     CargoToml,
     PackageJson,
@@ -32,56 +75,410 @@ pub struct VersionFile {
     pub path: PathBuf,
     pub kind: VersionFileKind,
     pub current_version: Version,
+    /// Directory of the owning package, relative to the project root
+    /// (empty for the root package).
+    pub package_dir: PathBuf,
+    /// Package name declared in the manifest, if any.
+    pub package_name: Option<String>,
+}
+
+impl VersionFile {
+    /// Path of the file relative to the project root.
+    pub fn relative_path(&self) -> PathBuf {
+        self.package_dir.join(self.kind.to_string())
+    }
+
+    /// Path of the file relative to the project root, for display.
+    pub fn display_path(&self) -> String {
+        self.relative_path().display().to_string()
+    }
+
+    /// Human-readable label of the owning package, e.g. `crates/core (core)`.
+    pub fn package_label(&self) -> String {
+        let dir = if self.package_dir.as_os_str().is_empty() {
+            ".".to_string()
+        } else {
+            self.package_dir.display().to_string()
+        };
+        match &self.package_name {
+            Some(name) => format!("{} ({})", dir, name),
+            None => dir,
+        }
+    }
 }
 
-/// Detect version files in the project root.
+/// Version files grouped by the package that owns them.
+#[derive(Debug)]
+pub struct PackageVersionFiles<'a> {
+    /// Label of the package (see [`VersionFile::package_label`]).
+    pub label: String,
+    /// Version files belonging to this package, in detection order.
+    pub files: Vec<&'a VersionFile>,
+}
+
+/// Detect version files in the project tree.
 ///
-/// Checks for Cargo.toml, package.json, and pyproject.toml in order.
-/// Returns all found files. Returns `ShipError::NoVersionFiles` if none found.
+/// Walks `root` recursively (skipping ignored directories), parses every
+/// Cargo.toml, package.json, and pyproject.toml that declares a version, and
+/// returns them sorted root-first, then by path. Manifests without a version
+/// field (e.g. workspace members inheriting it) are skipped.
+/// Returns `ShipError::NoVersionFiles` if none found.
 pub fn detect_version_files(root: &Path) -> Result<Vec<VersionFile>, ShipError> {
+    let manifests = discover_manifests(root);
+
     let mut files = Vec::new();
+    for result in parallel_map(&manifests, |(path, kind)| {
+        read_version_file(root, path, *kind)
+    }) {
+        if let Some(file) = result? {
+            files.push(file);
+        }
+    }
 
-    // Cargo.toml
-    let cargo_path = root.join("Cargo.toml");
-    if cargo_path.exists()
-        && let Some(version) = read_cargo_version(&cargo_path)?
-    {
-        files.push(VersionFile {
-            path: cargo_path,
-            kind: VersionFileKind::CargoToml,
-            current_version: version,
-        });
+    if files.is_empty() {
+        return Err(ShipError::NoVersionFiles);
     }
 
-    // package.json
-    let package_path = root.join("package.json");
-    if package_path.exists()
-        && let Some(version) = read_package_json_version(&package_path)?
-    {
-        files.push(VersionFile {
-            path: package_path,
-            kind: VersionFileKind::PackageJson,
-            current_version: version,
-        });
+    Ok(files)
+}
+
+/// Group version files by owning package, preserving detection order.
+///
+/// Relies on [`detect_version_files`] ordering, which keeps files of the same
+/// package adjacent.
+pub fn group_by_package(files: &[VersionFile]) -> Vec<PackageVersionFiles<'_>> {
+    let mut groups: Vec<PackageVersionFiles<'_>> = Vec::new();
+    for file in files {
+        match groups.last_mut() {
+            Some(group) if group.files[0].package_dir == file.package_dir => group.files.push(file),
+            _ => groups.push(PackageVersionFiles {
+                label: file.package_label(),
+                files: vec![file],
+            }),
+        }
+    }
+    groups
+}
+
+/// Explicit choice of which detected version files a release updates.
+///
+/// Paths are relative to the project root (absolute paths under the root are
+/// accepted too). An empty `include` list means "every detected file".
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct VersionFileSelection {
+    /// Only these files are updated. Every listed file must be detected.
+    pub include: Vec<PathBuf>,
+    /// These files are never updated.
+    pub exclude: Vec<PathBuf>,
+}
+
+impl VersionFileSelection {
+    /// Build a selection from CLI flags, falling back to the
+    /// `KERYX_VERSION_FILES` / `KERYX_EXCLUDE_VERSION_FILES` environment
+    /// variables for any list the flags leave empty.
+    pub fn from_flags_or_env(include: Vec<PathBuf>, exclude: Vec<PathBuf>) -> Self {
+        Self {
+            include: if include.is_empty() {
+                paths_from_env(INCLUDE_ENV_VAR)
+            } else {
+                include
+            },
+            exclude: if exclude.is_empty() {
+                paths_from_env(EXCLUDE_ENV_VAR)
+            } else {
+                exclude
+            },
+        }
+    }
+
+    /// Whether the selection pins an explicit set of files.
+    pub fn is_pinned(&self) -> bool {
+        !self.include.is_empty()
+    }
+
+    /// Narrow detected version files down to the selection.
+    ///
+    /// Fails if a pinned file was not detected, if nothing is left after
+    /// filtering, or if the pinned files disagree on their current version.
+    pub fn apply(
+        &self,
+        root: &Path,
+        files: Vec<VersionFile>,
+    ) -> Result<Vec<VersionFile>, ShipError> {
+        let include: Vec<PathBuf> = self
+            .include
+            .iter()
+            .map(|p| normalize_selection_path(root, p))
+            .collect();
+        let exclude: Vec<PathBuf> = self
+            .exclude
+            .iter()
+            .map(|p| normalize_selection_path(root, p))
+            .collect();
+
+        for wanted in &include {
+            if !files.iter().any(|f| &f.relative_path() == wanted) {
+                return Err(ShipError::VersionFileNotDetected {
+                    path: wanted.clone(),
+                });
+            }
+        }
+
+        let selected: Vec<VersionFile> = files
+            .into_iter()
+            .filter(|f| include.is_empty() || include.contains(&f.relative_path()))
+            .filter(|f| {
+                let excluded = exclude.contains(&f.relative_path());
+                if excluded {
+                    debug!("Excluding version file {}", f.display_path());
+                }
+                !excluded
+            })
+            .collect();
+
+        if selected.is_empty() {
+            return Err(ShipError::NoVersionFiles);
+        }
+
+        if self.is_pinned() {
+            check_versions_agree(&selected)?;
+        }
+
+        Ok(selected)
     }
+}
 
-    // pyproject.toml
-    let pyproject_path = root.join("pyproject.toml");
-    if pyproject_path.exists()
-        && let Some(version) = read_pyproject_version(&pyproject_path)?
+/// Ensure all version files declare the same current version.
+pub fn check_versions_agree(files: &[VersionFile]) -> Result<(), ShipError> {
+    let Some(first) = files.first() else {
+        return Ok(());
+    };
+    if files
+        .iter()
+        .all(|f| f.current_version == first.current_version)
     {
-        files.push(VersionFile {
-            path: pyproject_path,
-            kind: VersionFileKind::PyprojectToml,
-            current_version: version,
+        return Ok(());
+    }
+
+    let details = files
+        .iter()
+        .map(|f| format!("{} = {}", f.display_path(), f.current_version))
+        .collect::<Vec<_>>()
+        .join(", ");
+    Err(ShipError::InconsistentVersions(details))
+}
+
+/// How to resolve version files that disagree on their current version.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum VersionMismatchStrategy {
+    /// Bump from the latest release tag's version.
+    Tag,
+    /// Bump from the highest version declared by any version file.
+    Max,
+    /// Abort the release.
+    Fail,
+}
+
+/// Resolve disagreeing version files to a single base version.
+///
+/// Returns `Ok(None)` when the files already agree (the base version is left
+/// untouched), or `Ok(Some(version))` with the version to bump from.
+/// `Tag` fails when there is no release tag to fall back to.
+pub fn resolve_version_mismatch(
+    files: &[VersionFile],
+    tag_version: Option<&Version>,
+    strategy: VersionMismatchStrategy,
+) -> Result<Option<Version>, ShipError> {
+    let Err(mismatch) = check_versions_agree(files) else {
+        return Ok(None);
+    };
+
+    match strategy {
+        VersionMismatchStrategy::Tag => match tag_version {
+            Some(version) => Ok(Some(version.clone())),
+            None => Err(mismatch),
+        },
+        VersionMismatchStrategy::Max => Ok(files.iter().map(|f| &f.current_version).max().cloned()),
+        VersionMismatchStrategy::Fail => Err(mismatch),
+    }
+}
+
+/// Parse a comma-separated list of paths from an environment variable.
+fn paths_from_env(var: &str) -> Vec<PathBuf> {
+    match env::var(var) {
+        Ok(v) => v
+            .split(',')
+            .map(str::trim)
+            .filter(|p| !p.is_empty())
+            .map(PathBuf::from)
+            .collect(),
+        Err(_) => Vec::new(),
+    }
+}
+
+/// Make a user-supplied path comparable with [`VersionFile::relative_path`].
+fn normalize_selection_path(root: &Path, path: &Path) -> PathBuf {
+    let relative = path.strip_prefix(root).unwrap_or(path);
+    relative
+        .components()
+        .filter(|c| !matches!(c, Component::CurDir))
+        .collect()
+}
+
+/// Find all manifest paths under `root`, in stable order.
+///
+/// The tree is walked by [`ignore`]'s parallel walker, which applies the
+/// repository's ignore rules (`.gitignore`, `.git/info/exclude` and the
+/// global excludes file) as it descends.
+fn discover_manifests(root: &Path) -> Vec<(PathBuf, VersionFileKind)> {
+    let found = Mutex::new(Vec::new());
+    WalkBuilder::new(root)
+        .hidden(true)
+        .ignore(false)
+        .follow_links(false)
+        .filter_entry(|entry| {
+            entry.depth() == 0
+                || !entry.file_type().is_some_and(|t| t.is_dir())
+                || !IGNORED_DIRS.contains(&entry.file_name().to_string_lossy().as_ref())
+        })
+        .build_parallel()
+        .run(|| {
+            let found = &found;
+            Box::new(move |entry| {
+                let Ok(entry) = entry else {
+                    return WalkState::Continue;
+                };
+                if entry.file_type().is_some_and(|t| t.is_file())
+                    && let Some((_, kind)) = MANIFESTS
+                        .iter()
+                        .find(|(name, _)| entry.file_name() == *name)
+                {
+                    found
+                        .lock()
+                        .expect("manifest list poisoned")
+                        .push((entry.into_path(), *kind));
+                }
+                WalkState::Continue
+            })
         });
+
+    let mut found = found.into_inner().expect("manifest list poisoned");
+    found.sort_by(|(a, _), (b, _)| {
+        let depth = |p: &Path| p.components().count();
+        depth(a).cmp(&depth(b)).then_with(|| a.cmp(b))
+    });
+    found
+}
+
+/// Apply `f` to every item on a pool of scoped worker threads.
+///
+/// Results are returned in the same order as `items`.
+fn parallel_map<T, R, F>(items: &[T], f: F) -> Vec<R>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+{
+    let workers = thread::available_parallelism()
+        .map_or(1, NonZeroUsize::get)
+        .min(items.len());
+    if workers <= 1 {
+        return items.iter().map(&f).collect();
     }
 
-    if files.is_empty() {
-        return Err(ShipError::NoVersionFiles);
+    let chunk_size = items.len().div_ceil(workers);
+    let f = &f;
+    thread::scope(|scope| {
+        let handles: Vec<_> = items
+            .chunks(chunk_size)
+            .map(|chunk| scope.spawn(move || chunk.iter().map(f).collect::<Vec<R>>()))
+            .collect();
+        handles
+            .into_iter()
+            .flat_map(|handle| handle.join().expect("version file worker panicked"))
+            .collect()
+    })
+}
+
+/// Parse a single manifest into a [`VersionFile`], if it declares a version.
+fn read_version_file(
+    root: &Path,
+    path: &Path,
+    kind: VersionFileKind,
+) -> Result<Option<VersionFile>, ShipError> {
+    let content = read_file(path)?;
+    let manifest = parse_manifest(kind, path, &content)?;
+
+    Ok(manifest.map(|manifest| VersionFile {
+        path: path.to_path_buf(),
+        kind,
+        current_version: manifest.version,
+        package_dir: relative_package_dir(root, path),
+        package_name: manifest.name,
+    }))
+}
+
+/// Version declared by a manifest of `kind` with the given content, e.g. one
+/// read from a tag's tree rather than the working directory. `path` is only
+/// used in errors.
+pub fn manifest_version(
+    kind: VersionFileKind,
+    path: &Path,
+    content: &str,
+) -> Result<Option<Version>, ShipError> {
+    Ok(parse_manifest(kind, path, content)?.map(|manifest| manifest.version))
+}
+
+fn parse_manifest(
+    kind: VersionFileKind,
+    path: &Path,
+    content: &str,
+) -> Result<Option<ManifestVersion>, ShipError> {
+    match kind {
+        VersionFileKind::CargoToml => read_cargo_version(path, content),
+        VersionFileKind::PackageJson => read_package_json_version(path, content),
+        VersionFileKind::PyprojectToml => read_pyproject_version(path, content),
     }
+}
 
-    Ok(files)
+/// Directory containing `path`, relative to `root`.
+fn relative_package_dir(root: &Path, path: &Path) -> PathBuf {
+    path.parent()
+        .and_then(|dir| dir.strip_prefix(root).ok())
+        .map(|dir| {
+            dir.components()
+                .filter(|c| !matches!(c, Component::CurDir))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+/// Version and package name read from a manifest.
+struct ManifestVersion {
+    version: Version,
+    name: Option<String>,
+}
+
+/// Parse a version string found in `path`.
+fn parse_manifest_version(
+    path: &Path,
+    version: Option<&str>,
+    name: Option<&str>,
+) -> Result<Option<ManifestVersion>, ShipError> {
+    match version {
+        Some(s) => match Version::parse(s) {
+            Ok(version) => Ok(Some(ManifestVersion {
+                version,
+                name: name.map(str::to_string),
+            })),
+            Err(_) => Err(ShipError::InvalidVersion {
+                path: path.to_path_buf(),
+                version: s.to_string(),
+            }),
+        },
+        // No version field - skip this file (e.g., workspace member without version)
+        None => Ok(None),
+    }
 }
 
 /// Update a version file to the new version.
@@ -97,51 +494,34 @@ pub fn update_version_file(file: &VersionFile, new_version: &Version) -> Result<
 
 // --- Cargo.toml ---
 
-fn read_cargo_version(path: &Path) -> Result<Option<Version>, ShipError> {
-    let content = read_file(path)?;
-    let doc = parse_toml(path, &content)?;
+fn read_cargo_version(path: &Path, content: &str) -> Result<Option<ManifestVersion>, ShipError> {
+    let doc = parse_toml(path, content)?;
 
-    let version_str = doc
-        .get("package")
+    let package = doc.get("package");
+    let version_str = package
         .and_then(|p| p.get("version"))
         .and_then(|v| v.as_str());
+    let name = package.and_then(|p| p.get("name")).and_then(|v| v.as_str());
 
-    match version_str {
-        Some(s) => match Version::parse(s) {
-            Ok(v) => Ok(Some(v)),
-            Err(_) => Err(ShipError::InvalidVersion {
-                path: path.to_path_buf(),
-                version: s.to_string(),
-            }),
-        },
-        // No version field - skip this file (e.g., workspace member without version)
-        None => Ok(None),
-    }
+    parse_manifest_version(path, version_str, name)
 }
 
 // --- package.json ---
 
-fn read_package_json_version(path: &Path) -> Result<Option<Version>, ShipError> {
-    let content = read_file(path)?;
+fn read_package_json_version(
+    path: &Path,
+    content: &str,
+) -> Result<Option<ManifestVersion>, ShipError> {
     let json: serde_json::Value =
-        serde_json::from_str(&content).map_err(|e| ShipError::VersionFileUpdateFailed {
+        serde_json::from_str(content).map_err(|e| ShipError::VersionFileUpdateFailed {
             path: path.to_path_buf(),
             reason: format!("Invalid JSON: {}", e),
         })?;
 
     let version_str = json.get("version").and_then(|v| v.as_str());
+    let name = json.get("name").and_then(|v| v.as_str());
 
-    match version_str {
-        Some(s) => match Version::parse(s) {
-            Ok(v) => Ok(Some(v)),
-            Err(_) => Err(ShipError::InvalidVersion {
-                path: path.to_path_buf(),
-                version: s.to_string(),
-            }),
-        },
-        // No version field - skip this file
-        None => Ok(None),
-    }
+    parse_manifest_version(path, version_str, name)
 }
 
 fn update_package_json(path: &Path, new_version: &Version) -> Result<(), ShipError> {
@@ -166,9 +546,11 @@ fn update_package_json(path: &Path, new_version: &Version) -> Result<(), ShipErr
 
 // --- pyproject.toml ---
 
-fn read_pyproject_version(path: &Path) -> Result<Option<Version>, ShipError> {
-    let content = read_file(path)?;
-    let doc = parse_toml(path, &content)?;
+fn read_pyproject_version(
+    path: &Path,
+    content: &str,
+) -> Result<Option<ManifestVersion>, ShipError> {
+    let doc = parse_toml(path, content)?;
 
     // PEP 621: [project].version
     let version_str = doc
@@ -182,18 +564,17 @@ fn read_pyproject_version(path: &Path) -> Result<Option<Version>, ShipError> {
                 .and_then(|p| p.get("version"))
                 .and_then(|v| v.as_str())
         });
+    let name = doc
+        .get("project")
+        .and_then(|p| p.get("name"))
+        .or_else(|| {
+            doc.get("tool")
+                .and_then(|t| t.get("poetry"))
+                .and_then(|p| p.get("name"))
+        })
+        .and_then(|v| v.as_str());
 
-    match version_str {
-        Some(s) => match Version::parse(s) {
-            Ok(v) => Ok(Some(v)),
-            Err(_) => Err(ShipError::InvalidVersion {
-                path: path.to_path_buf(),
-                version: s.to_string(),
-            }),
-        },
-        // No version field - skip this file
-        None => Ok(None),
-    }
+    parse_manifest_version(path, version_str, name)
 }
 
 fn update_pyproject_toml(path: &Path, new_version: &Version) -> Result<(), ShipError> {
@@ -261,7 +642,7 @@ fn read_file(path: &Path) -> Result<String, ShipError> {
 }
 
 fn write_file(path: &Path, content: &str) -> Result<(), ShipError> {
-    std::fs::write(path, content).map_err(|e| ShipError::VersionFileUpdateFailed {
+    write_atomic(path, content).map_err(|e| ShipError::VersionFileUpdateFailed {
         path: path.to_path_buf(),
         reason: format!("Failed to write: {}", e),
     })
@@ -351,6 +732,8 @@ mod tests {
         let file = VersionFile {
             path: path.clone(),
             kind: VersionFileKind::CargoToml,
+            package_dir: PathBuf::new(),
+            package_name: None,
             current_version: Version::new(1, 0, 0),
         };
         update_version_file(&file, &Version::new(2, 0, 0)).unwrap();
@@ -370,6 +753,8 @@ mod tests {
         let file = VersionFile {
             path: path.clone(),
             kind: VersionFileKind::PackageJson,
+            package_dir: PathBuf::new(),
+            package_name: None,
             current_version: Version::new(1, 0, 0),
         };
         update_version_file(&file, &Version::new(1, 1, 0)).unwrap();
@@ -387,6 +772,8 @@ mod tests {
         let file = VersionFile {
             path: path.clone(),
             kind: VersionFileKind::PyprojectToml,
+            package_dir: PathBuf::new(),
+            package_name: None,
             current_version: Version::new(1, 0, 0),
         };
         update_version_file(&file, &Version::new(1, 2, 0)).unwrap();
@@ -408,6 +795,8 @@ mod tests {
         let file = VersionFile {
             path: path.clone(),
             kind: VersionFileKind::PyprojectToml,
+            package_dir: PathBuf::new(),
+            package_name: None,
             current_version: Version::new(0, 1, 0),
         };
         update_version_file(&file, &Version::new(0, 2, 0)).unwrap();
@@ -483,4 +872,337 @@ mod tests {
         // Should return NoVersionFiles, not InvalidVersion
         assert!(matches!(result, Err(ShipError::NoVersionFiles)));
     }
+
+    #[test]
+    fn test_detect_nested_packages_in_stable_order() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        for name in ["zeta", "alpha", "mid"] {
+            let pkg = root.join("crates").join(name);
+            fs::create_dir_all(&pkg).unwrap();
+            fs::write(
+                pkg.join("Cargo.toml"),
+                format!("[package]\nname = \"{}\"\nversion = \"1.0.0\"\n", name),
+            )
+            .unwrap();
+        }
+
+        let files = detect_version_files(root).unwrap();
+        let paths: Vec<String> = files.iter().map(|f| f.display_path()).collect();
+        assert_eq!(
+            paths,
+            vec![
+                "Cargo.toml",
+                "crates/alpha/Cargo.toml",
+                "crates/mid/Cargo.toml",
+                "crates/zeta/Cargo.toml",
+            ]
+        );
+        assert_eq!(files[1].package_name.as_deref(), Some("alpha"));
+        assert_eq!(files[1].package_dir, PathBuf::from("crates/alpha"));
+    }
+
+    #[test]
+    fn test_detect_skips_ignored_and_hidden_dirs() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "app", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+        for ignored in ["node_modules/dep", ".cache/pkg", "target/package/app"] {
+            let path = root.join(ignored);
+            fs::create_dir_all(&path).unwrap();
+            fs::write(
+                path.join("package.json"),
+                r#"{"name": "dep", "version": "9.9.9"}"#,
+            )
+            .unwrap();
+        }
+
+        let files = detect_version_files(root).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].package_name.as_deref(), Some("app"));
+    }
+
+    #[test]
+    fn test_detect_respects_gitignore() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        git2::Repository::init(root).unwrap();
+        fs::write(root.join(".gitignore"), "examples/\n").unwrap();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        let example = root.join("examples").join("demo");
+        fs::create_dir_all(&example).unwrap();
+        fs::write(
+            example.join("package.json"),
+            r#"{"name": "demo", "version": "0.0.1"}"#,
+        )
+        .unwrap();
+        // Nested ignore files apply below their directory
+        let web = root.join("packages").join("web");
+        fs::create_dir_all(web.join("generated")).unwrap();
+        fs::write(
+            web.join(".gitignore"),
+            "generated/
+",
+        )
+        .unwrap();
+        for dir in [web.clone(), web.join("generated")] {
+            fs::write(
+                dir.join("package.json"),
+                r#"{"name": "web", "version": "1.0.0"}"#,
+            )
+            .unwrap();
+        }
+
+        let files = detect_version_files(root).unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!(files[0].kind, VersionFileKind::CargoToml);
+        assert_eq!(files[1].package_dir, PathBuf::from("packages/web"));
+    }
+
+    #[test]
+    fn test_group_by_package() {
+        let dir = tempfile::tempdir().unwrap();
+        let root = dir.path();
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("package.json"),
+            r#"{"name": "app", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+        let web = root.join("web");
+        fs::create_dir_all(&web).unwrap();
+        fs::write(
+            web.join("package.json"),
+            r#"{"name": "web", "version": "1.0.0"}"#,
+        )
+        .unwrap();
+
+        let files = detect_version_files(root).unwrap();
+        let groups = group_by_package(&files);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[0].label, ". (app)");
+        assert_eq!(groups[0].files.len(), 2);
+        assert_eq!(groups[1].label, "web (web)");
+        assert_eq!(groups[1].files.len(), 1);
+    }
+
+    #[test]
+    fn test_parallel_map_preserves_order() {
+        let items: Vec<usize> = (0..100).collect();
+        let doubled = parallel_map(&items, |n| n * 2);
+        assert_eq!(doubled, (0..100).map(|n| n * 2).collect::<Vec<_>>());
+    }
+
+    fn write_manifests(root: &Path) {
+        fs::write(
+            root.join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+        let example = root.join("example");
+        fs::create_dir_all(&example).unwrap();
+        fs::write(
+            example.join("package.json"),
+            r#"{"name": "example", "version": "0.3.0"}"#,
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn test_selection_default_keeps_all_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifests(dir.path());
+
+        let files = detect_version_files(dir.path()).unwrap();
+        let selected = VersionFileSelection::default()
+            .apply(dir.path(), files)
+            .unwrap();
+        assert_eq!(selected.len(), 2);
+    }
+
+    #[test]
+    fn test_selection_exclude() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifests(dir.path());
+
+        let files = detect_version_files(dir.path()).unwrap();
+        let selection = VersionFileSelection {
+            include: Vec::new(),
+            exclude: vec![PathBuf::from("./example/package.json")],
+        };
+        let selected = selection.apply(dir.path(), files).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].kind, VersionFileKind::CargoToml);
+    }
+
+    #[test]
+    fn test_selection_include_pins_files() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifests(dir.path());
+
+        let files = detect_version_files(dir.path()).unwrap();
+        let selection = VersionFileSelection {
+            include: vec![dir.path().join("Cargo.toml")],
+            exclude: Vec::new(),
+        };
+        let selected = selection.apply(dir.path(), files).unwrap();
+        assert_eq!(selected.len(), 1);
+        assert_eq!(selected[0].display_path(), "Cargo.toml");
+    }
+
+    #[test]
+    fn test_selection_include_missing_file_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifests(dir.path());
+
+        let files = detect_version_files(dir.path()).unwrap();
+        let selection = VersionFileSelection {
+            include: vec![PathBuf::from("pyproject.toml")],
+            exclude: Vec::new(),
+        };
+        let result = selection.apply(dir.path(), files);
+        assert!(matches!(
+            result,
+            Err(ShipError::VersionFileNotDetected { .. })
+        ));
+    }
+
+    #[test]
+    fn test_selection_include_requires_agreeing_versions() {
+        let dir = tempfile::tempdir().unwrap();
+        write_manifests(dir.path());
+
+        let files = detect_version_files(dir.path()).unwrap();
+        let selection = VersionFileSelection {
+            include: vec![
+                PathBuf::from("Cargo.toml"),
+                PathBuf::from("example/package.json"),
+            ],
+            exclude: Vec::new(),
+        };
+        let result = selection.apply(dir.path(), files);
+        assert!(matches!(result, Err(ShipError::InconsistentVersions(_))));
+    }
+
+    #[test]
+    fn test_selection_excluding_everything_fails() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"app\"\nversion = \"1.0.0\"\n",
+        )
+        .unwrap();
+
+        let files = detect_version_files(dir.path()).unwrap();
+        let selection = VersionFileSelection {
+            include: Vec::new(),
+            exclude: vec![PathBuf::from("Cargo.toml")],
+        };
+        let result = selection.apply(dir.path(), files);
+        assert!(matches!(result, Err(ShipError::NoVersionFiles)));
+    }
+
+    #[test]
+    fn test_selection_from_env() {
+        temp_env::with_vars(
+            [
+                (INCLUDE_ENV_VAR, Some("Cargo.toml, crates/core/Cargo.toml")),
+                (EXCLUDE_ENV_VAR, None),
+            ],
+            || {
+                let selection = VersionFileSelection::from_flags_or_env(Vec::new(), Vec::new());
+                assert_eq!(
+                    selection.include,
+                    vec![
+                        PathBuf::from("Cargo.toml"),
+                        PathBuf::from("crates/core/Cargo.toml")
+                    ]
+                );
+                assert!(selection.exclude.is_empty());
+            },
+        );
+    }
+
+    #[test]
+    fn test_selection_flags_override_env() {
+        temp_env::with_var(INCLUDE_ENV_VAR, Some("package.json"), || {
+            let selection = VersionFileSelection::from_flags_or_env(
+                vec![PathBuf::from("Cargo.toml")],
+                Vec::new(),
+            );
+            assert_eq!(selection.include, vec![PathBuf::from("Cargo.toml")]);
+        });
+    }
+
+    fn version_file(path: &str, version: Version) -> VersionFile {
+        VersionFile {
+            path: PathBuf::from(path),
+            kind: VersionFileKind::CargoToml,
+            package_dir: PathBuf::new(),
+            package_name: None,
+            current_version: version,
+        }
+    }
+
+    #[test]
+    fn test_resolve_mismatch_when_versions_agree() {
+        let files = vec![
+            version_file("Cargo.toml", Version::new(1, 4, 0)),
+            version_file("package.json", Version::new(1, 4, 0)),
+        ];
+        let resolved =
+            resolve_version_mismatch(&files, None, VersionMismatchStrategy::Fail).unwrap();
+        assert_eq!(resolved, None);
+    }
+
+    #[test]
+    fn test_resolve_mismatch_strategies() {
+        let files = vec![
+            version_file("Cargo.toml", Version::new(1, 4, 0)),
+            version_file("package.json", Version::new(1, 3, 2)),
+        ];
+        let tag = Version::new(1, 3, 0);
+
+        assert_eq!(
+            resolve_version_mismatch(&files, Some(&tag), VersionMismatchStrategy::Tag).unwrap(),
+            Some(Version::new(1, 3, 0))
+        );
+        assert_eq!(
+            resolve_version_mismatch(&files, Some(&tag), VersionMismatchStrategy::Max).unwrap(),
+            Some(Version::new(1, 4, 0))
+        );
+        assert!(matches!(
+            resolve_version_mismatch(&files, Some(&tag), VersionMismatchStrategy::Fail),
+            Err(ShipError::InconsistentVersions(_))
+        ));
+    }
+
+    #[test]
+    fn test_resolve_mismatch_tag_without_tag_fails() {
+        let files = vec![
+            version_file("Cargo.toml", Version::new(1, 4, 0)),
+            version_file("package.json", Version::new(1, 3, 2)),
+        ];
+        assert!(matches!(
+            resolve_version_mismatch(&files, None, VersionMismatchStrategy::Tag),
+            Err(ShipError::InconsistentVersions(_))
+        ));
+    }
 }
@@ -0,0 +1,358 @@
+//! End-of-run recap for `generate` and `ship`.
+//!
+//! Progress lines are interleaved with warnings and provider output, so the
+//! numbers that matter (what went in, what came out, where the time went) are
+//! collected into a [`RunSummary`] and printed once when the run finishes,
+//! either as a short text block or as a single JSON object.
+//!
+//! Subsystems that ran with less data than usual (PRs that could not be
+//! fetched, incomplete verification evidence, a provider fallback) are
+//! recorded as [`Degradation`]s and listed together at the end, so a
+//! changelog built from partial input is obvious from the last lines of
+//! output.
+
+use std::time::{Duration, Instant};
+
+use serde::Serialize;
+use tracing::warn;
+
+use crate::llm::LlmUsage;
+
+/// How the run summary is printed.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum SummaryFormat {
+    /// Human-readable block.
+    #[default]
+    Text,
+    /// One JSON object on a single line, for scripts and CI.
+    Json,
+}
+
+/// Wall-clock time spent in one stage of a run.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct StageTiming {
+    /// Stage name (e.g. `"collect"`, `"generate"`, `"verify"`).
+    pub name: String,
+    /// Duration in milliseconds.
+    pub duration_ms: u64,
+}
+
+/// A subsystem that ran with reduced data during the run.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Degradation {
+    /// What was degraded (`"pull requests"`, `"verification"`, `"llm"`).
+    pub subsystem: String,
+    /// What was missing and what it means for the changelog.
+    pub reason: String,
+}
+
+/// Subsystem name of the degradation recorded for provider fallbacks.
+const LLM_SUBSYSTEM: &str = "llm";
+
+/// Structured recap of a single `generate` or `ship` run.
+#[derive(Debug, Clone, Serialize)]
+pub struct RunSummary {
+    /// Command that produced the summary (`"generate"` or `"ship"`).
+    pub command: String,
+    /// Number of commits analyzed.
+    pub commits: usize,
+    /// Number of merged PRs used as input.
+    pub pull_requests: usize,
+    /// Entries produced by the generation pass.
+    pub entries_generated: usize,
+    /// Entries remaining after verification.
+    pub entries_kept: usize,
+    /// Entries removed by verification.
+    pub entries_removed: usize,
+    /// Provider that answered the last LLM call, if any call succeeded.
+    pub provider: Option<String>,
+    /// Number of LLM provider invocations (including failed ones).
+    pub llm_calls: usize,
+    /// Number of times the fallback provider had to take over.
+    pub fallbacks: usize,
+    /// Time spent per stage, in the order the stages ran.
+    pub stages: Vec<StageTiming>,
+    /// Subsystems that ran with reduced data, in the order they were hit.
+    pub degradations: Vec<Degradation>,
+    /// Stage currently being timed.
+    #[serde(skip)]
+    running: Option<(String, Instant)>,
+}
+
+impl RunSummary {
+    /// Start an empty summary for `command`.
+    pub fn new(command: &str) -> Self {
+        Self {
+            command: command.to_string(),
+            commits: 0,
+            pull_requests: 0,
+            entries_generated: 0,
+            entries_kept: 0,
+            entries_removed: 0,
+            provider: None,
+            llm_calls: 0,
+            fallbacks: 0,
+            stages: Vec::new(),
+            degradations: Vec::new(),
+            running: None,
+        }
+    }
+
+    /// Start timing `name`, closing the stage that was running, if any.
+    pub fn begin_stage(&mut self, name: &str) {
+        self.end_stage();
+        self.running = Some((name.to_string(), Instant::now()));
+    }
+
+    /// Stop timing the running stage.
+    pub fn end_stage(&mut self) {
+        if let Some((name, started)) = self.running.take() {
+            self.push_stage(&name, started.elapsed());
+        }
+    }
+
+    /// Record a stage whose duration was measured elsewhere.
+    pub fn push_stage(&mut self, name: &str, duration: Duration) {
+        self.stages.push(StageTiming {
+            name: name.to_string(),
+            duration_ms: duration.as_millis().try_into().unwrap_or(u64::MAX),
+        });
+    }
+
+    /// Record how many entries were generated and how many survived verification.
+    pub fn record_entries(&mut self, generated: usize, kept: usize) {
+        self.entries_generated = generated;
+        self.entries_kept = kept;
+        self.entries_removed = generated.saturating_sub(kept);
+    }
+
+    /// Record that `subsystem` ran with reduced data. Repeated reports are
+    /// kept once.
+    pub fn degrade(&mut self, subsystem: &str, reason: impl Into<String>) {
+        let degradation = Degradation {
+            subsystem: subsystem.to_string(),
+            reason: reason.into(),
+        };
+        if !self.degradations.contains(&degradation) {
+            self.degradations.push(degradation);
+        }
+    }
+
+    /// Record provider usage from the LLM router. Fallbacks are recorded as
+    /// a degradation: the output came from the provider that was not chosen.
+    pub fn record_llm(&mut self, usage: LlmUsage) {
+        self.provider = usage.provider.map(|p| p.to_string());
+        self.llm_calls = usage.calls;
+        self.fallbacks = usage.fallbacks;
+
+        self.degradations.retain(|d| d.subsystem != LLM_SUBSYSTEM);
+        if usage.fallbacks > 0 {
+            self.degrade(
+                LLM_SUBSYSTEM,
+                format!(
+                    "the primary provider failed {} {}; the fallback answered instead",
+                    usage.fallbacks,
+                    plural(usage.fallbacks, "time", "times")
+                ),
+            );
+        }
+    }
+
+    /// Total time across all recorded stages.
+    pub fn total_duration(&self) -> Duration {
+        Duration::from_millis(self.stages.iter().map(|s| s.duration_ms).sum())
+    }
+
+    /// Render the summary as a human-readable block.
+    pub fn render_text(&self) -> String {
+        let mut out = format!("Run summary ({}):\n", self.command);
+        out.push_str(&format!("  Commits:   {} analyzed\n", self.commits));
+        out.push_str(&format!("  PRs:       {} used\n", self.pull_requests));
+        out.push_str(&format!(
+            "  Entries:   {} generated, {} kept, {} removed\n",
+            self.entries_generated, self.entries_kept, self.entries_removed
+        ));
+
+        let provider = match &self.provider {
+            Some(provider) => format!(
+                "{} ({} {}, {} {})",
+                provider,
+                self.llm_calls,
+                plural(self.llm_calls, "call", "calls"),
+                self.fallbacks,
+                plural(self.fallbacks, "fallback", "fallbacks")
+            ),
+            None => "not used".to_string(),
+        };
+        out.push_str(&format!("  Provider:  {}\n", provider));
+
+        let stages: Vec<String> = self
+            .stages
+            .iter()
+            .map(|s| format!("{} {}", s.name, format_seconds(s.duration_ms)))
+            .collect();
+        out.push_str(&format!(
+            "  Duration:  {} total",
+            format_seconds(self.total_duration().as_millis() as u64)
+        ));
+        if !stages.is_empty() {
+            out.push_str(&format!(" ({})", stages.join(", ")));
+        }
+        out.push('\n');
+
+        if !self.degradations.is_empty() {
+            out.push_str("\nThis changelog was generated with reduced data:\n");
+            for d in &self.degradations {
+                out.push_str(&format!("  • {}: {}\n", d.subsystem, d.reason));
+            }
+        }
+        out
+    }
+
+    /// Render the summary as a single-line JSON object.
+    pub fn render_json(&self) -> serde_json::Result<String> {
+        serde_json::to_string(self)
+    }
+
+    /// Close the running stage and print the summary to stdout.
+    pub fn print(&mut self, format: SummaryFormat) {
+        self.end_stage();
+        match format {
+            SummaryFormat::Text => {
+                println!();
+                print!("{}", self.render_text());
+            }
+            SummaryFormat::Json => match self.render_json() {
+                Ok(json) => println!("{}", json),
+                Err(e) => warn!("Failed to serialize run summary: {}", e),
+            },
+        }
+    }
+}
+
+fn plural<'a>(count: usize, one: &'a str, many: &'a str) -> &'a str {
+    if count == 1 { one } else { many }
+}
+
+fn format_seconds(millis: u64) -> String {
+    format!("{:.1}s", millis as f64 / 1000.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::llm::Provider;
+
+    fn sample() -> RunSummary {
+        let mut summary = RunSummary::new("generate");
+        summary.commits = 12;
+        summary.pull_requests = 3;
+        summary.record_entries(8, 7);
+        summary.record_llm(LlmUsage {
+            calls: 3,
+            fallbacks: 1,
+            provider: Some(Provider::Codex),
+        });
+        summary.push_stage("collect", Duration::from_millis(300));
+        summary.push_stage("generate", Duration::from_millis(12_400));
+        summary
+    }
+
+    #[test]
+    fn test_record_entries_computes_removed() {
+        let mut summary = RunSummary::new("ship");
+        summary.record_entries(5, 7);
+        assert_eq!(summary.entries_removed, 0);
+        summary.record_entries(5, 2);
+        assert_eq!(summary.entries_removed, 3);
+    }
+
+    #[test]
+    fn test_begin_stage_closes_previous() {
+        let mut summary = RunSummary::new("generate");
+        summary.begin_stage("collect");
+        summary.begin_stage("generate");
+        summary.end_stage();
+        summary.end_stage();
+
+        let names: Vec<_> = summary.stages.iter().map(|s| s.name.as_str()).collect();
+        assert_eq!(names, vec!["collect", "generate"]);
+    }
+
+    #[test]
+    fn test_render_text() {
+        let text = sample().render_text();
+        assert!(text.starts_with("Run summary (generate):"));
+        assert!(text.contains("Commits:   12 analyzed"));
+        assert!(text.contains("PRs:       3 used"));
+        assert!(text.contains("Entries:   8 generated, 7 kept, 1 removed"));
+        assert!(text.contains("Provider:  Codex (3 calls, 1 fallback)"));
+        assert!(text.contains("Duration:  12.7s total (collect 0.3s, generate 12.4s)"));
+    }
+
+    #[test]
+    fn test_render_text_without_llm() {
+        let text = RunSummary::new("ship").render_text();
+        assert!(text.contains("Provider:  not used"));
+        assert!(text.contains("Duration:  0.0s total\n"));
+    }
+
+    #[test]
+    fn test_render_text_lists_degradations() {
+        let mut summary = sample();
+        summary.degrade("pull requests", "GitHub API error; commits only");
+        summary.degrade("pull requests", "GitHub API error; commits only");
+        let text = summary.render_text();
+        assert!(text.contains(
+            "This changelog was generated with reduced data:\n  \
+             • llm: the primary provider failed 1 time; the fallback answered instead\n  \
+             • pull requests: GitHub API error; commits only\n"
+        ));
+        assert!(
+            !sample_without_fallback()
+                .render_text()
+                .contains("reduced data")
+        );
+    }
+
+    #[test]
+    fn test_record_llm_replaces_fallback_degradation() {
+        let mut summary = sample();
+        summary.record_llm(LlmUsage {
+            calls: 5,
+            fallbacks: 2,
+            provider: Some(Provider::Codex),
+        });
+        assert_eq!(summary.degradations.len(), 1);
+        assert!(summary.degradations[0].reason.contains("failed 2 times"));
+
+        summary.record_llm(LlmUsage::default());
+        assert!(summary.degradations.is_empty());
+    }
+
+    fn sample_without_fallback() -> RunSummary {
+        let mut summary = sample();
+        summary.record_llm(LlmUsage {
+            calls: 3,
+            fallbacks: 0,
+            provider: Some(Provider::Claude),
+        });
+        summary
+    }
+
+    #[test]
+    fn test_render_json() {
+        let json: serde_json::Value =
+            serde_json::from_str(&sample().render_json().unwrap()).unwrap();
+        assert_eq!(json["command"], "generate");
+        assert_eq!(json["commits"], 12);
+        assert_eq!(json["pull_requests"], 3);
+        assert_eq!(json["entries_removed"], 1);
+        assert_eq!(json["provider"], "Codex");
+        assert_eq!(json["fallbacks"], 1);
+        assert_eq!(json["stages"][1]["name"], "generate");
+        assert_eq!(json["stages"][1]["duration_ms"], 12_400);
+        assert_eq!(json["degradations"][0]["subsystem"], "llm");
+        assert!(json.get("running").is_none());
+    }
+}
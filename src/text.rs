@@ -0,0 +1,206 @@
+//! Unicode-aware text truncation shared across modules.
+//!
+//! Cutting strings at arbitrary byte offsets panics on multi-byte characters,
+//! and cutting at character boundaries can still split a user-perceived
+//! character (e.g. `e` + combining accent, flags, ZWJ emoji sequences).
+//! These helpers always cut between grapheme clusters, and the terminal
+//! helpers measure display width so wide (CJK, emoji) text lines up.
+
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Marker appended (or inserted) where text was cut.
+pub const ELLIPSIS: &str = "...";
+
+/// Split `s` into extended grapheme clusters (UAX #29).
+pub fn graphemes(s: &str) -> unicode_segmentation::Graphemes<'_> {
+    s.graphemes(true)
+}
+
+/// Longest prefix of `s` that fits in `max_bytes` without splitting a
+/// grapheme cluster.
+pub fn truncate_bytes(s: &str, max_bytes: usize) -> &str {
+    if s.len() <= max_bytes {
+        return s;
+    }
+    let mut end = 0;
+    for cluster in graphemes(s) {
+        if end + cluster.len() > max_bytes {
+            break;
+        }
+        end += cluster.len();
+    }
+    &s[..end]
+}
+
+/// First `max` grapheme clusters of `s`.
+pub fn truncate_graphemes(s: &str, max: usize) -> &str {
+    let end: usize = graphemes(s).take(max).map(str::len).sum();
+    &s[..end]
+}
+
+/// Truncate `s` to at most `max_bytes`, appending `suffix` when cut.
+///
+/// The suffix is not counted against the limit (matching how callers bound
+/// the size of the original text, e.g. `"...[truncated]"`).
+pub fn truncate_bytes_with_suffix(s: &str, max_bytes: usize, suffix: &str) -> String {
+    if s.len() <= max_bytes {
+        s.to_string()
+    } else {
+        format!("{}{}", truncate_bytes(s, max_bytes), suffix)
+    }
+}
+
+/// Terminal display width of `s` in columns.
+pub fn display_width(s: &str) -> usize {
+    s.width()
+}
+
+/// Truncate `s` to fit in `max_width` terminal columns, ending in
+/// [`ELLIPSIS`] when cut. The ellipsis counts against the width.
+pub fn truncate_to_width(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    let budget = max_width.saturating_sub(ELLIPSIS.len());
+    let mut width = 0;
+    let mut end = 0;
+    for cluster in graphemes(s) {
+        let w = display_width(cluster);
+        if width + w > budget {
+            break;
+        }
+        width += w;
+        end += cluster.len();
+    }
+    format!("{}{}", &s[..end], ELLIPSIS)
+}
+
+/// Shorten `s` to `max_width` columns by replacing its middle with
+/// [`ELLIPSIS`], keeping both ends visible. Suited to file paths, where the
+/// leading directory and the file name carry the most information.
+pub fn middle_ellipsis(s: &str, max_width: usize) -> String {
+    if display_width(s) <= max_width {
+        return s.to_string();
+    }
+    let budget = max_width.saturating_sub(ELLIPSIS.len());
+    let clusters: Vec<&str> = graphemes(s).collect();
+
+    // Give the tail (file name) the larger half
+    let head_budget = budget / 2;
+    let tail_budget = budget - head_budget;
+
+    let mut head_width = 0;
+    let mut head_len = 0;
+    for cluster in &clusters {
+        let w = display_width(cluster);
+        if head_width + w > head_budget {
+            break;
+        }
+        head_width += w;
+        head_len += 1;
+    }
+
+    let mut tail_width = 0;
+    let mut tail_len = 0;
+    for cluster in clusters[head_len..].iter().rev() {
+        let w = display_width(cluster);
+        if tail_width + w > tail_budget {
+            break;
+        }
+        tail_width += w;
+        tail_len += 1;
+    }
+
+    let head: String = clusters[..head_len].concat();
+    let tail: String = clusters[clusters.len() - tail_len..].concat();
+    format!("{}{}{}", head, ELLIPSIS, tail)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_graphemes_keep_combining_marks_together() {
+        let s = "e\u{301}a";
+        assert_eq!(graphemes(s).collect::<Vec<_>>(), vec!["e\u{301}", "a"]);
+    }
+
+    #[test]
+    fn test_graphemes_keep_emoji_sequences_together() {
+        // Family (ZWJ sequence), thumbs up with skin tone, flag pair
+        let family = "👨\u{200D}👩\u{200D}👧";
+        let thumbs = "👍\u{1F3FD}";
+        let flags = "🇩🇪🇫🇷";
+        let s = format!("{}{}{}", family, thumbs, flags);
+        assert_eq!(
+            graphemes(&s).collect::<Vec<_>>(),
+            vec![family, thumbs, "🇩🇪", "🇫🇷"]
+        );
+    }
+
+    #[test]
+    fn test_graphemes_follow_uax29() {
+        // Hangul syllable from conjoining jamo, keycap sequence, flag after
+        // a lone regional indicator
+        let hangul = "\u{1100}\u{1161}\u{11A8}";
+        let keycap = "1\u{FE0F}\u{20E3}";
+        let s = format!("{}{}\u{1F1E6}🇩🇪", hangul, keycap);
+        assert_eq!(
+            graphemes(&s).collect::<Vec<_>>(),
+            vec![hangul, keycap, "\u{1F1E6}\u{1F1E9}", "\u{1F1EA}"]
+        );
+        assert_eq!(truncate_graphemes(hangul, 1), hangul);
+    }
+
+    #[test]
+    fn test_truncate_bytes_never_splits_clusters() {
+        let s = "ab🎉cd";
+        assert_eq!(truncate_bytes(s, 4), "ab");
+        assert_eq!(truncate_bytes(s, 6), "ab🎉");
+        assert_eq!(truncate_bytes(s, 100), s);
+
+        let accented = "e\u{301}e\u{301}";
+        assert_eq!(truncate_bytes(accented, 4), "e\u{301}");
+    }
+
+    #[test]
+    fn test_truncate_graphemes() {
+        assert_eq!(truncate_graphemes("日本語テキスト", 3), "日本語");
+        assert_eq!(truncate_graphemes("ab", 5), "ab");
+    }
+
+    #[test]
+    fn test_truncate_bytes_with_suffix() {
+        assert_eq!(truncate_bytes_with_suffix("short", 10, "..."), "short");
+        assert_eq!(
+            truncate_bytes_with_suffix("日本語", 7, "...[truncated]"),
+            "日本...[truncated]"
+        );
+    }
+
+    #[test]
+    fn test_truncate_to_width_counts_wide_chars() {
+        assert_eq!(truncate_to_width("hello world", 8), "hello...");
+        assert_eq!(truncate_to_width("hello", 8), "hello");
+        // Each CJK character is two columns wide
+        assert_eq!(truncate_to_width("日本語テキスト", 9), "日本語...");
+        assert!(display_width(&truncate_to_width("日本語テキスト", 9)) <= 9);
+    }
+
+    #[test]
+    fn test_middle_ellipsis_keeps_both_ends() {
+        let path = "src/verification/scanner/very/deep/module.rs";
+        let short = middle_ellipsis(path, 24);
+        assert_eq!(short, "src/verifi...p/module.rs");
+        assert!(display_width(&short) <= 24);
+        assert_eq!(middle_ellipsis("src/lib.rs", 24), "src/lib.rs");
+    }
+
+    #[test]
+    fn test_tiny_widths_do_not_panic() {
+        assert_eq!(truncate_to_width("hello", 2), "...");
+        assert_eq!(middle_ellipsis("hello", 0), "...");
+    }
+}
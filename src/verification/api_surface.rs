@@ -0,0 +1,528 @@
+//! Public API diff for Rust library crates.
+//!
+//! Compares the public items under `src/` at the last release tag with the
+//! ones at `HEAD`. The diff is attached as evidence to entries that claim API
+//! changes, and added items no entry mentions are reported as possible
+//! omissions.
+//!
+//! The scan reads the source line by line rather than compiling it (rustdoc
+//! JSON needs a nightly toolchain), so it sees:
+//!
+//! - module-level `pub` items written at the start of a line,
+//! - `pub fn` methods of inherent `impl` blocks,
+//! - `#[macro_export]` macros,
+//!
+//! and skips modules declared without `pub` and everything after
+//! `#[cfg(test)]` in a file. Re-exports (`pub use`) and items generated by
+//! macros are not seen.
+
+use std::collections::{BTreeSet, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use git2::{Repository, Tree, TreeWalkMode, TreeWalkResult};
+use regex_lite::Regex;
+use tracing::warn;
+
+use crate::changelog::ChangelogEntry;
+use crate::git::tags::get_latest_reachable_tag;
+use crate::text::truncate_bytes;
+
+use super::collectors::ExternalEvidence;
+use super::evidence::VerificationEvidence;
+
+/// Collector name of the API diff in entry evidence.
+pub const API_SURFACE_COLLECTOR: &str = "api-surface";
+
+/// Maximum diff output attached to an entry.
+const MAX_OUTPUT_BYTES: usize = 8_000;
+
+static ITEM_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"^pub\s+(?:(?:async|const|unsafe)\s+|extern\s+"[^"]*"\s+)*(fn|struct|enum|trait|type|const|static|union|mod)\s+([A-Za-z_]\w*)"#,
+    )
+    .expect("Invalid public item regex - this is a bug")
+});
+
+static METHOD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r#"^    pub\s+(?:(?:async|const|unsafe)\s+|extern\s+"[^"]*"\s+)*fn\s+([A-Za-z_]\w*)"#,
+    )
+    .expect("Invalid public method regex - this is a bug")
+});
+
+static IMPL_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^impl(?:<[^>]*>)?\s+(?:\w+::)*([A-Za-z_]\w*)")
+        .expect("Invalid impl regex - this is a bug")
+});
+
+static PRIVATE_MOD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^(?:pub\([^)]*\)\s+)?mod\s+([A-Za-z_]\w*)\s*;")
+        .expect("Invalid private module regex - this is a bug")
+});
+
+static MACRO_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^macro_rules!\s+([A-Za-z_]\w*)").expect("Invalid macro regex - this is a bug")
+});
+
+/// A public item of the crate.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct PublicItem {
+    /// Path from the crate root, e.g. `crate::ship::run_ship`.
+    pub path: String,
+    /// Item kind: `fn`, `struct`, `method`, `macro`, ...
+    pub kind: String,
+}
+
+impl PublicItem {
+    /// Last path segment, e.g. `run_ship`.
+    pub fn name(&self) -> &str {
+        self.path.rsplit("::").next().unwrap_or(&self.path)
+    }
+}
+
+impl fmt::Display for PublicItem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {}", self.kind, self.path)
+    }
+}
+
+/// Public items added and removed since a release.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApiDiff {
+    /// Tag the diff starts from.
+    pub since: String,
+    pub added: Vec<PublicItem>,
+    pub removed: Vec<PublicItem>,
+}
+
+impl ApiDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty()
+    }
+
+    /// Render the diff as `+`/`-` lines.
+    pub fn render(&self) -> String {
+        if self.is_empty() {
+            return format!("No public API changes since {}", self.since);
+        }
+        let mut out = format!("Public API changes since {}:", self.since);
+        for item in &self.added {
+            out.push_str(&format!("\n+ {}", item));
+        }
+        for item in &self.removed {
+            out.push_str(&format!("\n- {}", item));
+        }
+        out
+    }
+
+    /// Whether `entry` claims an API change or names a changed item.
+    pub fn applies_to(&self, entry: &ChangelogEntry) -> bool {
+        let description = entry.description.to_lowercase();
+        description
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|w| w == "api")
+            || self
+                .added
+                .iter()
+                .chain(&self.removed)
+                .any(|item| mentions(&description, item))
+    }
+
+    /// Added items that none of `entries` mentions.
+    pub fn unmentioned<'a>(&'a self, entries: &[ChangelogEntry]) -> Vec<&'a PublicItem> {
+        let descriptions: Vec<String> = entries
+            .iter()
+            .map(|e| e.description.to_lowercase())
+            .collect();
+        self.added
+            .iter()
+            .filter(|item| !descriptions.iter().any(|d| mentions(d, item)))
+            .collect()
+    }
+}
+
+/// Whether a lowercased description names `item`, as written (`export_csv`)
+/// or as words (`export csv`).
+fn mentions(description: &str, item: &PublicItem) -> bool {
+    let name = item.name().to_lowercase();
+    description.contains(&name) || description.contains(&name.replace('_', " "))
+}
+
+/// Module path of a source file under `src/`, or `None` for files outside
+/// the library (binaries, build scripts).
+fn module_path(file: &str) -> Option<String> {
+    let relative = file.strip_prefix("src/")?.strip_suffix(".rs")?;
+    if relative == "lib" {
+        return Some("crate".to_string());
+    }
+    if relative == "main" || relative.starts_with("bin/") {
+        return None;
+    }
+    let relative = relative.strip_suffix("/mod").unwrap_or(relative);
+    Some(format!("crate::{}", relative.replace('/', "::")))
+}
+
+/// Public items declared in one file, plus the modules it declares without
+/// `pub`.
+fn scan_file(module: &str, source: &str) -> (Vec<PublicItem>, Vec<String>) {
+    let mut items = Vec::new();
+    let mut private_modules = Vec::new();
+    let mut current_impl: Option<String> = None;
+    let mut macro_export = false;
+
+    for line in source.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with("#[cfg(test)]") {
+            break;
+        }
+        if line == "}" {
+            current_impl = None;
+        }
+
+        if let Some(caps) = ITEM_REGEX.captures(line) {
+            items.push(PublicItem {
+                path: format!("{}::{}", module, &caps[2]),
+                kind: caps[1].to_string(),
+            });
+        } else if let Some(caps) = PRIVATE_MOD_REGEX.captures(line) {
+            private_modules.push(format!("{}::{}", module, &caps[1]));
+        } else if let Some(caps) = IMPL_REGEX.captures(line) {
+            // Trait methods are public through the trait, not the type
+            current_impl = (!line.contains(" for ")).then(|| caps[1].to_string());
+        } else if let Some(caps) = METHOD_REGEX.captures(line)
+            && let Some(type_name) = &current_impl
+        {
+            items.push(PublicItem {
+                path: format!("{}::{}::{}", module, type_name, &caps[1]),
+                kind: "method".to_string(),
+            });
+        } else if let Some(caps) = MACRO_REGEX.captures(line)
+            && macro_export
+        {
+            // Exported macros live at the crate root
+            items.push(PublicItem {
+                path: format!("crate::{}", &caps[1]),
+                kind: "macro".to_string(),
+            });
+        }
+
+        if !trimmed.is_empty() {
+            macro_export = trimmed == "#[macro_export]";
+        }
+    }
+
+    (items, private_modules)
+}
+
+/// Public items of the library in `sources` (path under the repository root
+/// to file content).
+pub fn public_items(sources: &HashMap<String, String>) -> BTreeSet<PublicItem> {
+    let mut items = Vec::new();
+    let mut private_modules = Vec::new();
+    for (file, source) in sources {
+        if let Some(module) = module_path(file) {
+            let (file_items, file_private) = scan_file(&module, source);
+            items.extend(file_items);
+            private_modules.extend(file_private);
+        }
+    }
+
+    items
+        .into_iter()
+        .filter(|item| {
+            !private_modules
+                .iter()
+                .any(|m| item.path.starts_with(&format!("{}::", m)))
+        })
+        .collect()
+}
+
+/// Rust sources under `src/` in `tree`.
+fn tree_sources(repo: &Repository, tree: &Tree) -> HashMap<String, String> {
+    let mut sources = HashMap::new();
+    let _ = tree.walk(TreeWalkMode::PreOrder, |root, entry| {
+        let path = format!("{}{}", root, entry.name().unwrap_or_default());
+        if !path.starts_with("src/") {
+            return if root.is_empty() && path != "src" {
+                TreeWalkResult::Skip
+            } else {
+                TreeWalkResult::Ok
+            };
+        }
+        if path.ends_with(".rs")
+            && let Ok(blob) = repo.find_blob(entry.id())
+            && let Ok(content) = std::str::from_utf8(blob.content())
+        {
+            sources.insert(path, content.to_string());
+        }
+        TreeWalkResult::Ok
+    });
+    sources
+}
+
+/// Diff the public API of the library at `HEAD` against the latest release
+/// tag reachable from it.
+///
+/// Returns `None` when there is nothing to compare: no git repository, no
+/// `src/lib.rs` at `HEAD`, or no release tag yet.
+pub fn diff_public_api(repo_path: &Path) -> Result<Option<ApiDiff>, String> {
+    let Ok(repo) = Repository::open(repo_path) else {
+        return Ok(None);
+    };
+    let Some(head_tree) = repo.head().ok().and_then(|h| h.peel_to_tree().ok()) else {
+        return Ok(None);
+    };
+    if head_tree.get_path(Path::new("src/lib.rs")).is_err() {
+        return Ok(None);
+    }
+    let Some(tag) = get_latest_reachable_tag(&repo).map_err(|e| e.to_string())? else {
+        return Ok(None);
+    };
+    let tag_tree = repo
+        .find_commit(tag.oid)
+        .and_then(|c| c.tree())
+        .map_err(|e| format!("cannot read {}: {}", tag.name, e))?;
+
+    let before = public_items(&tree_sources(&repo, &tag_tree));
+    let after = public_items(&tree_sources(&repo, &head_tree));
+    Ok(Some(ApiDiff {
+        since: tag.name,
+        added: after.difference(&before).cloned().collect(),
+        removed: before.difference(&after).cloned().collect(),
+    }))
+}
+
+/// Attach the public API diff to the evidence of entries claiming API
+/// changes, and record added items no entry mentions
+/// (`evidence.entries` must be in the same order as `entries`).
+pub fn attach_api_surface(
+    evidence: &mut VerificationEvidence,
+    entries: &[ChangelogEntry],
+    repo_path: &Path,
+) {
+    let diff = match diff_public_api(repo_path) {
+        Ok(Some(diff)) => diff,
+        Ok(None) => return,
+        Err(e) => {
+            let msg = format!("Public API diff failed: {}", e);
+            warn!("{}", msg);
+            evidence.add_warning(msg);
+            return;
+        }
+    };
+
+    let rendered = diff.render();
+    let attached = ExternalEvidence {
+        collector: API_SURFACE_COLLECTOR.to_string(),
+        output: truncate_bytes(&rendered, MAX_OUTPUT_BYTES).to_string(),
+        truncated: rendered.len() > MAX_OUTPUT_BYTES,
+    };
+    for (index, entry) in entries.iter().enumerate() {
+        if diff.applies_to(entry)
+            && let Some(entry_evidence) = evidence.entries.get_mut(index)
+        {
+            entry_evidence.external_evidence.push(attached.clone());
+        }
+    }
+
+    evidence.unmentioned_api = diff
+        .unmentioned(entries)
+        .into_iter()
+        .map(|item| item.to_string())
+        .collect();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogCategory;
+    use crate::verification::{EntryEvidence, ScanSummary};
+    use git2::{Oid, Signature};
+    use tempfile::TempDir;
+
+    fn item(kind: &str, path: &str) -> PublicItem {
+        PublicItem {
+            path: path.to_string(),
+            kind: kind.to_string(),
+        }
+    }
+
+    fn entry(description: &str) -> ChangelogEntry {
+        ChangelogEntry {
+            category: ChangelogCategory::Added,
+            description: description.to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    fn commit_file(repo: &Repository, path: &str, content: &str, message: &str) -> Oid {
+        let root = repo.workdir().unwrap();
+        std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+        std::fs::write(root.join(path), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn sources(files: &[(&str, &str)]) -> HashMap<String, String> {
+        files
+            .iter()
+            .map(|(path, content)| (path.to_string(), content.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_module_path() {
+        assert_eq!(module_path("src/lib.rs").as_deref(), Some("crate"));
+        assert_eq!(
+            module_path("src/ship/mod.rs").as_deref(),
+            Some("crate::ship")
+        );
+        assert_eq!(
+            module_path("src/ship/record.rs").as_deref(),
+            Some("crate::ship::record")
+        );
+        assert_eq!(module_path("src/main.rs"), None);
+        assert_eq!(module_path("src/bin/tool.rs"), None);
+        assert_eq!(module_path("build.rs"), None);
+    }
+
+    #[test]
+    fn test_public_items() {
+        let items = public_items(&sources(&[
+            (
+                "src/lib.rs",
+                "pub mod ship;\nmod internal;\npub(crate) mod helpers;\n\n#[macro_export]\nmacro_rules! bail {\n    () => {};\n}\n\npub const VERSION: &str = \"1\";\n",
+            ),
+            (
+                "src/ship/mod.rs",
+                "pub struct Ship;\n\nimpl Ship {\n    pub fn new() -> Self { Ship }\n    fn hidden(&self) {}\n}\n\nimpl Default for Ship {\n    fn default() -> Self { Ship }\n}\n\npub async fn run_ship() {}\npub(crate) fn internal() {}\n\n#[cfg(test)]\nmod tests {\n    pub fn helper() {}\n}\n",
+            ),
+            ("src/internal.rs", "pub fn not_exported() {}\n"),
+            ("src/main.rs", "pub fn main() {}\n"),
+        ]));
+
+        let expected: BTreeSet<PublicItem> = [
+            item("mod", "crate::ship"),
+            item("macro", "crate::bail"),
+            item("const", "crate::VERSION"),
+            item("struct", "crate::ship::Ship"),
+            item("method", "crate::ship::Ship::new"),
+            item("fn", "crate::ship::run_ship"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(items, expected);
+    }
+
+    #[test]
+    fn test_applies_to_and_unmentioned() {
+        let diff = ApiDiff {
+            since: "v1.0.0".to_string(),
+            added: vec![
+                item("fn", "crate::export::export_csv"),
+                item("struct", "crate::ReleaseRecord"),
+            ],
+            removed: vec![item("fn", "crate::legacy_export")],
+        };
+        let entries = vec![
+            entry("Add CSV export via export_csv"),
+            entry("New plugin API"),
+            entry("Fix crash on startup"),
+        ];
+
+        assert!(diff.applies_to(&entries[0]));
+        assert!(diff.applies_to(&entries[1]));
+        assert!(!diff.applies_to(&entries[2]));
+        assert!(!diff.applies_to(&entry("Faster rapid builds")));
+
+        let unmentioned = diff.unmentioned(&entries);
+        assert_eq!(unmentioned, vec![&item("struct", "crate::ReleaseRecord")]);
+    }
+
+    #[test]
+    fn test_render() {
+        let diff = ApiDiff {
+            since: "v1.0.0".to_string(),
+            added: vec![item("fn", "crate::export_csv")],
+            removed: vec![item("struct", "crate::Legacy")],
+        };
+        assert_eq!(
+            diff.render(),
+            "Public API changes since v1.0.0:\n+ fn crate::export_csv\n- struct crate::Legacy"
+        );
+        let empty = ApiDiff {
+            since: "v1.0.0".to_string(),
+            added: Vec::new(),
+            removed: Vec::new(),
+        };
+        assert_eq!(empty.render(), "No public API changes since v1.0.0");
+    }
+
+    #[test]
+    fn test_diff_against_latest_tag() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let released = commit_file(
+            &repo,
+            "src/lib.rs",
+            "pub fn legacy_export() {}\npub fn parse() {}\n",
+            "feat: init",
+        );
+        repo.tag_lightweight("v1.0.0", &repo.find_object(released, None).unwrap(), false)
+            .unwrap();
+        commit_file(
+            &repo,
+            "src/lib.rs",
+            "pub fn export_csv() {}\npub fn parse() {}\npub struct Report;\n",
+            "feat: csv export",
+        );
+
+        let diff = diff_public_api(dir.path()).unwrap().unwrap();
+        assert_eq!(diff.since, "v1.0.0");
+        assert_eq!(
+            diff.added,
+            vec![
+                item("struct", "crate::Report"),
+                item("fn", "crate::export_csv")
+            ]
+        );
+        assert_eq!(diff.removed, vec![item("fn", "crate::legacy_export")]);
+
+        let entries = vec![entry("Add CSV export (export_csv)"), entry("Fix typo")];
+        let mut evidence = VerificationEvidence::empty();
+        for entry in &entries {
+            evidence.entries.push(EntryEvidence::new(
+                entry.description.clone(),
+                entry.category.clone(),
+                Vec::new(),
+                Vec::new(),
+                Vec::new(),
+                ScanSummary::new(),
+            ));
+        }
+        attach_api_surface(&mut evidence, &entries, dir.path());
+        assert_eq!(
+            evidence.entries[0].external_evidence[0].collector,
+            API_SURFACE_COLLECTOR
+        );
+        assert!(evidence.entries[1].external_evidence.is_empty());
+        assert_eq!(evidence.unmentioned_api, vec!["struct crate::Report"]);
+    }
+
+    #[test]
+    fn test_no_diff_without_repository() {
+        let dir = TempDir::new().unwrap();
+        assert_eq!(diff_public_api(dir.path()), Ok(None));
+    }
+}
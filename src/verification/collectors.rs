@@ -0,0 +1,406 @@
+//! External evidence collectors configured in `keryx.toml`.
+//!
+//! Teams often have checkers keryx cannot know about: an API diff, a schema
+//! report, a migration lint. Each `[[verification.collectors]]` entry names a
+//! shell command that runs once in the repository root; its stdout is attached
+//! to the evidence of every entry it applies to:
+//!
+//! ```toml
+//! [[verification.collectors]]
+//! name = "public-api"
+//! command = "cargo public-api diff latest"
+//! # Only entries mentioning one of these terms (case-insensitive); default: all
+//! match = ["api", "pub fn", "removed"]
+//! # Only entries in these categories; default: all
+//! categories = ["Added", "Changed", "Removed"]
+//! # Seconds before the command is killed; default: 120
+//! timeout = 300
+//! ```
+//!
+//! A collector that fails, times out or applies to no entry adds no evidence;
+//! failures are recorded as evidence warnings.
+
+use std::io::Read;
+use std::path::Path;
+use std::process::{Command, Stdio};
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+use tracing::warn;
+
+use crate::changelog::product::CONFIG_FILE;
+use crate::changelog::{ChangelogCategory, ChangelogEntry};
+use crate::text::truncate_bytes;
+
+use super::evidence::VerificationEvidence;
+
+/// Default time a collector may run.
+const DEFAULT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Maximum collector output attached to an entry.
+const MAX_OUTPUT_BYTES: usize = 8_000;
+
+/// How often a running collector is checked for completion.
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// An external command whose output is verification evidence.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExternalCollector {
+    pub name: String,
+    /// Shell command, run in the repository root.
+    pub command: String,
+    /// Lowercased terms; the collector applies to entries mentioning any of
+    /// them (empty = every entry).
+    pub matches: Vec<String>,
+    /// Categories the collector applies to (empty = every category).
+    pub categories: Vec<ChangelogCategory>,
+    pub timeout: Duration,
+}
+
+/// Output of a collector attached to an entry.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExternalEvidence {
+    /// Collector name.
+    pub collector: String,
+    /// Standard output of the command (may be truncated).
+    pub output: String,
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub truncated: bool,
+}
+
+impl ExternalCollector {
+    /// Read the collectors from `keryx.toml` in `root`.
+    ///
+    /// A missing file or table gives no collectors; an unreadable or invalid
+    /// file is logged and ignored.
+    pub fn load(root: &Path) -> Vec<Self> {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Vec::new(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Vec::new();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid verification collectors in {}: {}. Ignoring them.",
+                path.display(),
+                e
+            );
+            Vec::new()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Vec<Self>, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let Some(collectors) = doc
+            .get("verification")
+            .and_then(|v| v.as_table_like())
+            .and_then(|v| v.get("collectors"))
+        else {
+            return Ok(Vec::new());
+        };
+        let collectors = collectors
+            .as_array_of_tables()
+            .ok_or("verification.collectors must be an array of tables")?;
+
+        collectors
+            .iter()
+            .enumerate()
+            .map(|(index, table)| {
+                let string = |key: &str| -> Result<Option<String>, String> {
+                    match table.get(key) {
+                        None => Ok(None),
+                        Some(item) => item
+                            .as_str()
+                            .map(|s| Some(s.trim().to_string()))
+                            .ok_or_else(|| {
+                                format!("collector {}: {} must be a string", index, key)
+                            }),
+                    }
+                };
+                let strings = |key: &str| -> Result<Vec<String>, String> {
+                    match table.get(key) {
+                        None => Ok(Vec::new()),
+                        Some(item) => item
+                            .as_array()
+                            .and_then(|a| {
+                                a.iter()
+                                    .map(|v| v.as_str().map(|s| s.trim().to_string()))
+                                    .collect::<Option<Vec<_>>>()
+                            })
+                            .ok_or_else(|| {
+                                format!("collector {}: {} must be a list of strings", index, key)
+                            }),
+                    }
+                };
+
+                let command = string("command")?
+                    .filter(|c| !c.is_empty())
+                    .ok_or_else(|| format!("collector {}: command is required", index))?;
+                let name = string("name")?
+                    .filter(|n| !n.is_empty())
+                    .unwrap_or_else(|| command.clone());
+                let categories = strings("categories")?
+                    .iter()
+                    .map(|c| c.parse::<ChangelogCategory>())
+                    .collect::<Result<Vec<_>, _>>()
+                    .map_err(|e| format!("collector {}: {}", name, e))?;
+                let timeout = match table.get("timeout") {
+                    None => DEFAULT_TIMEOUT,
+                    Some(item) => item
+                        .as_integer()
+                        .filter(|secs| *secs > 0)
+                        .map(|secs| Duration::from_secs(secs as u64))
+                        .ok_or_else(|| {
+                            format!("collector {}: timeout must be a positive integer", name)
+                        })?,
+                };
+
+                Ok(Self {
+                    matches: strings("match")?
+                        .into_iter()
+                        .filter(|m| !m.is_empty())
+                        .map(|m| m.to_lowercase())
+                        .collect(),
+                    name,
+                    command,
+                    categories,
+                    timeout,
+                })
+            })
+            .collect()
+    }
+
+    /// Whether the collector's output is evidence for `entry`.
+    pub fn applies_to(&self, entry: &ChangelogEntry) -> bool {
+        let description = entry.description.to_lowercase();
+        (self.categories.is_empty() || self.categories.contains(&entry.category))
+            && (self.matches.is_empty() || self.matches.iter().any(|m| description.contains(m)))
+    }
+
+    /// Run the command in `root` and return its standard output.
+    pub fn run(&self, root: &Path) -> Result<String, String> {
+        let mut cmd = if cfg!(windows) {
+            let mut cmd = Command::new("cmd");
+            cmd.arg("/C");
+            cmd
+        } else {
+            let mut cmd = Command::new("sh");
+            cmd.arg("-c");
+            cmd
+        };
+        let mut child = cmd
+            .arg(&self.command)
+            .current_dir(root)
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| format!("failed to start: {}", e))?;
+
+        // Read stdout on a thread so a chatty command cannot block on a full pipe
+        let mut stdout = child.stdout.take().ok_or("stdout not captured")?;
+        let reader = std::thread::spawn(move || {
+            let mut buf = Vec::new();
+            stdout.read_to_end(&mut buf).map(|_| buf)
+        });
+
+        let started = Instant::now();
+        let status = loop {
+            match child.try_wait() {
+                Ok(Some(status)) => break status,
+                Ok(None) if started.elapsed() >= self.timeout => {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    return Err(format!("timed out after {:?}", self.timeout));
+                }
+                Ok(None) => std::thread::sleep(POLL_INTERVAL),
+                Err(e) => return Err(format!("failed to wait: {}", e)),
+            }
+        };
+
+        let output = reader
+            .join()
+            .map_err(|_| "failed to read output".to_string())?
+            .map_err(|e| format!("failed to read output: {}", e))?;
+        if !status.success() {
+            return Err(match status.code() {
+                Some(code) => format!("exited with status {}", code),
+                None => "terminated by a signal".to_string(),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output).to_string())
+    }
+}
+
+/// Run every collector that applies to at least one of `entries` and attach
+/// its output to those entries' evidence (`evidence.entries` must be in the
+/// same order as `entries`).
+pub fn attach_external_evidence(
+    evidence: &mut VerificationEvidence,
+    entries: &[ChangelogEntry],
+    collectors: &[ExternalCollector],
+    root: &Path,
+) {
+    for collector in collectors {
+        let targets: Vec<usize> = entries
+            .iter()
+            .enumerate()
+            .filter(|(_, entry)| collector.applies_to(entry))
+            .map(|(index, _)| index)
+            .collect();
+        if targets.is_empty() {
+            continue;
+        }
+
+        let output = match collector.run(root) {
+            Ok(output) => output,
+            Err(e) => {
+                let msg = format!("Evidence collector '{}' {}", collector.name, e);
+                warn!("{}", msg);
+                evidence.add_warning(msg);
+                continue;
+            }
+        };
+        let trimmed = output.trim();
+        let attached = ExternalEvidence {
+            collector: collector.name.clone(),
+            output: truncate_bytes(trimmed, MAX_OUTPUT_BYTES).to_string(),
+            truncated: trimmed.len() > MAX_OUTPUT_BYTES,
+        };
+        for index in targets {
+            if let Some(entry) = evidence.entries.get_mut(index) {
+                entry.external_evidence.push(attached.clone());
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::verification::gather_verification_evidence;
+    use tempfile::TempDir;
+
+    fn entry(category: ChangelogCategory, description: &str) -> ChangelogEntry {
+        ChangelogEntry {
+            category,
+            description: description.to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_parse_collectors() {
+        let collectors = ExternalCollector::parse(
+            r#"
+[product]
+description = "ignored here"
+
+[[verification.collectors]]
+name = "public-api"
+command = "cargo public-api diff"
+match = ["API", "pub fn"]
+categories = ["Added", "Removed"]
+timeout = 30
+
+[[verification.collectors]]
+command = "npm run api-report"
+"#,
+        )
+        .unwrap();
+
+        assert_eq!(collectors.len(), 2);
+        assert_eq!(collectors[0].name, "public-api");
+        assert_eq!(collectors[0].matches, vec!["api", "pub fn"]);
+        assert_eq!(
+            collectors[0].categories,
+            vec![ChangelogCategory::Added, ChangelogCategory::Removed]
+        );
+        assert_eq!(collectors[0].timeout, Duration::from_secs(30));
+        assert_eq!(collectors[1].name, "npm run api-report");
+        assert_eq!(collectors[1].timeout, DEFAULT_TIMEOUT);
+
+        assert!(ExternalCollector::parse("[[verification.collectors]]\nname = \"x\"\n").is_err());
+        assert!(ExternalCollector::parse("[product]\n").unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_applies_to() {
+        let collector = ExternalCollector {
+            name: "api".to_string(),
+            command: "true".to_string(),
+            matches: vec!["api".to_string()],
+            categories: vec![ChangelogCategory::Added],
+            timeout: DEFAULT_TIMEOUT,
+        };
+        assert!(collector.applies_to(&entry(ChangelogCategory::Added, "New API for exports")));
+        assert!(!collector.applies_to(&entry(ChangelogCategory::Fixed, "API crash")));
+        assert!(!collector.applies_to(&entry(ChangelogCategory::Added, "Dark mode")));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collector_output_attached_to_matching_entries() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join(CONFIG_FILE),
+            r#"
+[[verification.collectors]]
+name = "api-diff"
+command = "echo '+ pub fn export_csv'"
+match = ["export"]
+
+[[verification.collectors]]
+name = "broken"
+command = "exit 3"
+"#,
+        )
+        .unwrap();
+        let entries = vec![
+            entry(ChangelogCategory::Added, "Add CSV export"),
+            entry(ChangelogCategory::Fixed, "Fix startup crash"),
+        ];
+
+        let evidence = gather_verification_evidence(&entries, dir.path());
+        assert_eq!(
+            evidence.entries[0].external_evidence,
+            vec![ExternalEvidence {
+                collector: "api-diff".to_string(),
+                output: "+ pub fn export_csv".to_string(),
+                truncated: false,
+            }]
+        );
+        assert!(evidence.entries[1].external_evidence.is_empty());
+        assert!(
+            evidence
+                .warnings
+                .iter()
+                .any(|w| w == "Evidence collector 'broken' exited with status 3")
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_collector_times_out() {
+        let dir = TempDir::new().unwrap();
+        let collector = ExternalCollector {
+            name: "slow".to_string(),
+            command: "sleep 5".to_string(),
+            matches: Vec::new(),
+            categories: Vec::new(),
+            timeout: Duration::from_millis(100),
+        };
+        assert_eq!(
+            collector.run(dir.path()),
+            Err("timed out after 100ms".to_string())
+        );
+    }
+}
@@ -4,6 +4,8 @@ use serde::{Deserialize, Serialize};
 
 use crate::changelog::ChangelogCategory;
 
+use super::collectors::ExternalEvidence;
+
 /// Complete verification evidence for all changelog entries.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VerificationEvidence {
@@ -22,6 +24,15 @@ pub struct VerificationEvidence {
     /// check `warnings.is_empty()` to determine if evidence is complete.
     #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub warnings: Vec<String>,
+    /// Public API items added since the last release that no entry mentions
+    /// (Rust library crates only). Reported to the user as possible
+    /// omissions, not sent to the verifier.
+    #[serde(skip)]
+    pub unmentioned_api: Vec<String>,
+    /// Paths keyword searches were limited to; empty when the whole
+    /// repository was searched.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub search_scope: Vec<String>,
 }
 
 /// Evidence for a single changelog entry.
@@ -44,6 +55,9 @@ pub struct EntryEvidence {
     /// Summary of search operations performed for this entry.
     #[serde(default)]
     pub scan_summary: ScanSummary,
+    /// Output of configured external collectors that apply to this entry.
+    #[serde(default)]
+    pub external_evidence: Vec<ExternalEvidence>,
 }
 
 impl EntryEvidence {
@@ -63,6 +77,7 @@ impl EntryEvidence {
             count_checks,
             stub_indicators,
             scan_summary,
+            external_evidence: Vec::new(),
         }
     }
 
@@ -131,13 +146,18 @@ impl Serialize for EntryEvidence {
     {
         use serde::ser::SerializeStruct;
 
-        let mut state = serializer.serialize_struct("EntryEvidence", 7)?;
+        let mut state = serializer.serialize_struct("EntryEvidence", 8)?;
         state.serialize_field("original_description", &self.original_description)?;
         state.serialize_field("category", &self.category)?;
         state.serialize_field("keyword_matches", &self.keyword_matches)?;
         state.serialize_field("count_checks", &self.count_checks)?;
         state.serialize_field("stub_indicators", &self.stub_indicators)?;
         state.serialize_field("scan_summary", &self.scan_summary)?;
+        if self.external_evidence.is_empty() {
+            state.skip_field("external_evidence")?;
+        } else {
+            state.serialize_field("external_evidence", &self.external_evidence)?;
+        }
         state.serialize_field("confidence", &self.confidence())?;
         state.end()
     }
@@ -384,6 +404,8 @@ impl VerificationEvidence {
             project_structure_source: None,
             key_files: Vec::new(),
             warnings: Vec::new(),
+            unmentioned_api: Vec::new(),
+            search_scope: Vec::new(),
         }
     }
 
@@ -397,6 +419,31 @@ impl VerificationEvidence {
         !self.warnings.is_empty()
     }
 
+    /// What made the evidence incomplete, in one line: gathering warnings
+    /// and failed keyword searches. `None` when nothing was degraded.
+    pub fn degradation(&self) -> Option<String> {
+        let mut parts = Vec::new();
+        if let Some(first) = self.warnings.first() {
+            parts.push(match self.warnings.len() {
+                1 => first.clone(),
+                n => format!("{} (and {} more warnings)", first, n - 1),
+            });
+        }
+        let failed: usize = self
+            .entries
+            .iter()
+            .map(|e| e.scan_summary.failed_searches)
+            .sum();
+        if failed > 0 {
+            parts.push(format!(
+                "{} keyword {} failed",
+                failed,
+                if failed == 1 { "search" } else { "searches" }
+            ));
+        }
+        (!parts.is_empty()).then(|| parts.join("; "))
+    }
+
     /// Check if any entries have low confidence.
     pub fn has_low_confidence_entries(&self) -> bool {
         self.entries
@@ -496,6 +543,24 @@ mod tests {
         assert_eq!(evidence.warnings.len(), 2);
     }
 
+    #[test]
+    fn test_verification_evidence_degradation() {
+        let mut evidence = VerificationEvidence::empty();
+        assert_eq!(evidence.degradation(), None);
+
+        evidence.add_warning("tree unavailable");
+        assert_eq!(evidence.degradation().as_deref(), Some("tree unavailable"));
+
+        evidence.add_warning("Cargo.toml unreadable");
+        let mut entry = create_low_confidence_entry("Add export");
+        entry.scan_summary.failed_searches = 3;
+        evidence.entries.push(entry);
+        assert_eq!(
+            evidence.degradation().as_deref(),
+            Some("tree unavailable (and 1 more warnings); 3 keyword searches failed")
+        );
+    }
+
     #[test]
     fn test_verification_evidence_warnings_serialization() {
         let mut evidence = VerificationEvidence::empty();
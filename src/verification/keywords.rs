@@ -0,0 +1,529 @@
+//! Keyword extraction for verification.
+//!
+//! Each entry is searched for by the keywords of its description. The
+//! default extractor picks identifiers (CamelCase, snake_case), quoted
+//! terms, product names and significant English words, plus CLI flags
+//! (`--dry-run`, searched as `dry-run` and `dry_run`, the forms flag
+//! definitions use) and environment variables (`KERYX_VERIFY_SCOPE`).
+//! Projects writing changelogs in another language, or with identifiers
+//! of their own (ticket keys, error codes), configure the extractor in the
+//! `[verification.keywords]` table of `keryx.toml`:
+//!
+//! ```toml
+//! [verification.keywords]
+//! locale = "de"                  # add German stop words to the English ones
+//! stop_words = ["widget"]        # never search for these
+//! patterns = ['E\d{4}']         # extra keywords: the match, or its first group
+//! stemming = true                # search "stream" for "streaming"
+//! ```
+
+use std::collections::{BTreeSet, HashSet};
+use std::path::Path;
+use std::sync::LazyLock;
+
+use regex_lite::Regex;
+use tracing::warn;
+
+use crate::changelog::product::CONFIG_FILE;
+
+/// Identifiers and significant words: CamelCase, snake_case, 4+ letters.
+static WORD_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"[A-Z][a-z]+(?:[A-Z][a-z]+)*|[a-z]+(?:_[a-z]+)+|[A-Za-z]{4,}")
+        .expect("Invalid word regex - this is a bug")
+});
+
+/// Quoted or backticked terms.
+static QUOTE_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"["'`]([^"'`]+)["'`]"#).expect("Invalid quote regex - this is a bug")
+});
+
+/// Technology and product names (capitalized words, optionally two).
+static TECH_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b([A-Z][a-zA-Z0-9]+(?:\s+[A-Z][a-zA-Z0-9]+)?)\b")
+        .expect("Invalid tech regex - this is a bug")
+});
+
+/// Long CLI flags: `--dry-run`, `--no-verify`.
+static FLAG_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"(?:^|[^A-Za-z0-9-])--([a-zA-Z][a-zA-Z0-9]*(?:-[a-zA-Z0-9]+)*)")
+        .expect("Invalid flag regex - this is a bug")
+});
+
+/// Environment variable names: `KERYX_VERIFY_SCOPE`, `GITHUB_TOKEN`.
+static ENV_VAR_RE: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"\b([A-Z][A-Z0-9]*(?:_[A-Z0-9]+)+)\b")
+        .expect("Invalid env var regex - this is a bug")
+});
+
+/// Capitalized category words that are not product names.
+const GENERIC_CAPITALIZED: &[&str] = &[
+    "Added", "Changed", "Fixed", "Removed", "Security", "The", "This", "With",
+];
+
+/// English words that say nothing about the code, and changelog verbs.
+const ENGLISH_STOP_WORDS: &[&str] = &[
+    "the",
+    "a",
+    "an",
+    "and",
+    "or",
+    "but",
+    "in",
+    "on",
+    "at",
+    "to",
+    "for",
+    "of",
+    "with",
+    "by",
+    "from",
+    "as",
+    "is",
+    "was",
+    "are",
+    "were",
+    "been",
+    "be",
+    "have",
+    "has",
+    "had",
+    "do",
+    "does",
+    "did",
+    "will",
+    "would",
+    "could",
+    "should",
+    "may",
+    "might",
+    "must",
+    "shall",
+    "can",
+    "need",
+    "new",
+    "add",
+    "added",
+    "change",
+    "changed",
+    "fix",
+    "fixed",
+    "update",
+    "updated",
+    "remove",
+    "removed",
+    "improve",
+    "improved",
+    "support",
+    "supported",
+    "feature",
+    "features",
+    "now",
+    "using",
+    "use",
+    "based",
+    "all",
+    "any",
+    "some",
+    "more",
+    "less",
+    "better",
+    "best",
+    "first",
+    "initial",
+    "release",
+    "version",
+    "multiple",
+    "various",
+    "several",
+];
+
+/// German stop words and changelog verbs.
+const GERMAN_STOP_WORDS: &[&str] = &[
+    "der",
+    "die",
+    "das",
+    "und",
+    "oder",
+    "aber",
+    "mit",
+    "für",
+    "von",
+    "bei",
+    "nach",
+    "auch",
+    "eine",
+    "einen",
+    "einem",
+    "einer",
+    "wird",
+    "werden",
+    "wurde",
+    "wurden",
+    "kann",
+    "können",
+    "jetzt",
+    "neue",
+    "neuen",
+    "neuer",
+    "hinzugefügt",
+    "geändert",
+    "behoben",
+    "entfernt",
+    "verbessert",
+    "unterstützung",
+    "beim",
+    "beim",
+    "nicht",
+    "sich",
+    "über",
+];
+
+/// French stop words and changelog verbs.
+const FRENCH_STOP_WORDS: &[&str] = &[
+    "le",
+    "la",
+    "les",
+    "des",
+    "une",
+    "et",
+    "ou",
+    "mais",
+    "avec",
+    "pour",
+    "dans",
+    "par",
+    "sur",
+    "est",
+    "sont",
+    "peut",
+    "maintenant",
+    "nouveau",
+    "nouvelle",
+    "nouveaux",
+    "ajout",
+    "ajouté",
+    "ajoutée",
+    "modifié",
+    "corrigé",
+    "correction",
+    "supprimé",
+    "amélioré",
+    "prise",
+    "charge",
+    "lors",
+    "plus",
+];
+
+/// Spanish stop words and changelog verbs.
+const SPANISH_STOP_WORDS: &[&str] = &[
+    "el",
+    "la",
+    "los",
+    "las",
+    "una",
+    "y",
+    "o",
+    "pero",
+    "con",
+    "para",
+    "por",
+    "del",
+    "en",
+    "es",
+    "son",
+    "puede",
+    "ahora",
+    "nuevo",
+    "nueva",
+    "nuevos",
+    "añadido",
+    "agregado",
+    "cambiado",
+    "corregido",
+    "eliminado",
+    "mejorado",
+    "soporte",
+    "cuando",
+    "más",
+];
+
+/// Stop words for a locale (`en`, `de`, `fr`, `es`; region suffixes such
+/// as `de-AT` are ignored). `None` for an unknown locale.
+fn locale_stop_words(locale: &str) -> Option<&'static [&'static str]> {
+    let language = locale
+        .split(['-', '_'])
+        .next()
+        .unwrap_or(locale)
+        .to_lowercase();
+    match language.as_str() {
+        "en" => Some(ENGLISH_STOP_WORDS),
+        "de" => Some(GERMAN_STOP_WORDS),
+        "fr" => Some(FRENCH_STOP_WORDS),
+        "es" => Some(SPANISH_STOP_WORDS),
+        _ => None,
+    }
+}
+
+/// Suffixes removed by the light stemmer, longest first.
+const STEM_SUFFIXES: &[&str] = &["ing", "ies", "ed", "es", "s"];
+
+/// Strip one inflection suffix, keeping at least four characters. Searches
+/// are substring matches, so the stem finds every inflected form.
+fn stem(word: &str) -> String {
+    for suffix in STEM_SUFFIXES {
+        if let Some(stem) = word.strip_suffix(suffix)
+            && stem.chars().count() >= 4
+            && !(*suffix == "s" && stem.ends_with('s'))
+        {
+            return stem.to_string();
+        }
+    }
+    word.to_string()
+}
+
+/// How keywords are picked from entry descriptions.
+#[derive(Debug, Clone)]
+pub struct KeywordExtractor {
+    stop_words: HashSet<String>,
+    /// Extra patterns; each match (or its first group) is a keyword.
+    patterns: Vec<Regex>,
+    /// Reduce words to their stem before searching.
+    stemming: bool,
+}
+
+impl Default for KeywordExtractor {
+    fn default() -> Self {
+        Self {
+            stop_words: ENGLISH_STOP_WORDS.iter().map(|w| w.to_string()).collect(),
+            patterns: Vec::new(),
+            stemming: false,
+        }
+    }
+}
+
+impl KeywordExtractor {
+    /// Read `[verification.keywords]` from `keryx.toml` in `root`.
+    ///
+    /// A missing file or table gives the default extractor; an unreadable
+    /// or invalid file is logged and ignored.
+    pub fn load(root: &Path) -> Self {
+        let path = root.join(CONFIG_FILE);
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Self::default(),
+            Err(e) => {
+                warn!("Failed to read {}: {}. Ignoring it.", path.display(), e);
+                return Self::default();
+            }
+        };
+        Self::parse(&content).unwrap_or_else(|e| {
+            warn!(
+                "Invalid verification keywords in {}: {}. Using the defaults.",
+                path.display(),
+                e
+            );
+            Self::default()
+        })
+    }
+
+    fn parse(content: &str) -> Result<Self, String> {
+        let doc = content
+            .parse::<toml_edit::DocumentMut>()
+            .map_err(|e| e.to_string())?;
+        let mut extractor = Self::default();
+        let Some(table) = doc
+            .get("verification")
+            .and_then(|v| v.as_table_like())
+            .and_then(|v| v.get("keywords"))
+        else {
+            return Ok(extractor);
+        };
+        let table = table
+            .as_table_like()
+            .ok_or("verification.keywords must be a table")?;
+
+        let strings = |key: &str| -> Result<Vec<String>, String> {
+            match table.get(key) {
+                None => Ok(Vec::new()),
+                Some(item) => item
+                    .as_array()
+                    .and_then(|a| {
+                        a.iter()
+                            .map(|v| v.as_str().map(str::to_string))
+                            .collect::<Option<Vec<_>>>()
+                    })
+                    .ok_or_else(|| format!("{} must be a list of strings", key)),
+            }
+        };
+
+        if let Some(locale) = table.get("locale") {
+            let locale = locale.as_str().ok_or("locale must be a string")?;
+            let words = locale_stop_words(locale).ok_or_else(|| {
+                format!("unknown locale '{}' (expected en, de, fr or es)", locale)
+            })?;
+            extractor
+                .stop_words
+                .extend(words.iter().map(|w| w.to_string()));
+        }
+        extractor.stop_words.extend(
+            strings("stop_words")?
+                .into_iter()
+                .map(|w| w.trim().to_lowercase()),
+        );
+        extractor.patterns = strings("patterns")?
+            .iter()
+            .map(|p| Regex::new(p).map_err(|e| format!("invalid pattern '{}': {}", p, e)))
+            .collect::<Result<_, _>>()?;
+        if let Some(stemming) = table.get("stemming") {
+            extractor.stemming = stemming.as_bool().ok_or("stemming must be a boolean")?;
+        }
+        Ok(extractor)
+    }
+
+    fn is_stop_word(&self, word: &str) -> bool {
+        self.stop_words.contains(&word.to_lowercase())
+    }
+
+    /// Lowercased words of `description`. Words with non-ASCII letters
+    /// (`hinzugefügt`, `añadido`) are kept whole instead of being cut at
+    /// the first accent.
+    fn words(description: &str) -> impl Iterator<Item = String> + '_ {
+        description
+            .split(|c: char| !c.is_alphanumeric() && c != '_')
+            .flat_map(|token| {
+                if !token.is_ascii() && token.chars().all(char::is_alphabetic) {
+                    vec![token.to_lowercase()]
+                } else {
+                    WORD_RE
+                        .find_iter(token)
+                        .map(|m| m.as_str().to_lowercase())
+                        .collect()
+                }
+            })
+    }
+
+    /// Extract the keywords of `description`, sorted and deduplicated.
+    pub fn extract(&self, description: &str) -> Vec<String> {
+        let mut keywords = BTreeSet::new();
+
+        for word in Self::words(description) {
+            let length = word.chars().count();
+            if self.is_stop_word(&word) || !(4..=30).contains(&length) {
+                continue;
+            }
+            keywords.insert(if self.stemming && !word.contains('_') {
+                stem(&word)
+            } else {
+                word
+            });
+        }
+
+        // Quoted terms; flags are handled below
+        for cap in QUOTE_RE.captures_iter(description) {
+            let term = &cap[1];
+            if term.starts_with('-') {
+                continue;
+            }
+            let term = term.to_lowercase();
+            if term.len() >= 3 && term.len() <= 50 && !self.is_stop_word(&term) {
+                keywords.insert(term);
+            }
+        }
+
+        for cap in TECH_RE.captures_iter(description) {
+            let term = &cap[1];
+            if !GENERIC_CAPITALIZED.contains(&term) && !self.is_stop_word(term) {
+                keywords.insert(term.to_lowercase());
+            }
+        }
+
+        // Flags as written in definitions: `long = "dry-run"` or `dry_run: bool`
+        for cap in FLAG_RE.captures_iter(description) {
+            let name = cap[1].to_lowercase();
+            if name.len() < 2 {
+                continue;
+            }
+            if name.contains('-') {
+                keywords.insert(name.replace('-', "_"));
+            }
+            keywords.insert(name);
+        }
+
+        for cap in ENV_VAR_RE.captures_iter(description) {
+            keywords.insert(cap[1].to_string());
+        }
+
+        for pattern in &self.patterns {
+            for cap in pattern.captures_iter(description) {
+                if let Some(m) = cap.get(1).or_else(|| cap.get(0))
+                    && !m.as_str().trim().is_empty()
+                {
+                    keywords.insert(m.as_str().trim().to_string());
+                }
+            }
+        }
+
+        keywords.into_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_flags_and_env_vars_are_keywords() {
+        let keywords = KeywordExtractor::default()
+            .extract("Added `--dry-run` to ship and KERYX_PUSH_RETRIES for flaky remotes");
+        assert!(keywords.contains(&"dry-run".to_string()));
+        assert!(keywords.contains(&"dry_run".to_string()));
+        assert!(keywords.contains(&"KERYX_PUSH_RETRIES".to_string()));
+        assert!(!keywords.iter().any(|k| k.starts_with('-')));
+    }
+
+    #[test]
+    fn test_locale_and_custom_stop_words() {
+        let extractor = KeywordExtractor::parse(
+            "[verification.keywords]\nlocale = \"de\"\nstop_words = [\"Dashboard\"]\n",
+        )
+        .unwrap();
+        let keywords = extractor.extract("Neue Exportfunktion für das Dashboard hinzugefügt");
+        assert!(keywords.contains(&"exportfunktion".to_string()));
+        assert!(!keywords.iter().any(|k| k.starts_with("hinzugef")));
+        assert!(!keywords.contains(&"dashboard".to_string()));
+        // English stop words still apply
+        assert!(
+            !extractor
+                .extract("Added support")
+                .contains(&"support".to_string())
+        );
+    }
+
+    #[test]
+    fn test_custom_patterns_and_stemming() {
+        let extractor = KeywordExtractor::parse(
+            "[verification.keywords]\npatterns = ['error (E\\d{4})']\nstemming = true\n",
+        )
+        .unwrap();
+        let keywords = extractor.extract("Fixed error E0042 when streaming exports");
+        assert!(keywords.contains(&"E0042".to_string()));
+        assert!(keywords.contains(&"stream".to_string()));
+        assert!(keywords.contains(&"export".to_string()));
+        assert!(!keywords.contains(&"streaming".to_string()));
+    }
+
+    #[test]
+    fn test_invalid_config_is_rejected() {
+        assert!(KeywordExtractor::parse("[verification.keywords]\nlocale = \"xx\"\n").is_err());
+        assert!(KeywordExtractor::parse("[verification.keywords]\npatterns = ['(']\n").is_err());
+        assert!(KeywordExtractor::parse("[product]\ndescription = \"x\"\n").is_ok());
+    }
+
+    #[test]
+    fn test_stem_keeps_short_words() {
+        assert_eq!(stem("streaming"), "stream");
+        assert_eq!(stem("tags"), "tags");
+        assert_eq!(stem("progress"), "progress");
+        assert_eq!(stem("retries"), "retr");
+    }
+}
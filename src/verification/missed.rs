@@ -0,0 +1,432 @@
+//! Missed-change detection: the reverse of verification.
+//!
+//! Verification checks that what the changelog says happened; this pass
+//! looks for significant changes in the release's commits that no entry
+//! covers:
+//!
+//! - new command-line flags (clap `#[arg(long ...)]` fields),
+//! - new files under `src/`,
+//! - files with large diffs.
+//!
+//! A change counts as covered when an entry cites one of its commits in
+//! `sources`, or mentions the flag or the file's name. Uncovered changes are
+//! suggestions for the user, not errors.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fmt;
+use std::path::Path;
+use std::sync::LazyLock;
+
+use git2::{Delta, DiffOptions, Patch, Repository};
+use regex_lite::Regex;
+use tracing::debug;
+
+use crate::changelog::ChangelogEntry;
+use crate::git::ParsedCommit;
+
+/// Changed lines (added + removed) above which a file's diff is significant.
+pub const LARGE_DIFF_LINES: usize = 300;
+
+/// Files whose size of change says nothing about user-facing changes.
+const IGNORED_FILES: [&str; 5] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "CHANGELOG.md",
+];
+
+/// Stems too generic to identify a file; the parent directory is used.
+const GENERIC_STEMS: [&str; 4] = ["mod", "lib", "main", "index"];
+
+static ARG_ATTR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r#"#\[(?:arg|clap)\(.*\blong\b(?:\s*=\s*"([^"]+)")?"#)
+        .expect("Invalid arg attribute regex - this is a bug")
+});
+
+static FIELD_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"^\s*(?:pub(?:\([^)]*\))?\s+)?(?:r#)?([a-z_][a-z0-9_]*)\s*:")
+        .expect("Invalid field regex - this is a bug")
+});
+
+/// Kind of significant change.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ChangeKind {
+    CliFlag,
+    NewFile,
+    LargeDiff,
+}
+
+/// A change that probably deserves a changelog entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SignificantChange {
+    pub kind: ChangeKind,
+    /// The flag (`--dry-run`) or file path.
+    pub subject: String,
+    /// Full hashes of the commits making the change.
+    pub commits: Vec<String>,
+    /// Lines added and removed, for large diffs.
+    pub lines: Option<(usize, usize)>,
+}
+
+impl fmt::Display for SignificantChange {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let hashes: Vec<&str> = self.commits.iter().map(|h| &h[..h.len().min(7)]).collect();
+        match (self.kind, self.lines) {
+            (ChangeKind::CliFlag, _) => write!(f, "new CLI flag {}", self.subject)?,
+            (ChangeKind::NewFile, _) => write!(f, "new file {}", self.subject)?,
+            (ChangeKind::LargeDiff, Some((added, removed))) => write!(
+                f,
+                "large change to {} (+{}/-{})",
+                self.subject, added, removed
+            )?,
+            (ChangeKind::LargeDiff, None) => write!(f, "large change to {}", self.subject)?,
+        }
+        write!(f, " ({})", hashes.join(", "))
+    }
+}
+
+impl SignificantChange {
+    /// Whether `entry` covers the change.
+    pub fn covered_by(&self, entry: &ChangelogEntry) -> bool {
+        let cited = entry.sources.iter().any(|source| {
+            let source = source.trim();
+            source.len() >= 7 && self.commits.iter().any(|c| c.starts_with(source))
+        });
+        cited
+            || self
+                .names()
+                .iter()
+                .any(|name| mentions(&entry.description, name))
+    }
+
+    /// Names an entry may use for the change.
+    fn names(&self) -> Vec<String> {
+        if self.kind == ChangeKind::CliFlag {
+            return vec![self.subject.trim_start_matches('-').to_string()];
+        }
+        let path = Path::new(&self.subject);
+        let stem = path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or_default();
+        let name = if GENERIC_STEMS.contains(&stem) {
+            path.parent()
+                .and_then(|p| p.file_name())
+                .and_then(|s| s.to_str())
+                .filter(|s| *s != "src")
+                .unwrap_or_default()
+        } else {
+            stem
+        };
+        if name.is_empty() {
+            Vec::new()
+        } else {
+            vec![name.to_string()]
+        }
+    }
+}
+
+/// Whether `description` names `name` as written (`dry-run`, `pr_limit`) or
+/// as words (`dry run`, `pr limit`).
+fn mentions(description: &str, name: &str) -> bool {
+    let description = description.to_lowercase();
+    let name = name.to_lowercase();
+    description.contains(&name) || description.contains(&name.replace(['-', '_'], " "))
+}
+
+/// `--flag` names of clap fields added in `added_lines`.
+fn added_flags(added_lines: &[&str]) -> Vec<String> {
+    let mut flags = Vec::new();
+    // Set by an `#[arg(long)]` attribute: the explicit name, if any
+    let mut pending: Option<Option<String>> = None;
+    for line in added_lines {
+        let trimmed = line.trim();
+        if let Some(caps) = ARG_ATTR_REGEX.captures(trimmed) {
+            pending = Some(caps.get(1).map(|m| m.as_str().to_string()));
+        } else if trimmed.starts_with("#[") || trimmed.starts_with("///") {
+            // Other attributes and doc comments may sit between
+        } else if let Some(explicit) = pending.take()
+            && let Some(caps) = FIELD_REGEX.captures(trimmed)
+        {
+            let name = explicit.unwrap_or_else(|| caps[1].replace('_', "-"));
+            flags.push(format!("--{}", name));
+        }
+    }
+    flags
+}
+
+/// Find the significant changes made by `commits`.
+///
+/// Each commit is diffed against its first parent. Commits that cannot be
+/// read are skipped.
+pub fn significant_changes(repo: &Repository, commits: &[ParsedCommit]) -> Vec<SignificantChange> {
+    let mut flags: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut new_files: BTreeMap<String, Vec<String>> = BTreeMap::new();
+    let mut sizes: HashMap<String, (usize, usize, Vec<String>)> = HashMap::new();
+
+    for commit in commits {
+        let Some(diff) = commit_diff(repo, &commit.hash) else {
+            debug!(
+                "Skipping unreadable commit {} in missed-change pass",
+                commit.hash
+            );
+            continue;
+        };
+        for index in 0..diff.deltas().len() {
+            let Ok(Some(patch)) = Patch::from_diff(&diff, index) else {
+                continue;
+            };
+            let delta = patch.delta();
+            let Some(path) = delta.new_file().path().and_then(|p| p.to_str()) else {
+                continue;
+            };
+            let path = path.to_string();
+
+            if delta.status() == Delta::Added && path.starts_with("src/") {
+                new_files
+                    .entry(path.clone())
+                    .or_default()
+                    .push(commit.hash.clone());
+            }
+
+            let Ok((_, additions, deletions)) = patch.line_stats() else {
+                continue;
+            };
+            let size = sizes.entry(path.clone()).or_default();
+            size.0 += additions;
+            size.1 += deletions;
+            if !size.2.contains(&commit.hash) {
+                size.2.push(commit.hash.clone());
+            }
+
+            if path.ends_with(".rs") {
+                let added = added_lines(&patch);
+                let added: Vec<&str> = added.iter().map(String::as_str).collect();
+                for flag in added_flags(&added) {
+                    flags.entry(flag).or_default().push(commit.hash.clone());
+                }
+            }
+        }
+    }
+
+    let mut changes: Vec<SignificantChange> = flags
+        .into_iter()
+        .map(|(subject, commits)| SignificantChange {
+            kind: ChangeKind::CliFlag,
+            subject,
+            commits,
+            lines: None,
+        })
+        .collect();
+    changes.extend(
+        new_files
+            .iter()
+            .map(|(subject, commits)| SignificantChange {
+                kind: ChangeKind::NewFile,
+                subject: subject.clone(),
+                commits: commits.clone(),
+                lines: None,
+            }),
+    );
+
+    let mut large: Vec<SignificantChange> = sizes
+        .into_iter()
+        .filter(|(path, (added, removed, _))| {
+            added + removed >= LARGE_DIFF_LINES
+                && !new_files.contains_key(path)
+                && !is_ignored(path)
+        })
+        .map(|(subject, (added, removed, commits))| SignificantChange {
+            kind: ChangeKind::LargeDiff,
+            subject,
+            commits,
+            lines: Some((added, removed)),
+        })
+        .collect();
+    large.sort_by(|a, b| a.subject.cmp(&b.subject));
+    changes.extend(large);
+    changes
+}
+
+/// Significant changes in `commits` that none of `entries` covers.
+pub fn find_missed_changes(
+    repo: &Repository,
+    commits: &[ParsedCommit],
+    entries: &[ChangelogEntry],
+) -> Vec<SignificantChange> {
+    significant_changes(repo, commits)
+        .into_iter()
+        .filter(|change| !entries.iter().any(|entry| change.covered_by(entry)))
+        .collect()
+}
+
+fn is_ignored(path: &str) -> bool {
+    let file_name = path.rsplit('/').next().unwrap_or(path);
+    IGNORED_FILES.contains(&file_name) || path.starts_with(".keryx/")
+}
+
+pub(crate) fn commit_diff<'r>(repo: &'r Repository, hash: &str) -> Option<git2::Diff<'r>> {
+    let oid = git2::Oid::from_str(hash).ok()?;
+    let commit = repo.find_commit(oid).ok()?;
+    let tree = commit.tree().ok()?;
+    let parent_tree = commit.parent(0).ok().and_then(|p| p.tree().ok());
+    let mut opts = DiffOptions::new();
+    opts.context_lines(0);
+    repo.diff_tree_to_tree(parent_tree.as_ref(), Some(&tree), Some(&mut opts))
+        .ok()
+}
+
+fn added_lines(patch: &Patch) -> Vec<String> {
+    let mut lines = Vec::new();
+    for hunk in 0..patch.num_hunks() {
+        let Ok(count) = patch.num_lines_in_hunk(hunk) else {
+            continue;
+        };
+        for index in 0..count {
+            if let Ok(line) = patch.line_in_hunk(hunk, index)
+                && line.origin() == '+'
+            {
+                lines.push(String::from_utf8_lossy(line.content()).to_string());
+            }
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogCategory;
+    use crate::git::Footers;
+    use chrono::Utc;
+    use git2::{Oid, Signature};
+    use tempfile::TempDir;
+
+    fn commit_file(repo: &Repository, path: &str, content: &str, message: &str) -> Oid {
+        let root = repo.workdir().unwrap();
+        std::fs::create_dir_all(root.join(path).parent().unwrap()).unwrap();
+        std::fs::write(root.join(path), content).unwrap();
+        let mut index = repo.index().unwrap();
+        index.add_path(Path::new(path)).unwrap();
+        index.write().unwrap();
+        let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+        let sig = Signature::now("Test", "test@example.com").unwrap();
+        let parent = repo.head().ok().and_then(|h| h.peel_to_commit().ok());
+        let parents: Vec<&git2::Commit> = parent.iter().collect();
+        repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+            .unwrap()
+    }
+
+    fn parsed(oid: Oid, message: &str) -> ParsedCommit {
+        ParsedCommit {
+            hash: oid.to_string(),
+            message: message.to_string(),
+            commit_type: None,
+            scope: None,
+            breaking: false,
+            timestamp: Utc::now(),
+            footers: Footers::default(),
+        }
+    }
+
+    fn entry(description: &str, sources: &[&str]) -> ChangelogEntry {
+        ChangelogEntry {
+            category: ChangelogCategory::Added,
+            description: description.to_string(),
+            sources: sources.iter().map(|s| s.to_string()).collect(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    #[test]
+    fn test_added_flags() {
+        let lines = [
+            "    /// Skip the PR fetch",
+            "    #[arg(long)]",
+            "    no_prs: bool,",
+            "    #[arg(short, long = \"out\", value_name = \"FILE\")]",
+            "    #[serde(default)]",
+            "    pub output_path: Option<String>,",
+            "    #[arg(long_help = \"no flag here\")]",
+            "    verbose: bool,",
+        ];
+        assert_eq!(added_flags(&lines), vec!["--no-prs", "--out"]);
+    }
+
+    #[test]
+    fn test_covered_by() {
+        let flag = SignificantChange {
+            kind: ChangeKind::CliFlag,
+            subject: "--dry-run".to_string(),
+            commits: vec!["0123456789abcdef".to_string()],
+            lines: None,
+        };
+        assert!(flag.covered_by(&entry("Add a dry run mode", &[])));
+        assert!(flag.covered_by(&entry("Preview releases", &["0123456"])));
+        assert!(!flag.covered_by(&entry("Preview releases", &["#12"])));
+
+        let file = SignificantChange {
+            kind: ChangeKind::NewFile,
+            subject: "src/ship/mod.rs".to_string(),
+            commits: vec!["abcdef0123456789".to_string()],
+            lines: None,
+        };
+        assert!(file.covered_by(&entry("Add the ship command", &[])));
+        let root = SignificantChange {
+            subject: "src/main.rs".to_string(),
+            ..file
+        };
+        assert!(!root.covered_by(&entry("Improve the main loop", &[])));
+    }
+
+    #[test]
+    fn test_display() {
+        let change = SignificantChange {
+            kind: ChangeKind::LargeDiff,
+            subject: "src/main.rs".to_string(),
+            commits: vec!["0123456789".to_string(), "abcdef0123".to_string()],
+            lines: Some((320, 45)),
+        };
+        assert_eq!(
+            change.to_string(),
+            "large change to src/main.rs (+320/-45) (0123456, abcdef0)"
+        );
+    }
+
+    #[test]
+    fn test_find_missed_changes() {
+        let dir = TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        commit_file(&repo, "src/main.rs", "struct Cli {}\n", "chore: init");
+        let flag = commit_file(
+            &repo,
+            "src/main.rs",
+            "struct Cli {\n    #[arg(long)]\n    dry_run: bool,\n}\n",
+            "feat: add --dry-run",
+        );
+        let export = commit_file(
+            &repo,
+            "src/export.rs",
+            "pub fn export() {}\n",
+            "feat: export",
+        );
+        let big: String = (0..LARGE_DIFF_LINES).map(|i| format!("{}\n", i)).collect();
+        let data = commit_file(&repo, "data.txt", &big, "chore: data");
+        let lock = commit_file(&repo, "Cargo.lock", &big, "chore: lock");
+
+        let commits = vec![
+            parsed(flag, "feat: add --dry-run"),
+            parsed(export, "feat: export"),
+            parsed(data, "chore: data"),
+            parsed(lock, "chore: lock"),
+        ];
+        let entries = vec![entry("Add CSV export", &[])];
+        let missed: Vec<String> = find_missed_changes(&repo, &commits, &entries)
+            .iter()
+            .map(|c| c.subject.clone())
+            .collect();
+        assert_eq!(missed, vec!["--dry-run", "data.txt"]);
+    }
+}
@@ -7,14 +7,30 @@ use std::process::Command;
 
 use crate::error::VerificationError;
 
+pub mod api_surface;
+pub mod collectors;
 pub mod evidence;
+pub mod keywords;
+pub mod missed;
+pub mod rules;
+pub mod sanity;
 pub mod scanner;
+pub mod scope;
 
+pub use api_surface::{ApiDiff, PublicItem, attach_api_surface, diff_public_api};
+pub use collectors::{ExternalCollector, ExternalEvidence, attach_external_evidence};
 pub use evidence::{
     Confidence, CountCheck, EntryEvidence, KeywordMatch, ScanSummary, StubIndicator, StubType,
     VerificationEvidence,
 };
-pub use scanner::gather_verification_evidence;
+pub use keywords::KeywordExtractor;
+pub use missed::{ChangeKind, SignificantChange, find_missed_changes, significant_changes};
+pub use rules::{
+    DropReason, RuleVerdict, RulesOutcome, VerifyMode, apply_verification_rules, judge_entry,
+};
+pub use sanity::{SanityOutcome, SourceCorpus, component_references, drop_unsupported_entries};
+pub use scanner::{gather_verification_evidence, gather_verification_evidence_in};
+pub use scope::{ScopeMode, SearchScope};
 
 /// Check if ripgrep (rg) is installed and accessible.
 ///
@@ -0,0 +1,316 @@
+//! Deterministic verification rules.
+//!
+//! A cheaper alternative to the LLM verification pass: entries are kept,
+//! flagged, or dropped based purely on the gathered evidence.
+
+use crate::changelog::{ChangelogEntry, ChangelogOutput};
+
+use super::evidence::{Confidence, EntryEvidence, VerificationEvidence};
+
+/// How generated entries are verified against the codebase.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum VerifyMode {
+    /// Apply deterministic rules to the gathered evidence (no second LLM pass).
+    Rules,
+    /// Send the draft and the evidence to the LLM for a verification pass.
+    #[default]
+    Llm,
+    /// Skip verification entirely.
+    Off,
+}
+
+impl std::fmt::Display for VerifyMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyMode::Rules => write!(f, "rules"),
+            VerifyMode::Llm => write!(f, "llm"),
+            VerifyMode::Off => write!(f, "off"),
+        }
+    }
+}
+
+/// Why the rules removed an entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DropReason {
+    /// Every keyword was searched successfully and none was found in the codebase.
+    NoKeywordMatches,
+    /// A numeric claim was checked and does not match the actual count.
+    CountMismatch {
+        claimed_text: String,
+        actual_count: usize,
+    },
+}
+
+impl std::fmt::Display for DropReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DropReason::NoKeywordMatches => write!(f, "no keyword found in the codebase"),
+            DropReason::CountMismatch {
+                claimed_text,
+                actual_count,
+            } => write!(f, "claimed {}, found {}", claimed_text, actual_count),
+        }
+    }
+}
+
+/// Verdict of the rules for a single entry.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RuleVerdict {
+    /// Evidence supports the entry.
+    Keep,
+    /// Entry is kept but has low confidence and should be reviewed.
+    Flag,
+    /// Entry is contradicted by the evidence and is removed.
+    Drop(DropReason),
+}
+
+/// Judge a single entry from its evidence.
+///
+/// An entry is dropped only when the evidence contradicts it: a verified count
+/// mismatch, or keywords that were all searched without error and never found.
+/// Entries without extractable keywords or with failed searches cannot be
+/// judged this way and fall through to the confidence check.
+pub fn judge_entry(evidence: &EntryEvidence) -> RuleVerdict {
+    if let Some(check) = evidence
+        .count_checks
+        .iter()
+        .find(|c| c.matches() == Some(false))
+    {
+        return RuleVerdict::Drop(DropReason::CountMismatch {
+            claimed_text: check.claimed_text.clone(),
+            actual_count: check.actual_count.unwrap_or_default(),
+        });
+    }
+
+    let summary = &evidence.scan_summary;
+    if summary.total_keywords > 0 && !summary.has_failures() && evidence.keyword_matches.is_empty()
+    {
+        return RuleVerdict::Drop(DropReason::NoKeywordMatches);
+    }
+
+    if evidence.confidence() == Confidence::Low {
+        RuleVerdict::Flag
+    } else {
+        RuleVerdict::Keep
+    }
+}
+
+/// Result of applying the rules to a draft.
+#[derive(Debug, Clone)]
+pub struct RulesOutcome {
+    /// The draft without the dropped entries.
+    pub output: ChangelogOutput,
+    /// Entries removed by the rules, with the reason.
+    pub dropped: Vec<(ChangelogEntry, DropReason)>,
+    /// Entries kept despite low confidence.
+    pub flagged: Vec<ChangelogEntry>,
+}
+
+/// Apply the deterministic rules to every entry of a draft.
+///
+/// Evidence is matched to entries by position, as produced by
+/// [`gather_verification_evidence`](super::gather_verification_evidence).
+/// Entries without evidence are kept unchanged.
+pub fn apply_verification_rules(
+    draft: &ChangelogOutput,
+    evidence: &VerificationEvidence,
+) -> RulesOutcome {
+    let mut output = draft.clone();
+    let mut dropped = Vec::new();
+    let mut flagged = Vec::new();
+
+    output.entries.clear();
+    for (i, entry) in draft.entries.iter().enumerate() {
+        let verdict = evidence
+            .entries
+            .get(i)
+            .map(judge_entry)
+            .unwrap_or(RuleVerdict::Keep);
+        match verdict {
+            RuleVerdict::Keep => output.entries.push(entry.clone()),
+            RuleVerdict::Flag => {
+                flagged.push(entry.clone());
+                output.entries.push(entry.clone());
+            }
+            RuleVerdict::Drop(reason) => dropped.push((entry.clone(), reason)),
+        }
+    }
+
+    RulesOutcome {
+        output,
+        dropped,
+        flagged,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogCategory;
+    use crate::verification::{CountCheck, KeywordMatch, ScanSummary, StubIndicator, StubType};
+
+    fn keyword_match(keyword: &str) -> KeywordMatch {
+        KeywordMatch {
+            keyword: keyword.to_string(),
+            files_found: vec!["a.rs".to_string(), "b.rs".to_string(), "c.rs".to_string()],
+            occurrence_count: Some(5),
+            sample_lines: Some(vec![]),
+            appears_complete: true,
+        }
+    }
+
+    fn summary(total: usize, failed: usize) -> ScanSummary {
+        ScanSummary {
+            total_keywords: total,
+            successful_searches: total - failed,
+            failed_searches: failed,
+        }
+    }
+
+    fn evidence(
+        matches: Vec<KeywordMatch>,
+        count_checks: Vec<CountCheck>,
+        stubs: Vec<StubIndicator>,
+        scan_summary: ScanSummary,
+    ) -> EntryEvidence {
+        EntryEvidence::new(
+            "entry".to_string(),
+            ChangelogCategory::Added,
+            matches,
+            count_checks,
+            stubs,
+            scan_summary,
+        )
+    }
+
+    fn count_check(claimed: usize, actual: Option<usize>) -> CountCheck {
+        CountCheck {
+            claimed_text: format!("{} templates", claimed),
+            claimed_count: Some(claimed),
+            actual_count: actual,
+            source_location: None,
+        }
+    }
+
+    fn stub() -> StubIndicator {
+        StubIndicator {
+            file: "a.rs".to_string(),
+            line: 1,
+            indicator: StubType::Todo,
+            context: "// TODO".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_judge_keeps_supported_entry() {
+        let ev = evidence(vec![keyword_match("export")], vec![], vec![], summary(1, 0));
+        assert_eq!(judge_entry(&ev), RuleVerdict::Keep);
+    }
+
+    #[test]
+    fn test_judge_drops_entry_without_keyword_matches() {
+        let ev = evidence(vec![], vec![], vec![], summary(2, 0));
+        assert_eq!(
+            judge_entry(&ev),
+            RuleVerdict::Drop(DropReason::NoKeywordMatches)
+        );
+    }
+
+    #[test]
+    fn test_judge_drops_count_mismatch() {
+        let ev = evidence(
+            vec![keyword_match("templates")],
+            vec![count_check(8, Some(5))],
+            vec![],
+            summary(1, 0),
+        );
+        assert_eq!(
+            judge_entry(&ev),
+            RuleVerdict::Drop(DropReason::CountMismatch {
+                claimed_text: "8 templates".to_string(),
+                actual_count: 5,
+            })
+        );
+    }
+
+    #[test]
+    fn test_judge_does_not_drop_when_searches_failed() {
+        // A failed search is not evidence of absence.
+        let ev = evidence(vec![], vec![], vec![], summary(2, 1));
+        assert_eq!(judge_entry(&ev), RuleVerdict::Flag);
+    }
+
+    #[test]
+    fn test_judge_does_not_drop_unverifiable_count() {
+        let ev = evidence(
+            vec![keyword_match("templates")],
+            vec![count_check(8, None)],
+            vec![],
+            summary(1, 0),
+        );
+        assert_eq!(judge_entry(&ev), RuleVerdict::Keep);
+    }
+
+    #[test]
+    fn test_judge_flags_low_confidence_entry() {
+        let mut km = keyword_match("export");
+        km.files_found.truncate(1);
+        km.appears_complete = false;
+        let ev = evidence(vec![km], vec![], vec![stub(), stub()], summary(1, 0));
+        assert_eq!(ev.confidence(), Confidence::Low);
+        assert_eq!(judge_entry(&ev), RuleVerdict::Flag);
+    }
+
+    #[test]
+    fn test_apply_rules_partitions_entries() {
+        let draft = ChangelogOutput {
+            entries: vec![
+                ChangelogEntry {
+                    category: ChangelogCategory::Added,
+                    description: "Kept".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
+                },
+                ChangelogEntry {
+                    category: ChangelogCategory::Fixed,
+                    description: "Dropped".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
+                },
+                ChangelogEntry {
+                    category: ChangelogCategory::Changed,
+                    description: "No evidence".to_string(),
+                    sources: Vec::new(),
+                    scope: None,
+                    importance: None,
+                },
+            ],
+            highlights: Some("Summary".to_string()),
+        };
+        let mut all = VerificationEvidence::empty();
+        all.entries.push(evidence(
+            vec![keyword_match("kept")],
+            vec![],
+            vec![],
+            summary(1, 0),
+        ));
+        all.entries
+            .push(evidence(vec![], vec![], vec![], summary(1, 0)));
+
+        let outcome = apply_verification_rules(&draft, &all);
+
+        let kept: Vec<_> = outcome
+            .output
+            .entries
+            .iter()
+            .map(|e| e.description.as_str())
+            .collect();
+        assert_eq!(kept, vec!["Kept", "No evidence"]);
+        assert_eq!(outcome.dropped.len(), 1);
+        assert_eq!(outcome.dropped[0].0.description, "Dropped");
+        assert!(outcome.flagged.is_empty());
+        assert_eq!(outcome.output.highlights.as_deref(), Some("Summary"));
+    }
+}
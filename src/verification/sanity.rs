@@ -0,0 +1,232 @@
+//! Cheap pre-verification sanity pass.
+//!
+//! Before the expensive verification step, entries are checked against the
+//! raw input: the commit messages, the PR titles and bodies, and the paths
+//! tracked in the repository. An entry that names components (code
+//! identifiers, paths, CLI flags) none of which appear anywhere in that text is
+//! an obvious hallucination and is dropped. Entries naming no components are
+//! left for the real verification.
+
+use std::sync::LazyLock;
+
+use git2::Repository;
+use regex_lite::Regex;
+use tracing::warn;
+
+use crate::changelog::{ChangelogEntry, ChangelogOutput};
+use crate::git::{ParsedCommit, head_tree_files};
+use crate::github::PullRequest;
+
+/// Terms in backticks, e.g. `` `Config::load` ``.
+static BACKTICK_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(r"`([^`]{2,60})`").expect("Invalid backtick regex - this is a bug")
+});
+
+/// Code-like identifiers: paths (`src/llm/router.rs`), `::` paths, CLI
+/// flags, snake_case and CamelCase with at least two humps.
+static COMPONENT_REGEX: LazyLock<Regex> = LazyLock::new(|| {
+    Regex::new(
+        r"[A-Za-z0-9_.-]+(?:/[A-Za-z0-9_.-]+)+|\b\w+(?:::\w+)+|--[a-z][a-z0-9-]+|\b[a-z][a-z0-9]*(?:_[a-z0-9]+)+\b|\b[A-Z][a-z0-9]+(?:[A-Z][a-z0-9]*)+\b",
+    )
+    .expect("Invalid component regex - this is a bug")
+});
+
+/// Everything an entry may legitimately refer to, lowercased.
+#[derive(Debug, Clone, Default)]
+pub struct SourceCorpus {
+    text: String,
+}
+
+impl SourceCorpus {
+    /// Build from commit messages, PR titles and bodies, and file paths.
+    pub fn new(commits: &[ParsedCommit], prs: &[PullRequest], files: &[String]) -> Self {
+        let mut text = String::new();
+        for commit in commits {
+            text.push_str(&commit.message);
+            text.push('\n');
+        }
+        for pr in prs {
+            text.push_str(&pr.title);
+            text.push('\n');
+            if let Some(body) = &pr.body {
+                text.push_str(body);
+                text.push('\n');
+            }
+        }
+        for file in files {
+            text.push_str(file);
+            text.push('\n');
+        }
+        Self {
+            text: text.to_lowercase(),
+        }
+    }
+
+    /// Build with the paths in `repo`'s index (the tree of `HEAD` in a bare
+    /// repository) as the file list.
+    pub fn for_repository(
+        repo: &Repository,
+        commits: &[ParsedCommit],
+        prs: &[PullRequest],
+    ) -> Self {
+        let files = if repo.is_bare() {
+            head_tree_files(repo)
+        } else {
+            match repo.index() {
+                Ok(index) => index
+                    .iter()
+                    .map(|entry| String::from_utf8_lossy(&entry.path).to_string())
+                    .collect(),
+                Err(e) => {
+                    warn!("Failed to read the index for the sanity pass: {}", e);
+                    Vec::new()
+                }
+            }
+        };
+        Self::new(commits, prs, &files)
+    }
+
+    /// Whether `term` (case-insensitive) appears anywhere in the corpus.
+    pub fn mentions(&self, term: &str) -> bool {
+        self.text.contains(&term.to_lowercase())
+    }
+}
+
+/// Result of [`drop_unsupported_entries`].
+#[derive(Debug, Clone)]
+pub struct SanityOutcome {
+    /// The output without the dropped entries.
+    pub output: ChangelogOutput,
+    /// Dropped entries with the components that were not found.
+    pub dropped: Vec<(ChangelogEntry, Vec<String>)>,
+}
+
+/// Components named in an entry description.
+pub fn component_references(description: &str) -> Vec<String> {
+    let mut components: Vec<String> = Vec::new();
+    let quoted = BACKTICK_REGEX
+        .captures_iter(description)
+        .filter_map(|cap| cap.get(1))
+        .map(|m| m.as_str().trim().to_string());
+    let bare = COMPONENT_REGEX
+        .find_iter(description)
+        .map(|m| m.as_str().trim_matches('.').to_string());
+    for component in quoted.chain(bare) {
+        if !component.is_empty() && !components.contains(&component) {
+            components.push(component);
+        }
+    }
+    components
+}
+
+/// Drop entries whose named components appear nowhere in `corpus`.
+///
+/// An entry is dropped only if it names at least one component and none of
+/// them is found; one match is enough to leave it to verification.
+pub fn drop_unsupported_entries(output: &ChangelogOutput, corpus: &SourceCorpus) -> SanityOutcome {
+    let mut kept = Vec::new();
+    let mut dropped = Vec::new();
+
+    for entry in &output.entries {
+        let components = component_references(&entry.description);
+        if !components.is_empty() && !components.iter().any(|c| corpus.mentions(c)) {
+            dropped.push((entry.clone(), components));
+        } else {
+            kept.push(entry.clone());
+        }
+    }
+
+    SanityOutcome {
+        output: ChangelogOutput {
+            entries: kept,
+            highlights: output.highlights.clone(),
+        },
+        dropped,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::changelog::ChangelogCategory;
+    use crate::git::Footers;
+    use chrono::Utc;
+    use std::num::NonZeroU64;
+
+    fn entry(description: &str) -> ChangelogEntry {
+        ChangelogEntry {
+            category: ChangelogCategory::Added,
+            description: description.to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
+        }
+    }
+
+    fn corpus() -> SourceCorpus {
+        let commits = vec![ParsedCommit {
+            hash: "abc1234".to_string(),
+            message: "feat(cli): add --dry-run to ship".to_string(),
+            commit_type: None,
+            scope: None,
+            breaking: false,
+            timestamp: Utc::now(),
+            footers: Footers::default(),
+        }];
+        let prs = vec![PullRequest {
+            number: NonZeroU64::new(7).unwrap(),
+            title: "Retry rate-limited requests".to_string(),
+            body: Some("Wraps HttpClient in a retry layer".to_string()),
+            merged_at: None,
+            labels: vec![],
+        }];
+        SourceCorpus::new(&commits, &prs, &["src/llm/router.rs".to_string()])
+    }
+
+    #[test]
+    fn test_component_references() {
+        let components = component_references(
+            "Add `keryx explain` and --no-follow-up to src/main.rs via HttpClient",
+        );
+        assert_eq!(
+            components,
+            vec![
+                "keryx explain".to_string(),
+                "--no-follow-up".to_string(),
+                "src/main.rs".to_string(),
+                "HttpClient".to_string(),
+            ]
+        );
+        assert!(component_references("Faster startup on large repositories").is_empty());
+    }
+
+    #[test]
+    fn test_drops_only_entries_with_no_known_component() {
+        let output = ChangelogOutput {
+            entries: vec![
+                entry("Add --dry-run to preview a release"),
+                entry("Retry requests in HttpClient and `TokenBucket`"),
+                entry("Add `QuantumCache` for faster builds"),
+                entry("Faster startup"),
+            ],
+            highlights: Some("Highlights".to_string()),
+        };
+
+        let outcome = drop_unsupported_entries(&output, &corpus());
+        assert_eq!(outcome.output.entries.len(), 3);
+        assert_eq!(outcome.output.highlights.as_deref(), Some("Highlights"));
+        assert_eq!(outcome.dropped.len(), 1);
+        assert_eq!(
+            outcome.dropped[0].0.description,
+            "Add `QuantumCache` for faster builds"
+        );
+        assert_eq!(outcome.dropped[0].1, vec!["QuantumCache".to_string()]);
+    }
+
+    #[test]
+    fn test_matches_paths_case_insensitively() {
+        let corpus = corpus();
+        assert!(corpus.mentions("SRC/LLM/ROUTER.RS"));
+        assert!(!corpus.mentions("src/llm/provider.rs"));
+    }
+}
@@ -1,18 +1,29 @@
 //! Codebase scanner for gathering verification evidence.
 
-use std::collections::HashSet;
-use std::path::Path;
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::sync::LazyLock;
 
+use git2::{ObjectType, Oid, Repository, StatusOptions};
 use regex_lite::Regex;
-use tracing::{debug, warn};
+use tracing::{debug, trace, warn};
 
+use super::api_surface::attach_api_surface;
+use super::collectors::{ExternalCollector, attach_external_evidence};
 use super::evidence::{
     CountCheck, EntryEvidence, KeyFileContent, KeywordMatch, ScanSummary, StubIndicator, StubType,
     VerificationEvidence,
 };
+use super::keywords::KeywordExtractor;
+use super::scope::SearchScope;
+use crate::atomic::write_atomic;
 use crate::changelog::ChangelogEntry;
 use crate::error::VerificationError;
+use crate::journal::{JOURNAL_DIR, ensure_state_dir};
+use crate::languages::source_extensions;
+use crate::text::truncate_bytes_with_suffix;
 
 /// Outcome of a ripgrep command execution.
 #[derive(Debug)]
@@ -30,6 +41,7 @@ enum RgOutcome {
 /// - Exit code 1 (no matches) → `Ok(RgOutcome::NoMatch)`
 /// - Other exit codes or errors → `Err(VerificationError)`
 fn run_rg(cmd: &mut Command) -> Result<RgOutcome, VerificationError> {
+    trace!("Running {:?}", cmd);
     match cmd.output() {
         Ok(out) if out.status.success() => Ok(RgOutcome::Success(
             String::from_utf8_lossy(&out.stdout).to_string(),
@@ -73,6 +85,10 @@ fn run_rg_or_warn(cmd: &mut Command, context: &str) -> Option<String> {
 /// Max number of lines to include in project structure output.
 const PROJECT_STRUCTURE_MAX_LINES: usize = 50;
 
+/// Ripgrep arguments reporting paths with `/` on every platform, so evidence
+/// paths match the forward-slash paths git uses on Windows too.
+const RG_PATH_SEPARATOR: &[&str] = &["--path-separator", "/"];
+
 /// Common ripgrep arguments to exclude build/dependency directories.
 const RG_EXCLUDE_PATTERNS: &[&str] = &[
     "-g",
@@ -87,13 +103,15 @@ const RG_EXCLUDE_PATTERNS: &[&str] = &[
     "!.git",
 ];
 
-/// Ripgrep type definition for common source code files.
-const RG_CODE_TYPE: &[&str] = &[
-    "--type-add",
-    "code:*.{rs,ts,tsx,js,jsx,py,go,java,c,cpp,h,hpp}",
-    "--type",
-    "code",
-];
+/// Ripgrep type definition for source files of every recognized language.
+static RG_CODE_TYPE: LazyLock<[String; 4]> = LazyLock::new(|| {
+    [
+        "--type-add".to_string(),
+        format!("code:*.{{{}}}", source_extensions().join(",")),
+        "--type".to_string(),
+        "code".to_string(),
+    ]
+});
 
 /// Patterns that indicate incomplete/stub code and their corresponding types.
 const STUB_PATTERNS: &[(&str, StubType)] = &[
@@ -114,88 +132,12 @@ const STUB_PATTERNS: &[(&str, StubType)] = &[
     ),
 ];
 
-/// Common words to exclude from keyword extraction.
-const STOP_WORDS: &[&str] = &[
-    "the",
-    "a",
-    "an",
-    "and",
-    "or",
-    "but",
-    "in",
-    "on",
-    "at",
-    "to",
-    "for",
-    "of",
-    "with",
-    "by",
-    "from",
-    "as",
-    "is",
-    "was",
-    "are",
-    "were",
-    "been",
-    "be",
-    "have",
-    "has",
-    "had",
-    "do",
-    "does",
-    "did",
-    "will",
-    "would",
-    "could",
-    "should",
-    "may",
-    "might",
-    "must",
-    "shall",
-    "can",
-    "need",
-    "new",
-    "add",
-    "added",
-    "change",
-    "changed",
-    "fix",
-    "fixed",
-    "update",
-    "updated",
-    "remove",
-    "removed",
-    "improve",
-    "improved",
-    "support",
-    "supported",
-    "feature",
-    "features",
-    "now",
-    "using",
-    "use",
-    "based",
-    "all",
-    "any",
-    "some",
-    "more",
-    "less",
-    "better",
-    "best",
-    "first",
-    "initial",
-    "release",
-    "version",
-    "multiple",
-    "various",
-    "several",
-];
-
 /// Gather verification evidence for changelog entries.
 ///
 /// This function scans the codebase to verify claims made in the changelog entries.
 /// It extracts keywords, searches for them in the code, checks for stub indicators,
-/// and verifies numeric claims.
+/// and verifies numeric claims. Output of the external collectors configured
+/// in `keryx.toml` is attached to the entries they apply to.
 ///
 /// Warnings are recorded in `evidence.warnings` when sub-operations fail.
 /// Callers can check `evidence.is_degraded()` to determine if evidence gathering
@@ -203,8 +145,23 @@ const STOP_WORDS: &[&str] = &[
 pub fn gather_verification_evidence(
     entries: &[ChangelogEntry],
     repo_path: &Path,
+) -> VerificationEvidence {
+    gather_verification_evidence_in(entries, repo_path, &SearchScope::repository())
+}
+
+/// Gather verification evidence, searching keywords within `scope`.
+///
+/// Keywords not found in the scope are searched for across the whole
+/// repository before being reported missing. Numeric claims, project
+/// structure and key files always cover the whole repository.
+#[tracing::instrument(level = "trace", skip_all)]
+pub fn gather_verification_evidence_in(
+    entries: &[ChangelogEntry],
+    repo_path: &Path,
+    scope: &SearchScope,
 ) -> VerificationEvidence {
     let mut evidence = VerificationEvidence::empty();
+    evidence.search_scope = scope.paths().to_vec();
 
     // Gather project structure
     let (structure, source, warning) = get_project_structure(repo_path);
@@ -222,13 +179,38 @@ pub fn gather_verification_evidence(
     }
 
     // Process each entry
+    let extractor = KeywordExtractor::load(repo_path);
+    let mut cache = ScanCache::default();
+    let evidence_cache = EvidenceCache::open(repo_path, scope);
+    let mut reused = 0;
     for entry in entries {
-        let (entry_evidence, entry_warnings) = analyze_entry(entry, repo_path);
+        if let Some(cached) = evidence_cache.as_ref().and_then(|c| c.load(entry)) {
+            reused += 1;
+            evidence.entries.push(cached);
+            continue;
+        }
+        let (entry_evidence, entry_warnings) =
+            analyze_entry(entry, repo_path, scope, &extractor, &mut cache);
+        // Evidence from failed searches is retried by the next run
+        if entry_warnings.is_empty()
+            && let Some(evidence_cache) = &evidence_cache
+        {
+            evidence_cache.store(&entry_evidence);
+        }
         evidence.entries.push(entry_evidence);
         for w in entry_warnings {
             evidence.add_warning(w);
         }
     }
+    debug!(
+        "Scan cache answered {} lookups from memory; {} entries reused from disk",
+        cache.hits, reused
+    );
+
+    // Project-specific checkers from keryx.toml, then the built-in API diff
+    let collectors = ExternalCollector::load(repo_path);
+    attach_external_evidence(&mut evidence, entries, &collectors, repo_path);
+    attach_api_surface(&mut evidence, entries, repo_path);
 
     evidence
 }
@@ -236,14 +218,20 @@ pub fn gather_verification_evidence(
 /// Analyze a single changelog entry against the codebase.
 ///
 /// Returns the entry evidence and any warnings encountered during analysis.
-fn analyze_entry(entry: &ChangelogEntry, repo_path: &Path) -> (EntryEvidence, Vec<String>) {
+fn analyze_entry(
+    entry: &ChangelogEntry,
+    repo_path: &Path,
+    scope: &SearchScope,
+    extractor: &KeywordExtractor,
+    cache: &mut ScanCache,
+) -> (EntryEvidence, Vec<String>) {
     let description = &entry.description;
     let category = entry.category.clone();
     let mut warnings = Vec::new();
     let mut scan_summary = ScanSummary::new();
 
     // Extract keywords from the description
-    let keywords = extract_keywords(description);
+    let keywords = extractor.extract(description);
     debug!("Extracted keywords from '{}': {:?}", description, keywords);
 
     // Track total keywords
@@ -255,22 +243,34 @@ fn analyze_entry(entry: &ChangelogEntry, repo_path: &Path) -> (EntryEvidence, Ve
     let mut keyword_matches = Vec::new();
     let mut all_stub_indicators = Vec::new();
 
+    let repository = SearchScope::repository();
     for keyword in &keywords {
-        match search_keyword(keyword, repo_path) {
+        // Search the scope first; a miss there is confirmed repository-wide
+        let mut search_scope = scope;
+        let mut result = search_keyword(keyword, repo_path, search_scope, cache);
+        if matches!(result, Ok(None)) && !scope.is_repository() {
+            debug!("'{}' not found in scope; searching repository", keyword);
+            search_scope = &repository;
+            result = search_keyword(keyword, repo_path, search_scope, cache);
+        }
+        match result {
             Ok(Some(match_result)) => {
                 scan_summary.add_success();
                 // Check for stub indicators near the matches
-                let (stubs, stub_detection_ok) =
-                    match find_stub_indicators_near_keyword(keyword, repo_path) {
-                        Ok(s) => (s, true),
-                        Err(e) => {
-                            let msg =
-                                format!("Stub detection failed for keyword '{}': {}", keyword, e);
-                            warn!("{}. Conservatively marking as incomplete.", msg);
-                            warnings.push(msg);
-                            (Vec::new(), false)
-                        }
-                    };
+                let (stubs, stub_detection_ok) = match find_stub_indicators_near_keyword(
+                    keyword,
+                    repo_path,
+                    search_scope,
+                    cache,
+                ) {
+                    Ok(s) => (s, true),
+                    Err(e) => {
+                        let msg = format!("Stub detection failed for keyword '{}': {}", keyword, e);
+                        warn!("{}. Conservatively marking as incomplete.", msg);
+                        warnings.push(msg);
+                        (Vec::new(), false)
+                    }
+                };
                 // Mark incomplete if stubs found, occurrence counting failed, OR stub detection failed
                 let appears_complete =
                     stub_detection_ok && stubs.is_empty() && match_result.count.is_some();
@@ -321,86 +321,179 @@ fn analyze_entry(entry: &ChangelogEntry, repo_path: &Path) -> (EntryEvidence, Ve
     (entry_evidence, warnings)
 }
 
-/// Extract meaningful keywords from a description.
-fn extract_keywords(description: &str) -> Vec<String> {
-    let mut keywords = HashSet::new();
+/// Extract meaningful keywords from a description with the default
+/// (English, unconfigured) extractor.
+pub(crate) fn extract_keywords(description: &str) -> Vec<String> {
+    KeywordExtractor::default().extract(description)
+}
 
-    // Regex for potential keywords (CamelCase, snake_case, or significant words)
-    let word_re = Regex::new(r"[A-Z][a-z]+(?:[A-Z][a-z]+)*|[a-z]+(?:_[a-z]+)+|[A-Za-z]{4,}")
-        .expect("Invalid regex");
+/// Result of searching for a keyword.
+#[derive(Clone)]
+struct SearchResult {
+    files: Vec<String>,
+    /// Total occurrence count, or `None` if counting failed.
+    count: Option<usize>,
+    /// Sample lines, or `None` if sampling failed.
+    samples: Option<Vec<String>>,
+}
 
-    for cap in word_re.find_iter(description) {
-        let word = cap.as_str().to_lowercase();
+/// Cache key for a keyword search: searches ignore case, so keywords
+/// differing only in case share results.
+type SearchKey = (String, Vec<String>);
 
-        // Skip stop words
-        if STOP_WORDS.contains(&word.as_str()) {
-            continue;
+fn search_key(keyword: &str, scope: &SearchScope) -> SearchKey {
+    (keyword.to_lowercase(), scope.paths().to_vec())
+}
+
+/// Ripgrep results shared by all entries of a run.
+///
+/// Entries of one release name the same components, so the same keyword is
+/// searched for again and again, and the keyword search and the stub scan
+/// both start from the files containing it. Each search runs ripgrep once
+/// per run; later lookups are answered from memory. Errors are not cached,
+/// so a failed search is retried by the next entry.
+#[derive(Default)]
+struct ScanCache {
+    /// Every file containing a keyword; `None` if there is none.
+    files: HashMap<SearchKey, Option<Vec<String>>>,
+    /// Keyword search results; `None` if nothing matched.
+    searches: HashMap<SearchKey, Option<SearchResult>>,
+    /// Stub indicators per file.
+    stubs: HashMap<String, Vec<StubIndicator>>,
+    /// Lookups answered from memory, for the debug log.
+    hits: usize,
+}
+
+impl ScanCache {
+    /// Files containing `keyword` in `scope`, in ripgrep's order.
+    fn files_with_matches(
+        &mut self,
+        keyword: &str,
+        repo_path: &Path,
+        scope: &SearchScope,
+    ) -> Result<Option<Vec<String>>, VerificationError> {
+        let key = search_key(keyword, scope);
+        if let Some(files) = self.files.get(&key) {
+            self.hits += 1;
+            return Ok(files.clone());
         }
+        let files = match run_rg(
+            build_rg_keyword_command(keyword, repo_path, scope).arg("--files-with-matches"),
+        )? {
+            RgOutcome::Success(stdout) => {
+                let files: Vec<String> = stdout.lines().map(String::from).collect();
+                (!files.is_empty()).then_some(files)
+            }
+            RgOutcome::NoMatch => None,
+        };
+        self.files.insert(key, files.clone());
+        Ok(files)
+    }
+}
 
-        // Skip very short or very long words
-        if word.len() < 4 || word.len() > 30 {
-            continue;
+/// Directory of the evidence cache inside [`JOURNAL_DIR`].
+pub const EVIDENCE_CACHE_DIR: &str = "cache/verification";
+
+/// Entry evidence of earlier runs, on disk.
+///
+/// Scanning an entry against the same code finds the same evidence, so
+/// repeated runs (e.g. `--dry-run` while tuning entries) reuse it instead of
+/// running ripgrep again. Evidence is stored under
+/// `.keryx/cache/verification/<HEAD>/`, in a file named by the hash of the
+/// entry description and search scope. The cache is only used while the
+/// working tree is clean, when `HEAD` describes the searched code; storing
+/// evidence for a new `HEAD` drops that of other commits.
+struct EvidenceCache {
+    /// The `.keryx` directory.
+    state_dir: PathBuf,
+    dir: PathBuf,
+    /// Scope and keryx version, hashed into every key.
+    salt: String,
+}
+
+impl EvidenceCache {
+    /// The cache for the repository containing `repo_path`, if it has a clean
+    /// working tree.
+    fn open(repo_path: &Path, scope: &SearchScope) -> Option<Self> {
+        let repo = Repository::discover(repo_path).ok()?;
+        let root = repo.workdir()?.to_path_buf();
+        let head = repo.head().ok()?.peel_to_commit().ok()?.id();
+        let mut options = StatusOptions::new();
+        options.include_untracked(true).include_ignored(false);
+        if !repo.statuses(Some(&mut options)).ok()?.is_empty() {
+            debug!("Working tree has changes; not using the evidence cache");
+            return None;
         }
+        let state_dir = root.join(JOURNAL_DIR);
+        Some(Self {
+            dir: state_dir.join(EVIDENCE_CACHE_DIR).join(head.to_string()),
+            state_dir,
+            salt: format!(
+                "{}\0{}\0{}",
+                env!("CARGO_PKG_VERSION"),
+                repo_path.display(),
+                scope.paths().join("\0")
+            ),
+        })
+    }
 
-        keywords.insert(word);
+    fn entry_path(&self, description: &str) -> Option<PathBuf> {
+        let key = format!("{}\0{}", self.salt, description);
+        let oid = Oid::hash_object(ObjectType::Blob, key.as_bytes()).ok()?;
+        Some(self.dir.join(format!("{}.json", oid)))
     }
 
-    // Also extract quoted terms (but skip CLI flags like --no-verify)
-    let quote_re = Regex::new(r#"["'`]([^"'`]+)["'`]"#).expect("Invalid regex");
-    for cap in quote_re.captures_iter(description) {
-        if let Some(quoted) = cap.get(1) {
-            let term = quoted.as_str();
-            // Skip CLI flags (start with - or --)
-            if term.starts_with('-') {
-                continue;
-            }
-            let term = term.to_lowercase();
-            if term.len() >= 3 && term.len() <= 50 {
-                keywords.insert(term);
-            }
-        }
+    /// Cached evidence for `entry`; unreadable entries are misses.
+    fn load(&self, entry: &ChangelogEntry) -> Option<EntryEvidence> {
+        let content = fs::read_to_string(self.entry_path(&entry.description)?).ok()?;
+        let mut evidence: EntryEvidence = serde_json::from_str(&content).ok()?;
+        (evidence.original_description == entry.description).then(|| {
+            evidence.category = entry.category.clone();
+            evidence
+        })
     }
 
-    // Extract technology/product names (capitalized words)
-    let tech_re =
-        Regex::new(r"\b([A-Z][a-zA-Z0-9]+(?:\s+[A-Z][a-zA-Z0-9]+)?)\b").expect("Invalid regex");
-    for cap in tech_re.captures_iter(description) {
-        if let Some(tech) = cap.get(1) {
-            let term = tech.as_str();
-            // Skip generic words
-            if ![
-                "Added", "Changed", "Fixed", "Removed", "Security", "The", "This", "With",
-            ]
-            .contains(&term)
+    /// Store `evidence`, dropping the evidence of other commits. Failures
+    /// only cost the next run a scan, so they are logged.
+    fn store(&self, evidence: &EntryEvidence) {
+        let Some(path) = self.entry_path(&evidence.original_description) else {
+            return;
+        };
+        if !self.dir.exists() {
+            if let Some(cache_dir) = self.dir.parent()
+                && let Ok(stale) = fs::read_dir(cache_dir)
             {
-                keywords.insert(term.to_lowercase());
+                for dir in stale.flatten() {
+                    let _ = fs::remove_dir_all(dir.path());
+                }
+            }
+            if let Err(e) = ensure_state_dir(&self.state_dir) {
+                debug!("Failed to create {}: {}", self.state_dir.display(), e);
+                return;
             }
         }
+        let result = serde_json::to_string(evidence)
+            .map_err(std::io::Error::other)
+            .and_then(|json| write_atomic(&path, json));
+        if let Err(e) = result {
+            debug!("Failed to cache evidence in {}: {}", path.display(), e);
+        }
     }
-
-    keywords.into_iter().collect()
-}
-
-/// Result of searching for a keyword.
-struct SearchResult {
-    files: Vec<String>,
-    /// Total occurrence count, or `None` if counting failed.
-    count: Option<usize>,
-    /// Sample lines, or `None` if sampling failed.
-    samples: Option<Vec<String>>,
 }
 
 /// Build a ripgrep command with standard arguments for keyword searching.
 ///
 /// Sets up `--ignore-case --fixed-strings`, code file type filters, and
-/// directory exclusion patterns. Callers can add additional arguments
-/// before executing.
-fn build_rg_keyword_command(keyword: &str, repo_path: &Path) -> Command {
+/// directory exclusion patterns, and limits the search to `scope`.
+/// Callers can add additional flags before executing.
+fn build_rg_keyword_command(keyword: &str, repo_path: &Path, scope: &SearchScope) -> Command {
     let mut cmd = Command::new("rg");
     cmd.args(["--ignore-case", "--fixed-strings"]);
-    cmd.args(RG_CODE_TYPE);
+    cmd.args(RG_CODE_TYPE.iter());
     cmd.args(RG_EXCLUDE_PATTERNS);
-    cmd.arg(keyword);
+    cmd.args(RG_PATH_SEPARATOR);
+    cmd.arg("-e").arg(keyword);
+    cmd.args(scope.paths());
     cmd.current_dir(repo_path);
     cmd
 }
@@ -414,29 +507,33 @@ fn build_rg_keyword_command(keyword: &str, repo_path: &Path) -> Command {
 fn search_keyword(
     keyword: &str,
     repo_path: &Path,
+    scope: &SearchScope,
+    cache: &mut ScanCache,
 ) -> Result<Option<SearchResult>, VerificationError> {
+    let key = search_key(keyword, scope);
+    if let Some(result) = cache.searches.get(&key) {
+        cache.hits += 1;
+        return Ok(result.clone());
+    }
+
     // Use ripgrep for fast searching
     // Use --fixed-strings to treat keyword as literal text, not regex
-    let files: Vec<String> =
-        match run_rg(build_rg_keyword_command(keyword, repo_path).arg("--files-with-matches"))? {
-            RgOutcome::Success(stdout) => stdout.lines().take(10).map(String::from).collect(),
-            RgOutcome::NoMatch => return Ok(None),
-        };
-
-    if files.is_empty() {
+    let Some(files) = cache.files_with_matches(keyword, repo_path, scope)? else {
+        cache.searches.insert(key, None);
         return Ok(None);
-    }
+    };
+    let files: Vec<String> = files.into_iter().take(10).collect();
 
     // Get sample lines with context (best-effort)
     let samples: Option<Vec<String>> = run_rg_or_warn(
-        build_rg_keyword_command(keyword, repo_path).args(["--max-count", "3", "-C", "1"]),
+        build_rg_keyword_command(keyword, repo_path, scope).args(["--max-count", "3", "-C", "1"]),
         &format!("samples for keyword '{}'", keyword),
     )
     .map(|stdout| stdout.lines().take(15).map(String::from).collect());
 
     // Count total occurrences (best-effort)
     let count: Option<usize> = run_rg_or_warn(
-        build_rg_keyword_command(keyword, repo_path).arg("--count-matches"),
+        build_rg_keyword_command(keyword, repo_path, scope).arg("--count-matches"),
         &format!("count for keyword '{}'", keyword),
     )
     .map(|stdout| {
@@ -446,11 +543,13 @@ fn search_keyword(
             .sum()
     });
 
-    Ok(Some(SearchResult {
+    let result = SearchResult {
         files,
         count,
         samples,
-    }))
+    };
+    cache.searches.insert(key, Some(result.clone()));
+    Ok(Some(result))
 }
 
 /// Find stub indicators near a keyword in the codebase.
@@ -461,71 +560,84 @@ fn search_keyword(
 fn find_stub_indicators_near_keyword(
     keyword: &str,
     repo_path: &Path,
+    scope: &SearchScope,
+    cache: &mut ScanCache,
 ) -> Result<Vec<StubIndicator>, VerificationError> {
     let mut indicators = Vec::new();
 
     // First, find files containing the keyword (only in source code files)
-    let mut cmd = Command::new("rg");
-    cmd.args(["--ignore-case", "--fixed-strings", "--files-with-matches"]);
-    cmd.args(RG_CODE_TYPE);
-    cmd.args(RG_EXCLUDE_PATTERNS);
-    cmd.arg(keyword);
-    cmd.current_dir(repo_path);
-
-    let files: Vec<String> = match run_rg(&mut cmd)? {
-        RgOutcome::Success(stdout) => stdout.lines().take(5).map(String::from).collect(),
-        RgOutcome::NoMatch => return Ok(indicators),
+    let Some(files) = cache.files_with_matches(keyword, repo_path, scope)? else {
+        return Ok(indicators);
     };
 
-    // Check each file for all stub patterns in a single rg call
-    // Using -e flag for multiple patterns reduces subprocess count by 12x
-    for file in &files {
-        let mut cmd = Command::new("rg");
-        cmd.args([
-            "--fixed-strings",
-            "--line-number",
-            "--max-count",
-            "36",
-            "--json",
-            "-C",
-            "1",
-        ]);
-
-        // Add each pattern with -e flag
-        for (pattern, _stub_type) in STUB_PATTERNS {
-            cmd.args(["-e", pattern]);
-        }
-
-        cmd.arg(file);
-        cmd.current_dir(repo_path);
-
-        match run_rg(&mut cmd)? {
-            RgOutcome::Success(stdout) => {
-                for line in stdout.lines() {
-                    if let Some((line_num, context)) = parse_rg_json_match(line) {
-                        // Determine which pattern matched by checking the context
-                        let indicator = STUB_PATTERNS
-                            .iter()
-                            .find(|(pattern, _)| context.contains(pattern))
-                            .map(|(_, stub_type)| *stub_type)
-                            .unwrap_or(StubType::Unknown);
-
-                        indicators.push(StubIndicator {
-                            file: file.clone(),
-                            line: line_num,
-                            indicator,
-                            context,
-                        });
-                    }
-                }
+    for file in files.iter().take(5) {
+        match cache.stubs.get(file) {
+            Some(stubs) => {
+                cache.hits += 1;
+                indicators.extend(stubs.iter().cloned());
+            }
+            None => {
+                let stubs = find_stub_indicators_in_file(file, repo_path)?;
+                indicators.extend(stubs.iter().cloned());
+                cache.stubs.insert(file.clone(), stubs);
             }
-            RgOutcome::NoMatch => {}
         }
     }
 
     Ok(indicators)
 }
 
+/// Stub indicators in one file.
+fn find_stub_indicators_in_file(
+    file: &str,
+    repo_path: &Path,
+) -> Result<Vec<StubIndicator>, VerificationError> {
+    // Check the file for all stub patterns in a single rg call
+    // Using -e flag for multiple patterns reduces subprocess count by 12x
+    let mut cmd = Command::new("rg");
+    cmd.args([
+        "--fixed-strings",
+        "--line-number",
+        "--max-count",
+        "36",
+        "--json",
+        "-C",
+        "1",
+    ]);
+
+    // Add each pattern with -e flag
+    for (pattern, _stub_type) in STUB_PATTERNS {
+        cmd.args(["-e", pattern]);
+    }
+
+    cmd.arg(file);
+    cmd.current_dir(repo_path);
+
+    let stdout = match run_rg(&mut cmd)? {
+        RgOutcome::Success(stdout) => stdout,
+        RgOutcome::NoMatch => return Ok(Vec::new()),
+    };
+    Ok(stdout
+        .lines()
+        .filter_map(parse_rg_json_match)
+        .map(|(line_num, context)| {
+            // Determine which pattern matched by checking the context
+            let indicator = STUB_PATTERNS
+                .iter()
+                .find(|(pattern, _)| context.contains(pattern))
+                .map(|(_, stub_type)| *stub_type)
+                .unwrap_or(StubType::Unknown);
+
+            StubIndicator {
+                file: file.to_string(),
+                line: line_num,
+                indicator,
+                context,
+            }
+        })
+        .collect())
+}
+
 /// Parse a ripgrep JSON output line into line number and content.
 fn parse_rg_json_match(line: &str) -> Option<(usize, String)> {
     let value: serde_json::Value = serde_json::from_str(line).ok()?;
@@ -696,6 +808,7 @@ fn search_and_count_array(
     let mut cmd = Command::new("rg");
     cmd.args(["--files-with-matches", "-g", file_glob]);
     cmd.args(RG_EXCLUDE_PATTERNS);
+    cmd.args(RG_PATH_SEPARATOR);
     cmd.arg(pattern);
     cmd.current_dir(repo_path);
 
@@ -902,16 +1015,8 @@ fn gather_key_files(repo_path: &Path) -> (Vec<KeyFileContent>, Vec<String>) {
         if path.exists() {
             match std::fs::read_to_string(&path) {
                 Ok(content) => {
-                    // Truncate large files (respecting UTF-8 character boundaries)
-                    let truncated = if content.len() > 5000 {
-                        let mut end = 5000;
-                        while end > 0 && !content.is_char_boundary(end) {
-                            end -= 1;
-                        }
-                        format!("{}...[truncated]", &content[..end])
-                    } else {
-                        content
-                    };
+                    // Truncate large files (respecting grapheme boundaries)
+                    let truncated = truncate_bytes_with_suffix(&content, 5000, "...[truncated]");
 
                     contents.push(KeyFileContent {
                         path: file.to_string(),
@@ -957,6 +1062,103 @@ mod tests {
         assert!(keywords.contains(&"application".to_string()));
     }
 
+    #[test]
+    fn test_scan_cache_answers_repeated_lookups() {
+        let mut cache = ScanCache::default();
+        let scope = SearchScope::repository();
+        // Any ripgrep call would fail in a directory that does not exist
+        let missing = Path::new("/nonexistent/keryx-scan-cache");
+        cache.files.insert(
+            search_key("Bybit", &scope),
+            Some(vec!["src/bybit.rs".to_string()]),
+        );
+        cache.stubs.insert(
+            "src/bybit.rs".to_string(),
+            vec![StubIndicator {
+                file: "src/bybit.rs".to_string(),
+                line: 3,
+                indicator: StubType::Todo,
+                context: "// TODO: reconnect".to_string(),
+            }],
+        );
+
+        let stubs =
+            find_stub_indicators_near_keyword("bybit", missing, &scope, &mut cache).unwrap();
+        assert_eq!(stubs.len(), 1);
+        assert_eq!(cache.hits, 2);
+        assert!(
+            cache
+                .files_with_matches("BYBIT", missing, &scope)
+                .unwrap()
+                .is_some()
+        );
+    }
+
+    #[test]
+    fn test_evidence_cache_follows_head_and_clean_tree() {
+        let dir = tempfile::TempDir::new().unwrap();
+        let repo = Repository::init(dir.path()).unwrap();
+        let commit = |message: &str| {
+            let mut index = repo.index().unwrap();
+            index
+                .add_all(["*"], git2::IndexAddOption::DEFAULT, None)
+                .unwrap();
+            index.write().unwrap();
+            let tree = repo.find_tree(index.write_tree().unwrap()).unwrap();
+            let sig = git2::Signature::now("Test", "test@example.com").unwrap();
+            let parent = repo.head().ok().map(|h| h.peel_to_commit().unwrap());
+            let parents: Vec<_> = parent.iter().collect();
+            repo.commit(Some("HEAD"), &sig, &sig, message, &tree, &parents)
+                .unwrap();
+        };
+        fs::write(dir.path().join("lib.rs"), "fn bybit() {}\n").unwrap();
+        commit("feat: bybit");
+
+        let scope = SearchScope::repository();
+        let entry = ChangelogEntry {
+            category: ChangelogCategory::Added,
+            description: "Add Bybit support".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
+        };
+        let evidence = EntryEvidence::new(
+            entry.description.clone(),
+            ChangelogCategory::Added,
+            Vec::new(),
+            Vec::new(),
+            Vec::new(),
+            ScanSummary::new(),
+        );
+
+        let cache = EvidenceCache::open(dir.path(), &scope).unwrap();
+        assert!(cache.load(&entry).is_none());
+        cache.store(&evidence);
+        let moved = ChangelogEntry {
+            category: ChangelogCategory::Changed,
+            ..entry.clone()
+        };
+        let cached = cache.load(&moved).unwrap();
+        assert_eq!(cached.original_description, "Add Bybit support");
+        assert_eq!(cached.category, ChangelogCategory::Changed);
+
+        // The cache directory itself is ignored, so the tree stays clean
+        let cache = EvidenceCache::open(dir.path(), &scope).unwrap();
+        assert!(cache.load(&entry).is_some());
+
+        fs::write(dir.path().join("lib.rs"), "fn bybit() { todo!() }\n").unwrap();
+        assert!(EvidenceCache::open(dir.path(), &scope).is_none());
+
+        commit("fix: bybit");
+        let cache = EvidenceCache::open(dir.path(), &scope).unwrap();
+        assert!(cache.load(&entry).is_none());
+        cache.store(&evidence);
+        let heads = fs::read_dir(dir.path().join(JOURNAL_DIR).join(EVIDENCE_CACHE_DIR))
+            .unwrap()
+            .count();
+        assert_eq!(heads, 1);
+    }
+
     #[test]
     fn test_count_array_elements() {
         let content = r#"
@@ -0,0 +1,269 @@
+//! Which paths verification searches.
+//!
+//! In a large repository, running ripgrep over the whole checkout for every
+//! keyword of every entry dominates verification time. The entries describe
+//! the commits in the range, so the evidence for them lives where those
+//! commits changed code. A [`SearchScope`] built from the range holds the
+//! parent directory of each changed file (its neighborhood: the tests, mod
+//! files and siblings next to it) plus changed files at the repository root.
+//! Keyword searches that find nothing in the scope are retried over the
+//! whole repository, so scoping never turns a real feature into a miss.
+
+use std::collections::BTreeSet;
+use std::env;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use git2::Repository;
+use tracing::{debug, warn};
+
+use crate::git::ParsedCommit;
+
+/// Environment variable selecting the search scope.
+const SCOPE_ENV_VAR: &str = "KERYX_VERIFY_SCOPE";
+
+/// Scopes with more paths than this search the whole repository instead:
+/// the range touched most of the tree, and the argument list would only
+/// slow ripgrep down.
+pub const MAX_SCOPE_PATHS: usize = 200;
+
+/// Whether verification searches the changed paths or the whole repository.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ScopeMode {
+    /// Paths touched by the commit range and their directories.
+    #[default]
+    Changed,
+    /// The whole repository (the historical behavior).
+    Repository,
+}
+
+impl FromStr for ScopeMode {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "changed" => Ok(ScopeMode::Changed),
+            "repo" | "repository" => Ok(ScopeMode::Repository),
+            _ => Err(format!(
+                "invalid verification scope '{}' (expected 'changed' or 'repo')",
+                s
+            )),
+        }
+    }
+}
+
+impl fmt::Display for ScopeMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ScopeMode::Changed => "changed",
+            ScopeMode::Repository => "repo",
+        })
+    }
+}
+
+impl ScopeMode {
+    /// Read `KERYX_VERIFY_SCOPE`.
+    ///
+    /// Logs a warning and uses the default if the variable is set but invalid.
+    pub fn from_env() -> Self {
+        match env::var(SCOPE_ENV_VAR) {
+            Ok(value) if !value.trim().is_empty() => value.parse().unwrap_or_else(|e| {
+                warn!("Ignoring {}: {}", SCOPE_ENV_VAR, e);
+                ScopeMode::default()
+            }),
+            _ => ScopeMode::default(),
+        }
+    }
+}
+
+/// Paths ripgrep searches, relative to the repository root.
+///
+/// An empty scope means the whole repository.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SearchScope {
+    paths: Vec<String>,
+}
+
+impl SearchScope {
+    /// Search the whole repository.
+    pub fn repository() -> Self {
+        Self::default()
+    }
+
+    /// The scope for `commits` under `mode` (see [`SearchScope::changed`]).
+    pub fn for_commits(repo: &Repository, commits: &[ParsedCommit], mode: ScopeMode) -> Self {
+        match mode {
+            ScopeMode::Changed => Self::changed(repo, commits),
+            ScopeMode::Repository => Self::repository(),
+        }
+    }
+
+    /// The neighborhood of the paths `commits` touched that still exist in
+    /// the working tree.
+    ///
+    /// Falls back to the whole repository when nothing usable was changed,
+    /// the repository is bare, or the scope would exceed [`MAX_SCOPE_PATHS`].
+    pub fn changed(repo: &Repository, commits: &[ParsedCommit]) -> Self {
+        let Some(workdir) = repo.workdir() else {
+            return Self::repository();
+        };
+        let scope = Self::from_changed_paths(changed_paths(repo, commits), workdir);
+        if scope.paths.len() > MAX_SCOPE_PATHS {
+            debug!(
+                "{} changed paths exceed the scope limit; searching the whole repository",
+                scope.paths.len()
+            );
+            return Self::repository();
+        }
+        scope
+    }
+
+    /// Build a scope from changed file paths: each file's parent directory,
+    /// or the file itself at the repository root, keeping only those present
+    /// under `workdir` and dropping paths inside another scoped directory.
+    pub fn from_changed_paths<I, S>(changed: I, workdir: &Path) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let candidates: BTreeSet<String> = changed
+            .into_iter()
+            .filter_map(|path| {
+                let path = path.as_ref().trim_matches('/');
+                match path.rsplit_once('/') {
+                    Some((parent, _)) => Some(parent.to_string()),
+                    None if !path.is_empty() => Some(path.to_string()),
+                    None => None,
+                }
+            })
+            .filter(|path| workdir.join(path).exists())
+            .collect();
+
+        // BTreeSet order puts a directory before everything beneath it
+        let mut paths: Vec<String> = Vec::new();
+        for path in candidates {
+            let nested = paths
+                .iter()
+                .any(|dir| path.starts_with(&format!("{}/", dir)));
+            if !nested {
+                paths.push(path);
+            }
+        }
+        Self { paths }
+    }
+
+    /// Whether this scope covers the whole repository.
+    pub fn is_repository(&self) -> bool {
+        self.paths.is_empty()
+    }
+
+    /// The scoped paths; empty for the whole repository.
+    pub fn paths(&self) -> &[String] {
+        &self.paths
+    }
+}
+
+/// Every path (old and new side) the commits changed against their first
+/// parent. Unreadable commits are skipped.
+pub fn changed_paths(repo: &Repository, commits: &[ParsedCommit]) -> BTreeSet<String> {
+    let mut paths = BTreeSet::new();
+    for commit in commits {
+        let Some(diff) = super::missed::commit_diff(repo, &commit.hash) else {
+            debug!("Skipping unreadable commit {} in search scope", commit.hash);
+            continue;
+        };
+        for delta in diff.deltas() {
+            for file in [delta.old_file(), delta.new_file()] {
+                if let Some(path) = file.path().and_then(|p| p.to_str()) {
+                    paths.insert(path.to_string());
+                }
+            }
+        }
+    }
+    paths
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serial_test::serial;
+    use std::fs;
+    use tempfile::TempDir;
+
+    fn workdir(paths: &[&str]) -> TempDir {
+        let dir = TempDir::new().unwrap();
+        for path in paths {
+            let full = dir.path().join(path);
+            fs::create_dir_all(full.parent().unwrap()).unwrap();
+            fs::write(full, "").unwrap();
+        }
+        dir
+    }
+
+    #[test]
+    fn test_scope_uses_parent_directories() {
+        let dir = workdir(&[
+            "src/cli/args.rs",
+            "src/cli/mod.rs",
+            "src/lib.rs",
+            "build.rs",
+        ]);
+        let scope = SearchScope::from_changed_paths(
+            ["src/cli/args.rs", "src/cli/mod.rs", "build.rs"],
+            dir.path(),
+        );
+        assert_eq!(scope.paths(), ["build.rs", "src/cli"]);
+    }
+
+    #[test]
+    fn test_scope_collapses_nested_directories() {
+        let dir = workdir(&["src/lib.rs", "src/cli/args.rs", "src/cli/sub/deep.rs"]);
+        let scope = SearchScope::from_changed_paths(
+            ["src/cli/sub/deep.rs", "src/lib.rs", "src/cli/args.rs"],
+            dir.path(),
+        );
+        assert_eq!(scope.paths(), ["src"]);
+    }
+
+    #[test]
+    fn test_scope_skips_missing_paths() {
+        let dir = workdir(&["src/lib.rs"]);
+        let scope = SearchScope::from_changed_paths(["old/gone.rs", "src/lib.rs"], dir.path());
+        assert_eq!(scope.paths(), ["src"]);
+
+        let scope = SearchScope::from_changed_paths(["old/gone.rs"], dir.path());
+        assert!(scope.is_repository());
+    }
+
+    #[test]
+    fn test_scope_does_not_collapse_sibling_prefixes() {
+        let dir = workdir(&["src/a.rs", "src-extra/b.rs", "src/cli/c.rs"]);
+        let scope = SearchScope::from_changed_paths(
+            ["src/a.rs", "src-extra/b.rs", "src/cli/c.rs"],
+            dir.path(),
+        );
+        assert_eq!(scope.paths(), ["src", "src-extra"]);
+    }
+
+    #[test]
+    fn test_parse_scope_mode() {
+        assert_eq!("Changed".parse::<ScopeMode>(), Ok(ScopeMode::Changed));
+        assert_eq!("repository".parse::<ScopeMode>(), Ok(ScopeMode::Repository));
+        assert!("nearby".parse::<ScopeMode>().is_err());
+    }
+
+    #[test]
+    #[serial]
+    fn test_scope_mode_from_env() {
+        temp_env::with_var(SCOPE_ENV_VAR, Some("repo"), || {
+            assert_eq!(ScopeMode::from_env(), ScopeMode::Repository);
+        });
+        temp_env::with_var(SCOPE_ENV_VAR, Some("everywhere"), || {
+            assert_eq!(ScopeMode::from_env(), ScopeMode::Changed);
+        });
+        temp_env::with_var(SCOPE_ENV_VAR, None::<&str>, || {
+            assert_eq!(ScopeMode::from_env(), ScopeMode::Changed);
+        });
+    }
+}
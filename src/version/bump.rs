@@ -68,6 +68,7 @@ pub fn determine_bump_type(commits: &[ParsedCommit]) -> BumpType {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::git::Footers;
     use chrono::Utc;
 
     fn make_commit(commit_type: Option<CommitType>, breaking: bool) -> ParsedCommit {
@@ -78,6 +79,7 @@ mod tests {
             scope: None,
             breaking,
             timestamp: Utc::now(),
+            footers: Footers::default(),
         }
     }
 
@@ -0,0 +1,206 @@
+//! Semver calculation from the `[Unreleased]` section of a changelog.
+//!
+//! For teams that curate `[Unreleased]` by hand, the categories of its
+//! entries decide the bump instead of the commits:
+//! - a breaking marker (`BREAKING` in an entry, or a "Breaking" category
+//!   heading) = major bump
+//! - Added, Changed, Deprecated or Removed entries = minor bump
+//! - anything else (Fixed, Security, uncategorized) = patch bump
+//!
+//! Headings that are category aliases ("Features", "Bug Fixes") count as
+//! the category they stand for.
+
+use semver::Version;
+
+use super::bump::{BumpType, apply_bump_to_version};
+use crate::changelog::ChangelogCategory;
+use crate::changelog::aliases::CategoryAliases;
+use crate::changelog::line_ending::normalize;
+
+/// The bump the `[Unreleased]` entries call for.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnreleasedBump {
+    pub bump: BumpType,
+    /// Number of entries in `[Unreleased]`.
+    pub entries: usize,
+    /// Entry (prefixed with its category) that decided the bump.
+    pub reason: String,
+}
+
+impl UnreleasedBump {
+    /// The version after `base`, the latest released version if any.
+    pub fn next_version(&self, base: Option<&Version>) -> Version {
+        apply_bump_to_version(base, self.bump)
+    }
+}
+
+/// Bump for an entry under `category` (a `### ` heading).
+fn category_bump(category: Option<&str>, aliases: &CategoryAliases) -> BumpType {
+    let Some(category) = category else {
+        return BumpType::Patch;
+    };
+    if category.to_lowercase().contains("breaking") {
+        return BumpType::Major;
+    }
+    match aliases.resolve(category) {
+        Some(
+            ChangelogCategory::Added
+            | ChangelogCategory::Changed
+            | ChangelogCategory::Deprecated
+            | ChangelogCategory::Removed,
+        ) => BumpType::Minor,
+        _ => BumpType::Patch,
+    }
+}
+
+/// Whether an entry is marked as breaking (`**BREAKING**`, `BREAKING:`,
+/// `[BREAKING]`, `BREAKING CHANGE: ...`). Only the uppercase word counts, so
+/// "no longer breaking on empty input" stays a regular entry.
+pub(crate) fn is_breaking(description: &str) -> bool {
+    description
+        .split(|c: char| !c.is_ascii_alphabetic())
+        .any(|word| word == "BREAKING")
+}
+
+/// Determine the bump from the `[Unreleased]` section of changelog
+/// `content`, whose category headings may be `aliases`. `None` when there is
+/// no such section or it has no entries.
+pub fn determine_unreleased_bump(
+    content: &str,
+    aliases: &CategoryAliases,
+) -> Option<UnreleasedBump> {
+    let content = normalize(content);
+    let mut in_unreleased = false;
+    let mut category: Option<String> = None;
+    let mut result: Option<UnreleasedBump> = None;
+
+    for line in content.lines() {
+        if let Some(heading) = line.strip_prefix("## ") {
+            if in_unreleased {
+                break;
+            }
+            let label = heading.trim().trim_start_matches('[');
+            in_unreleased = label.to_lowercase().starts_with("unreleased");
+            continue;
+        }
+        if !in_unreleased {
+            continue;
+        }
+        if let Some(heading) = line.strip_prefix("### ") {
+            category = Some(heading.trim().to_string());
+            continue;
+        }
+        let Some(text) = ["- ", "* "]
+            .iter()
+            .find_map(|b| line.strip_prefix(b))
+            .map(str::trim)
+            .filter(|text| !text.is_empty())
+        else {
+            continue;
+        };
+
+        let bump = if is_breaking(text) {
+            BumpType::Major
+        } else {
+            category_bump(category.as_deref(), aliases)
+        };
+        let reason = match &category {
+            Some(category) => format!("{}: {}", category, text),
+            None => text.to_string(),
+        };
+        match &mut result {
+            Some(current) => {
+                current.entries += 1;
+                if bump > current.bump {
+                    current.bump = bump;
+                    current.reason = reason;
+                }
+            }
+            None => {
+                result = Some(UnreleasedBump {
+                    bump,
+                    entries: 1,
+                    reason,
+                })
+            }
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADER: &str = "# Changelog\n\n";
+
+    fn bump(unreleased: &str) -> Option<UnreleasedBump> {
+        determine_unreleased_bump(
+            &format!(
+                "{}## [Unreleased]\n\n{}\n## [1.2.3] - 2024-01-01\n\n### Added\n\n- Old feature\n",
+                HEADER, unreleased
+            ),
+            &CategoryAliases::default(),
+        )
+    }
+
+    #[test]
+    fn test_fixed_is_patch() {
+        let result = bump("### Fixed\n\n- Crash on startup\n").unwrap();
+        assert_eq!(result.bump, BumpType::Patch);
+        assert_eq!(
+            result.next_version(Some(&Version::new(1, 2, 3))),
+            Version::new(1, 2, 4)
+        );
+    }
+
+    #[test]
+    fn test_added_is_minor_and_highest_wins() {
+        let result = bump("### Added\n\n- Dark mode\n\n### Fixed\n\n- Crash on startup\n").unwrap();
+        assert_eq!(result.bump, BumpType::Minor);
+        assert_eq!(result.entries, 2);
+        assert_eq!(result.reason, "Added: Dark mode");
+    }
+
+    #[test]
+    fn test_alias_headings() {
+        let result = bump("### Features\n\n- Dark mode\n").unwrap();
+        assert_eq!(result.bump, BumpType::Minor);
+        assert_eq!(result.reason, "Features: Dark mode");
+        let result = bump("### Bug Fixes\n\n- Crash on startup\n").unwrap();
+        assert_eq!(result.bump, BumpType::Patch);
+    }
+
+    #[test]
+    fn test_breaking_marker_is_major() {
+        let result = bump("### Changed\n\n- **BREAKING**: Config moved to keryx.toml\n").unwrap();
+        assert_eq!(result.bump, BumpType::Major);
+        let result = bump("### Breaking Changes\n\n- Dropped Node 16\n").unwrap();
+        assert_eq!(result.bump, BumpType::Major);
+        let result = bump("### Fixed\n\n- No longer breaking on empty input\n").unwrap();
+        assert_eq!(result.bump, BumpType::Patch);
+    }
+
+    #[test]
+    fn test_empty_or_missing_unreleased() {
+        assert_eq!(bump(""), None);
+        assert_eq!(
+            determine_unreleased_bump(
+                "# Changelog\n\n## [1.0.0] - 2024-01-01\n\n- Thing\n",
+                &CategoryAliases::default()
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn test_first_release() {
+        let result = determine_unreleased_bump(
+            "# Changelog\r\n\r\n## Unreleased\r\n\r\n* Initial API\r\n",
+            &CategoryAliases::default(),
+        )
+        .unwrap();
+        assert_eq!(result.bump, BumpType::Patch);
+        assert_eq!(result.next_version(None), Version::new(0, 0, 1));
+    }
+}
@@ -12,7 +12,9 @@ use crate::git::ParsedCommit;
 use crate::github::PullRequest;
 use crate::llm::LlmRouter;
 use crate::llm::extract_json;
-use crate::llm::prompt::sanitize_for_prompt;
+use crate::llm::prompt::{UNTRUSTED_INPUT_RULE, sanitize_for_prompt, untrusted_block};
+use crate::llm::request::{LlmRequest, LlmResponse, LlmTask};
+use crate::text::truncate_bytes;
 use crate::version::bump::{BumpType, apply_bump_to_version, determine_bump_type};
 
 /// Input for LLM-based version bump determination.
@@ -90,18 +92,7 @@ fn build_version_bump_prompt(
             let body_snippet = pr
                 .body
                 .as_deref()
-                .map(|b| {
-                    let truncated = if b.len() > 500 {
-                        let mut end = 500;
-                        while end > 0 && !b.is_char_boundary(end) {
-                            end -= 1;
-                        }
-                        &b[..end]
-                    } else {
-                        b
-                    };
-                    sanitize_for_prompt(truncated)
-                })
+                .map(|b| sanitize_for_prompt(truncate_bytes(b, 500)))
                 .unwrap_or_default();
             sanitize_for_prompt(&format!(
                 "PR #{}: {} - {}",
@@ -134,17 +125,19 @@ fn build_version_bump_prompt(
 {prs}
 
 ## Instructions
+{UNTRUSTED_INPUT_RULE}
+
 Analyze the commits and PRs above. Determine whether this release warrants a **major**, **minor**, or **patch** bump.
 
 Respond with JSON only (no markdown wrapping):
 {{"bump_type": "major|minor|patch", "reasoning": "brief explanation"}}"#,
         repo = sanitized_repo,
         version_context = version_context,
-        commits = sanitized_commits.join("\n"),
+        commits = untrusted_block(&sanitized_commits.join("\n")),
         prs = if sanitized_prs.is_empty() {
             "(none)".to_string()
         } else {
-            sanitized_prs.join("\n")
+            untrusted_block(&sanitized_prs.join("\n"))
         },
     ))
 }
@@ -195,7 +188,7 @@ pub async fn determine_version_with_llm(
         }
     };
 
-    let response = match llm.generate_raw(&prompt).await {
+    let (bump, reasoning) = match llm.request(&BumpRequest { prompt }).await {
         Ok(completion) => {
             if let Some(ref primary_err) = completion.primary_error {
                 warn!(
@@ -222,7 +215,7 @@ pub async fn determine_version_with_llm(
         Err(e) => {
             warn!(
                 "LLM version bump failed: {}. Using algorithmic bump.",
-                e.summary()
+                e.detailed()
             );
             eprintln!();
             eprintln!("\x1b[33m⚠ LLM version bump failed, using algorithmic bump\x1b[0m");
@@ -232,23 +225,29 @@ pub async fn determine_version_with_llm(
         }
     };
 
-    match parse_version_bump_response(&response) {
-        Some((bump, reasoning)) => {
-            debug!("LLM version bump: {:?} — {}", bump, reasoning);
-            (bump, Some(reasoning))
-        }
-        None => {
-            warn!(
-                "Could not parse LLM version bump response. Using algorithmic bump. Response: {}",
-                response.chars().take(200).collect::<String>()
-            );
-            eprintln!();
-            eprintln!(
-                "\x1b[33m⚠ Could not parse LLM version bump response, using algorithmic bump\x1b[0m"
-            );
-            eprintln!();
-            (algorithmic_bump, None)
-        }
+    debug!("LLM version bump: {:?} — {}", bump, reasoning);
+    (bump, Some(reasoning))
+}
+
+/// The bump and its reasoning, from the JSON the bump prompt asks for.
+struct BumpRequest {
+    prompt: String,
+}
+
+impl LlmRequest for BumpRequest {
+    type Output = (BumpType, String);
+
+    fn task(&self) -> LlmTask {
+        LlmTask::Bump
+    }
+
+    fn prompt(&self) -> &str {
+        &self.prompt
+    }
+
+    fn parse(&self, response: LlmResponse) -> Result<(BumpType, String), String> {
+        parse_version_bump_response(&response.into_text())
+            .ok_or_else(|| "no bump_type JSON object".to_string())
     }
 }
 
@@ -347,7 +346,7 @@ mod tests {
 
     #[test]
     fn test_build_prompt_with_previous_version() {
-        use crate::git::CommitType;
+        use crate::git::{CommitType, Footers};
         use chrono::Utc;
 
         let commits = vec![ParsedCommit {
@@ -357,6 +356,7 @@ mod tests {
             scope: None,
             breaking: false,
             timestamp: Utc::now(),
+            footers: Footers::default(),
         }];
 
         let input = VersionBumpInput {
@@ -453,7 +453,7 @@ mod tests {
 
     #[test]
     fn test_build_prompt_with_populated_prs() {
-        use crate::git::CommitType;
+        use crate::git::{CommitType, Footers};
         use chrono::Utc;
 
         let commits = vec![ParsedCommit {
@@ -463,6 +463,7 @@ mod tests {
             scope: None,
             breaking: false,
             timestamp: Utc::now(),
+            footers: Footers::default(),
         }];
 
         let prs = vec![
@@ -1,7 +1,9 @@
 //! Version management and semver bumping.
 
 pub mod bump;
+pub mod changelog_bump;
 pub mod llm_bump;
 
 pub use bump::{BumpType, apply_bump_to_version, calculate_next_version, determine_bump_type};
+pub use changelog_bump::{UnreleasedBump, determine_unreleased_bump};
 pub use llm_bump::{VersionBumpInput, calculate_next_version_with_llm};
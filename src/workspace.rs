@@ -0,0 +1,282 @@
+//! Workspace (monorepo) detection.
+//!
+//! Reads the member declarations of the workspace tools a project uses:
+//! `[workspace] members` of a root `Cargo.toml`, `packages` of
+//! `pnpm-workspace.yaml`, `workspaces` of a root `package.json` (npm and
+//! yarn) and `packages` of `lerna.json`. Members are directory patterns
+//! relative to the project root where `*` matches within one directory and
+//! `**` any number of them; a `!` prefix (pnpm, npm) or the Cargo `exclude`
+//! list removes directories again.
+
+use std::fmt;
+use std::path::{Component, Path};
+
+use tracing::warn;
+
+use crate::ship::assets::wildcard_match;
+
+/// Lerna's member patterns when `lerna.json` lists none.
+const LERNA_DEFAULT_PACKAGES: &str = "packages/*";
+
+/// Reads the workspace declared in one file's content, if any.
+type WorkspaceReader = fn(&str) -> Result<Option<Workspace>, String>;
+
+/// The tool a workspace is declared for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WorkspaceKind {
+    Cargo,
+    Pnpm,
+    /// `workspaces` in `package.json`, used by npm and yarn.
+    Npm,
+    Lerna,
+}
+
+impl fmt::Display for WorkspaceKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            WorkspaceKind::Cargo => write!(f, "Cargo workspace"),
+            WorkspaceKind::Pnpm => write!(f, "pnpm workspace"),
+            WorkspaceKind::Npm => write!(f, "npm/yarn workspaces"),
+            WorkspaceKind::Lerna => write!(f, "lerna"),
+        }
+    }
+}
+
+/// A detected workspace and its member patterns.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Workspace {
+    pub kind: WorkspaceKind,
+    /// Member directory patterns, relative to the project root.
+    pub members: Vec<String>,
+    /// Directory patterns excluded from the members.
+    pub exclude: Vec<String>,
+}
+
+impl Workspace {
+    /// Whether the directory `dir` (relative to the project root) is a
+    /// member of the workspace.
+    pub fn contains(&self, dir: &Path) -> bool {
+        self.members.iter().any(|pattern| dir_matches(pattern, dir))
+            && !self.exclude.iter().any(|pattern| dir_matches(pattern, dir))
+    }
+}
+
+/// Every workspace declared at `root`, in the order Cargo, pnpm, npm/yarn,
+/// lerna. Files that cannot be parsed are logged and skipped.
+pub fn detect_workspaces(root: &Path) -> Vec<Workspace> {
+    let readers: [(&str, WorkspaceReader); 4] = [
+        ("Cargo.toml", cargo_workspace),
+        ("pnpm-workspace.yaml", pnpm_workspace),
+        ("package.json", npm_workspace),
+        ("lerna.json", lerna_workspace),
+    ];
+    readers
+        .into_iter()
+        .filter_map(|(file, read)| {
+            let content = std::fs::read_to_string(root.join(file)).ok()?;
+            read(&content)
+                .map_err(|e| warn!("Ignoring workspace declaration in {}: {}", file, e))
+                .ok()
+                .flatten()
+        })
+        .collect()
+}
+
+fn cargo_workspace(content: &str) -> Result<Option<Workspace>, String> {
+    let doc = content
+        .parse::<toml_edit::DocumentMut>()
+        .map_err(|e| e.to_string())?;
+    let Some(workspace) = doc.get("workspace") else {
+        return Ok(None);
+    };
+    let strings = |key: &str| -> Vec<String> {
+        workspace
+            .get(key)
+            .and_then(|v| v.as_array())
+            .map(|array| {
+                array
+                    .iter()
+                    .filter_map(|v| v.as_str())
+                    .map(String::from)
+                    .collect()
+            })
+            .unwrap_or_default()
+    };
+    Ok(Some(Workspace {
+        kind: WorkspaceKind::Cargo,
+        members: strings("members"),
+        exclude: strings("exclude"),
+    }))
+}
+
+/// `pnpm-workspace.yaml` holds a `packages:` list of quoted or plain
+/// patterns; only that list is read.
+fn pnpm_workspace(content: &str) -> Result<Option<Workspace>, String> {
+    let mut in_packages = false;
+    let mut patterns = Vec::new();
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        if !line.starts_with([' ', '\t', '-']) {
+            in_packages = trimmed == "packages:";
+            continue;
+        }
+        if in_packages && let Some(item) = trimmed.strip_prefix('-') {
+            let item = item.split(" #").next().unwrap_or_default().trim();
+            patterns.push(item.trim_matches(['\'', '"']).to_string());
+        }
+    }
+    Ok(Some(split_negated(WorkspaceKind::Pnpm, patterns)))
+}
+
+fn npm_workspace(content: &str) -> Result<Option<Workspace>, String> {
+    let json: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    // An array, or yarn's `{ "packages": [...] }`
+    let declared = match json.get("workspaces") {
+        Some(serde_json::Value::Object(map)) => map.get("packages"),
+        other => other,
+    };
+    Ok(declared
+        .and_then(|v| v.as_array())
+        .map(|array| string_items(array))
+        .map(|patterns| split_negated(WorkspaceKind::Npm, patterns)))
+}
+
+fn lerna_workspace(content: &str) -> Result<Option<Workspace>, String> {
+    let json: serde_json::Value = serde_json::from_str(content).map_err(|e| e.to_string())?;
+    let patterns = json
+        .get("packages")
+        .and_then(|v| v.as_array())
+        .map(|array| string_items(array))
+        .unwrap_or_else(|| vec![LERNA_DEFAULT_PACKAGES.to_string()]);
+    Ok(Some(split_negated(WorkspaceKind::Lerna, patterns)))
+}
+
+fn string_items(array: &[serde_json::Value]) -> Vec<String> {
+    array
+        .iter()
+        .filter_map(|v| v.as_str())
+        .map(String::from)
+        .collect()
+}
+
+/// Split `!pattern` exclusions from the member patterns.
+fn split_negated(kind: WorkspaceKind, patterns: Vec<String>) -> Workspace {
+    let (exclude, members): (Vec<String>, Vec<String>) =
+        patterns.into_iter().partition(|p| p.starts_with('!'));
+    Workspace {
+        kind,
+        members,
+        exclude: exclude
+            .into_iter()
+            .map(|p| p.trim_start_matches('!').to_string())
+            .collect(),
+    }
+}
+
+/// Whether the relative directory `dir` matches the member `pattern`.
+pub fn dir_matches(pattern: &str, dir: &Path) -> bool {
+    let pattern = pattern.replace('\\', "/");
+    let pattern: Vec<&str> = pattern
+        .split('/')
+        .filter(|s| !s.is_empty() && *s != ".")
+        .collect();
+    let dir: Vec<&str> = dir
+        .components()
+        .filter_map(|c| match c {
+            Component::Normal(name) => name.to_str(),
+            _ => None,
+        })
+        .collect();
+    segments_match(&pattern, &dir)
+}
+
+fn segments_match(pattern: &[&str], dir: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => dir.is_empty(),
+        Some((&"**", rest)) => (0..=dir.len()).any(|skip| segments_match(rest, &dir[skip..])),
+        Some((segment, rest)) => match dir.split_first() {
+            Some((name, dir_rest)) => {
+                wildcard_match(segment, name) && segments_match(rest, dir_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tempfile::TempDir;
+
+    #[test]
+    fn test_dir_matches() {
+        assert!(dir_matches("crates/*", Path::new("crates/core")));
+        assert!(dir_matches("./crates/core/", Path::new("crates/core")));
+        assert!(!dir_matches("crates/*", Path::new("crates/core/macros")));
+        assert!(dir_matches("packages/**", Path::new("packages/a/b")));
+        assert!(dir_matches("apps/*-web", Path::new("apps/admin-web")));
+        assert!(!dir_matches("crates/*", Path::new("tools/xtask")));
+    }
+
+    #[test]
+    fn test_detects_each_workspace_kind() {
+        let dir = TempDir::new().unwrap();
+        let root = dir.path();
+        std::fs::write(
+            root.join("Cargo.toml"),
+            "[workspace]\nmembers = [\"crates/*\"]\nexclude = [\"crates/legacy\"]\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("pnpm-workspace.yaml"),
+            "packages:\n  - 'apps/*'\n  - \"!apps/sandbox\" # scratch\ncatalog:\n  - ignored\n",
+        )
+        .unwrap();
+        std::fs::write(
+            root.join("package.json"),
+            r#"{"name": "root", "workspaces": {"packages": ["web/*"]}}"#,
+        )
+        .unwrap();
+        std::fs::write(root.join("lerna.json"), r#"{"version": "1.0.0"}"#).unwrap();
+
+        let workspaces = detect_workspaces(root);
+        let kinds: Vec<WorkspaceKind> = workspaces.iter().map(|w| w.kind).collect();
+        assert_eq!(
+            kinds,
+            vec![
+                WorkspaceKind::Cargo,
+                WorkspaceKind::Pnpm,
+                WorkspaceKind::Npm,
+                WorkspaceKind::Lerna
+            ]
+        );
+
+        let cargo = &workspaces[0];
+        assert!(cargo.contains(Path::new("crates/core")));
+        assert!(!cargo.contains(Path::new("crates/legacy")));
+        let pnpm = &workspaces[1];
+        assert_eq!(pnpm.members, vec!["apps/*"]);
+        assert!(!pnpm.contains(Path::new("apps/sandbox")));
+        assert_eq!(workspaces[2].members, vec!["web/*"]);
+        assert!(workspaces[3].contains(Path::new("packages/cli")));
+    }
+
+    #[test]
+    fn test_no_workspace_declared() {
+        let dir = TempDir::new().unwrap();
+        std::fs::write(
+            dir.path().join("Cargo.toml"),
+            "[package]\nname = \"single\"\nversion = \"0.1.0\"\n",
+        )
+        .unwrap();
+        std::fs::write(
+            dir.path().join("package.json"),
+            r#"{"name": "single", "version": "0.1.0"}"#,
+        )
+        .unwrap();
+        assert!(detect_workspaces(dir.path()).is_empty());
+    }
+}
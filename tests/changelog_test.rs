@@ -87,12 +87,19 @@ fn test_write_changelog_creates_new_file() {
             ChangelogEntry {
                 category: ChangelogCategory::Added,
                 description: "New feature".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
             },
             ChangelogEntry {
                 category: ChangelogCategory::Fixed,
                 description: "Bug fix".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
             },
         ],
+        highlights: None,
     };
 
     write_changelog(&output_path, &entries, &Version::new(1, 0, 0)).unwrap();
@@ -122,7 +129,11 @@ fn test_write_changelog_creates_backup() {
         entries: vec![ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "New in 2.0".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         }],
+        highlights: None,
     };
 
     write_changelog(&output_path, &entries, &Version::new(2, 0, 0)).unwrap();
@@ -152,7 +163,11 @@ fn test_write_changelog_inserts_before_existing_versions() {
         entries: vec![ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "Feature in 3.0".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         }],
+        highlights: None,
     };
 
     write_changelog(&output_path, &entries, &Version::new(3, 0, 0)).unwrap();
@@ -190,7 +205,11 @@ mod write_failure_tests {
             entries: vec![ChangelogEntry {
                 category: ChangelogCategory::Added,
                 description: "New feature".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
             }],
+            highlights: None,
         };
 
         let result = write_changelog(&output_path, &entries, &Version::new(1, 0, 0));
@@ -224,7 +243,11 @@ mod write_failure_tests {
             entries: vec![ChangelogEntry {
                 category: ChangelogCategory::Added,
                 description: "New in 2.0".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
             }],
+            highlights: None,
         };
 
         let result = write_changelog(&output_path, &entries, &Version::new(2, 0, 0));
@@ -265,7 +288,11 @@ mod write_failure_tests {
             entries: vec![ChangelogEntry {
                 category: ChangelogCategory::Added,
                 description: "New in 2.0".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
             }],
+            highlights: None,
         };
 
         let result = write_changelog(&output_path, &entries, &Version::new(2, 0, 0));
@@ -297,7 +324,11 @@ mod write_failure_tests {
             entries: vec![ChangelogEntry {
                 category: ChangelogCategory::Added,
                 description: "Test".to_string(),
+                sources: Vec::new(),
+                scope: None,
+                importance: None,
             }],
+            highlights: None,
         };
 
         let result = write_changelog(&output_path, &entries, &Version::new(1, 0, 0));
@@ -354,7 +385,11 @@ fn test_write_changelog_with_crlf_line_endings() {
         entries: vec![ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "New in 2.0".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         }],
+        highlights: None,
     };
 
     write_changelog(&output_path, &entries, &Version::new(2, 0, 0)).unwrap();
@@ -385,7 +420,11 @@ fn test_write_changelog_with_crlf_and_unreleased() {
         entries: vec![ChangelogEntry {
             category: ChangelogCategory::Fixed,
             description: "Bug fix in 2.0".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         }],
+        highlights: None,
     };
 
     write_changelog(&output_path, &entries, &Version::new(2, 0, 0)).unwrap();
@@ -6,7 +6,7 @@
 mod common;
 
 use common::TestRepo;
-use keryx::git::fetch_commits;
+use keryx::git::{fetch_commits, walk_commits};
 
 // =============================================================================
 // BASIC FUNCTIONALITY TESTS
@@ -324,3 +324,49 @@ fn test_fetch_commits_returns_full_hashes() {
     // Hash should match the commit OID
     assert_eq!(commits[0].hash, commit2.to_string());
 }
+
+// =============================================================================
+// STREAMING TESTS
+// =============================================================================
+
+#[test]
+fn test_walk_commits_matches_fetch_commits() {
+    let test_repo = TestRepo::new();
+
+    let first = test_repo.commit("feat: first");
+    test_repo.commit("fix: second");
+    let last = test_repo.commit("docs: third");
+
+    let fetched =
+        fetch_commits(&test_repo.repo, first, last, false).expect("Failed to fetch commits");
+    let walked: Vec<_> = walk_commits(&test_repo.repo, first, last, false)
+        .expect("Failed to start walk")
+        .collect::<Result<_, _>>()
+        .expect("Failed to walk commits");
+
+    let fetched_hashes: Vec<_> = fetched.iter().map(|c| &c.hash).collect();
+    let walked_hashes: Vec<_> = walked.iter().map(|c| &c.hash).collect();
+    assert_eq!(fetched_hashes, walked_hashes);
+}
+
+#[test]
+fn test_walk_commits_is_lazy() {
+    let test_repo = TestRepo::new();
+
+    let first = test_repo.commit("feat: first");
+    let mut last = first;
+    for i in 0..20 {
+        last = test_repo.commit(&format!("fix: commit {}", i));
+    }
+
+    // Taking only the newest commits stops the walk early
+    let newest: Vec<_> = walk_commits(&test_repo.repo, first, last, false)
+        .expect("Failed to start walk")
+        .take(2)
+        .collect::<Result<_, _>>()
+        .expect("Failed to walk commits");
+
+    assert_eq!(newest.len(), 2);
+    assert_eq!(newest[0].hash, last.to_string());
+    assert_eq!(newest[0].subject(), "fix: commit 19");
+}
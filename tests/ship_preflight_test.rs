@@ -7,7 +7,8 @@ use serial_test::serial;
 
 use keryx::ShipError;
 use keryx::llm::ProviderSelection;
-use keryx::ship::preflight::run_checks;
+use keryx::ship::executor;
+use keryx::ship::preflight::{PreflightOverrides, RemoteSync, run_checks};
 
 use common::TestRepo;
 
@@ -73,8 +74,15 @@ fn test_preflight_single_commit_initial_repo() {
         &format!("HEAD:refs/heads/{}", branch),
     ]);
 
-    let result = run_checks(&repo.repo, false, ProviderSelection::default(), false)
-        .expect("preflight should succeed for single-commit repo");
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        PreflightOverrides::default(),
+        &[],
+        false,
+    )
+    .expect("preflight should succeed for single-commit repo");
 
     assert!(result.latest_tag.is_none());
     assert_eq!(result.commits_since_tag.len(), 1);
@@ -115,8 +123,15 @@ fn test_preflight_multi_commit_initial_repo_includes_root() {
         &format!("HEAD:refs/heads/{}", branch),
     ]);
 
-    let result = run_checks(&repo.repo, false, ProviderSelection::default(), false)
-        .expect("preflight should succeed for multi-commit repo");
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        PreflightOverrides::default(),
+        &[],
+        false,
+    )
+    .expect("preflight should succeed for multi-commit repo");
 
     assert!(result.latest_tag.is_none());
     assert_eq!(result.commits_since_tag.len(), 2);
@@ -152,7 +167,14 @@ fn test_preflight_fails_on_detached_head() {
     std::env::set_current_dir(repo.dir.path()).expect("Failed to change to repo dir");
     let _guard = DirGuard::new(original_dir);
 
-    let result = run_checks(&repo.repo, false, ProviderSelection::default(), false);
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        PreflightOverrides::default(),
+        &[],
+        false,
+    );
     assert!(matches!(result, Err(ShipError::DetachedHead)));
 }
 
@@ -200,8 +222,91 @@ fn test_preflight_respects_tracked_upstream_when_branch_names_differ() {
     run_git(&["push", "origin", "HEAD:refs/heads/main"]);
     run_git(&["switch", "release"]);
 
-    let result = run_checks(&repo.repo, false, ProviderSelection::default(), false);
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        PreflightOverrides::default(),
+        &[],
+        false,
+    );
     assert!(matches!(result, Err(ShipError::BehindRemote)));
+
+    let overrides = PreflightOverrides {
+        allow_behind: true,
+        ..Default::default()
+    };
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        overrides,
+        &[],
+        false,
+    )
+    .expect("--allow-behind should downgrade the failure");
+    assert_eq!(result.remote_sync, RemoteSync::Behind);
+    assert_eq!(result.upstream_branch, "main");
+}
+
+#[test]
+#[serial]
+fn test_preflight_overrides_dirty_tree_and_missing_upstream() {
+    let repo = TestRepo::new();
+    repo.commit("feat: initial commit");
+
+    let original_dir = std::env::current_dir().expect("Failed to get current dir");
+    std::env::set_current_dir(repo.dir.path()).expect("Failed to change to repo dir");
+    let _guard = DirGuard::new(original_dir);
+
+    std::fs::write(repo.dir.path().join("generated.md"), "docs\n")
+        .expect("Failed to write untracked file");
+
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        PreflightOverrides::default(),
+        &[],
+        false,
+    );
+    assert!(matches!(result, Err(ShipError::DirtyWorkingTree)));
+
+    let overrides = PreflightOverrides {
+        allow_dirty: true,
+        ..Default::default()
+    };
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        overrides,
+        &[],
+        false,
+    );
+    assert!(matches!(
+        result,
+        Err(ShipError::MissingUpstreamTracking { .. })
+    ));
+
+    let overrides = PreflightOverrides {
+        allow_dirty: true,
+        allow_no_upstream: true,
+        ..Default::default()
+    };
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        overrides,
+        &[],
+        false,
+    )
+    .expect("overrides should let preflight pass");
+    assert!(result.dirty_working_tree);
+    assert_eq!(result.remote_sync, RemoteSync::NoUpstream);
+    assert_eq!(result.remote_name, "origin");
+    assert_eq!(result.upstream_branch, result.current_branch);
 }
 
 #[test]
@@ -254,8 +359,15 @@ fn test_preflight_ignores_unreachable_semver_tag_from_orphan_branch() {
     // Return to mainline and verify the orphan tag does not affect preflight range resolution.
     run_git(&["switch", &local_branch]);
 
-    let result = run_checks(&repo.repo, false, ProviderSelection::default(), false)
-        .expect("preflight should succeed on mainline branch");
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        PreflightOverrides::default(),
+        &[],
+        false,
+    )
+    .expect("preflight should succeed on mainline branch");
 
     assert!(result.latest_tag.is_none());
     assert_eq!(result.commits_since_tag.len(), 1);
@@ -274,3 +386,127 @@ fn test_preflight_ignores_unreachable_semver_tag_from_orphan_branch() {
         "orphan branch commit should not be included"
     );
 }
+
+#[test]
+#[serial]
+fn test_release_commit_leaves_out_prestaged_changes() {
+    let repo = TestRepo::new();
+    repo.commit("feat: initial commit");
+
+    let original_dir = std::env::current_dir().expect("Failed to get current dir");
+    std::env::set_current_dir(repo.dir.path()).expect("Failed to change to repo dir");
+    let _guard = DirGuard::new(original_dir);
+    run_git(&["config", "user.name", "Test User"]);
+    run_git(&["config", "user.email", "test@example.com"]);
+
+    // Staged before the release, as --allow-dirty lets through
+    std::fs::write(repo.dir.path().join("notes.md"), "draft\n").expect("Failed to write file");
+    run_git(&["add", "notes.md"]);
+
+    let overrides = PreflightOverrides {
+        allow_dirty: true,
+        allow_no_upstream: true,
+        ..Default::default()
+    };
+    let result = run_checks(
+        &repo.repo,
+        false,
+        ProviderSelection::default(),
+        overrides,
+        &[],
+        false,
+    )
+    .expect("overrides should let preflight pass");
+    assert!(result.dirty_working_tree);
+
+    // Nothing to release: no commit, not even one of the staged file
+    let files = [PathBuf::from("CHANGELOG.md")];
+    let changelog = &files[0];
+    std::fs::write(changelog, "# Changelog\n").expect("Failed to write changelog");
+    run_git(&["add", "CHANGELOG.md"]);
+    run_git(&[
+        "commit",
+        "--only",
+        "-m",
+        "docs: changelog",
+        "--",
+        "CHANGELOG.md",
+    ]);
+    let head = repo.repo.head().unwrap().target().unwrap();
+    let result = executor::commit_release("chore(release): v1.0.0", &files, false)
+        .expect("commit_release should succeed");
+    assert!(!result.commit_created);
+    assert_eq!(repo.repo.head().unwrap().target().unwrap(), head);
+
+    std::fs::write(changelog, "# Changelog\n\n## [1.0.0]\n").expect("Failed to write changelog");
+    let result = executor::commit_release("chore(release): v1.0.0", &files, false)
+        .expect("commit_release should succeed");
+    assert!(result.commit_created);
+
+    let commit = repo.repo.head().unwrap().peel_to_commit().unwrap();
+    let tree = commit.tree().unwrap();
+    assert!(tree.get_name("CHANGELOG.md").is_some());
+    assert!(tree.get_name("notes.md").is_none());
+    // The user's change is still staged
+    let status = repo
+        .repo
+        .status_file(std::path::Path::new("notes.md"))
+        .unwrap();
+    assert!(status.contains(git2::Status::INDEX_NEW));
+}
+
+/// A repository with a committed changelog and git identity, as the
+/// working directory for the executor.
+fn tag_only_repo() -> (TestRepo, DirGuard) {
+    let repo = TestRepo::new();
+    repo.commit("feat: initial commit");
+
+    let original_dir = std::env::current_dir().expect("Failed to get current dir");
+    std::env::set_current_dir(repo.dir.path()).expect("Failed to change to repo dir");
+    let guard = DirGuard::new(original_dir);
+    run_git(&["config", "user.name", "Test User"]);
+    run_git(&["config", "user.email", "test@example.com"]);
+
+    std::fs::write("CHANGELOG.md", "# Changelog\n").expect("Failed to write changelog");
+    run_git(&["add", "CHANGELOG.md"]);
+    run_git(&["commit", "-m", "docs: changelog"]);
+    (repo, guard)
+}
+
+#[test]
+#[serial]
+fn test_tag_only_release_commits_generated_section() {
+    let (repo, _guard) = tag_only_repo();
+    let head = repo.repo.head().unwrap().target().unwrap();
+
+    // No version files, just the new changelog section
+    std::fs::write("CHANGELOG.md", "# Changelog\n\n## [1.1.0]\n").expect("Failed to write");
+    let result = executor::commit_and_tag(
+        "chore(release): v1.1.0",
+        "v1.1.0",
+        &[PathBuf::from("CHANGELOG.md")],
+    )
+    .expect("tag-only release should commit and tag");
+    assert!(result.commit_created);
+
+    let commit = repo.repo.head().unwrap().peel_to_commit().unwrap();
+    assert_eq!(commit.parent_id(0).unwrap(), head);
+    let tag = repo.repo.revparse_single("v1.1.0^{commit}").unwrap();
+    assert_eq!(tag.id(), commit.id());
+}
+
+#[test]
+#[serial]
+fn test_tag_only_release_tags_existing_section() {
+    let (repo, _guard) = tag_only_repo();
+    let head = repo.repo.head().unwrap().target().unwrap();
+
+    // The changelog already has the section: nothing to stage
+    let result = executor::commit_and_tag("chore(release): v1.0.0", "v1.0.0", &[])
+        .expect("tag-only release should tag HEAD");
+    assert!(!result.commit_created);
+
+    assert_eq!(repo.repo.head().unwrap().target().unwrap(), head);
+    let tag = repo.repo.revparse_single("v1.0.0^{commit}").unwrap();
+    assert_eq!(tag.id(), head);
+}
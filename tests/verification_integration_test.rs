@@ -8,7 +8,10 @@ mod common;
 use std::fs;
 
 use keryx::changelog::{ChangelogCategory, ChangelogEntry};
-use keryx::verification::{Confidence, StubType, gather_verification_evidence};
+use keryx::verification::{
+    Confidence, SearchScope, StubType, gather_verification_evidence,
+    gather_verification_evidence_in,
+};
 
 /// Create a test project with known code patterns.
 fn create_test_project() -> tempfile::TempDir {
@@ -156,6 +159,9 @@ fn test_keyword_search_finds_websocket() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Added WebSocket support for real-time updates".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -199,6 +205,9 @@ fn test_keyword_search_finds_database() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Added Postgres connection pooling".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -237,6 +246,9 @@ fn test_keyword_search_no_match_for_missing_feature() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Added GraphQL API with Apollo server".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -274,6 +286,9 @@ fn test_stub_indicators_detected_for_incomplete_code() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Added AuthProvider for authentication".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -316,6 +331,9 @@ fn test_no_stub_indicators_for_complete_code() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "WebSocket client implementation".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -347,6 +365,9 @@ fn test_confidence_high_for_complete_implementation() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "WebSocket support with connect and send methods".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -370,6 +391,9 @@ fn test_confidence_low_for_incomplete_implementation() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "AuthProvider authentication system".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -393,6 +417,9 @@ fn test_confidence_low_for_nonexistent_feature() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Redis caching layer with LRU eviction".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -426,6 +453,9 @@ fn test_key_files_gathered() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Test feature".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -456,14 +486,23 @@ fn test_multiple_entries_analyzed() {
         ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "WebSocket support".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         },
         ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "PostgreSQL database integration".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         },
         ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "AuthProvider authentication".to_string(),
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         },
     ];
 
@@ -499,10 +538,16 @@ fn test_has_low_confidence_entries_detection() {
         ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "WebSocket support".to_string(), // Complete
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         },
         ChangelogEntry {
             category: ChangelogCategory::Added,
             description: "GraphQL Federation".to_string(), // Doesn't exist
+            sources: Vec::new(),
+            scope: None,
+            importance: None,
         },
     ];
 
@@ -553,6 +598,9 @@ fn test_entry_with_no_extractable_keywords() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Fixed,
         description: "Fix a bug".to_string(), // Very short, generic description
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -571,6 +619,9 @@ fn test_project_structure_gathered() {
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Test".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, project.path());
@@ -580,3 +631,41 @@ fn test_project_structure_gathered() {
     // Just verify the evidence was gathered
     assert!(evidence.entries.len() == 1);
 }
+
+// === Search Scope Tests ===
+
+#[test]
+#[cfg_attr(not(feature = "rg-tests"), ignore = "requires ripgrep")]
+fn test_scoped_search_prefers_changed_paths() {
+    let project = create_test_project();
+    let client = project.path().join("client");
+    fs::create_dir(&client).unwrap();
+    fs::write(
+        client.join("socket.rs"),
+        "pub fn open_websocket() -> WebSocket { WebSocket::new(\"ws://\") }\n",
+    )
+    .unwrap();
+    let scope = SearchScope::from_changed_paths(["client/socket.rs"], project.path());
+    assert_eq!(scope.paths(), ["client"]);
+
+    let entries = vec![ChangelogEntry {
+        category: ChangelogCategory::Added,
+        description: "Added WebSocket client backed by Postgres".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
+    }];
+    let evidence = gather_verification_evidence_in(&entries, project.path(), &scope);
+    assert_eq!(evidence.search_scope, vec!["client".to_string()]);
+
+    let matches = &evidence.entries[0].keyword_matches;
+    let websocket = matches.iter().find(|k| k.keyword == "websocket").unwrap();
+    assert_eq!(websocket.files_found, vec!["client/socket.rs".to_string()]);
+
+    // Not in the scope, so found by the repository-wide retry
+    let postgres = matches
+        .iter()
+        .find(|k| k.keyword.contains("postgres"))
+        .expect("keyword outside the scope should still be found");
+    assert!(postgres.files_found.iter().any(|f| f.starts_with("src/")));
+}
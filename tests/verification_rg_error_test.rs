@@ -64,6 +64,9 @@ exit 1
     let entries = vec![ChangelogEntry {
         category: ChangelogCategory::Added,
         description: "Added Widget support".to_string(),
+        sources: Vec::new(),
+        scope: None,
+        importance: None,
     }];
 
     let evidence = gather_verification_evidence(&entries, dir.path());
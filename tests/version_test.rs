@@ -1,8 +1,10 @@
 //! Integration tests for version calculation.
 
 use chrono::Utc;
-use keryx::git::{CommitType, ParsedCommit};
-use keryx::version::calculate_next_version;
+use keryx::BumpType;
+use keryx::changelog::{CategoryAliases, parse_changelog_content};
+use keryx::git::{CommitType, Footers, ParsedCommit};
+use keryx::version::{calculate_next_version, determine_unreleased_bump};
 use semver::Version;
 
 fn make_commit(commit_type: Option<CommitType>, breaking: bool, message: &str) -> ParsedCommit {
@@ -13,6 +15,7 @@ fn make_commit(commit_type: Option<CommitType>, breaking: bool, message: &str) -
         scope: None,
         breaking,
         timestamp: Utc::now(),
+        footers: Footers::default(),
     }
 }
 
@@ -168,6 +171,7 @@ fn test_commit_with_scope_still_bumps_correctly() {
         scope: Some("auth".to_string()),
         breaking: false,
         timestamp: Utc::now(),
+        footers: Footers::default(),
     }];
 
     let next = calculate_next_version(Some(&base), &commits);
@@ -185,9 +189,23 @@ fn test_breaking_change_in_footer() {
         scope: None,
         breaking: true, // Parser sets this from footer
         timestamp: Utc::now(),
+        footers: Footers::default(),
     }];
 
     let next = calculate_next_version(Some(&base), &commits);
 
     assert_eq!(next, Version::new(2, 0, 0));
 }
+
+#[test]
+fn test_next_version_from_unreleased_entries() {
+    let content = "# Changelog\n\n## [Unreleased]\n\n### Added\n\n- Export to CSV\n\n### Fixed\n\n- Crash on empty input\n\n## [1.4.2] - 2024-05-01\n\n### Fixed\n\n- Typo in help\n";
+    let changelog = parse_changelog_content(content.to_string()).unwrap();
+    let bump = determine_unreleased_bump(content, &CategoryAliases::default()).unwrap();
+
+    assert_eq!(bump.bump, BumpType::Minor);
+    assert_eq!(
+        bump.next_version(changelog.latest_version.as_ref()),
+        Version::new(1, 5, 0)
+    );
+}